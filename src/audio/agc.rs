@@ -0,0 +1,52 @@
+//! Automatic gain control (`sensitivity = "auto"`).
+//!
+//! Follows the loudest band with separate attack and release times and
+//! scales the bars so that peak sits just below full height: quiet tracks
+//! still fill the display and loud ones don't pin every bar at the top.
+//! Unlike auto-exposure it reacts within seconds rather than over a session.
+
+use crate::config::AudioConfig;
+
+/// Bar height the tracked peak is scaled to
+const TARGET: f32 = 0.9;
+
+/// Gain limits so near-silence can't be blown up into full-height noise
+const MIN_GAIN: f32 = 0.05;
+const MAX_GAIN: f32 = 50.0;
+
+/// Frames whose loudest band is below this (-60 dB) leave the gain alone,
+/// so pauses between tracks don't wind it up to the maximum
+const SILENCE: f32 = 0.001;
+
+pub struct AutoGain {
+    /// Smoothed peak magnitude
+    peak: f32,
+    attack_secs: f32,
+    release_secs: f32,
+}
+
+impl AutoGain {
+    pub fn new(audio_config: &AudioConfig) -> Self {
+        Self {
+            peak: TARGET,
+            attack_secs: audio_config.agc_attack_secs.max(0.0),
+            release_secs: audio_config.agc_release_secs.max(0.0),
+        }
+    }
+
+    /// Update the tracked peak from `magnitudes` and scale them in place to
+    /// 0.0-1.0.
+    pub fn apply(&mut self, magnitudes: &mut [f32], dt: f32) {
+        let peak = magnitudes.iter().copied().fold(0.0, f32::max);
+        if peak >= SILENCE {
+            let secs = if peak > self.peak { self.attack_secs } else { self.release_secs };
+            let alpha = if secs > 0.0 { 1.0 - (-dt / secs).exp() } else { 1.0 };
+            self.peak += (peak - self.peak) * alpha;
+        }
+
+        let gain = (TARGET / self.peak.max(f32::EPSILON)).clamp(MIN_GAIN, MAX_GAIN);
+        for m in magnitudes.iter_mut() {
+            *m = (*m * gain).min(1.0);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Per-application capture: record a single PulseAudio/PipeWire sink input
+//! (e.g. only Spotify) instead of the whole sink monitor.
+//!
+//! `libpulse-simple` can only record whole sources, so this uses the
+//! asynchronous API with `pa_stream_set_monitor_stream` on the sink's monitor.
+
+use anyhow::{anyhow, bail, Result};
+use libpulse_binding as pulse;
+use pulse::callbacks::ListResult;
+use pulse::context::{Context, State as ContextState};
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::proplist::properties;
+use pulse::sample::Spec;
+use pulse::stream::{PeekResult, State as StreamState, Stream};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+use super::delay::SlidingWindow;
+use super::fft::FrequencyAnalyzer;
+use super::AudioData;
+use crate::config::AudioConfig;
+
+/// Prefix selecting an application instead of a source, e.g. `app:spotify`
+pub const APP_PREFIX: &str = "app:";
+
+/// How long to wait before looking for the application's stream again
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Mono samples analyzed per FFT frame, matching the monitor capture
+const FRAME_SAMPLES: usize = 2048;
+
+/// A playing application stream (sink input)
+#[derive(Debug, Clone)]
+pub struct AppStream {
+    pub index: u32,
+    /// Sink the stream plays to
+    pub sink: u32,
+    /// Application name, e.g. "Spotify"
+    pub name: String,
+    /// Process binary, e.g. "spotify"
+    pub binary: Option<String>,
+    /// Paused by the application
+    pub corked: bool,
+}
+
+impl AppStream {
+    /// Whether `query` names this application (name or binary, case-insensitive)
+    fn matches(&self, query: &str) -> bool {
+        self.name.eq_ignore_ascii_case(query) || self.binary.as_deref().is_some_and(|b| b.eq_ignore_ascii_case(query))
+    }
+}
+
+/// Connect a context on a fresh mainloop and wait until it is ready.
+fn connect(name: &str) -> Result<(Mainloop, Context)> {
+    let mut mainloop = Mainloop::new().ok_or_else(|| anyhow!("Failed to create PulseAudio mainloop"))?;
+    let mut context = Context::new(&mainloop, name).ok_or_else(|| anyhow!("Failed to create PulseAudio context"))?;
+    context
+        .connect(None, pulse::context::FlagSet::NOFLAGS, None)
+        .map_err(|_| anyhow!("Failed to connect to PulseAudio"))?;
+    loop {
+        iterate(&mut mainloop, true)?;
+        match context.get_state() {
+            ContextState::Ready => return Ok((mainloop, context)),
+            ContextState::Failed | ContextState::Terminated => bail!("PulseAudio connection failed"),
+            _ => {}
+        }
+    }
+}
+
+fn iterate(mainloop: &mut Mainloop, block: bool) -> Result<()> {
+    match mainloop.iterate(block) {
+        IterateResult::Success(_) => Ok(()),
+        _ => Err(anyhow!("PulseAudio mainloop error")),
+    }
+}
+
+/// Run the mainloop until `done` is set by a callback.
+fn wait(mainloop: &mut Mainloop, done: &Cell<bool>) -> Result<()> {
+    while !done.get() {
+        iterate(mainloop, true)?;
+    }
+    Ok(())
+}
+
+fn query_streams(mainloop: &mut Mainloop, context: &Context) -> Result<Vec<AppStream>> {
+    let streams = Rc::new(RefCell::new(Vec::new()));
+    let done = Rc::new(Cell::new(false));
+    let (streams_clone, done_clone) = (streams.clone(), done.clone());
+    let _op = context.introspect().get_sink_input_info_list(move |result| match result {
+        ListResult::Item(info) => {
+            let name = info
+                .proplist
+                .get_str(properties::APPLICATION_NAME)
+                .or_else(|| info.name.as_ref().map(|n| n.to_string()))
+                .unwrap_or_default();
+            streams_clone.borrow_mut().push(AppStream {
+                index: info.index,
+                sink: info.sink,
+                name,
+                binary: info.proplist.get_str(properties::APPLICATION_PROCESS_BINARY),
+                corked: info.corked,
+            });
+        }
+        ListResult::End | ListResult::Error => done_clone.set(true),
+    });
+    wait(mainloop, &done)?;
+    let streams = streams.borrow().clone();
+    Ok(streams)
+}
+
+fn monitor_source_of(mainloop: &mut Mainloop, context: &Context, sink: u32) -> Result<String> {
+    let monitor = Rc::new(RefCell::new(None::<String>));
+    let done = Rc::new(Cell::new(false));
+    let (monitor_clone, done_clone) = (monitor.clone(), done.clone());
+    let _op = context.introspect().get_sink_info_by_index(sink, move |result| match result {
+        ListResult::Item(info) => {
+            *monitor_clone.borrow_mut() = info.monitor_source_name.as_ref().map(|n| n.to_string());
+        }
+        ListResult::End | ListResult::Error => done_clone.set(true),
+    });
+    wait(mainloop, &done)?;
+    let monitor = monitor.borrow().clone();
+    monitor.ok_or_else(|| anyhow!("sink {} has no monitor source", sink))
+}
+
+/// List the application streams currently playing.
+pub fn list_apps() -> Result<Vec<AppStream>> {
+    let (mut mainloop, context) = connect("cavibe-list")?;
+    query_streams(&mut mainloop, &context)
+}
+
+/// Capture thread body: follow the stream of `app`, reconnecting whenever the
+/// application closes and reopens it, until `stop_flag` is set or the receiver is dropped.
+pub(super) fn run(
+    app: &str,
+    num_bars: usize,
+    spec: Spec,
+    audio_config: &AudioConfig,
+    sender: watch::Sender<Arc<AudioData>>,
+    stop_flag: Arc<AtomicBool>,
+) {
+    let mut analyzer = FrequencyAnalyzer::new(num_bars, spec.rate as f32, audio_config);
+    let mut window = SlidingWindow::new(FRAME_SAMPLES, audio_config.delay_ms, spec.rate as f32);
+    while !stop_flag.load(Ordering::Relaxed) {
+        match capture_app(app, spec, &mut analyzer, &mut window, &sender, &stop_flag) {
+            Ok(()) => break,
+            Err(e) => {
+                debug!("Application capture for '{}': {}", app, e);
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    }
+    debug!("Application capture for '{}' stopped", app);
+}
+
+/// Record one stream of `app`. Returns Ok when capture should stop for good,
+/// Err when the stream is missing or ended and should be looked up again.
+fn capture_app(
+    app: &str,
+    spec: Spec,
+    analyzer: &mut FrequencyAnalyzer,
+    window: &mut SlidingWindow,
+    sender: &watch::Sender<Arc<AudioData>>,
+    stop_flag: &AtomicBool,
+) -> Result<()> {
+    let (mut mainloop, mut context) = connect("cavibe")?;
+    let streams = query_streams(&mut mainloop, &context)?;
+    // Prefer a stream that is actually playing when the app has several
+    let target = streams
+        .iter()
+        .filter(|s| s.matches(app))
+        .min_by_key(|s| s.corked)
+        .ok_or_else(|| anyhow!("no stream from application '{}'", app))?
+        .clone();
+    let monitor = monitor_source_of(&mut mainloop, &context, target.sink)?;
+
+    let mut stream = Stream::new(&mut context, "audio-visualizer", &spec, None)
+        .ok_or_else(|| anyhow!("Failed to create PulseAudio stream"))?;
+    stream
+        .set_monitor_stream(target.index)
+        .map_err(|e| anyhow!("Failed to monitor stream {}: {}", target.index, e))?;
+    stream
+        .connect_record(Some(&monitor), None, pulse::stream::FlagSet::DONT_MOVE)
+        .map_err(|e| anyhow!("Failed to record from {}: {}", monitor, e))?;
+    info!("Capturing application '{}' (stream {} on {})", target.name, target.index, monitor);
+
+    // Stereo samples, analyzed a hop at a time
+    let hop = window.hop();
+    let mut samples: Vec<f32> = Vec::with_capacity(FRAME_SAMPLES * 4);
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        iterate(&mut mainloop, false)?;
+        match stream.get_state() {
+            StreamState::Failed | StreamState::Terminated => bail!("stream {} ended", target.index),
+            StreamState::Ready => {}
+            _ => {
+                std::thread::sleep(Duration::from_millis(5));
+                continue;
+            }
+        }
+
+        match stream.peek().map_err(|e| anyhow!("PulseAudio read error: {}", e))? {
+            PeekResult::Empty => {
+                std::thread::sleep(Duration::from_millis(5));
+                continue;
+            }
+            PeekResult::Hole(_) => {}
+            PeekResult::Data(bytes) => samples.extend(
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            ),
+        }
+        stream.discard().map_err(|e| anyhow!("PulseAudio read error: {}", e))?;
+
+        while samples.len() >= hop * 2 {
+            // Convert stereo to mono
+            let audio_data = analyzer.process_stereo(window, &samples[..hop * 2]);
+            samples.drain(..hop * 2);
+            if sender.send(Arc::new(audio_data)).is_err() {
+                debug!("Audio receiver dropped, stopping capture");
+                return Ok(());
+            }
+        }
+    }
+}
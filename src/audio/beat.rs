@@ -0,0 +1,23 @@
+use std::time::{Duration, Instant};
+
+/// Spots beats as intensity spikes well above its recent average.
+#[derive(Debug, Default)]
+pub struct BeatDetector {
+    last_beat: Option<Instant>,
+    /// Slow-moving average of intensity used as the beat baseline
+    energy_avg: f32,
+}
+
+impl BeatDetector {
+    /// Feed one frame's intensity. Returns true on a beat: intensity above the
+    /// average × `sensitivity`, at least `min_interval` after the previous beat.
+    pub fn update(&mut self, intensity: f32, sensitivity: f32, min_interval: Duration) -> bool {
+        let spike = intensity > 0.05 && intensity > self.energy_avg * sensitivity;
+        self.energy_avg = self.energy_avg * 0.95 + intensity * 0.05;
+        if spike && self.last_beat.is_none_or(|t| t.elapsed() >= min_interval) {
+            self.last_beat = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+}
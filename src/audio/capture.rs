@@ -1,21 +1,85 @@
 use anyhow::{anyhow, Result};
 use libpulse_binding as pulse;
 use libpulse_simple_binding as psimple;
+use pulse::context::subscribe::{Facility, InterestMaskSet};
+use pulse::context::{Context, State as ContextState};
+use pulse::mainloop::standard::{IterateResult, Mainloop};
 use pulse::sample::{Format, Spec};
 use pulse::stream::Direction;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use tokio::sync::watch;
 use tracing::{debug, info, warn};
 
+use super::app::APP_PREFIX;
+use super::delay::SlidingWindow;
 use super::fft::FrequencyAnalyzer;
 use super::AudioData;
+use crate::config::AudioConfig;
+
+/// How long to wait before reconnecting a lost default-output watcher
+const WATCH_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long to wait between attempts to reopen a failed stream
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Samples per silent frame sent while reconnecting, so the bars fall instead of freezing
+const SILENT_FRAME: usize = 2048;
+
+/// State of the capture stream, for `cavibe ctl status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioHealth {
+    /// Recording
+    Ok,
+    /// The stream failed and is being reopened
+    Reconnecting,
+}
+
+impl AudioHealth {
+    pub fn name(self) -> &'static str {
+        match self {
+            AudioHealth::Ok => "ok",
+            AudioHealth::Reconnecting => "reconnecting",
+        }
+    }
+}
+
+/// Stream state shared between an `AudioCapture` and its thread
+#[derive(Debug, Default)]
+struct CaptureStatus {
+    /// Source being recorded ("default" when PulseAudio picks it)
+    source: Mutex<String>,
+    reconnecting: AtomicBool,
+    /// Times the stream was reopened or moved to a new default output
+    reconnects: AtomicU32,
+}
+
+impl CaptureStatus {
+    fn new(source: String) -> Arc<Self> {
+        Arc::new(Self { source: Mutex::new(source), ..Default::default() })
+    }
+
+    fn source(&self) -> String {
+        self.source.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Record a newly opened stream on `source`
+    fn reconnected(&self, source: String) {
+        *self.source.lock().unwrap_or_else(|e| e.into_inner()) = source;
+        self.reconnecting.store(false, Ordering::Relaxed);
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+}
 
 pub struct AudioCapture {
     // Keep the thread handle to ensure it stays alive
     _capture_thread: thread::JoinHandle<()>,
     stop_flag: Arc<AtomicBool>,
+    status: Arc<CaptureStatus>,
 }
 
 impl Drop for AudioCapture {
@@ -29,10 +93,6 @@ impl Drop for AudioCapture {
 /// Returns a list of `(name, state)` tuples.
 pub fn list_sources() -> Result<Vec<(String, String)>> {
     use pulse::callbacks::ListResult;
-    use pulse::context::{Context, State as ContextState};
-    use pulse::mainloop::standard::{IterateResult, Mainloop};
-    use std::cell::{Cell, RefCell};
-    use std::rc::Rc;
 
     let mainloop = Rc::new(RefCell::new(
         Mainloop::new().ok_or_else(|| anyhow!("Failed to create PulseAudio mainloop"))?,
@@ -100,21 +160,23 @@ pub fn list_sources() -> Result<Vec<(String, String)>> {
 impl AudioCapture {
     pub fn new(
         num_bars: usize,
-        smoothing: f32,
-        sensitivity: f32,
+        audio_config: &AudioConfig,
         sender: watch::Sender<Arc<AudioData>>,
         device: Option<String>,
     ) -> Result<Self> {
-        // Use explicit device if provided, otherwise auto-detect
-        let source = if let Some(sink_name) = device {
-            let monitor = format!("{}.monitor", sink_name);
-            info!("Using explicit sink monitor: {}", monitor);
-            Some(monitor)
-        } else {
-            Self::find_monitor_source()
-        };
-
-        Self::start_capture(num_bars, smoothing, sensitivity, sender, source)
+        if let Some(app) = device.as_deref().and_then(|d| d.strip_prefix(APP_PREFIX)) {
+            return Self::new_app(num_bars, audio_config, sender, app);
+        }
+        // Use explicit device if provided, otherwise auto-detect and follow
+        // the default output when it changes
+        match device {
+            Some(sink_name) => {
+                let monitor = format!("{}.monitor", sink_name);
+                info!("Using explicit sink monitor: {}", monitor);
+                Self::start_capture(num_bars, audio_config, sender, Some(monitor), false)
+            }
+            None => Self::start_capture(num_bars, audio_config, sender, Self::find_monitor_source(), true),
+        }
     }
 
     /// Create an AudioCapture using a raw PulseAudio source name (no `.monitor` appended).
@@ -122,29 +184,113 @@ impl AudioCapture {
     /// Use this when the caller already has a full source name (e.g. from `list_sources()`).
     pub fn new_with_source(
         num_bars: usize,
-        smoothing: f32,
-        sensitivity: f32,
+        audio_config: &AudioConfig,
         sender: watch::Sender<Arc<AudioData>>,
         source: String,
     ) -> Result<Self> {
+        if let Some(app) = source.strip_prefix(APP_PREFIX) {
+            return Self::new_app(num_bars, audio_config, sender, app);
+        }
         info!("Using explicit source: {}", source);
-        Self::start_capture(num_bars, smoothing, sensitivity, sender, Some(source))
+        Self::start_capture(num_bars, audio_config, sender, Some(source), false)
+    }
+
+    /// Create an AudioCapture recording only the streams of application `app`.
+    ///
+    /// The application doesn't need to be playing yet: capture starts once it
+    /// opens a stream and follows it across reconnects.
+    pub fn new_app(
+        num_bars: usize,
+        audio_config: &AudioConfig,
+        sender: watch::Sender<Arc<AudioData>>,
+        app: &str,
+    ) -> Result<Self> {
+        if app.is_empty() {
+            return Err(anyhow!("No application name given"));
+        }
+        info!("Using application source: {}", app);
+
+        let spec = Spec {
+            format: Format::F32le,
+            channels: 2,
+            rate: 44100,
+        };
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let stop_flag_clone = stop_flag.clone();
+        let audio_config = audio_config.clone();
+        let app_name = app.to_string();
+
+        let capture_thread = thread::spawn(move || {
+            super::app::run(&app_name, num_bars, spec, &audio_config, sender, stop_flag_clone);
+        });
+
+        Ok(Self {
+            _capture_thread: capture_thread,
+            stop_flag,
+            status: CaptureStatus::new(format!("{}{}", APP_PREFIX, app)),
+        })
+    }
+
+    /// Create an AudioCapture that generates synthetic music instead of recording.
+    pub fn new_demo(
+        num_bars: usize,
+        audio_config: &AudioConfig,
+        sender: watch::Sender<Arc<AudioData>>,
+    ) -> Result<Self> {
+        info!("Demo mode: generating synthetic audio");
+
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let stop_flag_clone = stop_flag.clone();
+        let audio_config = audio_config.clone();
+
+        let capture_thread = thread::spawn(move || {
+            super::demo::run(num_bars, 44100.0, &audio_config, sender, stop_flag_clone);
+        });
+
+        Ok(Self {
+            _capture_thread: capture_thread,
+            stop_flag,
+            status: CaptureStatus::new("demo".to_string()),
+        })
+    }
+
+    /// Create an AudioCapture that replays a feature recording (`--replay-features`).
+    pub fn new_replay(
+        num_bars: usize,
+        recording: Arc<super::recording::Recording>,
+        sender: watch::Sender<Arc<AudioData>>,
+    ) -> Result<Self> {
+        info!("Replaying recorded audio features");
+
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let stop_flag_clone = stop_flag.clone();
+
+        let capture_thread = thread::spawn(move || {
+            super::recording::run_replay(num_bars, &recording, sender, stop_flag_clone);
+        });
+
+        Ok(Self {
+            _capture_thread: capture_thread,
+            stop_flag,
+            status: CaptureStatus::new("replay".to_string()),
+        })
     }
 
     /// Common setup: connect to PulseAudio and spawn the capture thread.
+    /// With `follow_default`, capture moves to the new default output's
+    /// monitor whenever the default sink changes.
     fn start_capture(
         num_bars: usize,
-        smoothing: f32,
-        sensitivity: f32,
+        audio_config: &AudioConfig,
         sender: watch::Sender<Arc<AudioData>>,
         device: Option<String>,
+        follow_default: bool,
     ) -> Result<Self> {
         // PulseAudio sample specification
-        let sample_rate = 44100;
         let spec = Spec {
             format: Format::F32le,
             channels: 2,
-            rate: sample_rate,
+            rate: 44100,
         };
 
         if !spec.is_valid() {
@@ -152,57 +298,116 @@ impl AudioCapture {
         }
 
         info!("Using audio device: {}", device.as_deref().unwrap_or("default"));
-
-        // Create PulseAudio simple connection for recording
-        let pulse = psimple::Simple::new(
-            None,                // Use default server
-            "cavibe",            // Application name
-            Direction::Record,   // Recording stream
-            device.as_deref(),   // Device name (None = default)
-            "audio-visualizer",  // Stream description
-            &spec,               // Sample format
-            None,                // Default channel map
-            None,                // Default buffering attributes
-        )
-        .map_err(|e| anyhow!("Failed to connect to PulseAudio: {:?}", e))?;
-
-        info!("Connected to PulseAudio, sensitivity: {}", sensitivity);
+        let pulse = Self::open_stream(device.as_deref(), &spec)?;
+        info!("Connected to PulseAudio, sensitivity: {}", audio_config.sensitivity);
 
         let stop_flag = Arc::new(AtomicBool::new(false));
         let stop_flag_clone = stop_flag.clone();
+        let status = CaptureStatus::new(device.clone().unwrap_or_else(|| "default".to_string()));
+        let status_clone = status.clone();
+        let audio_config = audio_config.clone();
 
         // Spawn capture thread
         let capture_thread = thread::spawn(move || {
-            Self::capture_loop(pulse, num_bars, sample_rate as f32, smoothing, sensitivity, sender, stop_flag_clone);
+            let stream = CaptureStream { pulse, device, spec, follow_default };
+            Self::capture_loop(stream, num_bars, &audio_config, sender, stop_flag_clone, &status_clone);
         });
 
         Ok(Self {
             _capture_thread: capture_thread,
             stop_flag,
+            status,
         })
     }
 
+    /// Open a PulseAudio recording stream on `device` (None = default source).
+    fn open_stream(device: Option<&str>, spec: &Spec) -> Result<psimple::Simple> {
+        psimple::Simple::new(
+            None,                // Use default server
+            "cavibe",            // Application name
+            Direction::Record,   // Recording stream
+            device,              // Device name (None = default)
+            "audio-visualizer",  // Stream description
+            spec,                // Sample format
+            None,                // Default channel map
+            None,                // Default buffering attributes
+        )
+        .map_err(|e| anyhow!("Failed to connect to PulseAudio: {:?}", e))
+    }
+
+    /// Name of the source being recorded.
+    pub fn source_name(&self) -> String {
+        self.status.source()
+    }
+
+    /// Whether the stream is recording or being reopened.
+    pub fn health(&self) -> AudioHealth {
+        if self.status.reconnecting.load(Ordering::Relaxed) {
+            AudioHealth::Reconnecting
+        } else {
+            AudioHealth::Ok
+        }
+    }
+
+    /// Times the stream was reopened after a failure or moved to a new default output.
+    pub fn reconnects(&self) -> u32 {
+        self.status.reconnects.load(Ordering::Relaxed)
+    }
+
     fn capture_loop(
-        pulse: psimple::Simple,
+        mut stream: CaptureStream,
         num_bars: usize,
-        sample_rate: f32,
-        smoothing: f32,
-        sensitivity: f32,
+        audio_config: &AudioConfig,
         sender: watch::Sender<Arc<AudioData>>,
         stop_flag: Arc<AtomicBool>,
+        status: &CaptureStatus,
     ) {
-        let mut analyzer = FrequencyAnalyzer::new(num_bars, sample_rate, smoothing, sensitivity);
+        let sample_rate = stream.spec.rate as f32;
+        let mut analyzer = FrequencyAnalyzer::new(num_bars, sample_rate, audio_config);
 
-        // Buffer for audio samples (stereo f32)
-        // Read enough samples for FFT processing (~46ms at 44100Hz)
-        let buffer_size = 2048 * 2; // stereo
-        let mut buffer = vec![0.0f32; buffer_size];
+        // Analyze ~46ms at 44100Hz, reading a hop of new samples at a time
+        // (the whole block unless audio.delay_ms is negative)
+        let mut window = SlidingWindow::new(2048, audio_config.delay_ms, sample_rate);
+        let hop = window.hop();
+        let mut buffer = vec![0.0f32; hop * 2]; // stereo
+        let mut watcher = if stream.follow_default { DefaultSinkWatcher::connect() } else { None };
+        let mut last_watch_attempt = Instant::now();
 
         loop {
             if stop_flag.load(Ordering::Relaxed) {
                 debug!("Stop flag set, ending capture loop");
                 break;
             }
+
+            // The stream stays on the old monitor when the default output
+            // changes (e.g. headphones plugged in), so move it explicitly
+            if stream.follow_default {
+                if watcher.is_none() && last_watch_attempt.elapsed() >= WATCH_RETRY_INTERVAL {
+                    last_watch_attempt = Instant::now();
+                    watcher = DefaultSinkWatcher::connect();
+                }
+                // The watcher only reports changes, so a failed switch isn't retried every read
+                match watcher.as_mut().map(DefaultSinkWatcher::update) {
+                    Some(Err(e)) => {
+                        debug!("Stopped watching the default output: {}", e);
+                        watcher = None;
+                        last_watch_attempt = Instant::now();
+                    }
+                    Some(Ok(Some(monitor))) if stream.device.as_deref() != Some(monitor.as_str()) => {
+                        info!("Default output changed, capturing {}", monitor);
+                        match Self::open_stream(Some(&monitor), &stream.spec) {
+                            Ok(pulse) => {
+                                stream.pulse = pulse;
+                                stream.device = Some(monitor.clone());
+                                status.reconnected(monitor);
+                            }
+                            Err(e) => warn!("Failed to switch to {}: {}", monitor, e),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
             // Read audio data from PulseAudio
             let byte_slice = unsafe {
                 std::slice::from_raw_parts_mut(
@@ -211,19 +416,16 @@ impl AudioCapture {
                 )
             };
 
-            if let Err(e) = pulse.read(byte_slice) {
-                warn!("PulseAudio read error: {:?}", e);
+            if let Err(e) = stream.pulse.read(byte_slice) {
+                warn!("PulseAudio read error: {:?}, reconnecting", e);
+                if !Self::reconnect(&mut stream, &mut analyzer, &sender, &stop_flag, status) {
+                    break;
+                }
                 continue;
             }
 
-            // Convert stereo to mono
-            let mono: Vec<f32> = buffer
-                .chunks(2)
-                .map(|chunk| (chunk[0] + chunk[1]) / 2.0)
-                .collect();
-
-            // Process through FFT
-            let audio_data = analyzer.process(&mono);
+            // Convert stereo to mono and process through FFT
+            let audio_data = analyzer.process_stereo(&mut window, &buffer);
 
             // Send to visualizer (ignore errors if receiver is dropped)
             if sender.send(Arc::new(audio_data)).is_err() {
@@ -233,17 +435,60 @@ impl AudioCapture {
         }
     }
 
+    /// Reopen a failed stream, retrying until it works (on the current
+    /// default output when following it). Sends silence meanwhile so the bars
+    /// fall. Returns false when capture should stop instead.
+    fn reconnect(
+        stream: &mut CaptureStream,
+        analyzer: &mut FrequencyAnalyzer,
+        sender: &watch::Sender<Arc<AudioData>>,
+        stop_flag: &AtomicBool,
+        status: &CaptureStatus,
+    ) -> bool {
+        status.reconnecting.store(true, Ordering::Relaxed);
+        let silence = vec![0.0f32; SILENT_FRAME];
+        let frame_time = Duration::from_secs_f32(SILENT_FRAME as f32 / stream.spec.rate as f32);
+        loop {
+            let retry_at = Instant::now() + RETRY_INTERVAL;
+            while Instant::now() < retry_at {
+                if stop_flag.load(Ordering::Relaxed) || sender.send(Arc::new(analyzer.process(&silence))).is_err() {
+                    return false;
+                }
+                thread::sleep(frame_time);
+            }
+
+            if stream.follow_default {
+                stream.device = Self::default_sink_monitor().or(stream.device.take());
+            }
+            match Self::open_stream(stream.device.as_deref(), &stream.spec) {
+                Ok(pulse) => {
+                    let source = stream.device.clone().unwrap_or_else(|| "default".to_string());
+                    info!("Audio capture reconnected to {}", source);
+                    stream.pulse = pulse;
+                    status.reconnected(source);
+                    return true;
+                }
+                Err(e) => debug!("Audio reconnect failed: {}", e),
+            }
+        }
+    }
+
     /// Find a monitor source for capturing system audio output.
     ///
     /// Queries PulseAudio/PipeWire for the default sink and uses its monitor
     /// source, so we always capture from whatever output the user is listening to.
     fn find_monitor_source() -> Option<String> {
-        // Use native libpulse API to get the default sink name
-        use pulse::context::{Context, State as ContextState};
-        use pulse::mainloop::standard::{IterateResult, Mainloop};
-        use std::cell::{Cell, RefCell};
-        use std::rc::Rc;
+        let result = Self::default_sink_monitor();
+        match result {
+            Some(ref monitor) => info!("Using default sink monitor: {}", monitor),
+            None => warn!("Could not determine default sink, using PulseAudio default source"),
+        }
+        result
+    }
 
+    /// Monitor source of the current default sink.
+    fn default_sink_monitor() -> Option<String> {
+        // Use native libpulse API to get the default sink name
         let mainloop = Rc::new(RefCell::new(Mainloop::new()?));
         let context = Rc::new(RefCell::new(
             Context::new(&*mainloop.borrow(), "cavibe-detect")?
@@ -290,14 +535,109 @@ impl AudioCapture {
             }
         }
 
-        let result = sink_name.borrow().as_ref().map(|sink| {
-            let monitor = format!("{}.monitor", sink);
-            info!("Using default sink monitor: {}", monitor);
-            monitor
-        });
-        if result.is_none() {
-            warn!("Could not determine default sink, using PulseAudio default source");
-        }
+        let result = sink_name.borrow().as_ref().map(|sink| format!("{}.monitor", sink));
         result
     }
 }
+
+/// One PulseAudio connection subscribed to server changes, reporting the
+/// monitor of the default sink whenever it changes
+struct DefaultSinkWatcher {
+    mainloop: Rc<RefCell<Mainloop>>,
+    context: Rc<RefCell<Context>>,
+    /// Set by the subscription when the server info (and so maybe the default sink) changed
+    changed: Rc<Cell<bool>>,
+    /// Monitor from the latest server info reply, until `update` looks at it
+    reply: Rc<RefCell<Option<String>>>,
+    /// Monitor last returned by `update`
+    current: Option<String>,
+}
+
+impl DefaultSinkWatcher {
+    fn connect() -> Option<Self> {
+        let mainloop = Rc::new(RefCell::new(Mainloop::new()?));
+        let context = Rc::new(RefCell::new(
+            Context::new(&*mainloop.borrow(), "cavibe-watch")?
+        ));
+
+        context
+            .borrow_mut()
+            .connect(None, pulse::context::FlagSet::NOFLAGS, None)
+            .ok()?;
+
+        // Wait for context to be ready
+        loop {
+            match mainloop.borrow_mut().iterate(true) {
+                IterateResult::Success(_) => {}
+                _ => return None,
+            }
+            match context.borrow().get_state() {
+                ContextState::Ready => break,
+                ContextState::Failed | ContextState::Terminated => return None,
+                _ => {}
+            }
+        }
+
+        // Start out "changed" so the first update fetches the current default
+        let changed = Rc::new(Cell::new(true));
+        let changed_clone = changed.clone();
+        context.borrow_mut().set_subscribe_callback(Some(Box::new(move |facility, _, _| {
+            if facility == Some(Facility::Server) {
+                changed_clone.set(true);
+            }
+        })));
+        let _op = context.borrow_mut().subscribe(InterestMaskSet::SERVER, |_| {});
+
+        Some(Self {
+            mainloop,
+            context,
+            changed,
+            reply: Rc::new(RefCell::new(None)),
+            current: None,
+        })
+    }
+
+    /// Dispatch pending events without blocking. Returns the default sink's
+    /// monitor when it is first known or has changed since the last call, and
+    /// an error once the connection is gone.
+    fn update(&mut self) -> Result<Option<String>> {
+        if self.changed.replace(false) {
+            let reply = self.reply.clone();
+            let _op = self.context.borrow().introspect().get_server_info(move |info| {
+                if let Some(ref name) = info.default_sink_name {
+                    *reply.borrow_mut() = Some(format!("{}.monitor", name));
+                }
+            });
+        }
+
+        loop {
+            match self.mainloop.borrow_mut().iterate(false) {
+                IterateResult::Success(0) => break,
+                IterateResult::Success(_) => {}
+                _ => return Err(anyhow!("PulseAudio mainloop error")),
+            }
+        }
+        if matches!(self.context.borrow().get_state(), ContextState::Failed | ContextState::Terminated) {
+            return Err(anyhow!("PulseAudio connection lost"));
+        }
+
+        let monitor = self.reply.borrow_mut().take();
+        Ok(match monitor {
+            Some(monitor) if self.current.as_ref() != Some(&monitor) => {
+                self.current = Some(monitor.clone());
+                Some(monitor)
+            }
+            _ => None,
+        })
+    }
+}
+
+/// An open recording stream and what it was opened on, owned by the capture thread
+struct CaptureStream {
+    pulse: psimple::Simple,
+    /// Source recorded (None = PulseAudio default)
+    device: Option<String>,
+    spec: Spec,
+    /// Move to the new default output's monitor when the default sink changes
+    follow_default: bool,
+}
@@ -0,0 +1,93 @@
+//! `audio.delay_ms`: shifting the visualization against the captured audio.
+//!
+//! A positive delay holds analyzed frames back before they reach the display,
+//! for outputs that play the sound later than it is captured (Bluetooth
+//! headphones add 100-300 ms). A negative delay can't see into the future;
+//! instead the capture analyzes overlapping windows more often than once per
+//! block, so the bars react up to most of a block (~40 ms) sooner.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+use super::AudioData;
+
+/// Longest supported delay
+const MAX_DELAY_MS: i32 = 5000;
+/// How often the delay thread checks for new and due frames (its timing error)
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+/// Shortest analysis hop, as a fraction of the block
+const MIN_HOP_FRACTION: usize = 8;
+
+/// Channel for a capture pipeline. With a positive `delay_ms`, frames sent
+/// on the sender reach the receiver that much later.
+pub(super) fn channel(delay_ms: i32) -> (watch::Sender<Arc<AudioData>>, watch::Receiver<Arc<AudioData>>) {
+    let (tx, rx) = watch::channel(Arc::new(AudioData::default()));
+    if delay_ms <= 0 {
+        return (tx, rx);
+    }
+    let (delayed_tx, delayed_rx) = watch::channel(Arc::new(AudioData::default()));
+    let delay = Duration::from_millis(delay_ms.min(MAX_DELAY_MS) as u64);
+    thread::spawn(move || forward_delayed(rx, delayed_tx, delay));
+    (tx, delayed_rx)
+}
+
+/// Pass frames from `input` to `output` `delay` after they arrive, until the
+/// capture stops or the display drops its receiver.
+fn forward_delayed(mut input: watch::Receiver<Arc<AudioData>>, output: watch::Sender<Arc<AudioData>>, delay: Duration) {
+    let mut queue: VecDeque<(Instant, Arc<AudioData>)> = VecDeque::new();
+    loop {
+        match input.has_changed() {
+            Ok(true) => queue.push_back((Instant::now() + delay, input.borrow_and_update().clone())),
+            Ok(false) => {}
+            Err(_) => break,
+        }
+        // Only the newest due frame matters; older ones would be overwritten at once
+        let now = Instant::now();
+        let mut due = None;
+        while queue.front().is_some_and(|(at, _)| *at <= now) {
+            due = queue.pop_front().map(|(_, frame)| frame);
+        }
+        if let Some(frame) = due {
+            if output.send(frame).is_err() {
+                break;
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Mono analysis window of a fixed block size, advanced by a hop that
+/// shrinks with a negative `audio.delay_ms`.
+pub(super) struct SlidingWindow {
+    samples: Vec<f32>,
+    hop: usize,
+}
+
+impl SlidingWindow {
+    pub fn new(block: usize, delay_ms: i32, sample_rate: f32) -> Self {
+        let lead = delay_ms.min(0).unsigned_abs() as f32 / 1000.0 * sample_rate;
+        let hop = (block as f32 - lead).round() as usize;
+        Self {
+            samples: vec![0.0; block],
+            hop: hop.clamp(block / MIN_HOP_FRACTION, block),
+        }
+    }
+
+    /// New mono samples per analysis
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    /// Shift in `hop` stereo frames, downmixed to mono, and return the window.
+    pub fn push_stereo(&mut self, stereo: &[f32]) -> &[f32] {
+        let keep = self.samples.len() - self.hop;
+        self.samples.copy_within(self.hop.., 0);
+        for (sample, frame) in self.samples[keep..].iter_mut().zip(stereo.chunks(2)) {
+            *sample = (frame[0] + frame[1]) / 2.0;
+        }
+        &self.samples
+    }
+}
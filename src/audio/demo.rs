@@ -0,0 +1,158 @@
+//! Synthetic audio for `--demo` mode.
+//!
+//! Generates a music-like signal (kick, snare, hi-hats, a bass line, an
+//! arpeggio and a slow melodic sweep) in real time and feeds it through the
+//! normal FFT analyzer, so every style and audio setting behaves as it would
+//! with captured audio — without needing PulseAudio or anything playing.
+
+use std::f32::consts::TAU;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::debug;
+
+use super::fft::FrequencyAnalyzer;
+use super::AudioData;
+use crate::config::AudioConfig;
+
+const BPM: f32 = 124.0;
+/// Bass roots for a four-bar progression (A, F, C, G)
+const ROOTS: [f32; 4] = [55.0, 43.65, 65.41, 49.0];
+/// Arpeggio intervals in semitones above the root
+const ARPEGGIO: [f32; 4] = [12.0, 19.0, 24.0, 28.0];
+/// Bars taken by one rise and fall of the melodic sweep
+const SWEEP_BARS: f32 = 8.0;
+
+/// Real-time synthetic music generator.
+pub struct SyntheticSignal {
+    sample_rate: f32,
+    /// Samples generated so far
+    position: u64,
+    noise_state: u32,
+    last_noise: f32,
+    bass_phase: f32,
+    arp_phase: f32,
+    sweep_phase: f32,
+}
+
+impl SyntheticSignal {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            position: 0,
+            noise_state: 0x1234_5678,
+            last_noise: 0.0,
+            bass_phase: 0.0,
+            arp_phase: 0.0,
+            sweep_phase: 0.0,
+        }
+    }
+
+    /// Fill `out` with the next block of mono samples.
+    pub fn fill(&mut self, out: &mut [f32]) {
+        let beat_len = 60.0 / BPM;
+        for sample in out.iter_mut() {
+            let t = self.position as f32 / self.sample_rate;
+            self.position += 1;
+
+            let beats = t / beat_len;
+            let beat = beats.floor() as usize;
+            let since_beat = (beats - beats.floor()) * beat_len;
+            let eighths = beats * 2.0;
+            let since_eighth = (eighths - eighths.floor()) * beat_len / 2.0;
+            let sixteenth = (beats * 4.0).floor() as usize;
+            let bar = beat / 4;
+            let root = ROOTS[bar % ROOTS.len()];
+
+            // Kick: pitch drops from ~140 Hz to 50 Hz with a fast decay
+            let kick_phase = TAU * (50.0 * since_beat + 3.0 * (1.0 - (-30.0 * since_beat).exp()));
+            let kick = kick_phase.sin() * (-8.0 * since_beat).exp() * 0.8;
+
+            // Snare on beats 2 and 4: noise burst plus a short tone
+            let noise = self.noise();
+            let snare = if beat % 2 == 1 {
+                (noise * 0.35 + (TAU * 180.0 * since_beat).sin() * 0.2) * (-18.0 * since_beat).exp()
+            } else {
+                0.0
+            };
+
+            // Hi-hat on the off-beat eighths: differentiated noise is roughly high-passed
+            let hat = if (eighths.floor() as usize) % 2 == 1 {
+                (noise - self.last_noise) * (-60.0 * since_eighth).exp() * 0.15
+            } else {
+                0.0
+            };
+            self.last_noise = noise;
+
+            // Bass: a few harmonics of the root, retriggered every eighth note
+            self.bass_phase = (self.bass_phase + root / self.sample_rate).fract();
+            let bass = ((TAU * self.bass_phase).sin()
+                + (2.0 * TAU * self.bass_phase).sin() * 0.5
+                + (3.0 * TAU * self.bass_phase).sin() * 0.25)
+                * (-6.0 * since_eighth).exp()
+                * 0.25;
+
+            // Arpeggio: chord tones stepping every sixteenth
+            let note = root * 2f32.powf(ARPEGGIO[sixteenth % ARPEGGIO.len()] / 12.0);
+            self.arp_phase = (self.arp_phase + note / self.sample_rate).fract();
+            let since_sixteenth = (beats * 4.0).fract() * beat_len / 4.0;
+            let arp = (TAU * self.arp_phase).sin() * (-12.0 * since_sixteenth).exp() * 0.12;
+
+            // Melodic sweep gliding between 300 Hz and 3 kHz and back
+            let sweep_pos = (beats / (4.0 * SWEEP_BARS)).fract();
+            let glide = 1.0 - (2.0 * sweep_pos - 1.0).abs();
+            let sweep_freq = 300.0 * 10f32.powf(glide);
+            self.sweep_phase = (self.sweep_phase + sweep_freq / self.sample_rate).fract();
+            let sweep = (TAU * self.sweep_phase).sin() * 0.08;
+
+            *sample = ((kick + snare + hat + bass + arp + sweep) * 0.5).clamp(-1.0, 1.0);
+        }
+    }
+
+    /// White noise in -1.0..1.0 (xorshift)
+    fn noise(&mut self) -> f32 {
+        let mut x = self.noise_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.noise_state = x;
+        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
+
+/// Generate and analyze synthetic audio in real time until `stop_flag` is set
+/// or the receiver is dropped.
+pub fn run(
+    num_bars: usize,
+    sample_rate: f32,
+    audio_config: &AudioConfig,
+    sender: watch::Sender<Arc<AudioData>>,
+    stop_flag: Arc<AtomicBool>,
+) {
+    let mut analyzer = FrequencyAnalyzer::new(num_bars, sample_rate, audio_config);
+    let mut signal = SyntheticSignal::new(sample_rate);
+
+    // Same block size as live capture (~46ms at 44100Hz)
+    let mut block = vec![0.0f32; 2048];
+    let block_duration = Duration::from_secs_f32(block.len() as f32 / sample_rate);
+    let mut deadline = Instant::now();
+
+    while !stop_flag.load(Ordering::Relaxed) {
+        signal.fill(&mut block);
+        let audio_data = analyzer.process(&block);
+        if sender.send(Arc::new(audio_data)).is_err() {
+            debug!("Audio receiver dropped, stopping demo audio");
+            break;
+        }
+
+        // Pace output like a real capture device
+        deadline += block_duration;
+        let now = Instant::now();
+        if deadline > now {
+            std::thread::sleep(deadline - now);
+        } else {
+            deadline = now;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Auto-exposure for bar magnitudes.
+//!
+//! Like camera AE: a decaying histogram of recent magnitudes (in dB) tracks
+//! how loud the session has been, and a gain is eased toward the value that
+//! puts the configured clipping percentile at full bar height. Quiet acoustic
+//! recordings and loud masters end up filling the display similarly.
+
+use crate::config::AudioConfig;
+
+/// Histogram range in dB relative to a full-height bar at unity gain
+const MIN_DB: f32 = -60.0;
+const MAX_DB: f32 = 20.0;
+const BINS: usize = 80;
+
+/// Gain limits so silence or a single spike can't push exposure to extremes
+const MIN_GAIN: f32 = 0.05;
+const MAX_GAIN: f32 = 50.0;
+
+/// Histogram memory relative to gain adaptation: the histogram forgets ten
+/// times more slowly than the gain moves, so exposure follows the session
+/// rather than the last few beats.
+const HISTORY_RATE: f32 = 0.1;
+
+/// Minimum histogram weight before exposure starts adapting
+const MIN_WEIGHT: f32 = 256.0;
+
+pub struct AutoExposure {
+    histogram: [f32; BINS],
+    gain: f32,
+    /// Adaptation rate per second
+    speed: f32,
+    /// Fraction of recent magnitudes mapped below full height
+    clip_percentile: f32,
+    /// Exposure compensation (the configured sensitivity)
+    compensation: f32,
+}
+
+impl AutoExposure {
+    pub fn new(audio_config: &AudioConfig) -> Self {
+        Self {
+            histogram: [0.0; BINS],
+            gain: 1.0,
+            speed: audio_config.auto_exposure_speed.max(0.0),
+            clip_percentile: audio_config.auto_exposure_clip.clamp(0.5, 1.0),
+            compensation: audio_config.sensitivity.scale(),
+        }
+    }
+
+    /// Record `magnitudes` in the histogram, update the gain, and scale them
+    /// in place to 0.0-1.0.
+    pub fn apply(&mut self, magnitudes: &mut [f32], dt: f32) {
+        let decay = (-self.speed * HISTORY_RATE * dt).exp();
+        let mut added = false;
+        for &m in magnitudes.iter() {
+            if let Some(bin) = bin_for(m) {
+                if !added {
+                    // Only age the histogram while there is signal, so pauses
+                    // between tracks don't reset exposure
+                    self.histogram.iter_mut().for_each(|w| *w *= decay);
+                    added = true;
+                }
+                self.histogram[bin] += 1.0;
+            }
+        }
+
+        if let Some(reference) = self.percentile() {
+            let target = (1.0 / reference).clamp(MIN_GAIN, MAX_GAIN);
+            // Ease in the log domain so brightening and darkening feel symmetric
+            let alpha = 1.0 - (-self.speed * dt).exp();
+            self.gain *= (target / self.gain).powf(alpha);
+        }
+
+        let gain = self.gain * self.compensation;
+        for m in magnitudes.iter_mut() {
+            *m = (*m * gain).min(1.0);
+        }
+    }
+
+    /// Magnitude below which `clip_percentile` of the histogram weight lies
+    fn percentile(&self) -> Option<f32> {
+        let total: f32 = self.histogram.iter().sum();
+        if total < MIN_WEIGHT {
+            return None;
+        }
+        let threshold = total * self.clip_percentile;
+        let mut cumulative = 0.0;
+        for (bin, &weight) in self.histogram.iter().enumerate() {
+            cumulative += weight;
+            if cumulative >= threshold {
+                let db = MIN_DB + (bin + 1) as f32 * (MAX_DB - MIN_DB) / BINS as f32;
+                return Some(10.0_f32.powf(db / 20.0));
+            }
+        }
+        None
+    }
+}
+
+/// Histogram bin for a magnitude, or None if it is below the noise floor
+fn bin_for(magnitude: f32) -> Option<usize> {
+    if magnitude <= 0.0 {
+        return None;
+    }
+    let db = 20.0 * magnitude.log10();
+    if db < MIN_DB {
+        return None;
+    }
+    let bin = ((db - MIN_DB) / (MAX_DB - MIN_DB) * BINS as f32) as usize;
+    Some(bin.min(BINS - 1))
+}
@@ -1,127 +1,343 @@
 use rustfft::{num_complex::Complex, FftPlanner};
 
-use super::AudioData;
+use super::agc::AutoGain;
+use super::exposure::AutoExposure;
+use super::delay::SlidingWindow;
+use super::waveform::WaveformBuffer;
+use super::{AudioData, Bands};
+use crate::config::{AudioConfig, Interpolation, Sensitivity, Weighting};
+
+/// FFT size the magnitude scale is calibrated for (and the single-window size)
+const REFERENCE_FFT_SIZE: usize = 2048;
+/// Multi-resolution windows: long for the bass, short for everything above
+const LONG_FFT_SIZE: usize = 8192;
+const SHORT_FFT_SIZE: usize = 1024;
+/// Bars entirely below this frequency use the long window in multi-resolution mode
+const MULTIRES_CROSSOVER_HZ: f32 = 250.0;
+/// Frequency range spread across the bars (the top is also capped at Nyquist)
+const MIN_FREQ: f32 = 50.0; // Hz - start a bit higher for better bass response
+const MAX_FREQ: f32 = 10000.0; // Hz - cap lower for more activity
+
+/// A-weighting gain in dB at `freq` (IEC 61672, 0 dB at 1 kHz)
+fn a_weighting_db(freq: f32) -> f32 {
+    let f2 = freq * freq;
+    let ra = 12194.0_f32.powi(2) * f2 * f2
+        / ((f2 + 20.6_f32.powi(2))
+            * ((f2 + 107.7_f32.powi(2)) * (f2 + 737.9_f32.powi(2))).sqrt()
+            * (f2 + 12194.0_f32.powi(2)));
+    20.0 * ra.log10() + 2.0
+}
+
+/// EQ gain in dB at `position` (0.0 = lowest band, 1.0 = highest), linearly
+/// interpolated between the evenly spread `points`
+fn eq_db(points: &[f32], position: f32) -> f32 {
+    match points {
+        [] => 0.0,
+        [only] => *only,
+        _ => {
+            let pos = position.clamp(0.0, 1.0) * (points.len() - 1) as f32;
+            let (a, t) = (pos as usize, pos.fract());
+            let b = (a + 1).min(points.len() - 1);
+            points[a] * (1.0 - t) + points[b] * t
+        }
+    }
+}
+
+/// One windowed FFT of a fixed size
+struct Spectrum {
+    size: usize,
+    buffer: Vec<Complex<f32>>,
+    window: Vec<f32>,
+}
+
+impl Spectrum {
+    fn new(size: usize) -> Self {
+        // Hann window for smoother frequency response
+        let window = (0..size)
+            .map(|i| 0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / (size - 1) as f32).cos()))
+            .collect();
+        Self {
+            size,
+            buffer: vec![Complex::new(0.0, 0.0); size],
+            window,
+        }
+    }
+
+    /// Transform the first `size` samples, zero-padding short input.
+    fn compute(&mut self, planner: &mut FftPlanner<f32>, samples: &[f32]) {
+        for (i, slot) in self.buffer.iter_mut().enumerate() {
+            let sample = samples.get(i).copied().unwrap_or(0.0);
+            *slot = Complex::new(sample * self.window[i], 0.0);
+        }
+        planner.plan_fft_forward(self.size).process(&mut self.buffer);
+    }
+
+    /// Average magnitude over `freq_start..freq_end`, scaled to match a
+    /// `REFERENCE_FFT_SIZE` transform. Bands narrower than a bin are read
+    /// between bins with `interpolation`.
+    fn band(&self, freq_start: f32, freq_end: f32, sample_rate: f32, interpolation: Interpolation) -> f32 {
+        // Use only positive frequencies (first half of FFT output)
+        let useful_bins = self.size / 2;
+        let (pos_start, pos_end) = (freq_start * self.size as f32 / sample_rate, freq_end * self.size as f32 / sample_rate);
+        let avg = if interpolation != Interpolation::Nearest && pos_end - pos_start < 1.0 {
+            self.magnitude_at((pos_start + pos_end) / 2.0, interpolation)
+        } else {
+            let bin_start = (pos_start.floor() as usize).min(useful_bins - 1);
+            let bin_end = (pos_end.ceil() as usize).min(useful_bins).max(bin_start + 1);
+            let sum: f32 = self.buffer[bin_start..bin_end].iter().map(|c| c.norm()).sum();
+            sum / (bin_end - bin_start) as f32
+        };
+        // Tonal magnitudes grow with the window length
+        avg * REFERENCE_FFT_SIZE as f32 / self.size as f32
+    }
+
+    /// Magnitude at fractional bin `pos`, interpolated between bins
+    fn magnitude_at(&self, pos: f32, interpolation: Interpolation) -> f32 {
+        let last = self.size / 2 - 1;
+        let bin = |i: isize| self.buffer[(i.max(0) as usize).min(last)].norm();
+        let (i, t) = (pos.floor() as isize, pos.fract());
+        let (y1, y2) = (bin(i), bin(i + 1));
+        if interpolation != Interpolation::Cubic {
+            return y1 + (y2 - y1) * t;
+        }
+        // Cubic Hermite with Fritsch-Carlson style slopes: flat at local
+        // extremes, so the curve never overshoots the bins
+        let slope = |a: f32, b: f32| if a * b <= 0.0 { 0.0 } else { 2.0 * a * b / (a + b) };
+        let (d0, d1, d2) = (y1 - bin(i - 1), y2 - y1, bin(i + 2) - y2);
+        let (m1, m2) = (slope(d0, d1), slope(d1, d2));
+        let (t2, t3) = (t * t, t * t * t);
+        (2.0 * t3 - 3.0 * t2 + 1.0) * y1 + (t3 - 2.0 * t2 + t) * m1 + (3.0 * t2 - 2.0 * t3) * y2 + (t3 - t2) * m2
+    }
+}
 
 pub struct FrequencyAnalyzer {
-    fft_size: usize,
     num_bars: usize,
     sample_rate: f32,
-    smoothing: f32,
+    /// Fraction of the gap closed per frame when a bar rises
+    rise_speed: f32,
+    /// Fraction of the gap closed per frame when a bar falls (non-gravity mode)
+    fall_speed: f32,
+    /// Falling acceleration in bar heights per second² (0.0 = disabled)
+    gravity: f32,
     planner: FftPlanner<f32>,
-    buffer: Vec<Complex<f32>>,
-    window: Vec<f32>,
+    spectrum: Spectrum,
+    /// Multi-resolution mode: sample history and the long-window FFT for the bass
+    bass: Option<(WaveformBuffer, Spectrum)>,
     previous_magnitudes: Vec<f32>,
+    /// Current downward velocity per bar (gravity mode)
+    fall_velocities: Vec<f32>,
+    /// Linear weighting/EQ gain per band (bass to treble, half the bars)
+    band_gains: Vec<f32>,
+    /// First band of the mids and of the treble (indices into the half bars)
+    crossovers: (usize, usize),
+    interpolation: Interpolation,
+    /// Magnitudes below this are dropped (0.0 = no floor)
+    noise_floor: f32,
     sensitivity: f32,
+    /// Adaptive gain replacing the fixed sensitivity scale when enabled
+    exposure: Option<AutoExposure>,
+    /// Peak-following gain for `sensitivity = "auto"` (auto-exposure wins when both are set)
+    agc: Option<AutoGain>,
+    /// Most recent `audio.waveform_samples` samples for oscilloscope display
+    waveform: WaveformBuffer,
 }
 
 impl FrequencyAnalyzer {
-    pub fn new(num_bars: usize, sample_rate: f32, smoothing: f32, sensitivity: f32) -> Self {
-        let fft_size = 2048; // Good balance of frequency resolution and responsiveness
-        let planner = FftPlanner::new();
+    pub fn new(num_bars: usize, sample_rate: f32, audio_config: &AudioConfig) -> Self {
+        // Multi-resolution trades the single 2048 window for a short one up
+        // top and a long one for the bass
+        let (fft_size, bass) = if audio_config.multires {
+            (SHORT_FFT_SIZE, Some((WaveformBuffer::new(LONG_FFT_SIZE), Spectrum::new(LONG_FFT_SIZE))))
+        } else {
+            (REFERENCE_FFT_SIZE, None)
+        };
 
-        // Hann window for smoother frequency response
-        let window: Vec<f32> = (0..fft_size)
-            .map(|i| {
-                0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / (fft_size - 1) as f32).cos())
+        let half_bars = num_bars / 2;
+        let band_gains = (0..half_bars)
+            .map(|bar| {
+                let (freq_start, freq_end) = band_range(bar, half_bars, sample_rate);
+                let weighting = match audio_config.weighting {
+                    Weighting::None => 0.0,
+                    Weighting::A => a_weighting_db((freq_start * freq_end).sqrt()),
+                };
+                let position = bar as f32 / half_bars.saturating_sub(1).max(1) as f32;
+                10.0_f32.powf((weighting + eq_db(&audio_config.eq, position)) / 20.0)
             })
             .collect();
+        // A band belongs to the range its center frequency falls in
+        let bands_below = |hz: f32| {
+            (0..half_bars)
+                .take_while(|&bar| {
+                    let (start, end) = band_range(bar, half_bars, sample_rate);
+                    (start * end).sqrt() < hz
+                })
+                .count()
+        };
+        let bass_end = bands_below(audio_config.bass_crossover_hz);
+        let crossovers = (bass_end, bands_below(audio_config.treble_crossover_hz).max(bass_end));
 
         Self {
-            fft_size,
             num_bars,
             sample_rate,
-            smoothing,
-            planner,
-            buffer: vec![Complex::new(0.0, 0.0); fft_size],
-            window,
+            rise_speed: audio_config.effective_rise_speed(),
+            fall_speed: audio_config.effective_fall_speed(),
+            gravity: audio_config.gravity.max(0.0),
+            planner: FftPlanner::new(),
+            spectrum: Spectrum::new(fft_size),
+            bass,
             previous_magnitudes: vec![0.0; num_bars],
-            sensitivity,
+            fall_velocities: vec![0.0; num_bars],
+            band_gains,
+            crossovers,
+            interpolation: audio_config.interpolation,
+            noise_floor: audio_config.noise_floor_db.map_or(0.0, |db| 10.0_f32.powf(db / 20.0)),
+            sensitivity: audio_config.sensitivity.scale(),
+            exposure: audio_config.auto_exposure.then(|| AutoExposure::new(audio_config)),
+            agc: (audio_config.sensitivity == Sensitivity::Auto).then(|| AutoGain::new(audio_config)),
+            waveform: WaveformBuffer::new(audio_config.waveform_samples),
         }
     }
 
     pub fn process(&mut self, samples: &[f32]) -> AudioData {
+        self.process_window(samples, samples.len())
+    }
+
+    /// Analyze a sliding window whose last `fresh` samples are new since the
+    /// previous call (the rest overlaps the previous window).
+    pub fn process_window(&mut self, samples: &[f32], fresh: usize) -> AudioData {
+        let fresh = fresh.min(samples.len());
+        let new_samples = &samples[samples.len() - fresh..];
+
+        // Input level (RMS on a -60..0 dBFS scale), before sensitivity scaling
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt();
+        let level = ((20.0 * rms.max(1e-6).log10() + 60.0) / 60.0).clamp(0.0, 1.0);
+
         // Capture raw waveform before windowing (for oscilloscope display)
-        let waveform: Vec<f32> = samples
+        let scaled: Vec<f32> = new_samples
             .iter()
-            .take(self.fft_size)
             .map(|&s| (s * self.sensitivity * 2.0).clamp(-1.0, 1.0))
             .collect();
+        self.waveform.push(&scaled);
 
-        // Fill buffer with windowed samples
-        for (i, sample) in samples.iter().take(self.fft_size).enumerate() {
-            self.buffer[i] = Complex::new(sample * self.window[i], 0.0);
-        }
-
-        // Zero-pad if needed
-        for i in samples.len()..self.fft_size {
-            self.buffer[i] = Complex::new(0.0, 0.0);
+        // Perform FFT; in multi-resolution mode the short window takes the newest samples
+        match &mut self.bass {
+            Some((history, long)) => {
+                history.push(new_samples);
+                long.compute(&mut self.planner, &history.to_vec());
+                let newest = samples.len().saturating_sub(self.spectrum.size);
+                self.spectrum.compute(&mut self.planner, &samples[newest..]);
+            }
+            None => self.spectrum.compute(&mut self.planner, samples),
         }
 
-        // Perform FFT
-        let fft = self.planner.plan_fft_forward(self.fft_size);
-        fft.process(&mut self.buffer);
-
         // Calculate magnitudes and map to bars
-        let frequencies = self.calculate_bar_magnitudes();
+        let mut frequencies = self.calculate_bar_magnitudes();
 
-        // Apply smoothing
-        let smoothed: Vec<f32> = frequencies
-            .iter()
-            .zip(self.previous_magnitudes.iter())
-            .map(|(&new, &old)| old * self.smoothing + new * (1.0 - self.smoothing))
-            .collect();
+        // Scale to bar heights, either adaptively or by the fixed sensitivity
+        let dt = fresh.max(1) as f32 / self.sample_rate;
+        match (&mut self.exposure, &mut self.agc) {
+            (Some(exposure), _) => exposure.apply(&mut frequencies, dt),
+            (None, Some(agc)) => agc.apply(&mut frequencies, dt),
+            (None, None) => frequencies
+                .iter_mut()
+                .for_each(|m| *m = (*m * self.sensitivity).min(1.0)),
+        }
 
-        self.previous_magnitudes = smoothed.clone();
+        // Apply attack/decay smoothing
+        let smoothed = self.apply_smoothing(&frequencies, dt);
 
         // Calculate overall metrics
         let intensity = smoothed.iter().sum::<f32>() / smoothed.len() as f32;
+        let bands = self.band_levels(&smoothed);
 
         AudioData {
             frequencies: smoothed,
             intensity,
-            waveform,
+            bands,
+            waveform: self.waveform.to_vec(),
+            stereo: Vec::new(),
+            level,
         }
     }
 
-    fn calculate_bar_magnitudes(&self) -> Vec<f32> {
-        // Use only positive frequencies (first half of FFT output)
-        let useful_bins = self.fft_size / 2;
+    /// Analyze interleaved stereo `frames` shifted into `window`, keeping
+    /// their left/right pairs for the vectorscope.
+    pub(super) fn process_stereo(&mut self, window: &mut SlidingWindow, frames: &[f32]) -> AudioData {
+        let hop = window.hop();
+        let mut audio_data = self.process_window(window.push_stereo(frames), hop);
+        let scale = |s: f32| (s * self.sensitivity * 2.0).clamp(-1.0, 1.0);
+        audio_data.stereo = frames.chunks_exact(2).map(|f| (scale(f[0]), scale(f[1]))).collect();
+        audio_data
+    }
+
+    /// Smooth new magnitudes against the previous frame.
+    ///
+    /// Rising bars close `rise_speed` of the gap per frame. Falling bars either
+    /// close `fall_speed` of the gap, or — when gravity is enabled — drop with
+    /// a velocity that accelerates until they reach the new value.
+    fn apply_smoothing(&mut self, frequencies: &[f32], dt: f32) -> Vec<f32> {
+        let smoothed: Vec<f32> = frequencies
+            .iter()
+            .zip(self.previous_magnitudes.iter())
+            .zip(self.fall_velocities.iter_mut())
+            .map(|((&new, &old), velocity)| {
+                if new >= old {
+                    *velocity = 0.0;
+                    old + (new - old) * self.rise_speed
+                } else if self.gravity > 0.0 {
+                    *velocity += self.gravity * dt;
+                    let fallen = old - *velocity * dt;
+                    if fallen <= new {
+                        // Landed on the new value — restart the fall from rest
+                        *velocity = 0.0;
+                        new
+                    } else {
+                        fallen
+                    }
+                } else {
+                    old + (new - old) * self.fall_speed
+                }
+            })
+            .collect();
+
+        self.previous_magnitudes = smoothed.clone();
+        smoothed
+    }
 
-        // Logarithmic frequency scaling for better visualization
-        // Human hearing is logarithmic, so we want more bars for lower frequencies
-        let min_freq = 50.0; // Hz - start a bit higher for better bass response
-        let max_freq = 10000.0_f32.min(self.sample_rate / 2.0); // Hz - cap lower for more activity
+    /// Average bass, mid and treble levels of the mirrored bars
+    fn band_levels(&self, bars: &[f32]) -> Bands {
+        // The right half runs bass → treble
+        let ordered = &bars[self.num_bars / 2..];
+        let (mid_start, treble_start) = self.crossovers;
+        let avg = |range: std::ops::Range<usize>| {
+            let slice = ordered.get(range).unwrap_or(&[]);
+            if slice.is_empty() { 0.0 } else { slice.iter().sum::<f32>() / slice.len() as f32 }
+        };
+        Bands {
+            bass: avg(0..mid_start),
+            mid: avg(mid_start..treble_start),
+            treble: avg(treble_start..self.num_bars / 2),
+        }
+    }
 
+    fn calculate_bar_magnitudes(&self) -> Vec<f32> {
         // Calculate half the bars, then mirror for symmetric display (like cava)
         let half_bars = self.num_bars / 2;
         let mut half_magnitudes = vec![0.0; half_bars];
 
         for (bar, magnitude_out) in half_magnitudes.iter_mut().enumerate() {
-            // Calculate frequency range for this bar (logarithmic scale)
-            let bar_start = (bar as f32) / (half_bars as f32);
-            let bar_end = ((bar + 1) as f32) / (half_bars as f32);
-
-            let freq_start = min_freq * (max_freq / min_freq).powf(bar_start);
-            let freq_end = min_freq * (max_freq / min_freq).powf(bar_end);
-
-            // Convert to bin indices
-            let bin_start =
-                ((freq_start * self.fft_size as f32) / self.sample_rate).floor() as usize;
-            let bin_end = ((freq_end * self.fft_size as f32) / self.sample_rate).ceil() as usize;
-
-            let bin_start = bin_start.min(useful_bins - 1);
-            let bin_end = bin_end.min(useful_bins).max(bin_start + 1);
-
-            // Average magnitude across bins
-            let mut sum = 0.0;
-            for bin in bin_start..bin_end {
-                let magnitude = self.buffer[bin].norm();
-                sum += magnitude;
-            }
+            let (freq_start, freq_end) = band_range(bar, half_bars, self.sample_rate);
 
-            let avg = sum / (bin_end - bin_start) as f32;
-            // Apply sensitivity scaling (base scale + user sensitivity multiplier)
-            *magnitude_out = (avg * 0.02 * self.sensitivity).min(1.0);
+            // Average magnitude across the bar's bins, from the long window for the bass
+            let spectrum = match &self.bass {
+                Some((_, long)) if freq_end <= MULTIRES_CROSSOVER_HZ => long,
+                _ => &self.spectrum,
+            };
+            let avg = spectrum.band(freq_start, freq_end, self.sample_rate, self.interpolation);
+            // Base scale and weighting only; sensitivity or auto-exposure is
+            // applied in `process`
+            let magnitude = avg * 0.02 * self.band_gains[bar];
+            *magnitude_out = if magnitude < self.noise_floor { 0.0 } else { magnitude };
         }
 
         // Mirror: bass on edges, treble in middle
@@ -137,3 +353,15 @@ impl FrequencyAnalyzer {
         bar_magnitudes
     }
 }
+
+/// Frequency range of band `bar` out of `bands`. Human hearing is
+/// logarithmic, so the bands are spaced logarithmically (more for the lows).
+fn band_range(bar: usize, bands: usize, sample_rate: f32) -> (f32, f32) {
+    let max_freq = MAX_FREQ.min(sample_rate / 2.0);
+    let bar_start = bar as f32 / bands as f32;
+    let bar_end = (bar + 1) as f32 / bands as f32;
+    (
+        MIN_FREQ * (max_freq / MIN_FREQ).powf(bar_start),
+        MIN_FREQ * (max_freq / MIN_FREQ).powf(bar_end),
+    )
+}
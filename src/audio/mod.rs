@@ -1,11 +1,48 @@
+mod agc;
+mod app;
+mod beat;
 mod capture;
+mod delay;
+mod demo;
+mod exposure;
 mod fft;
+pub mod recording;
+mod waveform;
 
+pub use app::{list_apps, APP_PREFIX};
+pub use beat::BeatDetector;
 pub use capture::{list_sources, AudioCapture};
+pub use fft::FrequencyAnalyzer;
 
 use std::sync::Arc;
 use tokio::sync::watch;
 
+use crate::config::AudioConfig;
+
+/// Aggregate levels of the bass, mid and treble ranges (0.0 to 1.0), split at
+/// `audio.bass_crossover_hz` and `audio.treble_crossover_hz`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Bands {
+    pub bass: f32,
+    pub mid: f32,
+    pub treble: f32,
+}
+
+impl Bands {
+    /// Approximate levels from the bars alone, splitting their bass → treble
+    /// half into thirds (for bars not straight from the analyzer, e.g. the idle demo)
+    pub fn from_bars(frequencies: &[f32]) -> Self {
+        let ordered = &frequencies[frequencies.len() / 2..];
+        let third = (ordered.len() / 3).max(1);
+        let avg = |s: &[f32]| if s.is_empty() { 0.0 } else { s.iter().sum::<f32>() / s.len() as f32 };
+        Self {
+            bass: avg(&ordered[..third.min(ordered.len())]),
+            mid: avg(ordered.get(third..(third * 2).min(ordered.len())).unwrap_or(&[])),
+            treble: avg(ordered.get((third * 2).min(ordered.len())..).unwrap_or(&[])),
+        }
+    }
+}
+
 /// Audio data shared between capture and visualization
 #[derive(Debug, Clone)]
 pub struct AudioData {
@@ -13,8 +50,15 @@ pub struct AudioData {
     pub frequencies: Vec<f32>,
     /// Overall volume/intensity
     pub intensity: f32,
+    /// Bass, mid and treble levels
+    pub bands: Bands,
     /// Raw waveform samples for oscilloscope display (-1.0 to 1.0)
     pub waveform: Vec<f32>,
+    /// Left/right sample pairs of the newest block for the vectorscope
+    /// (-1.0 to 1.0); empty for mono sources
+    pub stereo: Vec<(f32, f32)>,
+    /// Input level before sensitivity scaling (0.0 = -60 dBFS or quieter, 1.0 = 0 dBFS)
+    pub level: f32,
 }
 
 impl Default for AudioData {
@@ -22,20 +66,81 @@ impl Default for AudioData {
         Self {
             frequencies: vec![0.0; 64],
             intensity: 0.0,
+            bands: Bands::default(),
             waveform: Vec::new(),
+            stereo: Vec::new(),
+            level: 0.0,
         }
     }
 }
 
-/// Create an audio processing pipeline
+impl AudioData {
+    /// Copy with the frequency bands resampled to `bars` (per-monitor bar counts):
+    /// neighbouring bands are averaged when shrinking, interpolated when growing.
+    pub fn with_bars(&self, bars: usize) -> AudioData {
+        let n = self.frequencies.len();
+        let frequencies = (0..bars)
+            .map(|i| {
+                if n == 0 {
+                    return 0.0;
+                }
+                let start = i as f32 * n as f32 / bars as f32;
+                let end = (i + 1) as f32 * n as f32 / bars as f32;
+                if end - start >= 1.0 {
+                    let (a, b) = (start as usize, (end.ceil() as usize).min(n));
+                    self.frequencies[a..b].iter().sum::<f32>() / (b - a) as f32
+                } else {
+                    let pos = ((start + end) / 2.0 - 0.5).clamp(0.0, (n - 1) as f32);
+                    let (a, t) = (pos as usize, pos.fract());
+                    let b = (a + 1).min(n - 1);
+                    self.frequencies[a] * (1.0 - t) + self.frequencies[b] * t
+                }
+            })
+            .collect();
+        AudioData {
+            frequencies,
+            ..self.clone()
+        }
+    }
+
+    /// Resampled to `bars` and scaled by `gain` (per-monitor and per-style
+    /// overrides); shared as is when neither changes anything.
+    pub fn reshaped(self: &Arc<Self>, bars: Option<usize>, gain: Option<f32>) -> Arc<AudioData> {
+        let bars = bars.filter(|&bars| bars > 0 && bars != self.frequencies.len());
+        if bars.is_none() && gain.is_none() {
+            return self.clone();
+        }
+        let mut data = match bars {
+            Some(bars) => self.with_bars(bars),
+            None => (**self).clone(),
+        };
+        if let Some(gain) = gain {
+            for f in &mut data.frequencies {
+                *f = (*f * gain).clamp(0.0, 1.0);
+            }
+            for level in [&mut data.bands.bass, &mut data.bands.mid, &mut data.bands.treble] {
+                *level = (*level * gain).clamp(0.0, 1.0);
+            }
+        }
+        Arc::new(data)
+    }
+}
+
+/// Create an audio processing pipeline (synthetic audio when `audio.demo` is
+/// set, a recording with `--replay-features`)
 pub fn create_audio_pipeline(
     num_bars: usize,
-    smoothing: f32,
-    sensitivity: f32,
+    audio_config: &AudioConfig,
     device: Option<String>,
 ) -> anyhow::Result<(AudioCapture, watch::Receiver<Arc<AudioData>>)> {
-    let (tx, rx) = watch::channel(Arc::new(AudioData::default()));
-    let capture = AudioCapture::new(num_bars, smoothing, sensitivity, tx, device)?;
+    let (tx, rx) = delay::channel(audio_config.delay_ms);
+    let capture = if let Some(recording) = recording::replaying() {
+        AudioCapture::new_replay(num_bars, recording, tx)?
+    } else if audio_config.demo {
+        AudioCapture::new_demo(num_bars, audio_config, tx)?
+    } else {
+        AudioCapture::new(num_bars, audio_config, tx, device)?
+    };
     Ok((capture, rx))
 }
 
@@ -43,13 +148,51 @@ pub fn create_audio_pipeline(
 ///
 /// Unlike `create_audio_pipeline`, this does NOT append `.monitor` to the source name,
 /// which is appropriate when using source names from `list_sources()`.
+/// `app:<name>` records a single application instead.
 pub fn create_audio_pipeline_with_source(
     num_bars: usize,
-    smoothing: f32,
-    sensitivity: f32,
+    audio_config: &AudioConfig,
     source: String,
 ) -> anyhow::Result<(AudioCapture, watch::Receiver<Arc<AudioData>>)> {
-    let (tx, rx) = watch::channel(Arc::new(AudioData::default()));
-    let capture = AudioCapture::new_with_source(num_bars, smoothing, sensitivity, tx, source)?;
+    let (tx, rx) = delay::channel(audio_config.delay_ms);
+    let capture = if let Some(recording) = recording::replaying() {
+        AudioCapture::new_replay(num_bars, recording, tx)?
+    } else if audio_config.demo {
+        AudioCapture::new_demo(num_bars, audio_config, tx)?
+    } else {
+        AudioCapture::new_with_source(num_bars, audio_config, tx, source)?
+    };
     Ok((capture, rx))
 }
+
+/// Create the default pipeline, either on an explicitly selected source
+/// (a name from `list_sources()`) or, when `selected` is None, on the configured device.
+/// Its frames are what `--record-features` records.
+pub fn create_audio_pipeline_for(
+    num_bars: usize,
+    audio_config: &AudioConfig,
+    selected: Option<String>,
+) -> anyhow::Result<(AudioCapture, watch::Receiver<Arc<AudioData>>)> {
+    let (capture, rx) = match selected {
+        Some(source) => create_audio_pipeline_with_source(num_bars, audio_config, source)?,
+        None => create_audio_pipeline(num_bars, audio_config, audio_config.default_device())?,
+    };
+    Ok((capture, recording::tap(rx)))
+}
+
+/// Pick the source after (or before) `current` in the `list_sources()` order,
+/// wrapping around. Starts from the first source when `current` is unknown.
+pub fn adjacent_source(current: Option<&str>, forward: bool) -> anyhow::Result<String> {
+    let sources = list_sources()?;
+    if sources.is_empty() {
+        anyhow::bail!("no audio sources found");
+    }
+    let len = sources.len();
+    let idx = match current.and_then(|c| sources.iter().position(|(name, _)| name == c)) {
+        Some(i) if forward => (i + 1) % len,
+        Some(i) => (i + len - 1) % len,
+        None if forward => 0,
+        None => len - 1,
+    };
+    Ok(sources[idx].0.clone())
+}
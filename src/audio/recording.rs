@@ -0,0 +1,257 @@
+//! `--record-features` / `--replay-features`: capturing the analyzed audio
+//! and feeding it back later.
+//!
+//! A recording is a CBOR sequence: a header, then one item per frame the
+//! default pipeline produced, with its time since the recording started.
+//! Replaying it stands in for audio capture in every pipeline (like
+//! `--demo`), so a glitch can be reproduced from the exact data that caused
+//! it, and the renderer can be checked against known input.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+use super::{AudioData, Bands};
+
+/// Identifies a recording, in its header
+const FORMAT: &str = "cavibe-features";
+/// Bumped when `RecordedFrame` changes incompatibly
+const VERSION: u32 = 1;
+/// How often the recording thread checks for new frames
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+/// Shortest pause before a replay starts over (recordings of a single frame)
+const MIN_REPLAY_GAP: Duration = Duration::from_millis(20);
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+}
+
+/// One `AudioData` on disk
+#[derive(Serialize, Deserialize)]
+struct RecordedFrame {
+    /// Seconds since the recording started
+    time: f32,
+    frequencies: Vec<f32>,
+    intensity: f32,
+    /// Bass, mid and treble
+    bands: [f32; 3],
+    waveform: Vec<f32>,
+    stereo: Vec<(f32, f32)>,
+    level: f32,
+}
+
+impl RecordedFrame {
+    fn new(time: f32, data: &AudioData) -> Self {
+        Self {
+            time,
+            frequencies: data.frequencies.clone(),
+            intensity: data.intensity,
+            bands: [data.bands.bass, data.bands.mid, data.bands.treble],
+            waveform: data.waveform.clone(),
+            stereo: data.stereo.clone(),
+            level: data.level,
+        }
+    }
+
+    fn into_audio_data(self) -> AudioData {
+        let [bass, mid, treble] = self.bands;
+        AudioData {
+            frequencies: self.frequencies,
+            intensity: self.intensity,
+            bands: Bands { bass, mid, treble },
+            waveform: self.waveform,
+            stereo: self.stereo,
+            level: self.level,
+        }
+    }
+}
+
+/// A recording loaded into memory.
+pub struct Recording {
+    /// Frames with their time since the first one, in seconds
+    frames: Vec<(f32, AudioData)>,
+}
+
+impl Recording {
+    /// Read the recording at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut reader = BufReader::new(file);
+        let header: Header = ciborium::from_reader(&mut reader)
+            .with_context(|| format!("{} is not a feature recording", path.display()))?;
+        if header.format != FORMAT {
+            bail!("{} is not a feature recording", path.display());
+        }
+        if header.version != VERSION {
+            bail!("{} is a version {} recording; this cavibe reads version {}", path.display(), header.version, VERSION);
+        }
+
+        let mut frames = Vec::new();
+        loop {
+            match ciborium::from_reader::<RecordedFrame, _>(&mut reader) {
+                Ok(frame) => {
+                    let time = frame.time;
+                    let previous = frames.last().map_or(0.0, |(last, _)| *last);
+                    if !time.is_finite() || time < previous {
+                        bail!("{}: frame {} has an invalid time ({})", path.display(), frames.len(), time);
+                    }
+                    frames.push((time, frame.into_audio_data()));
+                }
+                // A recording cut short (cavibe killed mid-write) keeps the frames before
+                Err(ciborium::de::Error::Io(_)) => break,
+                Err(e) => bail!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+        if frames.is_empty() {
+            bail!("{} has no frames", path.display());
+        }
+        let start = frames[0].0;
+        for (time, _) in &mut frames {
+            *time -= start;
+        }
+        Ok(Self { frames })
+    }
+
+    /// Frames with their time since the first one, in seconds
+    pub fn frames(&self) -> &[(f32, AudioData)] {
+        &self.frames
+    }
+}
+
+/// The file frames are appended to, and when recording started
+struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+static REPLAY: OnceLock<Arc<Recording>> = OnceLock::new();
+
+/// Record the default pipeline's frames to `path` (`--record-features`),
+/// replacing the file.
+pub fn record_to(path: &Path) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    ciborium::into_writer(&Header { format: FORMAT.to_string(), version: VERSION }, &mut writer)
+        .map_err(|e| anyhow::anyhow!("{}", e))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    if let Ok(mut recorder) = RECORDER.lock() {
+        *recorder = Some(Recorder { writer, start: Instant::now() });
+    }
+    Ok(())
+}
+
+/// Replay the recording at `path` instead of capturing audio
+/// (`--replay-features`). Only the first call takes effect.
+pub fn replay_from(path: &Path) -> Result<()> {
+    let recording = Recording::load(path)?;
+    let _ = REPLAY.set(Arc::new(recording));
+    Ok(())
+}
+
+/// The recording to replay, if any
+pub(super) fn replaying() -> Option<Arc<Recording>> {
+    REPLAY.get().cloned()
+}
+
+/// Pass `input` through, writing every frame to the recording when one is
+/// being made.
+pub(super) fn tap(input: watch::Receiver<Arc<AudioData>>) -> watch::Receiver<Arc<AudioData>> {
+    if !RECORDER.lock().is_ok_and(|recorder| recorder.is_some()) {
+        return input;
+    }
+    let (output, rx) = watch::channel(input.borrow().clone());
+    thread::spawn(move || forward_recorded(input, output));
+    rx
+}
+
+/// Forward frames from `input` to `output`, appending each to the recording,
+/// until the capture stops or the display drops its receiver.
+fn forward_recorded(mut input: watch::Receiver<Arc<AudioData>>, output: watch::Sender<Arc<AudioData>>) {
+    loop {
+        match input.has_changed() {
+            Ok(true) => {
+                let frame = input.borrow_and_update().clone();
+                write_frame(&frame);
+                if output.send(frame).is_err() {
+                    break;
+                }
+            }
+            Ok(false) => {}
+            Err(_) => break,
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn write_frame(data: &AudioData) {
+    let Ok(mut guard) = RECORDER.lock() else {
+        return;
+    };
+    let Some(recorder) = guard.as_mut() else {
+        return;
+    };
+    let frame = RecordedFrame::new(recorder.start.elapsed().as_secs_f32(), data);
+    // Flushed per frame: the process usually ends by signal, without unwinding
+    let result = ciborium::into_writer(&frame, &mut recorder.writer)
+        .map_err(|e| e.to_string())
+        .and_then(|()| recorder.writer.flush().map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Failed to write feature recording, stopping it: {}", e);
+        *guard = None;
+    }
+}
+
+/// Send the frames of `recording`, resampled to `num_bars`, at their
+/// recorded pace and over again from the start, until `stop_flag` is set or
+/// the receiver is dropped.
+pub(super) fn run_replay(
+    num_bars: usize,
+    recording: &Recording,
+    sender: watch::Sender<Arc<AudioData>>,
+    stop_flag: Arc<AtomicBool>,
+) {
+    let frames: Vec<(Duration, Arc<AudioData>)> = recording
+        .frames()
+        .iter()
+        .map_while(|(time, data)| {
+            let data = if data.frequencies.len() == num_bars { data.clone() } else { data.with_bars(num_bars) };
+            Some((Duration::try_from_secs_f32(*time).ok()?, Arc::new(data)))
+        })
+        .collect();
+    // Leave one average frame between the last frame and the first of the next pass
+    let gap = frames.last().map_or(Duration::ZERO, |(end, _)| *end / frames.len() as u32).max(MIN_REPLAY_GAP);
+
+    let mut start = Instant::now();
+    loop {
+        for (at, data) in &frames {
+            if stop_flag.load(Ordering::Relaxed) {
+                return;
+            }
+            // Frames too far out to schedule end the pass
+            let Some(due) = start.checked_add(*at) else {
+                break;
+            };
+            let now = Instant::now();
+            if due > now {
+                thread::sleep(due - now);
+            }
+            if sender.send(data.clone()).is_err() {
+                debug!("Audio receiver dropped, stopping replay");
+                return;
+            }
+        }
+        let now = Instant::now();
+        start = now.checked_add(gap).unwrap_or(now);
+    }
+}
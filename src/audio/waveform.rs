@@ -0,0 +1,42 @@
+//! Fixed-length waveform history for the oscilloscope-style renderers.
+//!
+//! Capture backends deliver blocks of whatever size their buffers happen to
+//! use; keeping the most recent `audio.waveform_samples` samples in a ring
+//! gives the display the same time window regardless of backend settings.
+//! The multi-resolution analyzer uses the same ring as its long FFT window.
+
+/// Supported range for `audio.waveform_samples`
+const MIN_SAMPLES: usize = 64;
+const MAX_SAMPLES: usize = 65536;
+
+pub struct WaveformBuffer {
+    samples: Vec<f32>,
+    /// Index of the oldest sample (the next one to be overwritten)
+    head: usize,
+}
+
+impl WaveformBuffer {
+    pub fn new(len: usize) -> Self {
+        Self {
+            samples: vec![0.0; len.clamp(MIN_SAMPLES, MAX_SAMPLES)],
+            head: 0,
+        }
+    }
+
+    /// Append `block`, overwriting the oldest samples.
+    pub fn push(&mut self, block: &[f32]) {
+        let len = self.samples.len();
+        // Only the newest `len` samples of an oversized block survive
+        let block = &block[block.len().saturating_sub(len)..];
+        for &sample in block {
+            self.samples[self.head] = sample;
+            self.head = (self.head + 1) % len;
+        }
+    }
+
+    /// The buffered samples, oldest first.
+    pub fn to_vec(&self) -> Vec<f32> {
+        let (newer, older) = self.samples.split_at(self.head);
+        older.iter().chain(newer).copied().collect()
+    }
+}
@@ -0,0 +1,281 @@
+//! Headless benchmark (`cavibe bench`).
+//!
+//! Feeds synthetic audio (a sine sweep, white noise and impulses) through the
+//! analyzer and renders every style into an offscreen canvas, reporting frame
+//! times and heap allocations per style. No display backend or audio server
+//! is needed, so results are comparable across machines and commits.
+
+#[cfg(feature = "bench")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::audio::FrequencyAnalyzer;
+use crate::config::{Config, SourceIndicatorConfig};
+use crate::renderer::{self, fire::FireState, particles::ParticleSystem, sparks::SparkState, styles, vectorscope::VectorscopeState, vu::VuState, Canvas, FrameData, RenderParams};
+
+/// Allocator wrapper counting allocations, so the benchmark can report them.
+/// Only built with the `bench` feature.
+#[cfg(feature = "bench")]
+pub struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether allocations are counted in this build
+const COUNTS_ALLOCATIONS: bool = cfg!(feature = "bench");
+
+#[cfg(feature = "bench")]
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+fn allocation_counters() -> (usize, usize) {
+    (ALLOCATIONS.load(Ordering::Relaxed), ALLOCATED_BYTES.load(Ordering::Relaxed))
+}
+
+const SAMPLE_RATE: f32 = 44100.0;
+/// Samples analyzed per frame, as in live capture
+const BLOCK: usize = 2048;
+
+/// Synthetic test signal, each played for a third of the frames
+#[derive(Clone, Copy)]
+enum Signal {
+    /// Sine sweeping logarithmically from 30 Hz to 16 kHz
+    Sweep,
+    /// White noise
+    Noise,
+    /// Clicks four times a second, like a kick drum
+    Impulses,
+}
+
+/// Generates consecutive blocks of the test signals.
+struct SignalGenerator {
+    /// Samples generated so far
+    position: usize,
+    phase: f32,
+    seed: u32,
+}
+
+impl SignalGenerator {
+    fn new() -> Self {
+        Self { position: 0, phase: 0.0, seed: 0x2545_F491 }
+    }
+
+    fn next_block(&mut self, signal: Signal, progress: f32, out: &mut [f32]) {
+        for sample in out.iter_mut() {
+            *sample = match signal {
+                Signal::Sweep => {
+                    let freq = 30.0 * (16_000.0f32 / 30.0).powf(progress);
+                    self.phase = (self.phase + std::f32::consts::TAU * freq / SAMPLE_RATE) % std::f32::consts::TAU;
+                    self.phase.sin() * 0.5
+                }
+                Signal::Noise => {
+                    // xorshift32
+                    self.seed ^= self.seed << 13;
+                    self.seed ^= self.seed >> 17;
+                    self.seed ^= self.seed << 5;
+                    (self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0) * 0.3
+                }
+                Signal::Impulses => {
+                    let since_click = self.position % (SAMPLE_RATE as usize / 4);
+                    if since_click == 0 {
+                        1.0
+                    } else {
+                        // Decaying 60 Hz thump after the click
+                        let t = since_click as f32 / SAMPLE_RATE;
+                        (-t * 30.0).exp() * (std::f32::consts::TAU * 60.0 * t).sin() * 0.8
+                    }
+                }
+            };
+            self.position += 1;
+        }
+    }
+}
+
+/// Timing and allocation totals for one style
+struct StyleResult {
+    name: String,
+    frame_times: Vec<Duration>,
+    allocations: usize,
+    allocated_bytes: usize,
+}
+
+impl StyleResult {
+    fn report(&mut self) -> String {
+        self.frame_times.sort();
+        let frames = self.frame_times.len().max(1);
+        let total: Duration = self.frame_times.iter().sum();
+        let avg = total.as_secs_f64() * 1000.0 / frames as f64;
+        let p95 = self.frame_times.get(frames * 95 / 100).or(self.frame_times.last()).copied().unwrap_or_default();
+        let max = self.frame_times.last().copied().unwrap_or_default();
+        format!(
+            "{:<16} {:>8.3} {:>8.3} {:>8.3} {:>8.0} {:>10} {:>10}",
+            self.name,
+            avg,
+            p95.as_secs_f64() * 1000.0,
+            max.as_secs_f64() * 1000.0,
+            if avg > 0.0 { 1000.0 / avg } else { 0.0 },
+            per_frame(self.allocations as f64 / frames as f64),
+            per_frame(self.allocated_bytes as f64 / frames as f64 / 1024.0),
+        )
+    }
+}
+
+/// An allocation column, or `-` when the build doesn't count allocations
+fn per_frame(value: f64) -> String {
+    if COUNTS_ALLOCATIONS {
+        format!("{:.1}", value)
+    } else {
+        "-".to_string()
+    }
+}
+
+/// Render `frames` frames of every style at `width`×`height` and print a report.
+pub fn run(config: &Config, frames: usize, width: usize, height: usize) -> Result<()> {
+    if frames == 0 || width == 0 || height == 0 {
+        anyhow::bail!("frames, width and height must be greater than zero");
+    }
+    let scale = config.renderer.supersample;
+    println!(
+        "cavibe bench: {}x{}, {} bars, {} frames per style, render scale {}",
+        width, height, config.visualizer.bars, frames, scale
+    );
+    println!(
+        "{:<16} {:>8} {:>8} {:>8} {:>8} {:>10} {:>10}",
+        "style", "avg ms", "p95 ms", "max ms", "fps", "allocs/f", "KiB/f"
+    );
+    if !COUNTS_ALLOCATIONS {
+        println!("(allocation counts need a build with `--features bench`)");
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    let mut work = Canvas::new(0, 0);
+    let (_, render_height) = renderer::scaled_size(width, height, scale);
+    let title = Some("Synthetic Signal".to_string());
+    let artist = Some("cavibe bench".to_string());
+    let source_indicator = SourceIndicatorConfig { enabled: false, ..config.source_indicator.clone() };
+    let segments = &config.visualizer.segments;
+    // Bar sizes are in the same units as wallpaper mode
+    let pixel_scale = config.renderer.pixel_scale.max(1) as usize;
+    let mut block = vec![0.0f32; BLOCK];
+
+    for style in 0..styles::style_count() {
+        let mut analyzer = FrequencyAnalyzer::new(config.visualizer.bars, SAMPLE_RATE, &config.audio);
+        let mut generator = SignalGenerator::new();
+        let mut spectrogram_history = Vec::new();
+        let mut amplitude_history = Vec::new();
+        let mut particles = ParticleSystem::default();
+        let mut fire = FireState::default();
+        let mut vectorscope = VectorscopeState::default();
+        let mut vu = VuState::default();
+        let mut sparks = SparkState::default();
+        let mut result = StyleResult {
+            name: styles::style_name(style),
+            frame_times: Vec::with_capacity(frames),
+            allocations: 0,
+            allocated_bytes: 0,
+        };
+
+        for frame in 0..frames {
+            let third = frames.div_ceil(3);
+            let signal = [Signal::Sweep, Signal::Noise, Signal::Impulses][(frame / third).min(2)];
+            let progress = (frame % third) as f32 / third as f32;
+            generator.next_block(signal, progress, &mut block);
+            let audio = analyzer.process(&block);
+            let time = frame as f32 * BLOCK as f32 / SAMPLE_RATE;
+
+            let (allocs_before, bytes_before) = allocation_counters();
+            let start = Instant::now();
+
+            styles::push_spectrogram_row(&mut spectrogram_history, &audio.frequencies, &config.visualizer.spectrogram, render_height);
+            styles::push_amplitude(&mut amplitude_history, &audio.waveform, time, &config.visualizer.seismograph);
+            particles.update(&audio.frequencies, time, &config.visualizer.particles);
+            if styles::draws_builtin(style, styles::FIRE_STYLE) {
+                fire.update(&audio.frequencies, time, &config.visualizer.fire);
+            }
+            if styles::draws_builtin(style, styles::VECTORSCOPE_STYLE) {
+                vectorscope.update(&audio.stereo, &audio.waveform, time, &config.visualizer.vectorscope);
+            }
+            vu.update(&audio.stereo, &audio.waveform, time, &config.visualizer.vu);
+            sparks.update(&audio.frequencies, time, &config.effects.sparks);
+            let params = RenderParams {
+                style,
+                bar_width: config.visualizer.bar_width as usize * pixel_scale,
+                bar_spacing: config.visualizer.bar_spacing as usize * pixel_scale,
+                mirror: config.visualizer.mirror,
+                reverse_mirror: config.visualizer.reverse_mirror,
+                interpolation: config.audio.interpolation,
+                group_size: config.visualizer.group.size,
+                group_gap: config.visualizer.group.gap as usize * pixel_scale,
+                segment_height: if segments.enabled { segments.height as usize * pixel_scale } else { 0 },
+                segment_gap: segments.gap as usize * pixel_scale,
+                segment_zones: &segments.zones,
+                orientation: config.visualizer.orientation,
+                opacity: config.visualizer.opacity,
+                min_bar_height: config.visualizer.min_bar_height,
+                color_scheme: &config.visualizer.color_scheme,
+                waveform: &audio.waveform,
+                spectrogram_history: &spectrogram_history,
+                spectrogram: &config.visualizer.spectrogram,
+                amplitude_history: &amplitude_history,
+                seismograph: &config.visualizer.seismograph,
+                karaoke: &config.visualizer.karaoke,
+                radial: &config.visualizer.radial,
+                oscilloscope: &config.visualizer.oscilloscope,
+                particles: &particles,
+                fire: &fire,
+                vectorscope: &vectorscope,
+                vu: &vu,
+                sparks: &sparks,
+                text_config: &config.text,
+                album_art: &config.album_art,
+                source_indicator: &source_indicator,
+                widgets: &config.widgets,
+                effects: &config.renderer.effects,
+                transform: renderer::transform::Transform::from_config(&config.visualizer),
+                bar_rounding: config.visualizer.bar_rounding,
+                symmetry: config.visualizer.symmetry,
+                background: None,
+                font: None,
+            };
+            let frame_data = FrameData {
+                frequencies: &audio.frequencies,
+                intensity: audio.intensity,
+                bands: audio.bands,
+                track_title: &title,
+                track_artist: &artist,
+                album_art: None,
+                source: None,
+                input_level: audio.level,
+                time,
+                text_opacity: 1.0,
+                flash: 0.0,
+            };
+            renderer::render_frame_scaled(&mut canvas, &mut work, &frame_data, &params, scale, config.renderer.upscale);
+
+            result.frame_times.push(start.elapsed());
+            let (allocs_after, bytes_after) = allocation_counters();
+            result.allocations += allocs_after - allocs_before;
+            result.allocated_bytes += bytes_after - bytes_before;
+        }
+        println!("{}", result.report());
+    }
+    Ok(())
+}
@@ -0,0 +1,336 @@
+//! `cavibe check-config`: validate a config file without starting anything.
+//!
+//! Besides TOML syntax and type errors (which stop loading), this reports
+//! keys cavibe doesn't know and would silently ignore, values outside their
+//! range, style names that don't resolve, and `[[wallpaper.monitors]]`
+//! outputs a running instance doesn't have. Each finding points at its line.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use toml_edit::{ImDocument, Item, TableLike};
+
+use crate::config::{Config, CoordValue, Sensitivity};
+use crate::renderer::{custom, plugin, styles};
+
+/// Old key names still accepted through `#[serde(alias)]`
+const RENAMED_KEYS: &[(&str, &str)] = &[
+    ("renderer.render_scale", "supersample"),
+    ("performance.min_active_secs", "idle_timeout_secs"),
+    ("terminal.render_mode", "blocks"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Severity {
+    Error,
+    Warning,
+}
+
+struct Finding {
+    severity: Severity,
+    /// Dotted key the finding is about (array entries by index), for its line
+    key: String,
+    message: String,
+}
+
+#[derive(Default)]
+struct Findings(Vec<Finding>);
+
+impl Findings {
+    fn error(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Finding { severity: Severity::Error, key: key.into(), message: message.into() });
+    }
+
+    fn warn(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Finding { severity: Severity::Warning, key: key.into(), message: message.into() });
+    }
+
+    fn range(&mut self, key: &str, value: f32, min: f32, max: f32) {
+        if !(min..=max).contains(&value) {
+            self.error(key, format!("`{}` = {} is outside {}-{}", key, value, min, max));
+        }
+    }
+}
+
+/// Check the config at `path` and print the findings. Fails when there are errors.
+pub async fn run(path: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // Syntax and type errors already carry line context
+    let config = match Config::load(path) {
+        Ok(config) => config,
+        Err(e) => bail!("{}: {:#}", path.display(), e),
+    };
+    let doc = ImDocument::parse(content.as_str()).context("Failed to parse the config")?;
+
+    let mut findings = Findings::default();
+    let known = toml::Value::try_from(&config).context("Failed to serialize the config")?;
+    if let Some(known) = known.as_table() {
+        unknown_keys(doc.as_table(), known, "", &mut findings);
+    }
+    check_ranges(&config, &mut findings);
+    check_styles(&config, &mut findings);
+    let outputs_checked = check_outputs(&config, &mut findings).await;
+
+    for finding in &findings.0 {
+        let label = match finding.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match line_of(&doc, &content, &finding.key) {
+            Some(line) => {
+                println!("{}:{}: {}: {}", path.display(), line, label, finding.message);
+                if let Some(text) = content.lines().nth(line - 1) {
+                    println!("{:>5} | {}", line, text.trim_end());
+                }
+            }
+            None => println!("{}: {}: {}", path.display(), label, finding.message),
+        }
+    }
+    if !outputs_checked && !config.wallpaper.monitors.is_empty() {
+        println!("note: monitor outputs not checked (no running cavibe to ask)");
+    }
+
+    let errors = findings.0.iter().filter(|f| f.severity == Severity::Error).count();
+    let warnings = findings.0.len() - errors;
+    if errors > 0 {
+        bail!("{}: {} error(s), {} warning(s)", path.display(), errors, warnings);
+    }
+    println!("{}: OK ({} warning(s))", path.display(), warnings);
+    Ok(())
+}
+
+/// Keys in the file that aren't in the parsed config. Serde ignores them, so
+/// they are compared against the config serialized back, which holds every
+/// field cavibe read.
+fn unknown_keys(user: &dyn TableLike, known: &toml::Table, prefix: &str, findings: &mut Findings) {
+    for (key, item) in user.iter() {
+        let path = if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+        match known.get(key) {
+            None => {
+                if let Some((_, new)) = RENAMED_KEYS.iter().find(|(old, _)| *old == path) {
+                    findings.warn(&path, format!("`{}` is the old name of `{}`", path, new));
+                    continue;
+                }
+                let hint = closest(key, known.keys().map(String::as_str))
+                    .map(|name| format!(" (did you mean `{}`?)", name))
+                    .unwrap_or_default();
+                let section = if prefix.is_empty() { String::new() } else { format!(" in [{}]", prefix) };
+                findings.warn(&path, format!("unknown key `{}`{}, ignored{}", key, section, hint));
+            }
+            Some(toml::Value::Table(known)) => {
+                if let Some(table) = item.as_table_like() {
+                    unknown_keys(table, known, &path, findings);
+                }
+            }
+            Some(toml::Value::Array(known)) => {
+                for (index, known) in known.iter().enumerate() {
+                    if let (Some(table), Some(known)) = (array_table(item, index), known.as_table()) {
+                        unknown_keys(table, known, &format!("{}.{}", path, index), findings);
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Entry `index` of an array of tables or an array of inline tables
+fn array_table(item: &Item, index: usize) -> Option<&dyn TableLike> {
+    match item {
+        Item::ArrayOfTables(tables) => tables.get(index).map(|t| t as &dyn TableLike),
+        Item::Value(toml_edit::Value::Array(array)) => {
+            array.get(index)?.as_inline_table().map(|t| t as &dyn TableLike)
+        }
+        _ => None,
+    }
+}
+
+/// The known name closest to a misspelled one, if any is close enough
+fn closest<'a>(name: &str, known: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (name.len() / 3).max(2);
+    known
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1).min(row[j] + 1).min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+fn check_ranges(config: &Config, findings: &mut Findings) {
+    let audio = &config.audio;
+    findings.range("visualizer.opacity", config.visualizer.opacity, 0.0, 1.0);
+    if config.visualizer.bars < 1 {
+        findings.error("visualizer.bars", "`visualizer.bars` must be at least 1");
+    }
+    match config.visualizer.min_bar_height {
+        CoordValue::Pixels(px) if px < 0 => {
+            findings.error("visualizer.min_bar_height", "`visualizer.min_bar_height` can't be negative");
+        }
+        CoordValue::Percent(percent) => findings.range("visualizer.min_bar_height", percent, 0.0, 100.0),
+        CoordValue::Pixels(_) => {}
+    }
+    findings.range("audio.smoothing", audio.smoothing, 0.0, 1.0);
+    if let Some(rise) = audio.rise_speed {
+        findings.range("audio.rise_speed", rise, 0.0, 1.0);
+    }
+    if let Some(fall) = audio.fall_speed {
+        findings.range("audio.fall_speed", fall, 0.0, 1.0);
+    }
+    if let Sensitivity::Fixed(scale) = audio.sensitivity {
+        let (min, max) = crate::config::AudioConfig::SENSITIVITY_RANGE;
+        findings.range("audio.sensitivity", scale, min, max);
+    }
+    findings.range("audio.auto_exposure_clip", audio.auto_exposure_clip, 0.5, 1.0);
+    if audio.bass_crossover_hz >= audio.treble_crossover_hz {
+        findings.error(
+            "audio.bass_crossover_hz",
+            format!(
+                "`audio.bass_crossover_hz` ({}) must be below `audio.treble_crossover_hz` ({})",
+                audio.bass_crossover_hz, audio.treble_crossover_hz
+            ),
+        );
+    }
+    findings.range("renderer.supersample", config.renderer.supersample, 0.25, 4.0);
+    if config.renderer.backend == crate::config::RenderBackend::Gpu && !cfg!(feature = "gpu") {
+        findings.warn("renderer.backend", "this build has no `gpu` feature; frames are drawn on the CPU");
+    }
+    findings.range("color.brightness", config.color.brightness, 0.0, 3.0);
+    findings.range("color.saturation", config.color.saturation, 0.0, 3.0);
+    findings.range("color.gamma", config.color.gamma, 0.1, 5.0);
+    findings.range("leds.brightness", config.leds.brightness, 0.0, 1.0);
+    findings.range("performance.idle_threshold", config.performance.idle_threshold, 0.0, 1.0);
+    if config.display.rotate_styles && config.display.rotation_interval_secs == 0 {
+        findings.error("display.rotation_interval_secs", "`display.rotation_interval_secs` must be at least 1");
+    }
+    for (index, monitor) in config.wallpaper.monitors.iter().enumerate() {
+        let key = format!("wallpaper.monitors.{}", index);
+        if let Some(opacity) = monitor.opacity {
+            findings.range(&format!("{}.opacity", key), opacity, 0.0, 1.0);
+        }
+        if monitor.bars == Some(0) {
+            findings.error(format!("{}.bars", key), format!("`bars` of monitor {} must be at least 1", monitor.output));
+        }
+    }
+}
+
+/// Style names that don't resolve; cavibe falls back or ignores them
+fn check_styles(config: &Config, findings: &mut Findings) {
+    // So plugin style names resolve; broken plugins are reported at startup
+    let _ = plugin::load_plugins();
+    if let Err(e) = custom::register_custom_styles(&config.styles) {
+        findings.error("styles", e);
+        return;
+    }
+    let mut check = |key: String, style: &str| {
+        if let Err(e) = styles::resolve_style(style) {
+            findings.warn(key, e);
+        }
+    };
+    if let Some(style) = config.visualizer.style.as_deref().filter(|s| !s.eq_ignore_ascii_case("random")) {
+        check("visualizer.style".to_string(), style);
+    }
+    for (index, monitor) in config.wallpaper.monitors.iter().enumerate() {
+        if let Some(style) = &monitor.style {
+            check(format!("wallpaper.monitors.{}.style", index), style);
+        }
+    }
+    for (index, rule) in config.metadata.rules.iter().enumerate() {
+        if let Some(style) = &rule.style {
+            check(format!("metadata.rules.{}.style", index), style);
+        }
+    }
+    for (index, rule) in config.compositor.rules.iter().enumerate() {
+        if let Some(style) = &rule.style {
+            check(format!("compositor.rules.{}.style", index), style);
+        }
+    }
+    for name in config.style_overrides.keys() {
+        check(format!("style.{}", name), name);
+    }
+    for (name, overrides) in &config.style_overrides {
+        // Valid ones were already moved to [visualizer.<style>]
+        if let Some(mode) = &overrides.wide_mode {
+            let message = if name.eq_ignore_ascii_case("radial") {
+                format!("unknown wide_mode `{}` (single, rings, ellipse)", mode)
+            } else if name.eq_ignore_ascii_case("oscilloscope") {
+                format!("unknown wide_mode `{}` (stretch, repeat, zoom)", mode)
+            } else {
+                format!("`wide_mode` only applies to the radial and oscilloscope styles, not {}", name)
+            };
+            findings.warn(format!("style.{}.wide_mode", name), message);
+        }
+    }
+}
+
+/// Monitor outputs the running instance doesn't know. Returns false when
+/// there was no instance to ask.
+async fn check_outputs(config: &Config, findings: &mut Findings) -> bool {
+    if config.wallpaper.monitors.is_empty() {
+        return false;
+    }
+    let Ok(reply) = crate::ipc::send_command("list monitors", true).await else {
+        return false;
+    };
+    let reply: serde_json::Value = serde_json::from_str(&reply).unwrap_or_default();
+    let outputs: Vec<&str> = reply["data"]
+        .as_array()
+        .map(|list| list.iter().filter_map(|m| m["name"].as_str()).collect())
+        .unwrap_or_default();
+    // A terminal-mode instance has no outputs to compare against
+    if outputs.is_empty() {
+        return false;
+    }
+    for (index, monitor) in config.wallpaper.monitors.iter().enumerate() {
+        if !outputs.contains(&monitor.output.as_str()) {
+            findings.warn(
+                format!("wallpaper.monitors.{}.output", index),
+                format!("output `{}` is not connected (connected: {})", monitor.output, outputs.join(", ")),
+            );
+        }
+    }
+    true
+}
+
+/// 1-based line of a dotted key, or of the closest enclosing key found
+fn line_of(doc: &ImDocument<&str>, content: &str, key: &str) -> Option<usize> {
+    let mut table: &dyn TableLike = doc.as_table();
+    let mut span = None;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let Some((key, item)) = table.get_key_value(part) else {
+            break;
+        };
+        span = key.span().or_else(|| header_span(item)).or(span);
+        let child = match parts.next_if(|p| p.parse::<usize>().is_ok()) {
+            Some(index) => array_table(item, index.parse().unwrap_or_default()),
+            None => item.as_table_like(),
+        };
+        match child {
+            Some(child) => table = child,
+            None => break,
+        }
+    }
+    span.map(|span| content[..span.start].matches('\n').count() + 1)
+}
+
+/// Span of a table's header, for keys that don't have one of their own
+fn header_span(item: &Item) -> Option<std::ops::Range<usize>> {
+    match item {
+        Item::Table(table) => table.span(),
+        _ => None,
+    }
+}
@@ -1,9 +1,198 @@
-use palette::{Hsl, IntoColor, Srgb};
+use palette::{Hsl, Hsv, IntoColor, Srgb};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
-#[serde(rename_all = "lowercase")]
+use crate::audio::BeatDetector;
+use crate::config::{ColorAdjustConfig, ColorDynamicsConfig, ColorSchemeDef, RgbColor};
+
+/// A user-defined gradient color scheme loaded from `[[color_schemes]]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomColorScheme {
+    pub name: String,
+    /// Gradient stops, evenly spaced from position 0.0 to 1.0
+    pub stops: Vec<RgbColor>,
+    /// Exponent applied to intensity before it scales brightness (1.0 = linear)
+    pub intensity_curve: f32,
+}
+
+impl CustomColorScheme {
+    /// Build a scheme from its config definition, validating the hex stops.
+    pub fn from_def(def: &ColorSchemeDef) -> Result<Self, String> {
+        if def.name.trim().is_empty() {
+            return Err("color scheme name must not be empty".to_string());
+        }
+        if def.stops.is_empty() {
+            return Err(format!("color scheme '{}' has no stops", def.name));
+        }
+        let stops = def
+            .stops
+            .iter()
+            .map(|hex| {
+                RgbColor::from_hex(hex)
+                    .ok_or_else(|| format!("color scheme '{}': invalid stop '{}'", def.name, hex))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            name: def.name.clone(),
+            stops,
+            intensity_curve: def.intensity_curve.unwrap_or(1.0).max(0.01),
+        })
+    }
+
+    /// Interpolate the gradient at `position` and scale brightness by intensity.
+    fn get_color(&self, position: f32, intensity: f32) -> (u8, u8, u8) {
+        let position = position.clamp(0.0, 1.0);
+        let (r, g, b) = if self.stops.len() == 1 {
+            let c = self.stops[0];
+            (c.r as f32, c.g as f32, c.b as f32)
+        } else {
+            let scaled = position * (self.stops.len() - 1) as f32;
+            let idx = (scaled.floor() as usize).min(self.stops.len() - 2);
+            let t = scaled - idx as f32;
+            let a = self.stops[idx];
+            let b = self.stops[idx + 1];
+            (
+                a.r as f32 + (b.r as f32 - a.r as f32) * t,
+                a.g as f32 + (b.g as f32 - a.g as f32) * t,
+                a.b as f32 + (b.b as f32 - a.b as f32) * t,
+            )
+        };
+        // Match the built-in schemes: dim at low intensity, full color at peak
+        let brightness = 0.55 + 0.45 * intensity.clamp(0.0, 1.0).powf(self.intensity_curve);
+        (
+            (r * brightness) as u8,
+            (g * brightness) as u8,
+            (b * brightness) as u8,
+        )
+    }
+}
+
+/// Custom schemes registered from the config file, used to resolve names.
+static CUSTOM_SCHEMES: RwLock<Vec<Arc<CustomColorScheme>>> = RwLock::new(Vec::new());
+
+/// Replace the registered custom color schemes with the given definitions.
+///
+/// Must be called before deserializing any config that references custom
+/// scheme names. Returns an error describing the first invalid definition,
+/// including a name that a built-in or an earlier scheme already uses (it
+/// could never be selected).
+pub fn register_custom_schemes(defs: &[ColorSchemeDef]) -> Result<(), String> {
+    for (index, def) in defs.iter().enumerate() {
+        let name = def.name.trim();
+        if ColorScheme::builtin(name).is_some() || name.eq_ignore_ascii_case("random") {
+            return Err(format!("color scheme '{}' clashes with a built-in color scheme", name));
+        }
+        if defs[..index].iter().any(|earlier| earlier.name.trim().eq_ignore_ascii_case(name)) {
+            return Err(format!("color scheme '{}' is defined more than once", name));
+        }
+    }
+    let schemes = defs
+        .iter()
+        .map(|def| CustomColorScheme::from_def(def).map(Arc::new))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Ok(mut registry) = CUSTOM_SCHEMES.write() {
+        *registry = schemes;
+    }
+    Ok(())
+}
+
+fn custom_schemes() -> Vec<Arc<CustomColorScheme>> {
+    CUSTOM_SCHEMES.read().map(|r| r.clone()).unwrap_or_default()
+}
+
+/// Global brightness, saturation and gamma (f32 bits), read for every pixel
+/// so they are kept in atomics rather than behind a lock.
+static BRIGHTNESS: AtomicU32 = AtomicU32::new(0x3F80_0000); // 1.0
+static SATURATION: AtomicU32 = AtomicU32::new(0x3F80_0000);
+static GAMMA: AtomicU32 = AtomicU32::new(0x3F80_0000);
+/// `[color_dynamics]` state for the current frame: hue offset in degrees and
+/// brightness boost (0.0 = none)
+static HUE_SHIFT: AtomicU32 = AtomicU32::new(0);
+static FLASH: AtomicU32 = AtomicU32::new(0);
+
+/// Shortest gap between beats that count for color dynamics
+const MIN_BEAT_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Apply `[color]` adjustments to every color returned by [`ColorScheme::get_color`].
+pub fn set_adjustment(adjust: &ColorAdjustConfig) {
+    BRIGHTNESS.store(adjust.brightness.to_bits(), Ordering::Relaxed);
+    SATURATION.store(adjust.saturation.to_bits(), Ordering::Relaxed);
+    GAMMA.store(adjust.gamma.to_bits(), Ordering::Relaxed);
+}
+
+/// Scale HSV saturation and value, then apply gamma.
+fn adjust((r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+    let brightness = f32::from_bits(BRIGHTNESS.load(Ordering::Relaxed));
+    let saturation = f32::from_bits(SATURATION.load(Ordering::Relaxed));
+    let gamma = f32::from_bits(GAMMA.load(Ordering::Relaxed));
+    let hue_shift = f32::from_bits(HUE_SHIFT.load(Ordering::Relaxed));
+    let flash = f32::from_bits(FLASH.load(Ordering::Relaxed));
+    if brightness == 1.0 && saturation == 1.0 && gamma == 1.0 && hue_shift == 0.0 && flash == 0.0 {
+        return (r, g, b);
+    }
+
+    let mut hsv: Hsv = Srgb::new(r, g, b).into_format::<f32>().into_color();
+    hsv.hue += hue_shift;
+    hsv.saturation = (hsv.saturation * saturation).clamp(0.0, 1.0);
+    hsv.value = (hsv.value * brightness * (1.0 + flash)).clamp(0.0, 1.0);
+    let rgb: Srgb = hsv.into_color();
+
+    let inv_gamma = 1.0 / gamma.max(0.01);
+    let channel = |c: f32| (c.clamp(0.0, 1.0).powf(inv_gamma) * 255.0) as u8;
+    (channel(rgb.red), channel(rgb.green), channel(rgb.blue))
+}
+
+/// Drives `[color_dynamics]` from the audio, once per frame.
+#[derive(Debug, Default)]
+pub struct ColorDynamics {
+    /// Accumulated hue rotation in degrees
+    hue: f32,
+    flash: f32,
+    beats: BeatDetector,
+    /// Beats since the last scheme switch
+    beat_count: u32,
+}
+
+impl ColorDynamics {
+    /// Advance by `dt` seconds and publish the hue shift and flash for
+    /// [`ColorScheme::get_color`]. Returns true when it's time to switch to
+    /// the next color scheme.
+    pub fn update(&mut self, config: &ColorDynamicsConfig, frequencies: &[f32], intensity: f32, dt: f32) -> bool {
+        let beat = self.beats.update(intensity, config.beat_sensitivity, MIN_BEAT_INTERVAL);
+
+        self.hue = (self.hue + config.hue_speed * intensity * dt).rem_euclid(360.0);
+        // Bass: the lowest quarter of the bands
+        let bass_bands = &frequencies[..frequencies.len().div_ceil(4)];
+        let bass = bass_bands.iter().sum::<f32>() / bass_bands.len().max(1) as f32;
+        let hue_shift = self.hue + config.bass_hue_shift * bass.clamp(0.0, 1.0);
+
+        if beat {
+            self.flash = config.beat_flash;
+        } else if config.flash_decay_secs > 0.0 {
+            self.flash = (self.flash - config.beat_flash * dt / config.flash_decay_secs).max(0.0);
+        } else {
+            self.flash = 0.0;
+        }
+
+        HUE_SHIFT.store(hue_shift.to_bits(), Ordering::Relaxed);
+        FLASH.store(self.flash.to_bits(), Ordering::Relaxed);
+
+        if !beat || config.switch_every_beats == 0 {
+            return false;
+        }
+        self.beat_count += 1;
+        if self.beat_count >= config.switch_every_beats {
+            self.beat_count = 0;
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum ColorScheme {
     #[default]
     Spectrum,
@@ -13,28 +202,61 @@ pub enum ColorScheme {
     Forest,
     Purple,
     Monochrome,
+    Custom(Arc<CustomColorScheme>),
 }
 
 impl FromStr for ColorScheme {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(scheme) = Self::builtin(s) {
+            return Ok(scheme);
+        }
         match s.to_lowercase().as_str() {
-            "spectrum" => Ok(Self::Spectrum),
-            "rainbow" => Ok(Self::Rainbow),
-            "fire" => Ok(Self::Fire),
-            "ocean" => Ok(Self::Ocean),
-            "forest" => Ok(Self::Forest),
-            "purple" => Ok(Self::Purple),
-            "mono" | "monochrome" => Ok(Self::Monochrome),
-            _ => Err(format!("Unknown color scheme: {}", s)),
+            "random" => {
+                let all = Self::all();
+                Ok(all[crate::random::index(all.len())].clone())
+            }
+            _ => custom_schemes()
+                .into_iter()
+                .find(|c| c.name.eq_ignore_ascii_case(s))
+                .map(Self::Custom)
+                .ok_or_else(|| format!("Unknown color scheme: {}", s)),
+        }
+    }
+}
+
+impl Serialize for ColorScheme {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            ColorScheme::Custom(custom) => serializer.serialize_str(&custom.name),
+            builtin => serializer.serialize_str(&builtin.name().to_lowercase()),
         }
     }
 }
 
+impl<'de> Deserialize<'de> for ColorScheme {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        ColorScheme::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 impl ColorScheme {
-    /// Get color for a given position (0.0 to 1.0) and intensity (0.0 to 1.0)
+    /// Get color for a given position (0.0 to 1.0) and intensity (0.0 to 1.0),
+    /// with the global `[color]` adjustments applied
     pub fn get_color(&self, position: f32, intensity: f32) -> (u8, u8, u8) {
+        adjust(self.scheme_color(position, intensity))
+    }
+
+    /// Unadjusted scheme color
+    fn scheme_color(&self, position: f32, intensity: f32) -> (u8, u8, u8) {
         let (h, s, l) = match self {
             ColorScheme::Spectrum => {
                 // Classic spectrum: purple -> blue -> cyan -> green -> yellow -> red
@@ -69,6 +291,7 @@ impl ColorScheme {
                 // White/gray based on intensity
                 (0.0, 0.0, intensity * 0.8)
             }
+            ColorScheme::Custom(custom) => return custom.get_color(position, intensity),
         };
 
         let hsl = Hsl::new(h, s, l);
@@ -94,8 +317,23 @@ impl ColorScheme {
             .collect()
     }
 
-    pub fn all() -> &'static [ColorScheme] {
-        &[
+    /// The built-in scheme called `name` (case-insensitive)
+    fn builtin(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "spectrum" => Some(Self::Spectrum),
+            "rainbow" => Some(Self::Rainbow),
+            "fire" => Some(Self::Fire),
+            "ocean" => Some(Self::Ocean),
+            "forest" => Some(Self::Forest),
+            "purple" => Some(Self::Purple),
+            "mono" | "monochrome" => Some(Self::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// All built-in schemes followed by any registered custom schemes
+    pub fn all() -> Vec<ColorScheme> {
+        let mut all = vec![
             ColorScheme::Spectrum,
             ColorScheme::Rainbow,
             ColorScheme::Fire,
@@ -103,26 +341,40 @@ impl ColorScheme {
             ColorScheme::Forest,
             ColorScheme::Purple,
             ColorScheme::Monochrome,
-        ]
+        ];
+        all.extend(custom_schemes().into_iter().map(ColorScheme::Custom));
+        all
+    }
+
+    /// Look up a scheme from user input, accepting unique abbreviations
+    /// (see `crate::names`). The error lists the available schemes.
+    pub fn resolve(name: &str) -> Result<Self, String> {
+        if let Ok(scheme) = name.parse() {
+            return Ok(scheme);
+        }
+        let all = Self::all();
+        let names: Vec<&str> = all.iter().map(|c| c.name()).collect();
+        let idx = crate::names::find(name, &names, "color scheme")?;
+        Ok(all[idx].clone())
     }
 
     pub fn next(&self) -> Self {
         let all = Self::all();
         let current = all.iter().position(|c| c == self).unwrap_or(0);
-        all[(current + 1) % all.len()]
+        all[(current + 1) % all.len()].clone()
     }
 
     pub fn prev(&self) -> Self {
         let all = Self::all();
         let current = all.iter().position(|c| c == self).unwrap_or(0);
         if current == 0 {
-            all[all.len() - 1]
+            all[all.len() - 1].clone()
         } else {
-            all[current - 1]
+            all[current - 1].clone()
         }
     }
 
-    pub fn name(&self) -> &'static str {
+    pub fn name(&self) -> &str {
         match self {
             ColorScheme::Spectrum => "Spectrum",
             ColorScheme::Rainbow => "Rainbow",
@@ -131,6 +383,7 @@ impl ColorScheme {
             ColorScheme::Forest => "Forest",
             ColorScheme::Purple => "Purple",
             ColorScheme::Monochrome => "Monochrome",
+            ColorScheme::Custom(custom) => &custom.name,
         }
     }
 }
@@ -0,0 +1,298 @@
+//! Compositor integration (`[compositor]` section): hide, pause or restyle
+//! the visualizer depending on the focused workspace and window.
+//!
+//! Hyprland is followed through its event socket (`.socket2.sock`), Sway
+//! through its IPC socket (`$SWAYSOCK`). Under other compositors the rules
+//! never match.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+use crate::config::{CompositorConfig, CompositorRule};
+use crate::renderer::styles;
+
+/// Sway IPC message types
+const SWAY_GET_WORKSPACES: u32 = 1;
+const SWAY_SUBSCRIBE: u32 = 2;
+const SWAY_GET_TREE: u32 = 4;
+const SWAY_MAGIC: &[u8] = b"i3-ipc";
+
+/// The focused workspace and window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Focus {
+    pub workspace: String,
+    /// Window class (Hyprland, XWayland) or app_id (Sway); empty without a window
+    pub app: String,
+}
+
+/// Workspace rule state owned by a render loop
+#[derive(Default)]
+pub struct WorkspaceState {
+    /// Focus watcher, running while `[compositor]` is enabled
+    focus: Option<watch::Receiver<Focus>>,
+    /// Whether a watcher was started (it may have found no compositor)
+    started: bool,
+    /// Index of the rule in effect
+    rule: Option<usize>,
+    hide: bool,
+    pause: bool,
+}
+
+impl WorkspaceState {
+    /// Follow the current focus, starting or stopping the watcher as the
+    /// config changes. Returns the style to switch to when a rule with a
+    /// style just came into effect.
+    pub fn update(&mut self, config: &CompositorConfig) -> Option<usize> {
+        match (config.enabled, self.started) {
+            (true, false) => {
+                self.focus = start_watcher();
+                self.started = true;
+            }
+            // Dropping the receiver stops the watcher at its next event
+            (false, true) => {
+                self.focus = None;
+                self.started = false;
+            }
+            _ => {}
+        }
+
+        let rule = self.focus.as_ref().and_then(|rx| {
+            let focus = rx.borrow();
+            config.rules.iter().position(|rule| rule_matches(rule, &focus))
+        });
+        let entered = rule != self.rule;
+        self.rule = rule;
+        let rule = rule.and_then(|index| config.rules.get(index));
+        self.hide = rule.is_some_and(|rule| rule.hide);
+        self.pause = rule.is_some_and(|rule| rule.pause);
+        if !entered {
+            return None;
+        }
+        rule.and_then(|rule| rule.style.as_deref()).and_then(|style| {
+            styles::resolve_style(style)
+                .map_err(|e| warn!("Ignoring compositor rule style: {}", e))
+                .ok()
+        })
+    }
+
+    /// Whether the rule in effect hides the visualizer
+    pub fn hides(&self) -> bool {
+        self.hide
+    }
+
+    /// Whether the rule in effect freezes the visualizer. A hidden
+    /// visualizer still has to draw its blank frame.
+    pub fn pauses(&self) -> bool {
+        self.pause && !self.hide
+    }
+}
+
+fn rule_matches(rule: &CompositorRule, focus: &Focus) -> bool {
+    rule.workspace.as_ref().is_none_or(|workspace| *workspace == focus.workspace)
+        && rule
+            .app
+            .as_ref()
+            .is_none_or(|app| focus.app.to_lowercase().contains(&app.to_lowercase()))
+}
+
+/// Follow the focus of the running compositor in the background.
+/// Returns None when neither Hyprland nor Sway is running.
+fn start_watcher() -> Option<watch::Receiver<Focus>> {
+    let compositor = if let Ok(signature) = std::env::var("HYPRLAND_INSTANCE_SIGNATURE") {
+        Compositor::Hyprland(hyprland_dir(&signature))
+    } else if let Ok(socket) = std::env::var("SWAYSOCK") {
+        Compositor::Sway(PathBuf::from(socket))
+    } else {
+        warn!("Compositor rules disabled: neither Hyprland nor Sway is running");
+        return None;
+    };
+    let (tx, rx) = watch::channel(Focus::default());
+    tokio::spawn(async move {
+        let result = match compositor {
+            Compositor::Hyprland(dir) => watch_hyprland(&dir, &tx).await,
+            Compositor::Sway(socket) => watch_sway(&socket, &tx).await,
+        };
+        if let Err(e) = result {
+            warn!("Compositor rules disabled: {:#}", e);
+        }
+    });
+    Some(rx)
+}
+
+enum Compositor {
+    /// Directory holding Hyprland's sockets
+    Hyprland(PathBuf),
+    /// Sway's IPC socket
+    Sway(PathBuf),
+}
+
+/// Hyprland keeps its sockets under `$XDG_RUNTIME_DIR/hypr` (older
+/// releases: `/tmp/hypr`)
+fn hyprland_dir(signature: &str) -> PathBuf {
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("hypr").join(signature))
+        .filter(|dir| dir.exists());
+    runtime.unwrap_or_else(|| PathBuf::from("/tmp/hypr").join(signature))
+}
+
+/// Publish a changed focus. Returns false once nobody is listening.
+fn send_focus(tx: &watch::Sender<Focus>, focus: &Focus) -> bool {
+    if *tx.borrow() != *focus {
+        debug!("Focus: workspace {:?}, app {:?}", focus.workspace, focus.app);
+        tx.send_replace(focus.clone());
+    }
+    !tx.is_closed()
+}
+
+async fn watch_hyprland(dir: &std::path::Path, tx: &watch::Sender<Focus>) -> Result<()> {
+    let mut focus = Focus {
+        workspace: hyprland_request(dir, "j/activeworkspace").await?["name"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        app: hyprland_request(dir, "j/activewindow").await?["class"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    };
+    if !send_focus(tx, &focus) {
+        return Ok(());
+    }
+
+    let path = dir.join(".socket2.sock");
+    let events = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("Failed to connect to {}", path.display()))?;
+    info!("Following Hyprland workspaces");
+    let mut lines = BufReader::new(events).lines();
+    while let Some(line) = lines.next_line().await? {
+        let Some((event, data)) = line.split_once(">>") else {
+            continue;
+        };
+        match event {
+            "workspace" => focus.workspace = data.to_string(),
+            // `MONITOR,WORKSPACE` when another monitor is focused
+            "focusedmon" => {
+                if let Some((_, workspace)) = data.split_once(',') {
+                    focus.workspace = workspace.to_string();
+                }
+            }
+            // `CLASS,TITLE`, or just `,` when no window is focused
+            "activewindow" => focus.app = data.split(',').next().unwrap_or_default().to_string(),
+            _ => continue,
+        }
+        if !send_focus(tx, &focus) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Send a request to Hyprland's command socket and parse its JSON reply
+async fn hyprland_request(dir: &std::path::Path, request: &str) -> Result<Value> {
+    let path = dir.join(".socket.sock");
+    let mut stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("Failed to connect to {}", path.display()))?;
+    stream.write_all(request.as_bytes()).await?;
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    // `activewindow` replies `{}` when no window is focused
+    serde_json::from_slice(&reply).with_context(|| format!("Unexpected reply to {}", request))
+}
+
+async fn watch_sway(socket: &std::path::Path, tx: &watch::Sender<Focus>) -> Result<()> {
+    let mut stream = UnixStream::connect(socket)
+        .await
+        .with_context(|| format!("Failed to connect to {}", socket.display()))?;
+
+    sway_send(&mut stream, SWAY_GET_WORKSPACES, b"").await?;
+    let (_, workspaces) = sway_read(&mut stream).await?;
+    sway_send(&mut stream, SWAY_GET_TREE, b"").await?;
+    let (_, tree) = sway_read(&mut stream).await?;
+    let mut focus = Focus {
+        workspace: workspaces
+            .as_array()
+            .and_then(|list| list.iter().find(|ws| ws["focused"] == true))
+            .and_then(|ws| ws["name"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        app: focused_node(&tree).map(sway_app).unwrap_or_default(),
+    };
+    if !send_focus(tx, &focus) {
+        return Ok(());
+    }
+
+    sway_send(&mut stream, SWAY_SUBSCRIBE, br#"["workspace","window"]"#).await?;
+    let (_, reply) = sway_read(&mut stream).await?;
+    if reply["success"] != true {
+        bail!("Sway refused the event subscription");
+    }
+    info!("Following Sway workspaces");
+    loop {
+        let (_, event) = sway_read(&mut stream).await?;
+        if event["change"] != "focus" {
+            continue;
+        }
+        // Workspace events carry `current`, window events `container`
+        if let Some(name) = event["current"]["name"].as_str() {
+            focus.workspace = name.to_string();
+        } else if event["container"].is_object() {
+            focus.app = sway_app(&event["container"]);
+        }
+        if !send_focus(tx, &focus) {
+            return Ok(());
+        }
+    }
+}
+
+/// Sway IPC message: magic, payload length and type (native endian), payload
+async fn sway_send(stream: &mut UnixStream, kind: u32, payload: &[u8]) -> Result<()> {
+    let mut message = Vec::with_capacity(14 + payload.len());
+    message.extend_from_slice(SWAY_MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload);
+    stream.write_all(&message).await?;
+    Ok(())
+}
+
+/// Read one Sway IPC reply or event
+async fn sway_read(stream: &mut UnixStream) -> Result<(u32, Value)> {
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header).await?;
+    if &header[..6] != SWAY_MAGIC {
+        bail!("Unexpected data on the Sway socket");
+    }
+    let length = u32::from_ne_bytes(header[6..10].try_into()?) as usize;
+    let kind = u32::from_ne_bytes(header[10..14].try_into()?);
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).await?;
+    Ok((kind, serde_json::from_slice(&payload)?))
+}
+
+/// The focused node of a Sway layout tree
+fn focused_node(node: &Value) -> Option<&Value> {
+    if node["focused"] == true {
+        return Some(node);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(focused_node)
+}
+
+/// app_id of a Wayland window, or the class of an XWayland one
+fn sway_app(node: &Value) -> String {
+    node["app_id"]
+        .as_str()
+        .or_else(|| node["window_properties"]["class"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use crate::color::ColorScheme;
 use crate::display::DisplayMode;
@@ -27,56 +29,1753 @@ pub struct MonitorConfig {
     pub style: Option<String>, // Style name
     pub opacity: Option<f32>,
     pub audio_source: Option<String>, // PulseAudio sink name for this monitor
+    /// Bar count (the global bands are resampled to it)
+    #[serde(default)]
+    pub bars: Option<usize>,
+    /// Layer-shell layer for this output; unlike the other overrides it also
+    /// applies in clone mode
+    #[serde(default)]
+    pub layer: Option<WallpaperLayer>,
+    // Layout overrides, same formats as the `[wallpaper]` fields
+    #[serde(default)]
+    pub anchor: Option<WallpaperAnchor>,
+    #[serde(default)]
+    pub width: Option<String>,
+    #[serde(default)]
+    pub height: Option<String>,
+    /// Uniform margin, replacing all four global margins
+    #[serde(default)]
+    pub margin: Option<i32>,
+    #[serde(default)]
+    pub margin_top: Option<i32>,
+    #[serde(default)]
+    pub margin_right: Option<i32>,
+    #[serde(default)]
+    pub margin_bottom: Option<i32>,
+    #[serde(default)]
+    pub margin_left: Option<i32>,
 }
 
 fn default_true() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub display: DisplayConfig,
-    pub audio: AudioConfig,
-    pub visualizer: VisualizerConfig,
-    pub text: TextConfig,
-    #[serde(default)]
-    pub wallpaper: WallpaperConfig,
+/// User-defined gradient color scheme (`[[color_schemes]]` entries)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorSchemeDef {
+    pub name: String,
+    /// Hex color stops, evenly spaced across the gradient (e.g. ["#2B1055", "#D53369"])
+    pub stops: Vec<String>,
+    /// Exponent applied to intensity before it scales brightness (default 1.0)
+    #[serde(default)]
+    pub intensity_curve: Option<f32>,
+}
+
+/// What a custom style layer draws
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LayerKind {
+    /// One bar per frequency band
+    Bars,
+    /// A line connecting the band levels
+    Line,
+    /// Particles rising from each band, brighter when the band is loud
+    Particles,
+    /// A built-in style, drawn on its own and composited as one layer
+    Style,
+}
+
+/// How a custom style layer is combined with the layers beneath it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BlendMode {
+    /// Drawn over the layers beneath (alpha compositing)
+    #[default]
+    Normal,
+    /// Added to the layers beneath; overlaps glow brighter
+    Additive,
+    /// Lightens like overlapping projectors, without clipping as hard as additive
+    Screen,
+}
+
+/// Bar gain (`audio.sensitivity`): a fixed multiplier, or `"auto"` for
+/// automatic gain control
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sensitivity {
+    Fixed(f32),
+    Auto,
+}
+
+impl Sensitivity {
+    /// The fixed multiplier, or 1.0 under automatic gain control
+    pub fn scale(self) -> f32 {
+        match self {
+            Self::Fixed(scale) => scale,
+            Self::Auto => 1.0,
+        }
+    }
+}
+
+impl fmt::Display for Sensitivity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fixed(scale) => write!(f, "{}", scale),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl FromStr for Sensitivity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+        s.trim()
+            .parse()
+            .map(Self::Fixed)
+            .map_err(|_| format!("Invalid sensitivity '{}': expected a number or auto", s))
+    }
+}
+
+impl Serialize for Sensitivity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Fixed(scale) => serializer.serialize_f32(*scale),
+            Self::Auto => serializer.serialize_str("auto"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Sensitivity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f32),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(scale) => Ok(Self::Fixed(scale)),
+            Raw::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Loudness weighting of the frequency bands (`audio.weighting`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Weighting {
+    /// Raw FFT magnitudes
+    #[default]
+    None,
+    /// The A-weighting curve: tames the bass and lifts the presence range,
+    /// roughly matching perceived loudness
+    A,
+}
+
+/// How bars narrower than an FFT bin are read from the spectrum, and whether
+/// bars covering several bands average them (`audio.interpolation`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    /// Nearest bin or band: neighbouring narrow bars repeat the same value
+    #[default]
+    Nearest,
+    /// Straight line between neighbouring bins; averages when downsampling
+    Linear,
+    /// Monotone cubic spline between bins (no overshoot); averages when downsampling
+    Cubic,
+}
+
+/// Wallpaper behavior while no audio is playing (`idle_mode`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleMode {
+    /// Stop drawing and leave the last frame on screen
+    #[default]
+    Freeze,
+    /// Fade everything out over `idle_fade_secs`, then leave the surface empty
+    Fadeout,
+    /// Clear the surface immediately
+    Clear,
+    /// Keep drawing the current style with a gentle synthetic signal
+    Demo,
+    /// Show the time (HH:MM) in the bitmap font
+    Clock,
+}
+
+/// Edge a custom style layer grows from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LayerAnchor {
+    #[default]
+    Bottom,
+    Center,
+    Top,
+}
+
+/// Audio measure that can drive a layer parameter
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BandSource {
+    /// Lowest third of the spectrum
+    Bass,
+    /// Middle third of the spectrum
+    Mid,
+    /// Highest third of the spectrum
+    Treble,
+    /// Average of all bands
+    Intensity,
+}
+
+/// One layer of a custom style (`[[styles.layers]]` entries)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleLayerDef {
+    pub kind: LayerKind,
+    /// Built-in style drawn by a `style` layer (e.g. "radial")
+    #[serde(default)]
+    pub style: Option<String>,
+    #[serde(default)]
+    pub blend: BlendMode,
+    /// Stacking position: lower orders are drawn first, ties keep file order
+    #[serde(default)]
+    pub order: i32,
+    #[serde(default)]
+    pub anchor: LayerAnchor,
+    /// Fixed hex color (e.g. "#FF00AA"); omit to use the active color scheme
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Layer opacity (0.0-1.0)
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// Height multiplier applied to the band levels
+    #[serde(default = "default_layer_scale")]
+    pub scale: f32,
+    /// Bar width, line thickness or particle size as a fraction of the bar width
+    /// (default: 1.0 for bars, 0.25 for lines, 0.5 for particles)
+    #[serde(default)]
+    pub width: Option<f32>,
+    /// Number of particles (particles layers only)
+    #[serde(default = "default_particle_count")]
+    pub count: usize,
+    /// Particle rise speed in layer heights per second (particles layers only)
+    #[serde(default = "default_particle_speed")]
+    pub speed: f32,
+    /// Multiply the layer opacity by this band's level
+    #[serde(default)]
+    pub opacity_from: Option<BandSource>,
+    /// Multiply the layer scale by (1 + this band's level)
+    #[serde(default)]
+    pub scale_from: Option<BandSource>,
+}
+
+fn default_layer_scale() -> f32 {
+    1.0
+}
+
+fn default_particle_count() -> usize {
+    64
+}
+
+fn default_particle_speed() -> f32 {
+    0.5
+}
+
+/// Declarative visualizer style (`[[styles]]` entries or files in the styles directory)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleDef {
+    pub name: String,
+    /// Layers drawn in order, later layers on top
+    pub layers: Vec<StyleLayerDef>,
+}
+
+/// Named bundle of settings (`[[themes]]` entries or files in the themes directory)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeDef {
+    /// Theme files default to their file name
+    #[serde(default)]
+    pub name: String,
+    /// Config sections to overlay, e.g. `visualizer = { style = "wave", bars = 96 }`
+    #[serde(flatten)]
+    pub settings: toml::Table,
+}
+
+/// Curated style + color combinations stepped through by `cavibe ctl cycle`
+/// (`[cycle]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct CycleConfig {
+    pub order: CycleOrder,
+    /// Without presets, `cycle` advances the style and color scheme together
+    pub presets: Vec<CyclePreset>,
+}
+
+/// How `cycle next` and auto-rotation pick what comes next
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CycleOrder {
+    /// In the order listed
+    #[default]
+    Sequential,
+    /// Random, never the current one again (`cycle` favours higher preset weights)
+    Shuffle,
+}
+
+/// One `[cycle]` combination; an omitted style or color keeps the current one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CyclePreset {
+    #[serde(default)]
+    pub style: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Relative chance of being picked in shuffle order (0 = never)
+    #[serde(default = "default_cycle_weight")]
+    pub weight: u32,
+}
+
+fn default_cycle_weight() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// Theme applied on top of the rest of the file at load
+    #[serde(default)]
+    pub theme: Option<String>,
+    pub display: DisplayConfig,
+    pub audio: AudioConfig,
+    pub visualizer: VisualizerConfig,
+    pub text: TextConfig,
+    #[serde(default)]
+    pub wallpaper: WallpaperConfig,
+    #[serde(default)]
+    pub color_schemes: Vec<ColorSchemeDef>,
+    #[serde(default)]
+    pub styles: Vec<StyleDef>,
+    #[serde(default)]
+    pub themes: Vec<ThemeDef>,
+    #[serde(default)]
+    pub cycle: CycleConfig,
+    /// Per-style overrides, keyed by style name
+    #[serde(default, rename = "style")]
+    pub style_overrides: BTreeMap<String, StyleOverride>,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub metadata: MetadataConfig,
+    #[serde(default)]
+    pub album_art: AlbumArtConfig,
+    #[serde(default)]
+    pub renderer: RendererConfig,
+    #[serde(default)]
+    pub source_indicator: SourceIndicatorConfig,
+    #[serde(default)]
+    pub web: WebConfig,
+    #[serde(default)]
+    pub leds: LedsConfig,
+    #[serde(default)]
+    pub ipc: IpcConfig,
+    #[serde(default)]
+    pub color: ColorAdjustConfig,
+    #[serde(default)]
+    pub color_dynamics: ColorDynamicsConfig,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub compositor: CompositorConfig,
+    #[serde(default)]
+    pub effects: EffectsConfig,
+    #[serde(default)]
+    pub stats: StatsConfig,
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+    #[serde(default)]
+    pub widgets: WidgetsConfig,
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    /// The settings before any theme was applied; every theme switch starts
+    /// from these so keys set by an earlier theme don't linger
+    #[serde(skip)]
+    theme_base: Option<Arc<Config>>,
+}
+
+/// Bar-level effects (`[effects]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct EffectsConfig {
+    pub sparks: SparksConfig,
+}
+
+/// Sparks at the tips of bars that jump on transients (`[effects.sparks]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SparksConfig {
+    pub enabled: bool,
+    /// Rise in one frame (fraction of full height) that triggers a spark
+    pub threshold: f32,
+    /// Spark brightness (0.0-1.0)
+    pub intensity: f32,
+    /// Seconds a spark takes to fade out
+    pub decay: f32,
+}
+
+impl Default for SparksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.15,
+            intensity: 1.0,
+            decay: 0.15,
+        }
+    }
+}
+
+/// Pixel renderer settings (`[renderer]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RendererConfig {
+    /// Internal render scale: 2.0 renders at 2× and downsamples (smoother),
+    /// 0.5 renders at half resolution and upscales (faster). Range 0.25-4.0.
+    #[serde(alias = "render_scale")]
+    pub supersample: f32,
+    /// Pixels per unit of `bar_width`, `bar_spacing` and segment sizes in
+    /// wallpaper and window mode (terminal mode uses the cell size)
+    pub pixel_scale: u32,
+    /// How frames rendered below output resolution are scaled up
+    pub upscale: UpscaleFilter,
+    /// Post-processing effects applied in order to every frame
+    pub effects: Vec<Effect>,
+    /// What draws wallpaper frames
+    pub backend: RenderBackend,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            supersample: 1.0,
+            pixel_scale: 8,
+            upscale: UpscaleFilter::default(),
+            effects: Vec::new(),
+            backend: RenderBackend::default(),
+        }
+    }
+}
+
+/// What draws wallpaper frames (`renderer.backend`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderBackend {
+    /// The pixel renderer into shared-memory buffers
+    #[default]
+    Cpu,
+    /// wgpu (Vulkan or OpenGL) for the styles it can draw; needs a build
+    /// with the `gpu` feature, Wayland only
+    Gpu,
+}
+
+/// Upscaling of frames rendered at reduced resolution (`supersample` < 1)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpscaleFilter {
+    /// Repeat pixels: cheapest, blocky edges
+    #[default]
+    Nearest,
+    /// Blend neighbouring pixels: smooth edges, a little more work
+    Bilinear,
+    /// Hand the small buffer to the compositor to scale (wp_viewporter), which
+    /// also shrinks the buffers; wallpaper mode only, bilinear elsewhere
+    Compositor,
+}
+
+/// Post-processing effect applied to the finished frame
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Effect {
+    /// Bloom around bright pixels
+    Glow,
+    /// Darken every other row
+    Scanlines,
+    /// Offset red and blue channels
+    ChromaticAberration,
+    /// Darken toward the corners
+    Vignette,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 4] = [Effect::Glow, Effect::Scanlines, Effect::ChromaticAberration, Effect::Vignette];
+
+    /// Get the display name
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Glow => "glow",
+            Effect::Scanlines => "scanlines",
+            Effect::ChromaticAberration => "chromatic-aberration",
+            Effect::Vignette => "vignette",
+        }
+    }
+
+    /// Parse from string
+    pub fn from_name(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.name().eq_ignore_ascii_case(s))
+    }
+}
+
+/// What privacy mode hides
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrivacyHide {
+    /// Track title, artist and album art; bars keep running
+    #[default]
+    Text,
+    /// The whole visualizer
+    All,
+}
+
+/// Privacy mode settings (`[privacy]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Enter privacy mode automatically while the screen is shared
+    /// (detected from PipeWire screencast streams)
+    pub hide_text_on_screenshare: bool,
+    /// What privacy mode hides: text or all
+    pub hide: PrivacyHide,
+}
+
+/// Per-workspace behavior from the compositor's IPC (`[compositor]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct CompositorConfig {
+    /// Follow the focused workspace and window (Hyprland or Sway)
+    pub enabled: bool,
+    /// Rules checked against the focused workspace and window; the first
+    /// matching rule is in effect
+    pub rules: Vec<CompositorRule>,
+}
+
+/// What to do while a workspace or app is focused. Each given field must
+/// match; a rule without any matches everywhere.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct CompositorRule {
+    /// Workspace name (exact, e.g. "1" or "music")
+    pub workspace: Option<String>,
+    /// Substring of the focused window's class / app_id (case-insensitive)
+    pub app: Option<String>,
+    /// Hide the visualizer
+    pub hide: bool,
+    /// Freeze the current frame and stop rendering
+    pub pause: bool,
+    /// Switch to this style when the rule comes into effect
+    pub style: Option<String>,
+}
+
+/// Wallpaper main-loop pacing (`[performance]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerformanceConfig {
+    /// Loop interval while no audio is playing (nothing is rendered)
+    pub idle_poll_ms: u64,
+    /// Loop interval while audio is playing and frames are rendered
+    pub active_poll_ms: u64,
+    /// Intensity (0.0-1.0) the audio must exceed to count as playing; raise it
+    /// to ignore faint system sounds
+    pub idle_threshold: f32,
+    /// Keep rendering this long after the audio goes quiet, so sources with
+    /// brief dropouts don't flip between idle and active
+    #[serde(alias = "min_active_secs")]
+    pub idle_timeout_secs: f32,
+}
+
+impl PerformanceConfig {
+    /// Reset values the render loop can't use (NaN, infinite or negative)
+    fn sanitize(&mut self) {
+        let defaults = Self::default();
+        if !self.idle_threshold.is_finite() {
+            self.idle_threshold = defaults.idle_threshold;
+        }
+        if !self.idle_timeout_secs.is_finite() || self.idle_timeout_secs < 0.0 {
+            self.idle_timeout_secs = defaults.idle_timeout_secs;
+        }
+    }
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            idle_poll_ms: 50,
+            active_poll_ms: 4,
+            idle_threshold: 0.001,
+            idle_timeout_secs: 0.0,
+        }
+    }
+}
+
+/// Terminal mode drawing (`[terminal]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Characters `display.terminal_graphics = "blocks"` turns the pixel
+    /// canvas into, also used when kitty or sixel fall back to blocks
+    #[serde(alias = "render_mode")]
+    pub blocks: TerminalBlocks,
+}
+
+/// Characters terminal mode draws the canvas with when not sending images
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalBlocks {
+    /// Upper half blocks, two pixels per cell with a color each
+    #[default]
+    Halfblock,
+    /// Braille dots, 2×4 pixels per cell sharing one color
+    Braille,
+    /// Full blocks, one pixel per cell
+    Block,
+}
+
+/// Local usage statistics (`[stats]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Record run time and time spent in each style and color scheme
+    /// (stored locally, see `cavibe stats summary`)
+    pub enabled: bool,
+}
+
+/// Global adjustments applied to every color after scheme lookup (`[color]` section)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ColorAdjustConfig {
+    /// Value multiplier (HSV), 0.0-3.0
+    pub brightness: f32,
+    /// Saturation multiplier (HSV), 0.0-3.0
+    pub saturation: f32,
+    /// Gamma exponent, 0.1-5.0 (>1.0 lifts dark tones)
+    pub gamma: f32,
+}
+
+impl Default for ColorAdjustConfig {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            saturation: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+/// Colors reacting to the music (`[color_dynamics]`), applied on top of `[color]`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ColorDynamicsConfig {
+    /// Hue rotation in degrees per second at full intensity (0 = off)
+    pub hue_speed: f32,
+    /// Hue shift in degrees while the bass is at full strength (0 = off)
+    pub bass_hue_shift: f32,
+    /// Brightness boost on a beat (0.5 = +50%), fading over `flash_decay_secs`
+    pub beat_flash: f32,
+    pub flash_decay_secs: f32,
+    /// Switch to the next color scheme every N beats (0 = off)
+    pub switch_every_beats: u32,
+    /// Beat = intensity above its recent average × this
+    pub beat_sensitivity: f32,
+}
+
+impl Default for ColorDynamicsConfig {
+    fn default() -> Self {
+        Self {
+            hue_speed: 0.0,
+            bass_hue_shift: 0.0,
+            beat_flash: 0.0,
+            flash_decay_secs: 0.3,
+            switch_every_beats: 0,
+            beat_sensitivity: 1.5,
+        }
+    }
+}
+
+impl ColorAdjustConfig {
+    /// Names accepted by [`ColorAdjustConfig::set`]
+    pub const PROPERTIES: &'static [&'static str] = &["brightness", "saturation", "gamma"];
+
+    /// Reset adjustments that aren't finite numbers
+    fn sanitize(&mut self) {
+        let defaults = Self::default();
+        for (field, default) in [
+            (&mut self.brightness, defaults.brightness),
+            (&mut self.saturation, defaults.saturation),
+            (&mut self.gamma, defaults.gamma),
+        ] {
+            if !field.is_finite() {
+                *field = default;
+            }
+        }
+    }
+
+    /// Current value of an adjustment by name
+    pub fn get(&self, property: &str) -> Option<f32> {
+        match property {
+            "brightness" => Some(self.brightness),
+            "saturation" => Some(self.saturation),
+            "gamma" => Some(self.gamma),
+            _ => None,
+        }
+    }
+
+    /// Set one adjustment by name, clamped to its range. Returns the stored value.
+    pub fn set(&mut self, property: &str, value: f32) -> Result<f32, String> {
+        if !value.is_finite() {
+            return Err(format!("invalid {} value {}", property, value));
+        }
+        let (field, min, max) = match property {
+            "brightness" => (&mut self.brightness, 0.0, 3.0),
+            "saturation" => (&mut self.saturation, 0.0, 3.0),
+            "gamma" => (&mut self.gamma, 0.1, 5.0),
+            _ => {
+                return Err(format!(
+                    "unknown color adjustment '{}' ({})",
+                    property,
+                    Self::PROPERTIES.join(", ")
+                ))
+            }
+        };
+        *field = value.clamp(min, max);
+        Ok(*field)
+    }
+}
+
+/// Where album art is drawn on the pixel canvas
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlbumArtPlacement {
+    /// Thumbnail in the top-right corner
+    #[default]
+    Corner,
+    /// Blurred, cover-scaled behind the visualizer
+    Background,
+    /// Square thumbnail to the left of the track text
+    BesideText,
+}
+
+/// MPRIS track info source (`[metadata]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct MetadataConfig {
+    /// Only read this player: identity or bus name ("spotify"), case-insensitive,
+    /// glob-style: `*` matches anything ("firefox*"), nothing else is special
+    /// (not a regex). Unset follows the active player.
+    pub player: Option<String>,
+    /// Color scheme and style picked by the track's metadata; the first matching rule wins
+    pub rules: Vec<MetadataRule>,
+}
+
+/// Color scheme and/or style for tracks whose metadata matches, applied when
+/// a new track starts. Each given field must occur in the track's
+/// (case-insensitive); a rule without any matches every track.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataRule {
+    #[serde(default)]
+    pub genre: Option<String>,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub album: Option<String>,
+    #[serde(default)]
+    pub color_scheme: Option<ColorScheme>,
+    #[serde(default)]
+    pub style: Option<String>,
+}
+
+/// MPRIS album art display (`[album_art]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlbumArtConfig {
+    pub enabled: bool,
+    pub placement: AlbumArtPlacement,
+    /// Art opacity (0.0-1.0)
+    pub opacity: f32,
+    /// Corner thumbnail size as a fraction of the canvas height
+    pub size: f32,
+    /// Blur strength for the background placement (0 = sharp)
+    pub blur: u32,
+}
+
+impl Default for AlbumArtConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            placement: AlbumArtPlacement::Corner,
+            opacity: 0.8,
+            size: 0.25,
+            blur: 8,
+        }
+    }
+}
+
+/// Canvas corner for small overlays
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverlayCorner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Small overlays drawn by every backend (`[widgets]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WidgetsConfig {
+    pub intensity_bar: IntensityBarConfig,
+    pub osd: OsdConfig,
+}
+
+/// On-screen confirmation of runtime changes in wallpaper mode (`[widgets.osd]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OsdConfig {
+    pub enabled: bool,
+    /// Seconds a message stays up before fading out
+    pub duration_secs: f32,
+}
+
+impl Default for OsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_secs: 2.0,
+        }
+    }
+}
+
+/// Where the intensity bar is drawn
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IntensityBarPosition {
+    /// Just under the track text (the bottom edge when text is hidden)
+    #[default]
+    BelowText,
+    Top,
+    Bottom,
+}
+
+/// Audio intensity meter (`[widgets.intensity_bar]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntensityBarConfig {
+    pub enabled: bool,
+    /// Thickness in pixels
+    pub height: usize,
+    pub position: IntensityBarPosition,
+    /// Color scheme for the bar (None = the visualizer's scheme)
+    pub scheme: Option<ColorScheme>,
+}
+
+impl Default for IntensityBarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            height: 4,
+            position: IntensityBarPosition::default(),
+            scheme: None,
+        }
+    }
+}
+
+/// Active audio source name and input level overlay (`[source_indicator]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceIndicatorConfig {
+    pub enabled: bool,
+    /// Corner of the surface to draw in (wallpaper mode; terminal mode uses the status bar)
+    pub position: OverlayCorner,
+    /// Overlay opacity (0.0-1.0)
+    pub opacity: f32,
+}
+
+impl Default for SourceIndicatorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            position: OverlayCorner::TopLeft,
+            opacity: 0.8,
+        }
+    }
+}
+
+/// Web server settings (`[web]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebConfig {
+    pub enabled: bool,
+    /// Address to listen on (host:port)
+    pub bind: String,
+    /// Frames per second sent to `/stream` WebSocket clients
+    pub stream_fps: u32,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1:9470".to_string(),
+            stream_fps: 30,
+        }
+    }
+}
+
+/// RGB peripherals lit from the spectrum (`[leds]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LedsConfig {
+    pub enabled: bool,
+    /// Updates sent to each device per second (1-120)
+    pub fps: u32,
+    /// How the spectrum maps onto the LEDs, unless a device picks its own
+    pub mode: LedMode,
+    /// Overall brightness (0.0-1.0)
+    pub brightness: f32,
+    /// Colors for the LEDs (default: the visualizer's color scheme at startup)
+    pub color_scheme: Option<ColorScheme>,
+    pub devices: Vec<LedDevice>,
+}
+
+impl Default for LedsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fps: 30,
+            mode: LedMode::default(),
+            brightness: 1.0,
+            color_scheme: None,
+            devices: Vec::new(),
+        }
+    }
+}
+
+/// Mapping of the spectrum onto a strip of LEDs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LedMode {
+    /// Bass to treble along the strip, each LED as bright as its band
+    #[default]
+    Spectrum,
+    /// Every LED in the color of the loudest band, pulsing with the intensity
+    Pulse,
+}
+
+/// Protocol spoken to an LED device
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LedDeviceKind {
+    /// An OpenRGB SDK server (TCP, port 6742)
+    OpenRgb,
+    /// WLED realtime UDP (DNRGB, port 21324)
+    Wled,
+    /// Adalight over a serial device (baud rate set beforehand, e.g. with stty)
+    Adalight,
+}
+
+/// One `[[leds.devices]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedDevice {
+    pub kind: LedDeviceKind,
+    /// host:port for OpenRGB (default 127.0.0.1:6742) and WLED (port
+    /// defaults to 21324), or the serial device path for Adalight
+    #[serde(default)]
+    pub address: Option<String>,
+    /// Number of LEDs to drive
+    pub leds: usize,
+    /// OpenRGB controller index (see the OpenRGB device list)
+    #[serde(default)]
+    pub controller: u32,
+    /// Mapping for this device (default: `[leds] mode`)
+    #[serde(default)]
+    pub mode: Option<LedMode>,
+    /// Run the strip the other way round
+    #[serde(default)]
+    pub reverse: bool,
+}
+
+/// Remote control settings (`[ipc]` section)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct IpcConfig {
+    /// Also accept `cavibe ctl` commands over TCP/WebSocket on this host:port
+    pub listen: Option<String>,
+    /// Shared secret remote clients send first (`auth <token>`); required
+    /// for the listener to start
+    pub token: Option<String>,
+}
+
+/// Shell commands run on visualizer events (`[hooks]` section)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub on_track_change: Option<String>,
+    pub on_style_change: Option<String>,
+    pub on_color_change: Option<String>,
+    pub on_beat: Option<String>,
+    /// Minimum time between `on_beat` runs
+    pub beat_min_interval_ms: u64,
+    /// How far above the recent average intensity counts as a beat
+    pub beat_sensitivity: f32,
+    /// Built-in reactions to a new track
+    pub track_change: TrackChangeConfig,
+}
+
+/// What cavibe itself does when MPRIS reports a new track
+/// (`[hooks.track_change]`, alongside the `on_track_change` command)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackChangeConfig {
+    /// Seconds the visualizer flashes white for (0 = no flash)
+    pub flash: f32,
+    /// Seconds the new title fades in over (0 = shown at once)
+    pub title_fade_in: f32,
+    /// Advance to the next color scheme
+    pub next_color: bool,
+}
+
+impl Default for TrackChangeConfig {
+    fn default() -> Self {
+        Self {
+            flash: 0.0,
+            title_fade_in: 0.0,
+            next_color: false,
+        }
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_track_change: None,
+            on_style_change: None,
+            on_color_change: None,
+            on_beat: None,
+            beat_min_interval_ms: 250,
+            beat_sensitivity: 1.5,
+            track_change: TrackChangeConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    pub mode: DisplayMode,
+    pub rotate_styles: bool,
+    pub rotation_interval_secs: u64,
+    /// Order auto-rotation steps through: "sequential" or "shuffle"
+    #[serde(default)]
+    pub rotate_order: CycleOrder,
+    /// Terminal mode: show the now-playing info panel beside the visualizer
+    #[serde(default)]
+    pub info_panel: bool,
+    /// Width of the terminal info panel in columns
+    #[serde(default = "default_info_panel_width")]
+    pub info_panel_width: u16,
+    /// Terminal mode: how the visualizer is drawn (half-blocks, or pixels
+    /// through a terminal graphics protocol)
+    #[serde(default)]
+    pub terminal_graphics: TerminalGraphics,
+    /// Window mode: initial window size in pixels
+    #[serde(default = "default_window_width")]
+    pub window_width: u32,
+    #[serde(default = "default_window_height")]
+    pub window_height: u32,
+}
+
+fn default_window_width() -> u32 {
+    960
+}
+
+fn default_window_height() -> u32 {
+    540
+}
+
+/// How terminal mode draws the visualizer
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalGraphics {
+    /// Half-block characters, two pixels per cell; works everywhere
+    #[default]
+    Blocks,
+    /// Kitty or sixel if the terminal looks like it supports one, else blocks
+    Auto,
+    /// Full-resolution images over the kitty graphics protocol
+    Kitty,
+    /// Full-resolution images as sixels
+    Sixel,
+}
+
+fn default_info_panel_width() -> u16 {
+    34
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioConfig {
+    pub device: Option<String>,
+    /// Capture only this application's audio (name or binary, e.g. "spotify")
+    /// instead of the whole sink; takes precedence over `device`
+    #[serde(default)]
+    pub app: Option<String>,
+    pub sample_rate: u32,
+    pub buffer_size: usize,
+    pub smoothing: f32,
+    pub sensitivity: Sensitivity,
+    /// How quickly bars rise toward a louder value (0.0-1.0, 1.0 = snap instantly).
+    /// Falls back to `1.0 - smoothing` when unset.
+    #[serde(default)]
+    pub rise_speed: Option<f32>,
+    /// How quickly bars fall toward a quieter value (0.0-1.0).
+    /// Falls back to `1.0 - smoothing` when unset. Ignored when `gravity` is set.
+    #[serde(default)]
+    pub fall_speed: Option<f32>,
+    /// Cava-style gravity: bars fall with accelerating speed (bar heights per second²).
+    /// 0.0 disables gravity and uses `fall_speed` instead.
+    #[serde(default)]
+    pub gravity: f32,
+    /// Adapt gain to the session's loudness so quiet and loud music fill the
+    /// display similarly. `sensitivity` then acts as exposure compensation.
+    #[serde(default)]
+    pub auto_exposure: bool,
+    /// How quickly auto-exposure adapts (per second, higher = faster)
+    #[serde(default = "default_auto_exposure_speed")]
+    pub auto_exposure_speed: f32,
+    /// Fraction of recent magnitudes kept below full bar height (0.5-1.0)
+    #[serde(default = "default_auto_exposure_clip")]
+    pub auto_exposure_clip: f32,
+    /// `sensitivity = "auto"`: seconds for the gain to settle when the music
+    /// gets louder
+    #[serde(default = "default_agc_attack_secs")]
+    pub agc_attack_secs: f32,
+    /// `sensitivity = "auto"`: seconds for the gain to settle when the music
+    /// gets quieter
+    #[serde(default = "default_agc_release_secs")]
+    pub agc_release_secs: f32,
+    /// Generate synthetic music instead of capturing audio
+    #[serde(default)]
+    pub demo: bool,
+    /// Multi-resolution FFT: a long window for bass below 250 Hz (stable
+    /// pitch) and a short one above it (tight transients)
+    #[serde(default)]
+    pub multires: bool,
+    /// Length of the waveform shown by oscilloscope styles, in samples
+    /// (independent of the capture block size)
+    #[serde(default = "default_waveform_samples")]
+    pub waveform_samples: usize,
+    /// Psychoacoustic weighting applied to the bands
+    #[serde(default)]
+    pub weighting: Weighting,
+    /// Resampling of FFT bins into bars and of bands into displayed bars
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    /// Per-band EQ in dB, spread evenly from bass to treble and interpolated
+    /// between points (applied after `weighting`)
+    #[serde(default)]
+    pub eq: Vec<f32>,
+    /// Shift of the bars against the captured audio in ms: positive delays
+    /// them (e.g. for Bluetooth output), negative analyzes more often so they
+    /// react sooner
+    #[serde(default)]
+    pub delay_ms: i32,
+    /// Bands quieter than this, in dB relative to a full-height bar, are
+    /// drawn empty
+    #[serde(default)]
+    pub noise_floor_db: Option<f32>,
+    /// Bass/mid crossover for the band levels styles react to, in Hz
+    #[serde(default = "default_bass_crossover_hz")]
+    pub bass_crossover_hz: f32,
+    /// Mid/treble crossover for the band levels, in Hz
+    #[serde(default = "default_treble_crossover_hz")]
+    pub treble_crossover_hz: f32,
+}
+
+fn default_bass_crossover_hz() -> f32 {
+    250.0
+}
+
+fn default_treble_crossover_hz() -> f32 {
+    4000.0
+}
+
+fn default_auto_exposure_speed() -> f32 {
+    0.5
+}
+
+fn default_auto_exposure_clip() -> f32 {
+    0.95
+}
+
+fn default_agc_attack_secs() -> f32 {
+    0.1
+}
+
+fn default_agc_release_secs() -> f32 {
+    3.0
+}
+
+fn default_waveform_samples() -> usize {
+    2048
+}
+
+impl AudioConfig {
+    /// Valid range of `sensitivity`
+    pub const SENSITIVITY_RANGE: (f32, f32) = (0.1, 10.0);
+
+    /// Device for the default pipeline: `app:<name>` when `app` is set, else `device`
+    pub fn default_device(&self) -> Option<String> {
+        match &self.app {
+            Some(app) => Some(format!("{}{}", crate::audio::APP_PREFIX, app)),
+            None => self.device.clone(),
+        }
+    }
+
+    /// Effective per-frame rise coefficient
+    pub fn effective_rise_speed(&self) -> f32 {
+        self.rise_speed.unwrap_or(1.0 - self.smoothing).clamp(0.0, 1.0)
+    }
+
+    /// Effective per-frame fall coefficient
+    pub fn effective_fall_speed(&self) -> f32 {
+        self.fall_speed.unwrap_or(1.0 - self.smoothing).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisualizerConfig {
+    pub bars: usize,
+    pub color_scheme: ColorScheme,
+    #[serde(default)]
+    pub style: Option<String>,
+    pub bar_width: u16,
+    pub bar_spacing: u16,
+    pub mirror: bool,
+    /// When true with mirror, reverses the pattern: lows meet in middle, highs on outside
+    #[serde(default)]
+    pub reverse_mirror: bool,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// Height quiet bars never drop below, in pixels (`4`) or percent of the
+    /// bar area (`"2%"`); 0 lets them vanish
+    #[serde(default)]
+    pub min_bar_height: CoordValue,
+    /// Rounded bar ends (classic and mirrored bars)
+    #[serde(default)]
+    pub bar_rounding: BarRounding,
+    /// Extra spacing after every N bars ("equalizer rack" segments)
+    #[serde(default)]
+    pub group: BarGroup,
+    /// Split bars into LED-style blocks
+    #[serde(default)]
+    pub segments: SegmentsConfig,
+    /// Screen edge the bars grow from
+    #[serde(default)]
+    pub orientation: Orientation,
+    /// Mirror the finished frame left to right
+    #[serde(default)]
+    pub flip_horizontal: bool,
+    /// Mirror the finished frame top to bottom
+    #[serde(default)]
+    pub flip_vertical: bool,
+    /// Turn the finished frame clockwise (applied before the flips)
+    #[serde(default)]
+    pub rotation: Rotation,
+    /// Reflect the bar field into mirrored halves or quarters
+    #[serde(default)]
+    pub symmetry: Symmetry,
+    /// What the wallpaper shows once the audio goes quiet
+    #[serde(default)]
+    pub idle_mode: IdleMode,
+    /// Length of the `fadeout` idle mode in seconds
+    #[serde(default = "default_idle_fade_secs")]
+    pub idle_fade_secs: f32,
+    /// Go idle as soon as the MPRIS player pauses, instead of rendering
+    /// leftover noise until the audio falls below `idle_threshold`
+    #[serde(default)]
+    pub pause_on_media_pause: bool,
+    #[serde(default)]
+    pub spectrogram: SpectrogramConfig,
+    #[serde(default)]
+    pub radial: RadialConfig,
+    #[serde(default)]
+    pub oscilloscope: OscilloscopeConfig,
+    #[serde(default)]
+    pub particles: ParticlesConfig,
+    #[serde(default)]
+    pub seismograph: SeismographConfig,
+    #[serde(default)]
+    pub fire: FireConfig,
+    #[serde(default)]
+    pub vectorscope: VectorscopeConfig,
+    #[serde(default)]
+    pub vu: VuConfig,
+    #[serde(default)]
+    pub karaoke: KaraokeConfig,
+}
+
+/// Visualizer settings for one style (`[style.<name>]`), used while it is shown
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StyleOverride {
+    pub bars: Option<usize>,
+    pub bar_width: Option<u16>,
+    pub bar_spacing: Option<u16>,
+    pub mirror: Option<bool>,
+    /// Multiplies bar heights on top of `audio.sensitivity`
+    pub sensitivity: Option<f32>,
+    /// `[style.radial]` / `[style.oscilloscope]` spelling of
+    /// `visualizer.<style>.wide_mode`, moved there on load. Left here only
+    /// when it isn't valid for the style, for `cavibe check` to report.
+    pub wide_mode: Option<String>,
+}
+
+/// Overlay `patch` onto `base`: tables merge key by key, other values replace.
+fn merge_toml(base: &mut toml::Value, patch: &toml::Value) {
+    match (base, patch) {
+        (toml::Value::Table(base), toml::Value::Table(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch.clone(),
+    }
+}
+
+/// Parse `value` as the same TOML type as `existing` (any type when the key is unset).
+fn typed_value(existing: Option<&toml::Value>, key: &str, value: &str) -> Result<toml::Value, String> {
+    Ok(match existing {
+        Some(toml::Value::Boolean(_)) => value
+            .parse()
+            .map(toml::Value::Boolean)
+            .map_err(|_| format!("expected true or false for '{}'", key))?,
+        Some(toml::Value::Integer(_)) => value
+            .parse()
+            .map(toml::Value::Integer)
+            .map_err(|_| format!("expected an integer for '{}'", key))?,
+        Some(toml::Value::Float(_)) => value
+            .parse()
+            .map(toml::Value::Float)
+            .map_err(|_| format!("expected a number for '{}'", key))?,
+        Some(toml::Value::String(_)) => toml::Value::String(value.to_string()),
+        Some(_) => {
+            return Err(format!("'{}' is a section or list, set its fields individually", key));
+        }
+        None => format!("v = {}", value)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut t| t.remove("v"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string())),
+    })
+}
+
+impl StyleLayerDef {
+    /// A layer of `kind` ("bars", "line", "particles" or "style") with default settings
+    pub fn from_kind(kind: &str, style: Option<&str>) -> Result<Self, String> {
+        let mut table = toml::Table::new();
+        table.insert("kind".to_string(), toml::Value::String(kind.to_string()));
+        if let Some(style) = style {
+            table.insert("style".to_string(), toml::Value::String(style.to_string()));
+        }
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| e.message().to_string())
+    }
+
+    /// Set one field by name, parsing `value` like `Config::set_key`
+    pub fn set_key(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let mut root = toml::Value::try_from(&*self).map_err(|e| e.to_string())?;
+        let table = root.as_table_mut().ok_or("layer is not a table")?;
+        let new_value = typed_value(table.get(key), key, value)?;
+        table.insert(key.to_string(), new_value);
+        *self = root.try_into().map_err(|e: toml::de::Error| e.message().to_string())?;
+        Ok(())
+    }
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+impl VisualizerConfig {
+    /// Reset values the renderer can't use (NaN, infinite or negative)
+    fn sanitize(&mut self) {
+        if !self.opacity.is_finite() {
+            self.opacity = default_opacity();
+        }
+        if !self.idle_fade_secs.is_finite() || self.idle_fade_secs < 0.0 {
+            self.idle_fade_secs = default_idle_fade_secs();
+        }
+    }
+}
+
+fn default_idle_fade_secs() -> f32 {
+    2.0
+}
+
+/// Bar grouping: insert `gap` extra spacing after every `size` bars (0 = disabled)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BarGroup {
+    pub size: usize,
+    pub gap: u16,
+}
+
+impl Default for BarGroup {
+    fn default() -> Self {
+        Self { size: 0, gap: 2 }
+    }
+}
+
+/// LED-style bar segments: blocks of `height` separated by `gap` (same units as `bar_width`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SegmentsConfig {
+    pub enabled: bool,
+    pub height: u16,
+    pub gap: u16,
+    /// Fixed colors by height instead of the color scheme gradient (empty = gradient)
+    pub zones: Vec<SegmentZone>,
+}
+
+impl Default for SegmentsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            height: 2,
+            gap: 1,
+            zones: Vec::new(),
+        }
+    }
+}
+
+/// Color for the segments starting at or above `from` (fraction of the full bar height)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SegmentZone {
+    pub from: f32,
+    pub color: String,
+}
+
+/// Spectrogram (waterfall) smoothing and heatmap tuning
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SpectrogramConfig {
+    /// Blend each new row with the previous one (0.0 = raw, 0.9 = very smooth)
+    pub temporal_smoothing: f32,
+    /// Weights convolved across neighbouring frequencies (e.g. [1, 2, 1]; empty = off)
+    pub frequency_kernel: Vec<f32>,
+    /// Magnitude multiplier applied before coloring
+    pub gain: f32,
+    /// Exponent applied to magnitudes (>1.0 darkens quiet areas, <1.0 brightens them)
+    pub contrast: f32,
+}
+
+impl Default for SpectrogramConfig {
+    fn default() -> Self {
+        Self {
+            temporal_smoothing: 0.0,
+            frequency_kernel: Vec::new(),
+            gain: 1.0,
+            contrast: 1.0,
+        }
+    }
+}
+
+/// Screen edge the bars grow from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Bottom,
+    Top,
+    /// Bars grow rightward from the left edge, low frequencies at the top
+    Left,
+    /// Bars grow leftward from the right edge, low frequencies at the top
+    Right,
+}
+
+impl Orientation {
+    /// Whether bars grow horizontally (the frequency axis is vertical)
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Orientation::Left | Orientation::Right)
+    }
+}
+
+/// Shape of the bar ends (`visualizer.bar_rounding`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BarRounding {
+    /// Square ends
+    #[default]
+    None,
+    /// Tips rounded into a semicircle as wide as the bar
+    Top,
+    /// Both ends rounded
+    Pill,
+}
+
+/// Geometric reflection of the drawn bars (`visualizer.symmetry`), unlike
+/// `mirror`, which reorders the frequencies
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Left half as drawn, right half its mirror image
+    Horizontal,
+    /// Top half as drawn, bottom half its mirror image
+    Vertical,
+    /// Top-left quarter as drawn, reflected into the other three
+    Quad,
+}
+
+impl Symmetry {
+    /// Whether the bars are reflected left to right
+    pub fn reflects_x(self) -> bool {
+        matches!(self, Symmetry::Horizontal | Symmetry::Quad)
+    }
+
+    /// Whether the bars are reflected top to bottom
+    pub fn reflects_y(self) -> bool {
+        matches!(self, Symmetry::Vertical | Symmetry::Quad)
+    }
+}
+
+/// Clockwise turn of the whole frame (`visualizer.rotation`), written as
+/// degrees in the config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(try_from = "u16", into = "u16")]
+pub enum Rotation {
+    #[default]
+    None,
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+impl Rotation {
+    /// Whether the frame is turned on its side (width and height swap)
+    pub fn is_sideways(self) -> bool {
+        matches!(self, Rotation::Quarter | Rotation::ThreeQuarters)
+    }
+}
+
+impl TryFrom<u16> for Rotation {
+    type Error = String;
+
+    fn try_from(degrees: u16) -> Result<Self, Self::Error> {
+        match degrees {
+            0 => Ok(Rotation::None),
+            90 => Ok(Rotation::Quarter),
+            180 => Ok(Rotation::Half),
+            270 => Ok(Rotation::ThreeQuarters),
+            _ => Err(format!("Invalid rotation {}: expected 0, 90, 180 or 270", degrees)),
+        }
+    }
+}
+
+impl From<Rotation> for u16 {
+    fn from(rotation: Rotation) -> u16 {
+        match rotation {
+            Rotation::None => 0,
+            Rotation::Quarter => 90,
+            Rotation::Half => 180,
+            Rotation::ThreeQuarters => 270,
+        }
+    }
+}
+
+/// How the radial style uses surfaces wider than 16:9
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RadialWideMode {
+    /// One centered circle
+    #[default]
+    Single,
+    /// One circle per 16:9 slice of the surface, side by side
+    Rings,
+    /// Stretch the circle into an ellipse filling the surface
+    Ellipse,
+}
+
+/// Radial style tuning (`[visualizer.radial]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct RadialConfig {
+    pub wide_mode: RadialWideMode,
+}
+
+/// How the oscilloscope style uses surfaces wider than 16:9
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OscilloscopeWideMode {
+    /// Spread the whole waveform across the full width
+    #[default]
+    Stretch,
+    /// Draw the whole waveform once per 16:9 slice of the surface
+    Repeat,
+    /// Show the middle of the waveform magnified to the full width
+    Zoom,
+}
+
+/// Oscilloscope style tuning (`[visualizer.oscilloscope]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct OscilloscopeConfig {
+    pub wide_mode: OscilloscopeWideMode,
+}
+
+/// Particles style tuning (`[visualizer.particles]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ParticlesConfig {
+    /// Particles spawned by a full-strength bass hit
+    pub count: usize,
+    /// Downward acceleration in bar-area heights per second²
+    pub gravity: f32,
+    /// Seconds a particle lives before it has fully faded
+    pub lifetime: f32,
+}
+
+impl Default for ParticlesConfig {
+    fn default() -> Self {
+        Self {
+            count: 48,
+            gravity: 1.5,
+            lifetime: 1.5,
+        }
+    }
+}
+
+/// Fire style tuning (`[visualizer.fire]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FireConfig {
+    /// How high the flames of a full-strength band reach, as a fraction of the height
+    pub height: f32,
+    /// Random variation of the flames (0.0 = steady plumes, 1.0 = wild)
+    pub flicker: f32,
+    /// Terminal mode: draw the flames with shade characters (░▒▓█) instead of
+    /// half-block pixels
+    pub terminal_blocks: bool,
+}
+
+impl Default for FireConfig {
+    fn default() -> Self {
+        Self {
+            height: 0.6,
+            flicker: 0.5,
+            terminal_blocks: true,
+        }
+    }
+}
+
+/// Vectorscope style tuning (`[visualizer.vectorscope]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VectorscopeConfig {
+    /// Seconds until a trace has faded to a tenth of its brightness
+    pub persistence: f32,
+    /// Turn the figure 45° so mono is vertical and out-of-phase horizontal
+    /// (goniometer view); off plots left against right on the axes
+    pub rotate: bool,
+    /// Continuous rotation of the figure in degrees per second (0 = still)
+    pub spin: f32,
+    /// Scale of the figure relative to full-scale samples
+    pub gain: f32,
+    /// Draw a stereo correlation meter (-1 to +1) below the figure
+    pub correlation: bool,
+}
+
+impl Default for VectorscopeConfig {
+    fn default() -> Self {
+        Self {
+            persistence: 0.3,
+            rotate: true,
+            spin: 0.0,
+            gain: 1.0,
+            correlation: true,
+        }
+    }
+}
+
+/// VU Meters style tuning (`[visualizer.vu]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VuConfig {
+    /// One meter per channel; off shows a single meter for the mix
+    pub stereo: bool,
+    /// RMS level in dBFS (after `audio.sensitivity`) that reads 0 VU
+    pub reference_db: f32,
+    /// Time the needle takes to settle on a new level, in milliseconds (300 on a real VU meter)
+    pub response_ms: u32,
+    /// Sample peak in dBFS that lights the peak LED
+    pub peak_db: f32,
+}
+
+impl Default for VuConfig {
+    fn default() -> Self {
+        Self {
+            stereo: true,
+            reference_db: -12.0,
+            response_ms: 300,
+            peak_db: -1.0,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DisplayConfig {
-    pub mode: DisplayMode,
-    pub rotate_styles: bool,
-    pub rotation_interval_secs: u64,
+/// Karaoke style tuning (`[visualizer.karaoke]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct KaraokeConfig {
+    /// How much a letter grows when its band is at full strength (0.35 = 35% larger)
+    pub pulse: f32,
+    /// Show the artist in smaller letters below the title
+    pub artist: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AudioConfig {
-    pub device: Option<String>,
-    pub sample_rate: u32,
-    pub buffer_size: usize,
-    pub smoothing: f32,
-    pub sensitivity: f32,
+impl Default for KaraokeConfig {
+    fn default() -> Self {
+        Self { pulse: 0.35, artist: true }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VisualizerConfig {
-    pub bars: usize,
-    pub color_scheme: ColorScheme,
-    #[serde(default)]
-    pub style: Option<String>,
-    pub bar_width: u16,
-    pub bar_spacing: u16,
-    pub mirror: bool,
-    /// When true with mirror, reverses the pattern: lows meet in middle, highs on outside
-    #[serde(default)]
-    pub reverse_mirror: bool,
-    #[serde(default = "default_opacity")]
-    pub opacity: f32,
+/// Seismograph style tuning (`[visualizer.seismograph]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SeismographConfig {
+    /// Seconds of loudness history across the width
+    pub window_secs: f32,
+    /// Scale the envelope so the loudest moment in the window fills the height
+    pub normalize: bool,
+    /// Envelope multiplier when `normalize` is off
+    pub gain: f32,
 }
 
-fn default_opacity() -> f32 {
-    1.0
+impl Default for SeismographConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 8.0,
+            normalize: true,
+            gain: 3.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +1786,22 @@ pub struct TextConfig {
     pub pulse_intensity: f32,
     pub position: TextPosition,
     pub font_style: FontStyle,
+    /// Letter case the track text is drawn in
+    #[serde(default)]
+    pub case: TextCase,
+    /// TrueType/OpenType font for the track text in wallpaper mode
+    /// (takes precedence over `font_family`)
+    #[serde(default)]
+    pub font_file: Option<String>,
+    /// Font family looked up with fontconfig, e.g. "Inter"
+    #[serde(default)]
+    pub font_family: Option<String>,
+    /// Drop shadow behind the text
+    #[serde(default)]
+    pub shadow: Option<TextShadow>,
+    /// Outline around the text
+    #[serde(default)]
+    pub outline: Option<TextOutline>,
     // New fields for Issue #3
     pub alignment: TextAlignment,
     pub animation_style: TextAnimation,
@@ -97,6 +1812,73 @@ pub struct TextConfig {
     pub artist_color: Option<RgbColor>,
     pub background_color: Option<RgbColor>,
     pub use_color_scheme: bool,
+    /// Render title and artist on separate rows with independent marquees
+    #[serde(default)]
+    pub two_line: bool,
+    /// Title marquee speed multiplier (two-line mode)
+    #[serde(default = "default_scroll_speed")]
+    pub title_scroll_speed: f32,
+    /// Artist marquee speed multiplier (two-line mode)
+    #[serde(default = "default_scroll_speed")]
+    pub artist_scroll_speed: f32,
+    #[serde(default)]
+    pub title_scroll_direction: ScrollDirection,
+    #[serde(default)]
+    pub artist_scroll_direction: ScrollDirection,
+}
+
+fn default_scroll_speed() -> f32 {
+    1.0
+}
+
+/// Drop shadow drawn beneath the track text
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TextShadow {
+    /// Offset in font pixels (scales with the text size)
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub color: RgbColor,
+    /// Shadow opacity (0.0-1.0)
+    pub alpha: f32,
+}
+
+impl Default for TextShadow {
+    fn default() -> Self {
+        Self {
+            offset_x: 1,
+            offset_y: 1,
+            color: RgbColor { r: 0, g: 0, b: 0 },
+            alpha: 0.6,
+        }
+    }
+}
+
+/// Outline drawn around the track text
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TextOutline {
+    /// Width in font pixels (0 = none)
+    pub width: u32,
+    pub color: RgbColor,
+}
+
+impl Default for TextOutline {
+    fn default() -> Self {
+        Self {
+            width: 1,
+            color: RgbColor { r: 0, g: 0, b: 0 },
+        }
+    }
+}
+
+/// Direction a marquee line travels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollDirection {
+    #[default]
+    Left,
+    Right,
 }
 
 /// A coordinate value that can be pixels or a percentage of the total dimension.
@@ -106,6 +1888,12 @@ pub enum CoordValue {
     Percent(f32),
 }
 
+impl Default for CoordValue {
+    fn default() -> Self {
+        CoordValue::Pixels(0)
+    }
+}
+
 impl CoordValue {
     /// Resolve this coordinate value to an absolute pixel/cell position.
     pub fn resolve(&self, total: usize) -> usize {
@@ -183,6 +1971,35 @@ fn parse_coord_value(s: &str) -> Result<CoordValue, String> {
     }
 }
 
+/// Always a string, so `ctl config set` can switch between pixels and percent
+impl Serialize for CoordValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for CoordValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Pixels may also be written as a plain number
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Pixels(i32),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Pixels(px) => Ok(CoordValue::Pixels(px)),
+            Raw::Text(s) => parse_coord_value(s.trim()).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 impl Serialize for TextPosition {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -202,6 +2019,37 @@ impl<'de> Deserialize<'de> for TextPosition {
     }
 }
 
+/// Letter case of text drawn with the bitmap font
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TextCase {
+    /// Draw text as it is
+    Preserve,
+    /// All capitals
+    #[default]
+    Upper,
+    /// All lowercase
+    Lower,
+}
+
+impl TextCase {
+    /// `ch` in this case. Characters whose other case is several
+    /// characters (ß → SS) are kept as they are.
+    pub fn apply(self, ch: char) -> char {
+        fn single(mut chars: impl Iterator<Item = char>, ch: char) -> char {
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => ch,
+            }
+        }
+        match self {
+            TextCase::Preserve => ch,
+            TextCase::Upper => single(ch.to_uppercase(), ch),
+            TextCase::Lower => single(ch.to_lowercase(), ch),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, ValueEnum, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum FontStyle {
@@ -437,6 +2285,19 @@ impl WallpaperSize {
     }
 }
 
+/// How the wallpaper background image is scaled to the surface
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundFit {
+    /// Scale to cover the whole surface, cropping the overflow
+    #[default]
+    Fill,
+    /// Scale to fit inside the surface, leaving transparent borders
+    Fit,
+    /// Repeat at the image's native size
+    Tile,
+}
+
 /// Wallpaper positioning and sizing config
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -452,12 +2313,95 @@ pub struct WallpaperConfig {
     pub margin_left: i32,
     #[serde(default)]
     pub draggable: bool,
+    /// Distance (pixels) from a screen edge or center line within which a
+    /// dragged surface snaps to it on release (0 = no snapping)
+    pub snap_distance: u32,
+    /// Pointer shortcuts on the surface: scroll cycles styles, middle-click toggles the text
+    #[serde(default)]
+    pub pointer_actions: bool,
+    /// Let clicks pass through to whatever is below the surface
+    /// (only while `draggable` and `pointer_actions` are off)
+    #[serde(default)]
+    pub click_through: bool,
     #[serde(default)]
     pub multi_monitor: MultiMonitorMode,
     #[serde(default)]
     pub outputs: Option<Vec<String>>,   // CLI filter: only these outputs
     #[serde(default)]
     pub monitors: Vec<MonitorConfig>,   // Per-monitor overrides
+    /// Image drawn beneath the visualizer (png, jpeg, ...)
+    pub background_image: Option<String>,
+    /// How the background image is scaled to the surface
+    pub background_fit: BackgroundFit,
+    /// Darken the background image (0.0 = unchanged, 1.0 = black)
+    pub background_darken: f32,
+    /// Background blur strength (0 = sharp)
+    pub background_blur: u32,
+    /// Fade the surface out towards its edges
+    pub edge_fade: EdgeFadeConfig,
+    /// Always-on-top corner preset for streaming
+    pub overlay: OverlayConfig,
+}
+
+/// Streaming overlay preset (`[wallpaper.overlay]`): while enabled, every
+/// surface (or the one on `output`) moves to the overlay layer at its own
+/// anchor, size and margin, above fullscreen games, and the regular layout is
+/// kept for when it's off.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub enabled: bool,
+    /// Only this output (e.g. "DP-1") shows the overlay; the others keep the
+    /// regular layout. Unset = every output
+    pub output: Option<String>,
+    pub anchor: WallpaperAnchor,
+    pub width: String,
+    pub height: String,
+    /// Distance from the screen edges (pixels)
+    pub margin: i32,
+    /// Let clicks pass through to the game or window below
+    pub click_through: bool,
+    /// Reserve the overlay's strip so windows don't cover it
+    /// (top, bottom, left and right anchors only)
+    pub exclusive_zone: bool,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            output: None,
+            anchor: WallpaperAnchor::BottomRight,
+            width: "320".to_string(),
+            height: "180".to_string(),
+            margin: 24,
+            click_through: true,
+            exclusive_zone: false,
+        }
+    }
+}
+
+/// Transparency ramp along the surface edges (`[wallpaper.edge_fade]`), so a
+/// strip of bars blends into the desktop instead of ending in a hard edge.
+/// Widths are in pixels; 0 leaves that edge sharp.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct EdgeFadeConfig {
+    /// Fade width for all edges (shorthand)
+    pub width: u32,
+    /// Individual edges (override `width` if non-zero)
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl EdgeFadeConfig {
+    /// Fade widths as (top, right, bottom, left), falling back to `width`
+    pub fn effective(&self) -> (u32, u32, u32, u32) {
+        let side = |width: u32| if width != 0 { width } else { self.width };
+        (side(self.top), side(self.right), side(self.bottom), side(self.left))
+    }
 }
 
 impl Default for WallpaperConfig {
@@ -473,14 +2417,145 @@ impl Default for WallpaperConfig {
             margin_bottom: 0,
             margin_left: 0,
             draggable: false,
+            snap_distance: 24,
+            pointer_actions: false,
+            click_through: false,
             multi_monitor: MultiMonitorMode::default(),
             outputs: None,
             monitors: Vec::new(),
+            background_image: None,
+            background_fit: BackgroundFit::default(),
+            background_darken: 0.0,
+            background_blur: 0,
+            edge_fade: EdgeFadeConfig::default(),
+            overlay: OverlayConfig::default(),
         }
     }
 }
 
 impl WallpaperConfig {
+    /// Layer for `output`: the overlay layer while the overlay is on, else its
+    /// `[[wallpaper.monitors]]` layer, else the global one
+    pub fn layer_for(&self, output: Option<&str>) -> WallpaperLayer {
+        if self.overlay_on(output) {
+            return WallpaperLayer::Overlay;
+        }
+        output
+            .and_then(|name| self.monitors.iter().find(|m| m.output == name))
+            .and_then(|m| m.layer)
+            .unwrap_or(self.layer)
+    }
+
+    /// Whether the streaming overlay is shown on `output`
+    pub fn overlay_on(&self, output: Option<&str>) -> bool {
+        self.overlay.enabled && self.overlay.output.as_deref().is_none_or(|name| Some(name) == output)
+    }
+
+    /// Give `output` its own layer, adding a `[[wallpaper.monitors]]` entry if needed
+    pub fn set_output_layer(&mut self, output: &str, layer: WallpaperLayer) {
+        self.monitor_mut(output).layer = Some(layer);
+    }
+
+    /// The `[[wallpaper.monitors]]` entry for `output`, added without overrides if missing
+    pub fn monitor_mut(&mut self, output: &str) -> &mut MonitorConfig {
+        let idx = match self.monitors.iter().position(|m| m.output == output) {
+            Some(idx) => idx,
+            None => {
+                self.monitors.push(MonitorConfig {
+                    output: output.to_string(),
+                    enabled: true,
+                    color_scheme: None,
+                    style: None,
+                    opacity: None,
+                    audio_source: None,
+                    bars: None,
+                    layer: None,
+                    anchor: None,
+                    width: None,
+                    height: None,
+                    margin: None,
+                    margin_top: None,
+                    margin_right: None,
+                    margin_bottom: None,
+                    margin_left: None,
+                });
+                self.monitors.len() - 1
+            }
+        };
+        &mut self.monitors[idx]
+    }
+
+    /// Whether the surface should ignore pointer input entirely
+    pub fn passes_pointer_through(&self) -> bool {
+        if self.overlay.enabled {
+            return self.overlay.click_through && !self.pointer_actions;
+        }
+        self.click_through && !self.draggable && !self.pointer_actions
+    }
+
+    /// Copy of this config with the overlay's anchor, size and margin in
+    /// place of the regular layout
+    pub fn overlay_layout(&self) -> WallpaperConfig {
+        let overlay = &self.overlay;
+        WallpaperConfig {
+            layer: WallpaperLayer::Overlay,
+            anchor: overlay.anchor,
+            width: Some(overlay.width.clone()),
+            height: Some(overlay.height.clone()),
+            margin: overlay.margin,
+            margin_top: 0,
+            margin_right: 0,
+            margin_bottom: 0,
+            margin_left: 0,
+            ..self.clone()
+        }
+    }
+
+    /// Exclusive zone for a surface of `width`×`height` with this layout:
+    /// the overlay's strip when it reserves space, else -1 (extend under
+    /// panels and don't push windows). Compositors ignore zones for corner
+    /// and centered anchors, so those never reserve.
+    pub fn exclusive_zone(&self, (width, height): (u32, u32)) -> i32 {
+        if !self.overlay.enabled || !self.overlay.exclusive_zone {
+            return -1;
+        }
+        match self.anchor {
+            WallpaperAnchor::Top | WallpaperAnchor::Bottom => height as i32,
+            WallpaperAnchor::Left | WallpaperAnchor::Right => width as i32,
+            _ => -1,
+        }
+    }
+
+    /// Copy of this config with a monitor's anchor, size and margin overrides applied
+    pub fn with_monitor_layout(&self, monitor: &MonitorConfig) -> WallpaperConfig {
+        let mut layout = self.clone();
+        if let Some(anchor) = monitor.anchor {
+            layout.anchor = anchor;
+        }
+        if monitor.width.is_some() || monitor.height.is_some() {
+            layout.width = monitor.width.clone();
+            layout.height = monitor.height.clone();
+        }
+        if let Some(margin) = monitor.margin {
+            layout.margin = margin;
+            layout.margin_top = 0;
+            layout.margin_right = 0;
+            layout.margin_bottom = 0;
+            layout.margin_left = 0;
+        }
+        for (side, value) in [
+            (&mut layout.margin_top, monitor.margin_top),
+            (&mut layout.margin_right, monitor.margin_right),
+            (&mut layout.margin_bottom, monitor.margin_bottom),
+            (&mut layout.margin_left, monitor.margin_left),
+        ] {
+            if let Some(value) = value {
+                *side = value;
+            }
+        }
+        layout
+    }
+
     /// Get the effective margins, applying the uniform margin as a base
     pub fn effective_margins(&self) -> (i32, i32, i32, i32) {
         let top = if self.margin_top != 0 { self.margin_top } else { self.margin };
@@ -523,17 +2598,43 @@ impl WallpaperConfig {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            theme: None,
             display: DisplayConfig {
                 mode: DisplayMode::Terminal,
                 rotate_styles: false,
                 rotation_interval_secs: 30,
+                rotate_order: CycleOrder::default(),
+                info_panel: false,
+                info_panel_width: default_info_panel_width(),
+                terminal_graphics: TerminalGraphics::default(),
+                window_width: default_window_width(),
+                window_height: default_window_height(),
             },
             audio: AudioConfig {
                 device: None,
+                app: None,
                 sample_rate: 44100,
                 buffer_size: 1024,
                 smoothing: 0.7,
-                sensitivity: 1.0,
+                sensitivity: Sensitivity::Fixed(1.0),
+                rise_speed: None,
+                fall_speed: None,
+                gravity: 0.0,
+                auto_exposure: false,
+                auto_exposure_speed: default_auto_exposure_speed(),
+                auto_exposure_clip: default_auto_exposure_clip(),
+                agc_attack_secs: default_agc_attack_secs(),
+                agc_release_secs: default_agc_release_secs(),
+                demo: false,
+                multires: false,
+                waveform_samples: default_waveform_samples(),
+                weighting: Weighting::None,
+                interpolation: Interpolation::Nearest,
+                eq: Vec::new(),
+                delay_ms: 0,
+                noise_floor_db: None,
+                bass_crossover_hz: default_bass_crossover_hz(),
+                treble_crossover_hz: default_treble_crossover_hz(),
             },
             visualizer: VisualizerConfig {
                 bars: 64,
@@ -544,6 +2645,27 @@ impl Default for Config {
                 mirror: false,
                 reverse_mirror: false,
                 opacity: 1.0,
+                min_bar_height: CoordValue::default(),
+                bar_rounding: BarRounding::default(),
+                group: BarGroup::default(),
+                segments: SegmentsConfig::default(),
+                orientation: Orientation::default(),
+                flip_horizontal: false,
+                flip_vertical: false,
+                rotation: Rotation::default(),
+                symmetry: Symmetry::default(),
+                idle_mode: IdleMode::default(),
+                idle_fade_secs: default_idle_fade_secs(),
+                pause_on_media_pause: false,
+                spectrogram: SpectrogramConfig::default(),
+                radial: RadialConfig::default(),
+                oscilloscope: OscilloscopeConfig::default(),
+                particles: ParticlesConfig::default(),
+                seismograph: SeismographConfig::default(),
+                fire: FireConfig::default(),
+                vectorscope: VectorscopeConfig::default(),
+                vu: VuConfig::default(),
+                karaoke: KaraokeConfig::default(),
             },
             text: TextConfig {
                 show_title: true,
@@ -552,6 +2674,11 @@ impl Default for Config {
                 pulse_intensity: 0.8,
                 position: TextPosition::Bottom,
                 font_style: FontStyle::Normal,
+                case: TextCase::Upper,
+                font_file: None,
+                font_family: None,
+                shadow: None,
+                outline: None,
                 alignment: TextAlignment::Center,
                 animation_style: TextAnimation::Scroll,
                 margin_top: 0,
@@ -561,8 +2688,36 @@ impl Default for Config {
                 artist_color: None,
                 background_color: None,
                 use_color_scheme: true,
+                two_line: false,
+                title_scroll_speed: 1.0,
+                artist_scroll_speed: 1.0,
+                title_scroll_direction: ScrollDirection::Left,
+                artist_scroll_direction: ScrollDirection::Left,
             },
             wallpaper: WallpaperConfig::default(),
+            color_schemes: Vec::new(),
+            styles: Vec::new(),
+            themes: Vec::new(),
+            cycle: CycleConfig::default(),
+            style_overrides: BTreeMap::new(),
+            hooks: HooksConfig::default(),
+            metadata: MetadataConfig::default(),
+            album_art: AlbumArtConfig::default(),
+            renderer: RendererConfig::default(),
+            source_indicator: SourceIndicatorConfig::default(),
+            web: WebConfig::default(),
+            leds: LedsConfig::default(),
+            ipc: IpcConfig::default(),
+            color: ColorAdjustConfig::default(),
+            color_dynamics: ColorDynamicsConfig::default(),
+            privacy: PrivacyConfig::default(),
+            compositor: CompositorConfig::default(),
+            effects: EffectsConfig::default(),
+            stats: StatsConfig::default(),
+            performance: PerformanceConfig::default(),
+            widgets: WidgetsConfig::default(),
+            terminal: TerminalConfig::default(),
+            theme_base: None,
         }
     }
 }
@@ -570,15 +2725,192 @@ impl Default for Config {
 impl Config {
     pub fn load(path: &Path) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+
+        // Register custom color schemes first so `color_scheme = "mytheme"`
+        // references elsewhere in the file can be resolved
+        #[derive(Deserialize)]
+        struct SchemeDefs {
+            #[serde(default)]
+            color_schemes: Vec<ColorSchemeDef>,
+        }
+        let defs: SchemeDefs = toml::from_str(&content)?;
+        crate::color::register_custom_schemes(&defs.color_schemes)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut config: Config = toml::from_str(&content)?;
+        if let Some(theme) = config.theme.clone() {
+            config.apply_theme(&theme).map_err(|e| anyhow::anyhow!("{}", e))?;
+        }
+        config.sanitize();
         Ok(config)
     }
 
+    /// Replace out-of-range values (e.g. `nan` or `inf` floats, which TOML
+    /// allows) with their defaults so they can't reach the render loop.
+    pub fn sanitize(&mut self) {
+        self.visualizer.sanitize();
+        if matches!(self.audio.sensitivity, Sensitivity::Fixed(scale) if !scale.is_finite()) {
+            self.audio.sensitivity = Sensitivity::Fixed(1.0);
+        }
+        self.color.sanitize();
+        self.performance.sanitize();
+        self.renderer.pixel_scale = self.renderer.pixel_scale.max(1);
+        self.apply_style_wide_modes();
+    }
+
+    /// Move `wide_mode` from `[style.radial]` / `[style.oscilloscope]` into
+    /// `[visualizer.radial]` / `[visualizer.oscilloscope]`
+    fn apply_style_wide_modes(&mut self) {
+        for (name, overrides) in &mut self.style_overrides {
+            let Some(mode) = overrides.wide_mode.clone() else {
+                continue;
+            };
+            let value = toml::Value::String(mode);
+            let applied = if name.eq_ignore_ascii_case("radial") {
+                value.try_into().map(|mode| self.visualizer.radial.wide_mode = mode).is_ok()
+            } else if name.eq_ignore_ascii_case("oscilloscope") {
+                value.try_into().map(|mode| self.visualizer.oscilloscope.wide_mode = mode).is_ok()
+            } else {
+                false
+            };
+            if applied {
+                overrides.wide_mode = None;
+            }
+        }
+    }
+
+    /// Look up a value by dotted key path (e.g. "text.animation_speed")
+    pub fn get_key(&self, key: &str) -> Result<String, String> {
+        Ok(match self.get_value(key)? {
+            toml::Value::String(s) => s,
+            other => other.to_string(),
+        })
+    }
+
+    /// Look up the typed TOML value at a dotted key path
+    pub fn get_value(&self, key: &str) -> Result<toml::Value, String> {
+        let root = toml::Value::try_from(self).map_err(|e| e.to_string())?;
+        let mut node = &root;
+        for part in key.split('.') {
+            node = node
+                .get(part)
+                .ok_or_else(|| format!("unknown or unset key '{}'", key))?;
+        }
+        Ok(node.clone())
+    }
+
+    /// Set a value by dotted key path, returning the value as stored.
+    ///
+    /// The new value is parsed to match the type of the existing value; unset
+    /// optional keys accept any TOML literal, falling back to a plain string.
+    /// The whole config is re-validated, so a bad value leaves `self` unchanged.
+    pub fn set_key(&mut self, key: &str, value: &str) -> Result<String, String> {
+        let mut root = toml::Value::try_from(&*self).map_err(|e| e.to_string())?;
+        let (parents, field) = match key.rsplit_once('.') {
+            Some((parents, field)) => (Some(parents), field),
+            None => (None, key),
+        };
+
+        let mut table = root.as_table_mut().ok_or("config is not a table")?;
+        for part in parents.into_iter().flat_map(|p| p.split('.')) {
+            table = table
+                .get_mut(part)
+                .and_then(|v| v.as_table_mut())
+                .ok_or_else(|| format!("unknown section '{}' in '{}'", part, key))?;
+        }
+
+        let new_value = typed_value(table.get(field), key, value)?;
+        table.insert(field.to_string(), new_value);
+
+        let mut config: Config = root.try_into().map_err(|e: toml::de::Error| e.message().to_string())?;
+        config.theme_base = self.theme_base.clone();
+        config.sanitize();
+        *self = config;
+        self.get_key(key)
+    }
+
     /// Get the default XDG config path (~/.config/cavibe/config.toml)
     pub fn default_path() -> Option<PathBuf> {
         dirs::config_dir().map(|p| p.join("cavibe").join("config.toml"))
     }
 
+    /// Directory of shareable style files (~/.config/cavibe/styles/*.toml)
+    pub fn styles_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("cavibe").join("styles"))
+    }
+
+    /// Directory of style plugins (~/.local/share/cavibe/plugins/*.so)
+    pub fn plugins_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("cavibe").join("plugins"))
+    }
+
+    /// Overrides for the style named `name` (case-insensitive)
+    pub fn style_override(&self, name: &str) -> Option<&StyleOverride> {
+        self.style_overrides
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, overrides)| overrides)
+    }
+
+    /// Directory of shareable theme files (~/.config/cavibe/themes/*.toml)
+    pub fn themes_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("cavibe").join("themes"))
+    }
+
+    /// `[[themes]]` entries followed by the theme files, sorted by file name
+    pub fn all_themes(&self) -> Result<Vec<ThemeDef>, String> {
+        let mut themes = self.themes.clone();
+        let Some(entries) = Self::themes_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+            return Ok(themes);
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let content = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let mut theme: ThemeDef = toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+            if theme.name.is_empty() {
+                theme.name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            }
+            themes.push(theme);
+        }
+        Ok(themes)
+    }
+
+    /// Overlay the named theme (case-insensitive) on the settings as they were
+    /// before any theme, returning its name. A theme that doesn't validate
+    /// leaves `self` unchanged.
+    pub fn apply_theme(&mut self, name: &str) -> Result<String, String> {
+        let themes = self.all_themes()?;
+        let Some(theme) = themes.iter().find(|t| t.name.eq_ignore_ascii_case(name)) else {
+            let names: Vec<_> = themes.iter().map(|t| t.name.as_str()).collect();
+            return Err(format!("unknown theme '{}' ({})", name, names.join(", ")));
+        };
+
+        let base = self.theme_base.clone().unwrap_or_else(|| Arc::new(self.clone()));
+        let mut root = toml::Value::try_from(&*base).map_err(|e| e.to_string())?;
+        let table = root.as_table_mut().ok_or("config is not a table")?;
+        for (section, patch) in &theme.settings {
+            // Only settings sections; themes can't define styles or other themes
+            match table.get_mut(section) {
+                Some(existing) if existing.is_table() && patch.is_table() => merge_toml(existing, patch),
+                _ => return Err(format!("theme '{}': '{}' is not a settings section", theme.name, section)),
+            }
+        }
+
+        let mut config: Config = root
+            .try_into()
+            .map_err(|e: toml::de::Error| format!("theme '{}': {}", theme.name, e.message()))?;
+        config.theme = Some(theme.name.clone());
+        config.theme_base = Some(base);
+        config.sanitize();
+        *self = config;
+        Ok(theme.name.clone())
+    }
+
     /// Load config from the default XDG path if it exists
     /// Returns Ok(None) if file doesn't exist, Err on parse errors
     pub fn load_from_default_path() -> Result<Option<Self>> {
@@ -611,35 +2943,108 @@ impl Config {
 
     /// Generate a commented TOML config template
     pub fn generate_config_template() -> String {
-        r#"# Cavibe Configuration
+        r##"# Cavibe Configuration
 # This file is auto-generated. Edit as needed.
 
+# Theme applied at startup, on top of the settings below (see [[themes]] at the end)
+# theme = "neon"
+
 [display]
-# Display mode: "terminal" or "wallpaper"
+# Display mode: "terminal", "wallpaper", or "window" (a regular desktop window)
 mode = "terminal"
 # Automatically rotate visualizer styles
 rotate_styles = false
 # Rotation interval in seconds
 rotation_interval_secs = 30
+# Rotation order: "sequential" or "shuffle" (random, every entry once per round,
+# never the same one twice in a row)
+# rotate_order = "sequential"
+# Terminal mode: show a now-playing panel (album, progress, rotation, audio stats)
+# beside the visualizer. Toggle with 'i'
+info_panel = false
+info_panel_width = 34
+# Terminal mode: "blocks" (half-block characters, works everywhere), "kitty" or
+# "sixel" (full-resolution images in terminals supporting them), or "auto"
+# (kitty or sixel when the terminal looks capable, otherwise blocks)
+# terminal_graphics = "blocks"
+# Window mode: initial window size in pixels
+window_width = 960
+window_height = 540
+
+[terminal]
+# Characters the "blocks" terminal_graphics draw with (also when kitty or sixel
+# fall back to blocks): "halfblock" (two colored pixels per cell), "braille"
+# (2x4 dots per cell, one color each, for finer lines and curves) or "block"
+# (one pixel per cell)
+blocks = "halfblock"
 
 [audio]
 # Audio device (null = default)
 # device = "pulse"
+# Capture only one application's audio (matched by name or binary, e.g. "spotify")
+# instead of everything playing; list candidates with `cavibe ctl list sources --apps`
+# app = "spotify"
 # Sample rate in Hz
 sample_rate = 44100
 # Buffer size for audio capture
 buffer_size = 1024
 # Smoothing factor (0.0-1.0, higher = smoother)
 smoothing = 0.7
-# Audio sensitivity multiplier (0.1-10.0)
+# Audio sensitivity multiplier (0.1-10.0), or "auto" for automatic gain control:
+# bars are normalized to the recent peak, so quiet tracks fill the display and
+# loud ones don't clip
 sensitivity = 1.0
+# Automatic gain control: seconds to adapt when the music gets louder / quieter
+agc_attack_secs = 0.1
+agc_release_secs = 3.0
+# Separate attack/decay (0.0-1.0, 1.0 = instant). Default to 1.0 - smoothing when unset.
+# rise_speed = 1.0
+# fall_speed = 0.2
+# Cava-style gravity: bars fall with accelerating speed (0.0 = disabled, uses fall_speed)
+gravity = 0.0
+# Auto-exposure: adapt gain to the session's loudness so quiet and loud music
+# fill the display similarly (sensitivity then acts as exposure compensation)
+auto_exposure = false
+# Adaptation speed per second (higher = reacts faster to loudness changes)
+auto_exposure_speed = 0.5
+# Fraction of recent magnitudes kept below full bar height (0.5-1.0)
+auto_exposure_clip = 0.95
+# Demo mode: visualize built-in synthetic music instead of captured audio
+demo = false
+# Multi-resolution FFT: long window below 250 Hz for stable bass, short window
+# above it for snappier treble (adds ~0.2s of bass latency)
+multires = false
+# Waveform length for oscilloscope styles in samples (64-65536, 2048 = ~46ms at 44100Hz)
+waveform_samples = 2048
+# Loudness weighting: "none" (raw FFT magnitudes) or "a" (A-weighting, so bass,
+# mids and treble look as loud as they sound)
+weighting = "none"
+# Resampling of FFT bins into bars: "nearest" (low bars may repeat each other),
+# "linear" or "cubic" (smooth curve through the bins; both average bands when
+# fewer bars are displayed than analyzed)
+interpolation = "nearest"
+# Per-band EQ in dB, spread evenly from bass to treble, e.g. [-6.0, 0.0, 3.0]
+eq = []
+# Drop bands quieter than this, in dB relative to a full-height bar
+# noise_floor_db = -50.0
+# Crossovers splitting the spectrum into the bass, mid and treble levels that
+# custom style layers (opacity_from/scale_from) react to, in Hz
+bass_crossover_hz = 250.0
+treble_crossover_hz = 4000.0
+# Shift the bars against the audio, in ms. Positive holds them back to match
+# delayed outputs such as Bluetooth headphones (up to 5000); negative analyzes
+# more often so they react sooner (down to about -40). Tune it live with
+# `cavibe ctl set audio.delay_ms 150`
+delay_ms = 0
 
 [visualizer]
 # Number of frequency bars
 bars = 64
-# Color scheme: "spectrum", "rainbow", "fire", "ocean", "forest", "purple", "monochrome"
+# Color scheme: "spectrum", "rainbow", "fire", "ocean", "forest", "purple", "monochrome",
+# or the name of a custom scheme defined in [[color_schemes]] below ("random" picks one at startup)
 color_scheme = "spectrum"
-# Visualizer style: "classic bars", "mirrored bars", "wave", "dots", "blocks", "oscilloscope", "spectrogram", "radial"
+# Visualizer style: "classic bars", "mirrored bars", "wave", "dots", "blocks", "oscilloscope", "spectrogram", "radial", "particles", "ring", "skyline", "seismograph", "fire", "vectorscope", "vu meters", "karaoke"
+# or "random" to pick one at startup
 # style = "classic bars"
 # Width of each bar in characters
 bar_width = 2
@@ -651,6 +3056,204 @@ mirror = false
 reverse_mirror = false
 # Opacity level (0.0-1.0, where 1.0 is fully opaque, wallpaper mode only)
 opacity = 1.0
+# Baseline quiet bars never drop below: pixels (4) or percent of the bar area ("2%"), 0 = off
+# (classic, mirrored and blocks styles)
+min_bar_height = 0
+# Bar ends: "none" (square), "top" (rounded tips), "pill" (both ends rounded);
+# classic and mirrored bars without segments
+bar_rounding = "none"
+# Group bars into segments: extra gap (in characters) after every `size` bars (0 = disabled)
+group = { size = 0, gap = 2 }
+# Screen edge the bars grow from: "bottom", "top", "left", "right"
+# (left/right suit a vertical side-panel wallpaper; low frequencies at the top)
+orientation = "bottom"
+# Transform the finished frame (bars, text and all) for rotated monitors or
+# unusual layouts: rotation = 0, 90, 180 or 270 degrees clockwise, then the flips
+rotation = 0
+flip_horizontal = false
+flip_vertical = false
+# Kaleidoscope layouts: draw the bars into half (or a quarter) of the frame and
+# reflect them into the rest: "none", "horizontal" (left-right),
+# "vertical" (top-bottom), "quad" (both). Unlike `mirror` this is geometric
+symmetry = "none"
+# What the wallpaper shows when no audio is playing: "freeze" (keep the last frame),
+# "fadeout" (fade out over idle_fade_secs), "clear", "demo" (ambient animation), "clock"
+idle_mode = "freeze"
+idle_fade_secs = 2.0
+# Go idle as soon as the media player pauses (terminal mode freezes the frame)
+pause_on_media_pause = false
+
+[visualizer.segments]
+# Split classic and mirrored bars into LED-style blocks with gaps
+enabled = false
+# Block height and gap between blocks (same units as bar_width)
+height = 2
+gap = 1
+# Fixed colors by height instead of the color scheme gradient; each zone starts
+# at a fraction of the full bar height, e.g. a green/yellow/red VU meter:
+# zones = [
+#     { from = 0.0, color = "#00FF00" },
+#     { from = 0.6, color = "#FFFF00" },
+#     { from = 0.85, color = "#FF0000" },
+# ]
+zones = []
+
+[visualizer.spectrogram]
+# Blend each new row with the previous one (0.0 = raw, 0.9 = very smooth)
+temporal_smoothing = 0.0
+# Weights blurred across neighbouring frequencies, e.g. [1.0, 2.0, 1.0] (empty = off)
+frequency_kernel = []
+# Heatmap gain and contrast (contrast > 1.0 darkens quiet areas)
+gain = 1.0
+contrast = 1.0
+
+# Ultrawide handling: how styles use surfaces wider than 16:9
+[visualizer.radial]
+# "single" (one circle), "rings" (one circle per 16:9 slice), "ellipse" (stretch to fill)
+wide_mode = "single"
+
+[visualizer.oscilloscope]
+# "stretch" (whole waveform across the width), "repeat" (once per 16:9 slice),
+# "zoom" (middle of the waveform magnified to the full width)
+wide_mode = "stretch"
+
+[visualizer.particles]
+# Particles spawned by a full-strength bass hit
+count = 48
+# Downward pull in surface heights per second² (higher = shorter arcs)
+gravity = 1.5
+# Seconds before a particle has fully faded
+lifetime = 1.5
+
+[visualizer.seismograph]
+# Seconds of loudness history across the width
+window_secs = 8.0
+# Scale the envelope so the loudest moment in the window fills the height
+normalize = true
+# Envelope multiplier when normalize is false
+gain = 3.0
+
+[visualizer.fire]
+# How high the flames of a full-strength band reach (fraction of the height)
+height = 0.6
+# Random variation of the flames (0.0 = steady plumes, 1.0 = wild)
+flicker = 0.5
+# Terminal mode: draw the flames with shade characters instead of half-block pixels
+terminal_blocks = true
+
+[visualizer.vectorscope]
+# Seconds until a trace has faded to a tenth of its brightness
+persistence = 0.3
+# Goniometer view: mono vertical, out-of-phase horizontal (false = left vs right on the axes)
+rotate = true
+# Continuous rotation in degrees per second (0 = still)
+spin = 0.0
+# Scale of the figure
+gain = 1.0
+# Stereo correlation meter below the figure
+correlation = true
+
+[visualizer.vu]
+# One meter per channel (false = a single meter for the mix)
+stereo = true
+# RMS level in dBFS that reads 0 VU
+reference_db = -12.0
+# Milliseconds the needle takes to settle on a new level
+response_ms = 300
+# Sample peak in dBFS that lights the peak LED
+peak_db = -1.0
+
+[visualizer.karaoke]
+# How much a letter grows when its band is at full strength (0.35 = 35% larger)
+pulse = 0.35
+# Show the artist in smaller letters below the title
+artist = true
+
+# Per-style overrides, used while that style is shown (including via rotation).
+# Any of bars, bar_width, bar_spacing, mirror and sensitivity (a multiplier on
+# the bar heights, on top of audio.sensitivity). Quote names with spaces.
+# [style.dots]
+# bar_width = 3
+# bar_spacing = 2
+# [style."classic bars"]
+# bars = 96
+# sensitivity = 1.3
+
+# Global color adjustments, applied on top of any color scheme
+# (change live with `cavibe ctl color adjust saturation 1.2`)
+[color]
+# Brightness multiplier (0.0-3.0)
+brightness = 1.0
+# Saturation multiplier (0.0-3.0, 0 = grayscale)
+saturation = 1.0
+# Gamma (0.1-5.0, >1.0 lifts dark tones, <1.0 deepens them)
+gamma = 1.0
+
+# Colors that react to the music, for every style and color scheme
+[color_dynamics]
+# Hue rotation in degrees per second, scaled by the current intensity (0 = off)
+hue_speed = 0.0
+# Hue shift in degrees while the bass hits (0 = off)
+bass_hue_shift = 0.0
+# Brightness boost on each beat (0.5 = +50%, 0 = off), fading over flash_decay_secs
+beat_flash = 0.0
+flash_decay_secs = 0.3
+# Switch to the next color scheme every N beats (0 = off)
+switch_every_beats = 0
+# Beat = intensity above its recent average × this
+beat_sensitivity = 1.5
+
+# Privacy mode hides track info while the screen is shared
+# (or on demand with `cavibe ctl privacy on`)
+[privacy]
+# Detect screen sharing (PipeWire screencast streams, needs pw-dump)
+hide_text_on_screenshare = false
+# What to hide: "text" (title, artist, album art) or "all" (the whole visualizer)
+hide = "text"
+
+# Compositor integration: react to the focused workspace and window
+# (Hyprland or Sway). The first matching rule is in effect.
+[compositor]
+enabled = false
+# Only show the visualizer on workspace 1:
+# [[compositor.rules]]
+# workspace = "1"
+# [[compositor.rules]]
+# hide = true
+#
+# Other rule fields:
+# app = "mpv"                 # focused window class / app_id contains this
+# pause = true                # freeze the frame and stop rendering
+# style = "radial"            # switch style when the rule comes into effect
+
+# Bright sparks at the tips of bars that jump on drum hits (classic, mirrored,
+# dots and blocks styles)
+[effects.sparks]
+enabled = false
+# Rise in one frame (fraction of full bar height) that triggers a spark
+threshold = 0.15
+# Spark brightness (0.0-1.0)
+intensity = 1.0
+# Seconds a spark takes to fade out
+decay = 0.15
+
+# Usage statistics: which styles and color schemes you use and for how long.
+# Stored only in ~/.local/share/cavibe/stats.json; see `cavibe stats summary`
+[stats]
+enabled = false
+
+# Wallpaper mode pacing. Rendering stops while no audio is playing.
+[performance]
+# Loop interval while idle (ms)
+idle_poll_ms = 50
+# Loop interval while rendering (ms)
+active_poll_ms = 4
+# Audio intensity (0.0-1.0) that counts as playing; raise it to ignore faint
+# system sounds
+idle_threshold = 0.001
+# Keep rendering this many seconds after audio goes quiet (avoids stutter
+# from sources with brief dropouts)
+idle_timeout_secs = 0.0
 
 [text]
 # Show track title
@@ -666,6 +3269,14 @@ pulse_intensity = 0.8
 position = "bottom"
 # Font style: normal, bold, ascii, figlet
 font_style = "normal"
+# Letter case: upper, lower, or preserve (as the player reports it)
+case = "upper"
+# Anti-aliased TrueType/OpenType text in wallpaper mode instead of the bitmap font
+# font_file = "~/.local/share/fonts/Inter-Regular.ttf"
+# font_family = "Inter"       # looked up with fontconfig (fc-match)
+# Keep text readable over bright backgrounds (offsets and width in font pixels)
+# shadow = { offset_x = 1, offset_y = 1, color = { r = 0, g = 0, b = 0 }, alpha = 0.6 }
+# outline = { width = 1, color = { r = 0, g = 0, b = 0 } }
 # Text alignment: left, center, right
 alignment = "center"
 # Animation style: scroll, pulse, fade, wave, none
@@ -680,6 +3291,14 @@ margin_horizontal = 2
 # background_color = { r = 0, g = 0, b = 0 }  # semi-transparent text background (wallpaper only)
 # Use visualizer color scheme for text gradient
 use_color_scheme = true
+# Two-line marquee: title and artist on separate rows, each scrolling
+# independently when too wide (requires animation_style = "scroll")
+two_line = false
+title_scroll_speed = 1.0
+artist_scroll_speed = 1.0
+# Marquee direction per line: left, right
+title_scroll_direction = "left"
+artist_scroll_direction = "left"
 
 [wallpaper]
 # Layer-shell layer: background, bottom, top, overlay
@@ -699,10 +3318,51 @@ margin = 0
 # margin_left = 0
 # Enable drag-to-move (left-click drag to reposition; saves margins to config)
 # draggable = false
+# Snap a dragged surface to the screen edges and center when released within
+# this many pixels of them (0 = no snapping)
+# snap_distance = 24
+# Pointer shortcuts: scroll to cycle styles, middle-click to toggle the song text
+# pointer_actions = false
+# Let clicks reach whatever is below the visualizer, e.g. on the "top" or "overlay"
+# layer (ignored while draggable or pointer_actions is on)
+# click_through = false
 # Multi-monitor mode: "clone" (same on all) or "independent" (per-monitor overrides)
 # multi_monitor = "clone"
 # Only show on specific outputs (by name, e.g. "DP-1"):
 # outputs = ["DP-1", "HDMI-A-1"]
+# Image drawn beneath the bars, so cavibe can replace a static wallpaper tool:
+# background_image = "~/Pictures/wallpaper.png"
+# How it is scaled: "fill" (cover, cropped), "fit" (letterboxed), "tile"
+# background_fit = "fill"
+# Darken (0.0-1.0) and blur (0 = sharp) the image so the bars stand out
+# background_darken = 0.0
+# background_blur = 0
+
+# Fade the surface out towards its edges, so e.g. a bottom strip of bars
+# blends into the wallpaper instead of ending in a hard rectangle (pixels, 0 = sharp):
+# [wallpaper.edge_fade]
+# width = 0
+# Individual edges (override width if non-zero)
+# top = 0
+# right = 0
+# bottom = 0
+# left = 0
+
+# Streaming overlay: a small visualizer above everything, fullscreen games
+# included, toggled with `cavibe ctl overlay on/off`. While it's on, these
+# replace the layer, anchor, size and margins above:
+# [wallpaper.overlay]
+# enabled = false
+# Only on this output; the others keep the regular layout (unset = all)
+# output = "DP-1"
+# anchor = "bottom-right"
+# width = "320"
+# height = "180"
+# margin = 24
+# Let clicks reach the game below (ignored while pointer_actions is on)
+# click_through = true
+# Keep windows clear of the overlay's strip (top, bottom, left and right anchors)
+# exclusive_zone = false
 
 # Per-monitor overrides (only used in independent mode):
 # [[wallpaper.monitors]]
@@ -713,15 +3373,214 @@ margin = 0
 # # opacity = 0.8
 # # audio_source = "alsa_output.pci-0000_01_00.1.hdmi-stereo"
 #
+# An overlay above the windows on one output only (also works in clone mode):
+# [[wallpaper.monitors]]
+# output = "DP-3"
+# layer = "overlay"
+#
+# A portrait side monitor with 32 bars in a bottom strip:
+# [[wallpaper.monitors]]
+# output = "DP-2"
+# bars = 32
+# anchor = "bottom"
+# width = "100%"
+# height = "200"
+#
 # [[wallpaper.monitors]]
 # output = "HDMI-A-1"
 # enabled = false
-"#
+
+[renderer]
+# Internal render scale: 2.0 = render at 2x and downsample (smoother diagonals),
+# 0.5 = render at half resolution and upscale (lighter on weak hardware). Range 0.25-4.0
+supersample = 1.0
+# Wallpaper and window mode: pixels per unit of bar_width, bar_spacing and
+# segment sizes (terminal mode uses the character cell)
+pixel_scale = 8
+# Upscaling below 1.0: "nearest" (blocky, cheapest), "bilinear" (smooth), or
+# "compositor" (wallpaper mode: the compositor scales the small buffer on the GPU)
+upscale = "nearest"
+# Post-processing effects, applied in order:
+# "glow", "scanlines", "chromatic-aberration", "vignette"
+effects = []
+# Wallpaper frames: "cpu" (pixel renderer) or "gpu" (wgpu; Wayland, needs a build
+# with the `gpu` feature). Classic, mirrored and oscilloscope frames are drawn on
+# the GPU; anything else falls back to the pixel renderer frame by frame
+backend = "cpu"
+
+# Track info from MPRIS media players
+[metadata]
+# Follow only this player and ignore the others (see `cavibe ctl player list`);
+# a glob, not a regex: only `*` is special and matches anything, e.g. "firefox*".
+# Unset = whichever player is active
+# player = "spotify"
+# Color scheme and/or style by metadata (genre, artist and/or album,
+# case-insensitive substrings), applied when a new track starts; the first
+# matching rule wins, no match keeps the current look:
+# [[metadata.rules]]
+# genre = "metal"
+# color_scheme = "fire"
+#
+# [[metadata.rules]]
+# artist = "Tycho"
+# color_scheme = "ocean"
+# style = "wave"
+
+# Album art from the media player (MPRIS artUrl, file:// or http)
+[album_art]
+enabled = false
+# Placement: "corner" (top-right thumbnail), "background" (blurred, fills the surface),
+# or "beside-text" (square next to the track text)
+placement = "corner"
+# Art opacity (0.0-1.0)
+opacity = 0.8
+# Corner thumbnail size as a fraction of the surface height
+size = 0.25
+# Background blur strength (0 = sharp)
+blur = 8
+
+# Shows the active audio source and a live input level meter, to check that
+# cavibe is listening to the right device. Terminal mode shows it in the status bar.
+[source_indicator]
+enabled = false
+# Corner: "top-left", "top-right", "bottom-left", "bottom-right"
+position = "top-left"
+# Overlay opacity (0.0-1.0)
+opacity = 0.8
+
+# A meter that grows from the center with the overall audio intensity
+[widgets.intensity_bar]
+enabled = false
+# Thickness in pixels
+height = 4
+# "below-text" (bottom edge when text is hidden), "top", or "bottom"
+position = "below-text"
+# Color scheme for the bar; omit to follow the visualizer's scheme
+# scheme = "fire"
+
+# Wallpaper mode: briefly show what a `cavibe ctl` command changed
+# (e.g. "Style: Radial") at the top of the surface
+[widgets.osd]
+enabled = false
+# Seconds the message stays up before fading out
+duration_secs = 2.0
+
+# RGB peripherals lit from the spectrum: OpenRGB, WLED or Adalight strips
+[leds]
+enabled = false
+# Updates per second sent to each device (1-120)
+fps = 30
+# "spectrum" (bass to treble along the strip) or "pulse" (whole strip in the
+# color of the loudest band, pulsing with the music)
+mode = "spectrum"
+brightness = 1.0
+# Defaults to visualizer.color_scheme
+# color_scheme = "fire"
+# [[leds.devices]]
+# kind = "openrgb"            # OpenRGB SDK server (enable it in OpenRGB's SDK Server tab)
+# address = "127.0.0.1:6742"
+# controller = 0              # device index in OpenRGB's device list
+# leds = 30                   # must match the device's LED count
+#
+# [[leds.devices]]
+# kind = "wled"
+# address = "192.168.1.50"    # port 21324 unless given
+# leds = 120
+# mode = "pulse"
+# reverse = true
+#
+# [[leds.devices]]
+# kind = "adalight"           # set the baud rate first, e.g. stty -F /dev/ttyUSB0 115200
+# address = "/dev/ttyUSB0"
+# leds = 60
+
+# Web server: streams frequency and waveform data to WebSocket clients
+# at ws://<bind>/stream (see docs/examples/stream.html)
+[web]
+enabled = false
+bind = "127.0.0.1:9470"
+# Frames per second sent to stream clients (1-240)
+stream_fps = 30
+
+# Remote control: accept `cavibe ctl` commands over TCP or WebSocket (e.g. from a
+# phone or StreamDeck). Clients must send `auth <token>` first; use a long random token.
+[ipc]
+# listen = "127.0.0.1:9999"
+# token = "change-me"
+
+# Shell commands run on events. Details are passed as environment variables:
+# CAVIBE_EVENT, CAVIBE_TITLE, CAVIBE_ARTIST, CAVIBE_STYLE, CAVIBE_COLOR, CAVIBE_INTENSITY
+# [hooks]
+# on_track_change = 'notify-send "$CAVIBE_TITLE" "$CAVIBE_ARTIST"'
+# on_style_change = 'echo "$CAVIBE_STYLE" > /tmp/cavibe-style'
+# on_color_change = ''
+# on_beat = 'openrgb --mode static --color FFFFFF'
+# beat_min_interval_ms = 250  # rate limit for on_beat
+# beat_sensitivity = 1.5      # intensity must exceed the recent average by this factor
+
+# Built-in reactions to a new track (no command needed):
+# [hooks.track_change]
+# flash = 0.3                 # flash the visualizer white for this many seconds (0 = off)
+# title_fade_in = 1.0         # fade the new title in over this many seconds (0 = off)
+# next_color = false          # switch to the next color scheme
+# Color schemes and styles by metadata are [[metadata.rules]]
+
+# Custom color schemes (selectable by name, included in color cycling):
+# [[color_schemes]]
+# name = "sunset"
+# stops = ["#2B1055", "#D53369", "#DAAE51"]
+# # intensity_curve = 1.0  # >1.0 keeps quiet parts darker, <1.0 brightens them
+
+# Custom styles built from layers (selectable by name, included in style cycling).
+# Styles can also be shared as standalone files in ~/.config/cavibe/styles/*.toml
+# (same fields, without the [[styles]] header).
+# [[styles]]
+# name = "neon skyline"
+# [[styles.layers]]
+# kind = "bars"             # bars, line, particles, style (a built-in style: style = "Radial")
+# anchor = "bottom"         # bottom, center, top
+# blend = "normal"          # normal, additive, screen
+# order = 0                 # stacking position, lowest drawn first
+# opacity = 0.6
+# opacity_from = "bass"     # bass, mid, treble, intensity: opacity x band level
+# [[styles.layers]]
+# kind = "line"
+# color = "#FFFFFF"         # omit to use the color scheme
+# scale_from = "treble"     # scale x (1 + band level)
+# [[styles.layers]]
+# kind = "particles"
+# count = 96
+# speed = 0.4
+
+# Themes bundle settings from any section, switchable with `cavibe ctl theme <name>`
+# or `cavibe ctl theme next`. Themes can also be files in ~/.config/cavibe/themes/*.toml
+# (same fields without the [[themes]] header; the name defaults to the file name).
+# [[themes]]
+# name = "neon"
+# visualizer = { style = "skyline", color_scheme = "purple", bars = 96, bar_width = 2 }
+# text = { font_style = "figlet", animation_style = "pulse" }
+# renderer = { effects = ["glow", "scanlines"] }
+
+# Style + color combinations for `cavibe ctl cycle`, e.g. bound to a single hotkey.
+# Without presets, `cycle` advances the style and color scheme together.
+[cycle]
+# "sequential" (in order) or "shuffle" (random, favouring higher weights)
+order = "sequential"
+# presets = [
+#     { style = "radial", color = "fire" },
+#     { style = "skyline", color = "purple", weight = 3 },
+#     { style = "wave" },   # keeps the current color scheme
+# ]
+"##
         .to_string()
     }
 
     /// Merge CLI arguments into config (CLI takes priority)
     pub fn merge_args(&mut self, args: &crate::Args) {
+        // Command-line settings outlast theme switches too
+        if let Some(base) = &mut self.theme_base {
+            Arc::make_mut(base).merge_args(args);
+        }
         // Display settings - only override if explicitly provided via CLI
         if let Some(mode) = args.mode {
             self.display.mode = mode;
@@ -730,11 +3589,23 @@ margin = 0
             self.display.rotate_styles = true;
         }
         self.display.rotation_interval_secs = args.rotate_interval;
+        if args.info_panel {
+            self.display.info_panel = true;
+        }
+        if let Some(graphics) = args.terminal_graphics {
+            self.display.terminal_graphics = graphics;
+        }
+        if let Some(blocks) = args.terminal_blocks {
+            self.terminal.blocks = blocks;
+        }
 
         // Audio settings
         if let Some(ref device) = args.audio_device {
             self.audio.device = Some(device.clone());
         }
+        if let Some(ref app) = args.app_source {
+            self.audio.app = Some(app.clone());
+        }
         if let Some(rate) = args.sample_rate {
             self.audio.sample_rate = rate;
         }
@@ -744,11 +3615,30 @@ margin = 0
         if let Some(smoothing) = args.smoothing {
             self.audio.smoothing = smoothing;
         }
-        self.audio.sensitivity = args.sensitivity;
+        if let Some(sensitivity) = args.sensitivity {
+            self.audio.sensitivity = sensitivity;
+        }
+        if let Some(speed) = args.rise_speed {
+            self.audio.rise_speed = Some(speed.clamp(0.0, 1.0));
+        }
+        if let Some(speed) = args.fall_speed {
+            self.audio.fall_speed = Some(speed.clamp(0.0, 1.0));
+        }
+        if let Some(gravity) = args.gravity {
+            self.audio.gravity = gravity.max(0.0);
+        }
+        if args.auto_exposure {
+            self.audio.auto_exposure = true;
+        }
+        if args.demo {
+            self.audio.demo = true;
+        }
 
         // Visualizer settings
         self.visualizer.bars = args.bars;
-        self.visualizer.color_scheme = args.colors.parse().unwrap_or(self.visualizer.color_scheme);
+        if let Ok(scheme) = ColorScheme::resolve(&args.colors) {
+            self.visualizer.color_scheme = scheme;
+        }
         if args.style.is_some() {
             self.visualizer.style = args.style.clone();
         }
@@ -767,6 +3657,15 @@ margin = 0
         if let Some(opacity) = args.opacity {
             self.visualizer.opacity = opacity.clamp(0.0, 1.0);
         }
+        if let Some(size) = args.group_size {
+            self.visualizer.group.size = size;
+        }
+        if let Some(gap) = args.group_gap {
+            self.visualizer.group.gap = gap;
+        }
+        if let Some(orientation) = args.orientation {
+            self.visualizer.orientation = orientation;
+        }
 
         // Text settings
         if let Some(show) = args.show_title {
@@ -787,6 +3686,15 @@ margin = 0
         if let Some(style) = args.font_style {
             self.text.font_style = style;
         }
+        if let Some(case) = args.text_case {
+            self.text.case = case;
+        }
+        if let Some(ref file) = args.font_file {
+            self.text.font_file = Some(file.clone());
+        }
+        if let Some(ref family) = args.font_family {
+            self.text.font_family = Some(family.clone());
+        }
         if let Some(align) = args.text_alignment {
             self.text.alignment = align;
         }
@@ -808,6 +3716,33 @@ margin = 0
         if let Some(ref color) = args.artist_color {
             self.text.artist_color = RgbColor::from_hex(color);
         }
+        if args.two_line_text {
+            self.text.two_line = true;
+        }
+        if let Some(speed) = args.title_scroll_speed {
+            self.text.title_scroll_speed = speed;
+        }
+        if let Some(speed) = args.artist_scroll_speed {
+            self.text.artist_scroll_speed = speed;
+        }
+        if let Some(scale) = args.supersample {
+            self.renderer.supersample = scale;
+        }
+        if let Some(filter) = args.upscale {
+            self.renderer.upscale = filter;
+        }
+        if let Some(placement) = args.album_art {
+            self.album_art.enabled = true;
+            self.album_art.placement = placement;
+        }
+        if let Some(position) = args.source_indicator {
+            self.source_indicator.enabled = true;
+            self.source_indicator.position = position;
+        }
+        if let Some(bind) = &args.web {
+            self.web.enabled = true;
+            self.web.bind = bind.clone();
+        }
 
         // Wallpaper settings
         if let Some(ref size) = args.wallpaper_size {
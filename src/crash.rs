@@ -0,0 +1,146 @@
+//! Crash handling.
+//!
+//! Installs a panic hook that puts the terminal back into a usable state,
+//! removes the IPC socket and pid file, and writes a crash report (panic message,
+//! backtrace, and the most recent log lines) to
+//! `~/.cache/cavibe/crash-<timestamp>.log`.
+//!
+//! Wayland surfaces need no explicit teardown: the compositor destroys a
+//! client's surfaces as soon as its connection closes, which happens when the
+//! panicking process exits.
+
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Number of log lines kept for crash reports
+const LOG_RING_CAPACITY: usize = 256;
+
+static LOG_RING: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static START: OnceLock<Instant> = OnceLock::new();
+/// Set while the terminal is in raw mode / alternate screen
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Set while this process owns the IPC socket
+static SOCKET_OWNED: AtomicBool = AtomicBool::new(false);
+
+/// Record whether the terminal UI currently owns the terminal.
+pub fn set_terminal_active(active: bool) {
+    TERMINAL_ACTIVE.store(active, Ordering::SeqCst);
+}
+
+/// Record whether this process created the IPC socket.
+pub fn set_socket_owned(owned: bool) {
+    SOCKET_OWNED.store(owned, Ordering::SeqCst);
+}
+
+/// Whether this process created the IPC socket (and should remove it).
+pub fn socket_owned() -> bool {
+    SOCKET_OWNED.load(Ordering::SeqCst)
+}
+
+/// Tracing layer that keeps the most recent log lines in memory.
+pub struct LogRing;
+
+impl<S: Subscriber> Layer<S> for LogRing {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor(String::new());
+        event.record(&mut message);
+        let elapsed = START.get_or_init(Instant::now).elapsed().as_secs_f32();
+        let meta = event.metadata();
+        let line = format!("[{:>9.3}s] {:>5} {}: {}", elapsed, meta.level(), meta.target(), message.0);
+
+        let mut ring = LOG_RING.lock().unwrap_or_else(|e| e.into_inner());
+        if ring.len() >= LOG_RING_CAPACITY {
+            ring.pop_front();
+        }
+        ring.push_back(line);
+    }
+}
+
+/// Formats an event's message followed by its other fields.
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Install the panic hook. Call once, after logging is initialized.
+pub fn install() {
+    START.get_or_init(Instant::now);
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // Release builds abort on any panic, so a panic on any thread ends the
+        // process; when unwinding, only a main-thread panic does
+        if cfg!(panic = "abort") || std::thread::current().name() == Some("main") {
+            teardown();
+        }
+
+        match write_report(info) {
+            Ok(path) => eprintln!("cavibe crashed; report written to {}", path.display()),
+            Err(e) => eprintln!("cavibe crashed; failed to write crash report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Put the terminal back and remove the IPC socket and pid file if this
+/// process owns them, for exits that skip the normal shutdown path.
+pub fn teardown() {
+    restore_terminal();
+    if SOCKET_OWNED.load(Ordering::SeqCst) {
+        let _ = std::fs::remove_file(crate::ipc::socket_path());
+    }
+    crate::daemon::remove_pidfile();
+}
+
+fn restore_terminal() {
+    if TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = crossterm::terminal::disable_raw_mode();
+        let _ = crossterm::execute!(
+            std::io::stdout(),
+            crossterm::cursor::Show,
+            crossterm::terminal::LeaveAlternateScreen
+        );
+    }
+}
+
+fn write_report(info: &PanicHookInfo<'_>) -> std::io::Result<PathBuf> {
+    let dir = dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("cavibe");
+    std::fs::create_dir_all(&dir)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("crash-{}.log", timestamp));
+
+    let mut report = String::new();
+    let _ = writeln!(report, "cavibe {} crash report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "time: {} (unix)", timestamp);
+    let _ = writeln!(report, "thread: {}", std::thread::current().name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "panic: {}", info);
+    let _ = writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture());
+    let _ = writeln!(report, "recent log:");
+    let ring = LOG_RING.lock().unwrap_or_else(|e| e.into_inner());
+    for line in ring.iter() {
+        let _ = writeln!(report, "{}", line);
+    }
+
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
@@ -0,0 +1,145 @@
+//! Background lifecycle: `cavibe daemon`, `cavibe stop` and `cavibe restart`.
+//!
+//! The instance that owns the IPC socket writes its pid next to it
+//! (`cavibe.pid`) for service managers. `daemon` relaunches cavibe detached in wallpaper mode
+//! and waits until it answers on the socket; `stop` asks the running instance
+//! to quit over IPC and waits for the socket to go away. SIGTERM and SIGINT
+//! make the render loops exit the same clean way.
+
+use anyhow::{bail, Context, Result};
+use std::ffi::OsString;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{info, warn};
+
+use crate::ipc;
+
+/// How long to wait for an instance to come up or shut down
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set when the render loop should exit (signal or `quit` command)
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+/// `$XDG_RUNTIME_DIR/cavibe.pid`, next to the IPC socket
+pub fn pid_path() -> PathBuf {
+    ipc::socket_path().with_extension("pid")
+}
+
+/// Where a daemonized instance's output goes
+fn log_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("cavibe")
+        .join("daemon.log")
+}
+
+/// Record our pid. Only the instance that bound the IPC socket calls this,
+/// so a second instance never overwrites the daemon's pid.
+pub fn write_pidfile() {
+    if let Err(e) = std::fs::write(pid_path(), format!("{}\n", std::process::id())) {
+        warn!("Failed to write pid file {}: {}", pid_path().display(), e);
+    }
+}
+
+/// Remove the pid file if it is still ours.
+pub fn remove_pidfile() {
+    let path = pid_path();
+    let ours = std::fs::read_to_string(&path).is_ok_and(|pid| pid.trim() == std::process::id().to_string());
+    if ours {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Ask the render loop to exit at the end of its current frame.
+pub fn request_shutdown() {
+    SHUTDOWN.store(true, Ordering::SeqCst);
+}
+
+pub fn shutdown_requested() -> bool {
+    SHUTDOWN.load(Ordering::SeqCst)
+}
+
+/// Turn SIGTERM/SIGINT into a clean shutdown; a second signal exits at once,
+/// still restoring the terminal and removing the socket and pid file.
+pub fn install_signal_handlers() {
+    let (Ok(mut term), Ok(mut int)) = (signal(SignalKind::terminate()), signal(SignalKind::interrupt())) else {
+        warn!("Failed to install signal handlers");
+        return;
+    };
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = term.recv() => {}
+            _ = int.recv() => {}
+        }
+        info!("Received termination signal, shutting down");
+        request_shutdown();
+        tokio::select! {
+            _ = term.recv() => {}
+            _ = int.recv() => {}
+        }
+        crate::crash::teardown();
+        std::process::exit(130);
+    });
+}
+
+/// Whether an instance answers on the IPC socket
+pub async fn is_running() -> bool {
+    ipc::send_command("ping", false).await.is_ok()
+}
+
+/// Start cavibe detached with `args` (the command line without the
+/// subcommand) and wait until it answers on the IPC socket. Returns its pid.
+pub async fn spawn(args: Vec<OsString>) -> Result<u32> {
+    if is_running().await {
+        bail!("cavibe is already running (stop it with `cavibe stop`)");
+    }
+
+    let log = log_path();
+    if let Some(parent) = log.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let log_file = std::fs::File::create(&log).with_context(|| format!("Failed to create {}", log.display()))?;
+    let exe = std::env::current_exe().context("Could not locate the cavibe executable")?;
+
+    let mut child = std::process::Command::new(exe)
+        .args(args)
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file)
+        // Own process group, so the launching terminal's Ctrl-C or hangup
+        // doesn't reach it
+        .process_group(0)
+        .spawn()
+        .context("Failed to start cavibe")?;
+
+    let start = Instant::now();
+    while start.elapsed() < WAIT_TIMEOUT {
+        if is_running().await {
+            return Ok(child.id());
+        }
+        if let Some(status) = child.try_wait()? {
+            bail!("cavibe exited during startup ({}); see {}", status, log.display());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    bail!("cavibe didn't respond within {}s; see {}", WAIT_TIMEOUT.as_secs(), log.display())
+}
+
+/// Ask the running instance to quit and wait until it has.
+pub async fn stop() -> Result<()> {
+    ipc::send_command("quit", false).await?;
+    let start = Instant::now();
+    while start.elapsed() < WAIT_TIMEOUT {
+        if !ipc::socket_path().exists() {
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    bail!("cavibe didn't exit within {}s", WAIT_TIMEOUT.as_secs())
+}
@@ -0,0 +1,614 @@
+//! GPU presentation for the Wayland wallpaper (`renderer.backend = "gpu"`,
+//! built with the `gpu` feature).
+//!
+//! A surface drawn by the GPU gets a wgpu swapchain on its wl_surface
+//! instead of shm buffers. Frames with a triangle form
+//! ([`renderer::geometry`]) have their bars or waveform drawn by the GPU,
+//! with the pixel renderer only drawing what goes on top (text, album art,
+//! widgets) into an overlay texture that is updated where it changed. Other
+//! frames are drawn entirely by the pixel renderer and shown as that texture.
+
+use anyhow::{anyhow, bail, Context, Result};
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle};
+use std::ptr::NonNull;
+use tracing::{debug, info};
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::{Connection, Proxy};
+
+use crate::renderer::damage::DamageRect;
+use crate::renderer::geometry::Vertex;
+use crate::renderer::Canvas;
+
+/// Multisampling for the waveform's diagonal edges (when the format allows it)
+const SAMPLES: u32 = 4;
+
+const SHADER: &str = r#"
+struct ShapeOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_shape(@location(0) position: vec2<f32>, @location(1) color: vec4<f32>) -> ShapeOut {
+    return ShapeOut(vec4<f32>(position, 0.0, 1.0), color);
+}
+
+@fragment
+fn fs_shape(in: ShapeOut) -> @location(0) vec4<f32> {
+    return in.color;
+}
+
+@group(0) @binding(0) var overlay: texture_2d<f32>;
+@group(0) @binding(1) var overlay_sampler: sampler;
+
+struct OverlayOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// One triangle covering the whole surface
+@vertex
+fn vs_overlay(@builtin(vertex_index) index: u32) -> OverlayOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return OverlayOut(vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0), uv);
+}
+
+@fragment
+fn fs_overlay(in: OverlayOut) -> @location(0) vec4<f32> {
+    return textureSample(overlay, overlay_sampler, in.uv);
+}
+"#;
+
+// Two f32 for the position and four for the color, no padding
+unsafe impl bytemuck::Zeroable for Vertex {}
+unsafe impl bytemuck::Pod for Vertex {}
+
+/// The GPU device shared by all surfaces, set up with the first one
+#[derive(Default)]
+pub enum Gpu {
+    #[default]
+    Untried,
+    Ready(Box<GpuContext>),
+    /// Setting up failed; surfaces use shm buffers
+    Unavailable,
+}
+
+impl Gpu {
+    /// Whether surfaces should try the GPU
+    pub fn available(&self) -> bool {
+        !matches!(self, Gpu::Unavailable)
+    }
+
+    /// Put a GPU swapchain on `wl_surface`, setting the device up first if
+    /// this is the first surface. A failed setup makes the GPU unavailable.
+    pub fn surface(&mut self, connection: &Connection, wl_surface: &WlSurface, width: u32, height: u32) -> Result<GpuSurface> {
+        let instance = match self {
+            Gpu::Unavailable => bail!("GPU rendering is unavailable"),
+            Gpu::Ready(context) => return GpuSurface::new(context, connection, wl_surface, width, height),
+            Gpu::Untried => wgpu::Instance::new(&wgpu::InstanceDescriptor {
+                backends: wgpu::Backends::VULKAN | wgpu::Backends::GL,
+                ..Default::default()
+            }),
+        };
+        let setup = create_surface(&instance, connection, wl_surface).and_then(|surface| {
+            let context = GpuContext::new(instance, &surface)?;
+            Ok((context, surface))
+        });
+        match setup {
+            Ok((context, surface)) => {
+                let context = self.insert(context);
+                GpuSurface::with_surface(context, surface, width, height)
+            }
+            Err(e) => {
+                *self = Gpu::Unavailable;
+                Err(e)
+            }
+        }
+    }
+
+    fn insert(&mut self, context: GpuContext) -> &GpuContext {
+        *self = Gpu::Ready(Box::new(context));
+        match self {
+            Gpu::Ready(context) => context,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn context(&self) -> Option<&GpuContext> {
+        match self {
+            Gpu::Ready(context) => Some(context),
+            _ => None,
+        }
+    }
+}
+
+/// A wgpu surface for a wl_surface of `connection`
+fn create_surface(instance: &wgpu::Instance, connection: &Connection, wl_surface: &WlSurface) -> Result<wgpu::Surface<'static>> {
+    let display = NonNull::new(connection.backend().display_ptr().cast()).context("No Wayland display")?;
+    let window = NonNull::new(wl_surface.id().as_ptr().cast()).context("Wayland surface is gone")?;
+    let target = wgpu::SurfaceTargetUnsafe::RawHandle {
+        raw_display_handle: RawDisplayHandle::Wayland(WaylandDisplayHandle::new(display)),
+        raw_window_handle: RawWindowHandle::Wayland(WaylandWindowHandle::new(window)),
+    };
+    // Safety: the display outlives the surface, and the GpuSurface holding
+    // the wgpu surface is dropped before its wl_surface (see `OutputSurface`)
+    unsafe { instance.create_surface_unsafe(target) }.context("Failed to create a GPU surface")
+}
+
+/// Device, queue and pipelines
+pub struct GpuContext {
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    format: wgpu::TextureFormat,
+    samples: u32,
+    shape_pipeline: wgpu::RenderPipeline,
+    overlay_pipeline: wgpu::RenderPipeline,
+    overlay_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+}
+
+impl GpuContext {
+    fn new(instance: wgpu::Instance, surface: &wgpu::Surface) -> Result<Self> {
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            force_fallback_adapter: false,
+            compatible_surface: Some(surface),
+        }))
+        .context("No GPU adapter can draw to the Wayland surface")?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("cavibe"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+                memory_hints: wgpu::MemoryHints::MemoryUsage,
+            },
+            None,
+        ))
+        .context("Failed to open the GPU device")?;
+        let info = adapter.get_info();
+        info!("GPU rendering on {} ({:?})", info.name, info.backend);
+
+        // Frames are already sRGB-encoded; a plain format keeps them as they are
+        let capabilities = surface.get_capabilities(&adapter);
+        let format = capabilities
+            .formats
+            .iter()
+            .copied()
+            .find(|format| !format.is_srgb())
+            .or_else(|| capabilities.formats.first().copied())
+            .context("The GPU surface supports no formats")?;
+        let samples = if adapter.get_texture_format_features(format).flags.sample_count_supported(SAMPLES) {
+            SAMPLES
+        } else {
+            1
+        };
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("cavibe shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let overlay_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("overlay"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline = |label: &str, layouts: &[&wgpu::BindGroupLayout], entry: &str, buffers: &[wgpu::VertexBufferLayout]| {
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: layouts,
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some(&format!("vs_{}", entry)),
+                    compilation_options: Default::default(),
+                    buffers,
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState { count: samples, ..Default::default() },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(&format!("fs_{}", entry)),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        // The canvas and the vertex colors are pre-multiplied
+                        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let shape_pipeline = pipeline(
+            "shapes",
+            &[],
+            "shape",
+            &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4],
+            }],
+        );
+        let overlay_pipeline = pipeline("overlay", &[&overlay_layout], "overlay", &[]);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("overlay"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Ok(Self {
+            instance,
+            adapter,
+            device,
+            queue,
+            format,
+            samples,
+            shape_pipeline,
+            overlay_pipeline,
+            overlay_layout,
+            sampler,
+        })
+    }
+}
+
+/// The overlay texture and its binding, at the size of the canvas it holds
+struct Overlay {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    width: u32,
+    height: u32,
+}
+
+/// A wl_surface presented through wgpu
+pub struct GpuSurface {
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    /// Multisampled target resolved into the swapchain image (None without MSAA)
+    msaa: Option<wgpu::TextureView>,
+    overlay: Option<Overlay>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    /// Triangles of the last frame shown, to skip unchanged frames
+    last_vertices: Vec<Vertex>,
+}
+
+impl GpuSurface {
+    fn new(context: &GpuContext, connection: &Connection, wl_surface: &WlSurface, width: u32, height: u32) -> Result<Self> {
+        let surface = create_surface(&context.instance, connection, wl_surface)?;
+        Self::with_surface(context, surface, width, height)
+    }
+
+    fn with_surface(context: &GpuContext, surface: wgpu::Surface<'static>, width: u32, height: u32) -> Result<Self> {
+        let capabilities = surface.get_capabilities(&context.adapter);
+        if !capabilities.formats.contains(&context.format) {
+            bail!("The GPU surface doesn't support {:?}", context.format);
+        }
+        // Don't block the main loop on vblank; it paces the frames itself
+        let present_mode = [wgpu::PresentMode::Mailbox, wgpu::PresentMode::Immediate]
+            .into_iter()
+            .find(|mode| capabilities.present_modes.contains(mode))
+            .unwrap_or(wgpu::PresentMode::Fifo);
+        // Transparent wallpapers need the compositor to blend the frame
+        let alpha_mode = [wgpu::CompositeAlphaMode::PreMultiplied, wgpu::CompositeAlphaMode::Inherit]
+            .into_iter()
+            .find(|mode| capabilities.alpha_modes.contains(mode))
+            .unwrap_or(capabilities.alpha_modes[0]);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: context.format,
+            width,
+            height,
+            present_mode,
+            desired_maximum_frame_latency: 2,
+            alpha_mode,
+            view_formats: Vec::new(),
+        };
+        let mut gpu_surface = Self {
+            surface,
+            config,
+            msaa: None,
+            overlay: None,
+            vertex_buffer: None,
+            last_vertices: Vec::new(),
+        };
+        gpu_surface.configure(context);
+        debug!("GPU surface {}x{} ({:?}, {:?})", width, height, present_mode, alpha_mode);
+        Ok(gpu_surface)
+    }
+
+    fn configure(&mut self, context: &GpuContext) {
+        self.surface.configure(&context.device, &self.config);
+        self.msaa = (context.samples > 1).then(|| {
+            context
+                .device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("msaa"),
+                    size: wgpu::Extent3d { width: self.config.width, height: self.config.height, depth_or_array_layers: 1 },
+                    mip_level_count: 1,
+                    sample_count: context.samples,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: context.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&Default::default())
+        });
+        self.last_vertices.clear();
+    }
+
+    /// Present `shapes` (in canvas pixels of `canvas`'s size) with `canvas`
+    /// drawn over them. `damage` lists what changed in the canvas since the
+    /// last frame (None = all of it).
+    pub fn present(
+        &mut self,
+        context: &GpuContext,
+        (width, height): (u32, u32),
+        canvas: &Canvas,
+        damage: Option<&[DamageRect]>,
+        shapes: &mut Vec<Vertex>,
+    ) -> Result<()> {
+        if (self.config.width, self.config.height) != (width, height) {
+            self.config.width = width;
+            self.config.height = height;
+            self.configure(context);
+        }
+        let canvas_changed = self.upload_overlay(context, canvas, damage);
+        if !canvas_changed && *shapes == self.last_vertices {
+            return Ok(());
+        }
+        std::mem::swap(shapes, &mut self.last_vertices);
+
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            // Resized or lost under us: set up again and draw the next frame
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                self.configure(context);
+                return Ok(());
+            }
+            // The compositor holds every image; drop this frame
+            Err(wgpu::SurfaceError::Timeout) => {
+                self.last_vertices.clear();
+                return Ok(());
+            }
+            Err(e) => return Err(anyhow!("Failed to get a GPU frame: {}", e)),
+        };
+        let view = frame.texture.create_view(&Default::default());
+
+        let vertices = self.upload_vertices(context);
+        let mut encoder = context.device.create_command_encoder(&Default::default());
+        let (target, resolve_target) = match &self.msaa {
+            Some(msaa) => (msaa, Some(&view)),
+            None => (&view, None),
+        };
+        self.record_frame(context, &mut encoder, target, resolve_target, vertices);
+        context.queue.submit([encoder.finish()]);
+        frame.present();
+        Ok(())
+    }
+
+    /// Read back the last frame presented, bars and overlay together, as a
+    /// canvas (for `screenshot`).
+    pub fn read_frame(&mut self, context: &GpuContext) -> Result<Canvas> {
+        let swap_red_blue = match context.format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            format => bail!("Can't read back {:?} frames", format),
+        };
+        let (width, height) = (self.config.width, self.config.height);
+        let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("readback"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: context.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+        // Rows of a texture copy are padded to the copy alignment
+        let row_bytes = width * 4;
+        let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: padded_row_bytes as u64 * height as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let vertices = self.upload_vertices(context);
+        let mut encoder = context.device.create_command_encoder(&Default::default());
+        let (target, resolve_target) = match &self.msaa {
+            Some(msaa) => (msaa, Some(&view)),
+            None => (&view, None),
+        };
+        self.record_frame(context, &mut encoder, target, resolve_target, vertices);
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        context.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        context.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .context("The GPU readback was dropped")?
+            .map_err(|e| anyhow!("Failed to read the GPU frame: {}", e))?;
+
+        let mut canvas = Canvas::new(width as usize, height as usize);
+        {
+            let data = slice.get_mapped_range();
+            for (src, dest) in data
+                .chunks_exact(padded_row_bytes as usize)
+                .zip(canvas.data.chunks_exact_mut(row_bytes as usize))
+            {
+                dest.copy_from_slice(&src[..row_bytes as usize]);
+                if swap_red_blue {
+                    for px in dest.chunks_exact_mut(4) {
+                        px.swap(0, 2);
+                    }
+                }
+            }
+        }
+        buffer.unmap();
+        Ok(canvas)
+    }
+
+    /// Write the last frame's triangles, in clip space, to the vertex buffer.
+    /// Returns how many there are.
+    fn upload_vertices(&mut self, context: &GpuContext) -> usize {
+        // Pixels to clip space, with the overlay at the canvas size
+        let (width, height) = self.overlay.as_ref().map_or((self.config.width, self.config.height), |o| (o.width, o.height));
+        let (sx, sy) = (2.0 / width.max(1) as f32, 2.0 / height.max(1) as f32);
+        let clip: Vec<Vertex> = self
+            .last_vertices
+            .iter()
+            .map(|v| Vertex { position: [v.position[0] * sx - 1.0, 1.0 - v.position[1] * sy], color: v.color })
+            .collect();
+        let bytes: &[u8] = bytemuck::cast_slice(&clip);
+        if bytes.is_empty() {
+            return 0;
+        }
+        if self.vertex_buffer.as_ref().is_none_or(|buffer| buffer.size() < bytes.len() as u64) {
+            self.vertex_buffer = Some(context.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("shapes"),
+                size: (bytes.len() as u64).next_power_of_two(),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+        if let Some(buffer) = &self.vertex_buffer {
+            context.queue.write_buffer(buffer, 0, bytes);
+        }
+        clip.len()
+    }
+
+    /// Record the pass drawing `vertices` uploaded triangles and the overlay
+    /// into `target` (resolved into `resolve_target` when multisampled)
+    fn record_frame(
+        &self,
+        context: &GpuContext,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        resolve_target: Option<&wgpu::TextureView>,
+        vertices: usize,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("frame"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some(buffer) = self.vertex_buffer.as_ref().filter(|_| vertices > 0) {
+            pass.set_pipeline(&context.shape_pipeline);
+            pass.set_vertex_buffer(0, buffer.slice(..(vertices * std::mem::size_of::<Vertex>()) as u64));
+            pass.draw(0..vertices as u32, 0..1);
+        }
+        if let Some(overlay) = &self.overlay {
+            pass.set_pipeline(&context.overlay_pipeline);
+            pass.set_bind_group(0, &overlay.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+
+    /// Copy what changed in `canvas` into the overlay texture. Returns
+    /// whether anything did.
+    fn upload_overlay(&mut self, context: &GpuContext, canvas: &Canvas, damage: Option<&[DamageRect]>) -> bool {
+        let (width, height) = (canvas.width as u32, canvas.height as u32);
+        if width == 0 || height == 0 {
+            return false;
+        }
+        let fits = self.overlay.as_ref().is_some_and(|overlay| (overlay.width, overlay.height) == (width, height));
+        let damage = if fits { damage } else { None };
+        if !fits {
+            self.overlay = Some(create_overlay(context, width, height));
+        }
+        let Some(overlay) = &self.overlay else {
+            return false;
+        };
+        let full = [DamageRect { x: 0, y: 0, width: canvas.width, height: canvas.height }];
+        let rects = damage.unwrap_or(&full);
+        for rect in rects {
+            context.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &overlay.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: rect.x as u32, y: rect.y as u32, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &canvas.data[..canvas.width * canvas.height * 4],
+                wgpu::TexelCopyBufferLayout {
+                    offset: ((rect.y * canvas.width + rect.x) * 4) as u64,
+                    bytes_per_row: Some(width * 4),
+                    rows_per_image: Some(rect.height as u32),
+                },
+                wgpu::Extent3d { width: rect.width as u32, height: rect.height as u32, depth_or_array_layers: 1 },
+            );
+        }
+        !rects.is_empty()
+    }
+}
+
+fn create_overlay(context: &GpuContext, width: u32, height: u32) -> Overlay {
+    let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("overlay"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&Default::default());
+    let bind_group = context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("overlay"),
+        layout: &context.overlay_layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&context.sampler) },
+        ],
+    });
+    Overlay { texture, bind_group, width, height }
+}
@@ -0,0 +1,254 @@
+//! Full-resolution terminal output over the kitty graphics protocol or sixel.
+//!
+//! Instead of two pixels per cell, the canvas is rendered at the terminal's
+//! pixel size and sent as an image covering the visualizer cells. Frames
+//! identical to the last one sent are skipped, which keeps a paused or idle
+//! visualizer from streaming images.
+
+use anyhow::Result;
+use base64::Engine;
+use crossterm::{cursor::MoveTo, queue};
+use flate2::{write::ZlibEncoder, Compression};
+use std::io::Write;
+
+use crate::config::TerminalGraphics;
+use crate::renderer::Canvas;
+
+/// Graphics protocol the terminal is driven with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+impl Protocol {
+    /// The protocol for `setting`, guessing from the environment for `auto`
+    /// (`None` = half-blocks)
+    pub fn select(setting: TerminalGraphics) -> Option<Self> {
+        match setting {
+            TerminalGraphics::Blocks => None,
+            TerminalGraphics::Kitty => Some(Protocol::Kitty),
+            TerminalGraphics::Sixel => Some(Protocol::Sixel),
+            TerminalGraphics::Auto => detect(),
+        }
+    }
+
+    /// Canvas size for `cols`×`rows` cells of `cell_w`×`cell_h` pixels.
+    /// Sixel images are drawn in bands of six rows, so their height is
+    /// rounded down to keep the image off the rows below.
+    pub fn canvas_size(self, cols: u16, rows: u16, (cell_w, cell_h): (u16, u16)) -> (usize, usize) {
+        let width = cols as usize * cell_w as usize;
+        let height = rows as usize * cell_h as usize;
+        match self {
+            Protocol::Kitty => (width, height),
+            Protocol::Sixel => (width, height - height % 6),
+        }
+    }
+}
+
+/// Guess the protocol from environment variables the terminals set. Inside
+/// tmux or screen images need passthrough, so half-blocks are used there.
+fn detect() -> Option<Protocol> {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    let program = var("TERM_PROGRAM");
+    if std::env::var_os("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux") {
+        return None;
+    }
+    if std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || term == "xterm-kitty"
+        || term == "xterm-ghostty"
+        || program == "WezTerm"
+        || program == "ghostty"
+    {
+        return Some(Protocol::Kitty);
+    }
+    if ["foot", "mlterm", "contour"].iter().any(|t| term.starts_with(t)) || std::env::var_os("KONSOLE_VERSION").is_some() {
+        return Some(Protocol::Sixel);
+    }
+    None
+}
+
+/// Size of a terminal cell in pixels, if the terminal reports its pixel size
+pub fn cell_size() -> Option<(u16, u16)> {
+    let size = crossterm::terminal::window_size().ok()?;
+    if size.columns == 0 || size.rows == 0 {
+        return None;
+    }
+    let cell = (size.width / size.columns, size.height / size.rows);
+    (cell.0 > 0 && cell.1 > 0).then_some(cell)
+}
+
+/// Base64 characters per kitty escape sequence, the protocol's limit
+const KITTY_CHUNK: usize = 4096;
+/// Colors in the sixel palette: a 6×6×6 color cube
+const SIXEL_COLORS: usize = 216;
+
+/// Sends frames to the terminal as images.
+pub struct GraphicsOutput {
+    pub protocol: Protocol,
+    /// The canvas last sent, to skip unchanged frames
+    last: Vec<u8>,
+    last_size: (usize, usize),
+    /// Kitty: id of the image on screen, alternating so the new image is
+    /// placed before the old one is deleted
+    image_id: u32,
+    pixels: Vec<u8>,
+    out: Vec<u8>,
+    /// Sixel: per color, one sixel per column of the current band
+    masks: Vec<u8>,
+    /// Sixel: per color, the columns it appears in within the current band
+    spans: Vec<Option<(usize, usize)>>,
+}
+
+impl GraphicsOutput {
+    pub fn new(protocol: Protocol) -> Self {
+        Self {
+            protocol,
+            last: Vec::new(),
+            last_size: (0, 0),
+            image_id: 1,
+            pixels: Vec::new(),
+            out: Vec::new(),
+            masks: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Draw `canvas` with its top-left corner in the top-left cell.
+    pub fn draw(&mut self, stdout: &mut impl Write, canvas: &Canvas) -> Result<()> {
+        let len = canvas.width * canvas.height * 4;
+        let size = (canvas.width, canvas.height);
+        if size == self.last_size && self.last[..] == canvas.data[..len] {
+            return Ok(());
+        }
+        self.last.clear();
+        self.last.extend_from_slice(&canvas.data[..len]);
+        self.last_size = size;
+
+        self.out.clear();
+        match self.protocol {
+            Protocol::Kitty => self.encode_kitty(canvas)?,
+            Protocol::Sixel => self.encode_sixel(canvas),
+        }
+        queue!(stdout, MoveTo(0, 0))?;
+        stdout.write_all(&self.out)?;
+        Ok(())
+    }
+
+    /// Send the next frame even if it matches the last one, e.g. after the
+    /// screen was cleared.
+    pub fn invalidate(&mut self) {
+        self.last_size = (0, 0);
+    }
+
+    /// Remove the image from the screen, e.g. before leaving or when
+    /// falling back to half-blocks.
+    pub fn clear(&mut self, stdout: &mut impl Write) -> Result<()> {
+        if self.protocol == Protocol::Kitty && self.last_size != (0, 0) {
+            stdout.write_all(b"\x1b_Ga=d,d=A,q=2\x1b\\")?;
+        }
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Zlib-compressed RGBA, sent in chunks, placed without moving the cursor
+    fn encode_kitty(&mut self, canvas: &Canvas) -> Result<()> {
+        canvas.write_straight_rgba(&mut self.pixels);
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&self.pixels)?;
+        let payload = base64::engine::general_purpose::STANDARD.encode(encoder.finish()?);
+
+        let old_id = self.image_id;
+        self.image_id = 3 - old_id;
+        let chunks = payload.as_bytes().chunks(KITTY_CHUNK);
+        let count = chunks.len();
+        for (i, chunk) in chunks.enumerate() {
+            let more = (i + 1 < count) as u8;
+            if i == 0 {
+                write!(
+                    self.out,
+                    "\x1b_Ga=T,f=32,o=z,s={},v={},i={},q=2,C=1,m={};",
+                    canvas.width, canvas.height, self.image_id, more
+                )?;
+            } else {
+                write!(self.out, "\x1b_Gm={};", more)?;
+            }
+            self.out.extend_from_slice(chunk);
+            self.out.extend_from_slice(b"\x1b\\");
+        }
+        write!(self.out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", old_id)?;
+        Ok(())
+    }
+
+    /// Sixels quantized to the color cube; transparent pixels are left to
+    /// the terminal background
+    fn encode_sixel(&mut self, canvas: &Canvas) {
+        let (width, height) = (canvas.width, canvas.height);
+        // Quantize the unblended colors, or faint pixels would come out dark
+        canvas.write_straight_rgba(&mut self.pixels);
+        let out = &mut self.out;
+        out.extend_from_slice(b"\x1bP0;0;0q");
+        out.extend_from_slice(format!("\"1;1;{};{}", width, height).as_bytes());
+        for i in 0..SIXEL_COLORS {
+            let percent = |level: usize| level * 100 / 5;
+            out.extend_from_slice(format!("#{};2;{};{};{}", i, percent(i / 36), percent(i / 6 % 6), percent(i % 6)).as_bytes());
+        }
+
+        self.masks.clear();
+        self.masks.resize(SIXEL_COLORS * width, 0);
+        self.spans.clear();
+        self.spans.resize(SIXEL_COLORS, None);
+        for band in (0..height).step_by(6) {
+            for dy in 0..6.min(height - band) {
+                for x in 0..width {
+                    let i = ((band + dy) * width + x) * 4;
+                    let px = &self.pixels[i..i + 4];
+                    if px[3] == 0 {
+                        continue;
+                    }
+                    let level = |c: u8| (c as usize * 5 + 127) / 255;
+                    let color = level(px[0]) * 36 + level(px[1]) * 6 + level(px[2]);
+                    self.masks[color * width + x] |= 1 << dy;
+                    let span = self.spans[color].get_or_insert((x, x));
+                    span.1 = x;
+                }
+            }
+
+            let mut first = true;
+            for color in 0..SIXEL_COLORS {
+                let Some((start, end)) = self.spans[color].take() else {
+                    continue;
+                };
+                if !first {
+                    out.push(b'$');
+                }
+                first = false;
+                out.extend_from_slice(format!("#{}", color).as_bytes());
+                push_run(out, b'?', start);
+                let row = &mut self.masks[color * width + start..=color * width + end];
+                let mut x = 0;
+                while x < row.len() {
+                    let run = row[x..].iter().take_while(|&&m| m == row[x]).count();
+                    push_run(out, b'?' + row[x], run);
+                    x += run;
+                }
+                row.fill(0);
+            }
+            if band + 6 < height {
+                out.push(b'-');
+            }
+        }
+        out.extend_from_slice(b"\x1b\\");
+    }
+}
+
+/// `count` repetitions of the sixel `ch`, run-length encoded when shorter
+fn push_run(out: &mut Vec<u8>, ch: u8, count: usize) {
+    if count > 3 {
+        out.extend_from_slice(format!("!{}", count).as_bytes());
+        out.push(ch);
+    } else {
+        out.resize(out.len() + count, ch);
+    }
+}
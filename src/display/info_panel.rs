@@ -0,0 +1,161 @@
+//! Terminal mode now-playing panel, drawn beside the visualizer.
+//!
+//! Widgets are laid out and rendered into a ratatui `Buffer`, which is then
+//! written cell by cell with crossterm like the rest of the terminal output.
+
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    execute,
+    style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::Line,
+    widgets::{Block, Borders, LineGauge, Paragraph, Widget},
+};
+use std::io::Write;
+use std::time::Duration;
+
+use crate::metadata::TrackInfo;
+
+/// Narrowest visualizer pane kept when the panel is shown
+pub const MIN_VISUALIZER_COLS: u16 = 20;
+
+/// Everything the panel displays for one frame.
+pub struct PanelInfo<'a> {
+    pub track: &'a TrackInfo,
+    pub style: &'a str,
+    pub color: &'a str,
+    /// Next style and time until it rotates in, when rotation is enabled
+    pub next_rotation: Option<(String, Duration)>,
+    pub source: &'a str,
+    /// Input level before sensitivity scaling (0.0-1.0)
+    pub level: f32,
+    pub intensity: f32,
+    pub bars: usize,
+    pub fps: f32,
+    /// Accent color taken from the active color scheme
+    pub accent: (u8, u8, u8),
+}
+
+/// Split the visualizer rows into visualizer and panel areas.
+/// Returns None for the panel when the terminal is too narrow to fit it.
+pub fn split(area: Rect, panel_width: u16) -> (Rect, Option<Rect>) {
+    if area.width < panel_width + MIN_VISUALIZER_COLS {
+        return (area, None);
+    }
+    let [visualizer, panel] =
+        Layout::horizontal([Constraint::Min(MIN_VISUALIZER_COLS), Constraint::Length(panel_width)]).areas(area);
+    (visualizer, Some(panel))
+}
+
+/// Draw the panel into `area` of the terminal.
+pub fn render(stdout: &mut impl Write, area: Rect, info: &PanelInfo) -> Result<()> {
+    let mut buf = Buffer::empty(area);
+    let (r, g, b) = info.accent;
+    let accent = Color::Rgb(r, g, b);
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(dim)
+        .title(" Now Playing ");
+    let inner = block.inner(area);
+    block.render(area, &mut buf);
+
+    let [track_area, progress_area, _, stats_area, level_area, _] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(5),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .areas(inner);
+
+    let unknown = |s: &Option<String>| s.clone().unwrap_or_else(|| "-".to_string());
+    Paragraph::new(vec![
+        Line::styled(unknown(&info.track.title), Style::default().fg(accent).add_modifier(Modifier::BOLD)),
+        Line::from(unknown(&info.track.artist)),
+        Line::styled(unknown(&info.track.album), dim),
+    ])
+    .render(track_area, &mut buf);
+
+    let (ratio, label) = match (info.track.position, info.track.length) {
+        (Some(pos), Some(len)) if !len.is_zero() => (
+            (pos.as_secs_f64() / len.as_secs_f64()).clamp(0.0, 1.0),
+            format!("{} / {}", format_time(pos), format_time(len)),
+        ),
+        (Some(pos), _) => (0.0, format_time(pos)),
+        _ => (0.0, "--:--".to_string()),
+    };
+    LineGauge::default()
+        .filled_style(Style::default().fg(accent))
+        .unfilled_style(dim)
+        .line_set(symbols::line::THICK)
+        .label(label)
+        .ratio(ratio)
+        .render(progress_area, &mut buf);
+
+    let rotation = match &info.next_rotation {
+        Some((style, remaining)) => format!("{} in {}s", style, remaining.as_secs()),
+        None => "off".to_string(),
+    };
+    let stat = |name: &str, value: String| Line::from(format!("{:<9}{}", name, value));
+    Paragraph::new(vec![
+        stat("Style", info.style.to_string()),
+        stat("Color", info.color.to_string()),
+        stat("Next", rotation),
+        stat("Source", info.source.to_string()),
+        stat("Bars", format!("{}  {:.0} fps  {:.0}%", info.bars, info.fps, info.intensity * 100.0)),
+    ])
+    .render(stats_area, &mut buf);
+
+    LineGauge::default()
+        .filled_style(Style::default().fg(accent))
+        .unfilled_style(dim)
+        .label("Level    ")
+        .ratio(info.level.clamp(0.0, 1.0) as f64)
+        .render(level_area, &mut buf);
+
+    flush(stdout, &buf)
+}
+
+/// Write every cell of `buf` to the terminal at its own position.
+fn flush(stdout: &mut impl Write, buf: &Buffer) -> Result<()> {
+    let area = buf.area;
+    for y in area.top()..area.bottom() {
+        execute!(stdout, MoveTo(area.left(), y))?;
+        for x in area.left()..area.right() {
+            let cell = &buf[(x, y)];
+            let bold = cell.modifier.contains(Modifier::BOLD);
+            if bold {
+                execute!(stdout, SetAttribute(Attribute::Bold))?;
+            }
+            execute!(
+                stdout,
+                SetForegroundColor(cell.fg.into()),
+                SetBackgroundColor(cell.bg.into()),
+                Print(cell.symbol())
+            )?;
+            if bold {
+                execute!(stdout, SetAttribute(Attribute::NormalIntensity))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Format a duration as m:ss (or h:mm:ss for long tracks).
+fn format_time(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
@@ -1,6 +1,12 @@
+mod graphics;
+mod info_panel;
 pub mod terminal;
 pub mod wallpaper;
 
+#[cfg(feature = "gpu")]
+mod gpu;
+#[cfg(feature = "wayland")]
+mod swapchain;
 #[cfg(feature = "wayland")]
 pub mod wayland;
 
@@ -13,4 +19,6 @@ pub enum DisplayMode {
     #[default]
     Terminal,
     Wallpaper,
+    /// A regular, resizable desktop window (Wayland xdg-shell)
+    Window,
 }
@@ -0,0 +1,66 @@
+//! Per-surface wl_shm buffer swapchain.
+//!
+//! Each surface keeps up to `BUFFER_COUNT` buffers of its current size in one
+//! slot pool and reuses them. A buffer is busy from `attach_to` until the
+//! compositor releases it, and is never written while busy; when every buffer
+//! is busy the frame is skipped instead of drawing into one being read.
+//! Allocation failures are returned rather than fatal, so a transient
+//! shortage of shared memory costs a frame instead of the daemon.
+
+use anyhow::{Context, Result};
+use smithay_client_toolkit::shm::{
+    slot::{Buffer, SlotPool},
+    Shm,
+};
+use wayland_client::protocol::wl_shm;
+
+/// Triple buffering: one on screen, one queued, one being drawn
+const BUFFER_COUNT: usize = 3;
+
+pub struct Swapchain {
+    pool: SlotPool,
+    buffers: Vec<Buffer>,
+    width: u32,
+    height: u32,
+}
+
+impl Swapchain {
+    pub fn new(shm: &Shm, width: u32, height: u32) -> Result<Self> {
+        let pool = SlotPool::new((width * height * 4) as usize * BUFFER_COUNT, shm)
+            .context("Failed to create slot pool")?;
+        Ok(Self {
+            pool,
+            buffers: Vec::with_capacity(BUFFER_COUNT),
+            width,
+            height,
+        })
+    }
+
+    /// Whether the buffers match a `width`×`height` surface
+    pub fn fits(&self, width: u32, height: u32) -> bool {
+        self.width == width && self.height == height
+    }
+
+    /// A buffer the compositor has released, with its pixels (ARGB8888).
+    /// Allocates until `BUFFER_COUNT` exist; None when all are still busy.
+    pub fn acquire(&mut self) -> Result<Option<(&Buffer, &mut [u8])>> {
+        if let Some(i) = self.buffers.iter().position(|b| !b.slot().has_active_buffers()) {
+            let buffer = &self.buffers[i];
+            return Ok(buffer.canvas(&mut self.pool).map(|canvas| (buffer, canvas)));
+        }
+        if self.buffers.len() >= BUFFER_COUNT {
+            return Ok(None);
+        }
+        let (buffer, canvas) = self
+            .pool
+            .create_buffer(
+                self.width as i32,
+                self.height as i32,
+                (self.width * 4) as i32,
+                wl_shm::Format::Argb8888,
+            )
+            .context("Failed to create buffer")?;
+        self.buffers.push(buffer);
+        Ok(self.buffers.last().map(|buffer| (buffer, canvas)))
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! Renders the visualizer to a pixel `Canvas` then converts each pair of
 //! vertical pixels into a terminal cell using the upper-half-block character
-//! `'▀'` with foreground = top pixel and background = bottom pixel.
+//! `'▀'` with foreground = top pixel and background = bottom pixel, or with
+//! `terminal.blocks` into braille dots (2×4 pixels per cell) or full blocks
+//! (one pixel per cell). With kitty or sixel `terminal_graphics`, the canvas
+//! is instead rendered at the terminal's pixel size and sent as an image
+//! (see [`super::graphics`]).
 
 use anyhow::Result;
 use crossterm::{
@@ -12,48 +16,59 @@ use crossterm::{
     style::{Color, Print, SetBackgroundColor, SetForegroundColor},
     terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use ratatui::layout::Rect;
 use std::io::{stdout, Write};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
+use super::graphics::{self, GraphicsOutput, Protocol};
+use super::info_panel;
 use crate::audio;
-use crate::color::ColorScheme;
-use crate::config::Config;
+use crate::color::{ColorDynamics, ColorScheme};
+use crate::config::{Config, SourceIndicatorConfig, TerminalBlocks, TextAlignment, TextConfig};
+use crate::hooks::{HookRunner, TrackChangeEffects};
+use crate::ipc::{IpcCommand, IpcResponse, PendingChanges};
 use crate::metadata::{self, TrackInfo};
+use crate::compositor::WorkspaceState;
+use crate::privacy::PrivacyState;
 use crate::renderer;
-use crate::visualizer::VisualizerState;
+use crate::stats::StatsRecorder;
+use crate::visualizer::{Rotator, VisualizerState};
 
-pub async fn run(config: Config) -> Result<()> {
+pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<()> {
     let mut stdout = stdout();
 
     // Setup terminal
     terminal::enable_raw_mode()?;
     execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+    crate::crash::set_terminal_active(true);
 
-    let result = run_app(&mut stdout, config).await;
+    let result = run_app(&mut stdout, config, ipc_rx).await;
 
     // Restore terminal
+    crate::crash::set_terminal_active(false);
     terminal::disable_raw_mode()?;
     execute!(stdout, Show, LeaveAlternateScreen)?;
 
     result
 }
 
-async fn run_app(stdout: &mut impl Write, config: Config) -> Result<()> {
+async fn run_app(
+    stdout: &mut impl Write,
+    mut config: Config,
+    mut ipc_rx: mpsc::Receiver<IpcCommand>,
+) -> Result<()> {
     // Start audio capture
-    let (_audio_capture, audio_rx) = audio::create_audio_pipeline(
-        config.visualizer.bars,
-        config.audio.smoothing,
-        config.audio.sensitivity,
-        config.audio.device.clone(),
-    )?;
+    let (mut _audio_capture, mut audio_rx) = audio::create_audio_pipeline_for(config.visualizer.bars, &config.audio, None)?;
 
     // Start metadata watcher
-    let metadata_rx = metadata::start_watcher();
+    let metadata_rx = metadata::start_watcher(config.album_art.enabled, config.visualizer.pause_on_media_pause);
 
     // Initialize visualizer state
     let mut visualizer = VisualizerState::new(config.visualizer.clone(), config.text.clone());
-    let mut color_scheme = config.visualizer.color_scheme;
+    let mut rotator = Rotator::new(config.display.rotate_order);
+    let mut color_scheme = config.visualizer.color_scheme.clone();
 
     let mut last_frame = Instant::now();
     let mut style_timer = Instant::now();
@@ -61,21 +76,52 @@ async fn run_app(stdout: &mut impl Write, config: Config) -> Result<()> {
 
     // Reusable pixel canvas
     let mut canvas = renderer::Canvas::new(0, 0);
+    let mut work_canvas = renderer::Canvas::new(0, 0);
+    let mut graphics_setting = config.display.terminal_graphics;
+    let mut graphics = Protocol::select(graphics_setting).map(GraphicsOutput::new);
 
     // Spectrogram history buffer
     let mut spectrogram_history: Vec<Vec<f32>> = Vec::new();
+    let mut amplitude_history: Vec<(f32, f32)> = Vec::new();
+    let mut particles = renderer::particles::ParticleSystem::default();
+    let mut fire = renderer::fire::FireState::default();
+    let mut vectorscope = renderer::vectorscope::VectorscopeState::default();
+    let mut vu = renderer::vu::VuState::default();
+    let mut sparks = renderer::sparks::SparkState::default();
+    let mut hooks = HookRunner::default();
+    let mut stats = StatsRecorder::default();
+    let mut color_dynamics = ColorDynamics::default();
+    let mut track_change = TrackChangeEffects::default();
+
+    // State controlled over IPC
+    let mut visible = true;
+    let mut privacy = PrivacyState::new(&config.privacy);
+    let mut workspace_rules = WorkspaceState::default();
+    let mut current_source: Option<String> = None;
+
+    // Info panel state
+    let mut show_panel = config.display.info_panel;
+    let mut fps = 0.0f32;
 
     loop {
+        // `cavibe stop` or SIGTERM
+        if crate::daemon::shutdown_requested() {
+            break;
+        }
+
         // Calculate delta time
         let now = Instant::now();
         let dt = now.duration_since(last_frame).as_secs_f32();
         last_frame = now;
+        if dt > 0.0 {
+            fps = fps * 0.9 + (1.0 / dt) * 0.1;
+        }
 
         // Auto-rotate styles if enabled
         if config.display.rotate_styles
             && style_timer.elapsed() > Duration::from_secs(config.display.rotation_interval_secs)
         {
-            visualizer.next_style();
+            visualizer.current_style = rotator.next(visualizer.current_style, renderer::styles::style_count());
             style_timer = Instant::now();
         }
 
@@ -85,6 +131,50 @@ async fn run_app(stdout: &mut impl Write, config: Config) -> Result<()> {
         // Get current audio and metadata
         let audio_data = audio_rx.borrow().clone();
         let track_info = metadata_rx.borrow().clone();
+        crate::web::publish(&audio_data);
+
+        if let Some(change) = track_change.update(&config.hooks.track_change, &config.metadata.rules, &track_info, dt) {
+            if change.next_color {
+                color_scheme = color_scheme.next();
+            }
+            if let Some(color) = change.color {
+                color_scheme = color;
+            }
+            if let Some(style) = change.style {
+                visualizer.current_style = style;
+            }
+        }
+        if let Some(style) = workspace_rules.update(&config.compositor) {
+            visualizer.current_style = style;
+        }
+
+        // Per-style bar count, sensitivity and layout
+        let overrides = config.style_override(&visualizer.current_style_name()).cloned().unwrap_or_default();
+        let audio_data = audio_data.reshaped(overrides.bars, overrides.sensitivity);
+
+        hooks.update(
+            &config.hooks,
+            &track_info,
+            &visualizer.current_style_name(),
+            color_scheme.name(),
+            audio_data.intensity,
+        );
+
+        if color_dynamics.update(&config.color_dynamics, &audio_data.frequencies, audio_data.intensity, dt) {
+            color_scheme = color_scheme.next();
+        }
+
+        stats.update(
+            &config.stats,
+            visualizer.current_style,
+            color_scheme.name(),
+            visible && !privacy.hides_all() && !workspace_rules.hides(),
+            dt,
+        );
+
+        let media_paused = config.visualizer.pause_on_media_pause && track_info.paused;
+        // Hooks still see the real track; the screen doesn't
+        let track_info = if privacy.active() { Arc::default() } else { track_info };
 
         // Get terminal size
         let (term_width, term_height) = terminal::size()?;
@@ -93,9 +183,54 @@ async fn run_app(stdout: &mut impl Write, config: Config) -> Result<()> {
             continue;
         }
 
-        // Canvas: width = terminal cols, height = terminal rows × 2 (half-block)
-        let canvas_w = term_width as usize;
-        let canvas_h = (term_height as usize).saturating_sub(1) * 2; // -1 row for status bar
+        // Two-line marquee reserves a row each for title and artist above the status bar
+        let track_rows: u16 = if config.text.two_line && (config.text.show_title || config.text.show_artist) {
+            2
+        } else {
+            0
+        };
+        let vis_rows = term_height.saturating_sub(1 + track_rows);
+
+        // Optional info panel takes the right-hand columns of the visualizer rows
+        let (vis_area, panel_area) = if show_panel {
+            info_panel::split(Rect::new(0, 0, term_width, vis_rows), config.display.info_panel_width)
+        } else {
+            (Rect::new(0, 0, term_width, vis_rows), None)
+        };
+
+        // `terminal_graphics` changed by a config reload
+        if config.display.terminal_graphics != graphics_setting {
+            if let Some(output) = graphics.as_mut() {
+                output.clear(stdout)?;
+            }
+            graphics_setting = config.display.terminal_graphics;
+            graphics = Protocol::select(graphics_setting).map(GraphicsOutput::new);
+        }
+        // Graphics need the cell size in pixels; without it, fall back to half-blocks
+        let cell = graphics.as_ref().and_then(|_| graphics::cell_size());
+        if let (Some(output), None) = (graphics.as_mut(), cell) {
+            output.clear(stdout)?;
+        }
+
+        // Canvas: width = visualizer cols, height = terminal rows × 2 (half-block),
+        // other multiples of the cells for braille and blocks, or the cells' pixel
+        // size with graphics. Bar sizes are in cells either way, segment sizes in
+        // half-block pixels.
+        let (canvas_w, canvas_h, cell_w, cell_h) = match (&graphics, cell) {
+            (Some(output), Some(cell)) => {
+                let (w, h) = output.protocol.canvas_size(vis_area.width, vis_rows, cell);
+                (w, h, cell.0 as usize, cell.1 as usize)
+            }
+            _ => {
+                let (cell_w, cell_h) = match config.terminal.blocks {
+                    TerminalBlocks::Halfblock => (1, 2),
+                    TerminalBlocks::Braille => (2, 4),
+                    TerminalBlocks::Block => (1, 1),
+                };
+                (vis_area.width as usize * cell_w, vis_rows as usize * cell_h, cell_w, cell_h)
+            }
+        };
+        let segment_px = |size: u16| (size as usize * cell_h / 2).max(size.min(1) as usize);
         if canvas_w == 0 || canvas_h == 0 {
             tokio::time::sleep(Duration::from_millis(100)).await;
             continue;
@@ -104,46 +239,146 @@ async fn run_app(stdout: &mut impl Write, config: Config) -> Result<()> {
         canvas.resize(canvas_w, canvas_h);
 
         // Update spectrogram history
-        spectrogram_history.push(audio_data.frequencies.clone());
-        if spectrogram_history.len() > canvas_h {
-            let excess = spectrogram_history.len() - canvas_h;
-            spectrogram_history.drain(..excess);
+        let (_, render_h) = renderer::scaled_size(canvas_w, canvas_h, config.renderer.supersample);
+        renderer::styles::push_spectrogram_row(
+            &mut spectrogram_history,
+            &audio_data.frequencies,
+            &config.visualizer.spectrogram,
+            render_h,
+        );
+        renderer::styles::push_amplitude(&mut amplitude_history, &audio_data.waveform, visualizer.time, &config.visualizer.seismograph);
+        particles.update(&audio_data.frequencies, visualizer.time, &config.visualizer.particles);
+        if renderer::styles::draws_builtin(visualizer.current_style, renderer::styles::FIRE_STYLE) {
+            fire.update(&audio_data.frequencies, visualizer.time, &config.visualizer.fire);
         }
+        if renderer::styles::draws_builtin(visualizer.current_style, renderer::styles::VECTORSCOPE_STYLE) {
+            vectorscope.update(&audio_data.stereo, &audio_data.waveform, visualizer.time, &config.visualizer.vectorscope);
+        }
+        vu.update(&audio_data.stereo, &audio_data.waveform, visualizer.time, &config.visualizer.vu);
+        sparks.update(&audio_data.frequencies, visualizer.time, &config.effects.sparks);
 
         // Disable bitmap text rendering — the terminal status bar handles text.
         // The bitmap font is designed for high-res pixel buffers, not ~80×50 canvases.
         let mut term_text_config = config.text.clone();
         term_text_config.show_title = false;
         term_text_config.show_artist = false;
+        // Likewise the source indicator lives in the status bar
+        let term_indicator_config = SourceIndicatorConfig {
+            enabled: false,
+            ..config.source_indicator.clone()
+        };
 
+        let segments = &config.visualizer.segments;
         let params = renderer::RenderParams {
             style: visualizer.current_style,
-            bar_width: config.visualizer.bar_width as usize,
-            bar_spacing: config.visualizer.bar_spacing as usize,
-            mirror: config.visualizer.mirror,
+            bar_width: overrides.bar_width.unwrap_or(config.visualizer.bar_width) as usize * cell_w,
+            bar_spacing: overrides.bar_spacing.unwrap_or(config.visualizer.bar_spacing) as usize * cell_w,
+            mirror: overrides.mirror.unwrap_or(config.visualizer.mirror),
             reverse_mirror: config.visualizer.reverse_mirror,
+            interpolation: config.audio.interpolation,
+            group_size: config.visualizer.group.size,
+            group_gap: config.visualizer.group.gap as usize * cell_w,
+            segment_height: if segments.enabled { segment_px(segments.height) } else { 0 },
+            segment_gap: segment_px(segments.gap),
+            segment_zones: &segments.zones,
+            orientation: config.visualizer.orientation,
             opacity: 1.0, // terminal doesn't use opacity
+            min_bar_height: config.visualizer.min_bar_height,
             color_scheme: &color_scheme,
             waveform: &audio_data.waveform,
             spectrogram_history: &spectrogram_history,
+            spectrogram: &config.visualizer.spectrogram,
+            amplitude_history: &amplitude_history,
+            seismograph: &config.visualizer.seismograph,
+            karaoke: &config.visualizer.karaoke,
+            radial: &config.visualizer.radial,
+            oscilloscope: &config.visualizer.oscilloscope,
+            particles: &particles,
+            fire: &fire,
+            vectorscope: &vectorscope,
+            vu: &vu,
+            sparks: &sparks,
             text_config: &term_text_config,
+            album_art: &config.album_art,
+            source_indicator: &term_indicator_config,
+            widgets: &config.widgets,
+            effects: &config.renderer.effects,
+            transform: renderer::transform::Transform::from_config(&config.visualizer),
+            bar_rounding: config.visualizer.bar_rounding,
+            symmetry: config.visualizer.symmetry,
+            background: None,
+            font: None,
         };
 
         let frame_data = renderer::FrameData {
             frequencies: &audio_data.frequencies,
             intensity: audio_data.intensity,
+            bands: audio_data.bands,
             track_title: &track_info.title,
             track_artist: &track_info.artist,
+            album_art: track_info.album_art.as_deref(),
+            source: None,
+            input_level: audio_data.level,
             time: visualizer.time,
+            // The track text is in the status bar
+            text_opacity: 1.0,
+            flash: track_change.flash(&config.hooks.track_change),
         };
 
-        renderer::render_frame(&mut canvas, &frame_data, &params);
+        let shown = visible && !privacy.hides_all() && !workspace_rules.hides();
+        // A paused visualizer keeps showing the last frame
+        if shown && !workspace_rules.pauses() && !media_paused {
+            renderer::render_frame_scaled(&mut canvas, &mut work_canvas, &frame_data, &params, config.renderer.supersample, config.renderer.upscale);
+        } else if !shown {
+            canvas.clear();
+        }
+
+        if let (Some(output), Some(_)) = (graphics.as_mut(), cell) {
+            output.draw(stdout, &canvas)?;
+        } else if shown && visualizer.current_style == renderer::styles::FIRE_STYLE && config.visualizer.fire.terminal_blocks {
+            fire_to_terminal(stdout, &fire, &color_scheme, vis_area.width, vis_rows)?;
+        } else {
+            // Convert canvas to terminal characters
+            match config.terminal.blocks {
+                TerminalBlocks::Halfblock => canvas_to_terminal(stdout, &canvas, vis_area.width, vis_rows)?,
+                TerminalBlocks::Braille => canvas_to_braille(stdout, &canvas, vis_area.width, vis_rows)?,
+                TerminalBlocks::Block => canvas_to_blocks(stdout, &canvas, vis_area.width, vis_rows)?,
+            }
+        }
+
+        if let Some(area) = panel_area {
+            let rotation_interval = Duration::from_secs(config.display.rotation_interval_secs);
+            let next_rotation = config.display.rotate_styles.then(|| {
+                let next = rotator.peek(visualizer.current_style, renderer::styles::style_count());
+                (
+                    renderer::styles::style_name(next),
+                    rotation_interval.saturating_sub(style_timer.elapsed()),
+                )
+            });
+            let style_name = visualizer.current_style_name();
+            let panel = info_panel::PanelInfo {
+                track: &track_info,
+                style: &style_name,
+                color: color_scheme.name(),
+                next_rotation,
+                source: &_audio_capture.source_name(),
+                level: audio_data.level,
+                intensity: audio_data.intensity,
+                bars: audio_data.frequencies.len(),
+                fps,
+                accent: color_scheme.get_color(0.5, 1.0),
+            };
+            info_panel::render(stdout, area, &panel)?;
+        }
 
-        // Convert canvas to terminal half-block characters
-        canvas_to_terminal(stdout, &canvas, term_width, term_height.saturating_sub(1))?;
+        if track_rows > 0 {
+            render_track_rows(stdout, term_width, vis_rows, &config.text, &color_scheme, &track_info, &audio_data, visualizer.time)?;
+        }
 
         // Render status bar on the last row
-        render_status(stdout, term_width, term_height, &visualizer, &color_scheme, &track_info)?;
+        let source_name = _audio_capture.source_name();
+        let source = config.source_indicator.enabled.then_some((source_name.as_str(), audio_data.level));
+        render_status(stdout, term_width, term_height, &visualizer, &color_scheme, &track_info, source)?;
 
         stdout.flush()?;
 
@@ -175,6 +410,12 @@ async fn run_app(stdout: &mut impl Write, config: Config) -> Result<()> {
                     } => {
                         color_scheme = color_scheme.next();
                     }
+                    KeyEvent {
+                        code: KeyCode::Char('i'),
+                        ..
+                    } => {
+                        show_panel = !show_panel;
+                    }
                     KeyEvent {
                         code: KeyCode::Char('r'),
                         ..
@@ -185,8 +426,103 @@ async fn run_app(stdout: &mut impl Write, config: Config) -> Result<()> {
                 }
             }
         }
+
+        // Process IPC commands (non-blocking)
+        let mut pending = PendingChanges::default();
+        while let Ok(cmd) = ipc_rx.try_recv() {
+            // Intercept audio commands before the generic handler
+            let switch_to = match cmd {
+                IpcCommand::ListSources { reply } => {
+                    let _ = reply.send(crate::ipc::list_sources_reply());
+                    continue;
+                }
+                IpcCommand::Screenshot { output: Some(_), reply, .. } => {
+                    let _ = reply.send(IpcResponse::err("per-output commands need wallpaper mode"));
+                    continue;
+                }
+                // The canvas still holds the frame drawn above
+                IpcCommand::Screenshot { output: None, path, reply } => {
+                    crate::ipc::send_screenshot(canvas.clone(), path, reply);
+                    continue;
+                }
+                IpcCommand::SetSource { name, reply } => {
+                    let source = if name == "default" { None } else { Some(name) };
+                    Some((source, reply))
+                }
+                IpcCommand::SourceNext { reply } => {
+                    match audio::adjacent_source(current_source.as_deref(), true) {
+                        Ok(name) => Some((Some(name), reply)),
+                        Err(e) => {
+                            let _ = reply.send(IpcResponse::err(e));
+                            None
+                        }
+                    }
+                }
+                IpcCommand::SourcePrev { reply } => {
+                    match audio::adjacent_source(current_source.as_deref(), false) {
+                        Ok(name) => Some((Some(name), reply)),
+                        Err(e) => {
+                            let _ = reply.send(IpcResponse::err(e));
+                            None
+                        }
+                    }
+                }
+                cmd => {
+                    let mut opacity = config.visualizer.opacity;
+                    crate::ipc::process_ipc_command(
+                        cmd,
+                        &mut visualizer,
+                        &mut color_scheme,
+                        &mut visible,
+                        &mut opacity,
+                        &mut config,
+                        &[],
+                        &mut privacy,
+                        &mut pending,
+                        Some(&_audio_capture),
+                        // The terminal renders whether or not audio plays
+                        None,
+                    );
+                    config.visualizer.opacity = opacity;
+                    None
+                }
+            };
+
+            if let Some((source, reply)) = switch_to {
+                match audio::create_audio_pipeline_for(config.visualizer.bars, &config.audio, source.clone()) {
+                    Ok((capture, rx)) => {
+                        _audio_capture = capture;
+                        audio_rx = rx;
+                        let name = source.clone().unwrap_or_else(|| "default".to_string());
+                        current_source = source;
+                        let _ = reply.send(IpcResponse::ok(name));
+                    }
+                    Err(e) => {
+                        let _ = reply.send(IpcResponse::err(e));
+                    }
+                }
+            }
+        }
+
+        // Recreate the audio pipeline when audio settings or bar count changed
+        if pending.audio_restart {
+            match audio::create_audio_pipeline_for(config.visualizer.bars, &config.audio, current_source.clone()) {
+                Ok((capture, rx)) => {
+                    _audio_capture = capture;
+                    audio_rx = rx;
+                    spectrogram_history.clear();
+                    amplitude_history.clear();
+                    particles.clear();
+                    sparks.clear();
+                }
+                Err(e) => tracing::warn!("Failed to restart audio pipeline: {}", e),
+            }
+        }
     }
 
+    if let Some(output) = graphics.as_mut() {
+        output.clear(stdout)?;
+    }
     Ok(())
 }
 
@@ -252,6 +588,226 @@ fn canvas_to_terminal(
     Ok(())
 }
 
+/// Alpha a pixel needs to light its braille dot
+const BRAILLE_THRESHOLD: u8 = 128;
+
+/// Convert a pixel canvas to terminal output using braille characters.
+///
+/// Each terminal cell represents 2×4 pixels, one dot each; the foreground
+/// color is the average of the lit pixels.
+fn canvas_to_braille(
+    stdout: &mut impl Write,
+    canvas: &renderer::Canvas,
+    term_width: u16,
+    term_rows: u16,
+) -> Result<()> {
+    // Dot bits of U+2800 by (column, row) within the cell
+    const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+    let cols = ((canvas.width / 2) as u16).min(term_width);
+
+    for row in 0..term_rows {
+        execute!(stdout, MoveTo(0, row), SetBackgroundColor(Color::Reset))?;
+
+        for col in 0..cols {
+            let mut bits = 0;
+            let mut sum = [0u32; 3];
+            let mut lit = 0;
+            for (dx, column) in DOTS.iter().enumerate() {
+                for (dy, bit) in column.iter().enumerate() {
+                    let (r, g, b, a) = canvas.get_pixel(col as usize * 2 + dx, row as usize * 4 + dy);
+                    if a >= BRAILLE_THRESHOLD {
+                        bits |= bit;
+                        sum[0] += r as u32;
+                        sum[1] += g as u32;
+                        sum[2] += b as u32;
+                        lit += 1;
+                    }
+                }
+            }
+
+            if lit == 0 {
+                execute!(stdout, Print(" "))?;
+            } else {
+                let ch = char::from_u32(0x2800 + bits).unwrap_or(' ');
+                let [r, g, b] = sum.map(|c| (c / lit) as u8);
+                execute!(stdout, SetForegroundColor(Color::Rgb { r, g, b }), Print(ch))?;
+            }
+        }
+
+        // Clear rest of line
+        for _ in cols..term_width {
+            execute!(stdout, Print(" "))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a pixel canvas to terminal output using full blocks, one pixel
+/// per cell.
+fn canvas_to_blocks(
+    stdout: &mut impl Write,
+    canvas: &renderer::Canvas,
+    term_width: u16,
+    term_rows: u16,
+) -> Result<()> {
+    let cols = (canvas.width as u16).min(term_width);
+
+    for row in 0..term_rows {
+        execute!(stdout, MoveTo(0, row), SetBackgroundColor(Color::Reset))?;
+
+        for col in 0..cols {
+            let (r, g, b, a) = canvas.get_pixel(col as usize, row as usize);
+            if a == 0 {
+                execute!(stdout, Print(" "))?;
+            } else {
+                execute!(stdout, SetForegroundColor(Color::Rgb { r, g, b }), Print("█"))?;
+            }
+        }
+
+        // Clear rest of line
+        for _ in cols..term_width {
+            execute!(stdout, Print(" "))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw the Fire style's heat grid with shade characters, one heat sample
+/// per cell, so the flames keep their shape at terminal resolution.
+fn fire_to_terminal(
+    stdout: &mut impl Write,
+    fire: &renderer::fire::FireState,
+    color_scheme: &ColorScheme,
+    term_width: u16,
+    term_rows: u16,
+) -> Result<()> {
+    for row in 0..term_rows {
+        execute!(stdout, MoveTo(0, row), SetBackgroundColor(Color::Reset))?;
+        let y = 1.0 - (row as f32 + 0.5) / term_rows as f32;
+
+        for col in 0..term_width {
+            let heat = fire.sample((col as f32 + 0.5) / term_width as f32, y).min(1.0);
+            let ch = match heat {
+                h if h < 0.05 => ' ',
+                h if h < 0.25 => '░',
+                h if h < 0.45 => '▒',
+                h if h < 0.7 => '▓',
+                _ => '█',
+            };
+            let (r, g, b) = color_scheme.get_color(heat, heat);
+            execute!(stdout, SetForegroundColor(Color::Rgb { r, g, b }), Print(ch))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Render title and artist on their own rows, each as an independent marquee.
+#[allow(clippy::too_many_arguments)]
+fn render_track_rows(
+    stdout: &mut impl Write,
+    term_width: u16,
+    first_row: u16,
+    text_config: &TextConfig,
+    color_scheme: &ColorScheme,
+    track: &Arc<TrackInfo>,
+    audio_data: &audio::AudioData,
+    time: f32,
+) -> Result<()> {
+    let lines = [
+        (
+            text_config.show_title,
+            track.title.as_deref(),
+            text_config.title_scroll_speed,
+            text_config.title_scroll_direction,
+            text_config.title_color,
+            (255, 255, 255),
+            0.0,
+        ),
+        (
+            text_config.show_artist,
+            track.artist.as_deref(),
+            text_config.artist_scroll_speed,
+            text_config.artist_scroll_direction,
+            text_config.artist_color,
+            (200, 200, 200),
+            0.5,
+        ),
+    ];
+
+    let margin = (text_config.margin_horizontal as usize).min(term_width as usize / 4);
+    let available = (term_width as usize).saturating_sub(margin * 2);
+
+    for (row_offset, (show, text, speed, direction, custom_color, fallback, phase)) in lines.into_iter().enumerate() {
+        let row = first_row + row_offset as u16;
+        execute!(stdout, MoveTo(0, row), SetBackgroundColor(Color::Reset))?;
+
+        let chars: Vec<char> = match (show, text) {
+            (true, Some(t)) => t.chars().collect(),
+            _ => Vec::new(),
+        };
+        let len = chars.len();
+
+        let colors: Vec<(u8, u8, u8)> = if text_config.use_color_scheme {
+            color_scheme.get_text_gradient(
+                len,
+                audio_data.intensity * text_config.pulse_intensity,
+                time * text_config.animation_speed + phase,
+            )
+        } else {
+            let c = custom_color.map(|c| (c.r, c.g, c.b)).unwrap_or(fallback);
+            vec![c; len]
+        };
+
+        // Build the visible window: (char, color index) per cell
+        let mut cells: Vec<Option<usize>> = vec![None; available];
+        if len <= available || text_config.animation_style != crate::config::TextAnimation::Scroll {
+            let start = match text_config.alignment {
+                TextAlignment::Left => 0,
+                TextAlignment::Center => available.saturating_sub(len) / 2,
+                TextAlignment::Right => available.saturating_sub(len),
+            };
+            for (i, cell) in cells.iter_mut().skip(start).take(len).enumerate() {
+                *cell = Some(i);
+            }
+        } else {
+            let gap = renderer::text::MARQUEE_GAP_CHARS;
+            let period = len + gap;
+            let chars_per_sec = text_config.animation_speed * 4.0 * speed;
+            let offset = renderer::text::marquee_offset(len, gap, chars_per_sec, direction, time);
+            for (col, cell) in cells.iter_mut().enumerate() {
+                let idx = (offset + col) % period;
+                if idx < len {
+                    *cell = Some(idx);
+                }
+            }
+        }
+
+        for _ in 0..margin {
+            execute!(stdout, Print(" "))?;
+        }
+        for cell in &cells {
+            match cell {
+                Some(i) => {
+                    let (r, g, b) = colors.get(*i).copied().unwrap_or(fallback);
+                    execute!(stdout, SetForegroundColor(Color::Rgb { r, g, b }), Print(chars[*i]))?;
+                }
+                None => execute!(stdout, Print(" "))?,
+            }
+        }
+        for _ in (margin + available)..term_width as usize {
+            execute!(stdout, Print(" "))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Width of the status bar input level meter, in cells
+const STATUS_METER_CELLS: usize = 8;
+
 fn render_status(
     stdout: &mut impl Write,
     term_width: u16,
@@ -259,11 +815,25 @@ fn render_status(
     visualizer: &VisualizerState,
     color_scheme: &ColorScheme,
     _track: &Arc<TrackInfo>,
+    source: Option<(&str, f32)>,
 ) -> Result<()> {
+    let source_segment = match source {
+        Some((name, level)) => {
+            let filled = (level.clamp(0.0, 1.0) * STATUS_METER_CELLS as f32).round() as usize;
+            format!(
+                "src: {} {}{} | ",
+                renderer::indicator::truncate_name(name, 32),
+                "█".repeat(filled),
+                "░".repeat(STATUS_METER_CELLS - filled)
+            )
+        }
+        None => String::new(),
+    };
     let status = format!(
-        " [s]tyle: {} | [c]olor: {:?} | [q]uit ",
+        " [s]tyle: {} | [c]olor: {} | {}[i]nfo | [q]uit ",
         visualizer.current_style_name(),
-        color_scheme
+        color_scheme.name(),
+        source_segment
     );
 
     execute!(
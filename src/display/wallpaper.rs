@@ -1,12 +1,14 @@
 //! Wallpaper mode dispatcher.
 //!
-//! On Wayland: delegates to the layer-shell backend in `wayland.rs`.
+//! On Wayland: delegates to the layer-shell backend in `wayland.rs`, which
+//! also draws window mode into an xdg-shell toplevel.
 //! On X11/other: prints setup instructions (use terminal mode with a transparent
 //! terminal instead).
 
 use anyhow::Result;
 use tracing::info;
 
+use super::DisplayMode;
 use crate::config::Config;
 use crate::ipc::IpcCommand;
 use tokio::sync::mpsc;
@@ -21,6 +23,9 @@ fn is_wayland() -> bool {
 /// On Wayland: Uses wlr-layer-shell protocol to render as a background layer.
 /// On X11/other: Prints instructions for achieving the same effect with a
 /// transparent terminal.
+///
+/// Window mode runs the same backend with a regular xdg-shell window, so it
+/// needs Wayland too.
 pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<()> {
     info!("Wallpaper mode requested");
 
@@ -39,6 +44,9 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
 
     // Non-Wayland: print instructions
     drop(ipc_rx);
+    if config.display.mode == DisplayMode::Window {
+        anyhow::bail!("window mode needs a Wayland session; on X11, run terminal mode in a terminal window instead");
+    }
     run_x11_instructions().await
 }
 
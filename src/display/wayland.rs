@@ -6,47 +6,71 @@
 //! Supports multiple monitors: one LayerSurface per output. In "clone" mode all
 //! monitors show the same visualization; in "independent" mode per-monitor
 //! overrides for color scheme, style, and opacity are applied.
+//!
+//! Window mode (`mode = "window"`) draws into a single regular xdg-shell
+//! toplevel instead, the way wallpaper mode falls back when layer-shell is
+//! missing, but decorated, resizable and movable.
+//!
+//! If the compositor goes away (crash or session restart) the surfaces are
+//! dropped and the connection is retried with backoff, so a daemon started
+//! from a systemd user service survives compositor restarts.
 
 use anyhow::{Context, Result};
 use smithay_client_toolkit::{
-    compositor::{CompositorHandler, CompositorState},
+    compositor::{CompositorHandler, CompositorState, Region},
     delegate_compositor, delegate_layer, delegate_output, delegate_pointer, delegate_registry,
-    delegate_seat, delegate_shm,
+    delegate_seat, delegate_shm, delegate_xdg_shell, delegate_xdg_window,
     output::{OutputHandler, OutputState},
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
     seat::{Capability, SeatHandler, SeatState},
-    seat::pointer::{PointerEvent, PointerEventKind, PointerHandler, BTN_LEFT},
+    seat::pointer::{PointerEvent, PointerEventKind, PointerHandler, BTN_LEFT, BTN_MIDDLE},
     shell::{
         wlr_layer::{
             Anchor, KeyboardInteractivity, Layer, LayerShell, LayerShellHandler, LayerSurface,
             LayerSurfaceConfigure,
         },
+        xdg::{
+            window::{Window, WindowConfigure, WindowDecorations, WindowHandler},
+            XdgShell,
+        },
         WaylandSurface,
     },
-    shm::{
-        slot::SlotPool,
-        Shm, ShmHandler,
-    },
+    shm::{Shm, ShmHandler},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tracing::info;
+use tracing::{debug, info, warn};
 use wayland_client::{
-    globals::registry_queue_init,
-    protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
-    Connection, Proxy, QueueHandle,
+    backend::WaylandError,
+    globals::{registry_queue_init, GlobalList},
+    protocol::{wl_output, wl_pointer, wl_seat, wl_surface},
+    Connection, EventQueue, Proxy, QueueHandle,
 };
 
+#[cfg(feature = "gpu")]
+use super::gpu::{Gpu, GpuSurface};
+use super::swapchain::Swapchain;
+use super::DisplayMode;
 use crate::audio::{self, AudioCapture, AudioData};
-use crate::color::ColorScheme;
-use crate::config::{Config, MultiMonitorMode, WallpaperAnchor, WallpaperLayer};
-use crate::ipc::{IpcCommand, PendingChanges};
+use crate::color::{ColorDynamics, ColorScheme};
+use crate::config::{Config, MonitorConfig, MultiMonitorMode, RenderBackend, UpscaleFilter, WallpaperAnchor, WallpaperConfig, WallpaperLayer};
+use crate::hooks::{HookRunner, TrackChangeEffects};
+use crate::ipc::{IpcCommand, IpcResponse, OutputCommand, PendingChanges, Selection};
 use crate::metadata::{self, TrackInfo};
+use crate::compositor::WorkspaceState;
+use crate::privacy::PrivacyState;
+use crate::renderer::background::Background;
+use crate::renderer::idle::{self, IdleFrame};
+use crate::renderer::ttf::TrueTypeFont;
+use crate::renderer::widgets::{self, Osd};
 use crate::renderer;
-use crate::visualizer::VisualizerState;
+use crate::stats::StatsRecorder;
+use crate::visualizer::{Rotator, VisualizerState};
 use tokio::sync::{mpsc, watch};
+use wayland_protocols::wp::viewporter::client::{wp_viewport, wp_viewporter};
 
 impl WallpaperLayer {
     /// Convert to layer-shell Layer type
@@ -80,7 +104,7 @@ impl WallpaperAnchor {
 
 /// An audio capture pipeline with its receiver
 struct AudioPipeline {
-    _capture: AudioCapture,
+    capture: AudioCapture,
     rx: watch::Receiver<Arc<AudioData>>,
 }
 
@@ -99,11 +123,184 @@ struct DragState {
     surface_id: Option<wayland_client::backend::ObjectId>,
 }
 
+/// Top-left position of `surface` on its output when placed at `anchor` with
+/// `margins` (top, right, bottom, left).
+fn anchored_position(anchor: WallpaperAnchor, margins: (i32, i32, i32, i32), surface: &OutputSurface) -> (i32, i32) {
+    let (mt, mr, mb, ml) = margins;
+    let sw = surface.screen_width as i32;
+    let sh = surface.screen_height as i32;
+    let w = surface.width as i32;
+    let h = surface.height as i32;
+    match anchor {
+        WallpaperAnchor::TopLeft => (ml, mt),
+        WallpaperAnchor::Top => ((sw - w) / 2, mt),
+        WallpaperAnchor::TopRight => (sw - w - mr, mt),
+        WallpaperAnchor::Left => (ml, (sh - h) / 2),
+        WallpaperAnchor::Center => ((sw - w) / 2, (sh - h) / 2),
+        WallpaperAnchor::Right => (sw - w - mr, (sh - h) / 2),
+        WallpaperAnchor::BottomLeft => (ml, sh - h - mb),
+        WallpaperAnchor::Bottom => ((sw - w) / 2, sh - h - mb),
+        WallpaperAnchor::BottomRight => (sw - w - mr, sh - h - mb),
+        WallpaperAnchor::Fullscreen => (0, 0),
+    }
+}
+
+/// Offset along one axis after snapping to the start, center or end of the
+/// screen, whichever is nearest within `distance`.
+fn snap_axis(offset: i32, size: i32, screen: i32, distance: i32) -> i32 {
+    [0, (screen - size) / 2, screen - size]
+        .into_iter()
+        .filter(|target| (offset - target).abs() <= distance)
+        .min_by_key(|target| (offset - target).abs())
+        .unwrap_or(offset)
+}
+
+/// Scroll distance (surface pixels) of continuous touchpad scrolling per style step
+const SCROLL_STEP: f64 = 30.0;
+
+/// First wait before reconnecting to a restarted compositor, doubled per attempt
+const RECONNECT_MIN_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(10);
+/// Give up (and exit with an error) if the compositor is gone this long
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Frames in a row a surface may fail to draw before it is recreated
+const MAX_DRAW_FAILURES: u32 = 30;
+/// Smallest width and height a window-mode window can be resized to
+const MIN_WINDOW_SIZE: u32 = 64;
+
+/// The globals wallpaper mode binds on a connection
+struct BoundGlobals {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    compositor_state: CompositorState,
+    shm: Shm,
+    /// None when the compositor lacks layer-shell; surfaces fall back to xdg_shell
+    layer_shell: Option<LayerShell>,
+    xdg_shell: Option<XdgShell>,
+    /// For letting the compositor upscale reduced-resolution frames
+    viewporter: Option<wp_viewporter::WpViewporter>,
+    /// For GPU surfaces, which need the display
+    #[cfg(feature = "gpu")]
+    connection: Connection,
+}
+
+/// Give `wl_surface` an empty input region when the config asks for click-through,
+/// or the default (whole surface) otherwise. Takes effect on the next commit.
+fn apply_input_region(compositor: &CompositorState, wallpaper: &WallpaperConfig, wl_surface: &wl_surface::WlSurface) {
+    if !wallpaper.passes_pointer_through() {
+        wl_surface.set_input_region(None);
+        return;
+    }
+    match Region::new(compositor) {
+        Ok(region) => wl_surface.set_input_region(Some(region.wl_region())),
+        Err(e) => tracing::warn!("Failed to create input region: {}", e),
+    }
+}
+
+/// Write the per-output layer, style, color scheme and opacity set at runtime
+/// into the `[[wallpaper.monitors]]` entries of `doc`, adding an entry for
+/// outputs that don't have one yet.
+fn save_output_overrides(doc: &mut toml_edit::DocumentMut, wallpaper: &WallpaperConfig) {
+    let overridden: Vec<(&str, Vec<(&str, toml_edit::Value)>)> = wallpaper
+        .monitors
+        .iter()
+        .map(|m| {
+            let mut values: Vec<(&str, toml_edit::Value)> = Vec::new();
+            if let Some(layer) = m.layer {
+                values.push(("layer", layer.name().into()));
+            }
+            if let Some(style) = &m.style {
+                values.push(("style", style.as_str().into()));
+            }
+            if let Some(scheme) = &m.color_scheme {
+                values.push(("color_scheme", scheme.name().to_lowercase().into()));
+            }
+            if let Some(opacity) = m.opacity {
+                values.push(("opacity", (opacity as f64).into()));
+            }
+            (m.output.as_str(), values)
+        })
+        .filter(|(_, values)| !values.is_empty())
+        .collect();
+    if overridden.is_empty() {
+        return;
+    }
+    let monitors = &mut doc["wallpaper"]["monitors"];
+    if monitors.is_none() {
+        *monitors = toml_edit::Item::ArrayOfTables(toml_edit::ArrayOfTables::new());
+    }
+    let Some(tables) = monitors.as_array_of_tables_mut() else {
+        tracing::warn!("wallpaper.monitors is not an array of tables, not saving per-output settings");
+        return;
+    };
+    for (output, values) in overridden {
+        let existing = tables
+            .iter_mut()
+            .position(|t| t.get("output").and_then(|o| o.as_str()) == Some(output));
+        let idx = existing.unwrap_or_else(|| {
+            let mut table = toml_edit::Table::new();
+            table["output"] = toml_edit::value(output);
+            tables.push(table);
+            tables.len() - 1
+        });
+        let Some(table) = tables.get_mut(idx) else {
+            continue;
+        };
+        for (key, value) in values {
+            table[key] = toml_edit::Item::Value(value);
+        }
+    }
+}
+
+/// The shell role a surface was created with
+enum SurfaceRole {
+    /// wlr-layer-shell surface, positioned and stacked by us
+    Layer(LayerSurface),
+    /// Borderless maximized toplevel, used when layer-shell is unavailable (GNOME)
+    Window(Window),
+}
+
+impl SurfaceRole {
+    fn wl_surface(&self) -> &wl_surface::WlSurface {
+        match self {
+            SurfaceRole::Layer(layer) => layer.wl_surface(),
+            SurfaceRole::Window(window) => window.wl_surface(),
+        }
+    }
+
+    /// The layer surface; None for a fallback window, which ignores anchor,
+    /// margin, size and layer changes
+    fn layer(&self) -> Option<&LayerSurface> {
+        match self {
+            SurfaceRole::Layer(layer) => Some(layer),
+            SurfaceRole::Window(_) => None,
+        }
+    }
+}
+
+/// Buffers for a `width`×`height` frame, reallocated when the size changed
+fn swapchain_for<'a>(swapchain: &'a mut Option<Swapchain>, shm: &Shm, width: u32, height: u32) -> Result<&'a mut Swapchain> {
+    if !swapchain.as_ref().is_some_and(|s| s.fits(width, height)) {
+        *swapchain = None;
+    }
+    match swapchain {
+        Some(swapchain) => Ok(swapchain),
+        None => Ok(swapchain.insert(Swapchain::new(shm, width, height)?)),
+    }
+}
+
 /// Per-output surface state
 struct OutputSurface {
     output_name: Option<String>,
-    layer_surface: LayerSurface,
-    pool: Option<SlotPool>,
+    role: SurfaceRole,
+    /// Buffers for the current size (None until the first draw after configure)
+    swapchain: Option<Swapchain>,
+    /// wgpu swapchain replacing the buffers with `renderer.backend = "gpu"`
+    #[cfg(feature = "gpu")]
+    gpu: Option<GpuSurface>,
+    /// Scales buffers to the surface size (None without wp_viewporter)
+    viewport: Option<wp_viewport::WpViewport>,
     width: u32,
     height: u32,
     configured: bool,
@@ -114,16 +311,41 @@ struct OutputSurface {
     color_scheme_override: Option<ColorScheme>,
     style_override: Option<usize>,
     opacity_override: Option<f32>,
+    bars_override: Option<usize>,
     // Per-monitor audio
     audio_source_key: Option<String>, // Key into audio_pipelines map
     audio_data: Arc<AudioData>,       // Cached per-surface audio data
     // Spectrogram history (rolling buffer of frequency snapshots)
     spectrogram_history: Vec<Vec<f32>>,
+    // Seismograph history: (time, RMS) per frame
+    amplitude_history: Vec<(f32, f32)>,
+    particles: renderer::particles::ParticleSystem,
+    fire: renderer::fire::FireState,
+    vectorscope: renderer::vectorscope::VectorscopeState,
+    vu: renderer::vu::VuState,
+    sparks: renderer::sparks::SparkState,
     // Per-surface margin state (for independent drag positioning)
     margin_top: i32,
     margin_left: i32,
     // Reusable pixel canvas (RGBA)
     canvas: renderer::Canvas,
+    // Internal render target when supersampling or downscaling
+    work_canvas: renderer::Canvas,
+    // Last committed frame, for damage tracking
+    previous_canvas: renderer::Canvas,
+    // Frames in a row that failed to draw (the watchdog recreates the surface)
+    failures: u32,
+}
+
+impl Drop for OutputSurface {
+    fn drop(&mut self) {
+        // The GPU surface and the viewport have to go before their wl_surface
+        #[cfg(feature = "gpu")]
+        self.gpu.take();
+        if let Some(viewport) = self.viewport.take() {
+            viewport.destroy();
+        }
+    }
 }
 
 /// Wayland layer-shell wallpaper renderer with multi-monitor support
@@ -133,10 +355,26 @@ struct WallpaperState {
     output_state: OutputState,
     compositor_state: CompositorState,
     shm: Shm,
-    layer_shell: LayerShell,
+    /// None when the compositor lacks layer-shell; surfaces fall back to xdg_shell
+    layer_shell: Option<LayerShell>,
+    xdg_shell: Option<XdgShell>,
+    viewporter: Option<wp_viewporter::WpViewporter>,
+    #[cfg(feature = "gpu")]
+    connection: Connection,
+    /// Device for GPU surfaces, set up with the first one
+    #[cfg(feature = "gpu")]
+    gpu: Gpu,
+    /// Window mode: a regular window instead of layer surfaces
+    window_mode: bool,
     seat_state: Option<SeatState>,
+    /// The seat the pointer belongs to, for interactive window moves
+    seat: Option<wl_seat::WlSeat>,
     pointer: Option<wl_pointer::WlPointer>,
     drag: DragState,
+    /// Touchpad scrolling not yet turned into style steps (`pointer_actions`)
+    scroll: f64,
+    /// Commands triggered by pointer actions, run with the next IPC batch
+    pointer_commands: Vec<IpcCommand>,
 
     // Per-output surfaces, keyed by wl_output ObjectId
     surfaces: HashMap<wayland_client::backend::ObjectId, OutputSurface>,
@@ -145,54 +383,163 @@ struct WallpaperState {
     visualizer: VisualizerState,
     color_scheme: ColorScheme,
     audio_pipelines: HashMap<Option<String>, AudioPipeline>,
+    /// Source explicitly selected for the default pipeline (None = auto-detected)
+    current_source: Option<String>,
     track_info: Arc<TrackInfo>,
+    /// `[hooks.track_change]` flash and title fade-in
+    track_change: TrackChangeEffects,
     last_frame: Instant,
     time: f32,
 
     // Control
     running: bool,
     visible: bool,
+    /// Outputs hidden with `@OUTPUT toggle`
+    hidden_outputs: HashSet<String>,
+    privacy: PrivacyState,
+    /// `[compositor]` workspace rules
+    workspace_rules: WorkspaceState,
+    /// Decoded `wallpaper.background_image`
+    background: Option<Background>,
+    /// Loaded `text.font_file` / `text.font_family`
+    font: Option<TrueTypeFont>,
     active: bool, // true when audio is playing and frames are being rendered
+    /// What to draw this iteration, from `active` and `idle_mode`
+    idle: IdleFrame,
+    /// When the audio last went quiet (None while active)
+    idle_since: Option<Instant>,
+    /// Content of the last static idle frame drawn (clock text, or empty
+    /// for a blank surface), so it is only redrawn when it changes
+    idle_drawn: Option<String>,
+    /// Confirmation of the last IPC change (`[widgets.osd]`)
+    osd: Osd,
+    /// Whether the last frame drawn showed the OSD, so it is erased once gone
+    osd_drawn: bool,
     config: Config,
+    /// Rendered value of each `state_values` key as the config file holds
+    /// it, so saving only writes what changed at runtime
+    saved_state: HashMap<&'static str, String>,
 
     // IPC
     ipc_rx: mpsc::Receiver<IpcCommand>,
 }
 
 impl WallpaperState {
-    fn new(
-        registry_state: RegistryState,
-        output_state: OutputState,
-        compositor_state: CompositorState,
-        shm: Shm,
-        layer_shell: LayerShell,
-        config: Config,
-        ipc_rx: mpsc::Receiver<IpcCommand>,
-    ) -> Self {
+    fn new(bound: BoundGlobals, config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Self {
+        let BoundGlobals {
+            registry_state,
+            output_state,
+            compositor_state,
+            shm,
+            layer_shell,
+            xdg_shell,
+            viewporter,
+            #[cfg(feature = "gpu")]
+            connection,
+        } = bound;
         let visualizer = VisualizerState::new(config.visualizer.clone(), config.text.clone());
-        let color_scheme = config.visualizer.color_scheme;
+        let color_scheme = config.visualizer.color_scheme.clone();
+        let privacy = PrivacyState::new(&config.privacy);
+        let background = load_background(&config);
+        let font = load_font(&config);
+        let window_mode = config.display.mode == DisplayMode::Window;
 
-        Self {
+        let mut state = Self {
             registry_state,
             output_state,
             compositor_state,
             shm,
             layer_shell,
+            xdg_shell,
+            viewporter,
+            #[cfg(feature = "gpu")]
+            connection,
+            #[cfg(feature = "gpu")]
+            gpu: Gpu::default(),
+            window_mode,
             seat_state: None,
+            seat: None,
             pointer: None,
             drag: DragState::default(),
+            scroll: 0.0,
+            pointer_commands: Vec::new(),
             surfaces: HashMap::new(),
             visualizer,
             color_scheme,
             audio_pipelines: HashMap::new(),
+            current_source: None,
             track_info: Arc::new(TrackInfo::default()),
+            track_change: TrackChangeEffects::default(),
             last_frame: Instant::now(),
             time: 0.0,
             running: true,
             visible: true,
+            hidden_outputs: HashSet::new(),
+            privacy,
+            workspace_rules: WorkspaceState::default(),
+            background,
+            font,
             active: true,
+            idle: IdleFrame::Live,
+            idle_since: None,
+            idle_drawn: None,
+            osd: Osd::default(),
+            osd_drawn: false,
             config,
+            saved_state: HashMap::new(),
             ipc_rx,
+        };
+        state.saved_state = state.state_values().into_iter().map(|(key, value)| (key, value.to_string())).collect();
+        state
+    }
+
+    /// Drop everything tied to a lost connection and take the globals of a new one
+    fn rebind(&mut self, bound: BoundGlobals) {
+        self.surfaces.clear();
+        self.seat_state = None;
+        self.seat = None;
+        self.pointer = None;
+        self.drag = DragState::default();
+        self.registry_state = bound.registry_state;
+        self.output_state = bound.output_state;
+        self.compositor_state = bound.compositor_state;
+        self.shm = bound.shm;
+        self.layer_shell = bound.layer_shell;
+        self.xdg_shell = bound.xdg_shell;
+        self.viewporter = bound.viewporter;
+        // The device may be tied to the old display (EGL)
+        #[cfg(feature = "gpu")]
+        {
+            self.connection = bound.connection;
+            self.gpu = Gpu::default();
+        }
+    }
+
+    /// Rebuild the default audio pipeline on `source` (None = auto-detected device)
+    fn switch_default_source(&mut self, source: Option<String>) -> Result<()> {
+        let (capture, rx) = audio::create_audio_pipeline_for(
+            self.config.visualizer.bars,
+            &self.config.audio,
+            source.clone(),
+        )?;
+        self.audio_pipelines.insert(None, AudioPipeline {
+            capture,
+            rx,
+        });
+        self.current_source = source;
+        Ok(())
+    }
+
+    /// Switch the default pipeline to the next/previous listed source, returning the IPC reply
+    fn cycle_source(&mut self, forward: bool) -> IpcResponse {
+        let result = audio::adjacent_source(self.current_source.as_deref(), forward)
+            .and_then(|name| {
+                self.switch_default_source(Some(name.clone()))?;
+                Ok(name)
+            });
+        match result {
+            Ok(name) => IpcResponse::ok(name),
+            Err(e) => IpcResponse::err(e),
         }
     }
 
@@ -222,24 +569,29 @@ impl WallpaperState {
         true
     }
 
-    /// Get per-monitor overrides from config
-    fn get_monitor_overrides(&self, output_name: &Option<String>) -> (Option<ColorScheme>, Option<usize>, Option<f32>, Option<String>) {
+    /// Per-monitor overrides from config (independent mode only)
+    fn monitor_overrides(&self, output_name: &Option<String>) -> Option<&MonitorConfig> {
         if self.config.wallpaper.multi_monitor != MultiMonitorMode::Independent {
-            return (None, None, None, None);
+            return None;
         }
+        let name = output_name.as_ref()?;
+        self.config.wallpaper.monitors.iter().find(|m| m.output == *name)
+    }
 
-        if let Some(ref name) = output_name {
-            for monitor_cfg in &self.config.wallpaper.monitors {
-                if monitor_cfg.output == *name {
-                    let style_idx = monitor_cfg.style.as_ref().and_then(|s| {
-                        renderer::styles::STYLE_NAMES.iter().position(|&name| name.eq_ignore_ascii_case(s))
-                    });
-                    return (monitor_cfg.color_scheme, style_idx, monitor_cfg.opacity, monitor_cfg.audio_source.clone());
-                }
-            }
+    /// Wallpaper layout (anchor, size, margins) for an output, with its overrides
+    /// applied; the overlay's layout while it's on there
+    fn surface_layout(&self, output_name: &Option<String>) -> WallpaperConfig {
+        if self.config.wallpaper.overlay_on(output_name.as_deref()) {
+            return self.config.wallpaper.overlay_layout();
         }
-
-        (None, None, None, None)
+        let mut layout = match self.monitor_overrides(output_name) {
+            Some(monitor) => self.config.wallpaper.with_monitor_layout(monitor),
+            None => self.config.wallpaper.clone(),
+        };
+        // The overlay is on another output; this one keeps the regular click
+        // handling and exclusive zone
+        layout.overlay.enabled = false;
+        layout
     }
 
     /// Create a layer surface for a specific output
@@ -258,31 +610,49 @@ impl WallpaperState {
             return;
         }
 
-        // Get screen dimensions for this output
-        let (screen_w, screen_h) = output_info
-            .as_ref()
-            .and_then(|i| i.logical_size)
-            .map(|(w, h)| (w as u32, h as u32))
-            .unwrap_or((1920, 1080));
+        // A maximized window can't be placed on a chosen output, so the
+        // xdg_shell fallback uses a single window
+        if self.layer_shell.is_none() && !self.surfaces.is_empty() {
+            return;
+        }
 
-        info!("Creating layer surface for output {:?} ({}x{})", output_name, screen_w, screen_h);
+        // Get screen dimensions for this output; a window starts at its configured size
+        let (screen_w, screen_h) = if self.window_mode {
+            (self.config.display.window_width.max(1), self.config.display.window_height.max(1))
+        } else {
+            output_info
+                .as_ref()
+                .and_then(|i| i.logical_size)
+                .map(|(w, h)| (w as u32, h as u32))
+                .unwrap_or((1920, 1080))
+        };
 
         let wl_surface = self.compositor_state.create_surface(qh);
 
-        let layer_surface = self.layer_shell.create_layer_surface(
+        let Some(layer_shell) = &self.layer_shell else {
+            info!("Creating {} ({}x{})", if self.window_mode { "window" } else { "fallback desktop window" }, screen_w, screen_h);
+            let window = self.create_fallback_window(qh, wl_surface);
+            self.insert_surface(qh, output, output_name, SurfaceRole::Window(window), (screen_w, screen_h), None);
+            return;
+        };
+
+        info!("Creating layer surface for output {:?} ({}x{})", output_name, screen_w, screen_h);
+        let layout = self.surface_layout(&output_name);
+
+        let layer_surface = layer_shell.create_layer_surface(
             qh,
             wl_surface,
-            self.config.wallpaper.layer.to_layer_shell_layer(),
+            self.config.wallpaper.layer_for(output_name.as_deref()).to_layer_shell_layer(),
             Some("cavibe-wallpaper"),
             Some(output),
         );
 
         // Configure anchor based on wallpaper config
-        let anchor = self.config.wallpaper.anchor.to_layer_shell_anchor();
+        let anchor = layout.anchor.to_layer_shell_anchor();
         layer_surface.set_anchor(anchor);
 
         // Apply margins
-        let (top, right, bottom, left) = self.config.wallpaper.effective_margins();
+        let (top, right, bottom, left) = layout.effective_margins();
         layer_surface.set_margin(top, right, bottom, left);
 
         // Set size for non-fullscreen anchors.
@@ -291,7 +661,7 @@ impl WallpaperState {
         let needs_width = !anchor.contains(Anchor::LEFT | Anchor::RIGHT);
         let needs_height = !anchor.contains(Anchor::TOP | Anchor::BOTTOM);
         let explicit_size = if needs_width || needs_height {
-            let configured = self.config.wallpaper.get_size(screen_w, screen_h);
+            let configured = layout.get_size(screen_w, screen_h);
             let (w, h) = configured.unwrap_or((screen_w / 2, screen_h / 2));
             layer_surface.set_size(w, h);
             info!("Explicit size set to: {}x{}", w, h);
@@ -300,7 +670,7 @@ impl WallpaperState {
             None
         };
 
-        layer_surface.set_exclusive_zone(-1); // Don't reserve space
+        layer_surface.set_exclusive_zone(explicit_size.map_or(-1, |size| layout.exclusive_zone(size)));
         let interactivity = if self.config.wallpaper.draggable {
             KeyboardInteractivity::OnDemand
         } else {
@@ -311,14 +681,64 @@ impl WallpaperState {
         // Commit to get the configure event
         layer_surface.commit();
 
+        self.insert_surface(qh, output, output_name, SurfaceRole::Layer(layer_surface), (screen_w, screen_h), explicit_size);
+        info!("Layer surface created for output, waiting for configure event...");
+    }
+
+    /// Create a borderless, maximized toplevel standing in for the layer surface.
+    /// xdg_shell has no way to stack it below other windows; compositors can
+    /// match the `cavibe-wallpaper` app id in their window rules instead.
+    ///
+    /// In window mode it is a regular window instead: decorated where the
+    /// compositor draws decorations, at its configured size and resizable.
+    fn create_fallback_window(&self, qh: &QueueHandle<Self>, wl_surface: wl_surface::WlSurface) -> Window {
+        let xdg_shell = self.xdg_shell.as_ref().expect("xdg_shell is bound when layer-shell is missing");
+        if self.window_mode {
+            let window = xdg_shell.create_window(wl_surface, WindowDecorations::RequestServer, qh);
+            window.set_title("cavibe");
+            window.set_app_id("cavibe");
+            window.set_min_size(Some((MIN_WINDOW_SIZE, MIN_WINDOW_SIZE)));
+            window.commit();
+            return window;
+        }
+        let window = xdg_shell.create_window(wl_surface, WindowDecorations::None, qh);
+        window.set_title("cavibe");
+        window.set_app_id("cavibe-wallpaper");
+        window.set_maximized();
+        // Commit to get the configure event
+        window.commit();
+        window
+    }
+
+    /// Track a newly created surface for `output`, waiting for its first configure
+    fn insert_surface(
+        &mut self,
+        qh: &QueueHandle<Self>,
+        output: &wl_output::WlOutput,
+        output_name: Option<String>,
+        role: SurfaceRole,
+        (screen_w, screen_h): (u32, u32),
+        explicit_size: Option<(u32, u32)>,
+    ) {
         // Get per-monitor overrides
-        let (color_override, style_override, opacity_override, audio_source) = self.get_monitor_overrides(&output_name);
+        let monitor = self.monitor_overrides(&output_name);
+        let color_override = monitor.and_then(|m| m.color_scheme.clone());
+        let style_override = monitor.and_then(|m| m.style.as_deref()).and_then(renderer::styles::find_style);
+        let opacity_override = monitor.and_then(|m| m.opacity);
+        let bars_override = monitor.and_then(|m| m.bars).filter(|&bars| bars > 0);
+        let audio_source = monitor.and_then(|m| m.audio_source.clone());
+
+        apply_input_region(&self.compositor_state, &self.surface_layout(&output_name), role.wl_surface());
+        let viewport = self.viewporter.as_ref().map(|viewporter| viewporter.get_viewport(role.wl_surface(), qh, ()));
 
-        let (mt, _, _, ml) = self.config.wallpaper.effective_margins();
+        let (mt, _, _, ml) = self.surface_layout(&output_name).effective_margins();
         let surface = OutputSurface {
             output_name,
-            layer_surface,
-            pool: None,
+            role,
+            swapchain: None,
+            #[cfg(feature = "gpu")]
+            gpu: None,
+            viewport,
             width: 0,
             height: 0,
             configured: false,
@@ -328,16 +748,25 @@ impl WallpaperState {
             color_scheme_override: color_override,
             style_override,
             opacity_override,
+            bars_override,
             audio_source_key: audio_source,
             audio_data: Arc::new(AudioData::default()),
             spectrogram_history: Vec::new(),
+            amplitude_history: Vec::new(),
+            particles: renderer::particles::ParticleSystem::default(),
+            fire: renderer::fire::FireState::default(),
+            vectorscope: renderer::vectorscope::VectorscopeState::default(),
+            vu: renderer::vu::VuState::default(),
+            sparks: renderer::sparks::SparkState::default(),
             margin_top: mt,
             margin_left: ml,
             canvas: renderer::Canvas::new(0, 0),
+            work_canvas: renderer::Canvas::new(0, 0),
+            previous_canvas: renderer::Canvas::new(0, 0),
+            failures: 0,
         };
 
         self.surfaces.insert(output.id(), surface);
-        info!("Layer surface created for output, waiting for configure event...");
     }
 
     /// Create surfaces for all currently known outputs
@@ -348,16 +777,33 @@ impl WallpaperState {
         }
     }
 
+    /// Destroy and recreate the surfaces that have failed to draw
+    /// `MAX_DRAW_FAILURES` frames in a row. Returns whether any were.
+    fn recreate_failing_surfaces(&mut self, qh: &QueueHandle<Self>) -> bool {
+        let outputs: Vec<wl_output::WlOutput> = self.output_state.outputs()
+            .filter(|output| self.surfaces.get(&output.id()).is_some_and(|s| s.failures >= MAX_DRAW_FAILURES))
+            .collect();
+        for output in &outputs {
+            if let Some(surface) = self.surfaces.remove(&output.id()) {
+                warn!("Surface for {:?} failed {} frames in a row, recreating it", surface.output_name, surface.failures);
+            }
+            self.create_surface_for_output(qh, output);
+        }
+        !outputs.is_empty()
+    }
+
     /// Check if any surface is configured
     fn any_configured(&self) -> bool {
         self.surfaces.values().any(|s| s.configured)
     }
 
-    /// Draw a specific surface by wl_surface
+    /// Draw a specific surface by wl_surface. A frame that fails is skipped
+    /// and counted towards the watchdog, which recreates the surface once
+    /// `MAX_DRAW_FAILURES` frames in a row have failed.
     fn draw_surface(&mut self, surface_wl: &wl_surface::WlSurface) {
         // Find the OutputSurface matching this wl_surface
         let output_id = self.surfaces.iter()
-            .find(|(_, s)| s.layer_surface.wl_surface() == surface_wl)
+            .find(|(_, s)| s.role.wl_surface() == surface_wl)
             .map(|(id, _)| id.clone());
 
         let output_id = match output_id {
@@ -365,122 +811,322 @@ impl WallpaperState {
             None => return,
         };
 
-        let surface = match self.surfaces.get_mut(&output_id) {
+        let result = self.render_surface(&output_id);
+        let Some(surface) = self.surfaces.get_mut(&output_id) else {
+            return;
+        };
+        match result {
+            Ok(()) => surface.failures = 0,
+            Err(e) => {
+                surface.failures += 1;
+                // Start over with fresh buffers and a full redraw; the pool may be what failed
+                surface.swapchain = None;
+                surface.previous_canvas.resize(0, 0);
+                if surface.failures == 1 {
+                    warn!("Failed to draw surface for {:?}, skipping frame: {:#}", surface.output_name, e);
+                } else {
+                    debug!("Failed to draw surface for {:?} ({} in a row): {:#}", surface.output_name, surface.failures, e);
+                }
+            }
+        }
+    }
+
+    /// Put a wgpu swapchain on the surface for `output_id` once it is
+    /// configured while `renderer.backend = "gpu"`, or take it off again.
+    /// When the GPU can't be set up every surface keeps its shm buffers.
+    #[cfg(feature = "gpu")]
+    fn prepare_gpu(&mut self, output_id: &wayland_client::backend::ObjectId) {
+        let Some(surface) = self.surfaces.get_mut(output_id) else {
+            return;
+        };
+        if self.config.renderer.backend != RenderBackend::Gpu || !self.gpu.available() {
+            if surface.gpu.take().is_some() {
+                surface.previous_canvas.resize(0, 0);
+            }
+            return;
+        }
+        if surface.gpu.is_some() || !surface.configured || surface.width == 0 || surface.height == 0 {
+            return;
+        }
+        match self.gpu.surface(&self.connection, surface.role.wl_surface(), surface.width, surface.height) {
+            Ok(gpu) => {
+                surface.gpu = Some(gpu);
+                surface.swapchain = None;
+                surface.previous_canvas.resize(0, 0);
+            }
+            Err(e) => warn!("GPU rendering unavailable, using the pixel renderer: {:#}", e),
+        }
+    }
+
+    /// Render and commit one frame of the surface for `output_id`.
+    fn render_surface(&mut self, output_id: &wayland_client::backend::ObjectId) -> Result<()> {
+        #[cfg(feature = "gpu")]
+        self.prepare_gpu(output_id);
+        let surface = match self.surfaces.get_mut(output_id) {
             Some(s) => s,
-            None => return,
+            None => return Ok(()),
         };
 
         if !surface.configured || surface.width == 0 || surface.height == 0 {
-            return;
+            return Ok(());
         }
 
-        if !self.visible {
+        let output_hidden = surface.output_name.as_ref().is_some_and(|name| self.hidden_outputs.contains(name));
+        if !self.visible || output_hidden || self.privacy.hides_all() || self.workspace_rules.hides() {
             // Render a fully transparent frame
-            if surface.pool.is_none() {
-                let pool = SlotPool::new(
-                    (surface.width * surface.height * 4) as usize,
-                    &self.shm,
-                )
-                .expect("Failed to create slot pool");
-                surface.pool = Some(pool);
+            #[cfg(feature = "gpu")]
+            if let (Some(gpu), Some(context)) = (surface.gpu.as_mut(), self.gpu.context()) {
+                surface.canvas.resize(surface.width as usize, surface.height as usize);
+                surface.canvas.clear();
+                let damage = renderer::damage::dirty_rects(&surface.previous_canvas, &surface.canvas);
+                gpu.present(context, (surface.width, surface.height), &surface.canvas, damage.as_deref(), &mut Vec::new())?;
+                std::mem::swap(&mut surface.canvas, &mut surface.previous_canvas);
+                return Ok(());
             }
-            let pool = surface.pool.as_mut().unwrap();
-            let (buffer, canvas) = pool
-                .create_buffer(
-                    surface.width as i32,
-                    surface.height as i32,
-                    (surface.width * 4) as i32,
-                    wl_shm::Format::Argb8888,
-                )
-                .expect("Failed to create buffer");
+            let swapchain = swapchain_for(&mut surface.swapchain, &self.shm, surface.width, surface.height)?;
+            let Some((buffer, canvas)) = swapchain.acquire()? else {
+                return Ok(());
+            };
             canvas.fill(0);
-            let wl_surf = surface.layer_surface.wl_surface();
-            buffer.attach_to(wl_surf).expect("Failed to attach buffer");
+            // The next visible frame can't be diffed against this one
+            surface.previous_canvas.resize(0, 0);
+            let wl_surf = surface.role.wl_surface();
+            buffer.attach_to(wl_surf).context("Failed to attach buffer")?;
             wl_surf.damage_buffer(0, 0, surface.width as i32, surface.height as i32);
             wl_surf.commit();
-            return;
-        }
-
-        // Ensure we have a pool
-        if surface.pool.is_none() {
-            let pool = SlotPool::new(
-                (surface.width * surface.height * 4) as usize,
-                &self.shm,
-            )
-            .expect("Failed to create slot pool");
-            surface.pool = Some(pool);
+            return Ok(());
         }
 
-        let pool = surface.pool.as_mut().unwrap();
-
-        // Get a buffer from the pool
-        let (buffer, canvas) = pool
-            .create_buffer(
-                surface.width as i32,
-                surface.height as i32,
-                (surface.width * 4) as i32,
-                wl_shm::Format::Argb8888,
-            )
-            .expect("Failed to create buffer");
-
         // Resolve per-surface overrides
-        let color_scheme = surface.color_scheme_override.unwrap_or(self.color_scheme);
+        let color_scheme = surface.color_scheme_override.clone().unwrap_or_else(|| self.color_scheme.clone());
         let style = surface.style_override.unwrap_or(self.visualizer.current_style);
-        let opacity = surface.opacity_override.unwrap_or(self.config.visualizer.opacity);
+        let mut opacity = surface.opacity_override.unwrap_or(self.config.visualizer.opacity);
+        if let IdleFrame::Fade(factor) = self.idle {
+            opacity *= factor;
+        }
 
         // Render the visualizer to the canvas
         let width = surface.width as usize;
         let height = surface.height as usize;
-        let frequencies = surface.audio_data.frequencies.clone();
-        let waveform = surface.audio_data.waveform.clone();
+        let (frequencies, waveform, bands) = if self.idle == IdleFrame::Demo {
+            let bars = surface.audio_data.frequencies.len();
+            let samples = surface.audio_data.waveform.len();
+            let frequencies = idle::demo_frequencies(bars, self.time);
+            let bands = audio::Bands::from_bars(&frequencies);
+            (frequencies, idle::demo_waveform(samples, self.time), bands)
+        } else {
+            (surface.audio_data.frequencies.clone(), surface.audio_data.waveform.clone(), surface.audio_data.bands)
+        };
         let intensity = surface.audio_data.intensity;
-        let track_title = self.track_info.title.clone();
-        let track_artist = self.track_info.artist.clone();
-        let pixel_scale = 8;
-        let bar_width = (self.config.visualizer.bar_width as usize) * pixel_scale;
-        let bar_spacing = (self.config.visualizer.bar_spacing as usize) * pixel_scale;
+        // Privacy mode renders as if nothing were playing
+        let track_info = if self.privacy.active() { Arc::default() } else { self.track_info.clone() };
+        let track_title = track_info.title.clone();
+        let track_artist = track_info.artist.clone();
+        let pixel_scale = self.config.renderer.pixel_scale as usize;
+        let overrides = self.config.style_override(&renderer::styles::style_name(style)).cloned().unwrap_or_default();
+        let bar_width = (overrides.bar_width.unwrap_or(self.config.visualizer.bar_width) as usize) * pixel_scale;
+        let bar_spacing = (overrides.bar_spacing.unwrap_or(self.config.visualizer.bar_spacing) as usize) * pixel_scale;
+        let group_gap = (self.config.visualizer.group.gap as usize) * pixel_scale;
+        let segments = &self.config.visualizer.segments;
+        let (segment_height, segment_gap) = if segments.enabled {
+            (segments.height as usize * pixel_scale, segments.gap as usize * pixel_scale)
+        } else {
+            (0, 0)
+        };
         let time = self.time;
 
-        // Update spectrogram history for this surface
-        surface.spectrogram_history.push(frequencies.clone());
-        if surface.spectrogram_history.len() > height {
-            let excess = surface.spectrogram_history.len() - height;
-            surface.spectrogram_history.drain(..excess);
+        // Let the compositor upscale reduced-resolution frames (the clock and
+        // blank idle frames are drawn at full size)
+        let scale = self.config.renderer.supersample;
+        let compositor_scaled = surface.viewport.is_some()
+            && self.config.renderer.upscale == UpscaleFilter::Compositor
+            && scale < 1.0
+            && !matches!(self.idle, IdleFrame::Blank | IdleFrame::Clock);
+
+        // Update spectrogram history for this surface (one row per internal render row)
+        let (_, render_height) = renderer::scaled_size(width, height, scale);
+        renderer::styles::push_spectrogram_row(
+            &mut surface.spectrogram_history,
+            &frequencies,
+            &self.config.visualizer.spectrogram,
+            render_height,
+        );
+        renderer::styles::push_amplitude(&mut surface.amplitude_history, &waveform, time, &self.config.visualizer.seismograph);
+        surface.particles.update(&frequencies, time, &self.config.visualizer.particles);
+        if renderer::styles::draws_builtin(style, renderer::styles::FIRE_STYLE) {
+            surface.fire.update(&frequencies, time, &self.config.visualizer.fire);
         }
+        // The demo animation has no stereo, so these show its waveform as mono
+        let stereo = if self.idle == IdleFrame::Demo { &[][..] } else { &surface.audio_data.stereo[..] };
+        if renderer::styles::draws_builtin(style, renderer::styles::VECTORSCOPE_STYLE) {
+            surface.vectorscope.update(stereo, &waveform, time, &self.config.visualizer.vectorscope);
+        }
+        surface.vu.update(stereo, &waveform, time, &self.config.visualizer.vu);
+        surface.sparks.update(&frequencies, time, &self.config.effects.sparks);
 
         // Resize the per-surface canvas
-        surface.canvas.resize(width, height);
+        if !compositor_scaled {
+            surface.canvas.resize(width, height);
+        }
 
         let render_params = renderer::RenderParams {
             style,
             bar_width,
             bar_spacing,
-            mirror: self.config.visualizer.mirror,
+            mirror: overrides.mirror.unwrap_or(self.config.visualizer.mirror),
             reverse_mirror: self.config.visualizer.reverse_mirror,
+            interpolation: self.config.audio.interpolation,
+            group_size: self.config.visualizer.group.size,
+            group_gap,
+            segment_height,
+            segment_gap,
+            segment_zones: &segments.zones,
+            orientation: self.config.visualizer.orientation,
             opacity,
+            min_bar_height: self.config.visualizer.min_bar_height,
             color_scheme: &color_scheme,
             waveform: &waveform,
             spectrogram_history: &surface.spectrogram_history,
+            spectrogram: &self.config.visualizer.spectrogram,
+            amplitude_history: &surface.amplitude_history,
+            seismograph: &self.config.visualizer.seismograph,
+            karaoke: &self.config.visualizer.karaoke,
+            radial: &self.config.visualizer.radial,
+            oscilloscope: &self.config.visualizer.oscilloscope,
+            particles: &surface.particles,
+            fire: &surface.fire,
+            vectorscope: &surface.vectorscope,
+            vu: &surface.vu,
+            sparks: &surface.sparks,
             text_config: &self.config.text,
+            album_art: &self.config.album_art,
+            source_indicator: &self.config.source_indicator,
+            widgets: &self.config.widgets,
+            effects: &self.config.renderer.effects,
+            transform: renderer::transform::Transform::from_config(&self.config.visualizer),
+            bar_rounding: self.config.visualizer.bar_rounding,
+            symmetry: self.config.visualizer.symmetry,
+            background: self.background.as_ref().map(|image| (image, &self.config.wallpaper)),
+            font: self.font.as_ref(),
         };
 
+        let source = self
+            .audio_pipelines
+            .get(&surface.audio_source_key)
+            .or_else(|| self.audio_pipelines.get(&None))
+            .map(|pipeline| pipeline.capture.source_name());
+        let source = source.as_deref();
         let frame_data = renderer::FrameData {
             frequencies: &frequencies,
             intensity,
+            bands,
             track_title: &track_title,
             track_artist: &track_artist,
+            album_art: track_info.album_art.as_deref(),
+            source,
+            input_level: surface.audio_data.level,
             time,
+            text_opacity: self.track_change.title_opacity(&self.config.hooks.track_change),
+            flash: self.track_change.flash(&self.config.hooks.track_change),
+        };
+        // Bars and waveforms the GPU can draw, with the rest of the frame on top
+        #[cfg(feature = "gpu")]
+        let mut shapes = Vec::new();
+        #[cfg(feature = "gpu")]
+        let gpu_shapes = surface.gpu.is_some()
+            && scale == 1.0
+            && !self.config.effects.sparks.enabled
+            && self.config.wallpaper.edge_fade.effective() == (0, 0, 0, 0)
+            && renderer::geometry::drawable(&frame_data, &render_params);
+        match self.idle {
+            IdleFrame::Blank => surface.canvas.clear(),
+            IdleFrame::Clock => idle::render_clock(&mut surface.canvas, &idle::clock_text(), &render_params),
+            #[cfg(feature = "gpu")]
+            _ if gpu_shapes => {
+                renderer::geometry::build(&mut shapes, (width, height), &frame_data, &render_params);
+                renderer::render_overlay(&mut surface.canvas, &frame_data, &render_params);
+            }
+            _ if compositor_scaled => {
+                renderer::render_frame_at_scale(&mut surface.canvas, (width, height), &frame_data, &render_params, scale)
+            }
+            _ => renderer::render_frame_scaled(
+                &mut surface.canvas,
+                &mut surface.work_canvas,
+                &frame_data,
+                &render_params,
+                scale,
+                self.config.renderer.upscale,
+            ),
+        }
+        if let Some((text, osd_opacity)) = self.osd.current(self.config.widgets.osd.duration_secs) {
+            widgets::render_osd(&mut surface.canvas, text, osd_opacity, &render_params);
+        }
+        // Edge fade widths are in surface pixels; the canvas may be smaller
+        let (top, right, bottom, left) = self.config.wallpaper.edge_fade.effective();
+        let fade_scale = surface.canvas.width as f32 / width as f32;
+        renderer::effects::fade_edges(
+            &mut surface.canvas,
+            (top as f32 * fade_scale, right as f32 * fade_scale, bottom as f32 * fade_scale, left as f32 * fade_scale),
+        );
+
+        // Damage only what changed since the last frame; skip identical frames
+        let damage = renderer::damage::dirty_rects(&surface.previous_canvas, &surface.canvas);
+        #[cfg(feature = "gpu")]
+        if let (Some(gpu), Some(context)) = (surface.gpu.as_mut(), self.gpu.context()) {
+            gpu.present(context, (surface.width, surface.height), &surface.canvas, damage.as_deref(), &mut shapes)?;
+            std::mem::swap(&mut surface.canvas, &mut surface.previous_canvas);
+            return Ok(());
+        }
+        if damage.as_ref().is_some_and(|rects| rects.is_empty()) {
+            return Ok(());
+        }
+
+        // Draw into a buffer the compositor isn't reading; if it still holds
+        // all of them, drop this frame (it stays undamaged for the next one)
+        let (buffer_w, buffer_h) = (surface.canvas.width as u32, surface.canvas.height as u32);
+        let swapchain = swapchain_for(&mut surface.swapchain, &self.shm, buffer_w, buffer_h)?;
+        let Some((buffer, canvas)) = swapchain.acquire()? else {
+            return Ok(());
         };
-        renderer::render_frame(&mut surface.canvas, &frame_data, &render_params);
 
         // Convert RGBA to ARGB8888 for Wayland
         surface.canvas.write_argb8888(canvas);
 
         // Attach and commit
-        let wl_surf = surface.layer_surface.wl_surface();
-        buffer.attach_to(wl_surf).expect("Failed to attach buffer");
-        wl_surf.damage_buffer(0, 0, surface.width as i32, surface.height as i32);
+        let wl_surf = surface.role.wl_surface();
+        buffer.attach_to(wl_surf).context("Failed to attach buffer")?;
+        match damage {
+            Some(rects) => {
+                for rect in rects {
+                    wl_surf.damage_buffer(rect.x as i32, rect.y as i32, rect.width as i32, rect.height as i32);
+                }
+            }
+            None => wl_surf.damage_buffer(0, 0, buffer_w as i32, buffer_h as i32),
+        }
         wl_surf.commit();
+
+        // This frame becomes the reference; the old one is redrawn next frame
+        std::mem::swap(&mut surface.canvas, &mut surface.previous_canvas);
+        Ok(())
+    }
+
+    /// The settings the OSD reports on, as `(label, value)` pairs
+    fn osd_snapshot(&self) -> Vec<(&'static str, String)> {
+        let effects: Vec<_> = self.config.renderer.effects.iter().map(|e| e.name()).collect();
+        vec![
+            ("Style", self.visualizer.current_style_name()),
+            ("Colors", self.color_scheme.name().to_string()),
+            ("Theme", self.config.theme.clone().unwrap_or_default()),
+            ("Opacity", format!("{:.0}%", self.config.visualizer.opacity * 100.0)),
+            ("Sensitivity", self.config.audio.sensitivity.to_string()),
+            ("Source", self.current_source.clone().unwrap_or_else(|| "default".to_string())),
+            ("Effects", if effects.is_empty() { "none".to_string() } else { effects.join(", ") }),
+            ("Privacy", if self.privacy.active() { "on" } else { "off" }.to_string()),
+            ("Layer", self.config.wallpaper.layer.name().to_string()),
+            ("Anchor", self.config.wallpaper.anchor.name().to_string()),
+            ("Drag", if self.config.wallpaper.draggable { "on" } else { "off" }.to_string()),
+            ("Overlay", if self.config.wallpaper.overlay.enabled { "on" } else { "off" }.to_string()),
+        ]
     }
 
     /// Get a list of connected monitor names and their status
@@ -496,6 +1142,96 @@ impl WallpaperState {
         result
     }
 
+    /// A copy of the last frame committed on `output` (the first output with
+    /// a surface when None), for `screenshot`
+    fn screenshot(&mut self, output: Option<&str>) -> Result<renderer::Canvas, IpcResponse> {
+        let id = match output {
+            Some(name) => self.surfaces.iter().find(|(_, s)| s.output_name.as_deref() == Some(name)).map(|(id, _)| id.clone()),
+            None => self.output_state.outputs().map(|o| o.id()).find(|id| self.surfaces.contains_key(id)),
+        };
+        let Some(surface) = id.and_then(|id| self.surfaces.get_mut(&id)) else {
+            let Some(output) = output else {
+                return Err(IpcResponse::err("no surfaces configured"));
+            };
+            let outputs: Vec<String> = self.list_monitors().into_iter().filter(|(_, active)| *active).map(|(name, _)| name).collect();
+            return Err(IpcResponse::err(format!("No surface on output '{}' (outputs: {})", output, outputs.join(", "))));
+        };
+        // The GPU draws the bars itself; the canvas only holds what goes on top
+        #[cfg(feature = "gpu")]
+        if let (Some(gpu), Some(context)) = (surface.gpu.as_mut(), self.gpu.context()) {
+            return gpu.read_frame(context).map_err(|e| IpcResponse::err(format!("{:#}", e)));
+        }
+        Ok(surface.previous_canvas.clone())
+    }
+
+    /// Apply a command to one output only (`@DP-1 style next`), storing the
+    /// change as that output's `[[wallpaper.monitors]]` override
+    fn output_command(&mut self, output: &str, command: OutputCommand, pending: &mut PendingChanges) -> IpcResponse {
+        if self.config.wallpaper.multi_monitor != MultiMonitorMode::Independent {
+            return IpcResponse::err("per-output commands need wallpaper.multi_monitor = \"independent\"");
+        }
+        let Some(surface) = self.surfaces.values().find(|s| s.output_name.as_deref() == Some(output)) else {
+            let outputs: Vec<String> = self.list_monitors().into_iter().filter(|(_, active)| *active).map(|(name, _)| name).collect();
+            return IpcResponse::err(format!("No surface on output '{}' (outputs: {})", output, outputs.join(", ")));
+        };
+        let style = surface.style_override.unwrap_or(self.visualizer.current_style);
+        let color_scheme = surface.color_scheme_override.clone().unwrap_or_else(|| self.color_scheme.clone());
+        let opacity = surface.opacity_override.unwrap_or(self.config.visualizer.opacity);
+
+        let (text, data) = match command {
+            OutputCommand::Style(selection) => {
+                let count = renderer::styles::style_count();
+                let style = match selection {
+                    Selection::Next => (style + 1) % count,
+                    Selection::Prev => (style + count - 1) % count,
+                    Selection::Named(name) => match renderer::styles::resolve_style(&name) {
+                        Ok(idx) => idx,
+                        Err(e) => return IpcResponse::err(e),
+                    },
+                };
+                let name = renderer::styles::style_name(style);
+                self.config.wallpaper.monitor_mut(output).style = Some(name.to_lowercase());
+                (name.clone(), serde_json::json!(name))
+            }
+            OutputCommand::Color(selection) => {
+                let scheme = match selection {
+                    Selection::Next => color_scheme.next(),
+                    Selection::Prev => color_scheme.prev(),
+                    Selection::Named(name) => match ColorScheme::resolve(&name) {
+                        Ok(scheme) => scheme,
+                        Err(e) => return IpcResponse::err(e),
+                    },
+                };
+                let name = scheme.name().to_string();
+                self.config.wallpaper.monitor_mut(output).color_scheme = Some(scheme);
+                (name.clone(), serde_json::json!(name))
+            }
+            OutputCommand::Opacity(value) => {
+                let opacity = value.resolve(opacity).clamp(0.0, 1.0);
+                self.config.wallpaper.monitor_mut(output).opacity = Some(opacity);
+                (opacity.to_string(), serde_json::json!(opacity))
+            }
+            OutputCommand::Toggle => {
+                // Visibility is not saved, like the global toggle
+                let visible = self.hidden_outputs.remove(output);
+                if !visible {
+                    self.hidden_outputs.insert(output.to_string());
+                }
+                let state = if visible { "visible" } else { "hidden" };
+                return IpcResponse::ok_with(format!("{}: {}", output, state), serde_json::json!(visible));
+            }
+        };
+
+        let monitor = self.config.wallpaper.monitor_mut(output).clone();
+        for surface in self.surfaces.values_mut().filter(|s| s.output_name.as_deref() == Some(output)) {
+            surface.style_override = monitor.style.as_deref().and_then(renderer::styles::find_style);
+            surface.color_scheme_override = monitor.color_scheme.clone();
+            surface.opacity_override = monitor.opacity;
+        }
+        pending.save_config = true;
+        IpcResponse::ok_with(format!("{}: {}", output, text), data)
+    }
+
     fn update(&mut self, dt: f32) {
         self.time += dt;
         self.visualizer.update(dt);
@@ -520,33 +1256,19 @@ impl WallpaperState {
         // Apply per-surface: each monitor has its own screen dimensions
         let anchor_bits = Anchor::TOP | Anchor::LEFT;
         for surface in self.surfaces.values_mut() {
-            let sw = surface.screen_width as i32;
-            let sh = surface.screen_height as i32;
-            let w = surface.width as i32;
-            let h = surface.height as i32;
-
-            // Calculate current top-left position based on original anchor
-            let (x, y) = match original_anchor {
-                WallpaperAnchor::TopLeft => (ml, mt),
-                WallpaperAnchor::Top => ((sw - w) / 2, mt),
-                WallpaperAnchor::TopRight => (sw - w - mr, mt),
-                WallpaperAnchor::Left => (ml, (sh - h) / 2),
-                WallpaperAnchor::Center => ((sw - w) / 2, (sh - h) / 2),
-                WallpaperAnchor::Right => (sw - w - mr, (sh - h) / 2),
-                WallpaperAnchor::BottomLeft => (ml, sh - h - mb),
-                WallpaperAnchor::Bottom => ((sw - w) / 2, sh - h - mb),
-                WallpaperAnchor::BottomRight => (sw - w - mr, sh - h - mb),
-                WallpaperAnchor::Fullscreen => (0, 0),
+            let Some(layer) = surface.role.layer().cloned() else {
+                continue;
             };
+            let (x, y) = anchored_position(original_anchor, (mt, mr, mb, ml), surface);
 
             surface.margin_top = y;
             surface.margin_left = x;
-            surface.layer_surface.set_anchor(anchor_bits);
-            surface.layer_surface.set_margin(y, 0, 0, x);
+            layer.set_anchor(anchor_bits);
+            layer.set_margin(y, 0, 0, x);
             let (ew, eh) = surface.explicit_size.unwrap_or((surface.width, surface.height));
-            surface.layer_surface.set_size(ew, eh);
+            layer.set_size(ew, eh);
             surface.explicit_size = Some((ew, eh));
-            surface.layer_surface.commit();
+            layer.commit();
         }
 
         // Update global config
@@ -581,15 +1303,138 @@ impl WallpaperState {
             if let Some(surface) = self.surfaces.get_mut(surface_id) {
                 surface.margin_left += dx as i32;
                 surface.margin_top += dy as i32;
-                surface.layer_surface.set_margin(surface.margin_top, 0, 0, surface.margin_left);
-                surface.layer_surface.commit();
+                if let Some(layer) = surface.role.layer() {
+                    layer.set_margin(surface.margin_top, 0, 0, surface.margin_left);
+                    layer.commit();
+                }
             }
         }
     }
 
+    /// Snap the dragged surface to the nearest screen edge or center line on
+    /// each axis, if it was released within `wallpaper.snap_distance` of one.
+    fn snap_dragged_surface(&mut self) {
+        let distance = self.config.wallpaper.snap_distance as i32;
+        let Some(surface) = self.drag.surface_id.as_ref().and_then(|id| self.surfaces.get_mut(id)) else {
+            return;
+        };
+        if distance == 0 {
+            return;
+        }
+        let x = snap_axis(surface.margin_left, surface.width as i32, surface.screen_width as i32, distance);
+        let y = snap_axis(surface.margin_top, surface.height as i32, surface.screen_height as i32, distance);
+        if (x, y) == (surface.margin_left, surface.margin_top) {
+            return;
+        }
+        surface.margin_left = x;
+        surface.margin_top = y;
+        if let Some(layer) = surface.role.layer() {
+            layer.set_margin(y, 0, 0, x);
+            layer.commit();
+        }
+    }
+
+    /// Top-left offset of every surface on its output, for `drag position`.
+    fn drag_position(&self) -> IpcResponse {
+        if self.surfaces.is_empty() {
+            return IpcResponse::err("no surfaces");
+        }
+        let anchor = self.config.wallpaper.anchor;
+        let margins = self.config.wallpaper.effective_margins();
+        let mut lines = Vec::new();
+        let mut data = serde_json::Map::new();
+        for surface in self.surfaces.values() {
+            let (x, y) = if anchor == WallpaperAnchor::TopLeft {
+                (surface.margin_left, surface.margin_top)
+            } else {
+                anchored_position(anchor, margins, surface)
+            };
+            let output = surface.output_name.clone().unwrap_or_else(|| "unknown".to_string());
+            lines.push(format!("{}: {},{} ({}x{})", output, x, y, surface.width, surface.height));
+            data.insert(
+                output,
+                serde_json::json!({ "x": x, "y": y, "width": surface.width, "height": surface.height }),
+            );
+        }
+        IpcResponse::ok_with(lines.join("\n"), serde_json::Value::Object(data))
+    }
+
+    /// Move every surface to the top-left offset (`x`, `y`), or by it when
+    /// `relative`, and save the position like a drag does.
+    fn move_surfaces(&mut self, x: i32, y: i32, relative: bool) -> IpcResponse {
+        if self.config.wallpaper.anchor == WallpaperAnchor::Fullscreen {
+            return IpcResponse::err("cannot move in fullscreen anchor mode");
+        }
+        if self.config.wallpaper.anchor != WallpaperAnchor::TopLeft {
+            self.convert_to_topleft_anchor();
+        }
+        for surface in self.surfaces.values_mut() {
+            if relative {
+                surface.margin_left += x;
+                surface.margin_top += y;
+            } else {
+                surface.margin_left = x;
+                surface.margin_top = y;
+            }
+            if let Some(layer) = surface.role.layer() {
+                layer.set_margin(surface.margin_top, 0, 0, surface.margin_left);
+                layer.commit();
+            }
+        }
+        let Some(surface) = self.surfaces.values().next() else {
+            return IpcResponse::err("no surfaces");
+        };
+        let (left, top) = (surface.margin_left, surface.margin_top);
+        self.config.wallpaper.margin_top = top;
+        self.config.wallpaper.margin_left = left;
+        self.save_position_to_config();
+        IpcResponse::ok_with(format!("position {},{}", left, top), serde_json::json!({ "x": left, "y": top }))
+    }
+
     /// Save current state to the config file (style, color, layer, position, etc.)
     /// Creates the config file from the default template if it doesn't exist.
-    fn save_state_to_config(&self) {
+    /// Runtime state `save_state_to_config` writes back, by dotted key
+    fn state_values(&self) -> Vec<(&'static str, toml_edit::Value)> {
+        let wallpaper = &self.config.wallpaper;
+        let mut values: Vec<(&'static str, toml_edit::Value)> = vec![
+            ("visualizer.style", self.visualizer.current_style_name().to_lowercase().into()),
+            ("visualizer.color_scheme", self.color_scheme.name().to_lowercase().into()),
+            ("visualizer.opacity", (self.config.visualizer.opacity as f64).into()),
+            ("color.brightness", (self.config.color.brightness as f64).into()),
+            ("color.saturation", (self.config.color.saturation as f64).into()),
+            ("color.gamma", (self.config.color.gamma as f64).into()),
+            ("renderer.effects", self.config.renderer.effects.iter().map(|e| e.name()).collect::<toml_edit::Array>().into()),
+            ("text.show_title", self.config.text.show_title.into()),
+            ("text.show_artist", self.config.text.show_artist.into()),
+            ("text.position", self.config.text.position.to_string().into()),
+            ("text.font_style", format!("{:?}", self.config.text.font_style).to_lowercase().into()),
+            ("text.animation_style", format!("{:?}", self.config.text.animation_style).to_lowercase().into()),
+            ("wallpaper.layer", wallpaper.layer.name().into()),
+            ("wallpaper.anchor", wallpaper.anchor.name().into()),
+            ("wallpaper.draggable", wallpaper.draggable.into()),
+            ("wallpaper.margin", (wallpaper.margin as i64).into()),
+            ("wallpaper.margin_top", (wallpaper.margin_top as i64).into()),
+            ("wallpaper.margin_right", (wallpaper.margin_right as i64).into()),
+            ("wallpaper.margin_bottom", (wallpaper.margin_bottom as i64).into()),
+            ("wallpaper.margin_left", (wallpaper.margin_left as i64).into()),
+            // Only the on/off state; the rest of [wallpaper.overlay] is hand-edited
+            ("wallpaper.overlay.enabled", wallpaper.overlay.enabled.into()),
+        ];
+        if let Some(ref theme) = self.config.theme {
+            values.push(("theme", theme.as_str().into()));
+        }
+        if let Some(ref w) = wallpaper.width {
+            values.push(("wallpaper.width", w.as_str().into()));
+        }
+        if let Some(ref h) = wallpaper.height {
+            values.push(("wallpaper.height", h.as_str().into()));
+        }
+        values
+    }
+
+    /// Write the runtime state that changed since startup (or the last save)
+    /// back to the config file.
+    fn save_state_to_config(&mut self) {
         let Some(path) = Config::default_path() else {
             return;
         };
@@ -611,44 +1456,30 @@ impl WallpaperState {
             Ok(content) => {
                 match content.parse::<toml_edit::DocumentMut>() {
                     Ok(mut doc) => {
-                        // Ensure [visualizer] section exists
-                        if !doc.contains_key("visualizer") {
-                            doc["visualizer"] = toml_edit::table();
+                        let saved = self.state_values();
+                        for (key, value) in &saved {
+                            // Unchanged since the file was last loaded or saved: leave
+                            // the file's own value (e.g. `style = "random"`) alone
+                            if self.saved_state.get(*key) == Some(&value.to_string()) {
+                                continue;
+                            }
+                            let (parents, field) = key.rsplit_once('.').unwrap_or(("", key));
+                            let mut item = doc.as_item_mut();
+                            for section in parents.split('.').filter(|s| !s.is_empty()) {
+                                if item.get(section).is_none() {
+                                    item[section] = toml_edit::table();
+                                }
+                                item = &mut item[section];
+                            }
+                            item[field] = toml_edit::value(value.clone());
                         }
-                        doc["visualizer"]["style"] = toml_edit::value(self.visualizer.current_style_name().to_lowercase());
-                        doc["visualizer"]["color_scheme"] = toml_edit::value(self.color_scheme.name().to_lowercase());
-                        doc["visualizer"]["opacity"] = toml_edit::value(self.config.visualizer.opacity as f64);
 
-                        // Ensure [text] section exists
-                        if !doc.contains_key("text") {
-                            doc["text"] = toml_edit::table();
-                        }
-                        doc["text"]["show_title"] = toml_edit::value(self.config.text.show_title);
-                        doc["text"]["show_artist"] = toml_edit::value(self.config.text.show_artist);
-                        doc["text"]["position"] = toml_edit::value(self.config.text.position.to_string());
-                        doc["text"]["font_style"] = toml_edit::value(format!("{:?}", self.config.text.font_style).to_lowercase());
-                        doc["text"]["animation_style"] = toml_edit::value(format!("{:?}", self.config.text.animation_style).to_lowercase());
+                        save_output_overrides(&mut doc, &self.config.wallpaper);
 
-                        // Ensure [wallpaper] section exists
-                        if !doc.contains_key("wallpaper") {
-                            doc["wallpaper"] = toml_edit::table();
-                        }
-                        doc["wallpaper"]["layer"] = toml_edit::value(self.config.wallpaper.layer.name());
-                        doc["wallpaper"]["anchor"] = toml_edit::value(self.config.wallpaper.anchor.name());
-                        doc["wallpaper"]["draggable"] = toml_edit::value(self.config.wallpaper.draggable);
-                        doc["wallpaper"]["margin"] = toml_edit::value(self.config.wallpaper.margin as i64);
-                        doc["wallpaper"]["margin_top"] = toml_edit::value(self.config.wallpaper.margin_top as i64);
-                        doc["wallpaper"]["margin_right"] = toml_edit::value(self.config.wallpaper.margin_right as i64);
-                        doc["wallpaper"]["margin_bottom"] = toml_edit::value(self.config.wallpaper.margin_bottom as i64);
-                        doc["wallpaper"]["margin_left"] = toml_edit::value(self.config.wallpaper.margin_left as i64);
-                        if let Some(ref w) = self.config.wallpaper.width {
-                            doc["wallpaper"]["width"] = toml_edit::value(w.as_str());
+                        match std::fs::write(&path, doc.to_string()) {
+                            Ok(()) => self.saved_state = saved.into_iter().map(|(key, value)| (key, value.to_string())).collect(),
+                            Err(e) => tracing::warn!("Failed to save config: {}", e),
                         }
-                        if let Some(ref h) = self.config.wallpaper.height {
-                            doc["wallpaper"]["height"] = toml_edit::value(h.as_str());
-                        }
-
-                        let _ = std::fs::write(&path, doc.to_string());
                     }
                     Err(e) => {
                         tracing::warn!("Failed to parse config for save: {}", e);
@@ -812,7 +1643,7 @@ impl LayerShellHandler for WallpaperState {
 
         // Find the OutputSurface matching this layer surface
         let output_id = self.surfaces.iter()
-            .find(|(_, s)| &s.layer_surface == layer)
+            .find(|(_, s)| s.role.layer() == Some(layer))
             .map(|(id, _)| id.clone());
 
         let output_id = match output_id {
@@ -836,19 +1667,57 @@ impl LayerShellHandler for WallpaperState {
             (w, h)
         };
 
-        surface.width = width;
-        surface.height = height;
-
         info!("Layer surface configured for {:?}: {}x{} (suggested: {}x{}, explicit: {:?})",
-              surface.output_name, surface.width, surface.height, suggested_width, suggested_height, surface.explicit_size);
+              surface.output_name, width, height, suggested_width, suggested_height, surface.explicit_size);
+
+        self.apply_configure(&output_id, width, height);
+    }
+}
 
-        // Recreate the pool for new size
-        surface.pool = None;
+impl WindowHandler for WallpaperState {
+    fn request_close(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _window: &Window) {
+        self.running = false;
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        window: &Window,
+        configure: WindowConfigure,
+        _serial: u32,
+    ) {
+        let Some((output_id, surface)) = self.surfaces.iter().find(|(_, s)| s.role.wl_surface() == window.wl_surface()) else {
+            return;
+        };
+
+        // No suggestion (before the maximize applies) means we pick: the whole output
+        let width = configure.new_size.0.map_or(surface.screen_width, NonZeroU32::get);
+        let height = configure.new_size.1.map_or(surface.screen_height, NonZeroU32::get);
+        info!("Fallback window configured: {}x{}", width, height);
+
+        let output_id = output_id.clone();
+        self.apply_configure(&output_id, width, height);
+    }
+}
+
+impl WallpaperState {
+    /// Adopt the configured size of a surface and draw its first frame
+    fn apply_configure(&mut self, output_id: &wayland_client::backend::ObjectId, width: u32, height: u32) {
+        let Some(surface) = self.surfaces.get_mut(output_id) else {
+            return;
+        };
+        surface.width = width;
+        surface.height = height;
+        // Buffers of any size (e.g. reduced-resolution frames) fill the surface
+        if let Some(viewport) = &surface.viewport {
+            viewport.set_destination(width as i32, height as i32);
+        }
         surface.configured = true;
 
         // Initial draw so the surface isn't blank before the main loop catches up
-        let wl_surface = layer.wl_surface();
-        self.draw_surface(wl_surface);
+        let wl_surface = surface.role.wl_surface().clone();
+        self.draw_surface(&wl_surface);
     }
 }
 
@@ -882,6 +1751,7 @@ impl SeatHandler for WallpaperState {
             if let Some(ref mut seat_state) = self.seat_state {
                 if let Ok(pointer) = seat_state.get_pointer(qh, &seat) {
                     self.pointer = Some(pointer);
+                    self.seat = Some(seat);
                     info!("Pointer capability acquired (drag-to-move available)");
                 }
             }
@@ -898,6 +1768,7 @@ impl SeatHandler for WallpaperState {
         if capability == Capability::Pointer {
             if let Some(pointer) = self.pointer.take() {
                 pointer.release();
+                self.seat = None;
                 self.drag = DragState::default();
                 info!("Pointer capability removed");
             }
@@ -921,7 +1792,15 @@ impl PointerHandler for WallpaperState {
         _pointer: &wl_pointer::WlPointer,
         events: &[PointerEvent],
     ) {
-        if !self.config.wallpaper.draggable {
+        if self.config.wallpaper.pointer_actions {
+            self.handle_pointer_actions(events);
+        }
+        if self.window_mode {
+            self.move_window(events);
+            return;
+        }
+        // The overlay stays at its configured corner
+        if !self.config.wallpaper.draggable || self.config.wallpaper.overlay.enabled {
             return;
         }
 
@@ -929,7 +1808,7 @@ impl PointerHandler for WallpaperState {
             match event.kind {
                 PointerEventKind::Enter { .. } => {
                     // Track which surface the pointer entered
-                    if let Some((id, _)) = self.surfaces.iter().find(|(_, s)| s.layer_surface.wl_surface() == &event.surface) {
+                    if let Some((id, _)) = self.surfaces.iter().find(|(_, s)| s.role.wl_surface() == &event.surface) {
                         self.drag.surface_id = Some(id.clone());
                     }
                     self.drag.last_x = event.position.0;
@@ -950,22 +1829,80 @@ impl PointerHandler for WallpaperState {
                     self.drag.pending_dx += dx;
                     self.drag.pending_dy += dy;
                 }
-                PointerEventKind::Release { button, .. } if button == BTN_LEFT => {
-                    if self.drag.is_dragging {
-                        self.drag.is_dragging = false;
-                        self.drag.save_pending = true;
-                    }
+                PointerEventKind::Release { button, .. } if button == BTN_LEFT && self.drag.is_dragging => {
+                    self.drag.is_dragging = false;
+                    self.drag.save_pending = true;
+                }
+                PointerEventKind::Leave { .. } if self.drag.is_dragging => {
+                    self.drag.is_dragging = false;
+                    self.drag.save_pending = true;
                 }
-                PointerEventKind::Leave { .. } => {
-                    if self.drag.is_dragging {
-                        self.drag.is_dragging = false;
-                        self.drag.save_pending = true;
+                _ => {}
+            }
+        }
+    }
+}
+
+impl WallpaperState {
+    /// Window mode: dragging with the left button moves the window, so it can
+    /// be moved without decorations (GNOME draws none for other toolkits)
+    fn move_window(&self, events: &[PointerEvent]) {
+        let Some(seat) = &self.seat else {
+            return;
+        };
+        for event in events {
+            if let PointerEventKind::Press { button, serial, .. } = event.kind {
+                let window = self.surfaces.values().find_map(|s| match &s.role {
+                    SurfaceRole::Window(window) if window.wl_surface() == &event.surface => Some(window),
+                    _ => None,
+                });
+                if let (BTN_LEFT, Some(window)) = (button, window) {
+                    window.move_(seat, serial);
+                }
+            }
+        }
+    }
+
+    /// Queue the IPC commands for `pointer_actions`: scrolling down/up steps to
+    /// the next/previous style, middle-click toggles the song text.
+    fn handle_pointer_actions(&mut self, events: &[PointerEvent]) {
+        for event in events {
+            match event.kind {
+                PointerEventKind::Axis { vertical, .. } => {
+                    // Wheels report notches; touchpads only a distance
+                    let steps = if vertical.discrete != 0 {
+                        vertical.discrete
+                    } else {
+                        self.scroll += vertical.absolute;
+                        let steps = (self.scroll / SCROLL_STEP).trunc();
+                        self.scroll -= steps * SCROLL_STEP;
+                        steps as i32
+                    };
+                    for _ in 0..steps.unsigned_abs() {
+                        let reply = tokio::sync::oneshot::channel().0;
+                        self.pointer_commands.push(if steps > 0 {
+                            IpcCommand::StyleNext { reply }
+                        } else {
+                            IpcCommand::StylePrev { reply }
+                        });
                     }
                 }
+                PointerEventKind::Press { button, .. } if button == BTN_MIDDLE => {
+                    let reply = tokio::sync::oneshot::channel().0;
+                    self.pointer_commands.push(IpcCommand::TextToggle { reply });
+                }
                 _ => {}
             }
         }
     }
+
+    /// Reapply the click-through input region to every surface
+    fn apply_input_regions(&self) {
+        for surface in self.surfaces.values() {
+            apply_input_region(&self.compositor_state, &self.surface_layout(&surface.output_name), surface.role.wl_surface());
+            surface.role.wl_surface().commit();
+        }
+    }
 }
 
 impl ProvidesRegistryState for WallpaperState {
@@ -979,19 +1916,40 @@ impl ProvidesRegistryState for WallpaperState {
 delegate_compositor!(WallpaperState);
 delegate_output!(WallpaperState);
 delegate_layer!(WallpaperState);
+delegate_xdg_shell!(WallpaperState);
+delegate_xdg_window!(WallpaperState);
 delegate_shm!(WallpaperState);
 delegate_seat!(WallpaperState);
 delegate_pointer!(WallpaperState);
 delegate_registry!(WallpaperState);
+wayland_client::delegate_noop!(WallpaperState: ignore wp_viewporter::WpViewporter);
+wayland_client::delegate_noop!(WallpaperState: ignore wp_viewport::WpViewport);
+
+/// Decode the configured background image, logging (and skipping) failures.
+fn load_background(config: &Config) -> Option<Background> {
+    let path = config.wallpaper.background_image.as_deref()?;
+    match Background::load(path) {
+        Ok(background) => Some(background),
+        Err(e) => {
+            tracing::warn!("{:#}", e);
+            None
+        }
+    }
+}
 
-/// Run the Wayland layer-shell wallpaper mode
-pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<()> {
-    info!("Starting Wayland layer-shell wallpaper mode");
+/// Load the configured text font, logging failures (the bitmap font is used instead).
+fn load_font(config: &Config) -> Option<TrueTypeFont> {
+    TrueTypeFont::from_config(&config.text).unwrap_or_else(|e| {
+        tracing::warn!("Using the bitmap font: {:#}", e);
+        None
+    })
+}
 
-    // Connect to Wayland
+/// Connect to the compositor and bind the globals wallpaper mode needs
+fn connect(window_mode: bool) -> Result<(EventQueue<WallpaperState>, GlobalList, BoundGlobals)> {
     let conn = Connection::connect_to_env().context("Failed to connect to Wayland display")?;
 
-    let (globals, mut event_queue) =
+    let (globals, event_queue) =
         registry_queue_init(&conn).context("Failed to initialize Wayland registry")?;
 
     let qh = event_queue.handle();
@@ -999,27 +1957,50 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
     // Initialize required globals
     let compositor_state =
         CompositorState::bind(&globals, &qh).context("wl_compositor not available")?;
-    let layer_shell = LayerShell::bind(&globals, &qh).context(
-        "wlr-layer-shell not available. Your compositor may not support this protocol.",
-    )?;
+    // Window mode always uses xdg_shell; without layer-shell (e.g. GNOME)
+    // wallpaper mode degrades to a maximized desktop window
+    let (layer_shell, xdg_shell) = if window_mode {
+        let xdg_shell = XdgShell::bind(&globals, &qh).context("xdg_shell is not available")?;
+        (None, Some(xdg_shell))
+    } else {
+        match LayerShell::bind(&globals, &qh) {
+            Ok(layer_shell) => (Some(layer_shell), None),
+            Err(e) => {
+                tracing::warn!("wlr-layer-shell not available ({}); falling back to a desktop window", e);
+                let xdg_shell = XdgShell::bind(&globals, &qh)
+                    .context("Neither wlr-layer-shell nor xdg_shell is available")?;
+                (None, Some(xdg_shell))
+            }
+        }
+    };
     let shm = Shm::bind(&globals, &qh).context("wl_shm not available")?;
     let output_state = OutputState::new(&globals, &qh);
     let registry_state = RegistryState::new(&globals);
+    // Optional: without it reduced render scales are upscaled in software
+    let viewporter = globals.bind::<wp_viewporter::WpViewporter, _, _>(&qh, 1..=1, ()).ok();
 
-    // Create state (without seat — initialized after surfaces are configured)
-    let mut state = WallpaperState::new(
+    let bound = BoundGlobals {
         registry_state,
         output_state,
         compositor_state,
         shm,
         layer_shell,
-        config.clone(),
-        ipc_rx,
-    );
+        xdg_shell,
+        viewporter,
+        #[cfg(feature = "gpu")]
+        connection: conn,
+    };
+    Ok((event_queue, globals, bound))
+}
+
+/// Create a surface per output on a fresh connection, wait until one is
+/// configured, then bind the seat.
+fn create_surfaces(state: &mut WallpaperState, event_queue: &mut EventQueue<WallpaperState>, globals: &GlobalList) -> Result<()> {
+    let qh = event_queue.handle();
 
     // Do an initial roundtrip to discover outputs
     event_queue
-        .roundtrip(&mut state)
+        .roundtrip(state)
         .context("Failed initial Wayland roundtrip")?;
 
     // Create layer surfaces for all known outputs
@@ -1027,7 +2008,7 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
 
     // Do another roundtrip to get configure events
     event_queue
-        .roundtrip(&mut state)
+        .roundtrip(state)
         .context("Failed Wayland roundtrip after surface creation")?;
 
     // Wait for at least one surface to be configured
@@ -1035,7 +2016,7 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
     let timeout = Duration::from_secs(30);
     while !state.any_configured() && start.elapsed() < timeout {
         event_queue
-            .roundtrip(&mut state)
+            .roundtrip(state)
             .context("Wayland roundtrip failed while waiting for configure")?;
         if !state.any_configured() {
             std::thread::sleep(Duration::from_millis(100));
@@ -1043,19 +2024,87 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
     }
 
     if !state.any_configured() {
+        if state.layer_shell.is_none() {
+            anyhow::bail!("No window was configured within 30 seconds - compositor may not support xdg-shell or no outputs available");
+        }
         anyhow::bail!("No layer surface was configured within 30 seconds - compositor may not support wlr-layer-shell or no outputs available");
     }
 
-    let configured_count = state.surfaces.values().filter(|s| s.configured).count();
-    let total_count = state.surfaces.len();
-    info!("Layer surfaces configured: {}/{} outputs", configured_count, total_count);
+    if state.layer_shell.is_none() {
+        info!("Window configured");
+    } else {
+        let configured_count = state.surfaces.values().filter(|s| s.configured).count();
+        let total_count = state.surfaces.len();
+        info!("Layer surfaces configured: {}/{} outputs", configured_count, total_count);
+    }
 
     // Initialize seat/pointer for drag-to-move (after surfaces are configured
     // to avoid interfering with layer surface setup)
-    state.seat_state = Some(SeatState::new(&globals, &qh));
+    state.seat_state = Some(SeatState::new(globals, &qh));
     event_queue
-        .roundtrip(&mut state)
+        .roundtrip(state)
         .context("Failed Wayland roundtrip after seat initialization")?;
+    Ok(())
+}
+
+/// After the connection was lost: retry with backoff until the compositor is
+/// back, then recreate the surfaces. `None` when shutdown was requested while
+/// waiting.
+fn reconnect(state: &mut WallpaperState) -> Result<Option<EventQueue<WallpaperState>>> {
+    let started = Instant::now();
+    let mut delay = RECONNECT_MIN_DELAY;
+    loop {
+        // Sleep in short steps so `cavibe stop` and SIGTERM stay responsive
+        let wake = Instant::now() + delay;
+        while Instant::now() < wake {
+            if crate::daemon::shutdown_requested() {
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        let attempt = connect(state.window_mode).and_then(|(mut event_queue, globals, bound)| {
+            state.rebind(bound);
+            create_surfaces(state, &mut event_queue, &globals)?;
+            Ok(event_queue)
+        });
+        match attempt {
+            Ok(event_queue) => {
+                info!("Reconnected to the Wayland compositor");
+                return Ok(Some(event_queue));
+            }
+            Err(e) if started.elapsed() < RECONNECT_TIMEOUT => {
+                tracing::debug!("Wayland reconnect failed: {:#}", e);
+                delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+            }
+            Err(e) => {
+                return Err(e.context(format!(
+                    "Wayland compositor did not come back within {} seconds",
+                    RECONNECT_TIMEOUT.as_secs()
+                )));
+            }
+        }
+    }
+}
+
+/// Run the Wayland layer-shell wallpaper mode
+pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<()> {
+    let window_mode = config.display.mode == DisplayMode::Window;
+    if window_mode {
+        info!("Starting Wayland window mode");
+    } else {
+        info!("Starting Wayland layer-shell wallpaper mode");
+    }
+    #[cfg(not(feature = "gpu"))]
+    if config.renderer.backend == RenderBackend::Gpu {
+        warn!("renderer.backend = \"gpu\" needs a build with the `gpu` feature; using the pixel renderer");
+    }
+
+    let (mut event_queue, globals, bound) = connect(window_mode)?;
+    // Create state (without seat — initialized after surfaces are configured)
+    let mut state = WallpaperState::new(bound, config.clone(), ipc_rx);
+    create_surfaces(&mut state, &mut event_queue, &globals)?;
+    let mut qh = event_queue.handle();
 
     // Collect unique audio sources across all surfaces
     // Always include None (default) for surfaces without an override
@@ -1070,23 +2119,20 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
 
     // Create one audio pipeline per unique source
     for source in &audio_sources {
-        // For the default pipeline, use config.audio.device; for overrides, use the sink name
-        let device = source.clone().or_else(|| config.audio.device.clone());
-        let (capture, rx) = audio::create_audio_pipeline(
-            config.visualizer.bars,
-            config.audio.smoothing,
-            config.audio.sensitivity,
-            device,
-        )?;
+        // For the default pipeline, use config.audio (device or app); for overrides, use the sink name
+        let (capture, rx) = match source {
+            None => audio::create_audio_pipeline_for(config.visualizer.bars, &config.audio, None)?,
+            Some(sink) => audio::create_audio_pipeline(config.visualizer.bars, &config.audio, Some(sink.clone()))?,
+        };
         state.audio_pipelines.insert(source.clone(), AudioPipeline {
-            _capture: capture,
+            capture,
             rx,
         });
         info!("Audio pipeline created for source: {:?}", source);
     }
 
     // Start metadata watcher
-    let metadata_rx = metadata::start_watcher();
+    let metadata_rx = metadata::start_watcher(config.album_art.enabled, config.visualizer.pause_on_media_pause);
 
     info!("Wayland wallpaper mode running. Press Ctrl+C to stop.");
 
@@ -1095,22 +2141,26 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
     // Style rotation timer
     let mut style_timer = Instant::now();
     let rotation_interval = Duration::from_secs(config.display.rotation_interval_secs);
-    let color_schemes = [
-        ColorScheme::Spectrum,
-        ColorScheme::Rainbow,
-        ColorScheme::Fire,
-        ColorScheme::Ocean,
-        ColorScheme::Forest,
-        ColorScheme::Purple,
-        ColorScheme::Monochrome,
-    ];
+    let color_schemes = ColorScheme::all();
     let mut color_scheme_idx = color_schemes
         .iter()
-        .position(|&c| c == config.visualizer.color_scheme)
+        .position(|c| *c == config.visualizer.color_scheme)
         .unwrap_or(0);
+    let mut rotator = Rotator::new(config.display.rotate_order);
+
+    let mut hooks = HookRunner::default();
+    let mut stats = StatsRecorder::default();
+    let mut color_dynamics = ColorDynamics::default();
+    let mut last_audio = Instant::now();
+    // Whether workspace rules hid the surfaces when they were last drawn
+    let mut rules_hid = false;
 
     // Main loop
     while state.running {
+        // `cavibe stop` or SIGTERM
+        if crate::daemon::shutdown_requested() {
+            break;
+        }
         let frame_start = Instant::now();
 
         // Collect latest audio data from all pipelines
@@ -1118,100 +2168,194 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
         for (source_key, pipeline) in &state.audio_pipelines {
             latest_audio.insert(source_key.clone(), pipeline.rx.borrow().clone());
         }
+        if let Some(data) = latest_audio.get(&None) {
+            crate::web::publish(data);
+        }
 
         // Update each surface's audio data based on its source key
         for surface in state.surfaces.values_mut() {
-            let key = &surface.audio_source_key;
-            if let Some(data) = latest_audio.get(key) {
-                surface.audio_data = data.clone();
-            } else if let Some(data) = latest_audio.get(&None) {
-                // Fall back to default pipeline
-                surface.audio_data = data.clone();
+            // Fall back to default pipeline
+            let data = latest_audio.get(&surface.audio_source_key).or_else(|| latest_audio.get(&None));
+            if let Some(data) = data {
+                // A monitor's bar count wins over the style's
+                let style = surface.style_override.unwrap_or(state.visualizer.current_style);
+                let overrides = state.config.style_override(&renderer::styles::style_name(style));
+                let bars = surface.bars_override.or(overrides.and_then(|o| o.bars));
+                surface.audio_data = data.reshaped(bars, overrides.and_then(|o| o.sensitivity));
             }
         }
 
-        // Detect whether any audio is playing
+        // Detect whether any audio is playing, holding the active state for
+        // `idle_timeout_secs` after it stops
+        let threshold = state.config.performance.idle_threshold;
         let has_audio = state.surfaces.values()
-            .any(|s| s.audio_data.intensity > 0.001);
-
-        if has_audio && !state.active {
-            state.active = true;
-        } else if !has_audio && state.active {
-            state.active = false;
+            .any(|s| s.audio_data.intensity > threshold);
+        if has_audio {
+            last_audio = Instant::now();
         }
+        let hold = Duration::try_from_secs_f32(state.config.performance.idle_timeout_secs).unwrap_or(Duration::MAX);
+        // A paused player leaves only noise; go idle right away
+        let media_paused = state.config.visualizer.pause_on_media_pause && metadata_rx.borrow().paused;
+        state.active = !media_paused && (has_audio || (state.active && last_audio.elapsed() < hold));
 
         // Update metadata
         state.track_info = metadata_rx.borrow().clone();
 
+        // Run user hooks against the global (non-override) style and colors
+        let intensity = state.surfaces.values()
+            .map(|s| s.audio_data.intensity)
+            .fold(0.0, f32::max);
+        hooks.update(
+            &state.config.hooks,
+            &state.track_info,
+            &state.visualizer.current_style_name(),
+            state.color_scheme.name(),
+            intensity,
+        );
+
         // Calculate delta time
         let dt = state.last_frame.elapsed().as_secs_f32();
         state.last_frame = Instant::now();
         state.update(dt);
 
+        let frequencies = latest_audio.get(&None).map(|data| data.frequencies.as_slice()).unwrap_or_default();
+        if color_dynamics.update(&state.config.color_dynamics, frequencies, intensity, dt) {
+            state.color_scheme = state.color_scheme.next();
+        }
+        if let Some(change) = state.track_change.update(
+            &state.config.hooks.track_change,
+            &state.config.metadata.rules,
+            &state.track_info,
+            dt,
+        ) {
+            if change.next_color {
+                state.color_scheme = state.color_scheme.next();
+            }
+            if let Some(color) = change.color {
+                state.color_scheme = color;
+            }
+            if let Some(style) = change.style {
+                state.visualizer.current_style = style;
+            }
+        }
+        if let Some(style) = state.workspace_rules.update(&state.config.compositor) {
+            state.visualizer.current_style = style;
+        }
+
+        stats.update(
+            &state.config.stats,
+            state.visualizer.current_style,
+            state.color_scheme.name(),
+            state.active && state.visible && !state.privacy.hides_all() && !state.workspace_rules.hides(),
+            dt,
+        );
+
+        // Decide what to show: live frames, or the idle mode once the audio
+        // has been quiet past the hold time
+        let idle_secs = if state.active {
+            state.idle_since = None;
+            None
+        } else {
+            Some(state.idle_since.get_or_insert_with(Instant::now).elapsed().as_secs_f32())
+        };
+        state.idle = IdleFrame::new(state.config.visualizer.idle_mode, state.config.visualizer.idle_fade_secs, idle_secs);
+        // Static idle frames are drawn once, and again only when they change
+        let idle_content = match state.idle {
+            IdleFrame::Blank => Some(String::new()),
+            IdleFrame::Clock => Some(idle::clock_text()),
+            _ => None,
+        };
+        let osd_shown = state.config.widgets.osd.enabled
+            && state.osd.current(state.config.widgets.osd.duration_secs).is_some();
+        // A paused visualizer keeps its last frame on screen
+        let paused = state.workspace_rules.pauses();
+        let rules_hidden = state.workspace_rules.hides();
+        let redraw = (state.idle.animated() && !paused)
+            || rules_hidden != rules_hid
+            || osd_shown
+            || state.osd_drawn
+            || (idle_content.is_some() && idle_content != state.idle_drawn);
+        state.idle_drawn = idle_content;
+        state.osd_drawn = osd_shown;
+        rules_hid = rules_hidden;
+
         // Render all surfaces from the main loop
-        if state.active {
+        if redraw {
             let surface_keys: Vec<_> = state.surfaces.keys().cloned().collect();
             for key in surface_keys {
                 if let Some(surface) = state.surfaces.get(&key) {
                     if surface.configured {
-                        let wl_surface = surface.layer_surface.wl_surface().clone();
+                        let wl_surface = surface.role.wl_surface().clone();
                         state.draw_surface(&wl_surface);
                     }
                 }
             }
         }
 
+        // Watchdog: a surface that keeps failing gets fresh Wayland objects
+        if state.recreate_failing_surfaces(&qh) {
+            let _ = event_queue.roundtrip(&mut state);
+        }
+
         // Process IPC commands (non-blocking)
         let mut pending = PendingChanges::default();
-        while let Ok(cmd) = state.ipc_rx.try_recv() {
+        let mut osd_before = None;
+        let mut pointer_commands = std::mem::take(&mut state.pointer_commands).into_iter();
+        while let Some(cmd) = pointer_commands.next().or_else(|| state.ipc_rx.try_recv().ok()) {
+            if state.config.widgets.osd.enabled && osd_before.is_none() {
+                osd_before = Some(state.osd_snapshot());
+            }
             // Intercept audio commands before generic handler
             match cmd {
                 IpcCommand::ListSources { reply } => {
-                    let response = match audio::list_sources() {
-                        Ok(sources) => {
-                            let list: Vec<String> = sources
-                                .iter()
-                                .map(|(name, s)| format!("{} ({})", name, s))
-                                .collect();
-                            format!("ok: {}", list.join(", "))
-                        }
-                        Err(e) => format!("err: {}", e),
-                    };
-                    let _ = reply.send(response);
+                    let _ = reply.send(crate::ipc::list_sources_reply());
                 }
                 IpcCommand::SetSource { name, reply } => {
-                    let result = if name == "default" {
-                        audio::create_audio_pipeline(
-                            config.visualizer.bars,
-                            config.audio.smoothing,
-                            config.audio.sensitivity,
-                            config.audio.device.clone(),
-                        )
-                    } else {
-                        audio::create_audio_pipeline_with_source(
-                            config.visualizer.bars,
-                            config.audio.smoothing,
-                            config.audio.sensitivity,
-                            name.clone(),
-                        )
+                    let source = if name == "default" { None } else { Some(name.clone()) };
+                    let response = match state.switch_default_source(source) {
+                        Ok(()) => IpcResponse::ok(name),
+                        Err(e) => IpcResponse::err(e),
                     };
-                    match result {
-                        Ok((capture, rx)) => {
-                            state.audio_pipelines.remove(&None);
-                            state.audio_pipelines.insert(None, AudioPipeline {
-                                _capture: capture,
-                                rx,
-                            });
-                            let _ = reply.send(format!("ok: {}", name));
-                        }
-                        Err(e) => {
-                            let _ = reply.send(format!("err: {}", e));
+                    let _ = reply.send(response);
+                }
+                IpcCommand::SourceNext { reply } => {
+                    let _ = reply.send(state.cycle_source(true));
+                }
+                IpcCommand::SourcePrev { reply } => {
+                    let _ = reply.send(state.cycle_source(false));
+                }
+                IpcCommand::Output { output, command, reply } => {
+                    let _ = reply.send(state.output_command(&output, command, &mut pending));
+                }
+                IpcCommand::Screenshot { output, path, reply } => {
+                    match state.screenshot(output.as_deref()) {
+                        Ok(canvas) => crate::ipc::send_screenshot(canvas, path, reply),
+                        Err(response) => {
+                            let _ = reply.send(response);
                         }
                     }
                 }
+                IpcCommand::OverlayOn { reply } | IpcCommand::OverlayOff { reply } | IpcCommand::OverlayToggle { reply }
+                    if state.layer_shell.is_none() =>
+                {
+                    let _ = reply.send(IpcResponse::err(if state.window_mode {
+                        "the overlay needs wallpaper mode"
+                    } else {
+                        "the overlay needs wlr-layer-shell, which this compositor lacks"
+                    }));
+                }
+                IpcCommand::DragPosition { reply } | IpcCommand::DragMove { reply, .. } if state.config.wallpaper.overlay.enabled => {
+                    let _ = reply.send(IpcResponse::err("the overlay stays at its configured position; turn it off first"));
+                }
+                IpcCommand::DragPosition { reply } => {
+                    let _ = reply.send(state.drag_position());
+                }
+                IpcCommand::DragMove { x, y, relative, reply } => {
+                    let _ = reply.send(state.move_surfaces(x, y, relative));
+                }
                 IpcCommand::ResizeRelative { delta, is_percent, reply } => {
                     if state.config.wallpaper.anchor == WallpaperAnchor::Fullscreen {
-                        let _ = reply.send("err: cannot resize in fullscreen anchor mode".to_string());
+                        let _ = reply.send(IpcResponse::err("cannot resize in fullscreen anchor mode"));
                     } else if let Some(first_surface) = state.surfaces.values().next() {
                         let cur_w = first_surface.width as i32;
                         let cur_h = first_surface.height as i32;
@@ -1232,9 +2376,9 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
                         state.config.wallpaper.height = Some(new_h.to_string());
                         pending.surface_update = true;
                         pending.save_config = true;
-                        let _ = reply.send(format!("ok: {}x{}", new_w, new_h));
+                        let _ = reply.send(IpcResponse::ok(format!("{}x{}", new_w, new_h)));
                     } else {
-                        let _ = reply.send("err: no surfaces configured".to_string());
+                        let _ = reply.send(IpcResponse::err("no surfaces configured"));
                     }
                 }
                 IpcCommand::Resize { width, height, reply } => {
@@ -1254,14 +2398,16 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
                         }
                         pending.surface_update = true;
                         pending.save_config = true;
-                        let _ = reply.send(format!("ok: {}x{}", width, height));
+                        let _ = reply.send(IpcResponse::ok(format!("{}x{}", width, height)));
                     } else {
-                        let _ = reply.send("err: no surfaces configured".to_string());
+                        let _ = reply.send(IpcResponse::err("no surfaces configured"));
                     }
                 }
                 cmd => {
                     let mut opacity = state.config.visualizer.opacity;
                     let monitors = state.list_monitors();
+                    let background_image = state.config.wallpaper.background_image.clone();
+                    let font = (state.config.text.font_file.clone(), state.config.text.font_family.clone());
                     crate::ipc::process_ipc_command(
                         cmd,
                         &mut state.visualizer,
@@ -1270,55 +2416,131 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
                         &mut opacity,
                         &mut state.config,
                         &monitors,
+                        &mut state.privacy,
                         &mut pending,
+                        state.audio_pipelines.get(&None).map(|pipeline| &pipeline.capture),
+                        Some(!state.active),
                     );
                     state.config.visualizer.opacity = opacity;
+                    if state.config.wallpaper.background_image != background_image {
+                        state.background = load_background(&state.config);
+                    }
+                    if (&state.config.text.font_file, &state.config.text.font_family) != (&font.0, &font.1) {
+                        state.font = load_font(&state.config);
+                    }
                 }
             }
         }
+        if let Some(before) = osd_before {
+            if let Some(message) = widgets::describe_change(&before, &state.osd_snapshot()) {
+                state.osd.show(message);
+            }
+        }
 
         // Handle pending layer change (requires surface recreation)
         if pending.layer_change {
-            info!("Layer changed to {}, recreating surfaces", state.config.wallpaper.layer.name());
+            let wallpaper = &state.config.wallpaper;
+            match (wallpaper.overlay.enabled, &wallpaper.overlay.output) {
+                (true, Some(output)) => info!("Overlay on, {} moves to the overlay layer; recreating surfaces", output),
+                (true, None) => info!("Overlay on, layer changed to overlay; recreating surfaces"),
+                (false, _) => info!("Layer changed to {}, recreating surfaces", wallpaper.layer.name()),
+            }
             // Destroy all existing surfaces
             state.surfaces.clear();
             // Recreate surfaces with the new layer
             state.create_surfaces_for_all_outputs(&qh);
             // Roundtrip to get configure events for new surfaces
             let _ = event_queue.roundtrip(&mut state);
+        } else if !pending.layer_outputs.is_empty() {
+            // Only the outputs given their own layer are recreated
+            let outputs: Vec<wl_output::WlOutput> = state.output_state.outputs().collect();
+            for output in outputs {
+                let name = state.output_state.info(&output).and_then(|i| i.name);
+                if name.as_ref().is_some_and(|name| pending.layer_outputs.contains(name)) {
+                    info!("Layer of {:?} changed, recreating its surface", name);
+                    state.surfaces.remove(&output.id());
+                    state.create_surface_for_output(&qh, &output);
+                }
+            }
+            let _ = event_queue.roundtrip(&mut state);
+        }
+
+        // Recreate audio pipelines when audio settings or bar count changed
+        if pending.audio_restart {
+            if let Err(e) = state.switch_default_source(state.current_source.clone()) {
+                tracing::warn!("Failed to restart default audio pipeline: {}", e);
+            }
+            let sources: Vec<Option<String>> = state.audio_pipelines.keys().flatten().cloned().map(Some).collect();
+            for source in sources {
+                let device = source.clone();
+                match audio::create_audio_pipeline(
+                    state.config.visualizer.bars,
+                    &state.config.audio,
+                    device,
+                ) {
+                    Ok((capture, rx)) => {
+                        state.audio_pipelines.insert(source, AudioPipeline {
+                            capture,
+                            rx,
+                        });
+                    }
+                    Err(e) => tracing::warn!("Failed to restart audio pipeline for {:?}: {}", source, e),
+                }
+            }
+            for surface in state.surfaces.values_mut() {
+                surface.spectrogram_history.clear();
+                surface.amplitude_history.clear();
+                surface.particles.clear();
+                surface.sparks.clear();
+            }
         }
 
         // Handle pending surface property updates (anchor/margin/size — dynamic)
         if pending.surface_update && !pending.layer_change {
-            let anchor = state.config.wallpaper.anchor.to_layer_shell_anchor();
-            let (mt, mr, mb, ml) = state.config.wallpaper.effective_margins();
+            // ObjectId hashes by protocol id; the liveness flag the system
+            // backend (`gpu` feature) keeps in it doesn't take part
+            #[allow(clippy::mutable_key_type)]
+            let layouts: HashMap<_, _> = state.surfaces.iter()
+                .map(|(id, surface)| (id.clone(), state.surface_layout(&surface.output_name)))
+                .collect();
+
+            for (id, surface) in state.surfaces.iter_mut() {
+                let Some(layer) = surface.role.layer().cloned() else {
+                    continue;
+                };
+                // Per-monitor anchor/size/margin overrides win over the global change
+                let layout = &layouts[id];
+                let anchor = layout.anchor.to_layer_shell_anchor();
+                let (mt, mr, mb, ml) = layout.effective_margins();
 
-            for surface in state.surfaces.values_mut() {
                 // Sync per-surface margins from global config
                 surface.margin_top = mt;
                 surface.margin_left = ml;
-                surface.layer_surface.set_anchor(anchor);
-                surface.layer_surface.set_margin(mt, mr, mb, ml);
+                layer.set_anchor(anchor);
+                layer.set_margin(mt, mr, mb, ml);
 
                 // Update size — use same fallback logic as create_surface_for_output
                 let needs_width = !anchor.contains(Anchor::LEFT | Anchor::RIGHT);
                 let needs_height = !anchor.contains(Anchor::TOP | Anchor::BOTTOM);
                 if needs_width || needs_height {
-                    let configured = state.config.wallpaper.get_size(surface.screen_width, surface.screen_height);
+                    let configured = layout.get_size(surface.screen_width, surface.screen_height);
                     let (w, h) = configured.unwrap_or_else(|| {
                         // Preserve current size if available, otherwise half screen
                         surface.explicit_size.unwrap_or((surface.screen_width / 2, surface.screen_height / 2))
                     });
-                    surface.layer_surface.set_size(w, h);
+                    layer.set_size(w, h);
+                    layer.set_exclusive_zone(layout.exclusive_zone((w, h)));
                     surface.explicit_size = Some((w, h));
                 } else {
                     // Fullscreen — let compositor decide
-                    surface.layer_surface.set_size(0, 0);
+                    layer.set_size(0, 0);
+                    layer.set_exclusive_zone(-1);
                     surface.explicit_size = None;
                 }
 
-                surface.layer_surface.commit();
+                layer.commit();
             }
+            state.apply_input_regions();
         }
 
         // Apply accumulated drag delta
@@ -1333,6 +2555,7 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
         // Save margins after drag release (position only, not draggable flag)
         if state.drag.save_pending {
             state.drag.save_pending = false;
+            state.snap_dragged_surface();
             // Sync dragged surface's margins to global config before saving
             if let Some(ref surface_id) = state.drag.surface_id {
                 if let Some(surface) = state.surfaces.get(surface_id) {
@@ -1350,12 +2573,13 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
             } else {
                 KeyboardInteractivity::None
             };
-            for surface in state.surfaces.values() {
-                surface.layer_surface.set_keyboard_interactivity(interactivity);
-                surface.layer_surface.commit();
+            for layer in state.surfaces.values().filter_map(|s| s.role.layer()) {
+                layer.set_keyboard_interactivity(interactivity);
+                layer.commit();
             }
+            state.apply_input_regions();
             // Convert to top-left anchor for reliable margin-based positioning
-            if state.config.wallpaper.draggable {
+            if state.config.wallpaper.draggable && !state.config.wallpaper.overlay.enabled {
                 state.convert_to_topleft_anchor();
             }
         }
@@ -1367,40 +2591,57 @@ pub async fn run(config: Config, ipc_rx: mpsc::Receiver<IpcCommand>) -> Result<(
 
         // Auto-rotate color schemes if enabled
         if config.display.rotate_styles && style_timer.elapsed() >= rotation_interval {
-            color_scheme_idx = (color_scheme_idx + 1) % color_schemes.len();
-            state.color_scheme = color_schemes[color_scheme_idx];
-            info!("Rotated to color scheme: {:?}", state.color_scheme);
+            color_scheme_idx = rotator.next(color_scheme_idx, color_schemes.len());
+            state.color_scheme = color_schemes[color_scheme_idx].clone();
+            info!("Rotated to color scheme: {}", state.color_scheme.name());
             style_timer = Instant::now();
         }
 
-        // Flush outgoing requests
-        if event_queue.flush().is_err() {
-            // Connection lost
-            break;
-        }
-
-        // Read and dispatch incoming events (non-blocking)
-        if let Some(guard) = event_queue.prepare_read() {
-            // Non-blocking read
-            let _ = guard.read();
+        // Flush outgoing requests, then read and dispatch incoming events (non-blocking)
+        let flushed = match event_queue.flush() {
+            // The socket buffer is full; the rest goes out next iteration
+            Err(WaylandError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(()),
+            result => result,
+        };
+        let connection = flushed.map_err(anyhow::Error::from).and_then(|()| {
+            if let Some(guard) = event_queue.prepare_read() {
+                // Non-blocking read
+                let _ = guard.read();
+            }
+            event_queue.dispatch_pending(&mut state).context("Wayland dispatch failed")
+        });
+        if let Err(e) = connection {
+            tracing::warn!("Lost the Wayland connection ({:#}); reconnecting", e);
+            match reconnect(&mut state)? {
+                Some(new_queue) => {
+                    event_queue = new_queue;
+                    qh = event_queue.handle();
+                    // Don't advance animations by the time spent disconnected
+                    state.last_frame = Instant::now();
+                    continue;
+                }
+                None => break,
+            }
         }
-        event_queue
-            .dispatch_pending(&mut state)
-            .context("Wayland dispatch failed")?;
 
         // Sleep — use short interval when active (audio playing) for responsive
         // state updates, longer when idle to minimize CPU usage
         let elapsed = frame_start.elapsed();
-        let poll_interval = if state.active {
-            Duration::from_millis(4)
+        let poll_interval = Duration::from_millis(if (state.idle.animated() && !state.workspace_rules.pauses()) || state.osd_drawn {
+            state.config.performance.active_poll_ms
         } else {
-            Duration::from_millis(50)
-        };
+            state.config.performance.idle_poll_ms
+        });
         if elapsed < poll_interval {
             std::thread::sleep(poll_interval - elapsed);
         }
     }
 
+    // Unmap the wallpaper before exiting rather than leaving it to the
+    // compositor to notice the dead connection
+    state.surfaces.clear();
+    let _ = event_queue.flush();
+
     info!("Wayland wallpaper mode stopped");
     Ok(())
 }
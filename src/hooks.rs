@@ -0,0 +1,185 @@
+//! User command hooks.
+//!
+//! Runs shell commands from the `[hooks]` config section when the track,
+//! style, or color scheme changes, or when a beat is detected. Event details
+//! are passed to the command through `CAVIBE_*` environment variables.
+//! `[hooks.track_change]` reactions that need no command (flash, title
+//! fade-in, color and style switches) are driven by `TrackChangeEffects`,
+//! which also applies `[[metadata.rules]]`.
+
+use std::process::Stdio;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::audio::BeatDetector;
+use crate::color::ColorScheme;
+use crate::config::{HooksConfig, MetadataRule, TrackChangeConfig};
+use crate::metadata::TrackInfo;
+use crate::renderer::styles;
+
+/// Tracks state between frames and fires hooks on changes.
+#[derive(Default)]
+pub struct HookRunner {
+    last_track: Option<(Option<String>, Option<String>)>,
+    last_style: Option<String>,
+    last_color: Option<String>,
+    beats: BeatDetector,
+}
+
+impl HookRunner {
+    /// Check for events this frame and spawn any configured hooks.
+    pub fn update(&mut self, hooks: &HooksConfig, track: &TrackInfo, style: &str, color: &str, intensity: f32) {
+        let env = |event: &str| -> Vec<(&'static str, String)> {
+            vec![
+                ("CAVIBE_EVENT", event.to_string()),
+                ("CAVIBE_TITLE", track.title.clone().unwrap_or_default()),
+                ("CAVIBE_ARTIST", track.artist.clone().unwrap_or_default()),
+                ("CAVIBE_STYLE", style.to_string()),
+                ("CAVIBE_COLOR", color.to_string()),
+                ("CAVIBE_INTENSITY", format!("{:.3}", intensity)),
+            ]
+        };
+
+        // Track change (ignore the empty "no player" state)
+        let track_key = (track.title.clone(), track.artist.clone());
+        if self.last_track.as_ref() != Some(&track_key) {
+            let has_track = track_key.0.is_some() || track_key.1.is_some();
+            self.last_track = Some(track_key);
+            if has_track {
+                if let Some(cmd) = &hooks.on_track_change {
+                    spawn_hook(cmd, env("track_change"));
+                }
+            }
+        }
+
+        // Style change (the initial style is recorded, not reported)
+        match &self.last_style {
+            Some(last) if last != style => {
+                if let Some(cmd) = &hooks.on_style_change {
+                    spawn_hook(cmd, env("style_change"));
+                }
+                self.last_style = Some(style.to_string());
+            }
+            None => self.last_style = Some(style.to_string()),
+            _ => {}
+        }
+
+        // Color scheme change
+        match &self.last_color {
+            Some(last) if last != color => {
+                if let Some(cmd) = &hooks.on_color_change {
+                    spawn_hook(cmd, env("color_change"));
+                }
+                self.last_color = Some(color.to_string());
+            }
+            None => self.last_color = Some(color.to_string()),
+            _ => {}
+        }
+
+        // Beat: intensity spikes well above its recent average, rate-limited
+        let min_interval = Duration::from_millis(hooks.beat_min_interval_ms);
+        if self.beats.update(intensity, hooks.beat_sensitivity, min_interval) {
+            if let Some(cmd) = &hooks.on_beat {
+                spawn_hook(cmd, env("beat"));
+            }
+        }
+    }
+}
+
+/// Spawn a hook command through `sh -c` without waiting for it.
+fn spawn_hook(cmd: &str, env: Vec<(&'static str, String)>) {
+    debug!("Running hook: {}", cmd);
+    let result = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Err(e) = result {
+        warn!("Failed to run hook '{}': {}", cmd, e);
+    }
+}
+
+/// Switches the render loop makes for a new track
+pub struct TrackChange {
+    pub next_color: bool,
+    /// Color scheme of the first matching metadata rule (after `next_color`)
+    pub color: Option<ColorScheme>,
+    /// Style of the first matching metadata rule
+    pub style: Option<usize>,
+}
+
+/// Notices new tracks for `[hooks.track_change]` and times the flash and
+/// title fade-in that follow.
+#[derive(Default)]
+pub struct TrackChangeEffects {
+    /// `TrackInfo::changes` when last seen
+    last_changes: u64,
+    /// Seconds since the last new track (None before one follows the baseline)
+    since: Option<f32>,
+}
+
+impl TrackChangeEffects {
+    /// Advance by `dt` seconds. Returns the switches to make when a new track
+    /// started since the last call. The first track seen is only the
+    /// baseline: it gets its metadata rule but no flash, fade-in or `next_color`.
+    pub fn update(
+        &mut self,
+        config: &TrackChangeConfig,
+        rules: &[MetadataRule],
+        track: &TrackInfo,
+        dt: f32,
+    ) -> Option<TrackChange> {
+        if let Some(since) = &mut self.since {
+            *since += dt;
+        }
+        if track.changes == self.last_changes {
+            return None;
+        }
+        let first = self.last_changes == 0;
+        self.last_changes = track.changes;
+        if !first {
+            self.since = Some(0.0);
+        }
+
+        let rule = rules.iter().find(|rule| rule_matches(rule, track));
+        let style = rule.and_then(|rule| rule.style.as_deref()).and_then(|style| {
+            styles::resolve_style(style)
+                .map_err(|e| warn!("Ignoring metadata rule style: {}", e))
+                .ok()
+        });
+        Some(TrackChange {
+            next_color: config.next_color && !first,
+            color: rule.and_then(|rule| rule.color_scheme.clone()),
+            style,
+        })
+    }
+
+    /// Strength of the white flash this frame (0.0 = none, 1.0 = white)
+    pub fn flash(&self, config: &TrackChangeConfig) -> f32 {
+        match self.since {
+            Some(since) if config.flash > 0.0 => (1.0 - since / config.flash).max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Opacity of the track text this frame (1.0 = fully shown)
+    pub fn title_opacity(&self, config: &TrackChangeConfig) -> f32 {
+        match self.since {
+            Some(since) if config.title_fade_in > 0.0 => (since / config.title_fade_in).min(1.0),
+            _ => 1.0,
+        }
+    }
+}
+
+/// Whether every field a rule gives occurs in the track's metadata (ignoring case)
+fn rule_matches(rule: &MetadataRule, track: &TrackInfo) -> bool {
+    let field = |wanted: &Option<String>, actual: &Option<String>| match (wanted, actual) {
+        (None, _) => true,
+        (Some(wanted), Some(actual)) => actual.to_lowercase().contains(&wanted.to_lowercase()),
+        (Some(_), None) => false,
+    };
+    field(&rule.genre, &track.genre) && field(&rule.artist, &track.artist) && field(&rule.album, &track.album)
+}
@@ -1,13 +1,23 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{UnixListener, UnixStream};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
 use tokio::sync::{mpsc, oneshot};
-use tracing::{debug, info};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
 
+use crate::audio::AudioCapture;
 use crate::color::ColorScheme;
-use crate::config::{Config, FontStyle, TextAnimation, TextPosition, WallpaperAnchor, WallpaperLayer, WallpaperSize};
-use crate::renderer::styles::STYLE_NAMES;
+use crate::privacy::PrivacyState;
+use crate::config::{
+    AudioConfig, ColorAdjustConfig, Config, CycleConfig, CycleOrder, Effect, IpcConfig, FontStyle, Sensitivity, StyleLayerDef, TextAnimation, TextPosition, WallpaperAnchor, WallpaperLayer,
+    WallpaperSize,
+};
+use crate::renderer::{custom, styles, Canvas};
 use crate::visualizer::VisualizerState;
 
 /// Pending changes that require action in the render loop
@@ -19,41 +29,249 @@ pub struct PendingChanges {
     pub surface_update: bool,
     /// Drag mode changed — update keyboard interactivity
     pub drag_changed: bool,
+    /// Outputs whose own layer changed — recreate only their surfaces
+    pub layer_outputs: Vec<String>,
     /// State changed — save to config file
     pub save_config: bool,
+    /// Audio settings or bar count changed — recreate audio pipelines
+    pub audio_restart: bool,
+}
+
+/// Reply to an IPC command, rendered as `ok: ...`/`err: ...` text or as JSON.
+///
+/// Every handler replies with one of these so `cavibe ctl --json` gets
+/// structured data (objects for status, arrays for lists) for any command.
+#[derive(Debug)]
+pub enum IpcResponse {
+    Ok {
+        /// Plain-text form shown after `ok: `
+        text: String,
+        /// Structured form sent in JSON mode
+        data: Value,
+    },
+    Err(String),
+}
+
+impl IpcResponse {
+    /// Success whose JSON data is the same text.
+    pub fn ok(text: impl std::fmt::Display) -> Self {
+        let text = text.to_string();
+        Self::Ok { data: Value::String(text.clone()), text }
+    }
+
+    /// Success with separate text and structured data.
+    pub fn ok_with(text: impl std::fmt::Display, data: Value) -> Self {
+        Self::Ok { text: text.to_string(), data }
+    }
+
+    /// Success listing items: comma-separated text, JSON array.
+    pub fn list<S: AsRef<str>>(items: &[S]) -> Self {
+        let items: Vec<&str> = items.iter().map(|s| s.as_ref()).collect();
+        Self::ok_with(items.join(","), json!(items))
+    }
+
+    pub fn err(message: impl std::fmt::Display) -> Self {
+        Self::Err(message.to_string())
+    }
+
+    /// Serialize for the wire: legacy text, or a `{"ok": .., "data"|"error": ..}` object.
+    pub fn render(&self, json: bool) -> String {
+        match (self, json) {
+            (Self::Ok { text, .. }, false) => format!("ok: {}", text),
+            (Self::Err(message), false) => format!("err: {}", message),
+            (Self::Ok { data, .. }, true) => json!({ "ok": true, "data": data }).to_string(),
+            (Self::Err(message), true) => json!({ "ok": false, "error": message }).to_string(),
+        }
+    }
 }
 
 /// Commands sent from IPC server to render loop
 pub enum IpcCommand {
-    StyleNext { reply: oneshot::Sender<String> },
-    StylePrev { reply: oneshot::Sender<String> },
-    ColorNext { reply: oneshot::Sender<String> },
-    ColorPrev { reply: oneshot::Sender<String> },
-    Toggle { reply: oneshot::Sender<String> },
-    SetOpacity { value: f32, reply: oneshot::Sender<String> },
-    Reload { reply: oneshot::Sender<String> },
-    Status { reply: oneshot::Sender<String> },
-    ListStyles { reply: oneshot::Sender<String> },
-    ListColors { reply: oneshot::Sender<String> },
-    ListMonitors { reply: oneshot::Sender<String> },
-    Ping { reply: oneshot::Sender<String> },
-    TextPosition { value: TextPosition, reply: oneshot::Sender<String> },
-    TextFont { value: FontStyle, reply: oneshot::Sender<String> },
-    TextAnimation { value: TextAnimation, reply: oneshot::Sender<String> },
-    TextToggle { reply: oneshot::Sender<String> },
-    ListSources { reply: oneshot::Sender<String> },
-    SetSource { name: String, reply: oneshot::Sender<String> },
-    LayerNext { reply: oneshot::Sender<String> },
-    LayerPrev { reply: oneshot::Sender<String> },
-    LayerSet { name: String, reply: oneshot::Sender<String> },
-    ListLayers { reply: oneshot::Sender<String> },
-    AnchorSet { anchor: WallpaperAnchor, reply: oneshot::Sender<String> },
-    MarginSet { top: i32, right: i32, bottom: i32, left: i32, reply: oneshot::Sender<String> },
-    Resize { width: String, height: String, reply: oneshot::Sender<String> },
-    ResizeRelative { delta: i32, is_percent: bool, reply: oneshot::Sender<String> },
-    DragToggle { reply: oneshot::Sender<String> },
-    DragOn { reply: oneshot::Sender<String> },
-    DragOff { reply: oneshot::Sender<String> },
+    StyleNext { reply: oneshot::Sender<IpcResponse> },
+    StylePrev { reply: oneshot::Sender<IpcResponse> },
+    StyleSet { name: String, reply: oneshot::Sender<IpcResponse> },
+    ColorNext { reply: oneshot::Sender<IpcResponse> },
+    ColorPrev { reply: oneshot::Sender<IpcResponse> },
+    ColorSet { name: String, reply: oneshot::Sender<IpcResponse> },
+    ColorAdjust { property: String, value: NumericArg, reply: oneshot::Sender<IpcResponse> },
+    Toggle { reply: oneshot::Sender<IpcResponse> },
+    /// `Some` forces privacy on/off, `None` follows screen-share detection
+    PrivacySet { value: Option<bool>, reply: oneshot::Sender<IpcResponse> },
+    PrivacyToggle { reply: oneshot::Sender<IpcResponse> },
+    SetOpacity { value: NumericArg, reply: oneshot::Sender<IpcResponse> },
+    /// `None` switches to automatic gain control
+    AudioSensitivity { value: Option<NumericArg>, reply: oneshot::Sender<IpcResponse> },
+    Reload { reply: oneshot::Sender<IpcResponse> },
+    Status { reply: oneshot::Sender<IpcResponse> },
+    ListStyles { reply: oneshot::Sender<IpcResponse> },
+    ListColors { reply: oneshot::Sender<IpcResponse> },
+    ListMonitors { reply: oneshot::Sender<IpcResponse> },
+    Ping { reply: oneshot::Sender<IpcResponse> },
+    /// Shut down cleanly (`cavibe stop`)
+    Quit { reply: oneshot::Sender<IpcResponse> },
+    TextPosition { value: TextPosition, reply: oneshot::Sender<IpcResponse> },
+    TextFont { value: FontStyle, reply: oneshot::Sender<IpcResponse> },
+    TextAnimation { value: TextAnimation, reply: oneshot::Sender<IpcResponse> },
+    TextToggle { reply: oneshot::Sender<IpcResponse> },
+    EffectToggle { effect: Effect, reply: oneshot::Sender<IpcResponse> },
+    EffectClear { reply: oneshot::Sender<IpcResponse> },
+    ListEffects { reply: oneshot::Sender<IpcResponse> },
+    ListSources { reply: oneshot::Sender<IpcResponse> },
+    ListApps { reply: oneshot::Sender<IpcResponse> },
+    PlayerList { reply: oneshot::Sender<IpcResponse> },
+    /// Follow the matching MPRIS player ("auto" = the active one)
+    PlayerSet { name: String, reply: oneshot::Sender<IpcResponse> },
+    SetSource { name: String, reply: oneshot::Sender<IpcResponse> },
+    SourceNext { reply: oneshot::Sender<IpcResponse> },
+    SourcePrev { reply: oneshot::Sender<IpcResponse> },
+    LayerNext { reply: oneshot::Sender<IpcResponse> },
+    LayerPrev { reply: oneshot::Sender<IpcResponse> },
+    LayerSet { name: String, reply: oneshot::Sender<IpcResponse> },
+    LayerSetOutput { output: String, layer: WallpaperLayer, reply: oneshot::Sender<IpcResponse> },
+    ListLayers { reply: oneshot::Sender<IpcResponse> },
+    AnchorSet { anchor: WallpaperAnchor, reply: oneshot::Sender<IpcResponse> },
+    MarginSet { top: i32, right: i32, bottom: i32, left: i32, reply: oneshot::Sender<IpcResponse> },
+    Resize { width: String, height: String, reply: oneshot::Sender<IpcResponse> },
+    ResizeRelative { delta: i32, is_percent: bool, reply: oneshot::Sender<IpcResponse> },
+    DragToggle { reply: oneshot::Sender<IpcResponse> },
+    DragOn { reply: oneshot::Sender<IpcResponse> },
+    DragOff { reply: oneshot::Sender<IpcResponse> },
+    DragPosition { reply: oneshot::Sender<IpcResponse> },
+    OverlayToggle { reply: oneshot::Sender<IpcResponse> },
+    OverlayOn { reply: oneshot::Sender<IpcResponse> },
+    OverlayOff { reply: oneshot::Sender<IpcResponse> },
+    DragMove { x: i32, y: i32, relative: bool, reply: oneshot::Sender<IpcResponse> },
+    ConfigGet { key: String, reply: oneshot::Sender<IpcResponse> },
+    ConfigSet { key: String, value: String, reply: oneshot::Sender<IpcResponse> },
+    /// Next or previous `[cycle]` preset
+    Cycle { forward: bool, reply: oneshot::Sender<IpcResponse> },
+    ThemeNext { reply: oneshot::Sender<IpcResponse> },
+    ThemePrev { reply: oneshot::Sender<IpcResponse> },
+    ThemeSet { name: String, reply: oneshot::Sender<IpcResponse> },
+    ListThemes { reply: oneshot::Sender<IpcResponse> },
+    LayerStyleList { style: String, reply: oneshot::Sender<IpcResponse> },
+    /// `source` names the built-in style drawn by a `style` layer
+    LayerStyleAdd { style: String, kind: String, source: Option<String>, reply: oneshot::Sender<IpcResponse> },
+    LayerStyleRemove { style: String, index: usize, reply: oneshot::Sender<IpcResponse> },
+    LayerStyleSet { style: String, index: usize, key: String, value: String, reply: oneshot::Sender<IpcResponse> },
+    /// Save the last frame of `output` (the first output when None) as a PNG
+    Screenshot { output: Option<String>, path: Option<PathBuf>, reply: oneshot::Sender<IpcResponse> },
+    /// A command for one output only (`@DP-1 style next`)
+    Output { output: String, command: OutputCommand, reply: oneshot::Sender<IpcResponse> },
+}
+
+impl IpcCommand {
+    /// Whether a remote (`[ipc] listen`) client may send this command: looks
+    /// and playback only. Anything that changes the config, touches files,
+    /// moves the wallpaper or stops cavibe stays on the local socket, since
+    /// e.g. `set hooks.on_beat ...` would run shell commands.
+    fn remote_allowed(&self) -> bool {
+        matches!(
+            self,
+            Self::StyleNext { .. }
+                | Self::StylePrev { .. }
+                | Self::StyleSet { .. }
+                | Self::ColorNext { .. }
+                | Self::ColorPrev { .. }
+                | Self::ColorSet { .. }
+                | Self::ColorAdjust { .. }
+                | Self::Toggle { .. }
+                | Self::PrivacySet { .. }
+                | Self::PrivacyToggle { .. }
+                | Self::SetOpacity { .. }
+                | Self::AudioSensitivity { .. }
+                | Self::Status { .. }
+                | Self::ListStyles { .. }
+                | Self::ListColors { .. }
+                | Self::ListMonitors { .. }
+                | Self::Ping { .. }
+                | Self::TextPosition { .. }
+                | Self::TextFont { .. }
+                | Self::TextAnimation { .. }
+                | Self::TextToggle { .. }
+                | Self::EffectToggle { .. }
+                | Self::EffectClear { .. }
+                | Self::ListEffects { .. }
+                | Self::PlayerList { .. }
+                | Self::PlayerSet { .. }
+                | Self::Cycle { .. }
+                | Self::ThemeNext { .. }
+                | Self::ThemePrev { .. }
+                | Self::ThemeSet { .. }
+                | Self::ListThemes { .. }
+                | Self::Output { .. }
+        )
+    }
+}
+
+/// Which style or color scheme to switch to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    Next,
+    Prev,
+    /// By name, resolved forgivingly (see `crate::names`)
+    Named(String),
+}
+
+/// Commands that can target a single output in independent multi-monitor
+/// mode, changing that output's `[[wallpaper.monitors]]` overrides
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputCommand {
+    Style(Selection),
+    Color(Selection),
+    Opacity(NumericArg),
+    Toggle,
+}
+
+impl OutputCommand {
+    fn parse(parts: &[&str]) -> Result<Self> {
+        let selection = |args: &[&str]| match args {
+            ["next"] => Some(Selection::Next),
+            ["prev"] => Some(Selection::Prev),
+            ["set", name @ ..] if !name.is_empty() => Some(Selection::Named(name.join(" "))),
+            _ => None,
+        };
+        let command = match parts {
+            ["style", args @ ..] => selection(args).map(Self::Style),
+            ["color", args @ ..] => selection(args).map(Self::Color),
+            ["opacity", val] => Some(Self::Opacity(NumericArg::parse(val).context("Invalid opacity value")?)),
+            ["toggle"] => Some(Self::Toggle),
+            _ => None,
+        };
+        command.ok_or_else(|| {
+            anyhow::anyhow!("Not a per-output command: {} (style, color, opacity and toggle can target an output)", parts.join(" "))
+        })
+    }
+}
+
+/// A numeric command argument: an absolute value, or a step from the current
+/// value when prefixed with `+` or `-` (e.g. `+0.1`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericArg {
+    Absolute(f32),
+    Relative(f32),
+}
+
+impl NumericArg {
+    fn parse(value: &str) -> Result<Self> {
+        let parsed: f32 = value.parse()?;
+        if !parsed.is_finite() {
+            anyhow::bail!("'{}' is not a finite number", value);
+        }
+        Ok(if value.starts_with(['+', '-']) {
+            Self::Relative(parsed)
+        } else {
+            Self::Absolute(parsed)
+        })
+    }
+
+    /// The new value given the `current` one (callers clamp to the valid range)
+    pub fn resolve(self, current: f32) -> f32 {
+        match self {
+            Self::Absolute(value) => value,
+            Self::Relative(step) => current + step,
+        }
+    }
 }
 
 /// Get the socket path for IPC
@@ -66,20 +284,52 @@ pub fn socket_path() -> PathBuf {
 }
 
 /// Parse a protocol line into an IpcCommand
-fn parse_command(line: &str, reply: oneshot::Sender<String>) -> Result<IpcCommand> {
+fn parse_command(line: &str, reply: oneshot::Sender<IpcResponse>) -> Result<IpcCommand> {
     let parts: Vec<&str> = line.split_whitespace().collect();
+    let screenshot_path = |path: &[&str]| (!path.is_empty()).then(|| PathBuf::from(path.join(" ")));
     match parts.as_slice() {
+        [target, "screenshot", path @ ..] if target.len() > 1 && target.starts_with('@') => Ok(IpcCommand::Screenshot {
+            output: Some(target[1..].to_string()),
+            path: screenshot_path(path),
+            reply,
+        }),
+        [target, command @ ..] if target.starts_with('@') => {
+            let output = &target[1..];
+            if output.is_empty() {
+                return Err(anyhow::anyhow!("Missing output name after '@'"));
+            }
+            Ok(IpcCommand::Output { output: output.to_string(), command: OutputCommand::parse(command)?, reply })
+        }
         ["style", "next"] => Ok(IpcCommand::StyleNext { reply }),
         ["style", "prev"] => Ok(IpcCommand::StylePrev { reply }),
+        ["style", "set", name @ ..] if !name.is_empty() => Ok(IpcCommand::StyleSet { name: name.join(" "), reply }),
         ["color", "next"] => Ok(IpcCommand::ColorNext { reply }),
         ["color", "prev"] => Ok(IpcCommand::ColorPrev { reply }),
+        ["color", "set", name @ ..] if !name.is_empty() => Ok(IpcCommand::ColorSet { name: name.join(" "), reply }),
+        ["color", "adjust", property, val] => {
+            if !ColorAdjustConfig::PROPERTIES.contains(property) {
+                return Err(anyhow::anyhow!(
+                    "Unknown color adjustment: {} ({})",
+                    property,
+                    ColorAdjustConfig::PROPERTIES.join(", ")
+                ));
+            }
+            let value = NumericArg::parse(val).context("Invalid adjustment value")?;
+            Ok(IpcCommand::ColorAdjust { property: property.to_string(), value, reply })
+        }
         ["toggle"] => Ok(IpcCommand::Toggle { reply }),
+        ["privacy", "on"] => Ok(IpcCommand::PrivacySet { value: Some(true), reply }),
+        ["privacy", "off"] => Ok(IpcCommand::PrivacySet { value: Some(false), reply }),
+        ["privacy", "auto"] => Ok(IpcCommand::PrivacySet { value: None, reply }),
+        ["privacy", "toggle"] => Ok(IpcCommand::PrivacyToggle { reply }),
         ["opacity", val] => {
-            let v: f32 = val.parse().context("Invalid opacity value")?;
-            Ok(IpcCommand::SetOpacity {
-                value: v.clamp(0.0, 1.0),
-                reply,
-            })
+            let value = NumericArg::parse(val).context("Invalid opacity value")?;
+            Ok(IpcCommand::SetOpacity { value, reply })
+        }
+        ["audio", "sensitivity", "auto"] => Ok(IpcCommand::AudioSensitivity { value: None, reply }),
+        ["audio", "sensitivity", val] => {
+            let value = NumericArg::parse(val).context("Invalid sensitivity value")?;
+            Ok(IpcCommand::AudioSensitivity { value: Some(value), reply })
         }
         ["reload"] => Ok(IpcCommand::Reload { reply }),
         ["status"] => Ok(IpcCommand::Status { reply }),
@@ -87,6 +337,8 @@ fn parse_command(line: &str, reply: oneshot::Sender<String>) -> Result<IpcComman
         ["list", "colors"] => Ok(IpcCommand::ListColors { reply }),
         ["list", "monitors"] => Ok(IpcCommand::ListMonitors { reply }),
         ["ping"] => Ok(IpcCommand::Ping { reply }),
+        ["screenshot", path @ ..] => Ok(IpcCommand::Screenshot { output: None, path: screenshot_path(path), reply }),
+        ["quit"] => Ok(IpcCommand::Quit { reply }),
         ["text", "position", val] => {
             let pos = val.parse::<TextPosition>()
                 .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -114,10 +366,29 @@ fn parse_command(line: &str, reply: oneshot::Sender<String>) -> Result<IpcComman
             Ok(IpcCommand::TextAnimation { value: anim, reply })
         }
         ["text", "toggle"] => Ok(IpcCommand::TextToggle { reply }),
+        ["effect", "toggle", name] => match Effect::from_name(name) {
+            Some(effect) => Ok(IpcCommand::EffectToggle { effect, reply }),
+            None => {
+                let names: Vec<_> = Effect::ALL.iter().map(|e| e.name()).collect();
+                Err(anyhow::anyhow!("Unknown effect: {} ({})", name, names.join(", ")))
+            }
+        },
+        ["effect", "clear"] => Ok(IpcCommand::EffectClear { reply }),
+        ["list", "effects"] => Ok(IpcCommand::ListEffects { reply }),
         ["list", "sources"] => Ok(IpcCommand::ListSources { reply }),
+        ["list", "sources", "--apps"] | ["list", "apps"] => Ok(IpcCommand::ListApps { reply }),
+        ["player", "list"] | ["list", "players"] => Ok(IpcCommand::PlayerList { reply }),
+        ["player", "set", name @ ..] if !name.is_empty() => Ok(IpcCommand::PlayerSet { name: name.join(" "), reply }),
         ["set", "source", name] => Ok(IpcCommand::SetSource { name: name.to_string(), reply }),
+        ["source", "next"] => Ok(IpcCommand::SourceNext { reply }),
+        ["source", "prev"] => Ok(IpcCommand::SourcePrev { reply }),
         ["layer", "next"] => Ok(IpcCommand::LayerNext { reply }),
         ["layer", "prev"] => Ok(IpcCommand::LayerPrev { reply }),
+        ["layer", "set", "--output", output, name] | ["layer", "--output", output, name] => match WallpaperLayer::from_name(name) {
+            Some(layer) => Ok(IpcCommand::LayerSetOutput { output: output.to_string(), layer, reply }),
+            None => Err(anyhow::anyhow!("Unknown layer: {} ({})", name, WallpaperLayer::all_names().join(", "))),
+        },
+        ["layer", "set", name] => Ok(IpcCommand::LayerSet { name: name.to_string(), reply }),
         ["layer", name] => {
             if WallpaperLayer::from_name(name).is_some() {
                 Ok(IpcCommand::LayerSet { name: name.to_string(), reply })
@@ -126,6 +397,12 @@ fn parse_command(line: &str, reply: oneshot::Sender<String>) -> Result<IpcComman
             }
         }
         ["list", "layers"] => Ok(IpcCommand::ListLayers { reply }),
+        ["list", "themes"] => Ok(IpcCommand::ListThemes { reply }),
+        ["cycle"] | ["cycle", "next"] => Ok(IpcCommand::Cycle { forward: true, reply }),
+        ["cycle", "prev"] => Ok(IpcCommand::Cycle { forward: false, reply }),
+        ["theme", "next"] => Ok(IpcCommand::ThemeNext { reply }),
+        ["theme", "prev"] => Ok(IpcCommand::ThemePrev { reply }),
+        ["theme", name @ ..] if !name.is_empty() => Ok(IpcCommand::ThemeSet { name: name.join(" "), reply }),
         ["anchor", pos] => {
             let anchor: WallpaperAnchor = pos.parse()
                 .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -168,6 +445,40 @@ fn parse_command(line: &str, reply: oneshot::Sender<String>) -> Result<IpcComman
         ["drag", "toggle"] => Ok(IpcCommand::DragToggle { reply }),
         ["drag", "on"] => Ok(IpcCommand::DragOn { reply }),
         ["drag", "off"] => Ok(IpcCommand::DragOff { reply }),
+        ["drag", "position"] => Ok(IpcCommand::DragPosition { reply }),
+        ["drag", action @ ("position" | "move"), x, y] => {
+            let x: i32 = x.parse().context("Invalid x offset")?;
+            let y: i32 = y.parse().context("Invalid y offset")?;
+            Ok(IpcCommand::DragMove { x, y, relative: *action == "move", reply })
+        }
+        ["overlay", "toggle"] => Ok(IpcCommand::OverlayToggle { reply }),
+        ["overlay", "on"] => Ok(IpcCommand::OverlayOn { reply }),
+        ["overlay", "off"] => Ok(IpcCommand::OverlayOff { reply }),
+        ["get", key] => Ok(IpcCommand::ConfigGet { key: key.to_string(), reply }),
+        ["set", key, value @ ..] if !value.is_empty() => Ok(IpcCommand::ConfigSet {
+            key: key.to_string(),
+            value: value.join(" "),
+            reply,
+        }),
+        ["layer-style", "list", style] => Ok(IpcCommand::LayerStyleList { style: style.to_string(), reply }),
+        ["layer-style", "add", style, kind, source @ ..] => Ok(IpcCommand::LayerStyleAdd {
+            style: style.to_string(),
+            kind: kind.to_string(),
+            source: (!source.is_empty()).then(|| source.join(" ")),
+            reply,
+        }),
+        ["layer-style", "remove", style, index] => Ok(IpcCommand::LayerStyleRemove {
+            style: style.to_string(),
+            index: index.parse().context("Invalid layer index")?,
+            reply,
+        }),
+        ["layer-style", "set", style, index, key, value @ ..] if !value.is_empty() => Ok(IpcCommand::LayerStyleSet {
+            style: style.to_string(),
+            index: index.parse().context("Invalid layer index")?,
+            key: key.to_string(),
+            value: value.join(" "),
+            reply,
+        }),
         _ => Err(anyhow::anyhow!("Unknown command: {}", line)),
     }
 }
@@ -182,52 +493,124 @@ pub fn process_ipc_command(
     opacity: &mut f32,
     config: &mut Config,
     monitors: &[(String, bool)],
+    privacy: &mut PrivacyState,
     pending: &mut PendingChanges,
+    audio: Option<&AudioCapture>,
+    idle: Option<bool>,
 ) {
     match cmd {
         IpcCommand::StyleNext { reply } => {
             visualizer.next_style();
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", visualizer.current_style_name()));
+            let _ = reply.send(IpcResponse::ok(visualizer.current_style_name()));
         }
         IpcCommand::StylePrev { reply } => {
             visualizer.prev_style();
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", visualizer.current_style_name()));
+            let _ = reply.send(IpcResponse::ok(visualizer.current_style_name()));
+        }
+        IpcCommand::StyleSet { name, reply } => {
+            let _ = reply.send(match styles::resolve_style(&name) {
+                Ok(idx) => {
+                    visualizer.current_style = idx;
+                    pending.save_config = true;
+                    IpcResponse::ok(visualizer.current_style_name())
+                }
+                Err(e) => IpcResponse::err(e),
+            });
         }
         IpcCommand::ColorNext { reply } => {
             *color_scheme = color_scheme.next();
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", color_scheme.name()));
+            let _ = reply.send(IpcResponse::ok(color_scheme.name()));
         }
         IpcCommand::ColorPrev { reply } => {
             *color_scheme = color_scheme.prev();
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", color_scheme.name()));
+            let _ = reply.send(IpcResponse::ok(color_scheme.name()));
+        }
+        IpcCommand::ColorSet { name, reply } => {
+            let _ = reply.send(match ColorScheme::resolve(&name) {
+                Ok(scheme) => {
+                    *color_scheme = scheme;
+                    pending.save_config = true;
+                    IpcResponse::ok(color_scheme.name())
+                }
+                Err(e) => IpcResponse::err(e),
+            });
+        }
+        IpcCommand::ColorAdjust { property, value, reply } => {
+            let current = config.color.get(&property).unwrap_or_default();
+            let _ = reply.send(match config.color.set(&property, value.resolve(current)) {
+                Ok(stored) => {
+                    crate::color::set_adjustment(&config.color);
+                    pending.save_config = true;
+                    IpcResponse::ok_with(format!("{} = {}", property, stored), json!(stored))
+                }
+                Err(e) => IpcResponse::err(e),
+            });
         }
         IpcCommand::Toggle { reply } => {
             *visible = !*visible;
             let state = if *visible { "visible" } else { "hidden" };
-            let _ = reply.send(format!("ok: {}", state));
+            let _ = reply.send(IpcResponse::ok(state));
+        }
+        IpcCommand::PrivacySet { value, reply } => {
+            privacy.set_override(value);
+            let state = match value {
+                Some(_) if privacy.active() => "on",
+                Some(_) => "off",
+                None if privacy.active() => "auto (on: screen shared)",
+                None => "auto (off)",
+            };
+            let _ = reply.send(IpcResponse::ok_with(state, json!(privacy.active())));
+        }
+        IpcCommand::PrivacyToggle { reply } => {
+            privacy.set_override(Some(!privacy.active()));
+            let state = if privacy.active() { "on" } else { "off" };
+            let _ = reply.send(IpcResponse::ok_with(state, json!(privacy.active())));
         }
         IpcCommand::SetOpacity { value, reply } => {
-            *opacity = value;
+            *opacity = value.resolve(*opacity).clamp(0.0, 1.0);
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", value));
+            let _ = reply.send(IpcResponse::ok_with(*opacity, json!(*opacity)));
+        }
+        IpcCommand::AudioSensitivity { value, reply } => {
+            let (min, max) = AudioConfig::SENSITIVITY_RANGE;
+            let sensitivity = match value {
+                // Steps from "auto" start at the neutral 1.0
+                Some(value) => Sensitivity::Fixed(value.resolve(config.audio.sensitivity.scale()).clamp(min, max)),
+                None => Sensitivity::Auto,
+            };
+            config.audio.sensitivity = sensitivity;
+            pending.audio_restart = true;
+            let _ = reply.send(IpcResponse::ok_with(format!("sensitivity = {}", sensitivity), json!(sensitivity)));
         }
         IpcCommand::Reload { reply } => {
             match Config::load_from_default_path() {
-                Ok(Some(new_config)) => {
-                    *color_scheme = new_config.visualizer.color_scheme;
-                    *opacity = new_config.visualizer.opacity;
-                    *config = new_config;
-                    let _ = reply.send("ok: reloaded".to_string());
-                }
+                Ok(Some(new_config)) => match crate::renderer::custom::register_custom_styles(&new_config.styles) {
+                    Ok(()) => {
+                        // Custom styles may have been removed
+                        if visualizer.current_style >= styles::style_count() {
+                            visualizer.current_style = 0;
+                        }
+                        *color_scheme = new_config.visualizer.color_scheme.clone();
+                        *opacity = new_config.visualizer.opacity;
+                        crate::color::set_adjustment(&new_config.color);
+                        crate::metadata::set_player(new_config.metadata.player.as_deref());
+                        privacy.configure(&new_config.privacy);
+                        *config = new_config;
+                        let _ = reply.send(IpcResponse::ok("reloaded"));
+                    }
+                    Err(e) => {
+                        let _ = reply.send(IpcResponse::err(e));
+                    }
+                },
                 Ok(None) => {
-                    let _ = reply.send("err: config file not found".to_string());
+                    let _ = reply.send(IpcResponse::err("config file not found"));
                 }
                 Err(e) => {
-                    let _ = reply.send(format!("err: {}", e));
+                    let _ = reply.send(IpcResponse::err(e));
                 }
             }
         }
@@ -237,102 +620,191 @@ pub fn process_ipc_command(
                 (Some(w), Some(h)) => format!("{}x{}", w, h),
                 _ => "auto".to_string(),
             };
-            let status = format!(
-                "ok: style={} color={} visible={} opacity={} layer={} anchor={:?} margin={},{},{},{} size={} draggable={}",
+            let mode = format!("{:?}", config.display.mode).to_lowercase();
+            let anchor = format!("{:?}", config.wallpaper.anchor).to_lowercase();
+            let audio_health = audio.map_or("none", |capture| capture.health().name());
+            let mut text = format!(
+                "mode={} style={} color={} visible={} privacy={} opacity={} layer={} anchor={} margin={},{},{},{} size={} draggable={} audio={}",
+                mode,
                 visualizer.current_style_name(),
                 color_scheme.name(),
                 visible,
+                privacy.active(),
                 opacity,
                 config.wallpaper.layer.name(),
-                config.wallpaper.anchor,
+                anchor,
                 mt, mr, mb, ml,
                 size_str,
                 config.wallpaper.draggable,
+                audio_health,
             ).to_lowercase();
-            let _ = reply.send(status);
+            if let Some(idle) = idle {
+                text.push_str(if idle { " state=idle" } else { " state=active" });
+            }
+            let data = json!({
+                "mode": mode,
+                "style": visualizer.current_style_name(),
+                "color": color_scheme.name(),
+                "visible": *visible,
+                "privacy": privacy.active(),
+                "screen_shared": privacy.screen_shared(),
+                "opacity": *opacity,
+                "layer": config.wallpaper.layer.name(),
+                "anchor": anchor,
+                "margin": [mt, mr, mb, ml],
+                "size": size_str,
+                "draggable": config.wallpaper.draggable,
+                "effects": config.renderer.effects.iter().map(|e| e.name()).collect::<Vec<_>>(),
+                "audio": audio.map(|capture| json!({
+                    "health": capture.health().name(),
+                    "source": capture.source_name(),
+                    "reconnects": capture.reconnects(),
+                })),
+                "idle": idle,
+            });
+            let _ = reply.send(IpcResponse::ok_with(text, data));
         }
         IpcCommand::ListStyles { reply } => {
-            let _ = reply.send(format!("ok: {}", STYLE_NAMES.join(",")));
+            let _ = reply.send(IpcResponse::list(&styles::style_names()));
         }
         IpcCommand::ListColors { reply } => {
-            let names: Vec<&str> = ColorScheme::all().iter().map(|c| c.name()).collect();
-            let _ = reply.send(format!("ok: {}", names.join(",")));
+            let all = ColorScheme::all();
+            let names: Vec<&str> = all.iter().map(|c| c.name()).collect();
+            let _ = reply.send(IpcResponse::list(&names));
         }
         IpcCommand::ListMonitors { reply } => {
+            let data: Vec<Value> = monitors
+                .iter()
+                .map(|(name, active)| json!({ "name": name, "active": active }))
+                .collect();
             if monitors.is_empty() {
-                let _ = reply.send("ok: (no monitors)".to_string());
+                let _ = reply.send(IpcResponse::ok_with("(no monitors)", json!(data)));
             } else {
                 let list: Vec<String> = monitors.iter().map(|(name, active)| {
                     format!("{} ({})", name, if *active { "active" } else { "inactive" })
                 }).collect();
-                let _ = reply.send(format!("ok: {}", list.join(", ")));
+                let _ = reply.send(IpcResponse::ok_with(list.join(", "), json!(data)));
             }
         }
         IpcCommand::Ping { reply } => {
-            let _ = reply.send("ok: pong".to_string());
+            let _ = reply.send(IpcResponse::ok("pong"));
+        }
+        IpcCommand::Quit { reply } => {
+            crate::daemon::request_shutdown();
+            let _ = reply.send(IpcResponse::ok("stopping"));
         }
         IpcCommand::TextPosition { value, reply } => {
             config.text.position = value;
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", value));
+            let _ = reply.send(IpcResponse::ok(value));
         }
         IpcCommand::TextFont { value, reply } => {
             config.text.font_style = value;
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {:?}", value).to_lowercase());
+            let _ = reply.send(IpcResponse::ok(format!("{:?}", value).to_lowercase()));
         }
         IpcCommand::TextAnimation { value, reply } => {
             config.text.animation_style = value;
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {:?}", value).to_lowercase());
+            let _ = reply.send(IpcResponse::ok(format!("{:?}", value).to_lowercase()));
         }
         IpcCommand::TextToggle { reply } => {
             let both_off = !config.text.show_title && !config.text.show_artist;
             if both_off {
                 config.text.show_title = true;
                 config.text.show_artist = true;
-                let _ = reply.send("ok: visible".to_string());
+                let _ = reply.send(IpcResponse::ok("visible"));
             } else {
                 config.text.show_title = false;
                 config.text.show_artist = false;
-                let _ = reply.send("ok: hidden".to_string());
+                let _ = reply.send(IpcResponse::ok("hidden"));
             }
             pending.save_config = true;
         }
+        IpcCommand::EffectToggle { effect, reply } => {
+            let effects = &mut config.renderer.effects;
+            let state = if let Some(pos) = effects.iter().position(|e| *e == effect) {
+                effects.remove(pos);
+                "off"
+            } else {
+                // Newly enabled effects run last in the chain
+                effects.push(effect);
+                "on"
+            };
+            let _ = reply.send(IpcResponse::ok(format!("{} {}", effect.name(), state)));
+            pending.save_config = true;
+        }
+        IpcCommand::EffectClear { reply } => {
+            config.renderer.effects.clear();
+            let _ = reply.send(IpcResponse::ok("effects cleared"));
+            pending.save_config = true;
+        }
+        IpcCommand::ListEffects { reply } => {
+            let names: Vec<&str> = Effect::ALL.iter().map(|e| e.name()).collect();
+            let _ = reply.send(IpcResponse::list(&names));
+        }
+        IpcCommand::ListApps { reply } => {
+            let _ = reply.send(list_apps_reply());
+        }
+        IpcCommand::PlayerList { reply } => {
+            let _ = reply.send(list_players_reply());
+        }
+        IpcCommand::PlayerSet { name, reply } => {
+            let player = (!name.eq_ignore_ascii_case("auto")).then_some(name);
+            crate::metadata::set_player(player.as_deref());
+            let reply_text = player.clone().unwrap_or_else(|| "auto".to_string());
+            config.metadata.player = player;
+            let _ = reply.send(IpcResponse::ok(reply_text));
+        }
         IpcCommand::LayerNext { reply } => {
             config.wallpaper.layer = config.wallpaper.layer.next();
             pending.layer_change = true;
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", config.wallpaper.layer.name()));
+            let _ = reply.send(IpcResponse::ok(config.wallpaper.layer.name()));
         }
         IpcCommand::LayerPrev { reply } => {
             config.wallpaper.layer = config.wallpaper.layer.prev();
             pending.layer_change = true;
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {}", config.wallpaper.layer.name()));
+            let _ = reply.send(IpcResponse::ok(config.wallpaper.layer.name()));
         }
         IpcCommand::LayerSet { name, reply } => {
             if let Some(layer) = WallpaperLayer::from_name(&name) {
                 config.wallpaper.layer = layer;
                 pending.layer_change = true;
                 pending.save_config = true;
-                let _ = reply.send(format!("ok: {}", layer.name()));
+                let _ = reply.send(IpcResponse::ok(layer.name()));
             } else {
-                let _ = reply.send(format!("err: unknown layer '{}' ({})", name, WallpaperLayer::all_names().join(", ")));
+                let _ = reply.send(IpcResponse::err(format!("unknown layer '{}' ({})", name, WallpaperLayer::all_names().join(", "))));
             }
         }
+        IpcCommand::LayerSetOutput { output, layer, reply } => {
+            config.wallpaper.set_output_layer(&output, layer);
+            pending.save_config = true;
+            let text = if monitors.is_empty() || monitors.iter().any(|(name, _)| *name == output) {
+                format!("{}: {}", output, layer.name())
+            } else {
+                format!("{}: {} (not connected)", output, layer.name())
+            };
+            pending.layer_outputs.push(output);
+            let _ = reply.send(IpcResponse::ok(text));
+        }
         IpcCommand::ListLayers { reply } => {
             let current = config.wallpaper.layer.name();
             let list: Vec<String> = WallpaperLayer::all_names().iter().map(|&n| {
                 if n == current { format!("{}*", n) } else { n.to_string() }
             }).collect();
-            let _ = reply.send(format!("ok: {}", list.join(",")));
+            let data: Vec<Value> = WallpaperLayer::all_names()
+                .iter()
+                .map(|&n| json!({ "name": n, "current": n == current }))
+                .collect();
+            let _ = reply.send(IpcResponse::ok_with(list.join(","), json!(data)));
         }
         IpcCommand::AnchorSet { anchor, reply } => {
             config.wallpaper.anchor = anchor;
             pending.surface_update = true;
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {:?}", anchor).to_lowercase());
+            let _ = reply.send(IpcResponse::ok(format!("{:?}", anchor).to_lowercase()));
         }
         IpcCommand::MarginSet { top, right, bottom, left, reply } => {
             config.wallpaper.margin_top = top;
@@ -342,76 +814,518 @@ pub fn process_ipc_command(
             config.wallpaper.margin = 0; // Clear uniform margin
             pending.surface_update = true;
             pending.save_config = true;
-            let _ = reply.send(format!("ok: {},{},{},{}", top, right, bottom, left));
+            let _ = reply.send(IpcResponse::ok_with(
+                format!("{},{},{},{}", top, right, bottom, left),
+                json!([top, right, bottom, left]),
+            ));
         }
-        IpcCommand::Resize { .. } => {
-            // Handled in wayland.rs main loop (needs surface dimensions for margin adjustment)
-            unreachable!("Resize should be intercepted in wayland.rs");
+        // Resize needs surface dimensions; wayland.rs intercepts it before reaching here
+        IpcCommand::Resize { reply, .. } => {
+            let _ = reply.send(IpcResponse::err("not supported in this mode"));
         }
         IpcCommand::DragToggle { reply } => {
             config.wallpaper.draggable = !config.wallpaper.draggable;
             pending.drag_changed = true;
             pending.save_config = true;
             let state = if config.wallpaper.draggable { "on" } else { "off" };
-            let mut msg = format!("ok: drag {}", state);
+            let mut msg = format!("drag {}", state);
             if config.wallpaper.draggable && config.wallpaper.layer == WallpaperLayer::Background {
                 msg.push_str(" (warning: background layer may not receive pointer events)");
             }
-            let _ = reply.send(msg);
+            let _ = reply.send(IpcResponse::ok(msg));
         }
         IpcCommand::DragOn { reply } => {
             config.wallpaper.draggable = true;
             pending.drag_changed = true;
             pending.save_config = true;
-            let mut msg = "ok: drag on".to_string();
+            let mut msg = "drag on".to_string();
             if config.wallpaper.layer == WallpaperLayer::Background {
                 msg.push_str(" (warning: background layer may not receive pointer events)");
             }
-            let _ = reply.send(msg);
+            let _ = reply.send(IpcResponse::ok(msg));
         }
         IpcCommand::DragOff { reply } => {
             config.wallpaper.draggable = false;
             pending.drag_changed = true;
             pending.save_config = true;
-            let _ = reply.send("ok: drag off".to_string());
+            let _ = reply.send(IpcResponse::ok("drag off"));
+        }
+        IpcCommand::OverlayToggle { reply } => {
+            let enabled = !config.wallpaper.overlay.enabled;
+            let _ = reply.send(set_overlay(config, pending, enabled));
+        }
+        IpcCommand::OverlayOn { reply } => {
+            let _ = reply.send(set_overlay(config, pending, true));
+        }
+        IpcCommand::OverlayOff { reply } => {
+            let _ = reply.send(set_overlay(config, pending, false));
+        }
+        // Offsets are per surface; wayland.rs intercepts these before reaching here
+        IpcCommand::DragPosition { reply } | IpcCommand::DragMove { reply, .. } => {
+            let _ = reply.send(IpcResponse::err("not supported in this mode"));
+        }
+        IpcCommand::ConfigGet { key, reply } => {
+            sync_runtime_to_config(visualizer, color_scheme, *opacity, config);
+            let _ = reply.send(match (config.get_key(&key), config.get_value(&key)) {
+                (Ok(text), Ok(value)) => IpcResponse::ok_with(text, json!(value)),
+                (Err(e), _) | (_, Err(e)) => IpcResponse::err(e),
+            });
+        }
+        IpcCommand::ConfigSet { key, value, reply } => {
+            sync_runtime_to_config(visualizer, color_scheme, *opacity, config);
+            let previous = config.clone();
+            let result = config.set_key(&key, &value).and_then(|stored| {
+                // Style names aren't validated by the config schema
+                if key == "visualizer.style" && !visualizer.set_style_by_name(&stored) {
+                    return Err(format!("unknown style '{}' ({})", stored, styles::style_names().join(", ")));
+                }
+                Ok(stored)
+            });
+            match result {
+                Ok(stored) => {
+                    *color_scheme = config.visualizer.color_scheme.clone();
+                    *opacity = config.visualizer.opacity;
+                    crate::color::set_adjustment(&config.color);
+                    crate::metadata::set_player(config.metadata.player.as_deref());
+                    privacy.configure(&config.privacy);
+                    if key == "visualizer.bars" || key.starts_with("audio.") {
+                        pending.audio_restart = true;
+                    }
+                    if key == "wallpaper.layer" {
+                        pending.layer_change = true;
+                    } else if key == "wallpaper.draggable" {
+                        pending.drag_changed = true;
+                    } else if key.starts_with("wallpaper.") {
+                        pending.surface_update = true;
+                    }
+                    pending.save_config = true;
+                    let value = config.get_value(&key).map_or(Value::Null, |v| json!(v));
+                    let data = json!({ "key": key, "value": value });
+                    let _ = reply.send(IpcResponse::ok_with(format!("{} = {}", key, stored), data));
+                }
+                Err(e) => {
+                    *config = previous;
+                    let _ = reply.send(IpcResponse::err(e));
+                }
+            }
+        }
+        IpcCommand::Cycle { forward, reply } => {
+            let _ = reply.send(match step_cycle(&config.cycle, visualizer, color_scheme, forward) {
+                Ok(()) => {
+                    pending.save_config = true;
+                    let style = visualizer.current_style_name();
+                    IpcResponse::ok_with(
+                        format!("{} / {}", style, color_scheme.name()),
+                        json!({ "style": style, "color": color_scheme.name() }),
+                    )
+                }
+                Err(e) => IpcResponse::err(e),
+            });
+        }
+        IpcCommand::ThemeNext { reply } => {
+            let response = match step_theme(config, 1) {
+                Ok(name) => switch_theme(&name, visualizer, color_scheme, opacity, config, privacy, pending),
+                Err(e) => IpcResponse::err(e),
+            };
+            let _ = reply.send(response);
+        }
+        IpcCommand::ThemePrev { reply } => {
+            let response = match step_theme(config, -1) {
+                Ok(name) => switch_theme(&name, visualizer, color_scheme, opacity, config, privacy, pending),
+                Err(e) => IpcResponse::err(e),
+            };
+            let _ = reply.send(response);
+        }
+        IpcCommand::ThemeSet { name, reply } => {
+            let _ = reply.send(switch_theme(&name, visualizer, color_scheme, opacity, config, privacy, pending));
+        }
+        IpcCommand::ListThemes { reply } => {
+            let _ = reply.send(match config.all_themes() {
+                Ok(themes) => {
+                    let is_current = |name: &str| config.theme.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(name));
+                    let list: Vec<String> = themes.iter().map(|t| {
+                        if is_current(&t.name) { format!("{}*", t.name) } else { t.name.clone() }
+                    }).collect();
+                    let data: Vec<Value> = themes
+                        .iter()
+                        .map(|t| json!({ "name": t.name, "current": is_current(&t.name) }))
+                        .collect();
+                    IpcResponse::ok_with(list.join(","), json!(data))
+                }
+                Err(e) => IpcResponse::err(e),
+            });
+        }
+        // The frame lives in the render loops, which intercept this where they can
+        IpcCommand::Screenshot { reply, .. } => {
+            let _ = reply.send(IpcResponse::err("not supported in this mode"));
+        }
+        IpcCommand::Output { reply, .. } => {
+            // Wallpaper mode handles these itself; there are no outputs here
+            let _ = reply.send(IpcResponse::err("per-output commands need wallpaper mode"));
+        }
+        IpcCommand::LayerStyleList { style, reply } => {
+            let found = custom::custom_styles().into_iter().find(|s| s.name.eq_ignore_ascii_case(&style));
+            let _ = reply.send(match found {
+                Some(found) => {
+                    let layers = &found.def().layers;
+                    let lines: Vec<String> = layers
+                        .iter()
+                        .enumerate()
+                        .map(|(i, layer)| describe_layer(i, layer))
+                        .collect();
+                    IpcResponse::ok_with(lines.join("\n"), json!(layers))
+                }
+                None => IpcResponse::err(format!("no custom style named '{}'", style)),
+            });
+        }
+        IpcCommand::LayerStyleAdd { style, kind, source, reply } => {
+            let result = custom::edit_style(&style, |def| {
+                def.layers.push(StyleLayerDef::from_kind(&kind, source.as_deref())?);
+                Ok(describe_layer(def.layers.len() - 1, &def.layers[def.layers.len() - 1]))
+            });
+            let _ = reply.send(result.map_or_else(IpcResponse::err, IpcResponse::ok));
+        }
+        IpcCommand::LayerStyleRemove { style, index, reply } => {
+            let result = custom::edit_style(&style, |def| {
+                if index >= def.layers.len() {
+                    return Err(format!("style '{}' has {} layers", def.name, def.layers.len()));
+                }
+                def.layers.remove(index);
+                Ok(format!("removed layer {}", index))
+            });
+            let _ = reply.send(result.map_or_else(IpcResponse::err, IpcResponse::ok));
+        }
+        IpcCommand::LayerStyleSet { style, index, key, value, reply } => {
+            let result = custom::edit_style(&style, |def| {
+                let count = def.layers.len();
+                let layer = def
+                    .layers
+                    .get_mut(index)
+                    .ok_or_else(|| format!("style '{}' has {} layers", def.name, count))?;
+                layer.set_key(&key, &value)?;
+                Ok(describe_layer(index, layer))
+            });
+            let _ = reply.send(result.map_or_else(IpcResponse::err, IpcResponse::ok));
         }
         // ResizeRelative is intercepted in wayland.rs before reaching here
         IpcCommand::ResizeRelative { reply, .. } => {
-            let _ = reply.send("err: not supported in this mode".to_string());
+            let _ = reply.send(IpcResponse::err("not supported in this mode"));
         }
         // Audio commands are intercepted in render loops before reaching here
         IpcCommand::ListSources { reply } => {
-            let _ = reply.send("err: not supported in this mode".to_string());
+            let _ = reply.send(IpcResponse::err("not supported in this mode"));
         }
-        IpcCommand::SetSource { reply, .. } => {
-            let _ = reply.send("err: not supported in this mode".to_string());
+        IpcCommand::SetSource { reply, .. }
+        | IpcCommand::SourceNext { reply }
+        | IpcCommand::SourcePrev { reply } => {
+            let _ = reply.send(IpcResponse::err("not supported in this mode"));
         }
     }
 }
 
-/// Handle a single client connection
-async fn handle_client(stream: UnixStream, cmd_tx: mpsc::Sender<IpcCommand>) -> Result<()> {
-    let (reader, mut writer) = stream.into_split();
-    let mut buf_reader = BufReader::new(reader);
-    let mut line = String::new();
-    buf_reader.read_line(&mut line).await?;
-    let line = line.trim();
+/// Save `canvas`, a copy of the last frame drawn, to `path` or a timestamped
+/// file in the pictures directory, for `screenshot`. The PNG is encoded and
+/// written on a blocking thread so the render loop keeps going.
+pub fn send_screenshot(canvas: Canvas, path: Option<PathBuf>, reply: oneshot::Sender<IpcResponse>) {
+    if canvas.width == 0 || canvas.height == 0 {
+        let _ = reply.send(IpcResponse::err("nothing has been drawn yet"));
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        let _ = reply.send(screenshot_reply(&canvas, path.as_deref()));
+    });
+}
 
-    if line.is_empty() {
+fn screenshot_reply(canvas: &Canvas, path: Option<&Path>) -> IpcResponse {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let Some(dir) = dirs::picture_dir().or_else(dirs::home_dir) else {
+                return IpcResponse::err("no pictures directory; give a path");
+            };
+            dir.join(chrono::Local::now().format("cavibe-%Y%m%d-%H%M%S.png").to_string())
+        }
+    };
+    match canvas.save_png(&path) {
+        Ok(()) => IpcResponse::ok_with(
+            path.display(),
+            json!({ "path": path, "width": canvas.width, "height": canvas.height }),
+        ),
+        Err(e) => IpcResponse::err(format!("{:#}", e)),
+    }
+}
+
+/// One-line summary of a custom style layer, e.g. `1: style (Radial) blend=additive order=2 opacity=0.8`
+fn describe_layer(index: usize, layer: &StyleLayerDef) -> String {
+    let kind = serde_json::to_value(layer.kind).ok();
+    let blend = serde_json::to_value(layer.blend).ok();
+    let mut text = format!("{}: {}", index, kind.as_ref().and_then(Value::as_str).unwrap_or("?"));
+    if let Some(style) = &layer.style {
+        text.push_str(&format!(" ({})", style));
+    }
+    text.push_str(&format!(
+        " blend={} order={} opacity={}",
+        blend.as_ref().and_then(Value::as_str).unwrap_or("?"),
+        layer.order,
+        layer.opacity
+    ));
+    text
+}
+
+/// Build the reply for `list sources`
+pub fn list_sources_reply() -> IpcResponse {
+    match crate::audio::list_sources() {
+        Ok(sources) => {
+            let list: Vec<String> = sources
+                .iter()
+                .map(|(name, s)| format!("{} ({})", name, s))
+                .collect();
+            let data: Vec<Value> = sources
+                .iter()
+                .map(|(name, s)| json!({ "name": name, "state": s.to_lowercase() }))
+                .collect();
+            IpcResponse::ok_with(list.join(", "), json!(data))
+        }
+        Err(e) => IpcResponse::err(e),
+    }
+}
+
+/// Build the reply for `list sources --apps`: application streams, usable as `app:<name>`
+fn list_apps_reply() -> IpcResponse {
+    match crate::audio::list_apps() {
+        Ok(apps) => {
+            let list: Vec<String> = apps
+                .iter()
+                .map(|app| {
+                    let state = if app.corked { "paused" } else { "playing" };
+                    format!("{}{} ({})", crate::audio::APP_PREFIX, app.name, state)
+                })
+                .collect();
+            let data: Vec<Value> = apps
+                .iter()
+                .map(|app| {
+                    json!({
+                        "name": app.name,
+                        "binary": app.binary,
+                        "source": format!("{}{}", crate::audio::APP_PREFIX, app.name),
+                        "index": app.index,
+                        "corked": app.corked,
+                    })
+                })
+                .collect();
+            IpcResponse::ok_with(list.join(", "), json!(data))
+        }
+        Err(e) => IpcResponse::err(e),
+    }
+}
+
+fn list_players_reply() -> IpcResponse {
+    match crate::metadata::list_players() {
+        Ok(players) => {
+            let list: Vec<String> = players
+                .iter()
+                .map(|player| {
+                    let marker = if player.followed { ", followed" } else { "" };
+                    format!("{} [{}] ({}{})", player.identity, player.name, player.status, marker)
+                })
+                .collect();
+            let data: Vec<Value> = players
+                .iter()
+                .map(|player| {
+                    json!({
+                        "name": player.name,
+                        "identity": player.identity,
+                        "status": player.status,
+                        "followed": player.followed,
+                    })
+                })
+                .collect();
+            if list.is_empty() {
+                IpcResponse::ok_with("(no players)", json!(data))
+            } else {
+                IpcResponse::ok_with(list.join(", "), json!(data))
+            }
+        }
+        Err(e) => IpcResponse::err(e),
+    }
+}
+
+/// Copy runtime-owned state into the config so key lookups see current values
+fn sync_runtime_to_config(
+    visualizer: &VisualizerState,
+    color_scheme: &ColorScheme,
+    opacity: f32,
+    config: &mut Config,
+) {
+    config.visualizer.style = Some(visualizer.current_style_name().to_lowercase());
+    config.visualizer.color_scheme = color_scheme.clone();
+    config.visualizer.opacity = opacity;
+}
+
+/// Turn the streaming overlay on or off. The surfaces are recreated, since
+/// the layer they're on can't change in place.
+fn set_overlay(config: &mut Config, pending: &mut PendingChanges, enabled: bool) -> IpcResponse {
+    if config.wallpaper.overlay.enabled != enabled {
+        config.wallpaper.overlay.enabled = enabled;
+        pending.layer_change = true;
+        pending.save_config = true;
+    }
+    IpcResponse::ok(if enabled { "overlay on" } else { "overlay off" })
+}
+
+/// Switch to the next (or previous) `[cycle]` preset, or step the style and
+/// color scheme together when there are none.
+fn step_cycle(
+    cycle: &CycleConfig,
+    visualizer: &mut VisualizerState,
+    color_scheme: &mut ColorScheme,
+    forward: bool,
+) -> Result<(), String> {
+    if cycle.presets.is_empty() {
+        if forward {
+            visualizer.next_style();
+            *color_scheme = color_scheme.next();
+        } else {
+            visualizer.prev_style();
+            *color_scheme = color_scheme.prev();
+        }
         return Ok(());
     }
 
+    // Resolve every preset, so a misspelled name is reported rather than skipped
+    let presets = cycle
+        .presets
+        .iter()
+        .map(|preset| {
+            let style = preset.style.as_deref().map(styles::resolve_style).transpose()?;
+            let color = preset.color.as_deref().map(ColorScheme::resolve).transpose()?;
+            Ok((style, color, preset.weight))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let current = presets.iter().position(|(style, color, _)| {
+        style.is_none_or(|s| s == visualizer.current_style) && color.as_ref().is_none_or(|c| c == color_scheme)
+    });
+
+    let count = presets.len();
+    let index = match current {
+        _ if forward && cycle.order == CycleOrder::Shuffle => {
+            // Never the current preset again, so every press visibly changes something
+            let weights: Vec<u64> = (0..count)
+                .map(|i| if Some(i) == current { 0 } else { presets[i].2 as u64 })
+                .collect();
+            weighted_pick(&weights).or(current).unwrap_or(0)
+        }
+        Some(i) if forward => (i + 1) % count,
+        Some(i) => (i + count - 1) % count,
+        None if forward => 0,
+        None => count - 1,
+    };
+
+    let (style, color, _) = &presets[index];
+    if let Some(style) = style {
+        visualizer.current_style = *style;
+    }
+    if let Some(color) = color {
+        *color_scheme = color.clone();
+    }
+    Ok(())
+}
+
+/// Random index with probability proportional to its weight, or `None` when
+/// all weights are zero
+fn weighted_pick(weights: &[u64]) -> Option<usize> {
+    let total: u64 = weights.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = crate::random::next_u64() % total;
+    weights.iter().position(|&weight| {
+        if roll < weight {
+            return true;
+        }
+        roll -= weight;
+        false
+    })
+}
+
+/// Name of the theme `step` places after (or before) the current one; the
+/// first theme when none is active.
+fn step_theme(config: &Config, step: isize) -> Result<String, String> {
+    let themes = config.all_themes()?;
+    if themes.is_empty() {
+        return Err("no themes defined".to_string());
+    }
+    let current = config
+        .theme
+        .as_deref()
+        .and_then(|name| themes.iter().position(|t| t.name.eq_ignore_ascii_case(name)));
+    let index = match current {
+        Some(i) => (i as isize + step).rem_euclid(themes.len() as isize) as usize,
+        None => 0,
+    };
+    Ok(themes[index].name.clone())
+}
+
+/// Apply theme `name` to the config and bring the render state along.
+fn switch_theme(
+    name: &str,
+    visualizer: &mut VisualizerState,
+    color_scheme: &mut ColorScheme,
+    opacity: &mut f32,
+    config: &mut Config,
+    privacy: &mut PrivacyState,
+    pending: &mut PendingChanges,
+) -> IpcResponse {
+    sync_runtime_to_config(visualizer, color_scheme, *opacity, config);
+    let previous = config.clone();
+    let result = config.apply_theme(name).and_then(|applied| {
+        match config.visualizer.style.as_deref() {
+            Some(style) if !visualizer.set_style_by_name(style) => Err(format!("theme '{}': unknown style '{}'", applied, style)),
+            _ => Ok(applied),
+        }
+    });
+    match result {
+        Ok(applied) => {
+            *color_scheme = config.visualizer.color_scheme.clone();
+            *opacity = config.visualizer.opacity;
+            crate::color::set_adjustment(&config.color);
+            crate::metadata::set_player(config.metadata.player.as_deref());
+            privacy.configure(&config.privacy);
+
+            let changed = |key: &str| previous.get_value(key).ok() != config.get_value(key).ok();
+            pending.audio_restart |= changed("audio") || changed("visualizer.bars");
+            pending.layer_change |= changed("wallpaper.layer");
+            pending.drag_changed |= changed("wallpaper.draggable");
+            pending.surface_update |= changed("wallpaper");
+            pending.save_config = true;
+            IpcResponse::ok_with(applied.clone(), json!(applied))
+        }
+        Err(e) => {
+            *config = previous;
+            IpcResponse::err(e)
+        }
+    }
+}
+
+/// Run one protocol line against the render loop and return the rendered reply.
+///
+/// A line prefixed with `json ` gets its reply as a JSON object instead of text.
+/// `remote` limits it to the commands remote clients may send.
+async fn dispatch(line: &str, cmd_tx: &mpsc::Sender<IpcCommand>, remote: bool) -> Result<String> {
+    let (json, line) = match line.strip_prefix("json ") {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, line),
+    };
+
     let (reply_tx, reply_rx) = oneshot::channel();
 
     let command = match parse_command(line, reply_tx) {
         Ok(cmd) => cmd,
-        Err(e) => {
-            writer
-                .write_all(format!("err: {}\n", e).as_bytes())
-                .await?;
-            return Ok(());
-        }
+        Err(e) => return Ok(IpcResponse::err(e).render(json)),
     };
+    if remote && !command.remote_allowed() {
+        return Ok(IpcResponse::err(format!("'{}' is only available on the local socket", line)).render(json));
+    }
 
     cmd_tx
         .send(command)
@@ -420,28 +1334,143 @@ async fn handle_client(stream: UnixStream, cmd_tx: mpsc::Sender<IpcCommand>) ->
 
     let response = reply_rx
         .await
-        .unwrap_or_else(|_| "err: internal error".to_string());
+        .unwrap_or_else(|_| IpcResponse::err("internal error"));
+    Ok(response.render(json))
+}
 
-    writer
-        .write_all(format!("{}\n", response).as_bytes())
-        .await?;
+/// Handle a single client connection
+async fn handle_client(stream: UnixStream, cmd_tx: mpsc::Sender<IpcCommand>) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut buf_reader = BufReader::new(reader);
+    let mut line = String::new();
+    buf_reader.read_line(&mut line).await?;
+    let line = line.trim();
+
+    if line.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(args) = line.strip_prefix("subscribe") {
+        return match parse_subscription(args) {
+            Ok(subscription) => stream_spectrum(&mut writer, subscription).await,
+            Err(e) => {
+                writer.write_all(format!("{}\n", IpcResponse::err(e).render(false)).as_bytes()).await?;
+                Ok(())
+            }
+        };
+    }
+
+    let reply = dispatch(line, &cmd_tx, false).await?;
+    writer.write_all(format!("{}\n", reply).as_bytes()).await?;
     Ok(())
 }
 
-/// Start the IPC server, listening for commands on a Unix socket
-pub async fn start_server(cmd_tx: mpsc::Sender<IpcCommand>) -> Result<()> {
+/// Frame rate of `subscribe spectrum` without an explicit one
+const DEFAULT_SUBSCRIBE_FPS: u32 = 30;
+
+/// A `subscribe spectrum [json|binary] [FPS]` request
+struct Subscription {
+    binary: bool,
+    interval: Duration,
+}
+
+fn parse_subscription(args: &str) -> Result<Subscription, String> {
+    let mut parts = args.split_whitespace();
+    if parts.next() != Some("spectrum") {
+        return Err("usage: subscribe spectrum [json|binary] [FPS]".to_string());
+    }
+    let mut binary = false;
+    let mut fps = DEFAULT_SUBSCRIBE_FPS;
+    for part in parts {
+        match part {
+            "json" => binary = false,
+            "binary" => binary = true,
+            _ => {
+                fps = part
+                    .parse()
+                    .map_err(|_| format!("expected json, binary or a frame rate, got '{}'", part))?;
+            }
+        }
+    }
+    Ok(Subscription { binary, interval: Duration::from_secs_f32(1.0 / fps.clamp(1, 240) as f32) })
+}
+
+/// Write the displayed spectrum to `writer` at the subscribed rate until the
+/// client goes away: a JSON object per line, or binary `CAV1` frames as on
+/// the web stream (see `crate::web`).
+async fn stream_spectrum(writer: &mut tokio::net::unix::OwnedWriteHalf, subscription: Subscription) -> Result<()> {
+    let rx = crate::web::subscribe();
+    let mut ticker = tokio::time::interval(subscription.interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+        let frame = if subscription.binary {
+            crate::web::encode_frame(&rx.borrow())
+        } else {
+            let mut line = crate::web::encode_json(&rx.borrow()).into_bytes();
+            line.push(b'\n');
+            line
+        };
+        if writer.write_all(&frame).await.is_err() {
+            // Client disconnected
+            return Ok(());
+        }
+    }
+}
+
+/// Stream a `subscribe` command's output to stdout until cavibe or the
+/// reader (e.g. the other end of a pipe) goes away.
+pub async fn stream_command(line: &str) -> Result<()> {
+    let mut stream = tokio::time::timeout(Duration::from_secs(2), UnixStream::connect(socket_path()))
+        .await
+        .context("Connection timed out")?
+        .context("Could not connect to cavibe. Is it running?")?;
+    stream.write_all(format!("{}\n", line).as_bytes()).await?;
+    match tokio::io::copy(&mut stream, &mut tokio::io::stdout()).await {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.map(|_| ()).context("Failed to read the stream"),
+    }
+}
+
+/// Pause after a failed `accept()` before trying again
+pub(crate) const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Bind the IPC socket, unless another instance is already listening on it.
+///
+/// A socket file nobody answers on is left over from a crashed run and gets
+/// replaced. On success this process owns the socket and removes it on exit.
+pub async fn bind_server() -> Result<UnixListener> {
     let path = socket_path();
 
-    // Remove stale socket from previous run
-    let _ = std::fs::remove_file(&path);
+    if path.exists() {
+        let probe = tokio::time::timeout(Duration::from_millis(500), UnixStream::connect(&path)).await;
+        if matches!(probe, Ok(Ok(_))) {
+            anyhow::bail!("another cavibe instance is listening on {}", path.display());
+        }
+        // Remove stale socket from previous run
+        let _ = std::fs::remove_file(&path);
+    }
 
     let listener =
         UnixListener::bind(&path).context("Failed to bind IPC socket")?;
+    crate::crash::set_socket_owned(true);
 
     info!("IPC server listening on {}", path.display());
+    Ok(listener)
+}
 
+/// Serve commands from a socket bound by [`bind_server`]
+pub async fn start_server(listener: UnixListener, cmd_tx: mpsc::Sender<IpcCommand>) -> Result<()> {
     loop {
-        let (stream, _) = listener.accept().await?;
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                // Transient (e.g. out of file descriptors); keep serving
+                warn!("IPC accept failed: {}", e);
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
         let cmd_tx = cmd_tx.clone();
 
         tokio::spawn(async move {
@@ -452,8 +1481,150 @@ pub async fn start_server(cmd_tx: mpsc::Sender<IpcCommand>) -> Result<()> {
     }
 }
 
-/// Send a command to a running cavibe instance (client mode)
-pub async fn send_command(line: &str) -> Result<String> {
+/// Longest line accepted from a remote client
+const MAX_REMOTE_LINE: u64 = 4096;
+/// Time a remote client has to authenticate
+const REMOTE_AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Start the remote control listener (`[ipc] listen`).
+///
+/// Speaks the socket's line protocol over plain TCP, or over WebSocket text
+/// messages when the connection opens with an HTTP upgrade, on the same port.
+/// Every connection must send `auth <token>` first and may then send any
+/// number of commands, each answered with one reply. Only the commands
+/// `IpcCommand::remote_allowed` accepts are served.
+pub async fn start_remote_server(config: IpcConfig, cmd_tx: mpsc::Sender<IpcCommand>) -> Result<()> {
+    let Some(addr) = config.listen else {
+        return Ok(());
+    };
+    let token: Arc<str> = config
+        .token
+        .filter(|t| !t.trim().is_empty())
+        .context("[ipc] listen requires a token")?
+        .trim()
+        .into();
+
+    let listener = TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind remote control to {}", addr))?;
+    info!("Remote control listening on {} (TCP and WebSocket)", addr);
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("Remote control accept failed: {}", e);
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        let cmd_tx = cmd_tx.clone();
+        let token = token.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_remote_client(stream, cmd_tx, token).await {
+                debug!("Remote client {} error: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Serve one remote connection, as WebSocket if it starts with an HTTP request
+async fn handle_remote_client(stream: TcpStream, cmd_tx: mpsc::Sender<IpcCommand>, token: Arc<str>) -> Result<()> {
+    let mut head = [0u8; 4];
+    let n = tokio::time::timeout(REMOTE_AUTH_TIMEOUT, stream.peek(&mut head))
+        .await
+        .context("Client sent nothing")??;
+    let mut session = RemoteSession { token, authenticated: false, cmd_tx };
+
+    if &head[..n] == b"GET " {
+        let ws = tokio_tungstenite::accept_async(stream).await?;
+        let (mut sink, mut incoming) = ws.split();
+        while let Some(Some(message)) = session.read(incoming.next()).await {
+            let text = match message? {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let (reply, close) = session.handle(text.trim()).await?;
+            sink.send(Message::Text(reply)).await?;
+            if close {
+                break;
+            }
+        }
+    } else {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        loop {
+            let mut line = String::new();
+            let mut limited = (&mut reader).take(MAX_REMOTE_LINE);
+            match session.read(limited.read_line(&mut line)).await.transpose()? {
+                Some(0) | None => break,
+                // Hit the limit mid-line: the rest isn't a command of its own
+                Some(n) if n as u64 >= MAX_REMOTE_LINE && !line.ends_with('\n') => {
+                    let reply = IpcResponse::err(format!("line longer than {} bytes", MAX_REMOTE_LINE));
+                    writer.write_all(format!("{}\n", reply.render(false)).as_bytes()).await?;
+                    break;
+                }
+                Some(_) => {}
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (reply, close) = session.handle(line.trim()).await?;
+            writer.write_all(format!("{}\n", reply).as_bytes()).await?;
+            if close {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Authentication state of one remote connection
+struct RemoteSession {
+    token: Arc<str>,
+    authenticated: bool,
+    cmd_tx: mpsc::Sender<IpcCommand>,
+}
+
+impl RemoteSession {
+    /// Await the client's next input. Before authentication this gives up
+    /// (None) after `REMOTE_AUTH_TIMEOUT`.
+    async fn read<F: std::future::Future>(&self, read: F) -> Option<F::Output> {
+        if self.authenticated {
+            Some(read.await)
+        } else {
+            tokio::time::timeout(REMOTE_AUTH_TIMEOUT, read).await.ok()
+        }
+    }
+
+    /// Reply to one line, and whether to close the connection
+    async fn handle(&mut self, line: &str) -> Result<(String, bool)> {
+        if self.authenticated {
+            return Ok((dispatch(line, &self.cmd_tx, true).await?, false));
+        }
+        let (json, line) = match line.strip_prefix("json ") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+        match line.strip_prefix("auth ") {
+            Some(token) if token_matches(token.trim(), &self.token) => {
+                self.authenticated = true;
+                Ok((IpcResponse::ok("authenticated").render(json), false))
+            }
+            _ => Ok((IpcResponse::err("authentication required: send `auth <token>` first").render(json), true)),
+        }
+    }
+}
+
+/// Compare tokens without exiting early on the first differing byte
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Send a command to a running cavibe instance (client mode).
+/// With `json`, the reply is a JSON object rather than `ok:`/`err:` text.
+pub async fn send_command(line: &str, json: bool) -> Result<String> {
     let path = socket_path();
 
     let stream = tokio::time::timeout(
@@ -462,10 +1633,11 @@ pub async fn send_command(line: &str) -> Result<String> {
     )
     .await
     .context("Connection timed out")?
-    .context("Could not connect to cavibe. Is it running in wallpaper mode?")?;
+    .context("Could not connect to cavibe. Is it running?")?;
 
     let (mut reader, mut writer) = stream.into_split();
 
+    let line = if json { format!("json {}", line) } else { line.to_string() };
     writer.write_all(format!("{}\n", line).as_bytes()).await?;
     writer.shutdown().await?;
 
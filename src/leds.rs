@@ -0,0 +1,241 @@
+//! LED output (`[leds]` section).
+//!
+//! Lights RGB peripherals from the spectrum on screen, next to the terminal
+//! or wallpaper display: every `[[leds.devices]]` entry gets its own task
+//! that reads the frames the render loop publishes (the same feed as the web
+//! stream), maps them onto the device's LEDs and sends them in the device's
+//! protocol:
+//!
+//! - **OpenRGB**: the SDK protocol over TCP, `UPDATELEDS` for one controller
+//!   after switching it to its direct ("custom") mode
+//! - **WLED**: realtime UDP in the DNRGB format, which WLED leaves again a
+//!   couple of seconds after the packets stop
+//! - **Adalight**: the `Ada` header protocol written to a serial device
+//!
+//! A device that can't be reached is retried every few seconds without
+//! affecting the others.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+use crate::audio::AudioData;
+use crate::color::ColorScheme;
+use crate::config::{LedDevice, LedDeviceKind, LedMode, LedsConfig};
+
+/// Wait before reconnecting to a device that failed
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+const OPENRGB_DEFAULT_ADDRESS: &str = "127.0.0.1:6742";
+const WLED_DEFAULT_PORT: u16 = 21324;
+/// Seconds WLED keeps showing realtime data after the last packet
+const WLED_TIMEOUT_SECS: u8 = 2;
+/// Most LEDs per WLED DNRGB packet
+const WLED_MAX_LEDS_PER_PACKET: usize = 489;
+
+/// OpenRGB SDK packet ids
+const OPENRGB_SET_CLIENT_NAME: u32 = 50;
+const OPENRGB_UPDATE_LEDS: u32 = 1050;
+const OPENRGB_SET_CUSTOM_MODE: u32 = 1100;
+
+/// Start a task per configured device. Does nothing unless `[leds]` is enabled.
+pub fn start(config: &LedsConfig, visualizer_scheme: &ColorScheme) {
+    if !config.enabled {
+        return;
+    }
+    let interval = Duration::from_secs_f32(1.0 / config.fps.clamp(1, 120) as f32);
+    let scheme = config.color_scheme.clone().unwrap_or_else(|| visualizer_scheme.clone());
+    for device in &config.devices {
+        let output = LedOutput {
+            device: device.clone(),
+            mode: device.mode.unwrap_or(config.mode),
+            brightness: config.brightness.clamp(0.0, 1.0),
+            scheme: scheme.clone(),
+        };
+        let rx = crate::web::subscribe();
+        tokio::spawn(output.run(rx, interval));
+    }
+}
+
+/// One device and how it is lit
+struct LedOutput {
+    device: LedDevice,
+    mode: LedMode,
+    brightness: f32,
+    scheme: ColorScheme,
+}
+
+impl LedOutput {
+    /// Connect and send frames until the process exits, reconnecting after errors
+    async fn run(self, rx: watch::Receiver<Arc<AudioData>>, interval: Duration) {
+        let name = match (&self.device.address, self.device.kind) {
+            (Some(address), _) => address.clone(),
+            (None, LedDeviceKind::OpenRgb) => OPENRGB_DEFAULT_ADDRESS.to_string(),
+            (None, kind) => format!("{:?}", kind),
+        };
+        let mut warned = false;
+        loop {
+            let result = match Sink::connect(&self.device).await {
+                Ok(mut sink) => {
+                    info!("LED output: sending to {:?} device {}", self.device.kind, name);
+                    warned = false;
+                    self.stream(&mut sink, &rx, interval).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                // Report the first failure; an unplugged device would flood the log
+                if warned {
+                    debug!("LED device {}: {:#}", name, e);
+                } else {
+                    warn!("LED device {}: {:#} (retrying every {}s)", name, e, RETRY_DELAY.as_secs());
+                    warned = true;
+                }
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+
+    async fn stream(&self, sink: &mut Sink, rx: &watch::Receiver<Arc<AudioData>>, interval: Duration) -> Result<()> {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut colors = Vec::with_capacity(self.device.leds);
+        loop {
+            ticker.tick().await;
+            let data = rx.borrow().clone();
+            self.colors(&data, &mut colors);
+            sink.send(&self.device, &colors).await?;
+        }
+    }
+
+    /// Color of every LED for one frame
+    fn colors(&self, data: &AudioData, out: &mut Vec<[u8; 3]>) {
+        out.clear();
+        let leds = self.device.leds;
+        // The right half of the mirrored bars runs bass → treble
+        let bands = &data.frequencies[data.frequencies.len() / 2..];
+        if leds == 0 || bands.is_empty() {
+            out.resize(leds, [0; 3]);
+            return;
+        }
+        match self.mode {
+            LedMode::Spectrum => {
+                out.extend((0..leds).map(|led| {
+                    let position = (led as f32 + 0.5) / leds as f32;
+                    let value = bands[((position * bands.len() as f32) as usize).min(bands.len() - 1)].clamp(0.0, 1.0);
+                    scale(self.scheme.get_color(position, value), value * self.brightness)
+                }));
+            }
+            LedMode::Pulse => {
+                let loudest = bands
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(b.1))
+                    .map_or(0, |(band, _)| band);
+                let position = (loudest as f32 + 0.5) / bands.len() as f32;
+                let intensity = data.intensity.clamp(0.0, 1.0);
+                out.resize(leds, scale(self.scheme.get_color(position, intensity), intensity * self.brightness));
+            }
+        }
+        if self.device.reverse {
+            out.reverse();
+        }
+    }
+}
+
+fn scale((r, g, b): (u8, u8, u8), factor: f32) -> [u8; 3] {
+    [(r as f32 * factor) as u8, (g as f32 * factor) as u8, (b as f32 * factor) as u8]
+}
+
+/// An open connection to a device
+enum Sink {
+    OpenRgb(TcpStream),
+    Wled(UdpSocket),
+    Adalight(tokio::fs::File),
+}
+
+impl Sink {
+    async fn connect(device: &LedDevice) -> Result<Self> {
+        let address = device.address.as_deref();
+        match device.kind {
+            LedDeviceKind::OpenRgb => {
+                let address = address.unwrap_or(OPENRGB_DEFAULT_ADDRESS);
+                let mut stream = TcpStream::connect(address)
+                    .await
+                    .with_context(|| format!("Failed to connect to the OpenRGB server at {}", address))?;
+                stream.write_all(&openrgb_packet(0, OPENRGB_SET_CLIENT_NAME, b"cavibe\0")).await?;
+                // Direct color updates need the controller's custom mode
+                stream.write_all(&openrgb_packet(device.controller, OPENRGB_SET_CUSTOM_MODE, &[])).await?;
+                Ok(Sink::OpenRgb(stream))
+            }
+            LedDeviceKind::Wled => {
+                let address = address.context("a WLED device needs an address")?;
+                let address = if address.contains(':') {
+                    address.to_string()
+                } else {
+                    format!("{}:{}", address, WLED_DEFAULT_PORT)
+                };
+                let socket = UdpSocket::bind("0.0.0.0:0").await?;
+                socket.connect(&address).await.with_context(|| format!("Failed to resolve {}", address))?;
+                Ok(Sink::Wled(socket))
+            }
+            LedDeviceKind::Adalight => {
+                let path = address.context("an Adalight device needs the serial device path as its address")?;
+                let file = tokio::fs::OpenOptions::new()
+                    .write(true)
+                    .open(path)
+                    .await
+                    .with_context(|| format!("Failed to open {}", path))?;
+                Ok(Sink::Adalight(file))
+            }
+        }
+    }
+
+    async fn send(&mut self, device: &LedDevice, colors: &[[u8; 3]]) -> Result<()> {
+        match self {
+            Sink::OpenRgb(stream) => {
+                let count = colors.len().min(u16::MAX as usize);
+                let mut data = Vec::with_capacity(6 + count * 4);
+                data.extend_from_slice(&((6 + count * 4) as u32).to_le_bytes());
+                data.extend_from_slice(&(count as u16).to_le_bytes());
+                for [r, g, b] in &colors[..count] {
+                    data.extend_from_slice(&[*r, *g, *b, 0]);
+                }
+                stream.write_all(&openrgb_packet(device.controller, OPENRGB_UPDATE_LEDS, &data)).await?;
+            }
+            Sink::Wled(socket) => {
+                for (chunk_index, chunk) in colors.chunks(WLED_MAX_LEDS_PER_PACKET).enumerate() {
+                    let start = (chunk_index * WLED_MAX_LEDS_PER_PACKET) as u16;
+                    let mut packet = Vec::with_capacity(4 + chunk.len() * 3);
+                    packet.extend_from_slice(&[4, WLED_TIMEOUT_SECS]);
+                    packet.extend_from_slice(&start.to_be_bytes());
+                    packet.extend(chunk.iter().flatten());
+                    socket.send(&packet).await?;
+                }
+            }
+            Sink::Adalight(file) => {
+                let count = colors.len().clamp(1, 1 << 16) - 1;
+                let (hi, lo) = ((count >> 8) as u8, count as u8);
+                let mut frame = Vec::with_capacity(6 + colors.len() * 3);
+                frame.extend_from_slice(&[b'A', b'd', b'a', hi, lo, hi ^ lo ^ 0x55]);
+                frame.extend(colors.iter().flatten());
+                file.write_all(&frame).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// OpenRGB SDK packet: `ORGB` magic, device index, packet id and data length, then the data
+fn openrgb_packet(device: u32, id: u32, data: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(16 + data.len());
+    packet.extend_from_slice(b"ORGB");
+    packet.extend_from_slice(&device.to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    packet.extend_from_slice(data);
+    packet
+}
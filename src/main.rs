@@ -1,19 +1,37 @@
 use anyhow::Result;
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::Shell;
+use tracing_subscriber::prelude::*;
 
 mod audio;
+mod bench;
+mod check;
 mod color;
+mod compositor;
 mod config;
+mod crash;
+mod daemon;
 mod display;
+mod hooks;
 mod ipc;
+mod leds;
 mod metadata;
+mod names;
+mod privacy;
+mod random;
 mod renderer;
+mod stats;
 mod visualizer;
+mod web;
 
-use config::{Config, FontStyle, MultiMonitorMode, TextAlignment, TextAnimation, TextPosition, WallpaperAnchor, WallpaperLayer};
+use config::{AlbumArtPlacement, Config, FontStyle, MultiMonitorMode, Orientation, OverlayCorner, Sensitivity, TextAlignment, TerminalGraphics, TerminalBlocks, TextAnimation, TextCase, TextPosition, UpscaleFilter, WallpaperAnchor, WallpaperLayer};
 use display::DisplayMode;
 
+/// Counts allocations for `cavibe bench` in builds with the `bench` feature
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: bench::CountingAlloc = bench::CountingAlloc;
+
 #[derive(Parser, Debug)]
 #[command(name = "cavibe")]
 #[command(author, version, about = "Audio visualizer with animated song display")]
@@ -21,7 +39,7 @@ pub struct Args {
     #[command(subcommand)]
     pub command: Option<Command>,
 
-    /// Display mode: terminal or wallpaper
+    /// Display mode: terminal, wallpaper, or window
     #[arg(short, long)]
     pub mode: Option<DisplayMode>,
 
@@ -42,11 +60,11 @@ pub struct Args {
     #[arg(short, long, default_value = "64")]
     pub bars: usize,
 
-    /// Color scheme: spectrum, rainbow, fire, ocean, monochrome
+    /// Color scheme: spectrum, rainbow, fire, ocean, monochrome, or random
     #[arg(long, default_value = "spectrum")]
     pub colors: String,
 
-    /// Visualizer style: "classic bars", "mirrored bars", "wave", "dots", "blocks"
+    /// Visualizer style by name or unique abbreviation, e.g. "classic bars", "wave", "radial", "spec" (see `ctl list styles`), or "random"
     #[arg(long)]
     pub style: Option<String>,
 
@@ -58,6 +76,18 @@ pub struct Args {
     #[arg(long, default_value = "30")]
     pub rotate_interval: u64,
 
+    /// Show the now-playing info panel beside the visualizer (terminal mode)
+    #[arg(long)]
+    pub info_panel: bool,
+
+    /// Terminal drawing: blocks, kitty or sixel graphics, or auto-detect (terminal mode)
+    #[arg(long)]
+    pub terminal_graphics: Option<TerminalGraphics>,
+
+    /// Characters for blocks terminal graphics: halfblock, braille or block (terminal mode)
+    #[arg(long, visible_alias = "terminal-render-mode")]
+    pub terminal_blocks: Option<TerminalBlocks>,
+
     /// Width of each bar in characters
     #[arg(long)]
     pub bar_width: Option<u16>,
@@ -78,11 +108,35 @@ pub struct Args {
     #[arg(long)]
     pub opacity: Option<f32>,
 
+    /// Group bars into segments of N bars (0 = disabled)
+    #[arg(long)]
+    pub group_size: Option<usize>,
+
+    /// Extra spacing between bar groups in characters
+    #[arg(long)]
+    pub group_gap: Option<u16>,
+
+    /// Screen edge the bars grow from: bottom, top, left, right
+    #[arg(long, value_enum)]
+    pub orientation: Option<Orientation>,
+
+    /// Internal render scale (2 = supersample, 0.5 = half resolution)
+    #[arg(long, visible_alias = "render-scale")]
+    pub supersample: Option<f32>,
+
+    /// Upscaling when rendering below output resolution: nearest, bilinear, compositor
+    #[arg(long, value_enum)]
+    pub upscale: Option<UpscaleFilter>,
+
     // === Audio settings ===
     /// Audio device name (e.g., "pulse")
     #[arg(long)]
     pub audio_device: Option<String>,
 
+    /// Capture only this application's audio (name or binary, e.g. "spotify")
+    #[arg(long)]
+    pub app_source: Option<String>,
+
     /// Sample rate in Hz
     #[arg(long)]
     pub sample_rate: Option<u32>,
@@ -95,9 +149,39 @@ pub struct Args {
     #[arg(long)]
     pub smoothing: Option<f32>,
 
-    /// Audio sensitivity (0.1-10.0, default 1.0)
-    #[arg(short, long, default_value = "1.0")]
-    pub sensitivity: f32,
+    /// Audio sensitivity (0.1-10.0, or auto for automatic gain control; default 1.0)
+    #[arg(short, long)]
+    pub sensitivity: Option<Sensitivity>,
+
+    /// Rise speed (0.0-1.0, 1.0 = bars snap up instantly)
+    #[arg(long)]
+    pub rise_speed: Option<f32>,
+
+    /// Fall speed (0.0-1.0, lower = bars fall more slowly)
+    #[arg(long)]
+    pub fall_speed: Option<f32>,
+
+    /// Cava-style gravity for falling bars (0.0 = disabled)
+    #[arg(long)]
+    pub gravity: Option<f32>,
+
+    /// Adapt gain to the session's loudness (sensitivity becomes exposure compensation)
+    #[arg(long)]
+    pub auto_exposure: bool,
+
+    /// Visualize built-in synthetic music instead of captured audio
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Record the analyzed audio (bars, waveform, levels) to a file, for
+    /// reproducing what was on screen with --replay-features
+    #[arg(long, value_name = "FILE")]
+    pub record_features: Option<std::path::PathBuf>,
+
+    /// Visualize a recording made with --record-features (looped) instead of
+    /// captured audio
+    #[arg(long, value_name = "FILE")]
+    pub replay_features: Option<std::path::PathBuf>,
 
     // === Text settings ===
     /// Show track title
@@ -124,6 +208,18 @@ pub struct Args {
     #[arg(long)]
     pub font_style: Option<FontStyle>,
 
+    /// Letter case of the text: upper, lower, preserve
+    #[arg(long)]
+    pub text_case: Option<TextCase>,
+
+    /// TrueType/OpenType font file for the text (wallpaper mode)
+    #[arg(long)]
+    pub font_file: Option<String>,
+
+    /// Font family for the text, looked up with fontconfig (wallpaper mode)
+    #[arg(long)]
+    pub font_family: Option<String>,
+
     /// Text alignment: left, center, right
     #[arg(long)]
     pub text_alignment: Option<TextAlignment>,
@@ -152,6 +248,30 @@ pub struct Args {
     #[arg(long)]
     pub artist_color: Option<String>,
 
+    /// Show title and artist on separate lines with independent marquees
+    #[arg(long)]
+    pub two_line_text: bool,
+
+    /// Title marquee speed multiplier (two-line mode)
+    #[arg(long)]
+    pub title_scroll_speed: Option<f32>,
+
+    /// Artist marquee speed multiplier (two-line mode)
+    #[arg(long)]
+    pub artist_scroll_speed: Option<f32>,
+
+    /// Show album art: corner, background, beside-text
+    #[arg(long, value_enum)]
+    pub album_art: Option<AlbumArtPlacement>,
+
+    /// Show the audio source and input level: top-left, top-right, bottom-left, bottom-right
+    #[arg(long, value_enum)]
+    pub source_indicator: Option<OverlayCorner>,
+
+    /// Start the web server on ADDRESS (host:port, e.g. "127.0.0.1:9470")
+    #[arg(long, value_name = "ADDRESS")]
+    pub web: Option<String>,
+
     // === Wallpaper settings ===
     /// Wallpaper size: WIDTHxHEIGHT (pixels or %, e.g., "400x300" or "50%x50%")
     #[arg(long)]
@@ -182,9 +302,45 @@ pub struct Args {
 pub enum Command {
     /// Control a running cavibe instance
     Ctl {
+        /// Print the reply as JSON ({"ok": true, "data": ...} or {"ok": false, "error": ...})
+        #[arg(long, global = true)]
+        json: bool,
+        /// Apply to one output only, e.g. DP-1 (style, color, opacity, toggle, layer
+        /// and screenshot; style, color, opacity and toggle need independent
+        /// multi-monitor mode)
+        #[arg(long, global = true)]
+        output: Option<String>,
         #[command(subcommand)]
         action: CtlAction,
     },
+    /// Start wallpaper mode in the background (writes $XDG_RUNTIME_DIR/cavibe.pid)
+    Daemon,
+    /// Shut down the running instance cleanly
+    Stop,
+    /// Stop the running instance and start a fresh background one
+    Restart,
+    /// Show or manage local usage statistics (enable with `[stats] enabled = true`)
+    Stats {
+        #[command(subcommand)]
+        action: StatsAction,
+    },
+    /// Render synthetic audio through every style without a display and report frame times
+    Bench {
+        /// Frames to render per style
+        #[arg(long, default_value_t = 300)]
+        frames: usize,
+        /// Canvas width in pixels
+        #[arg(long, default_value_t = 1920)]
+        width: usize,
+        /// Canvas height in pixels
+        #[arg(long, default_value_t = 1080)]
+        height: usize,
+    },
+    /// Validate a config file: unknown keys, out-of-range values, style and output names
+    CheckConfig {
+        /// Config file to check (default: -c, else ~/.config/cavibe/config.toml)
+        path: Option<std::path::PathBuf>,
+    },
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -197,20 +353,45 @@ pub enum Command {
 pub enum CtlAction {
     /// Change visualizer style
     Style {
-        /// Direction: next, prev
-        direction: String,
+        /// Direction: next, prev, or "set" followed by a style name
+        action: String,
+        /// Style name for "set" (case-insensitive, unique abbreviations allowed)
+        name: Vec<String>,
     },
-    /// Change color scheme
+    /// Change color scheme, or adjust colors globally
     Color {
-        /// Direction: next, prev
-        direction: String,
+        /// Direction: next, prev, "set" followed by a scheme name, or "adjust" followed by brightness|saturation|gamma VALUE
+        action: String,
+        /// Adjustment property and value (e.g. saturation 1.2, or a step like saturation -0.1)
+        #[arg(allow_hyphen_values = true)]
+        args: Vec<String>,
     },
     /// Toggle visibility
     Toggle,
-    /// Set opacity (0.0-1.0)
+    /// Privacy mode: on, off, toggle, or auto (follow screen-share detection)
+    Privacy {
+        mode: String,
+    },
+    /// Toggle post-processing effects
+    Effect {
+        /// Action: toggle, clear
+        action: String,
+        /// Effect to toggle: glow, scanlines, chromatic-aberration, vignette
+        name: Option<String>,
+    },
+    /// Set opacity (0.0-1.0), or step it with a signed value (+0.1, -0.1)
     Opacity {
-        /// Opacity value
-        value: f32,
+        /// Opacity value, or +/- step
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    /// Adjust audio settings
+    Audio {
+        /// Setting: sensitivity
+        setting: String,
+        /// New value (0.1-10.0), +/- step (e.g. -0.2), or auto
+        #[arg(allow_hyphen_values = true)]
+        value: String,
     },
     /// Reload config file
     Reload,
@@ -218,8 +399,11 @@ pub enum CtlAction {
     Status,
     /// List available options
     List {
-        /// What to list: styles, colors, monitors
+        /// What to list: styles, colors, monitors, sources, effects, layers, themes
         what: String,
+        /// With `sources`: list application streams for `set-source app:<name>`
+        #[arg(long)]
+        apps: bool,
     },
     /// Check if daemon is running
     Ping,
@@ -234,10 +418,25 @@ pub enum CtlAction {
         /// Source name (use "default" to revert to auto-detected)
         name: String,
     },
+    /// Cycle through audio sources
+    Source {
+        /// Direction: next, prev
+        direction: String,
+    },
+    /// List MPRIS players, or choose the one to follow
+    Player {
+        /// Action: list, or "set" followed by a player name ("auto" = the active player)
+        action: String,
+        /// Player identity or bus name for "set" (* matches anything)
+        name: Vec<String>,
+    },
     /// Change layer-shell layer
     Layer {
         /// Direction or layer name: next, prev, background, bottom, top, overlay
+        /// (or `set <NAME>`)
         value: String,
+        /// Layer name after `set`
+        layer: Option<String>,
     },
     /// Set wallpaper anchor/position
     Anchor {
@@ -254,11 +453,81 @@ pub enum CtlAction {
         /// Size: WxH (e.g. 800x600, 50%x50%), +50, -50, +10%, -10%
         size: String,
     },
-    /// Toggle drag-to-move mode
+    /// Toggle drag-to-move mode, or query and set the surface offset
     Drag {
+        /// Mode: toggle, on, off, position, move
+        mode: String,
+        /// Offset for `position X Y` (absolute) or `move DX DY` (relative)
+        #[arg(allow_hyphen_values = true)]
+        offset: Vec<String>,
+    },
+    /// Toggle the always-on-top streaming overlay (`[wallpaper.overlay]`)
+    Overlay {
         /// Mode: toggle, on, off
         mode: String,
     },
+    /// Get a config value by key path
+    Get {
+        /// Dotted key path (e.g. text.animation_speed)
+        key: String,
+    },
+    /// Set a config value by key path
+    Set {
+        /// Dotted key path (e.g. visualizer.bars)
+        key: String,
+        /// New value
+        value: String,
+    },
+    /// Switch theme (bundles from `[[themes]]` or ~/.config/cavibe/themes/)
+    Theme {
+        /// Theme name, or next, prev
+        #[arg(required = true)]
+        name: Vec<String>,
+    },
+    /// Step through the `[cycle]` style + color presets (for a single hotkey)
+    Cycle {
+        /// Direction: next, prev
+        #[arg(default_value = "next")]
+        direction: String,
+    },
+    /// Stream the spectrum shown on screen to stdout until interrupted
+    Subscribe {
+        /// What to stream: spectrum
+        #[arg(default_value = "spectrum")]
+        what: String,
+        /// Binary frames (same layout as the web stream) instead of JSON lines
+        #[arg(long)]
+        binary: bool,
+        /// Frames per second (1-240)
+        #[arg(long, default_value = "30")]
+        fps: u32,
+    },
+    /// Inspect or edit the layers of a custom style (until the next reload)
+    LayerStyle {
+        /// Action: list, add, remove, set
+        action: String,
+        /// Style name, then: add KIND [BUILTIN STYLE]; remove INDEX; set INDEX KEY VALUE
+        #[arg(required = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Save the frame on screen as a PNG (with --output, that output's frame)
+    Screenshot {
+        /// PNG file to write (default: a timestamped file in ~/Pictures)
+        path: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatsAction {
+    /// Print run time and favorite styles and color schemes
+    Summary,
+    /// Write the recorded totals as JSON
+    Export {
+        /// Output file (stdout if omitted)
+        path: Option<std::path::PathBuf>,
+    },
+    /// Delete all recorded stats
+    Reset,
 }
 
 #[derive(Subcommand, Debug)]
@@ -280,16 +549,20 @@ pub enum TextAction {
 }
 
 impl CtlAction {
-    /// Convert to the wire protocol line
-    fn to_protocol_line(&self) -> String {
-        match self {
-            CtlAction::Style { direction } => format!("style {}", direction),
-            CtlAction::Color { direction } => format!("color {}", direction),
+    /// Convert to the wire protocol line, aimed at `output` if given
+    fn to_protocol_line(&self, output: Option<&str>) -> String {
+        let line = match self {
+            CtlAction::Style { action, name } => format!("style {} {}", action, name.join(" ")).trim_end().to_string(),
+            CtlAction::Color { action, args } => format!("color {} {}", action, args.join(" ")).trim_end().to_string(),
             CtlAction::Toggle => "toggle".to_string(),
+            CtlAction::Privacy { mode } => format!("privacy {}", mode),
+            CtlAction::Effect { action, name } => format!("effect {} {}", action, name.as_deref().unwrap_or("")).trim_end().to_string(),
             CtlAction::Opacity { value } => format!("opacity {}", value),
+            CtlAction::Audio { setting, value } => format!("audio {} {}", setting, value),
             CtlAction::Reload => "reload".to_string(),
             CtlAction::Status => "status".to_string(),
-            CtlAction::List { what } => format!("list {}", what),
+            CtlAction::List { what, apps: false } => format!("list {}", what),
+            CtlAction::List { what, apps: true } => format!("list {} --apps", what),
             CtlAction::Ping => "ping".to_string(),
             CtlAction::Text { action } => match action {
                 TextAction::Position { value } => format!("text position {}", value),
@@ -298,31 +571,126 @@ impl CtlAction {
                 TextAction::Toggle => "text toggle".to_string(),
             },
             CtlAction::SetSource { name } => format!("set source {}", name),
-            CtlAction::Layer { value } => format!("layer {}", value),
+            CtlAction::Source { direction } => format!("source {}", direction),
+            CtlAction::Player { action, name } => format!("player {} {}", action, name.join(" ")).trim_end().to_string(),
+            // The per-output form always names the layer after `--output`,
+            // whether it was given as `layer <NAME>` or `layer set <NAME>`
+            CtlAction::Layer { value, layer } => match (output, layer) {
+                (Some(output), Some(layer)) => format!("layer {} --output {} {}", value, output, layer),
+                (Some(output), None) => format!("layer set --output {} {}", output, value),
+                (None, Some(layer)) => format!("layer {} {}", value, layer),
+                (None, None) => format!("layer {}", value),
+            },
             CtlAction::Anchor { position } => format!("anchor {}", position),
             CtlAction::Margin { values } => format!("margin {}", values.join(" ")),
             CtlAction::Resize { size } => format!("resize {}", size),
-            CtlAction::Drag { mode } => format!("drag {}", mode),
+            CtlAction::Drag { mode, offset } if offset.is_empty() => format!("drag {}", mode),
+            CtlAction::Drag { mode, offset } => format!("drag {} {}", mode, offset.join(" ")),
+            CtlAction::Overlay { mode } => format!("overlay {}", mode),
+            CtlAction::Get { key } => format!("get {}", key),
+            CtlAction::Set { key, value } => format!("set {} {}", key, value),
+            CtlAction::Theme { name } => format!("theme {}", name.join(" ")),
+            CtlAction::Cycle { direction } => format!("cycle {}", direction),
+            CtlAction::LayerStyle { action, args } => format!("layer-style {} {}", action, args.join(" ")),
+            CtlAction::Subscribe { what, binary, fps } => {
+                format!("subscribe {} {} {}", what, if *binary { "binary" } else { "json" }, fps)
+            }
+            // The running instance has its own working directory
+            CtlAction::Screenshot { path: Some(path) } => {
+                let path = std::env::current_dir().map(|dir| dir.join(path)).unwrap_or_else(|_| path.clone());
+                format!("screenshot {}", path.display())
+            }
+            CtlAction::Screenshot { path: None } => "screenshot".to_string(),
+        };
+        match output {
+            // `layer` has its own `--output` form
+            Some(output) if !matches!(self, CtlAction::Layer { .. }) => format!("@{} {}", output, line),
+            _ => line,
         }
     }
 }
 
+/// The command line without the `subcommand` token, in wallpaper mode, for
+/// relaunching cavibe in the background
+fn daemon_args(args: &Args, subcommand: &str) -> Result<Vec<std::ffi::OsString>> {
+    if args.mode == Some(DisplayMode::Terminal) {
+        anyhow::bail!("the daemon runs in wallpaper mode; drop --mode terminal");
+    }
+    let mut argv: Vec<_> = std::env::args_os().skip(1).collect();
+    if let Some(pos) = argv.iter().rposition(|arg| arg == subcommand) {
+        argv.remove(pos);
+    }
+    if args.mode.is_none() {
+        argv.extend(["--mode".into(), "wallpaper".into()]);
+    }
+    Ok(argv)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Handle subcommands that don't need daemon startup
     match &args.command {
-        Some(Command::Ctl { action }) => {
-            let response = ipc::send_command(&action.to_protocol_line()).await?;
+        Some(Command::Ctl { action: action @ CtlAction::Subscribe { .. }, .. }) => {
+            return ipc::stream_command(&action.to_protocol_line(None)).await;
+        }
+        Some(Command::Ctl { json, output, action }) => {
+            let response = ipc::send_command(&action.to_protocol_line(output.as_deref()), *json).await?;
             println!("{}", response);
             return Ok(());
         }
+        Some(Command::Stats { action }) => {
+            match action {
+                StatsAction::Summary => println!("{}", stats::UsageStats::load()?.summary()),
+                StatsAction::Export { path } => {
+                    let json = serde_json::to_string_pretty(&stats::UsageStats::load()?)?;
+                    match path {
+                        Some(path) => {
+                            std::fs::write(path, json)?;
+                            println!("Exported usage stats to {}", path.display());
+                        }
+                        None => println!("{}", json),
+                    }
+                }
+                StatsAction::Reset => {
+                    stats::UsageStats::reset()?;
+                    println!("Usage stats reset");
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Daemon) => {
+            let pid = daemon::spawn(daemon_args(&args, "daemon")?).await?;
+            println!("cavibe started in the background (pid {})", pid);
+            return Ok(());
+        }
+        Some(Command::Stop) => {
+            daemon::stop().await?;
+            println!("cavibe stopped");
+            return Ok(());
+        }
+        Some(Command::Restart) => {
+            let argv = daemon_args(&args, "restart")?;
+            if daemon::is_running().await {
+                daemon::stop().await?;
+            }
+            let pid = daemon::spawn(argv).await?;
+            println!("cavibe restarted (pid {})", pid);
+            return Ok(());
+        }
+        Some(Command::CheckConfig { path }) => {
+            let path = match path.clone().or_else(|| args.config.clone()).or_else(Config::default_path) {
+                Some(path) => path,
+                None => anyhow::bail!("No config file given and no config directory found"),
+            };
+            return check::run(&path).await;
+        }
         Some(Command::Completions { shell }) => {
             clap_complete::generate(*shell, &mut Args::command(), "cavibe", &mut std::io::stdout());
             return Ok(());
         }
-        None => {}
+        Some(Command::Bench { .. }) | None => {}
     }
 
     // Handle --init-config flag (before logging init)
@@ -361,42 +729,120 @@ async fn main() -> Result<()> {
 
     // Merge CLI arguments (CLI takes priority over config file)
     config.merge_args(&args);
+    config.sanitize();
+    // Before any audio pipeline starts; both last until exit
+    if let Some(path) = &args.replay_features {
+        audio::recording::replay_from(path)?;
+    }
+    if let Some(path) = &args.record_features {
+        audio::recording::record_to(path)?;
+    }
+    if let Err(e) = color::ColorScheme::resolve(&args.colors) {
+        eprintln!("Warning: {}", e);
+    }
 
-    // Initialize logging - only enable info level for wallpaper mode
+    // Register custom and plugin styles before the visualizer resolves
+    // `visualizer.style`; plugins first, custom styles mustn't shadow them
+    for problem in renderer::plugin::load_plugins() {
+        eprintln!("Warning: skipping plugin {}", problem);
+    }
+    if let Err(e) = renderer::custom::register_custom_styles(&config.styles) {
+        eprintln!("Warning: {}\nCustom styles disabled.", e);
+    }
+    // Accept abbreviations like `--style spec` in every display mode
+    if let Some(style) = config.visualizer.style.as_deref() {
+        let resolved = if style.eq_ignore_ascii_case("random") {
+            Ok(random::index(renderer::styles::style_count()))
+        } else {
+            renderer::styles::resolve_style(style)
+        };
+        match resolved {
+            Ok(idx) => config.visualizer.style = Some(renderer::styles::style_name(idx)),
+            Err(e) => eprintln!("Warning: {}\nStarting with the first style.", e),
+        }
+    }
+    color::set_adjustment(&config.color);
+    metadata::set_player(config.metadata.player.as_deref());
+
+    // Benchmark needs the merged config but no logging, IPC or display
+    if let Some(Command::Bench { frames, width, height }) = args.command {
+        return bench::run(&config, frames, width, height);
+    }
+
+    // Initialize logging - only enable info level for wallpaper and window mode
     // Terminal mode uses a TUI that would be corrupted by log output
-    let log_level = if config.display.mode == DisplayMode::Wallpaper {
+    let log_level = if config.display.mode != DisplayMode::Terminal {
         "cavibe=info"
     } else {
         "cavibe=error"
     };
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(log_level.parse()?),
+    // The crash log ring always records info-level messages, even when the
+    // terminal UI suppresses console output
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer().with_filter(
+                tracing_subscriber::EnvFilter::from_default_env()
+                    .add_directive(log_level.parse()?),
+            ),
         )
+        .with(crash::LogRing.with_filter(tracing_subscriber::EnvFilter::new("cavibe=info")))
         .init();
+    crash::install();
 
-    // Run the visualizer
-    match config.display.mode {
-        DisplayMode::Terminal => {
-            display::terminal::run(config).await?;
-        }
-        DisplayMode::Wallpaper => {
-            // Create IPC channel and start server
-            let (ipc_tx, ipc_rx) = tokio::sync::mpsc::channel::<ipc::IpcCommand>(32);
+    // Create IPC channel and start server (both modes accept `cavibe ctl`)
+    let (ipc_tx, ipc_rx) = tokio::sync::mpsc::channel::<ipc::IpcCommand>(32);
+
+    if config.ipc.listen.is_some() {
+        let remote_config = config.ipc.clone();
+        let remote_tx = ipc_tx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = ipc::start_remote_server(remote_config, remote_tx).await {
+                tracing::warn!("Remote control error: {:#}", e);
+            }
+        });
+    }
 
+    // A second instance (e.g. a terminal-mode run next to the wallpaper
+    // daemon) leaves the running instance's socket alone
+    match ipc::bind_server().await {
+        Ok(listener) => {
+            daemon::write_pidfile();
             tokio::spawn(async move {
-                if let Err(e) = ipc::start_server(ipc_tx).await {
+                if let Err(e) = ipc::start_server(listener, ipc_tx).await {
                     tracing::warn!("IPC server error: {}", e);
                 }
             });
+        }
+        Err(e) => tracing::warn!("IPC server disabled: {:#}", e),
+    }
+    daemon::install_signal_handlers();
 
-            display::wallpaper::run(config, ipc_rx).await?;
+    if config.web.enabled {
+        let web_config = config.web.clone();
+        tokio::spawn(async move {
+            if let Err(e) = web::start_server(web_config).await {
+                tracing::warn!("Web server error: {:#}", e);
+            }
+        });
+    }
+    leds::start(&config.leds, &config.visualizer.color_scheme);
 
-            // Clean up socket on exit
-            let _ = std::fs::remove_file(ipc::socket_path());
+    // Run the visualizer
+    match config.display.mode {
+        DisplayMode::Terminal => {
+            display::terminal::run(config, ipc_rx).await?;
         }
+        DisplayMode::Wallpaper | DisplayMode::Window => {
+            display::wallpaper::run(config, ipc_rx).await?;
+        }
+    }
+
+    // Clean up socket and pid file on exit
+    if crash::socket_owned() {
+        let _ = std::fs::remove_file(ipc::socket_path());
+        crash::set_socket_owned(false);
     }
+    daemon::remove_pidfile();
 
     Ok(())
 }
@@ -0,0 +1,123 @@
+//! Album art loading and scaling.
+//!
+//! Art is fetched from the MPRIS `mpris:artUrl` (file:// or http(s)://),
+//! decoded once per track, and scaled on demand for each render target.
+
+use anyhow::{Context, Result};
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Longest edge kept after decoding; larger covers are downscaled.
+const MAX_ART_SIZE: u32 = 512;
+/// Refuse to download covers larger than this
+const MAX_DOWNLOAD_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Scaled RGBA copies keyed by (width, height, blur)
+type ScaledCache = HashMap<(usize, usize, u32), Arc<Vec<u8>>>;
+
+/// Decoded album art with a cache of scaled copies.
+#[derive(Debug)]
+pub struct AlbumArt {
+    image: RgbaImage,
+    scaled: Mutex<ScaledCache>,
+}
+
+impl AlbumArt {
+    fn new(image: RgbaImage) -> Self {
+        Self {
+            image,
+            scaled: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get an RGBA copy scaled to cover `width`×`height` (center-cropped).
+    ///
+    /// `blur` > 0 softens the image by downsampling it before scaling up,
+    /// which is far cheaper than a full-resolution Gaussian blur.
+    pub fn scaled(&self, width: usize, height: usize, blur: u32) -> Arc<Vec<u8>> {
+        let key = (width, height, blur);
+        let mut cache = self.scaled.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pixels) = cache.get(&key) {
+            return pixels.clone();
+        }
+
+        // Crop the source to the target aspect ratio
+        let (src_w, src_h) = self.image.dimensions();
+        let target_aspect = width as f32 / height.max(1) as f32;
+        let (crop_w, crop_h) = if src_w as f32 / src_h as f32 > target_aspect {
+            (((src_h as f32 * target_aspect) as u32).max(1), src_h)
+        } else {
+            (src_w, ((src_w as f32 / target_aspect) as u32).max(1))
+        };
+        let cropped = imageops::crop_imm(&self.image, (src_w - crop_w) / 2, (src_h - crop_h) / 2, crop_w, crop_h).to_image();
+
+        let source = if blur > 0 {
+            let small_w = (crop_w / (blur + 1)).max(2);
+            let small_h = (crop_h / (blur + 1)).max(2);
+            imageops::resize(&cropped, small_w, small_h, FilterType::Triangle)
+        } else {
+            cropped
+        };
+        let pixels = Arc::new(
+            imageops::resize(&source, width.max(1) as u32, height.max(1) as u32, FilterType::Triangle).into_raw(),
+        );
+
+        // Multiple surfaces may share one track; a handful of sizes is plenty
+        if cache.len() >= 8 {
+            cache.clear();
+        }
+        cache.insert(key, pixels.clone());
+        pixels
+    }
+}
+
+/// Fetch and decode album art from an MPRIS art URL.
+pub fn load_album_art(url: &str) -> Result<AlbumArt> {
+    let bytes = if let Some(path) = url.strip_prefix("file://") {
+        std::fs::read(percent_decode(path)).with_context(|| format!("Failed to read {}", url))?
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        let response = ureq::get(url)
+            .timeout(Duration::from_secs(5))
+            .call()
+            .with_context(|| format!("Failed to fetch {}", url))?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_DOWNLOAD_BYTES)
+            .read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        anyhow::bail!("Unsupported art URL: {}", url);
+    };
+
+    let image = image::load_from_memory(&bytes).context("Failed to decode album art")?;
+    let image = if image.width() > MAX_ART_SIZE || image.height() > MAX_ART_SIZE {
+        image.thumbnail(MAX_ART_SIZE, MAX_ART_SIZE)
+    } else {
+        image
+    };
+    Ok(AlbumArt::new(image.to_rgba8()))
+}
+
+/// Decode %XX escapes in a file:// URL path.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(b) = s.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -1,9 +1,12 @@
+mod art;
+
+pub use art::AlbumArt;
+
 use anyhow::Result;
-use mpris::PlayerFinder;
-use std::sync::Arc;
-use std::time::Duration;
+use mpris::{PlaybackStatus, Player, PlayerFinder};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tokio::sync::watch;
-use tokio::time::interval;
 use tracing::{debug, warn};
 
 /// Current track metadata
@@ -11,26 +14,148 @@ use tracing::{debug, warn};
 pub struct TrackInfo {
     pub title: Option<String>,
     pub artist: Option<String>,
+    pub album: Option<String>,
+    /// Genres joined with ", ", when the player reports them
+    pub genre: Option<String>,
+    /// Track length, when the player reports it
+    pub length: Option<Duration>,
+    /// Playback position at the last poll
+    pub position: Option<Duration>,
+    pub art_url: Option<String>,
+    /// Decoded cover art (only fetched when album art display is enabled)
+    pub album_art: Option<Arc<AlbumArt>>,
+    /// Number of track changes seen so far; a new value means a new track
+    /// started (a player briefly disappearing doesn't count)
+    pub changes: u64,
+    /// The player is paused or stopped (false without a player)
+    pub paused: bool,
+}
+
+/// Player to follow, from `[metadata] player` or `ctl player set` (None = whichever is active)
+static PLAYER: RwLock<Option<String>> = RwLock::new(None);
+
+/// Follow only players matching `pattern` (None = whichever is active).
+pub fn set_player(pattern: Option<&str>) {
+    *PLAYER.write().unwrap_or_else(|e| e.into_inner()) = pattern.map(str::to_string);
+}
+
+fn preferred_player() -> Option<String> {
+    PLAYER.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Whether `player` is the one `pattern` names: its identity ("Spotify") or
+/// bus name ("spotify", "firefox.instance_1_42"), case-insensitive, with `*`
+/// matching any run of characters. Deliberately a glob rather than a regex:
+/// player names are full of dots, which would need escaping.
+fn player_matches(player: &Player, pattern: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    [player.identity(), player.bus_name_player_name_part()]
+        .iter()
+        .any(|name| wildcard_match(&pattern, &name.to_lowercase()))
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    // Without a `*` the whole name must match
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// A running MPRIS player, for `ctl player list`
+pub struct PlayerEntry {
+    /// Bus name part, as accepted by `ctl player set`
+    pub name: String,
+    pub identity: String,
+    pub status: &'static str,
+    /// The player cavibe follows
+    pub followed: bool,
+}
+
+/// Running players and which one is followed
+pub fn list_players() -> Result<Vec<PlayerEntry>> {
+    let finder = PlayerFinder::new()?;
+    let players = finder.find_all()?;
+    let followed = follow(&finder).ok().map(|player| player.bus_name().to_string());
+    Ok(players
+        .iter()
+        .map(|player| PlayerEntry {
+            name: player.bus_name_player_name_part().to_string(),
+            identity: player.identity().to_string(),
+            status: match player.get_playback_status() {
+                Ok(PlaybackStatus::Playing) => "playing",
+                Ok(PlaybackStatus::Paused) => "paused",
+                Ok(PlaybackStatus::Stopped) => "stopped",
+                Err(_) => "unknown",
+            },
+            followed: followed.as_deref() == Some(player.bus_name()),
+        })
+        .collect())
+}
+
+/// The player to read: the first matching the chosen one (others are
+/// ignored, even when playing), else the active one
+fn follow(finder: &PlayerFinder) -> Result<Player> {
+    if let Some(pattern) = preferred_player() {
+        return finder
+            .find_all()?
+            .into_iter()
+            .find(|player| player_matches(player, &pattern))
+            .ok_or_else(|| anyhow::anyhow!("Player '{}' is not running", pattern));
+    }
+    Ok(finder.find_active().or_else(|_| {
+        finder
+            .find_all()
+            .map_err(|_| mpris::DBusError::Miscellaneous("Failed to find players".into()))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| mpris::DBusError::Miscellaneous("No players found".into()))
+    })?)
 }
 
+/// How often the player is polled
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Poll interval while paused, so playback resuming is noticed quickly
+const PAUSED_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long album art that failed to load waits before another try, e.g.
+/// for a player that writes the file after announcing it
+const ART_RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
 /// MPRIS metadata watcher
 pub struct MetadataWatcher {
     sender: watch::Sender<Arc<TrackInfo>>,
+    fetch_art: bool,
+    /// Poll faster while paused (`pause_on_media_pause`)
+    follow_pause: bool,
+    /// Art for the most recent URL, reused while the track plays, or when
+    /// loading it failed, the time it may be tried again
+    art_cache: Option<(String, Result<Arc<AlbumArt>, Instant>)>,
+    /// Title, artist and album of the last track seen, for change detection
+    last_track: Option<(Option<String>, Option<String>, Option<String>)>,
+    changes: u64,
 }
 
 impl MetadataWatcher {
-    pub fn new() -> (Self, watch::Receiver<Arc<TrackInfo>>) {
+    pub fn new(fetch_art: bool, follow_pause: bool) -> (Self, watch::Receiver<Arc<TrackInfo>>) {
         let (sender, receiver) = watch::channel(Arc::new(TrackInfo::default()));
-        (Self { sender }, receiver)
+        (Self { sender, fetch_art, follow_pause, art_cache: None, last_track: None, changes: 0 }, receiver)
     }
 
-    pub async fn run(self) -> Result<()> {
-        let mut poll_interval = interval(Duration::from_secs(1));
-
+    pub async fn run(mut self) -> Result<()> {
         loop {
-            poll_interval.tick().await;
-
-            let track_info = match Self::fetch_current_track() {
+            let mut track_info = match Self::fetch_current_track() {
                 Ok(info) => info,
                 Err(e) => {
                     debug!("Failed to fetch track info: {}", e);
@@ -38,35 +163,79 @@ impl MetadataWatcher {
                 }
             };
 
+            if self.fetch_art {
+                track_info.album_art = self.album_art_for(track_info.art_url.as_deref()).await;
+            }
+
+            // Empty polls (no player, or a failed fetch) don't reset the
+            // last track, so the same track coming back isn't a change
+            if track_info.title.is_some() || track_info.artist.is_some() {
+                let key = (track_info.title.clone(), track_info.artist.clone(), track_info.album.clone());
+                if self.last_track.as_ref() != Some(&key) {
+                    self.last_track = Some(key);
+                    self.changes += 1;
+                }
+            }
+            track_info.changes = self.changes;
+
+            let fast = self.follow_pause && track_info.paused;
             let _ = self.sender.send(Arc::new(track_info));
+            tokio::time::sleep(if fast { PAUSED_POLL_INTERVAL } else { POLL_INTERVAL }).await;
+        }
+    }
+
+    /// Load art for `url`, reusing the cached result when the URL is unchanged.
+    /// A failed load is retried once `ART_RETRY_INTERVAL` has passed.
+    async fn album_art_for(&mut self, url: Option<&str>) -> Option<Arc<AlbumArt>> {
+        let url = url?;
+        match &self.art_cache {
+            Some((cached_url, Ok(art))) if cached_url == url => return Some(art.clone()),
+            Some((cached_url, Err(retry_at))) if cached_url == url && Instant::now() < *retry_at => return None,
+            _ => {}
         }
+
+        let owned_url = url.to_string();
+        let art = match tokio::task::spawn_blocking(move || art::load_album_art(&owned_url)).await {
+            Ok(Ok(art)) => Ok(Arc::new(art)),
+            Ok(Err(e)) => {
+                debug!("Failed to load album art: {}", e);
+                Err(Instant::now() + ART_RETRY_INTERVAL)
+            }
+            Err(e) => {
+                warn!("Album art loader panicked: {}", e);
+                Err(Instant::now() + ART_RETRY_INTERVAL)
+            }
+        };
+        self.art_cache = Some((url.to_string(), art.clone()));
+        art.ok()
     }
 
     fn fetch_current_track() -> Result<TrackInfo> {
         let finder = PlayerFinder::new()?;
-
-        // Find active player
-        let player = finder.find_active().or_else(|_| {
-            finder
-                .find_all()
-                .map_err(|_| mpris::DBusError::Miscellaneous("Failed to find players".into()))?
-                .into_iter()
-                .next()
-                .ok_or_else(|| mpris::DBusError::Miscellaneous("No players found".into()))
-        })?;
+        let player = follow(&finder)?;
 
         let metadata = player.get_metadata()?;
 
         Ok(TrackInfo {
             title: metadata.title().map(|s| s.to_string()),
             artist: metadata.artists().map(|a| a.join(", ")),
+            album: metadata.album_name().map(|s| s.to_string()),
+            genre: metadata.get("xesam:genre").and_then(|g| g.as_str_array()).map(|g| g.join(", ")),
+            length: metadata.length(),
+            position: player.get_position().ok(),
+            art_url: metadata.art_url().map(|s| s.to_string()),
+            album_art: None,
+            changes: 0,
+            paused: matches!(player.get_playback_status(), Ok(PlaybackStatus::Paused | PlaybackStatus::Stopped)),
         })
     }
 }
 
-/// Start the metadata watcher in the background
-pub fn start_watcher() -> watch::Receiver<Arc<TrackInfo>> {
-    let (watcher, receiver) = MetadataWatcher::new();
+/// Start the metadata watcher in the background.
+/// `fetch_art` enables downloading and decoding album art for each track;
+/// `follow_pause` polls faster while paused so resuming is noticed quickly.
+pub fn start_watcher(fetch_art: bool, follow_pause: bool) -> watch::Receiver<Arc<TrackInfo>> {
+    let (watcher, receiver) = MetadataWatcher::new(fetch_art, follow_pause);
 
     tokio::spawn(async move {
         if let Err(e) = watcher.run().await {
@@ -0,0 +1,39 @@
+//! Forgiving lookup of user-typed names (styles, color schemes).
+//!
+//! Names match case-insensitively and ignoring spaces, dashes and
+//! underscores, so `classic-bars`, `ClassicBars` and `"classic bars"` are the
+//! same. A unique prefix or substring is accepted too (`spec` → Spectrogram).
+//! Errors list the available names.
+
+/// Lowercase alphanumerics only
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Index of the entry in `names` that `query` refers to. `kind` names the
+/// kind of thing for the error message, e.g. "style".
+pub fn find<S: AsRef<str>>(query: &str, names: &[S], kind: &str) -> Result<usize, String> {
+    let wanted = normalize(query);
+    let normalized: Vec<String> = names.iter().map(|n| normalize(n.as_ref())).collect();
+    if !wanted.is_empty() {
+        if let Some(idx) = normalized.iter().position(|n| *n == wanted) {
+            return Ok(idx);
+        }
+        // Prefix matches win over matches anywhere in the name
+        for prefix in [true, false] {
+            let matches: Vec<usize> = (0..names.len())
+                .filter(|&i| if prefix { normalized[i].starts_with(&wanted) } else { normalized[i].contains(&wanted) })
+                .collect();
+            match matches[..] {
+                [idx] => return Ok(idx),
+                [] => {}
+                _ => {
+                    let candidates: Vec<&str> = matches.iter().map(|&i| names[i].as_ref()).collect();
+                    return Err(format!("Ambiguous {} '{}': could be {}", kind, query, candidates.join(", ")));
+                }
+            }
+        }
+    }
+    let available: Vec<&str> = names.iter().map(|n| n.as_ref()).collect();
+    Err(format!("Unknown {} '{}' (available: {})", kind, query, available.join(", ")))
+}
@@ -0,0 +1,138 @@
+//! Privacy mode: hide track text (or the whole visualizer) while the screen
+//! is being shared, or on demand with `cavibe ctl privacy on`.
+//!
+//! Screen sharing is detected by polling `pw-dump` for a running PipeWire
+//! video stream produced by a screencast portal backend or compositor.
+
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+use crate::config::{PrivacyConfig, PrivacyHide};
+
+/// How often PipeWire is checked for screencast streams
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Substrings of node/application names that identify screencast producers
+/// (xdg-desktop-portal-wlr/-hyprland/-gnome/-kde backends and compositors)
+const SCREENCAST_HINTS: &[&str] = &["xdg-desktop-portal", "xdpw", "xdph", "screencast", "gnome-shell", "kwin"];
+
+/// Privacy state owned by a render loop
+pub struct PrivacyState {
+    /// `ctl privacy on|off` override; None follows screen-share detection
+    manual: Option<bool>,
+    /// Screen-share detector, running while `hide_text_on_screenshare` is set
+    sharing: Option<watch::Receiver<bool>>,
+    hide: PrivacyHide,
+}
+
+impl PrivacyState {
+    pub fn new(config: &PrivacyConfig) -> Self {
+        let mut state = Self {
+            manual: None,
+            sharing: None,
+            hide: config.hide,
+        };
+        state.configure(config);
+        state
+    }
+
+    /// Apply (possibly changed) settings, starting or stopping detection.
+    pub fn configure(&mut self, config: &PrivacyConfig) {
+        self.hide = config.hide;
+        match (config.hide_text_on_screenshare, self.sharing.is_some()) {
+            (true, false) => self.sharing = Some(start_screenshare_watcher()),
+            // Dropping the receiver stops the watcher at its next poll
+            (false, true) => self.sharing = None,
+            _ => {}
+        }
+    }
+
+    /// Whether the screen is currently being shared (false when detection is off)
+    pub fn screen_shared(&self) -> bool {
+        self.sharing.as_ref().is_some_and(|rx| *rx.borrow())
+    }
+
+    /// Whether privacy mode is in effect
+    pub fn active(&self) -> bool {
+        self.manual.unwrap_or_else(|| self.screen_shared())
+    }
+
+    /// Whether the whole visualizer should be hidden, not just the text
+    pub fn hides_all(&self) -> bool {
+        self.active() && self.hide == PrivacyHide::All
+    }
+
+    /// Force privacy on or off, or `None` to follow screen-share detection
+    pub fn set_override(&mut self, value: Option<bool>) {
+        self.manual = value;
+    }
+}
+
+/// Poll PipeWire for screencast streams in the background.
+/// Stops when the returned receiver is dropped or `pw-dump` is unavailable.
+fn start_screenshare_watcher() -> watch::Receiver<bool> {
+    let (tx, rx) = watch::channel(false);
+    tokio::spawn(async move {
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            poll.tick().await;
+            if tx.is_closed() {
+                break;
+            }
+            let shared = match screencast_active().await {
+                Ok(Some(shared)) => shared,
+                // Unreadable output (e.g. cut off while the graph changed):
+                // keep the last state and look again next time
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Screen-share detection disabled: {}", e);
+                    break;
+                }
+            };
+            if *tx.borrow() != shared {
+                info!("Screen sharing {}", if shared { "started" } else { "stopped" });
+            }
+            tx.send_replace(shared);
+        }
+    });
+    rx
+}
+
+/// Whether any screencast video stream is currently running, or None when
+/// `pw-dump`'s output couldn't be read. Fails only if `pw-dump` can't be run.
+async fn screencast_active() -> anyhow::Result<Option<bool>> {
+    let output = tokio::process::Command::new("pw-dump")
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to run pw-dump: {}", e))?;
+    if !output.status.success() {
+        debug!("pw-dump exited with {}", output.status);
+        return Ok(Some(false));
+    }
+    match serde_json::from_slice::<Vec<Value>>(&output.stdout) {
+        Ok(objects) => Ok(Some(objects.iter().any(is_screencast_node))),
+        Err(e) => {
+            debug!("Unreadable pw-dump output: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+fn is_screencast_node(object: &Value) -> bool {
+    let info = &object["info"];
+    let props = &info["props"];
+    let class = props["media.class"].as_str().unwrap_or_default();
+    if info["state"].as_str() != Some("running") || !matches!(class, "Video/Source" | "Stream/Output/Video") {
+        return false;
+    }
+    ["node.name", "application.name", "application.process.binary"]
+        .iter()
+        .filter_map(|key| props[*key].as_str())
+        .any(|name| {
+            let name = name.to_lowercase();
+            SCREENCAST_HINTS.iter().any(|hint| name.contains(hint))
+        })
+}
@@ -0,0 +1,38 @@
+//! Pseudo-random numbers for picks that only need to differ between runs
+//! (a random startup style or color scheme, shuffled rotation).
+//!
+//! A splitmix64 sequence seeded once from the clock and process id; not
+//! suitable for anything security related.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Next pseudo-random 64-bit value
+pub fn next_u64() -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let seed = *SEED.get_or_init(|| {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+        nanos ^ (std::process::id() as u64) << 32
+    });
+    let mut z = seed.wrapping_add(COUNTER.fetch_add(1, Ordering::Relaxed).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Random index below `len` (0 when `len` is 0)
+pub fn index(len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    (next_u64() % len as u64) as usize
+}
+
+/// Shuffle `items` in place (Fisher-Yates)
+pub fn shuffle<T>(items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, index(i + 1));
+    }
+}
@@ -0,0 +1,84 @@
+//! Album art compositing onto the pixel canvas.
+
+use crate::config::AlbumArtConfig;
+use crate::metadata::AlbumArt;
+use super::Canvas;
+
+/// Gap between beside-text art and the text block
+const BESIDE_TEXT_GAP: usize = 12;
+/// Inset of the corner thumbnail from the canvas edges
+const CORNER_MARGIN: usize = 16;
+
+/// Draw the art as a blurred background covering the whole canvas.
+pub fn render_background(canvas: &mut Canvas, art: &AlbumArt, config: &AlbumArtConfig, opacity: f32) {
+    let (w, h) = (canvas.width, canvas.height);
+    if w == 0 || h == 0 {
+        return;
+    }
+    let pixels = art.scaled(w, h, config.blur);
+    blit(canvas, &pixels, 0, 0, w, h, config.opacity * opacity);
+}
+
+/// Draw the art as a square thumbnail in the top-right corner.
+pub fn render_corner(canvas: &mut Canvas, art: &AlbumArt, config: &AlbumArtConfig, opacity: f32) {
+    let size = (canvas.height as f32 * config.size.clamp(0.0, 1.0)) as usize;
+    let size = size.min(canvas.width.saturating_sub(CORNER_MARGIN * 2));
+    if size == 0 {
+        return;
+    }
+    let x = canvas.width - CORNER_MARGIN - size;
+    let pixels = art.scaled(size, size, 0);
+    blit(canvas, &pixels, x, CORNER_MARGIN.min(canvas.height - 1), size, size, config.opacity * opacity);
+}
+
+/// Horizontal space to reserve left of the text for beside-text art of `band_height`.
+pub fn beside_text_inset(band_height: usize) -> usize {
+    band_height + BESIDE_TEXT_GAP
+}
+
+/// Draw the art as a square filling the text band, just left of `text_x`.
+pub fn render_beside_text(
+    canvas: &mut Canvas,
+    art: &AlbumArt,
+    config: &AlbumArtConfig,
+    opacity: f32,
+    text_x: usize,
+    band: (usize, usize),
+) {
+    let (top, size) = band;
+    if size == 0 {
+        return;
+    }
+    let x = text_x.saturating_sub(beside_text_inset(size));
+    let pixels = art.scaled(size, size, 0);
+    blit(canvas, &pixels, x, top, size, size, config.opacity * opacity);
+}
+
+/// Composite straight-alpha RGBA `pixels` (`w`×`h`) over the canvas at (x, y).
+fn blit(canvas: &mut Canvas, pixels: &[u8], x: usize, y: usize, w: usize, h: usize, opacity: f32) {
+    let opacity = opacity.clamp(0.0, 1.0);
+    for row in 0..h {
+        let cy = y + row;
+        if cy >= canvas.height {
+            break;
+        }
+        for col in 0..w {
+            let cx = x + col;
+            if cx >= canvas.width {
+                break;
+            }
+            let si = (row * w + col) * 4;
+            let a = pixels[si + 3] as f32 / 255.0 * opacity;
+            if a <= 0.0 {
+                continue;
+            }
+            // Canvas is pre-multiplied: out = src * a + dst * (1 - a)
+            let di = (cy * canvas.width + cx) * 4;
+            let inv = 1.0 - a;
+            for c in 0..3 {
+                canvas.data[di + c] = (pixels[si + c] as f32 * a + canvas.data[di + c] as f32 * inv) as u8;
+            }
+            canvas.data[di + 3] = (a * 255.0 + canvas.data[di + 3] as f32 * inv) as u8;
+        }
+    }
+}
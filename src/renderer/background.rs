@@ -0,0 +1,130 @@
+//! Static background image beneath the visualizer (wallpaper mode).
+//!
+//! The image is decoded once when configured. Each surface size gets a
+//! prepared, pre-multiplied copy (scaled, darkened and blurred) that is
+//! copied straight into the canvas at the start of every frame.
+
+use anyhow::{Context, Result};
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::config::{BackgroundFit, WallpaperConfig};
+use super::Canvas;
+
+/// Prepared frames keyed by (width, height, fit, darken bits, blur)
+type PreparedCache = HashMap<(usize, usize, BackgroundFit, u32, u32), Arc<Vec<u8>>>;
+
+/// A decoded background image with a cache of prepared frames.
+pub struct Background {
+    image: RgbaImage,
+    prepared: Mutex<PreparedCache>,
+}
+
+impl Background {
+    /// Decode the image at `path` (a leading `~/` expands to the home directory).
+    pub fn load(path: &str) -> Result<Self> {
+        let path = expand_home(path);
+        let image = image::open(&path)
+            .with_context(|| format!("Failed to load background image {}", path.display()))?;
+        if image.width() == 0 || image.height() == 0 {
+            anyhow::bail!("Background image {} is empty", path.display());
+        }
+        Ok(Self {
+            image: image.to_rgba8(),
+            prepared: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Pre-multiplied RGBA frame of `width`×`height` for the configured fit,
+    /// darkening and blur.
+    fn prepared(&self, width: usize, height: usize, config: &WallpaperConfig) -> Arc<Vec<u8>> {
+        let darken = config.background_darken.clamp(0.0, 1.0);
+        let key = (width, height, config.background_fit, darken.to_bits(), config.background_blur);
+        let mut cache = self.prepared.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pixels) = cache.get(&key) {
+            return pixels.clone();
+        }
+
+        let (w, h) = (width as u32, height as u32);
+        let mut frame = match config.background_fit {
+            BackgroundFit::Fill => cover(&self.image, w, h),
+            BackgroundFit::Fit => contain(&self.image, w, h),
+            BackgroundFit::Tile => tile(&self.image, w, h),
+        };
+
+        // Same cheap blur as album art: downsample, then scale back up
+        let blur = config.background_blur;
+        if blur > 0 {
+            let small = imageops::resize(&frame, (w / (blur + 1)).max(2), (h / (blur + 1)).max(2), FilterType::Triangle);
+            frame = imageops::resize(&small, w, h, FilterType::Triangle);
+        }
+
+        let brightness = 1.0 - darken;
+        let mut pixels = frame.into_raw();
+        for px in pixels.chunks_exact_mut(4) {
+            let a = px[3] as f32 / 255.0;
+            for c in &mut px[..3] {
+                *c = (*c as f32 * a * brightness) as u8;
+            }
+        }
+        let pixels = Arc::new(pixels);
+
+        // One entry per surface size is all that's ever needed
+        if cache.len() >= 8 {
+            cache.clear();
+        }
+        cache.insert(key, pixels.clone());
+        pixels
+    }
+}
+
+/// Fill the canvas with the background, replacing its contents.
+pub fn render(canvas: &mut Canvas, background: &Background, config: &WallpaperConfig) {
+    let (w, h) = (canvas.width, canvas.height);
+    if w == 0 || h == 0 {
+        return;
+    }
+    let pixels = background.prepared(w, h, config);
+    canvas.data[..w * h * 4].copy_from_slice(&pixels);
+}
+
+/// Scale to cover `w`×`h`, center-cropping the overflow.
+fn cover(image: &RgbaImage, w: u32, h: u32) -> RgbaImage {
+    let (src_w, src_h) = image.dimensions();
+    let target_aspect = w as f32 / h as f32;
+    let (crop_w, crop_h) = if src_w as f32 / src_h as f32 > target_aspect {
+        (((src_h as f32 * target_aspect) as u32).clamp(1, src_w), src_h)
+    } else {
+        (src_w, ((src_w as f32 / target_aspect) as u32).clamp(1, src_h))
+    };
+    let cropped = imageops::crop_imm(image, (src_w - crop_w) / 2, (src_h - crop_h) / 2, crop_w, crop_h).to_image();
+    imageops::resize(&cropped, w, h, FilterType::Triangle)
+}
+
+/// Scale to fit inside `w`×`h`, centered on a transparent frame.
+fn contain(image: &RgbaImage, w: u32, h: u32) -> RgbaImage {
+    let (src_w, src_h) = image.dimensions();
+    let scale = (w as f32 / src_w as f32).min(h as f32 / src_h as f32);
+    let fit_w = ((src_w as f32 * scale) as u32).clamp(1, w);
+    let fit_h = ((src_h as f32 * scale) as u32).clamp(1, h);
+    let scaled = imageops::resize(image, fit_w, fit_h, FilterType::Triangle);
+    let mut frame = RgbaImage::new(w, h);
+    imageops::replace(&mut frame, &scaled, ((w - fit_w) / 2) as i64, ((h - fit_h) / 2) as i64);
+    frame
+}
+
+/// Repeat the image at its native size from the top-left corner.
+fn tile(image: &RgbaImage, w: u32, h: u32) -> RgbaImage {
+    let (src_w, src_h) = image.dimensions();
+    RgbaImage::from_fn(w, h, |x, y| *image.get_pixel(x % src_w, y % src_h))
+}
+
+pub(super) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
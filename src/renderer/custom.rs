@@ -0,0 +1,402 @@
+//! Data-driven styles: layers declared in `[[styles]]` or style files,
+//! interpreted by a generic engine on top of the shared bar layout.
+
+use std::sync::{Arc, RwLock};
+
+use crate::config::{BandSource, BlendMode, Config, LayerAnchor, LayerKind, RgbColor, StyleDef, StyleLayerDef};
+use super::layout::{BarCanvas, BarLayout};
+use super::{FrameData, RenderParams};
+
+/// A validated custom style, ready to render.
+#[derive(Debug)]
+pub struct CustomStyle {
+    pub name: String,
+    /// Layers in drawing order (sorted by `order`)
+    layers: Vec<Layer>,
+    /// The definition this style was built from, kept for runtime edits
+    def: StyleDef,
+}
+
+#[derive(Debug)]
+struct Layer {
+    kind: LayerKind,
+    /// Built-in style index drawn by a `style` layer
+    style: Option<usize>,
+    blend: BlendMode,
+    order: i32,
+    anchor: LayerAnchor,
+    color: Option<RgbColor>,
+    opacity: f32,
+    scale: f32,
+    width: f32,
+    count: usize,
+    speed: f32,
+    opacity_from: Option<BandSource>,
+    scale_from: Option<BandSource>,
+}
+
+impl CustomStyle {
+    /// Build a style from its definition, validating names and colors.
+    pub fn from_def(def: &StyleDef) -> Result<Self, String> {
+        if def.name.trim().is_empty() {
+            return Err("style name must not be empty".to_string());
+        }
+        if super::styles::STYLE_NAMES.iter().any(|s| s.eq_ignore_ascii_case(&def.name)) {
+            return Err(format!("style '{}' clashes with a built-in style", def.name));
+        }
+        if def.layers.is_empty() {
+            return Err(format!("style '{}' has no layers", def.name));
+        }
+        let mut layers = def
+            .layers
+            .iter()
+            .map(|layer| Layer::from_def(&def.name, layer))
+            .collect::<Result<Vec<_>, _>>()?;
+        layers.sort_by_key(|layer| layer.order);
+        Ok(Self {
+            name: def.name.clone(),
+            layers,
+            def: def.clone(),
+        })
+    }
+
+    /// Whether one of the layers is built-in style `builtin`
+    pub fn draws_builtin(&self, builtin: usize) -> bool {
+        self.layers.iter().any(|layer| layer.style == Some(builtin))
+    }
+
+    pub fn def(&self) -> &StyleDef {
+        &self.def
+    }
+}
+
+impl Layer {
+    fn from_def(style: &str, def: &StyleLayerDef) -> Result<Self, String> {
+        let color = def
+            .color
+            .as_deref()
+            .map(|hex| {
+                RgbColor::from_hex(hex).ok_or_else(|| format!("style '{}': invalid color '{}'", style, hex))
+            })
+            .transpose()?;
+        let style_index = match (def.kind, def.style.as_deref()) {
+            (LayerKind::Style, None) => {
+                return Err(format!("style '{}': a style layer needs `style = \"<built-in name>\"`", style));
+            }
+            (LayerKind::Style, Some(name)) => Some(
+                super::styles::STYLE_NAMES
+                    .iter()
+                    .position(|s| s.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("style '{}': '{}' is not a built-in style", style, name))?,
+            ),
+            _ => None,
+        };
+        let default_width = match def.kind {
+            LayerKind::Bars | LayerKind::Style => 1.0,
+            LayerKind::Line => 0.25,
+            LayerKind::Particles => 0.5,
+        };
+        Ok(Self {
+            kind: def.kind,
+            style: style_index,
+            blend: def.blend,
+            order: def.order,
+            anchor: def.anchor,
+            color,
+            opacity: def.opacity.clamp(0.0, 1.0),
+            scale: def.scale.max(0.0),
+            width: def.width.unwrap_or(default_width).max(0.0),
+            count: def.count,
+            speed: def.speed,
+            opacity_from: def.opacity_from,
+            scale_from: def.scale_from,
+        })
+    }
+}
+
+/// Custom styles registered at startup, indexed after the built-in styles.
+static CUSTOM_STYLES: RwLock<Vec<Arc<CustomStyle>>> = RwLock::new(Vec::new());
+
+/// Replace the registered custom styles with the config's `[[styles]]` plus
+/// every `*.toml` file in the styles directory.
+///
+/// Returns an error describing the first unreadable or invalid definition.
+pub fn register_custom_styles(defs: &[StyleDef]) -> Result<(), String> {
+    let mut all = defs.to_vec();
+    all.extend(load_style_files()?);
+
+    let mut styles: Vec<Arc<CustomStyle>> = Vec::with_capacity(all.len());
+    for def in &all {
+        let style = CustomStyle::from_def(def)?;
+        if styles.iter().any(|s| s.name.eq_ignore_ascii_case(&style.name)) {
+            return Err(format!("style '{}' is defined more than once", style.name));
+        }
+        if let Some(plugin) = super::plugin::find_plugin(&style.name) {
+            return Err(format!("style '{}' clashes with the plugin {}", style.name, plugin.path.display()));
+        }
+        styles.push(Arc::new(style));
+    }
+    if let Ok(mut registry) = CUSTOM_STYLES.write() {
+        *registry = styles;
+    }
+    Ok(())
+}
+
+/// Read style definitions from the styles directory, sorted by file name.
+fn load_style_files() -> Result<Vec<StyleDef>, String> {
+    let Some(dir) = Config::styles_dir() else {
+        return Ok(Vec::new());
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+pub fn custom_styles() -> Vec<Arc<CustomStyle>> {
+    CUSTOM_STYLES.read().map(|r| r.clone()).unwrap_or_default()
+}
+
+/// Change the definition of registered style `name` in place and rebuild it.
+///
+/// `edit` returns a message for the caller or an error, in which case the
+/// style is left untouched. Edits last until the styles are next registered
+/// (a config reload).
+pub fn edit_style(name: &str, edit: impl FnOnce(&mut StyleDef) -> Result<String, String>) -> Result<String, String> {
+    let mut registry = CUSTOM_STYLES.write().map_err(|_| "style registry is unavailable".to_string())?;
+    let slot = registry
+        .iter_mut()
+        .find(|s| s.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("no custom style named '{}'", name))?;
+    let mut def = slot.def.clone();
+    let message = edit(&mut def)?;
+    *slot = Arc::new(CustomStyle::from_def(&def)?);
+    Ok(message)
+}
+
+/// Level of `source` in this frame
+fn band_level(frame: &FrameData, source: BandSource) -> f32 {
+    match source {
+        BandSource::Bass => frame.bands.bass,
+        BandSource::Mid => frame.bands.mid,
+        BandSource::Treble => frame.bands.treble,
+        BandSource::Intensity => frame.intensity,
+    }
+}
+
+/// Render every layer of `style`, bottom to top.
+pub fn render(
+    canvas: &mut BarCanvas,
+    layout: &BarLayout,
+    style: &CustomStyle,
+    frame: &FrameData,
+    params: &RenderParams,
+) {
+    let time = frame.time;
+    for layer in &style.layers {
+        let opacity = params.opacity * layer.opacity * layer.opacity_from.map_or(1.0, |b| band_level(frame, b));
+        let scale = layer.scale * layer.scale_from.map_or(1.0, |b| 1.0 + band_level(frame, b));
+        if opacity <= 0.0 {
+            continue;
+        }
+        let ctx = LayerContext {
+            layer,
+            layout,
+            params,
+            opacity,
+            scale,
+            size: ((params.bar_width as f32 * layer.width).round() as usize).max(1),
+        };
+        match layer.kind {
+            LayerKind::Bars => render_bars_layer(canvas, &ctx),
+            LayerKind::Line => render_line_layer(canvas, &ctx),
+            LayerKind::Particles => render_particles_layer(canvas, &ctx, time),
+            LayerKind::Style => render_style_layer(canvas, &ctx),
+        }
+    }
+}
+
+/// Per-frame values shared by the layer renderers.
+struct LayerContext<'a> {
+    layer: &'a Layer,
+    layout: &'a BarLayout,
+    params: &'a RenderParams<'a>,
+    opacity: f32,
+    scale: f32,
+    /// Bar width, line thickness or particle diameter in pixels
+    size: usize,
+}
+
+impl LayerContext<'_> {
+    fn magnitude(&self, i: usize) -> f32 {
+        (self.layout.render_frequencies[i] * self.scale).clamp(0.0, 1.0)
+    }
+
+    fn color(&self, position: f32, intensity: f32) -> (u8, u8, u8) {
+        match self.layer.color {
+            Some(c) => (c.r, c.g, c.b),
+            None => self.params.color_scheme.get_color(position, intensity),
+        }
+    }
+
+    /// Vertical span (top, bottom exclusive) covered by a level of `magnitude`.
+    fn span(&self, magnitude: f32) -> (usize, usize) {
+        let top = self.layout.bars_y_start;
+        let height = self.layout.bars_height;
+        let len = (magnitude * height as f32) as usize;
+        match self.layer.anchor {
+            LayerAnchor::Bottom => (top + height - len, top + height),
+            LayerAnchor::Top => (top, top + len),
+            LayerAnchor::Center => {
+                let center = top + height / 2;
+                (center - len / 2, center + len.div_ceil(2))
+            }
+        }
+    }
+
+    /// Y coordinate of the level line for `magnitude`.
+    fn level_y(&self, magnitude: f32) -> usize {
+        let (top, bottom) = self.span(magnitude);
+        match self.layer.anchor {
+            LayerAnchor::Bottom | LayerAnchor::Center => top,
+            LayerAnchor::Top => bottom.saturating_sub(1).max(top),
+        }
+    }
+
+    /// Center x of bar `i`.
+    fn center_x(&self, i: usize) -> usize {
+        self.layout.bar_x(i) + self.params.bar_width / 2
+    }
+
+    fn put(&self, canvas: &mut BarCanvas, x: usize, y: usize, color: (u8, u8, u8), opacity: f32) {
+        let layout = self.layout;
+        if x < canvas.width && y >= layout.bars_y_start && y < layout.bars_y_start + layout.bars_height && y < canvas.height {
+            canvas.composite_pixel(x, y, color.0, color.1, color.2, opacity, self.layer.blend);
+        }
+    }
+}
+
+/// Draw a built-in style offscreen and stack it as a single layer.
+fn render_style_layer(canvas: &mut BarCanvas, ctx: &LayerContext) {
+    let Some(style) = ctx.layer.style else {
+        return;
+    };
+    let mut layout = ctx.layout.clone();
+    for magnitude in &mut layout.render_frequencies {
+        *magnitude = (*magnitude * ctx.scale).clamp(0.0, 1.0);
+    }
+    let params = RenderParams {
+        style,
+        opacity: 1.0,
+        ..*ctx.params
+    };
+    canvas.composite_layer(ctx.opacity, ctx.layer.blend, |layer| {
+        super::styles::render_builtin(layer, &layout, &params);
+    });
+}
+
+fn render_bars_layer(canvas: &mut BarCanvas, ctx: &LayerContext) {
+    let layout = ctx.layout;
+    for i in 0..layout.displayable {
+        let magnitude = ctx.magnitude(i);
+        let position = i as f32 / layout.displayable as f32;
+        let x_start = ctx.center_x(i).saturating_sub(ctx.size / 2);
+        let (top, bottom) = ctx.span(magnitude);
+        for y in top..bottom {
+            let intensity = 1.0 - (y - layout.bars_y_start) as f32 / layout.bars_height as f32;
+            let color = ctx.color(position, intensity);
+            for x in x_start..x_start + ctx.size {
+                ctx.put(canvas, x, y, color, ctx.opacity);
+            }
+        }
+    }
+}
+
+fn render_line_layer(canvas: &mut BarCanvas, ctx: &LayerContext) {
+    let layout = ctx.layout;
+    let half = ctx.size / 2;
+    let mut prev: Option<(usize, usize)> = None;
+    for i in 0..layout.displayable {
+        let magnitude = ctx.magnitude(i);
+        let point = (ctx.center_x(i), ctx.level_y(magnitude));
+        let color = ctx.color(i as f32 / layout.displayable as f32, magnitude.max(0.3));
+        let (x0, y0) = prev.unwrap_or(point);
+        // Step along the longer axis so the segment has no gaps
+        let dx = point.0 as isize - x0 as isize;
+        let dy = point.1 as isize - y0 as isize;
+        let steps = dx.abs().max(dy.abs()).max(1);
+        for s in 0..=steps {
+            let x = (x0 as isize + dx * s / steps) as usize;
+            let y = (y0 as isize + dy * s / steps) as usize;
+            for by in y.saturating_sub(half)..=y + half {
+                for bx in x.saturating_sub(half)..=x + half {
+                    ctx.put(canvas, bx, by, color, ctx.opacity);
+                }
+            }
+        }
+        prev = Some(point);
+    }
+}
+
+fn render_particles_layer(canvas: &mut BarCanvas, ctx: &LayerContext, time: f32) {
+    let layout = ctx.layout;
+    let count = ctx.layer.count;
+    let radius = (ctx.size / 2).max(1) as isize;
+    let r2 = radius * radius;
+    for k in 0..count {
+        // Deterministic pseudo-random phase and speed per particle
+        let seed = hash(k as u32);
+        let phase = (seed & 0xFFFF) as f32 / 65535.0;
+        let speed = ctx.layer.speed * (0.5 + (seed >> 16) as f32 / 65535.0);
+        let progress = (time * speed + phase).fract();
+
+        let i = k * layout.displayable / count.max(1);
+        let magnitude = ctx.magnitude(i);
+        if magnitude < 0.02 {
+            continue;
+        }
+        // Travel away from the anchor edge, fading out as the particle goes
+        let travel = (progress * layout.bars_height as f32) as usize;
+        let bottom = layout.bars_y_start + layout.bars_height - 1;
+        let y = match ctx.layer.anchor {
+            LayerAnchor::Bottom => bottom - travel.min(bottom),
+            LayerAnchor::Top => layout.bars_y_start + travel,
+            LayerAnchor::Center => {
+                let center = layout.bars_y_start + layout.bars_height / 2;
+                let half_travel = travel / 2;
+                if k % 2 == 0 { center.saturating_sub(half_travel) } else { center + half_travel }
+            }
+        };
+        let opacity = ctx.opacity * magnitude * (1.0 - progress);
+        let color = ctx.color(i as f32 / layout.displayable as f32, magnitude);
+        let cx = ctx.center_x(i) as isize;
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx * dx + dy * dy <= r2 {
+                    ctx.put(canvas, (cx + dx) as usize, (y as isize + dy) as usize, color, opacity);
+                }
+            }
+        }
+    }
+}
+
+/// Small integer hash (xorshift-multiply) for stable per-particle randomness.
+pub(super) fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^ (x >> 16)
+}
@@ -0,0 +1,81 @@
+//! Dirty-region detection between consecutive frames.
+//!
+//! Output backends keep the previous frame and damage only the tiles that
+//! changed, so the compositor doesn't recomposite a whole 4K surface when
+//! only a strip of bars moved.
+
+use super::Canvas;
+
+/// Tile edge in pixels; changes are tracked at this granularity
+const TILE: usize = 64;
+/// Above this many regions a single bounding box is cheaper for the compositor
+const MAX_RECTS: usize = 32;
+
+/// A changed region in canvas pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DamageRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Regions of `current` that differ from `previous`, one per band of tile
+/// rows with the same horizontal extent. Returns None when the frames can't
+/// be compared (size changed) and the whole surface must be damaged; an
+/// empty list means nothing changed.
+pub fn dirty_rects(previous: &Canvas, current: &Canvas) -> Option<Vec<DamageRect>> {
+    let (w, h) = (current.width, current.height);
+    if previous.width != w || previous.height != h {
+        return None;
+    }
+
+    let tiles_x = w.div_ceil(TILE);
+    let row_bytes = w * 4;
+    let mut rects: Vec<DamageRect> = Vec::new();
+
+    for y0 in (0..h).step_by(TILE) {
+        let y1 = (y0 + TILE).min(h);
+        // First and last changed tile column in this tile row
+        let mut span: Option<(usize, usize)> = None;
+        for y in y0..y1 {
+            let row = y * row_bytes;
+            let (prev_row, cur_row) = (&previous.data[row..row + row_bytes], &current.data[row..row + row_bytes]);
+            if prev_row == cur_row {
+                continue;
+            }
+            let changed = |tx: usize| {
+                let (a, b) = (tx * TILE * 4, ((tx + 1) * TILE).min(w) * 4);
+                prev_row[a..b] != cur_row[a..b]
+            };
+            let first = (0..tiles_x).find(|&tx| changed(tx)).unwrap_or(0);
+            let last = (first..tiles_x).rev().find(|&tx| changed(tx)).unwrap_or(first);
+            span = Some(span.map_or((first, last), |(a, b)| (a.min(first), b.max(last))));
+        }
+
+        let Some((first, last)) = span else { continue };
+        let x = first * TILE;
+        let rect = DamageRect {
+            x,
+            y: y0,
+            width: ((last + 1) * TILE).min(w) - x,
+            height: y1 - y0,
+        };
+        // Extend the previous band when it lines up, keeping the list short
+        match rects.last_mut() {
+            Some(prev) if prev.x == rect.x && prev.width == rect.width && prev.y + prev.height == rect.y => {
+                prev.height += rect.height;
+            }
+            _ => rects.push(rect),
+        }
+    }
+
+    if rects.len() > MAX_RECTS {
+        let x = rects.iter().map(|r| r.x).min().unwrap_or(0);
+        let y = rects.iter().map(|r| r.y).min().unwrap_or(0);
+        let right = rects.iter().map(|r| r.x + r.width).max().unwrap_or(w);
+        let bottom = rects.iter().map(|r| r.y + r.height).max().unwrap_or(h);
+        rects = vec![DamageRect { x, y, width: right - x, height: bottom - y }];
+    }
+    Some(rects)
+}
@@ -0,0 +1,391 @@
+//! Anti-aliased drawing primitives in bar space.
+//!
+//! Lines (Wu's hairline and thick strokes with round caps), polylines,
+//! circles/ellipses, arcs and round-ended bars, all composited with per-pixel coverage so edges
+//! stay smooth at wallpaper resolutions. Styles pass a [`Paint`] with the
+//! overall opacity and the rows they may draw into.
+
+use std::cell::RefCell;
+use std::ops::Range;
+
+use super::layout::BarCanvas;
+
+/// An RGB color
+pub type Color = (u8, u8, u8);
+
+/// Opacity and clip shared by a run of drawing calls
+#[derive(Clone)]
+pub struct Paint {
+    pub opacity: f32,
+    /// Bar-space rows the primitives may touch
+    pub rows: Range<usize>,
+}
+
+impl Paint {
+    /// Blend `color` at `coverage` (0-1) into the pixel at (x, y), if it is in the clip
+    #[inline]
+    fn plot(&self, canvas: &mut BarCanvas, x: isize, y: isize, color: Color, coverage: f32) {
+        if coverage <= 0.0 || x < 0 || y < 0 || !self.rows.contains(&(y as usize)) {
+            return;
+        }
+        canvas.put_pixel(x as usize, y as usize, color.0, color.1, color.2, self.opacity * coverage.min(1.0));
+    }
+
+    /// Rows of `min..=max` inside the clip
+    fn clip_rows(&self, min: f32, max: f32) -> Range<isize> {
+        let start = (min.floor() as isize).max(self.rows.start as isize);
+        let end = (max.ceil() as isize + 1).min(self.rows.end as isize);
+        start..end
+    }
+}
+
+/// Coverage of a pixel whose center is `distance` from the middle of a
+/// stroke `width` wide (1-pixel linear falloff at the edge)
+#[inline]
+fn stroke_coverage(distance: f32, width: f32) -> f32 {
+    (width / 2.0 + 0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Distance from `p` to the segment `a`-`b`, and how far along it (0-1) the
+/// closest point lies
+#[inline]
+fn segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (cx, cy) = (a.0 + dx * t, a.1 + dy * t);
+    (((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt(), t)
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// One-pixel anti-aliased line (Xiaolin Wu's algorithm).
+pub fn line(canvas: &mut BarCanvas, from: (f32, f32), to: (f32, f32), color: Color, paint: &Paint) {
+    // Work with pixel centers on integer coordinates
+    let (mut x0, mut y0, mut x1, mut y1) = (from.0 - 0.5, from.1 - 0.5, to.0 - 0.5, to.1 - 0.5);
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        std::mem::swap(&mut x0, &mut y0);
+        std::mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+    let gradient = if x1 > x0 { (y1 - y0) / (x1 - x0) } else { 1.0 };
+    let mut plot = |major: isize, minor: isize, coverage: f32| {
+        if steep {
+            paint.plot(canvas, minor, major, color, coverage);
+        } else {
+            paint.plot(canvas, major, minor, color, coverage);
+        }
+    };
+
+    let (start, end) = (x0.round() as isize, x1.round() as isize);
+    for x in start..=end {
+        let y = y0 + gradient * (x as f32 - x0);
+        // Endpoints are weighted by how much of their pixel the line covers
+        let weight = if x == start {
+            1.0 - (x0 + 0.5 - start as f32)
+        } else if x == end {
+            x1 + 0.5 - end as f32
+        } else {
+            1.0
+        };
+        let (row, frac) = (y.floor() as isize, y - y.floor());
+        plot(x, row, (1.0 - frac) * weight);
+        plot(x, row + 1, frac * weight);
+    }
+}
+
+/// Line `width` pixels thick with round caps. `color` gets the position
+/// along the line, 0 at `from` and 1 at `to`.
+pub fn thick_line(
+    canvas: &mut BarCanvas,
+    from: (f32, f32),
+    to: (f32, f32),
+    width: f32,
+    paint: &Paint,
+    color: impl Fn(f32) -> Color,
+) {
+    if width <= 1.0 {
+        return line(canvas, from, to, color(0.5), paint);
+    }
+    let reach = width / 2.0 + 1.0;
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let steep = dy.abs() > dx.abs();
+    // Walk the longer axis; at each step the stroke spans `across` either
+    // side of the center line
+    let swap = |(x, y): (f32, f32)| if steep { (y, x) } else { (x, y) };
+    let (start, end) = (swap(from), swap(to));
+    let major_len = (end.0 - start.0).abs();
+    let across = if major_len > 0.0 { reach * (dx * dx + dy * dy).sqrt() / major_len } else { reach };
+    let majors = (start.0.min(end.0) - reach).floor() as isize..=(start.0.max(end.0) + reach).ceil() as isize;
+    for major in majors {
+        let t = if major_len > 0.0 { ((major as f32 + 0.5 - start.0) / (end.0 - start.0)).clamp(0.0, 1.0) } else { 0.0 };
+        let center = start.1 + (end.1 - start.1) * t;
+        for minor in (center - across).floor() as isize..=(center + across).ceil() as isize {
+            let (x, y) = if steep { (minor, major) } else { (major, minor) };
+            let (distance, t) = segment_distance((x as f32 + 0.5, y as f32 + 0.5), from, to);
+            paint.plot(canvas, x, y, color(t), stroke_coverage(distance, width));
+        }
+    }
+}
+
+thread_local! {
+    /// Per-pixel coverage and color of a polyline being drawn, reused across frames
+    static STROKE_MASK: RefCell<StrokeMask> = RefCell::new(StrokeMask::default());
+}
+
+/// Accumulates a polyline so pixels shared by neighbouring segments are
+/// drawn once, at their highest coverage
+#[derive(Default)]
+struct StrokeMask {
+    width: usize,
+    height: usize,
+    /// (coverage, r, g, b) per pixel
+    pixels: Vec<[u8; 4]>,
+    /// Bounding box of touched pixels: (min x, min y, max x, max y)
+    bounds: Option<(usize, usize, usize, usize)>,
+}
+
+impl StrokeMask {
+    fn reset(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        if self.pixels.len() < width * height {
+            self.pixels.resize(width * height, [0; 4]);
+        }
+        self.bounds = None;
+    }
+
+    fn segment(&mut self, (a, ca): ((f32, f32), Color), (b, cb): ((f32, f32), Color), width: f32, rows: &Range<usize>) {
+        let reach = width / 2.0 + 1.0;
+        let x_start = (a.0.min(b.0) - reach).floor().max(0.0) as usize;
+        let x_end = ((a.0.max(b.0) + reach).ceil().max(0.0) as usize + 1).min(self.width);
+        let y_start = ((a.1.min(b.1) - reach).floor().max(0.0) as usize).max(rows.start);
+        let y_end = ((a.1.max(b.1) + reach).ceil().max(0.0) as usize + 1).min(rows.end).min(self.height);
+        if x_start >= x_end || y_start >= y_end {
+            return;
+        }
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let (distance, t) = segment_distance((x as f32 + 0.5, y as f32 + 0.5), a, b);
+                let coverage = (stroke_coverage(distance, width) * 255.0) as u8;
+                let pixel = &mut self.pixels[y * self.width + x];
+                if coverage > pixel[0] {
+                    let (r, g, b) = lerp_color(ca, cb, t);
+                    *pixel = [coverage, r, g, b];
+                }
+            }
+        }
+        let (x_max, y_max) = (x_end - 1, y_end - 1);
+        self.bounds = Some(match self.bounds {
+            Some((x0, y0, x1, y1)) => (x0.min(x_start), y0.min(y_start), x1.max(x_max), y1.max(y_max)),
+            None => (x_start, y_start, x_max, y_max),
+        });
+    }
+
+    /// Blend the accumulated stroke into `canvas` and clear it
+    fn flush(&mut self, canvas: &mut BarCanvas, opacity: f32) {
+        let Some((x0, y0, x1, y1)) = self.bounds.take() else {
+            return;
+        };
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let pixel = &mut self.pixels[y * self.width + x];
+                if pixel[0] > 0 {
+                    canvas.put_pixel(x, y, pixel[1], pixel[2], pixel[3], opacity * pixel[0] as f32 / 255.0);
+                    *pixel = [0; 4];
+                }
+            }
+        }
+    }
+}
+
+/// Connected line through `points` (each with its color, blended along the
+/// segments), `width` pixels thick with round joins.
+pub fn polyline(canvas: &mut BarCanvas, points: impl IntoIterator<Item = ((f32, f32), Color)>, width: f32, paint: &Paint) {
+    let width = width.max(1.0);
+    STROKE_MASK.with(|mask| {
+        let mut mask = mask.borrow_mut();
+        mask.reset(canvas.width, canvas.height);
+        let mut prev = None;
+        for point in points {
+            mask.segment(prev.unwrap_or(point), point, width, &paint.rows);
+            prev = Some(point);
+        }
+        mask.flush(canvas, paint.opacity);
+    });
+}
+
+/// Bar `x..x + width` wide over rows `top..bottom`, with its top and/or
+/// bottom end rounded into a semicircle as wide as the bar (anti-aliased).
+/// `color` gets the row.
+pub fn fill_capsule(
+    canvas: &mut BarCanvas,
+    columns: (f32, f32),
+    rows: (f32, f32),
+    ends: (bool, bool),
+    paint: &Paint,
+    color: impl Fn(isize) -> Color,
+) {
+    fill_capsule_shaded(canvas, columns, rows, ends, paint, |y| (color(y), 1.0));
+}
+
+/// [`fill_capsule`] whose `shade` gets the row and returns its color and
+/// opacity (0-1, on top of the paint's), for bars that fade along their length
+pub fn fill_capsule_shaded(
+    canvas: &mut BarCanvas,
+    (x, width): (f32, f32),
+    (top, bottom): (f32, f32),
+    (round_top, round_bottom): (bool, bool),
+    paint: &Paint,
+    shade: impl Fn(isize) -> (Color, f32),
+) {
+    if width <= 0.0 || bottom <= top {
+        return;
+    }
+    let radius = width / 2.0;
+    let center_x = x + radius;
+    // Centers of the end circles; a bar shorter than its caps gets the
+    // overlap of both (or a dome cut off at its base)
+    let (top_center, bottom_center) = (top + radius, bottom - radius);
+    let disk = |p: (f32, f32), center_y: f32| 0.5 - ((p.0 - center_x).hypot(p.1 - center_y) - radius);
+    for y in paint.clip_rows(top, bottom - 1.0) {
+        let (row_color, row_opacity) = shade(y);
+        let py = y as f32 + 0.5;
+        for px in (x.floor() as isize)..((x + width).ceil() as isize) {
+            let p = (px as f32 + 0.5, py);
+            let mut coverage: f32 = 1.0;
+            if round_top && py < top_center {
+                coverage = coverage.min(disk(p, top_center));
+            }
+            if round_bottom && py > bottom_center {
+                coverage = coverage.min(disk(p, bottom_center));
+            }
+            paint.plot(canvas, px, y, row_color, coverage * row_opacity);
+        }
+    }
+}
+
+/// Pixels of row `y` that may be within `reach` of an ellipse edge:
+/// the spans between the inner and outer ellipses, left and right
+fn ellipse_row_spans(center: (f32, f32), (rx, ry): (f32, f32), reach: f32, y: isize) -> [Range<isize>; 2] {
+    let dy = y as f32 + 0.5 - center.1;
+    let half_chord = |rx: f32, ry: f32| {
+        if rx <= 0.0 || ry <= 0.0 || dy.abs() >= ry {
+            0.0
+        } else {
+            rx * (1.0 - (dy / ry).powi(2)).sqrt()
+        }
+    };
+    let outer = half_chord(rx + reach, ry + reach);
+    let inner = half_chord(rx - reach, ry - reach);
+    let span = |from: f32, to: f32| (from.floor().max(0.0) as isize)..(to.ceil().max(0.0) as isize + 1);
+    let left = span(center.0 - outer, center.0 - inner);
+    let mut right = span(center.0 + inner, center.0 + outer);
+    // Rows past the inner ellipse would share the middle pixels
+    right.start = right.start.max(left.end);
+    [left, right]
+}
+
+/// Approximate signed distance from `p` to the edge of an ellipse (negative
+/// inside); exact for circles
+#[inline]
+fn ellipse_distance(p: (f32, f32), center: (f32, f32), (rx, ry): (f32, f32)) -> f32 {
+    let (dx, dy) = (p.0 - center.0, p.1 - center.1);
+    let (nx, ny) = (dx / (rx * rx), dy / (ry * ry));
+    let q = ((dx / rx).powi(2) + (dy / ry).powi(2)).sqrt();
+    let gradient = (nx * nx + ny * ny).sqrt();
+    if gradient > 0.0 {
+        (q - 1.0) * q / gradient
+    } else {
+        -rx.min(ry)
+    }
+}
+
+/// Filled ellipse with anti-aliased edges (a circle when both radii are equal).
+pub fn fill_ellipse(canvas: &mut BarCanvas, center: (f32, f32), radii: (f32, f32), color: Color, paint: &Paint) {
+    if radii.0 <= 0.0 || radii.1 <= 0.0 {
+        return;
+    }
+    for y in paint.clip_rows(center.1 - radii.1 - 1.0, center.1 + radii.1 + 1.0) {
+        let [left, right] = ellipse_row_spans(center, radii, 1.0, y);
+        for x in left.start..right.end {
+            let distance = ellipse_distance((x as f32 + 0.5, y as f32 + 0.5), center, radii);
+            paint.plot(canvas, x, y, color, 0.5 - distance);
+        }
+    }
+}
+
+/// Ellipse outline `width` pixels thick with anti-aliased edges (a circle
+/// when both radii are equal). `color` gets the position around the
+/// ellipse, 0-1 clockwise from the top.
+pub fn stroke_ellipse(
+    canvas: &mut BarCanvas,
+    center: (f32, f32),
+    radii: (f32, f32),
+    width: f32,
+    paint: &Paint,
+    color: impl Fn(f32) -> Color,
+) {
+    if radii.0 <= 0.0 || radii.1 <= 0.0 {
+        return;
+    }
+    let reach = width / 2.0 + 1.0;
+    for y in paint.clip_rows(center.1 - radii.1 - reach, center.1 + radii.1 + reach) {
+        for span in ellipse_row_spans(center, radii, reach, y) {
+            for x in span {
+                let p = (x as f32 + 0.5, y as f32 + 0.5);
+                let coverage = stroke_coverage(ellipse_distance(p, center, radii).abs(), width);
+                if coverage > 0.0 {
+                    let angle = (p.1 - center.1).atan2(p.0 - center.0) + std::f32::consts::FRAC_PI_2;
+                    paint.plot(canvas, x, y, color((angle / std::f32::consts::TAU).rem_euclid(1.0)), coverage);
+                }
+            }
+        }
+    }
+}
+
+/// Part of a circle outline `width` pixels thick, running clockwise from
+/// `start` to `end` (turns from the top, as in [`stroke_ellipse`]; `start`
+/// may be negative to span the top). `color` gets the position along the
+/// arc, 0 at `start` and 1 at `end`.
+#[allow(clippy::too_many_arguments)]
+pub fn stroke_arc(
+    canvas: &mut BarCanvas,
+    center: (f32, f32),
+    radius: f32,
+    (start, end): (f32, f32),
+    width: f32,
+    paint: &Paint,
+    color: impl Fn(f32) -> Color,
+) {
+    let sweep = end - start;
+    if radius <= 0.0 || sweep <= 0.0 {
+        return;
+    }
+    let reach = width / 2.0 + 1.0;
+    for y in paint.clip_rows(center.1 - radius - reach, center.1 + radius + reach) {
+        for span in ellipse_row_spans(center, (radius, radius), reach, y) {
+            for x in span {
+                let p = (x as f32 + 0.5, y as f32 + 0.5);
+                let angle = (p.1 - center.1).atan2(p.0 - center.0) + std::f32::consts::FRAC_PI_2;
+                let along = (angle / std::f32::consts::TAU - start).rem_euclid(1.0);
+                if along > sweep {
+                    continue;
+                }
+                let coverage = stroke_coverage(ellipse_distance(p, center, (radius, radius)).abs(), width);
+                paint.plot(canvas, x, y, color(along / sweep), coverage);
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Post-processing effects applied to the finished canvas.
+//!
+//! Effects run in the order listed in `renderer.effects`, each reading and
+//! writing the pre-multiplied RGBA canvas in place, so they chain freely
+//! (e.g. glow before scanlines dims the glow along with the bars).
+
+use crate::config::Effect;
+use super::Canvas;
+
+/// Glow is computed on a grid this many times smaller than the canvas
+const GLOW_DOWNSCALE: usize = 4;
+/// Box blur radius on the downscaled grid (two passes approximate a gaussian)
+const GLOW_RADIUS: usize = 3;
+/// Brightness (0-255 luma) above which pixels bloom
+const GLOW_THRESHOLD: f32 = 96.0;
+/// Strength of the added glow
+const GLOW_GAIN: f32 = 1.6;
+/// Brightness kept on every other row
+const SCANLINE_LEVEL: f32 = 0.6;
+/// Red/blue channel offset as a fraction of the canvas width
+const ABERRATION_SHIFT: f32 = 0.003;
+/// Darkening at the corners (0.0-1.0)
+const VIGNETTE_STRENGTH: f32 = 0.6;
+
+/// Apply `effects` in order.
+pub fn apply(canvas: &mut Canvas, effects: &[Effect]) {
+    if canvas.width == 0 || canvas.height == 0 {
+        return;
+    }
+    for effect in effects {
+        match effect {
+            Effect::Glow => glow(canvas),
+            Effect::Scanlines => scanlines(canvas),
+            Effect::ChromaticAberration => chromatic_aberration(canvas),
+            Effect::Vignette => vignette(canvas),
+        }
+    }
+}
+
+/// Bloom around bright pixels: bright-pass at reduced resolution, blur, then
+/// add back with bilinear upsampling.
+fn glow(canvas: &mut Canvas) {
+    let (w, h) = (canvas.width, canvas.height);
+    let gw = w.div_ceil(GLOW_DOWNSCALE);
+    let gh = h.div_ceil(GLOW_DOWNSCALE);
+
+    // Bright-pass, averaged over each downscaled block
+    let mut grid = vec![[0.0f32; 3]; gw * gh];
+    for y in 0..h {
+        for x in 0..w {
+            let i = (y * w + x) * 4;
+            let (r, g, b) = (canvas.data[i] as f32, canvas.data[i + 1] as f32, canvas.data[i + 2] as f32);
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+            if luma > GLOW_THRESHOLD {
+                let cell = &mut grid[(y / GLOW_DOWNSCALE) * gw + x / GLOW_DOWNSCALE];
+                cell[0] += r;
+                cell[1] += g;
+                cell[2] += b;
+            }
+        }
+    }
+    let block = (GLOW_DOWNSCALE * GLOW_DOWNSCALE) as f32;
+    grid.iter_mut().flatten().for_each(|c| *c /= block);
+
+    for _ in 0..2 {
+        box_blur(&mut grid, gw, gh);
+    }
+
+    for y in 0..h {
+        // Sample at block centers so the glow stays centered on its source
+        let gy = ((y as f32 + 0.5) / GLOW_DOWNSCALE as f32 - 0.5).clamp(0.0, (gh - 1) as f32);
+        let (y0, fy) = (gy as usize, gy.fract());
+        let y1 = (y0 + 1).min(gh - 1);
+        for x in 0..w {
+            let gx = ((x as f32 + 0.5) / GLOW_DOWNSCALE as f32 - 0.5).clamp(0.0, (gw - 1) as f32);
+            let (x0, fx) = (gx as usize, gx.fract());
+            let x1 = (x0 + 1).min(gw - 1);
+            let i = (y * w + x) * 4;
+            let corners = [grid[y0 * gw + x0], grid[y0 * gw + x1], grid[y1 * gw + x0], grid[y1 * gw + x1]];
+            for (c, channel) in canvas.data[i..i + 3].iter_mut().enumerate() {
+                let top = corners[0][c] * (1.0 - fx) + corners[1][c] * fx;
+                let bottom = corners[2][c] * (1.0 - fx) + corners[3][c] * fx;
+                let value = (top * (1.0 - fy) + bottom * fy) * GLOW_GAIN;
+                *channel = (*channel as f32 + value).min(255.0) as u8;
+            }
+            // Keep the pixel pre-multiplied so glow shows over transparent areas
+            canvas.data[i + 3] = canvas.data[i + 3].max(canvas.data[i]).max(canvas.data[i + 1]).max(canvas.data[i + 2]);
+        }
+    }
+}
+
+/// Separable box blur of radius `GLOW_RADIUS` over a `w`×`h` grid.
+fn box_blur(grid: &mut [[f32; 3]], w: usize, h: usize) {
+    let mut temp = vec![[0.0f32; 3]; grid.len()];
+    let taps = (2 * GLOW_RADIUS + 1) as f32;
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0.0f32; 3];
+            for k in x.saturating_sub(GLOW_RADIUS)..(x + GLOW_RADIUS + 1).min(w) {
+                let cell = grid[y * w + k];
+                (0..3).for_each(|c| sum[c] += cell[c]);
+            }
+            temp[y * w + x] = sum.map(|s| s / taps);
+        }
+    }
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0.0f32; 3];
+            for k in y.saturating_sub(GLOW_RADIUS)..(y + GLOW_RADIUS + 1).min(h) {
+                let cell = temp[k * w + x];
+                (0..3).for_each(|c| sum[c] += cell[c]);
+            }
+            grid[y * w + x] = sum.map(|s| s / taps);
+        }
+    }
+}
+
+/// Dim every other row, CRT style.
+fn scanlines(canvas: &mut Canvas) {
+    let row_bytes = canvas.width * 4;
+    for y in (1..canvas.height).step_by(2) {
+        for byte in &mut canvas.data[y * row_bytes..(y + 1) * row_bytes] {
+            *byte = (*byte as f32 * SCANLINE_LEVEL) as u8;
+        }
+    }
+}
+
+/// Shift red left and blue right, fringing edges like a cheap lens.
+fn chromatic_aberration(canvas: &mut Canvas) {
+    let (w, h) = (canvas.width, canvas.height);
+    let shift = ((w as f32 * ABERRATION_SHIFT).round() as usize).max(1);
+    if shift >= w {
+        return;
+    }
+    let mut row = vec![0u8; w * 4];
+    for y in 0..h {
+        let start = y * w * 4;
+        row.copy_from_slice(&canvas.data[start..start + w * 4]);
+        for x in 0..w {
+            let i = start + x * 4;
+            let red = if x + shift < w { row[(x + shift) * 4] } else { 0 };
+            let blue = if x >= shift { row[(x - shift) * 4 + 2] } else { 0 };
+            canvas.data[i] = red;
+            canvas.data[i + 2] = blue;
+            // Keep the pixel pre-multiplied: alpha must cover every channel
+            canvas.data[i + 3] = canvas.data[i + 3].max(red).max(blue);
+        }
+    }
+}
+
+/// Darken toward the corners.
+fn vignette(canvas: &mut Canvas) {
+    let (w, h) = (canvas.width, canvas.height);
+    let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+    for y in 0..h {
+        let dy = (y as f32 + 0.5 - cy) / cy;
+        for x in 0..w {
+            let dx = (x as f32 + 0.5 - cx) / cx;
+            // 0 at the center, 1 at the corners
+            let distance = (dx * dx + dy * dy) / 2.0;
+            let factor = 1.0 - VIGNETTE_STRENGTH * distance;
+            let i = (y * w + x) * 4;
+            for c in 0..4 {
+                canvas.data[i + c] = (canvas.data[i + c] as f32 * factor) as u8;
+            }
+        }
+    }
+}
+
+/// Brighten every pixel towards white by `amount` (0.0-1.0), keeping its
+/// alpha so transparent parts stay transparent.
+pub fn flash(canvas: &mut Canvas, amount: f32) {
+    if amount <= 0.0 {
+        return;
+    }
+    let amount = amount.min(1.0);
+    for pixel in canvas.data.chunks_exact_mut(4) {
+        // Premultiplied white is the alpha value in every channel
+        let alpha = pixel[3] as f32;
+        for c in &mut pixel[..3] {
+            *c = (*c as f32 + (alpha - *c as f32) * amount) as u8;
+        }
+    }
+}
+
+/// Fade the canvas to transparent towards its edges, over `top`, `right`,
+/// `bottom` and `left` pixels (0 = sharp edge), with a smoothstep ramp.
+pub fn fade_edges(canvas: &mut Canvas, (top, right, bottom, left): (f32, f32, f32, f32)) {
+    let (w, h) = (canvas.width, canvas.height);
+    if w == 0 || h == 0 || top.max(right).max(bottom).max(left) <= 0.0 {
+        return;
+    }
+    // Opacity at `distance` pixels from an edge faded over `width`
+    let ramp = |distance: usize, width: f32| {
+        if width <= 0.0 {
+            return 1.0;
+        }
+        let t = ((distance as f32 + 0.5) / width).min(1.0);
+        t * t * (3.0 - 2.0 * t)
+    };
+    let columns: Vec<f32> = (0..w).map(|x| ramp(x, left) * ramp(w - 1 - x, right)).collect();
+    // Columns outside the side fades keep their opacity
+    let (left_end, right_start) = ((left.ceil() as usize).min(w), w.saturating_sub(right.ceil() as usize));
+
+    for y in 0..h {
+        let row = ramp(y, top) * ramp(h - 1 - y, bottom);
+        let scale_pixel = |data: &mut [u8], x: usize, factor: f32| {
+            let i = (y * w + x) * 4;
+            for c in 0..4 {
+                data[i + c] = (data[i + c] as f32 * factor) as u8;
+            }
+        };
+        if row < 1.0 {
+            for (x, &column) in columns.iter().enumerate() {
+                scale_pixel(&mut canvas.data, x, row * column);
+            }
+        } else {
+            for x in (0..left_end).chain(right_start.max(left_end)..w) {
+                scale_pixel(&mut canvas.data, x, columns[x]);
+            }
+        }
+    }
+}
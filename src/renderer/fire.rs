@@ -0,0 +1,122 @@
+//! Heat simulation for the Fire style.
+//!
+//! The classic demoscene fire: a low-resolution grid of heat values whose
+//! bottom row is fed by the spectrum, one band per column. Every step each
+//! cell takes the average of the cells below it minus a little random
+//! cooling, so heat rises, spreads and flickers into flames. Like the
+//! particles, each surface owns a `FireState` that is advanced once per frame
+//! and handed to the renderer through `RenderParams`, which scales the grid
+//! up to the bar area.
+
+use crate::config::FireConfig;
+use super::custom::hash;
+
+/// Grid size in cells (16:9; scaled to the bar area when drawn)
+pub const COLUMNS: usize = 128;
+pub const ROWS: usize = 72;
+/// Simulation steps per second, independent of the frame rate
+const STEPS_PER_SECOND: f32 = 60.0;
+/// Most steps run in one update; after a stall the fire carries on from where it was
+const MAX_STEPS: usize = 8;
+
+#[derive(Default)]
+pub struct FireState {
+    /// Heat 0.0-1.0 per cell, row-major with row 0 at the bottom
+    heat: Vec<f32>,
+    /// Heat fed into the bottom row, per column
+    source: Vec<f32>,
+    /// Simulation steps owed to elapsed time
+    pending_steps: f32,
+    last_time: Option<f32>,
+    seed: u32,
+}
+
+impl FireState {
+    /// Advance the fire to `time`, feeding it the current spectrum.
+    pub fn update(&mut self, frequencies: &[f32], time: f32, config: &FireConfig) {
+        if self.heat.is_empty() {
+            self.heat = vec![0.0; COLUMNS * ROWS];
+        }
+        let dt = self.last_time.map_or(0.0, |last| (time - last).clamp(0.0, 0.1));
+        self.last_time = Some(time);
+
+        // Bands laid out across the columns like the bars
+        self.source.clear();
+        self.source.extend((0..COLUMNS).map(|col| match frequencies.len() {
+            0 => 0.0,
+            n => frequencies[col * n / COLUMNS].clamp(0.0, 1.0),
+        }));
+
+        self.pending_steps += dt * STEPS_PER_SECOND;
+        let steps = (self.pending_steps as usize).min(MAX_STEPS);
+        self.pending_steps = if steps == MAX_STEPS { 0.0 } else { self.pending_steps.fract() };
+        for _ in 0..steps {
+            self.step(config);
+        }
+    }
+
+    fn step(&mut self, config: &FireConfig) {
+        let flicker = config.flicker.clamp(0.0, 1.0);
+        for col in 0..COLUMNS {
+            self.heat[col] = self.source[col] * (1.0 - flicker * 0.5 * self.random());
+        }
+
+        // A full-heat column cools off after about `height` of the rows
+        let cooling = 1.0 / (ROWS as f32 * config.height.clamp(0.05, 1.0));
+        // Top down, so every row still reads the previous step's rows below it
+        for row in (1..ROWS).rev() {
+            let below = (row - 1) * COLUMNS;
+            let further = row.saturating_sub(2) * COLUMNS;
+            for col in 0..COLUMNS {
+                // Drift sideways now and then, so the flames lick instead of rising straight
+                let drift = self.random();
+                let src = if drift < flicker * 0.2 {
+                    col.saturating_sub(1)
+                } else if drift > 1.0 - flicker * 0.2 {
+                    (col + 1).min(COLUMNS - 1)
+                } else {
+                    col
+                };
+                let left = self.heat[below + src.saturating_sub(1)];
+                let right = self.heat[below + (src + 1).min(COLUMNS - 1)];
+                let average = (left + self.heat[below + src] + right + self.heat[further + src]) / 4.0;
+                let cool = cooling * (1.0 + flicker * (self.random() - 0.5));
+                self.heat[row * COLUMNS + col] = (average - cool).max(0.0);
+            }
+        }
+    }
+
+    /// Whether there is anything to draw yet
+    pub fn is_empty(&self) -> bool {
+        self.heat.is_empty()
+    }
+
+    /// Heat of cell (`col`, `row`), row 0 at the bottom
+    #[inline]
+    pub fn heat(&self, col: usize, row: usize) -> f32 {
+        self.heat.get(row * COLUMNS + col).copied().unwrap_or(0.0)
+    }
+
+    /// Heat interpolated at `x` (0.0-1.0 left to right) and `y` (0.0-1.0 bottom to top)
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let (col, tx) = grid_position(x, COLUMNS);
+        let (row, ty) = grid_position(y, ROWS);
+        let (col1, row1) = ((col + 1).min(COLUMNS - 1), (row + 1).min(ROWS - 1));
+        let bottom = self.heat(col, row) * (1.0 - tx) + self.heat(col1, row) * tx;
+        let top = self.heat(col, row1) * (1.0 - tx) + self.heat(col1, row1) * tx;
+        bottom * (1.0 - ty) + top * ty
+    }
+
+    /// Next pseudo-random value in 0.0-1.0
+    fn random(&mut self) -> f32 {
+        self.seed = self.seed.wrapping_add(1);
+        (hash(self.seed) & 0xFFFF) as f32 / 65535.0
+    }
+}
+
+/// Cell index and fraction towards the next cell at `pos` (0.0-1.0) along
+/// an axis of `cells` cells, sampling cell centers
+pub fn grid_position(pos: f32, cells: usize) -> (usize, f32) {
+    let cell = (pos * cells as f32 - 0.5).clamp(0.0, (cells - 1) as f32);
+    (cell as usize, cell.fract())
+}
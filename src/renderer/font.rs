@@ -0,0 +1,521 @@
+//! 8×8 bitmap font.
+//!
+//! Covers ASCII, Latin-1 and Latin Extended-A, Cyrillic and Greek capitals,
+//! and common typographic punctuation. Accented letters are composed from a
+//! base letter and a diacritic; letters that share a shape with a Latin one
+//! (Cyrillic А, Greek Ο, ...) reuse its glyph. Lowercase letters without a
+//! glyph of their own (Cyrillic, Greek) are drawn as capitals.
+//!
+//! Characters without a glyph (CJK, emoji, ...) are drawn as a hollow box so
+//! missing text is visible instead of silently blank.
+
+use crate::config::TextCase;
+
+/// One glyph: 8 rows, most significant bit = leftmost pixel. Capitals and
+/// ascenders sit on rows 0-6, lowercase letters on rows 2-6; row 7 holds
+/// descenders and marks below the baseline.
+pub type Glyph = [u8; 8];
+
+/// Drawn for printable characters the font doesn't cover
+const MISSING: Glyph = [0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x00];
+
+const BLANK: Glyph = [0x00; 8];
+
+/// Glyph for `ch`, or `None` for control characters (nothing is drawn).
+pub fn glyph(ch: char) -> Option<Glyph> {
+    if ch.is_whitespace() {
+        return Some(BLANK);
+    }
+    if ch.is_control() {
+        return None;
+    }
+    let lookup = |ch| base_glyph(ch).or_else(|| composed_glyph(ch));
+    Some(
+        lookup(ch)
+            .or_else(|| lookup(TextCase::Upper.apply(ch)))
+            .unwrap_or(MISSING),
+    )
+}
+
+/// Glyph drawn directly, without composing a diacritic.
+fn base_glyph(ch: char) -> Option<Glyph> {
+    ascii_glyph(ch)
+        .or_else(|| extended_glyph(ch))
+        .or_else(|| lookalike(ch).and_then(base_glyph))
+}
+
+fn composed_glyph(ch: char) -> Option<Glyph> {
+    let &(_, base, mark) = DECOMPOSITIONS.iter().find(|(c, _, _)| *c == ch)?;
+    Some(compose(base_glyph(base)?, mark))
+}
+
+fn ascii_glyph(ch: char) -> Option<Glyph> {
+    Some(match ch {
+        'A' => [0x18, 0x24, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x00],
+        'B' => [0x7C, 0x42, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x00],
+        'C' => [0x3C, 0x42, 0x40, 0x40, 0x40, 0x42, 0x3C, 0x00],
+        'D' => [0x78, 0x44, 0x42, 0x42, 0x42, 0x44, 0x78, 0x00],
+        'E' => [0x7E, 0x40, 0x7C, 0x40, 0x40, 0x40, 0x7E, 0x00],
+        'F' => [0x7E, 0x40, 0x7C, 0x40, 0x40, 0x40, 0x40, 0x00],
+        'G' => [0x3C, 0x42, 0x40, 0x4E, 0x42, 0x42, 0x3C, 0x00],
+        'H' => [0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x00],
+        'I' => [0x3E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x3E, 0x00],
+        'J' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x44, 0x38, 0x00],
+        'K' => [0x42, 0x44, 0x78, 0x48, 0x44, 0x42, 0x42, 0x00],
+        'L' => [0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7E, 0x00],
+        'M' => [0x42, 0x66, 0x5A, 0x42, 0x42, 0x42, 0x42, 0x00],
+        'N' => [0x42, 0x62, 0x52, 0x4A, 0x46, 0x42, 0x42, 0x00],
+        'O' => [0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00],
+        'P' => [0x7C, 0x42, 0x42, 0x7C, 0x40, 0x40, 0x40, 0x00],
+        'Q' => [0x3C, 0x42, 0x42, 0x42, 0x4A, 0x44, 0x3A, 0x00],
+        'R' => [0x7C, 0x42, 0x42, 0x7C, 0x48, 0x44, 0x42, 0x00],
+        'S' => [0x3C, 0x42, 0x30, 0x0C, 0x02, 0x42, 0x3C, 0x00],
+        'T' => [0x7F, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00],
+        'U' => [0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00],
+        'V' => [0x42, 0x42, 0x42, 0x42, 0x24, 0x24, 0x18, 0x00],
+        'W' => [0x42, 0x42, 0x42, 0x5A, 0x5A, 0x66, 0x42, 0x00],
+        'X' => [0x42, 0x24, 0x18, 0x18, 0x24, 0x42, 0x42, 0x00],
+        'Y' => [0x41, 0x22, 0x14, 0x08, 0x08, 0x08, 0x08, 0x00],
+        'Z' => [0x7E, 0x04, 0x08, 0x10, 0x20, 0x40, 0x7E, 0x00],
+        '0' => [0x3C, 0x42, 0x46, 0x5A, 0x62, 0x42, 0x3C, 0x00],
+        '1' => [0x08, 0x18, 0x28, 0x08, 0x08, 0x08, 0x3E, 0x00],
+        '2' => [0x3C, 0x42, 0x02, 0x0C, 0x30, 0x40, 0x7E, 0x00],
+        '3' => [0x3C, 0x42, 0x02, 0x1C, 0x02, 0x42, 0x3C, 0x00],
+        '4' => [0x04, 0x0C, 0x14, 0x24, 0x7E, 0x04, 0x04, 0x00],
+        '5' => [0x7E, 0x40, 0x7C, 0x02, 0x02, 0x42, 0x3C, 0x00],
+        '6' => [0x1C, 0x20, 0x40, 0x7C, 0x42, 0x42, 0x3C, 0x00],
+        '7' => [0x7E, 0x02, 0x04, 0x08, 0x10, 0x10, 0x10, 0x00],
+        '8' => [0x3C, 0x42, 0x42, 0x3C, 0x42, 0x42, 0x3C, 0x00],
+        '9' => [0x3C, 0x42, 0x42, 0x3E, 0x02, 0x04, 0x38, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x08, 0x10],
+        '!' => [0x08, 0x08, 0x08, 0x08, 0x08, 0x00, 0x08, 0x00],
+        '?' => [0x3C, 0x42, 0x02, 0x0C, 0x10, 0x00, 0x10, 0x00],
+        ':' => [0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00],
+        ';' => [0x00, 0x18, 0x18, 0x00, 0x18, 0x08, 0x10, 0x00],
+        '\'' => [0x08, 0x08, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '"' => [0x24, 0x24, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '`' => [0x10, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '(' => [0x04, 0x08, 0x10, 0x10, 0x10, 0x08, 0x04, 0x00],
+        ')' => [0x20, 0x10, 0x08, 0x08, 0x08, 0x10, 0x20, 0x00],
+        '[' => [0x1C, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1C, 0x00],
+        ']' => [0x38, 0x08, 0x08, 0x08, 0x08, 0x08, 0x38, 0x00],
+        '{' => [0x0C, 0x10, 0x10, 0x20, 0x10, 0x10, 0x0C, 0x00],
+        '}' => [0x30, 0x08, 0x08, 0x04, 0x08, 0x08, 0x30, 0x00],
+        '<' => [0x04, 0x08, 0x10, 0x20, 0x10, 0x08, 0x04, 0x00],
+        '>' => [0x20, 0x10, 0x08, 0x04, 0x08, 0x10, 0x20, 0x00],
+        '&' => [0x30, 0x48, 0x30, 0x50, 0x4A, 0x44, 0x3A, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E],
+        '/' => [0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x00, 0x00],
+        '\\' => [0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x00, 0x00],
+        '|' => [0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00],
+        '#' => [0x24, 0x24, 0x7E, 0x24, 0x7E, 0x24, 0x24, 0x00],
+        '$' => [0x08, 0x3E, 0x40, 0x3C, 0x02, 0x7C, 0x08, 0x00],
+        '%' => [0x00, 0x62, 0x64, 0x08, 0x10, 0x26, 0x46, 0x00],
+        '+' => [0x00, 0x08, 0x08, 0x3E, 0x08, 0x08, 0x00, 0x00],
+        '=' => [0x00, 0x00, 0x7E, 0x00, 0x7E, 0x00, 0x00, 0x00],
+        '*' => [0x00, 0x2A, 0x1C, 0x3E, 0x1C, 0x2A, 0x00, 0x00],
+        '@' => [0x3C, 0x42, 0x4E, 0x52, 0x4E, 0x40, 0x3C, 0x00],
+        '^' => [0x08, 0x14, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '~' => [0x00, 0x00, 0x32, 0x4C, 0x00, 0x00, 0x00, 0x00],
+        'a' => [0x00, 0x00, 0x3C, 0x02, 0x3E, 0x42, 0x3E, 0x00],
+        'b' => [0x40, 0x40, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x00],
+        'c' => [0x00, 0x00, 0x3C, 0x40, 0x40, 0x40, 0x3C, 0x00],
+        'd' => [0x02, 0x02, 0x3E, 0x42, 0x42, 0x42, 0x3E, 0x00],
+        'e' => [0x00, 0x00, 0x3C, 0x42, 0x7E, 0x40, 0x3C, 0x00],
+        'f' => [0x0C, 0x10, 0x3C, 0x10, 0x10, 0x10, 0x10, 0x00],
+        'g' => [0x00, 0x00, 0x3E, 0x42, 0x42, 0x3E, 0x02, 0x3C],
+        'h' => [0x40, 0x40, 0x7C, 0x42, 0x42, 0x42, 0x42, 0x00],
+        'i' => [0x08, 0x00, 0x18, 0x08, 0x08, 0x08, 0x1C, 0x00],
+        'j' => [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x44, 0x38],
+        'k' => [0x40, 0x40, 0x44, 0x48, 0x70, 0x48, 0x44, 0x00],
+        'l' => [0x18, 0x08, 0x08, 0x08, 0x08, 0x08, 0x1C, 0x00],
+        'm' => [0x00, 0x00, 0x76, 0x49, 0x49, 0x49, 0x49, 0x00],
+        'n' => [0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x42, 0x00],
+        'o' => [0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x3C, 0x00],
+        'p' => [0x00, 0x00, 0x7C, 0x42, 0x42, 0x7C, 0x40, 0x40],
+        'q' => [0x00, 0x00, 0x3E, 0x42, 0x42, 0x3E, 0x02, 0x02],
+        'r' => [0x00, 0x00, 0x5C, 0x62, 0x40, 0x40, 0x40, 0x00],
+        's' => [0x00, 0x00, 0x3E, 0x40, 0x3C, 0x02, 0x7C, 0x00],
+        't' => [0x10, 0x10, 0x7C, 0x10, 0x10, 0x10, 0x0C, 0x00],
+        'u' => [0x00, 0x00, 0x42, 0x42, 0x42, 0x46, 0x3A, 0x00],
+        'v' => [0x00, 0x00, 0x42, 0x42, 0x24, 0x24, 0x18, 0x00],
+        'w' => [0x00, 0x00, 0x41, 0x49, 0x49, 0x49, 0x36, 0x00],
+        'x' => [0x00, 0x00, 0x42, 0x24, 0x18, 0x24, 0x42, 0x00],
+        'y' => [0x00, 0x00, 0x42, 0x42, 0x42, 0x3E, 0x02, 0x3C],
+        'z' => [0x00, 0x00, 0x7E, 0x04, 0x18, 0x20, 0x7E, 0x00],
+        _ => return None,
+    })
+}
+
+/// Glyphs beyond ASCII that aren't a Latin shape plus a diacritic.
+fn extended_glyph(ch: char) -> Option<Glyph> {
+    Some(match ch {
+        // Latin
+        'Æ' => [0x3F, 0x48, 0x48, 0x7E, 0x48, 0x48, 0x4F, 0x00],
+        'Œ' => [0x37, 0x48, 0x48, 0x4E, 0x48, 0x48, 0x37, 0x00],
+        'Ø' => [0x3D, 0x46, 0x4A, 0x5A, 0x52, 0x62, 0xBC, 0x00],
+        'Ð' | 'Đ' => [0x78, 0x44, 0x42, 0xF2, 0x42, 0x44, 0x78, 0x00],
+        'Þ' => [0x40, 0x7C, 0x42, 0x42, 0x7C, 0x40, 0x40, 0x00],
+        'ß' => [0x3C, 0x42, 0x44, 0x5C, 0x42, 0x42, 0x5C, 0x40],
+        'Ł' => [0x40, 0x40, 0x50, 0x60, 0xC0, 0x40, 0x7E, 0x00],
+        'Ħ' => [0x42, 0xFF, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x00],
+        'æ' => [0x00, 0x00, 0x76, 0x09, 0x7F, 0x48, 0x37, 0x00],
+        'œ' => [0x00, 0x00, 0x36, 0x49, 0x4F, 0x48, 0x37, 0x00],
+        'ø' => [0x00, 0x00, 0x3D, 0x46, 0x5A, 0x62, 0xBC, 0x00],
+        'đ' => [0x02, 0x0F, 0x3E, 0x42, 0x42, 0x42, 0x3E, 0x00],
+        'þ' => [0x40, 0x40, 0x7C, 0x42, 0x42, 0x7C, 0x40, 0x40],
+        'ł' => [0x18, 0x08, 0x0C, 0x18, 0x08, 0x08, 0x1C, 0x00],
+        'ħ' => [0x40, 0xF0, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x00],
+        'ı' => [0x00, 0x00, 0x18, 0x08, 0x08, 0x08, 0x1C, 0x00],
+        'ȷ' => [0x00, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x44, 0x38],
+        // Cyrillic
+        'Б' => [0x7E, 0x40, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x00],
+        'Г' => [0x7E, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x00],
+        'Ґ' => [0x02, 0x7E, 0x40, 0x40, 0x40, 0x40, 0x40, 0x00],
+        'Д' => [0x1C, 0x24, 0x24, 0x24, 0x24, 0x7E, 0x42, 0x00],
+        'Є' => [0x3C, 0x42, 0x40, 0x78, 0x40, 0x42, 0x3C, 0x00],
+        'Ж' => [0x49, 0x49, 0x2A, 0x1C, 0x2A, 0x49, 0x49, 0x00],
+        'И' => [0x42, 0x46, 0x4A, 0x52, 0x62, 0x42, 0x42, 0x00],
+        'Л' => [0x1E, 0x22, 0x22, 0x22, 0x22, 0x22, 0x62, 0x00],
+        'П' => [0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x00],
+        'У' => [0x42, 0x42, 0x42, 0x3E, 0x02, 0x02, 0x3C, 0x00],
+        'Ф' => [0x08, 0x3E, 0x49, 0x49, 0x49, 0x3E, 0x08, 0x00],
+        'Ц' => [0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x7E, 0x02],
+        'Ч' => [0x42, 0x42, 0x42, 0x3E, 0x02, 0x02, 0x02, 0x00],
+        'Ш' => [0x49, 0x49, 0x49, 0x49, 0x49, 0x49, 0x7F, 0x00],
+        'Щ' => [0x49, 0x49, 0x49, 0x49, 0x49, 0x49, 0x7F, 0x01],
+        'Ъ' => [0x60, 0x20, 0x3C, 0x22, 0x22, 0x22, 0x3C, 0x00],
+        'Ы' => [0x41, 0x41, 0x79, 0x45, 0x45, 0x45, 0x79, 0x00],
+        'Ь' => [0x40, 0x40, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x00],
+        'Э' => [0x3C, 0x42, 0x02, 0x1E, 0x02, 0x42, 0x3C, 0x00],
+        'Ю' => [0x4C, 0x52, 0x52, 0x72, 0x52, 0x52, 0x4C, 0x00],
+        'Я' => [0x3E, 0x42, 0x42, 0x3E, 0x12, 0x22, 0x42, 0x00],
+        // Greek
+        'Δ' => [0x08, 0x14, 0x14, 0x22, 0x22, 0x41, 0x7F, 0x00],
+        'Θ' => [0x3C, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x3C, 0x00],
+        'Λ' => [0x08, 0x14, 0x14, 0x22, 0x22, 0x41, 0x41, 0x00],
+        'Ξ' => [0x7E, 0x00, 0x00, 0x3C, 0x00, 0x00, 0x7E, 0x00],
+        'Σ' => [0x7E, 0x40, 0x20, 0x10, 0x20, 0x40, 0x7E, 0x00],
+        'Ψ' => [0x49, 0x49, 0x49, 0x3E, 0x08, 0x08, 0x08, 0x00],
+        'Ω' => [0x3C, 0x42, 0x42, 0x42, 0x24, 0x24, 0x66, 0x00],
+        // Punctuation and symbols
+        '…' => [0x00, 0x00, 0x00, 0x00, 0x00, 0xDB, 0xDB, 0x00],
+        '·' | '•' => [0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00],
+        '°' => [0x18, 0x24, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '×' => [0x00, 0x42, 0x24, 0x18, 0x24, 0x42, 0x00, 0x00],
+        '¡' => [0x08, 0x00, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00],
+        '¿' => [0x08, 0x00, 0x08, 0x30, 0x40, 0x42, 0x3C, 0x00],
+        '€' => [0x1C, 0x22, 0x78, 0x20, 0x78, 0x22, 0x1C, 0x00],
+        '£' => [0x1C, 0x22, 0x20, 0x78, 0x20, 0x20, 0x7E, 0x00],
+        '♪' => [0x0C, 0x0A, 0x08, 0x08, 0x38, 0x78, 0x30, 0x00],
+        _ => return None,
+    })
+}
+
+/// Characters drawn with another character's glyph.
+fn lookalike(ch: char) -> Option<char> {
+    Some(match ch {
+        // Cyrillic
+        'А' => 'A',
+        'В' => 'B',
+        'Е' => 'E',
+        'З' => '3',
+        'І' => 'I',
+        'Ј' => 'J',
+        'К' => 'K',
+        'М' => 'M',
+        'Н' => 'H',
+        'О' => 'O',
+        'Р' => 'P',
+        'С' => 'C',
+        'Ѕ' => 'S',
+        'Т' => 'T',
+        'Х' => 'X',
+        // Greek
+        'Α' | 'Ά' => 'A',
+        'Β' => 'B',
+        'Γ' => 'Г',
+        'Ε' | 'Έ' => 'E',
+        'Ζ' => 'Z',
+        'Η' | 'Ή' => 'H',
+        'Ι' | 'Ί' => 'I',
+        'Κ' => 'K',
+        'Μ' => 'M',
+        'Ν' => 'N',
+        'Ο' | 'Ό' => 'O',
+        'Π' => 'П',
+        'Ρ' => 'P',
+        'Τ' => 'T',
+        'Υ' | 'Ύ' => 'Y',
+        'Φ' => 'Ф',
+        'Χ' => 'X',
+        'Ώ' => 'Ω',
+        // Typographic punctuation
+        '‘' | '’' | '‚' | '′' => '\'',
+        '“' | '”' | '„' | '″' => '"',
+        '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => '-',
+        '«' | '‹' => '<',
+        '»' | '›' => '>',
+        '¦' => '|',
+        '♫' => '♪',
+        'ẞ' => 'ß',
+        _ => return None,
+    })
+}
+
+/// Diacritic composed onto a base glyph
+#[derive(Clone, Copy)]
+enum Mark {
+    Acute,
+    Grave,
+    Circumflex,
+    Caron,
+    Diaeresis,
+    Tilde,
+    Ring,
+    Macron,
+    Breve,
+    DoubleAcute,
+    Dot,
+    /// Cedilla or comma below
+    Cedilla,
+    Ogonek,
+}
+
+impl Mark {
+    /// Rows drawn above the letter (rows 0-1), or `None` for marks below the baseline
+    fn above(self) -> Option<[u8; 2]> {
+        Some(match self {
+            Mark::Acute => [0x04, 0x08],
+            Mark::Grave => [0x10, 0x08],
+            Mark::Circumflex => [0x08, 0x14],
+            Mark::Caron => [0x14, 0x08],
+            Mark::Diaeresis => [0x24, 0x00],
+            Mark::Tilde => [0x32, 0x4C],
+            Mark::Ring => [0x18, 0x24],
+            Mark::Macron => [0x3C, 0x00],
+            Mark::Breve => [0x24, 0x18],
+            Mark::DoubleAcute => [0x12, 0x24],
+            Mark::Dot => [0x18, 0x00],
+            Mark::Cedilla | Mark::Ogonek => return None,
+        })
+    }
+}
+
+/// Accented letters as (character, base letter, mark)
+const DECOMPOSITIONS: &[(char, char, Mark)] = &[
+    ('À', 'A', Mark::Grave),
+    ('Á', 'A', Mark::Acute),
+    ('Â', 'A', Mark::Circumflex),
+    ('Ã', 'A', Mark::Tilde),
+    ('Ä', 'A', Mark::Diaeresis),
+    ('Å', 'A', Mark::Ring),
+    ('Ā', 'A', Mark::Macron),
+    ('Ă', 'A', Mark::Breve),
+    ('Ą', 'A', Mark::Ogonek),
+    ('Ç', 'C', Mark::Cedilla),
+    ('Ć', 'C', Mark::Acute),
+    ('Ĉ', 'C', Mark::Circumflex),
+    ('Ċ', 'C', Mark::Dot),
+    ('Č', 'C', Mark::Caron),
+    ('Ď', 'D', Mark::Caron),
+    ('È', 'E', Mark::Grave),
+    ('É', 'E', Mark::Acute),
+    ('Ê', 'E', Mark::Circumflex),
+    ('Ë', 'E', Mark::Diaeresis),
+    ('Ē', 'E', Mark::Macron),
+    ('Ĕ', 'E', Mark::Breve),
+    ('Ė', 'E', Mark::Dot),
+    ('Ę', 'E', Mark::Ogonek),
+    ('Ě', 'E', Mark::Caron),
+    ('Ĝ', 'G', Mark::Circumflex),
+    ('Ğ', 'G', Mark::Breve),
+    ('Ġ', 'G', Mark::Dot),
+    ('Ģ', 'G', Mark::Cedilla),
+    ('Ĥ', 'H', Mark::Circumflex),
+    ('Ì', 'I', Mark::Grave),
+    ('Í', 'I', Mark::Acute),
+    ('Î', 'I', Mark::Circumflex),
+    ('Ï', 'I', Mark::Diaeresis),
+    ('Ĩ', 'I', Mark::Tilde),
+    ('Ī', 'I', Mark::Macron),
+    ('Ĭ', 'I', Mark::Breve),
+    ('Į', 'I', Mark::Ogonek),
+    ('İ', 'I', Mark::Dot),
+    ('Ĵ', 'J', Mark::Circumflex),
+    ('Ķ', 'K', Mark::Cedilla),
+    ('Ĺ', 'L', Mark::Acute),
+    ('Ļ', 'L', Mark::Cedilla),
+    ('Ľ', 'L', Mark::Caron),
+    ('Ñ', 'N', Mark::Tilde),
+    ('Ń', 'N', Mark::Acute),
+    ('Ņ', 'N', Mark::Cedilla),
+    ('Ň', 'N', Mark::Caron),
+    ('Ò', 'O', Mark::Grave),
+    ('Ó', 'O', Mark::Acute),
+    ('Ô', 'O', Mark::Circumflex),
+    ('Õ', 'O', Mark::Tilde),
+    ('Ö', 'O', Mark::Diaeresis),
+    ('Ō', 'O', Mark::Macron),
+    ('Ŏ', 'O', Mark::Breve),
+    ('Ő', 'O', Mark::DoubleAcute),
+    ('Ŕ', 'R', Mark::Acute),
+    ('Ŗ', 'R', Mark::Cedilla),
+    ('Ř', 'R', Mark::Caron),
+    ('Ś', 'S', Mark::Acute),
+    ('Ŝ', 'S', Mark::Circumflex),
+    ('Ş', 'S', Mark::Cedilla),
+    ('Ș', 'S', Mark::Cedilla),
+    ('Š', 'S', Mark::Caron),
+    ('Ţ', 'T', Mark::Cedilla),
+    ('Ț', 'T', Mark::Cedilla),
+    ('Ť', 'T', Mark::Caron),
+    ('Ù', 'U', Mark::Grave),
+    ('Ú', 'U', Mark::Acute),
+    ('Û', 'U', Mark::Circumflex),
+    ('Ü', 'U', Mark::Diaeresis),
+    ('Ũ', 'U', Mark::Tilde),
+    ('Ū', 'U', Mark::Macron),
+    ('Ŭ', 'U', Mark::Breve),
+    ('Ů', 'U', Mark::Ring),
+    ('Ű', 'U', Mark::DoubleAcute),
+    ('Ų', 'U', Mark::Ogonek),
+    ('Ŵ', 'W', Mark::Circumflex),
+    ('Ý', 'Y', Mark::Acute),
+    ('Ŷ', 'Y', Mark::Circumflex),
+    ('Ÿ', 'Y', Mark::Diaeresis),
+    ('Ź', 'Z', Mark::Acute),
+    ('Ż', 'Z', Mark::Dot),
+    ('Ž', 'Z', Mark::Caron),
+    ('à', 'a', Mark::Grave),
+    ('á', 'a', Mark::Acute),
+    ('â', 'a', Mark::Circumflex),
+    ('ã', 'a', Mark::Tilde),
+    ('ä', 'a', Mark::Diaeresis),
+    ('å', 'a', Mark::Ring),
+    ('ā', 'a', Mark::Macron),
+    ('ă', 'a', Mark::Breve),
+    ('ą', 'a', Mark::Ogonek),
+    ('ç', 'c', Mark::Cedilla),
+    ('ć', 'c', Mark::Acute),
+    ('ĉ', 'c', Mark::Circumflex),
+    ('ċ', 'c', Mark::Dot),
+    ('č', 'c', Mark::Caron),
+    ('ď', 'd', Mark::Caron),
+    ('è', 'e', Mark::Grave),
+    ('é', 'e', Mark::Acute),
+    ('ê', 'e', Mark::Circumflex),
+    ('ë', 'e', Mark::Diaeresis),
+    ('ē', 'e', Mark::Macron),
+    ('ĕ', 'e', Mark::Breve),
+    ('ė', 'e', Mark::Dot),
+    ('ę', 'e', Mark::Ogonek),
+    ('ě', 'e', Mark::Caron),
+    ('ĝ', 'g', Mark::Circumflex),
+    ('ğ', 'g', Mark::Breve),
+    ('ġ', 'g', Mark::Dot),
+    ('ĥ', 'h', Mark::Circumflex),
+    ('ì', 'ı', Mark::Grave),
+    ('í', 'ı', Mark::Acute),
+    ('î', 'ı', Mark::Circumflex),
+    ('ï', 'ı', Mark::Diaeresis),
+    ('ĩ', 'ı', Mark::Tilde),
+    ('ī', 'ı', Mark::Macron),
+    ('ĭ', 'ı', Mark::Breve),
+    ('į', 'i', Mark::Ogonek),
+    ('ĵ', 'ȷ', Mark::Circumflex),
+    ('ķ', 'k', Mark::Cedilla),
+    ('ĺ', 'l', Mark::Acute),
+    ('ļ', 'l', Mark::Cedilla),
+    ('ľ', 'l', Mark::Caron),
+    ('ñ', 'n', Mark::Tilde),
+    ('ń', 'n', Mark::Acute),
+    ('ņ', 'n', Mark::Cedilla),
+    ('ň', 'n', Mark::Caron),
+    ('ò', 'o', Mark::Grave),
+    ('ó', 'o', Mark::Acute),
+    ('ô', 'o', Mark::Circumflex),
+    ('õ', 'o', Mark::Tilde),
+    ('ö', 'o', Mark::Diaeresis),
+    ('ō', 'o', Mark::Macron),
+    ('ŏ', 'o', Mark::Breve),
+    ('ő', 'o', Mark::DoubleAcute),
+    ('ŕ', 'r', Mark::Acute),
+    ('ŗ', 'r', Mark::Cedilla),
+    ('ř', 'r', Mark::Caron),
+    ('ś', 's', Mark::Acute),
+    ('ŝ', 's', Mark::Circumflex),
+    ('ş', 's', Mark::Cedilla),
+    ('ș', 's', Mark::Cedilla),
+    ('š', 's', Mark::Caron),
+    ('ţ', 't', Mark::Cedilla),
+    ('ț', 't', Mark::Cedilla),
+    ('ť', 't', Mark::Caron),
+    ('ù', 'u', Mark::Grave),
+    ('ú', 'u', Mark::Acute),
+    ('û', 'u', Mark::Circumflex),
+    ('ü', 'u', Mark::Diaeresis),
+    ('ũ', 'u', Mark::Tilde),
+    ('ū', 'u', Mark::Macron),
+    ('ŭ', 'u', Mark::Breve),
+    ('ů', 'u', Mark::Ring),
+    ('ű', 'u', Mark::DoubleAcute),
+    ('ų', 'u', Mark::Ogonek),
+    ('ŵ', 'w', Mark::Circumflex),
+    ('ý', 'y', Mark::Acute),
+    ('ŷ', 'y', Mark::Circumflex),
+    ('ÿ', 'y', Mark::Diaeresis),
+    ('ź', 'z', Mark::Acute),
+    ('ż', 'z', Mark::Dot),
+    ('ž', 'z', Mark::Caron),
+    // Cyrillic and Greek
+    ('Ё', 'Е', Mark::Diaeresis),
+    ('Ї', 'І', Mark::Diaeresis),
+    ('Й', 'И', Mark::Breve),
+    ('Ў', 'У', Mark::Breve),
+    ('Ϊ', 'Ι', Mark::Diaeresis),
+    ('Ϋ', 'Υ', Mark::Diaeresis),
+];
+
+/// Put `mark` on `base`. Marks above go in rows 0-1: lowercase letters
+/// already leave them free, taller letters are squeezed into rows 2-6 so the
+/// baseline stays where unaccented letters have it.
+fn compose(base: Glyph, mark: Mark) -> Glyph {
+    let mut glyph = base;
+    match mark.above() {
+        Some(accent) => {
+            if base[..2] != [0, 0] {
+                let letter = squeeze(&base);
+                glyph[2..7].copy_from_slice(&letter);
+            }
+            glyph[..2].copy_from_slice(&accent);
+        }
+        None => {
+            glyph[7] |= match mark {
+                Mark::Ogonek => 0x0C,
+                _ => 0x10,
+            };
+        }
+    }
+    glyph
+}
+
+/// Shrink the 7 letter rows of `base` to 5 by repeatedly dropping the row
+/// that differs least from the one above it.
+fn squeeze(base: &Glyph) -> [u8; 5] {
+    let mut rows = [0u8; 7];
+    rows.copy_from_slice(&base[..7]);
+    let mut len = rows.len();
+    while len > 5 {
+        let i = (0..len - 1)
+            .min_by_key(|&i| (rows[i] ^ rows[i + 1]).count_ones())
+            .unwrap_or(0);
+        rows.copy_within(i + 2..len, i + 1);
+        len -= 1;
+    }
+    let mut letter = [0u8; 5];
+    letter.copy_from_slice(&rows[..5]);
+    letter
+}
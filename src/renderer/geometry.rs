@@ -0,0 +1,204 @@
+//! Bars and waveforms as triangles, for the GPU renderer (`gpu` feature).
+//!
+//! Builds the shapes `styles` rasterizes for Classic Bars, Mirrored and
+//! Oscilloscope as triangle lists in canvas pixels, so the GPU can draw them
+//! while the rest of the frame stays on the pixel renderer. Gradients are
+//! approximated by splitting bars into bands the colors are interpolated
+//! across.
+
+use crate::config::{AlbumArtPlacement, BarRounding, Orientation, OscilloscopeWideMode, Symmetry};
+use super::layout::{compute_bar_layout, BarLayout};
+use super::styles::wide_slices;
+use super::{FrameData, RenderParams};
+
+/// Color bands a full-height bar is split into
+const GRADIENT_STEPS: usize = 8;
+
+/// A bar-space point and its color
+type Corner = ((f32, f32), (u8, u8, u8));
+
+/// A triangle corner: position in canvas pixels and pre-multiplied RGBA (0-1)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Whether a frame with `params` can be drawn as triangles plus an overlay:
+/// a style with a triangle form and nothing that works on the drawn bars.
+pub fn drawable(frame: &FrameData, params: &RenderParams) -> bool {
+    let background_art = frame.album_art.is_some()
+        && params.album_art.enabled
+        && params.album_art.placement == AlbumArtPlacement::Background;
+    matches!(params.style, 0 | 1 | 5)
+        && params.background.is_none()
+        && !background_art
+        && params.effects.is_empty()
+        && frame.flash <= 0.0
+        && params.transform.is_identity()
+        && params.symmetry == Symmetry::None
+        && params.segment_height == 0
+        && params.bar_rounding == BarRounding::None
+}
+
+/// Append the bars or waveform of a `width`×`height` frame to `out`.
+pub fn build(out: &mut Vec<Vertex>, (width, height): (usize, usize), frame: &FrameData, params: &RenderParams) {
+    let Some(layout) = compute_bar_layout(width, height, frame.frequencies, params) else {
+        return;
+    };
+    // Bar space is turned for the left and right orientations
+    let bar_height = if params.orientation.is_vertical() { width } else { height };
+    let mut shapes = Shapes { out, orientation: params.orientation, bar_height: bar_height as f32, opacity: params.opacity };
+    match params.style {
+        1 => mirrored(&mut shapes, &layout, params),
+        5 => oscilloscope(&mut shapes, &layout, params, if params.orientation.is_vertical() { height } else { width }),
+        _ => classic(&mut shapes, &layout, params),
+    }
+}
+
+/// Triangle output in bar space (x along the bars, y down from the far edge)
+struct Shapes<'a> {
+    out: &'a mut Vec<Vertex>,
+    orientation: Orientation,
+    /// Bar-space height, for mapping back to the canvas
+    bar_height: f32,
+    opacity: f32,
+}
+
+impl Shapes<'_> {
+    /// Canvas position of bar-space point (x, y), as `BarCanvas` maps pixels
+    fn map(&self, (x, y): (f32, f32)) -> [f32; 2] {
+        match self.orientation {
+            Orientation::Bottom => [x, y],
+            Orientation::Top => [x, self.bar_height - y],
+            Orientation::Left => [self.bar_height - y, x],
+            Orientation::Right => [y, x],
+        }
+    }
+
+    fn vertex(&self, point: (f32, f32), (r, g, b): (u8, u8, u8)) -> Vertex {
+        let channel = |c: u8| c as f32 / 255.0 * self.opacity;
+        Vertex { position: self.map(point), color: [channel(r), channel(g), channel(b), self.opacity] }
+    }
+
+    /// Quadrilateral `corners` (in order around it), each with its color
+    fn quad(&mut self, corners: [Corner; 4]) {
+        let [a, b, c, d] = corners.map(|(point, color)| self.vertex(point, color));
+        self.out.extend_from_slice(&[a, b, c, a, c, d]);
+    }
+
+    /// Bar `x0..x1` over rows `top..bottom`, in bands colored by `color(row)`
+    fn bar(&mut self, (x0, x1): (f32, f32), (top, bottom): (f32, f32), step: f32, color: impl Fn(f32) -> (u8, u8, u8)) {
+        let mut y = bottom;
+        while y > top {
+            let next = (y - step).max(top);
+            self.quad([((x0, next), color(next)), ((x1, next), color(next)), ((x1, y), color(y)), ((x0, y), color(y))]);
+            y = next;
+        }
+    }
+}
+
+fn classic(shapes: &mut Shapes, layout: &BarLayout, params: &RenderParams) {
+    let base = (layout.bars_y_start + layout.bars_height) as f32;
+    let extent = layout.bars_height as f32;
+    let step = (extent / GRADIENT_STEPS as f32).max(1.0);
+    for i in 0..layout.displayable {
+        let magnitude = layout.render_frequencies[i];
+        let bar_height = ((magnitude * layout.bars_height as f32) as usize)
+            .max(layout.min_bar_height)
+            .min(layout.bars_height);
+        if bar_height == 0 {
+            continue;
+        }
+        let x = layout.bar_x(i) as f32;
+        let position = i as f32 / layout.displayable as f32;
+        let color = |y: f32| params.color_scheme.get_color(position, (base - y) / extent);
+        shapes.bar((x, x + params.bar_width as f32), (base - bar_height as f32, base), step, color);
+    }
+}
+
+fn mirrored(shapes: &mut Shapes, layout: &BarLayout, params: &RenderParams) {
+    let center = (layout.bars_y_start + layout.bars_height / 2) as f32;
+    let extent = (layout.bars_height / 2).max(1) as f32;
+    let step = (extent / GRADIENT_STEPS as f32).max(1.0);
+    let min_half = layout.min_bar_height.div_ceil(2);
+    for i in 0..layout.displayable {
+        let magnitude = layout.render_frequencies[i];
+        let half_height = ((magnitude * layout.bars_height as f32 / 2.0) as usize)
+            .max(min_half)
+            .min(layout.bars_height / 2);
+        if half_height == 0 {
+            continue;
+        }
+        let x = layout.bar_x(i) as f32;
+        let position = i as f32 / layout.displayable as f32;
+        let color = |y: f32| params.color_scheme.get_color(position, (y - center).abs() / extent);
+        // Upper half, then the lower one: the center row belongs to both
+        let x_range = (x, x + params.bar_width as f32);
+        shapes.bar(x_range, (center + 1.0 - half_height as f32, center + 1.0), step, color);
+        shapes.bar(x_range, (center + 1.0, center + half_height as f32), step, color);
+    }
+}
+
+fn oscilloscope(shapes: &mut Shapes, layout: &BarLayout, params: &RenderParams, width: usize) {
+    let waveform = params.waveform;
+    if waveform.is_empty() {
+        return;
+    }
+    let slices = wide_slices(width, layout);
+    match params.oscilloscope.wide_mode {
+        OscilloscopeWideMode::Repeat if slices > 1 => {
+            for slice in 0..slices {
+                waveform_line(shapes, layout, params, waveform, (slice * width / slices, (slice + 1) * width / slices));
+            }
+        }
+        OscilloscopeWideMode::Zoom if slices > 1 => {
+            let len = waveform.len();
+            let window = (len / slices).max(2).min(len);
+            let start = (len - window) / 2;
+            waveform_line(shapes, layout, params, &waveform[start..start + window], (0, width));
+        }
+        _ => waveform_line(shapes, layout, params, waveform, (0, width)),
+    }
+}
+
+/// `samples` as a connected line across columns `x_start..x_end`, one quad
+/// per segment, extended by half the thickness to close the joints
+fn waveform_line(shapes: &mut Shapes, layout: &BarLayout, params: &RenderParams, samples: &[f32], (x_start, x_end): (usize, usize)) {
+    let span = x_end.saturating_sub(x_start);
+    if samples.is_empty() || span == 0 {
+        return;
+    }
+    let center = layout.bars_y_start as f32 + layout.bars_height as f32 / 2.0;
+    let half_height = layout.bars_height as f32 / 2.0;
+    let (top, bottom) = (layout.bars_y_start as f32, (layout.bars_y_start + layout.bars_height) as f32);
+    let half_thickness = (params.bar_width / 4).max(1) as f32 / 2.0;
+
+    let points = samples.len().min(span);
+    let point = |k: usize| {
+        let sample = samples[k * samples.len() / points];
+        let x = x_start as f32 + (k as f32 + 0.5) * span as f32 / points as f32;
+        let y = (center - sample * half_height).clamp(top, bottom);
+        let color = params.color_scheme.get_color(k as f32 / points as f32, sample.abs().clamp(0.3, 1.0));
+        ((x, y), color)
+    };
+    for k in 1..points {
+        let ((ax, ay), a_color) = point(k - 1);
+        let ((bx, by), b_color) = point(k);
+        let length = (bx - ax).hypot(by - ay);
+        if length <= 0.0 {
+            continue;
+        }
+        // Along and across the segment, half a thickness long
+        let (dx, dy) = ((bx - ax) / length * half_thickness, (by - ay) / length * half_thickness);
+        let (nx, ny) = (-dy, dx);
+        let (ax, ay, bx, by) = (ax - dx, ay - dy, bx + dx, by + dy);
+        shapes.quad([
+            ((ax + nx, ay + ny), a_color),
+            ((bx + nx, by + ny), b_color),
+            ((bx - nx, by - ny), b_color),
+            ((ax - nx, ay - ny), a_color),
+        ]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Golden-image regression tests for the built-in styles.
+//!
+//! Every style is rendered at fixed sizes from canned spectrum and waveform
+//! data, and a hash of each frame is compared with `tests/golden/styles.txt`.
+//! A frame that differs is written to `target/golden/` for inspection. When
+//! a change to the output is intended, regenerate the references with
+//! `CAVIBE_BLESS=1 cargo test golden` and commit them.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::audio::Bands;
+use crate::config::{Config, SourceIndicatorConfig};
+use super::fire::FireState;
+use super::particles::ParticleSystem;
+use super::sparks::SparkState;
+use super::styles::{self, STYLE_NAMES};
+use super::vectorscope::VectorscopeState;
+use super::vu::VuState;
+use super::{transform, Canvas, FrameData, RenderParams};
+
+/// Canvas sizes each style is checked at, and whether the track text is on:
+/// a wallpaper-like frame, and a terminal-like one without text as terminal
+/// mode draws it (the text alone would leave no room for the bars)
+const SIZES: [(usize, usize, bool); 2] = [(320, 180, true), (96, 48, false)];
+const BARS: usize = 32;
+/// Frames rendered before the checked one, so history-based styles
+/// (spectrogram, seismograph, particles, fire) have something to show
+const WARMUP_FRAMES: usize = 40;
+/// Seconds between frames, as with ~46 ms capture blocks
+const FRAME_TIME: f32 = 2048.0 / 44100.0;
+/// Bar sizes are in the same units as wallpaper mode, which scales them by 8
+const PIXEL_SCALE: usize = 8;
+
+/// Canned input for frame `n`: bars with a moving hump, mirrored like the
+/// analyzer's, and a two-tone waveform
+fn canned_audio(n: usize) -> (Vec<f32>, Vec<f32>) {
+    let t = n as f32 * 0.2;
+    let half: Vec<f32> = (0..BARS / 2)
+        .map(|i| {
+            let x = i as f32 / (BARS / 2) as f32;
+            (0.55 + 0.35 * (x * 7.0 - t).sin() * (1.0 - x * 0.5)).clamp(0.0, 1.0)
+        })
+        .collect();
+    let frequencies = half.iter().rev().chain(half.iter()).copied().collect();
+    let waveform = (0..512)
+        .map(|i| {
+            let x = i as f32 / 512.0 * std::f32::consts::TAU;
+            0.6 * (x * 3.0 + t).sin() + 0.25 * (x * 17.0 - t * 2.0).sin()
+        })
+        .collect();
+    (frequencies, waveform)
+}
+
+/// FNV-1a, stable across Rust versions unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+fn frame_hash(canvas: &Canvas) -> u64 {
+    fnv1a(&canvas.data[..canvas.width * canvas.height * 4])
+}
+
+/// Render the checked frame of `style` at `width`×`height`.
+fn render_style(config: &Config, style: usize, (width, height, text): (usize, usize, bool)) -> Canvas {
+    let visualizer = &config.visualizer;
+    let mut canvas = Canvas::new(width, height);
+    let mut spectrogram_history = Vec::new();
+    let mut amplitude_history = Vec::new();
+    let mut particles = ParticleSystem::default();
+    let mut fire = FireState::default();
+    let mut vectorscope = VectorscopeState::default();
+    let mut vu = VuState::default();
+    let mut sparks = SparkState::default();
+    let title = Some("Golden Hour".to_string());
+    let artist = Some("The References".to_string());
+    let mut text_config = config.text.clone();
+    text_config.show_title &= text;
+    text_config.show_artist &= text;
+    let source_indicator = SourceIndicatorConfig { enabled: false, ..config.source_indicator.clone() };
+    let segments = &visualizer.segments;
+
+    for n in 0..=WARMUP_FRAMES {
+        let (frequencies, waveform) = canned_audio(n);
+        let stereo: Vec<(f32, f32)> = waveform.iter().map(|&s| (s, s * 0.8)).collect();
+        let time = n as f32 * FRAME_TIME;
+        styles::push_spectrogram_row(&mut spectrogram_history, &frequencies, &visualizer.spectrogram, height);
+        styles::push_amplitude(&mut amplitude_history, &waveform, time, &visualizer.seismograph);
+        particles.update(&frequencies, time, &visualizer.particles);
+        fire.update(&frequencies, time, &visualizer.fire);
+        vectorscope.update(&stereo, &waveform, time, &visualizer.vectorscope);
+        vu.update(&stereo, &waveform, time, &visualizer.vu);
+        sparks.update(&frequencies, time, &config.effects.sparks);
+
+        let params = RenderParams {
+            style,
+            bar_width: visualizer.bar_width as usize * PIXEL_SCALE,
+            bar_spacing: visualizer.bar_spacing as usize * PIXEL_SCALE,
+            mirror: visualizer.mirror,
+            reverse_mirror: visualizer.reverse_mirror,
+            interpolation: config.audio.interpolation,
+            group_size: visualizer.group.size,
+            group_gap: visualizer.group.gap as usize * PIXEL_SCALE,
+            segment_height: if segments.enabled { segments.height as usize * PIXEL_SCALE } else { 0 },
+            segment_gap: segments.gap as usize * PIXEL_SCALE,
+            segment_zones: &segments.zones,
+            orientation: visualizer.orientation,
+            opacity: visualizer.opacity,
+            min_bar_height: visualizer.min_bar_height,
+            color_scheme: &visualizer.color_scheme,
+            waveform: &waveform,
+            spectrogram_history: &spectrogram_history,
+            spectrogram: &visualizer.spectrogram,
+            amplitude_history: &amplitude_history,
+            seismograph: &visualizer.seismograph,
+            karaoke: &visualizer.karaoke,
+            radial: &visualizer.radial,
+            oscilloscope: &visualizer.oscilloscope,
+            particles: &particles,
+            fire: &fire,
+            vectorscope: &vectorscope,
+            vu: &vu,
+            sparks: &sparks,
+            text_config: &text_config,
+            album_art: &config.album_art,
+            source_indicator: &source_indicator,
+            widgets: &config.widgets,
+            effects: &config.renderer.effects,
+            transform: transform::Transform::from_config(visualizer),
+            bar_rounding: visualizer.bar_rounding,
+            symmetry: visualizer.symmetry,
+            background: None,
+            font: None,
+        };
+        let frame = FrameData {
+            frequencies: &frequencies,
+            intensity: frequencies.iter().sum::<f32>() / frequencies.len() as f32,
+            bands: Bands::from_bars(&frequencies),
+            track_title: &title,
+            track_artist: &artist,
+            album_art: None,
+            source: None,
+            input_level: 0.7,
+            time,
+            text_opacity: 1.0,
+            flash: 0.0,
+        };
+        canvas.clear();
+        super::render_frame(&mut canvas, &frame, &params);
+    }
+    canvas
+}
+
+/// Every style at every size, with its reference line
+/// (`<style>\t<width>x<height>\t<hash>`)
+fn render_all() -> Vec<(String, Canvas)> {
+    let mut config = Config::default();
+    config.visualizer.bars = BARS;
+    let mut frames = Vec::new();
+    for (style, name) in STYLE_NAMES.iter().enumerate() {
+        for size in SIZES {
+            let canvas = render_style(&config, style, size);
+            let line = format!("{}\t{}x{}\t{:016x}", name, size.0, size.1, frame_hash(&canvas));
+            frames.push((line, canvas));
+        }
+    }
+    frames
+}
+
+fn manifest_path(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+#[test]
+fn styles_match_golden_hashes() {
+    let frames = render_all();
+    // A style that draws nothing would match its reference without checking anything
+    let blank: Vec<&str> = frames
+        .iter()
+        .filter(|(_, canvas)| frame_hash(canvas) == frame_hash(&Canvas::new(canvas.width, canvas.height)))
+        .map(|(line, _)| line.rsplit_once('\t').map_or(line.as_str(), |(key, _)| key))
+        .collect();
+    assert!(blank.is_empty(), "styles rendered a blank frame: {}", blank.join(", ").replace('\t', " "));
+
+    let reference_path = manifest_path("tests/golden/styles.txt");
+    let actual: String = frames.iter().map(|(line, _)| format!("{}\n", line)).collect();
+
+    if std::env::var_os("CAVIBE_BLESS").is_some_and(|v| v == "1") {
+        std::fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+        std::fs::write(&reference_path, &actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&reference_path).unwrap_or_else(|e| {
+        panic!(
+            "can't read {}: {}\nGenerate it with CAVIBE_BLESS=1 cargo test golden and commit it.",
+            reference_path.display(),
+            e
+        )
+    });
+
+    let expected: Vec<&str> = expected.lines().collect();
+    let out_dir = manifest_path("target/golden");
+    let mut report = String::new();
+    for (line, canvas) in &frames {
+        if expected.contains(&line.as_str()) {
+            continue;
+        }
+        let key = line.rsplit_once('\t').map_or(line.as_str(), |(key, _)| key);
+        let file = out_dir.join(format!("{}.png", key.replace(['\t', ' '], "-")));
+        let saved = canvas.save_png(&file).map_or_else(|e| format!("not saved: {:#}", e), |()| file.display().to_string());
+        let _ = writeln!(report, "{} changed ({})", key.replace('\t', " "), saved);
+    }
+    assert!(
+        report.is_empty(),
+        "rendered output differs from tests/golden/styles.txt:\n{}\nIf the change is intended, rerun with CAVIBE_BLESS=1 and commit the references.",
+        report
+    );
+}
@@ -0,0 +1,103 @@
+//! What the wallpaper shows once the audio goes quiet (`idle_mode`).
+//!
+//! The render loop asks [`IdleFrame::new`] what to draw each iteration. Demo
+//! mode feeds the normal renderer a synthetic signal; clock mode replaces the
+//! visualizer with the time in the bitmap font.
+
+use crate::config::IdleMode;
+use super::text::render_char;
+use super::{background, Canvas, RenderParams};
+
+/// Clock digit height as a fraction of the surface height
+const CLOCK_HEIGHT_RATIO: usize = 6;
+
+/// What to draw this frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdleFrame {
+    /// Audio is playing: render normally
+    Live,
+    /// Render normally with opacity scaled by this factor
+    Fade(f32),
+    /// An empty (transparent) surface
+    Blank,
+    /// Render the current style from a synthetic signal
+    Demo,
+    /// Render the clock instead of the visualizer
+    Clock,
+    /// Leave the last frame on screen
+    Hold,
+}
+
+impl IdleFrame {
+    /// `idle_secs` is how long the audio has been quiet, or None while it plays.
+    pub fn new(mode: IdleMode, fade_secs: f32, idle_secs: Option<f32>) -> Self {
+        let Some(idle_secs) = idle_secs else {
+            return Self::Live;
+        };
+        match mode {
+            IdleMode::Freeze => Self::Hold,
+            IdleMode::Clear => Self::Blank,
+            IdleMode::Fadeout if idle_secs < fade_secs => Self::Fade(1.0 - idle_secs / fade_secs.max(0.001)),
+            IdleMode::Fadeout => Self::Blank,
+            IdleMode::Demo => Self::Demo,
+            IdleMode::Clock => Self::Clock,
+        }
+    }
+
+    /// Whether the frame changes every iteration (so the loop should run at
+    /// the active poll rate)
+    pub fn animated(self) -> bool {
+        matches!(self, Self::Live | Self::Fade(_) | Self::Demo)
+    }
+}
+
+/// A slowly drifting spectrum for demo mode: two travelling waves under a
+/// breathing envelope, kept well below full scale.
+pub fn demo_frequencies(bars: usize, time: f32) -> Vec<f32> {
+    let breath = 0.75 + 0.25 * (time * 0.4).sin();
+    (0..bars)
+        .map(|i| {
+            let p = i as f32 / bars.max(1) as f32;
+            let wave = 0.5 + 0.3 * (p * 6.0 + time * 1.3).sin() + 0.2 * (p * 17.0 - time * 2.1).sin();
+            (wave * breath * 0.6).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// A matching waveform for the oscilloscope-style renderers.
+pub fn demo_waveform(samples: usize, time: f32) -> Vec<f32> {
+    (0..samples)
+        .map(|i| {
+            let t = i as f32 / samples.max(1) as f32 * std::f32::consts::TAU;
+            0.4 * (t * 3.0 + time * 2.0).sin() + 0.15 * (t * 11.0 - time * 3.0).sin()
+        })
+        .collect()
+}
+
+/// Current local time as shown by the clock (HH:MM).
+pub fn clock_text() -> String {
+    chrono::Local::now().format("%H:%M").to_string()
+}
+
+/// Draw `text` centered on the background (or a clear canvas), colored
+/// across the current scheme.
+pub fn render_clock(canvas: &mut Canvas, text: &str, params: &RenderParams) {
+    match params.background {
+        Some((image, config)) => background::render(canvas, image, config),
+        None => canvas.clear(),
+    }
+
+    let scale = (canvas.height / CLOCK_HEIGHT_RATIO / 8).max(1);
+    let advance = 9 * scale;
+    let count = text.chars().count();
+    let width = (count * advance).saturating_sub(scale);
+    if width > canvas.width || 8 * scale > canvas.height {
+        return;
+    }
+    let x0 = (canvas.width - width) / 2;
+    let y0 = (canvas.height - 8 * scale) / 2;
+    for (i, ch) in text.chars().enumerate() {
+        let (r, g, b) = params.color_scheme.get_color(i as f32 / count.max(1) as f32, 1.0);
+        render_char(canvas, x0 + i * advance, y0, ch, r, g, b, scale, params.opacity);
+    }
+}
@@ -0,0 +1,86 @@
+//! Audio source indicator: the active source name over a live input level meter.
+
+use crate::color::ColorScheme;
+use crate::config::{OverlayCorner, SourceIndicatorConfig, TextCase};
+use super::text::render_char;
+use super::Canvas;
+
+/// Inset of the indicator from the canvas edges
+const MARGIN: usize = 16;
+/// Longest source name shown before truncating
+const MAX_NAME_CHARS: usize = 40;
+/// Minimum meter width in (unscaled) pixels
+const MIN_METER_WIDTH: usize = 64;
+
+/// Draw the indicator in the configured corner.
+///
+/// `level` is the input level (0.0-1.0) before sensitivity scaling, so a
+/// silent meter means the source itself is silent.
+pub fn render_source_indicator(
+    canvas: &mut Canvas,
+    source: &str,
+    level: f32,
+    config: &SourceIndicatorConfig,
+    color_scheme: &ColorScheme,
+    case: TextCase,
+    opacity: f32,
+) {
+    let opacity = (config.opacity * opacity).clamp(0.0, 1.0);
+    let scale = (canvas.height / 300).clamp(1, 3);
+    let advance = 9 * scale;
+
+    let label = truncate_name(source, MAX_NAME_CHARS);
+    let text_width = label.chars().count() * advance;
+    let meter_width = text_width.max(MIN_METER_WIDTH * scale);
+    let meter_height = 3 * scale;
+    let padding = 4 * scale;
+    let box_w = meter_width + padding * 2;
+    let box_h = 8 * scale + padding + meter_height + padding * 2;
+    if box_w + MARGIN * 2 > canvas.width || box_h + MARGIN * 2 > canvas.height {
+        return;
+    }
+
+    let x0 = match config.position {
+        OverlayCorner::TopLeft | OverlayCorner::BottomLeft => MARGIN,
+        OverlayCorner::TopRight | OverlayCorner::BottomRight => canvas.width - MARGIN - box_w,
+    };
+    let y0 = match config.position {
+        OverlayCorner::TopLeft | OverlayCorner::TopRight => MARGIN,
+        OverlayCorner::BottomLeft | OverlayCorner::BottomRight => canvas.height - MARGIN - box_h,
+    };
+
+    fill_rect(canvas, x0, y0, box_w, box_h, (0, 0, 0), opacity * 0.5);
+
+    let text_x = x0 + padding;
+    let text_y = y0 + padding;
+    for (i, ch) in label.chars().enumerate() {
+        render_char(canvas, text_x + i * advance, text_y, case.apply(ch), 230, 230, 230, scale, opacity);
+    }
+
+    let meter_y = text_y + 8 * scale + padding;
+    fill_rect(canvas, text_x, meter_y, meter_width, meter_height, (60, 60, 60), opacity);
+    let filled = (meter_width as f32 * level.clamp(0.0, 1.0)) as usize;
+    for dx in 0..filled {
+        let (r, g, b) = color_scheme.get_color(dx as f32 / meter_width as f32, 1.0);
+        for dy in 0..meter_height {
+            canvas.put_pixel(text_x + dx, meter_y + dy, r, g, b, opacity);
+        }
+    }
+}
+
+/// Shorten a source name to at most `max_chars`, ending in "..." when cut.
+pub fn truncate_name(source: &str, max_chars: usize) -> String {
+    if source.chars().count() <= max_chars {
+        return source.to_string();
+    }
+    let head: String = source.chars().take(max_chars.saturating_sub(3)).collect();
+    format!("{}...", head)
+}
+
+fn fill_rect(canvas: &mut Canvas, x: usize, y: usize, w: usize, h: usize, color: (u8, u8, u8), opacity: f32) {
+    for py in y..(y + h).min(canvas.height) {
+        for px in x..(x + w).min(canvas.width) {
+            canvas.put_pixel(px, py, color.0, color.1, color.2, opacity);
+        }
+    }
+}
@@ -1,18 +1,140 @@
 //! Bar layout computation shared by all styles.
+//!
+//! Styles draw in "bar space": x runs along the frequency axis and bars grow
+//! up from the bottom edge. [`BarCanvas`] maps bar space onto the real canvas
+//! for the configured orientation, so every style can hug any screen edge.
 
-use crate::config::TextPosition;
-use super::RenderParams;
+use std::cell::RefCell;
+use std::ops::Range;
+
+use crate::config::{BlendMode, Interpolation, Orientation, TextPosition};
+use super::{Canvas, RenderParams};
+
+thread_local! {
+    /// Offscreen target for [`BarCanvas::composite_layer`], reused across frames
+    static LAYER_SCRATCH: RefCell<Canvas> = RefCell::new(Canvas::new(0, 0));
+}
+
+/// A canvas viewed in bar space for a given orientation.
+///
+/// `width` and `height` are the bar-space dimensions (swapped relative to the
+/// canvas for `left`/`right`). For side orientations the frequency axis runs
+/// top to bottom.
+pub struct BarCanvas<'a> {
+    canvas: &'a mut Canvas,
+    orientation: Orientation,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl<'a> BarCanvas<'a> {
+    pub fn new(canvas: &'a mut Canvas, orientation: Orientation) -> Self {
+        let (width, height) = if orientation.is_vertical() {
+            (canvas.height, canvas.width)
+        } else {
+            (canvas.width, canvas.height)
+        };
+        Self { canvas, orientation, width, height }
+    }
+
+    /// Canvas coordinates of a bar-space pixel, or None if it is off the canvas.
+    #[inline]
+    fn map(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(match self.orientation {
+            Orientation::Bottom => (x, y),
+            Orientation::Top => (x, self.height - 1 - y),
+            Orientation::Left => (self.height - 1 - y, x),
+            Orientation::Right => (y, x),
+        })
+    }
+
+    /// Composite a bar-space pixel over the existing contents, ignoring
+    /// coordinates outside the canvas.
+    #[inline]
+    pub fn put_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8, opacity: f32) {
+        if let Some((cx, cy)) = self.map(x, y) {
+            self.canvas.put_pixel(cx, cy, r, g, b, opacity);
+        }
+    }
+
+    /// Write a bar-space pixel, replacing the existing contents.
+    #[inline]
+    pub fn put_pixel_replace(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8, opacity: f32) {
+        if let Some((cx, cy)) = self.map(x, y) {
+            self.canvas.put_pixel_replace(cx, cy, r, g, b, opacity);
+        }
+    }
+
+    /// Combine a bar-space pixel with the existing contents using `blend`.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn composite_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8, opacity: f32, blend: BlendMode) {
+        if let Some((cx, cy)) = self.map(x, y) {
+            self.canvas.composite_pixel(cx, cy, r, g, b, opacity, blend);
+        }
+    }
+
+    /// Run `draw` on a blank canvas of the same size, then combine the result
+    /// with this one at `opacity` using `blend`, so a whole style can be
+    /// stacked as one layer.
+    pub fn composite_layer(&mut self, opacity: f32, blend: BlendMode, draw: impl FnOnce(&mut BarCanvas)) {
+        LAYER_SCRATCH.with(|scratch| {
+            let mut scratch = scratch.borrow_mut();
+            scratch.resize(self.canvas.width, self.canvas.height);
+            scratch.clear();
+            draw(&mut BarCanvas::new(&mut scratch, self.orientation));
+            self.canvas.composite(&scratch, opacity, blend);
+        });
+    }
+}
 
 /// Pre-computed bar layout used by every style renderer.
+#[derive(Clone)]
 pub struct BarLayout {
     pub bars_y_start: usize,
     pub bars_height: usize,
     pub start_x: usize,
     pub slot_width: usize,
+    pub group_size: usize,
+    pub group_gap: usize,
     pub displayable: usize,
+    /// Height in pixels that bars never drop below
+    pub min_bar_height: usize,
     pub render_frequencies: Vec<f32>,
+    /// Index into the input frequencies that each displayed bar shows
+    pub source_indices: Vec<usize>,
+}
+
+impl BarLayout {
+    /// Left edge of bar `i`, including any group gaps before it.
+    pub fn bar_x(&self, i: usize) -> usize {
+        self.start_x + i * self.slot_width + group_gaps_before(i, self.group_size) * self.group_gap
+    }
+}
+
+/// Number of group gaps that precede bar `i`.
+fn group_gaps_before(i: usize, group_size: usize) -> usize {
+    i.checked_div(group_size).unwrap_or(0)
+}
+
+/// Total width occupied by `count` bars, including group gaps.
+fn bars_total_width(count: usize, slot_width: usize, group_size: usize, group_gap: usize) -> usize {
+    count * slot_width + group_gaps_before(count.saturating_sub(1), group_size) * group_gap
+}
+
+/// Bands (indices into `count`) covered by slot `k` of `n`; never empty
+fn slot_bands(count: usize, k: usize, n: usize) -> Range<usize> {
+    let start = ((k * count) / n.max(1)).min(count - 1);
+    let end = (((k + 1) * count) / n.max(1)).clamp(start + 1, count);
+    start..end
 }
 
+/// Lay out bars for a `width`×`height` canvas (screen pixels). The returned
+/// layout is in bar space for `params.orientation`; text rows are kept clear
+/// of bars in every orientation.
 pub fn compute_bar_layout(
     width: usize,
     height: usize,
@@ -23,78 +145,84 @@ pub fn compute_bar_layout(
         return None;
     }
 
-    let bar_count = frequencies.len().min(width);
     let text_height = if params.text_config.show_title || params.text_config.show_artist {
-        60 + params.text_config.margin_top as usize + params.text_config.margin_bottom as usize
+        let lines_height = if params.text_config.two_line { 120 } else { 60 };
+        lines_height + params.text_config.margin_top as usize + params.text_config.margin_bottom as usize
     } else {
         0
     };
 
-    let (bars_y_start, bars_height) = match params.text_config.position {
+    // Screen rows left free by the text
+    let (free_y, free_height) = match params.text_config.position {
         TextPosition::Top => (text_height, height.saturating_sub(text_height)),
         TextPosition::Bottom => (0, height.saturating_sub(text_height)),
         TextPosition::Center | TextPosition::Coordinates { .. } => (0, height),
     };
 
-    if bars_height == 0 {
+    // Along the bars for top/bottom, across them for left/right
+    let ((axis_start, width), (bars_y_start, bars_height)) = match params.orientation {
+        Orientation::Bottom => ((0, width), (free_y, free_height)),
+        Orientation::Top => ((0, width), (height.saturating_sub(free_y + free_height), free_height)),
+        Orientation::Left | Orientation::Right => ((free_y, free_height), (0, width)),
+    };
+
+    if bars_height == 0 || width == 0 {
         return None;
     }
 
+    let bar_count = frequencies.len().min(width);
+
     let slot_width = params.bar_width + params.bar_spacing;
-    let max_bars = width / slot_width.max(1);
-    let displayable = max_bars.min(bar_count);
+    let (group_size, group_gap) = (params.group_size, params.group_gap);
+    let mut displayable = (width / slot_width.max(1)).min(bar_count);
+    while displayable > 0 && bars_total_width(displayable, slot_width, group_size, group_gap) > width {
+        displayable -= 1;
+    }
 
     if displayable == 0 {
         return None;
     }
 
-    let total_width = displayable * slot_width;
-    let start_x = (width.saturating_sub(total_width)) / 2;
+    let total_width = bars_total_width(displayable, slot_width, group_size, group_gap);
+    let start_x = axis_start + (width.saturating_sub(total_width)) / 2;
 
-    let render_frequencies: Vec<f32> = match (params.mirror, params.reverse_mirror) {
-        (true, true) => {
-            let half = displayable / 2;
-            let mut result = Vec::with_capacity(displayable);
-            for i in 0..half {
-                let freq_idx = ((half - 1 - i) * frequencies.len()) / half.max(1);
-                result.push(frequencies[freq_idx.min(frequencies.len() - 1)]);
-            }
-            for i in 0..displayable - half {
-                let freq_idx = (i * frequencies.len()) / (displayable - half).max(1);
-                result.push(frequencies[freq_idx.min(frequencies.len() - 1)]);
-            }
-            result
-        }
-        (true, false) => {
-            let half = displayable / 2;
-            let mut result = Vec::with_capacity(displayable);
-            for i in 0..half {
-                let freq_idx = (i * frequencies.len()) / half.max(1);
-                result.push(frequencies[freq_idx.min(frequencies.len() - 1)]);
-            }
-            for i in 0..displayable - half {
-                let freq_idx = ((displayable - half - 1 - i) * frequencies.len()) / (displayable - half).max(1);
-                result.push(frequencies[freq_idx.min(frequencies.len() - 1)]);
-            }
-            result
-        }
-        (false, true) => {
-            (0..displayable)
-                .map(|i| {
-                    let freq_idx = ((displayable - 1 - i) * frequencies.len()) / displayable.max(1);
-                    frequencies[freq_idx.min(frequencies.len() - 1)]
-                })
-                .collect()
-        }
-        (false, false) => {
-            (0..displayable)
-                .map(|i| {
-                    let freq_idx = (i * frequencies.len()) / displayable.max(1);
-                    frequencies[freq_idx.min(frequencies.len() - 1)]
-                })
-                .collect()
-        }
+    // Displayed bar `i` is slot `k` of `n` spread evenly over the bands
+    let half = displayable / 2;
+    let slot = |i: usize| match (params.mirror, params.reverse_mirror) {
+        (true, true) if i < half => (half - 1 - i, half),
+        (true, true) => (i - half, displayable - half),
+        (true, false) if i < half => (i, half),
+        (true, false) => (displayable - 1 - i, displayable - half),
+        (false, true) => (displayable - 1 - i, displayable),
+        (false, false) => (i, displayable),
     };
+    let bands_of = |i: usize| {
+        let (k, n) = slot(i);
+        slot_bands(frequencies.len(), k, n)
+    };
+
+    let source_indices = (0..displayable).map(|i| bands_of(i).start).collect();
+    let render_frequencies = (0..displayable)
+        .map(|i| {
+            let bands = bands_of(i);
+            match params.interpolation {
+                Interpolation::Nearest => frequencies[bands.start],
+                // Average every band the bar covers instead of skipping some
+                _ => frequencies[bands.clone()].iter().sum::<f32>() / bands.len() as f32,
+            }
+        })
+        .collect();
 
-    Some(BarLayout { bars_y_start, bars_height, start_x, slot_width, displayable, render_frequencies })
+    Some(BarLayout {
+        bars_y_start,
+        bars_height,
+        start_x,
+        slot_width,
+        group_size,
+        group_gap,
+        displayable,
+        min_bar_height: params.min_bar_height.resolve(bars_height).min(bars_height),
+        render_frequencies,
+        source_indices,
+    })
 }
@@ -4,18 +4,50 @@
 //! Output backends (Wayland layer-shell, terminal half-block) convert the
 //! canvas to their native format at submission time.
 
+pub mod art;
+pub mod background;
+pub mod custom;
+pub mod damage;
+pub mod draw;
+pub mod effects;
+pub mod fire;
+pub mod font;
+#[cfg(feature = "gpu")]
+pub mod geometry;
+#[cfg(test)]
+mod golden;
+pub mod idle;
+pub mod indicator;
 pub mod layout;
+pub mod particles;
+pub mod plugin;
+pub mod sparks;
 pub mod styles;
 pub mod text;
+pub mod transform;
+pub mod ttf;
+pub mod vectorscope;
+pub mod vu;
+pub mod widgets;
 
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::audio::Bands;
 use crate::color::ColorScheme;
-use crate::config::TextConfig;
+use crate::config::{
+    AlbumArtConfig, AlbumArtPlacement, BarRounding, BlendMode, CoordValue, Effect, Interpolation, KaraokeConfig, OscilloscopeConfig, Orientation, RadialConfig, SegmentZone, SourceIndicatorConfig,
+    SeismographConfig, SpectrogramConfig, Symmetry, TextConfig, UpscaleFilter, WallpaperConfig, WidgetsConfig,
+};
+use crate::metadata::AlbumArt;
 
 /// Owned RGBA pixel buffer.
 ///
 /// Internal format is 4 bytes per pixel in **RGBA** order.
 /// Call [`Canvas::write_argb8888`] to convert to the pre-multiplied ARGB8888
 /// format required by Wayland `wl_shm`.
+#[derive(Clone)]
 pub struct Canvas {
     pub data: Vec<u8>,
     pub width: usize,
@@ -48,10 +80,32 @@ impl Canvas {
         self.data[..len].fill(0);
     }
 
-    /// Write a pixel at (x, y) with the given color and opacity.
-    /// Stored as RGBA internally.
+    /// Composite a pixel at (x, y) over the existing contents with the given
+    /// color and opacity (source-over, pre-multiplied), so overlapping
+    /// elements layer instead of the last one drawn winning.
     #[inline]
     pub fn put_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8, opacity: f32) {
+        let idx = (y * self.width + x) * 4;
+        if idx + 3 < self.data.len() {
+            let a = opacity.clamp(0.0, 1.0);
+            // Nothing to blend with: plain write
+            if a >= 1.0 || self.data[idx + 3] == 0 && self.data[idx..idx + 3] == [0, 0, 0] {
+                self.put_pixel_replace(x, y, r, g, b, a);
+                return;
+            }
+            let inv = 1.0 - a;
+            for (c, src) in [r, g, b].into_iter().enumerate() {
+                self.data[idx + c] = (src as f32 * a + self.data[idx + c] as f32 * inv) as u8;
+            }
+            self.data[idx + 3] = (a * 255.0 + self.data[idx + 3] as f32 * inv) as u8;
+        }
+    }
+
+    /// Write a pixel at (x, y) with the given color and opacity, replacing
+    /// the existing contents. For shapes stamped with overlapping points,
+    /// where blending would darken the overlaps.
+    #[inline]
+    pub fn put_pixel_replace(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8, opacity: f32) {
         let idx = (y * self.width + x) * 4;
         if idx + 3 < self.data.len() {
             let a = (opacity * 255.0) as u8;
@@ -62,6 +116,32 @@ impl Canvas {
         }
     }
 
+    /// Combine a pixel at (x, y) with the existing contents using `blend`.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub fn composite_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8, opacity: f32, blend: BlendMode) {
+        let idx = (y * self.width + x) * 4;
+        if idx + 3 < self.data.len() {
+            let a = opacity.clamp(0.0, 1.0);
+            let src = [r as f32 * a, g as f32 * a, b as f32 * a, a * 255.0];
+            mix(&mut self.data[idx..idx + 4], src, blend);
+        }
+    }
+
+    /// Combine all of `src` (same size) with this canvas using `blend`,
+    /// scaling its pixels by `opacity`.
+    pub fn composite(&mut self, src: &Canvas, opacity: f32, blend: BlendMode) {
+        let len = (self.width * self.height * 4).min(src.width * src.height * 4);
+        let opacity = opacity.clamp(0.0, 1.0);
+        for (dst, px) in self.data[..len].chunks_exact_mut(4).zip(src.data[..len].chunks_exact(4)) {
+            if px[3] == 0 && px[..3] == [0, 0, 0] {
+                continue;
+            }
+            let src = [px[0], px[1], px[2], px[3]].map(|c| c as f32 * opacity);
+            mix(dst, src, blend);
+        }
+    }
+
     /// Read the RGBA values at (x, y). Returns (r, g, b, a) — pre-multiplied.
     #[inline]
     pub fn get_pixel(&self, x: usize, y: usize) -> (u8, u8, u8, u8) {
@@ -91,15 +171,78 @@ impl Canvas {
             dest[si + 3] = a;
         }
     }
+
+    /// Save the canvas as a PNG with straight alpha, creating the directory
+    /// it goes in if needed.
+    pub fn save_png(&self, path: &Path) -> anyhow::Result<()> {
+        let mut pixels = Vec::new();
+        self.write_straight_rgba(&mut pixels);
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        image::save_buffer_with_format(
+            path,
+            &pixels,
+            self.width as u32,
+            self.height as u32,
+            image::ColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Replace `dest` with the canvas as RGBA with straight (not
+    /// pre-multiplied) alpha, as image formats expect.
+    pub fn write_straight_rgba(&self, dest: &mut Vec<u8>) {
+        dest.clear();
+        dest.extend_from_slice(&self.data[..self.width * self.height * 4]);
+        for px in dest.chunks_exact_mut(4) {
+            let a = px[3] as u32;
+            if a > 0 && a < 255 {
+                for c in &mut px[..3] {
+                    *c = (*c as u32 * 255 / a).min(255) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Combine a pre-multiplied source pixel (0-255 per channel) into `dst`.
+#[inline]
+fn mix(dst: &mut [u8], src: [f32; 4], blend: BlendMode) {
+    let inv = 1.0 - src[3] / 255.0;
+    for (c, s) in src.into_iter().enumerate() {
+        let d = dst[c] as f32;
+        let value = match blend {
+            BlendMode::Normal => s + d * inv,
+            BlendMode::Additive if c < 3 => d + s,
+            BlendMode::Screen if c < 3 => s + d - s * d / 255.0,
+            // Alpha still covers like source-over, so additive light shows
+            // on a transparent wallpaper
+            BlendMode::Additive | BlendMode::Screen => s + d * inv,
+        };
+        dst[c] = value.min(255.0) as u8;
+    }
 }
 
 /// Per-frame data passed to the renderer.
 pub struct FrameData<'a> {
     pub frequencies: &'a [f32],
     pub intensity: f32,
+    /// Bass, mid and treble levels, for styles reacting to part of the spectrum
+    pub bands: Bands,
     pub track_title: &'a Option<String>,
     pub track_artist: &'a Option<String>,
+    pub album_art: Option<&'a AlbumArt>,
+    /// Active audio source name, for the source indicator
+    pub source: Option<&'a str>,
+    /// Input level before sensitivity scaling (0.0-1.0)
+    pub input_level: f32,
     pub time: f32,
+    /// Opacity of the track text, below 1.0 while a new title fades in
+    pub text_opacity: f32,
+    /// White flashed over the frame on a new track (0.0 = none)
+    pub flash: f32,
 }
 
 /// Parameters controlling how a frame is rendered.
@@ -109,16 +252,259 @@ pub struct RenderParams<'a> {
     pub bar_spacing: usize,
     pub mirror: bool,
     pub reverse_mirror: bool,
+    /// Whether bars covering several bands average them (`audio.interpolation`)
+    pub interpolation: Interpolation,
+    /// Bars per group (0 = no grouping)
+    pub group_size: usize,
+    /// Extra spacing inserted after each group
+    pub group_gap: usize,
+    /// LED segment height in pixels (0 = solid bars)
+    pub segment_height: usize,
+    /// Gap between LED segments in pixels
+    pub segment_gap: usize,
+    /// Fixed segment colors by height (empty = color scheme gradient)
+    pub segment_zones: &'a [SegmentZone],
+    /// Screen edge the bars grow from
+    pub orientation: Orientation,
     pub opacity: f32,
+    /// Height quiet bars never drop below (classic, mirrored and blocks)
+    pub min_bar_height: CoordValue,
+    /// Rounded bar ends (classic and mirrored)
+    pub bar_rounding: BarRounding,
     pub color_scheme: &'a ColorScheme,
     pub waveform: &'a [f32],
     pub spectrogram_history: &'a [Vec<f32>],
+    pub spectrogram: &'a SpectrogramConfig,
+    /// (time, RMS loudness) per frame for the seismograph, oldest first
+    pub amplitude_history: &'a [(f32, f32)],
+    pub seismograph: &'a SeismographConfig,
+    pub karaoke: &'a KaraokeConfig,
+    pub radial: &'a RadialConfig,
+    pub oscilloscope: &'a OscilloscopeConfig,
+    pub particles: &'a particles::ParticleSystem,
+    pub fire: &'a fire::FireState,
+    pub vectorscope: &'a vectorscope::VectorscopeState,
+    pub vu: &'a vu::VuState,
+    pub sparks: &'a sparks::SparkState,
     pub text_config: &'a TextConfig,
+    pub album_art: &'a AlbumArtConfig,
+    pub source_indicator: &'a SourceIndicatorConfig,
+    pub widgets: &'a WidgetsConfig,
+    /// Post-processing effects, applied in order
+    pub effects: &'a [Effect],
+    /// Rotation and flips of the finished frame
+    pub transform: transform::Transform,
+    /// Reflection of the drawn bars
+    pub symmetry: Symmetry,
+    /// Wallpaper background image with the config holding its fit, darken and blur
+    pub background: Option<(&'a background::Background, &'a WallpaperConfig)>,
+    /// Outline font for the track text (`None` = bitmap font)
+    pub font: Option<&'a ttf::TrueTypeFont>,
+}
+
+/// Smallest supported render scale (render at ¼ resolution)
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+/// Largest supported render scale (4× supersampling)
+pub const MAX_RENDER_SCALE: f32 = 4.0;
+
+/// Size of the internal render target for an output of `width`×`height` at `scale`.
+pub fn scaled_size(width: usize, height: usize, scale: f32) -> (usize, usize) {
+    let scale = scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+    (
+        ((width as f32 * scale).round() as usize).max(1),
+        ((height as f32 * scale).round() as usize).max(1),
+    )
+}
+
+/// Render a frame at `scale` × the output resolution into `work`, then resample
+/// into `canvas`.
+///
+/// Scales above 1.0 supersample (box-filtered downsample, smoother diagonals);
+/// scales below 1.0 render at reduced resolution and upscale with `filter`
+/// (cheaper; `Compositor` is treated as bilinear here).
+pub fn render_frame_scaled(
+    canvas: &mut Canvas,
+    work: &mut Canvas,
+    frame: &FrameData,
+    params: &RenderParams,
+    scale: f32,
+    filter: UpscaleFilter,
+) {
+    if (scale - 1.0).abs() < f32::EPSILON || canvas.width == 0 || canvas.height == 0 {
+        render_frame(canvas, frame, params);
+        return;
+    }
+
+    render_frame_at_scale(work, (canvas.width, canvas.height), frame, params, scale);
+    if work.width >= canvas.width || filter == UpscaleFilter::Nearest {
+        resample(work, canvas);
+    } else {
+        upscale_bilinear(work, canvas);
+    }
+}
+
+/// Render a frame for an output of `width`×`height` into `work` at `scale` ×
+/// its resolution, without resampling (e.g. for the compositor to scale).
+/// Pixel-sized parameters are scaled so the layout matches an unscaled render.
+pub fn render_frame_at_scale(
+    work: &mut Canvas,
+    (width, height): (usize, usize),
+    frame: &FrameData,
+    params: &RenderParams,
+    scale: f32,
+) {
+    let (work_w, work_h) = scaled_size(width, height, scale);
+    let factor_x = work_w as f32 / width.max(1) as f32;
+    let scale_px = |px: usize| ((px as f32 * factor_x).round() as usize).max(usize::from(px > 0));
+    let scaled_params = RenderParams {
+        bar_width: scale_px(params.bar_width),
+        bar_spacing: scale_px(params.bar_spacing),
+        group_gap: scale_px(params.group_gap),
+        segment_height: scale_px(params.segment_height),
+        segment_gap: scale_px(params.segment_gap),
+        min_bar_height: match params.min_bar_height {
+            CoordValue::Pixels(px) => CoordValue::Pixels(scale_px(px.max(0) as usize) as i32),
+            percent => percent,
+        },
+        ..*params
+    };
+
+    work.resize(work_w, work_h);
+    render_frame(work, frame, &scaled_params);
+}
+
+/// Bilinear upscale of `src` into the larger `dest`, with pixel centres aligned
+fn upscale_bilinear(src: &Canvas, dest: &mut Canvas) {
+    let (sw, sh) = (src.width, src.height);
+    let (dw, dh) = (dest.width, dest.height);
+    if sw == 0 || sh == 0 {
+        return;
+    }
+    // Source position of a destination pixel: index and 8-bit weight of the next one
+    let source_pos = |d: usize, src_len: usize, dest_len: usize| {
+        let pos = ((d as f32 + 0.5) * src_len as f32 / dest_len as f32 - 0.5).max(0.0);
+        let i = (pos as usize).min(src_len - 1);
+        (i, (i + 1).min(src_len - 1), ((pos - i as f32) * 256.0) as u32)
+    };
+    for dy in 0..dh {
+        let (y0, y1, fy) = source_pos(dy, sh, dh);
+        let (row0, row1) = (y0 * sw, y1 * sw);
+        for dx in 0..dw {
+            let (x0, x1, fx) = source_pos(dx, sw, dw);
+            let di = (dy * dw + dx) * 4;
+            // Pre-multiplied RGBA interpolates channel by channel
+            for c in 0..4 {
+                let at = |i: usize| src.data[i * 4 + c] as u32;
+                let top = at(row0 + x0) * (256 - fx) + at(row0 + x1) * fx;
+                let bottom = at(row1 + x0) * (256 - fx) + at(row1 + x1) * fx;
+                dest.data[di + c] = ((top * (256 - fy) + bottom * fy) >> 16) as u8;
+            }
+        }
+    }
+}
+
+/// Resample `src` into `dest`: box filter when shrinking, nearest when growing.
+fn resample(src: &Canvas, dest: &mut Canvas) {
+    let (sw, sh) = (src.width, src.height);
+    let (dw, dh) = (dest.width, dest.height);
+    for dy in 0..dh {
+        let sy0 = dy * sh / dh;
+        let sy1 = ((dy + 1) * sh / dh).max(sy0 + 1).min(sh);
+        for dx in 0..dw {
+            let sx0 = dx * sw / dw;
+            let sx1 = ((dx + 1) * sw / dw).max(sx0 + 1).min(sw);
+            // Average pre-multiplied RGBA over the source block
+            let mut sum = [0u32; 4];
+            for sy in sy0..sy1 {
+                let row = sy * sw;
+                for sx in sx0..sx1 {
+                    let si = (row + sx) * 4;
+                    for (c, total) in sum.iter_mut().enumerate() {
+                        *total += src.data[si + c] as u32;
+                    }
+                }
+            }
+            let count = ((sy1 - sy0) * (sx1 - sx0)) as u32;
+            let di = (dy * dw + dx) * 4;
+            for (c, total) in sum.iter().enumerate() {
+                dest.data[di + c] = (total / count) as u8;
+            }
+        }
+    }
 }
 
 /// Main entry point: render a complete frame to the canvas.
 pub fn render_frame(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams) {
-    canvas.clear();
-    styles::render_bars(canvas, frame.frequencies, params);
-    text::render_text(canvas, frame, params);
+    transform::render_transformed(canvas, params.transform, |canvas| render_layers(canvas, frame, params, true));
+}
+
+/// Render everything but the bars, for the GPU to draw them underneath
+/// (only for frames [`geometry::drawable`] accepts).
+#[cfg(feature = "gpu")]
+pub fn render_overlay(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams) {
+    render_layers(canvas, frame, params, false);
+}
+
+/// Draw every layer of a frame, untransformed; without `bars` only what
+/// goes on top of them.
+fn render_layers(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams, bars: bool) {
+    match params.background {
+        Some((image, config)) => background::render(canvas, image, config),
+        None => canvas.clear(),
+    }
+
+    let art = frame.album_art.filter(|_| params.album_art.enabled);
+    let placement = params.album_art.placement;
+    if let Some(art) = art.filter(|_| placement == AlbumArtPlacement::Background) {
+        art::render_background(canvas, art, params.album_art, params.opacity);
+    }
+
+    if bars {
+        transform::render_symmetric(canvas, params.symmetry, |canvas| styles::render_bars(canvas, frame, params));
+    }
+
+    // Beside-text art needs the text layout; fall back to the corner when text is hidden
+    let band = art
+        .filter(|_| placement == AlbumArtPlacement::BesideText)
+        .and_then(|_| text::text_band(canvas, frame, params));
+    let inset = band.map_or(0, |(_, height)| art::beside_text_inset(height));
+    // Karaoke already shows the track text as its visual
+    let text_x = if params.style == styles::KARAOKE_STYLE {
+        None
+    } else if frame.text_opacity < 1.0 {
+        let faded = RenderParams { opacity: params.opacity * frame.text_opacity, ..*params };
+        text::render_text(canvas, frame, &faded, inset)
+    } else {
+        text::render_text(canvas, frame, params, inset)
+    };
+
+    if let Some(art) = art {
+        match (placement, band, text_x) {
+            (AlbumArtPlacement::Background, _, _) => {}
+            (AlbumArtPlacement::BesideText, Some(band), Some(x)) => {
+                art::render_beside_text(canvas, art, params.album_art, params.opacity, x, band)
+            }
+            _ => art::render_corner(canvas, art, params.album_art, params.opacity),
+        }
+    }
+
+    if params.widgets.intensity_bar.enabled {
+        let band = text::text_band(canvas, frame, params);
+        widgets::render_intensity_bar(canvas, frame.intensity, band, params);
+    }
+
+    if let Some(source) = frame.source.filter(|_| params.source_indicator.enabled) {
+        indicator::render_source_indicator(
+            canvas,
+            source,
+            frame.input_level,
+            params.source_indicator,
+            params.color_scheme,
+            params.text_config.case,
+            params.opacity,
+        );
+    }
+
+    effects::apply(canvas, params.effects);
+    effects::flash(canvas, frame.flash);
 }
@@ -0,0 +1,119 @@
+//! Particle state for the Particles style.
+//!
+//! Bass onsets spawn bursts of particles that shoot up from the bottom edge,
+//! spread out, fall back under gravity and fade over their lifetime. Like the
+//! spectrogram history, each surface owns a `ParticleSystem` that is advanced
+//! once per frame and handed to the renderer through `RenderParams`.
+
+use crate::audio::Bands;
+use crate::config::ParticlesConfig;
+use super::custom::hash;
+
+/// Bass must exceed its running average by this factor to count as an onset
+const ONSET_RATIO: f32 = 1.35;
+/// Quietest bass level that can trigger a burst
+const ONSET_FLOOR: f32 = 0.08;
+/// Minimum time between bursts (seconds)
+const ONSET_COOLDOWN: f32 = 0.12;
+/// Hard cap on live particles, as a multiple of the burst size
+const MAX_BURSTS: usize = 8;
+
+/// One particle, in coordinates normalized to the bar area:
+/// x runs 0.0-1.0 left to right, y runs 0.0-1.0 bottom to top.
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    vx: f32,
+    vy: f32,
+    pub age: f32,
+    pub lifetime: f32,
+    /// Color scheme position
+    pub hue: f32,
+}
+
+impl Particle {
+    /// Remaining life (1.0 = just spawned, 0.0 = expired)
+    pub fn life(&self) -> f32 {
+        (1.0 - self.age / self.lifetime.max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Default)]
+pub struct ParticleSystem {
+    pub particles: Vec<Particle>,
+    bass_average: f32,
+    cooldown: f32,
+    last_time: Option<f32>,
+    seed: u32,
+}
+
+impl ParticleSystem {
+    /// Advance particles to `time`, spawning a burst if a bass onset occurred.
+    pub fn update(&mut self, frequencies: &[f32], time: f32, config: &ParticlesConfig) {
+        let dt = self.last_time.map_or(0.0, |last| (time - last).clamp(0.0, 0.1));
+        self.last_time = Some(time);
+
+        for p in &mut self.particles {
+            p.vy -= config.gravity * dt;
+            p.x += p.vx * dt;
+            p.y += p.vy * dt;
+            p.age += dt;
+        }
+        self.particles.retain(|p| p.age < p.lifetime && p.y > -0.05);
+
+        if frequencies.is_empty() {
+            return;
+        }
+        let bass = Bands::from_bars(frequencies).bass;
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        let onset = bass > ONSET_FLOOR && bass > self.bass_average * ONSET_RATIO && self.cooldown == 0.0;
+        // Slow running average so sustained bass doesn't retrigger
+        self.bass_average += (bass - self.bass_average) * (dt * 4.0).min(1.0);
+
+        if onset {
+            self.cooldown = ONSET_COOLDOWN;
+            self.spawn_burst(bass, config);
+        }
+    }
+
+    /// Spawn up to `config.count` particles from a random point on the bottom
+    /// edge, more and faster for harder hits.
+    fn spawn_burst(&mut self, strength: f32, config: &ParticlesConfig) {
+        let count = ((config.count as f32 * strength.min(1.0).sqrt()).ceil() as usize).max(1);
+        let origin = self.random();
+        let hue = self.random();
+        for _ in 0..count {
+            let angle = (self.random() - 0.5) * std::f32::consts::FRAC_PI_2;
+            let speed = (0.6 + self.random() * 0.8) * (0.5 + strength);
+            let lifetime = config.lifetime.max(0.1) * (0.6 + self.random() * 0.4);
+            let x = origin + (self.random() - 0.5) * 0.05;
+            let hue = (hue + (self.random() - 0.5) * 0.2).rem_euclid(1.0);
+            self.particles.push(Particle {
+                x,
+                y: 0.0,
+                vx: angle.sin() * speed * 0.5,
+                vy: angle.cos() * speed,
+                age: 0.0,
+                lifetime,
+                hue,
+            });
+        }
+
+        let cap = config.count.max(1) * MAX_BURSTS;
+        if self.particles.len() > cap {
+            let excess = self.particles.len() - cap;
+            self.particles.drain(..excess);
+        }
+    }
+
+    /// Next pseudo-random value in 0.0-1.0
+    fn random(&mut self) -> f32 {
+        self.seed = self.seed.wrapping_add(1);
+        (hash(self.seed) & 0xFFFF) as f32 / 65535.0
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.bass_average = 0.0;
+    }
+}
@@ -0,0 +1,213 @@
+//! Third-party styles loaded from shared libraries.
+//!
+//! Every `*.so` in `~/.local/share/cavibe/plugins/` that exports the
+//! functions below is registered as a style, numbered after the custom
+//! styles. A plugin draws a whole frame into an RGBA buffer; cavibe lays it
+//! over whatever is beneath (background, album art) at the visualizer
+//! opacity, and text and widgets are drawn on top as usual.
+//!
+//! The ABI, in C terms (see [`PluginFrame`] for the frame layout):
+//!
+//! ```c
+//! uint32_t    cavibe_plugin_abi_version(void);   /* PLUGIN_ABI_VERSION */
+//! void       *cavibe_plugin_init(void);          /* plugin state, may be NULL */
+//! const char *cavibe_plugin_name(void *state);   /* UTF-8 style name */
+//! void        cavibe_plugin_render(void *state, const CavibeFrame *frame, uint8_t *pixels);
+//! ```
+//!
+//! `pixels` holds `frame->width * frame->height` pixels, row by row, as
+//! pre-multiplied RGBA bytes, cleared to transparent before each call.
+//! Calls into one plugin never overlap, so plugins need not be thread-safe.
+//! Libraries are loaded once at startup and stay loaded until exit.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::config::{BlendMode, Config};
+use super::layout::BarLayout;
+use super::{Canvas, FrameData, RenderParams};
+
+/// Version of the plugin ABI; bumped whenever [`PluginFrame`] or a function
+/// signature changes
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Colors of the active color scheme passed to plugins, bass to treble
+pub const PALETTE_SIZE: usize = 16;
+
+/// One frame as plugins see it (`CavibeFrame` in C).
+///
+/// Levels are 0.0-1.0. `frequencies` has one level per displayed bar, bass
+/// first; `waveform` holds the latest samples (-1.0 to 1.0). Both pointers
+/// are only valid during the render call.
+#[repr(C)]
+pub struct PluginFrame {
+    pub width: u32,
+    pub height: u32,
+    /// Seconds since cavibe started
+    pub time: f32,
+    pub intensity: f32,
+    pub bass: f32,
+    pub mid: f32,
+    pub treble: f32,
+    pub frequencies: *const f32,
+    pub frequency_count: u32,
+    pub waveform: *const f32,
+    pub waveform_len: u32,
+    /// The color scheme sampled at full intensity, RGB
+    pub palette: [[u8; 3]; PALETTE_SIZE],
+}
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type InitFn = unsafe extern "C" fn() -> *mut c_void;
+type NameFn = unsafe extern "C" fn(*mut c_void) -> *const c_char;
+type RenderFn = unsafe extern "C" fn(*mut c_void, *const PluginFrame, *mut u8);
+
+/// A loaded plugin, registered as a style.
+pub struct PluginStyle {
+    pub name: String,
+    pub path: PathBuf,
+    render: RenderFn,
+    /// The state from `cavibe_plugin_init`; the lock serializes calls
+    state: Mutex<PluginState>,
+    /// Keeps the code behind `render` mapped
+    _library: libloading::Library,
+}
+
+struct PluginState(*mut c_void);
+
+// The state is only handed back to the plugin that created it, one call at a time
+unsafe impl Send for PluginState {}
+
+static PLUGINS: OnceLock<Vec<Arc<PluginStyle>>> = OnceLock::new();
+
+thread_local! {
+    /// Buffer plugins draw into before it is laid over the frame
+    static SCRATCH: RefCell<Canvas> = RefCell::new(Canvas::new(0, 0));
+}
+
+/// Load every plugin in the plugins directory, sorted by file name. Only
+/// the first call loads anything.
+///
+/// Returns a message for each library that was skipped.
+pub fn load_plugins() -> Vec<String> {
+    let mut problems = Vec::new();
+    PLUGINS.get_or_init(|| {
+        let mut plugins: Vec<Arc<PluginStyle>> = Vec::new();
+        for path in plugin_files() {
+            match load(&path) {
+                Ok(plugin) if super::styles::STYLE_NAMES.iter().any(|s| s.eq_ignore_ascii_case(&plugin.name)) => {
+                    problems.push(format!("{}: style '{}' clashes with a built-in style", path.display(), plugin.name));
+                }
+                Ok(plugin) if plugins.iter().any(|p| p.name.eq_ignore_ascii_case(&plugin.name)) => {
+                    problems.push(format!("{}: style '{}' is defined more than once", path.display(), plugin.name));
+                }
+                Ok(plugin) => plugins.push(Arc::new(plugin)),
+                Err(e) => problems.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        plugins
+    });
+    problems
+}
+
+/// Plugin styles, indexed after the custom styles.
+pub fn plugins() -> &'static [Arc<PluginStyle>] {
+    PLUGINS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The plugin providing style `name` (case-insensitive)
+pub fn find_plugin(name: &str) -> Option<&'static Arc<PluginStyle>> {
+    plugins().iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// `*.so` files in the plugins directory, sorted by file name
+fn plugin_files() -> Vec<PathBuf> {
+    let Some(dir) = Config::plugins_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "so"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn load(path: &Path) -> Result<PluginStyle, String> {
+    // SAFETY: running the library's initializers and calling its exports is
+    // what installing a plugin asks for; the signatures are the documented ABI
+    unsafe {
+        let library = libloading::Library::new(path).map_err(|e| e.to_string())?;
+        let abi_version = *library
+            .get::<AbiVersionFn>(b"cavibe_plugin_abi_version\0")
+            .map_err(|e| e.to_string())?;
+        let version = abi_version();
+        if version != PLUGIN_ABI_VERSION {
+            return Err(format!("plugin ABI version {} is not supported (cavibe uses {})", version, PLUGIN_ABI_VERSION));
+        }
+        let init = *library.get::<InitFn>(b"cavibe_plugin_init\0").map_err(|e| e.to_string())?;
+        let name = *library.get::<NameFn>(b"cavibe_plugin_name\0").map_err(|e| e.to_string())?;
+        let render = *library.get::<RenderFn>(b"cavibe_plugin_render\0").map_err(|e| e.to_string())?;
+
+        let state = init();
+        let name = name(state);
+        if name.is_null() {
+            return Err("plugin has no style name".to_string());
+        }
+        let name = CStr::from_ptr(name)
+            .to_str()
+            .map_err(|_| "style name is not UTF-8".to_string())?
+            .trim()
+            .to_string();
+        if name.is_empty() {
+            return Err("style name must not be empty".to_string());
+        }
+        Ok(PluginStyle {
+            name,
+            path: path.to_path_buf(),
+            render,
+            state: Mutex::new(PluginState(state)),
+            _library: library,
+        })
+    }
+}
+
+/// Let `plugin` draw the frame and lay it over `canvas`.
+pub fn render(canvas: &mut Canvas, layout: &BarLayout, plugin: &PluginStyle, frame: &FrameData, params: &RenderParams) {
+    let frequencies = &layout.render_frequencies[..layout.displayable.min(layout.render_frequencies.len())];
+    let palette = std::array::from_fn(|i| {
+        let (r, g, b) = params.color_scheme.get_color(i as f32 / (PALETTE_SIZE - 1) as f32, 1.0);
+        [r, g, b]
+    });
+    let plugin_frame = PluginFrame {
+        width: canvas.width as u32,
+        height: canvas.height as u32,
+        time: frame.time,
+        intensity: frame.intensity,
+        bass: frame.bands.bass,
+        mid: frame.bands.mid,
+        treble: frame.bands.treble,
+        frequencies: frequencies.as_ptr(),
+        frequency_count: frequencies.len() as u32,
+        waveform: params.waveform.as_ptr(),
+        waveform_len: params.waveform.len() as u32,
+        palette,
+    };
+    let Ok(state) = plugin.state.lock() else {
+        return;
+    };
+    SCRATCH.with(|scratch| {
+        let mut scratch = scratch.borrow_mut();
+        scratch.resize(canvas.width, canvas.height);
+        scratch.clear();
+        // SAFETY: the buffer holds width * height RGBA pixels and the frame's
+        // slices outlive the call, as the ABI promises
+        unsafe { (plugin.render)(state.0, &plugin_frame, scratch.data.as_mut_ptr()) };
+        canvas.composite(&scratch, params.opacity, BlendMode::Normal);
+    });
+}
@@ -0,0 +1,107 @@
+//! Spark highlights for bars that jump on transients.
+//!
+//! A bar whose magnitude rises by more than the threshold in one frame lights
+//! a spark at its tip that fades over `decay` seconds. Like the particle
+//! system, each surface owns a `SparkState` that is advanced once per frame
+//! and handed to the renderer through `RenderParams`.
+
+use crate::config::SparksConfig;
+use super::layout::{BarCanvas, BarLayout};
+use super::RenderParams;
+
+/// Spark height in pixels per unit of bar width
+const SPARK_HEIGHT_RATIO: usize = 2;
+
+#[derive(Default)]
+pub struct SparkState {
+    /// Current brightness per frequency bin (0.0 = none)
+    levels: Vec<f32>,
+    previous: Vec<f32>,
+    last_time: Option<f32>,
+}
+
+impl SparkState {
+    /// Light sparks for bins that jumped since the last frame and fade the rest.
+    pub fn update(&mut self, frequencies: &[f32], time: f32, config: &SparksConfig) {
+        let dt = self.last_time.map_or(0.0, |last| (time - last).clamp(0.0, 0.1));
+        self.last_time = Some(time);
+
+        if !config.enabled {
+            self.levels.clear();
+            self.previous.clear();
+            return;
+        }
+        if self.levels.len() != frequencies.len() {
+            self.levels = vec![0.0; frequencies.len()];
+            self.previous = frequencies.to_vec();
+            return;
+        }
+
+        let intensity = config.intensity.clamp(0.0, 1.0);
+        let fade = intensity * dt / config.decay.max(0.01);
+        for ((level, previous), &magnitude) in self.levels.iter_mut().zip(&mut self.previous).zip(frequencies) {
+            if magnitude - *previous > config.threshold {
+                *level = intensity;
+            } else {
+                *level = (*level - fade).max(0.0);
+            }
+            *previous = magnitude;
+        }
+    }
+
+    /// Spark brightness for frequency bin `index`
+    fn level(&self, index: usize) -> f32 {
+        self.levels.get(index).copied().unwrap_or(0.0)
+    }
+
+    pub fn clear(&mut self) {
+        self.levels.clear();
+        self.previous.clear();
+    }
+}
+
+/// Draw sparks at the tips of the laid-out bars. With `mirrored` the bars
+/// grow both ways from the center and both tips spark.
+pub fn render(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams, mirrored: bool) {
+    if params.sparks.levels.is_empty() {
+        return;
+    }
+    let spark_height = (params.bar_width * SPARK_HEIGHT_RATIO).max(1);
+    let bottom = layout.bars_y_start + layout.bars_height;
+    let center = layout.bars_y_start + layout.bars_height / 2;
+
+    for i in 0..layout.displayable {
+        let level = params.sparks.level(layout.source_indices[i]);
+        if level <= 0.0 {
+            continue;
+        }
+        let magnitude = layout.render_frequencies[i];
+        let x_start = layout.bar_x(i);
+
+        // (tip row, whether the bar extends downward from it)
+        let tips = if mirrored {
+            let half = (magnitude * layout.bars_height as f32 / 2.0) as usize;
+            [Some((center.saturating_sub(half), true)), Some(((center + half).min(bottom.saturating_sub(1)), false))]
+        } else {
+            let height = ((magnitude * layout.bars_height as f32) as usize).min(layout.bars_height);
+            [Some((bottom.saturating_sub(height), true)), None]
+        };
+
+        // Whitened bar color, brightest at the tip and fading along the bar
+        let position = i as f32 / layout.displayable as f32;
+        let (r, g, b) = params.color_scheme.get_color(position, 1.0);
+        let (r, g, b) = (r.max(220), g.max(220), b.max(220));
+        for (tip, downward) in tips.into_iter().flatten() {
+            for offset in 0..spark_height {
+                let y = if downward { tip + offset } else { tip.saturating_sub(offset) };
+                if y < layout.bars_y_start || y >= bottom {
+                    continue;
+                }
+                let fade = 1.0 - offset as f32 / spark_height as f32;
+                for bx in 0..params.bar_width {
+                    canvas.put_pixel(x_start + bx, y, r, g, b, params.opacity * level * fade);
+                }
+            }
+        }
+    }
+}
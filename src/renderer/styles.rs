@@ -1,10 +1,22 @@
-//! All 8 visualization style render functions.
+//! All 16 built-in visualization style render functions.
 //!
-//! Each function takes a `Canvas`, a `BarLayout`, and `RenderParams` and
-//! writes pixels directly into the canvas buffer.
+//! Each function takes a `BarCanvas`, a `BarLayout`, and `RenderParams` and
+//! writes pixels in bar space; the `BarCanvas` maps them onto the canvas for
+//! the configured orientation. Custom styles registered from config are
+//! numbered after the built-ins and drawn by `super::custom`, followed by
+//! plugin styles drawn by `super::plugin`.
 
-use super::layout::{compute_bar_layout, BarLayout};
-use super::{Canvas, RenderParams};
+use crate::config::{BarRounding, OscilloscopeWideMode, RadialWideMode, RgbColor, SeismographConfig, SpectrogramConfig};
+use super::custom;
+use super::draw::{self, Paint};
+use super::fire;
+use super::plugin;
+use super::sparks;
+use super::text;
+use super::vectorscope;
+use super::vu;
+use super::layout::{compute_bar_layout, BarCanvas, BarLayout};
+use super::{Canvas, FrameData, RenderParams};
 
 /// Human-readable names for each style, indexed by style number.
 pub const STYLE_NAMES: &[&str] = &[
@@ -16,44 +28,255 @@ pub const STYLE_NAMES: &[&str] = &[
     "Oscilloscope",
     "Spectrogram",
     "Radial",
+    "Particles",
+    "Ring",
+    "Skyline",
+    "Seismograph",
+    "Fire",
+    "Vectorscope",
+    "VU Meters",
+    "Karaoke",
 ];
 
-/// Total number of styles.
+/// Style number of Fire, which terminal mode can draw with shade characters
+pub const FIRE_STYLE: usize = 12;
+/// Style number of Vectorscope
+pub const VECTORSCOPE_STYLE: usize = 13;
+/// Style number of Karaoke, which draws the track text upright on the whole
+/// canvas instead of into the bar area
+pub const KARAOKE_STYLE: usize = 15;
+
+/// Whether style `idx` draws built-in style `builtin`, itself or as a
+/// layer of a custom style. State only one style reads, like the fire
+/// simulation or the vectorscope trace, needn't be stepped while nothing
+/// draws it.
+pub fn draws_builtin(idx: usize, builtin: usize) -> bool {
+    if idx == builtin {
+        return true;
+    }
+    idx.checked_sub(STYLE_NAMES.len())
+        .and_then(|i| custom::custom_styles().get(i).map(|style| style.draws_builtin(builtin)))
+        .unwrap_or(false)
+}
+
+/// Total number of styles: built-in, custom and plugin.
 pub fn style_count() -> usize {
-    STYLE_NAMES.len()
+    STYLE_NAMES.len() + custom::custom_styles().len() + plugin::plugins().len()
+}
+
+/// Names of all styles, indexed by style number.
+pub fn style_names() -> Vec<String> {
+    STYLE_NAMES
+        .iter()
+        .map(|s| s.to_string())
+        .chain(custom::custom_styles().iter().map(|c| c.name.clone()))
+        .chain(plugin::plugins().iter().map(|p| p.name.clone()))
+        .collect()
+}
+
+/// Name of style `idx` (falls back to the first style if out of range).
+pub fn style_name(idx: usize) -> String {
+    style_names().into_iter().nth(idx).unwrap_or_else(|| STYLE_NAMES[0].to_string())
+}
+
+/// Look up a style number by name (case-insensitive).
+pub fn find_style(name: &str) -> Option<usize> {
+    style_names().iter().position(|s| s.eq_ignore_ascii_case(name))
+}
+
+/// Look up a style number from user input, accepting unique abbreviations
+/// (see `crate::names`). The error lists the available styles.
+pub fn resolve_style(name: &str) -> Result<usize, String> {
+    crate::names::find(name, &style_names(), "style")
+}
+
+/// Aspect ratio the built-in styles are designed around
+const REFERENCE_ASPECT: f32 = 16.0 / 9.0;
+
+/// Number of 16:9 slices that fit across a bar area `width` pixels wide
+/// (1 on normal displays, 2 on 32:9 ultrawides)
+pub(super) fn wide_slices(width: usize, layout: &BarLayout) -> usize {
+    let aspect = width as f32 / layout.bars_height.max(1) as f32;
+    ((aspect / REFERENCE_ASPECT).round() as usize).max(1)
 }
 
 /// Dispatch to the correct style renderer.
-pub fn render_bars(canvas: &mut Canvas, frequencies: &[f32], params: &RenderParams) {
-    let layout = match compute_bar_layout(canvas.width, canvas.height, frequencies, params) {
+pub fn render_bars(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams) {
+    if params.style == KARAOKE_STYLE {
+        text::render_karaoke(canvas, frame, params);
+        return;
+    }
+    let layout = match compute_bar_layout(canvas.width, canvas.height, frame.frequencies, params) {
         Some(l) => l,
         None => return,
     };
+    if let Some(idx) = params.style.checked_sub(STYLE_NAMES.len()) {
+        let customs = custom::custom_styles();
+        if let Some(style) = customs.get(idx) {
+            custom::render(&mut BarCanvas::new(canvas, params.orientation), &layout, style, frame, params);
+        } else if let Some(style) = plugin::plugins().get(idx - customs.len()) {
+            // Plugins draw the whole canvas themselves, orientation and all
+            plugin::render(canvas, &layout, style, frame, params);
+        }
+        return;
+    }
+    render_builtin(&mut BarCanvas::new(canvas, params.orientation), &layout, params);
+}
 
+/// Draw built-in style `params.style` (plus its sparks) onto the laid-out bars.
+pub(super) fn render_builtin(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     match params.style {
-        1 => render_bars_mirrored(canvas, &layout, params),
-        2 => render_bars_wave(canvas, &layout, params),
-        3 => render_bars_dots(canvas, &layout, params),
-        4 => render_bars_blocks(canvas, &layout, params),
-        5 => render_bars_oscilloscope(canvas, &layout, params),
-        6 => render_bars_spectrogram(canvas, &layout, params),
-        7 => render_bars_radial(canvas, &layout, params),
-        _ => render_bars_classic(canvas, &layout, params),
+        1 => render_bars_mirrored(canvas, layout, params),
+        2 => render_bars_wave(canvas, layout, params),
+        3 => render_bars_dots(canvas, layout, params),
+        4 => render_bars_blocks(canvas, layout, params),
+        5 => render_bars_oscilloscope(canvas, layout, params),
+        6 => render_bars_spectrogram(canvas, layout, params),
+        7 => render_bars_radial(canvas, layout, params),
+        8 => render_bars_particles(canvas, layout, params),
+        9 => render_bars_ring(canvas, layout, params),
+        10 => render_bars_skyline(canvas, layout, params),
+        11 => render_bars_seismograph(canvas, layout, params),
+        FIRE_STYLE => render_bars_fire(canvas, layout, params),
+        13 => render_bars_vectorscope(canvas, layout, params),
+        14 => render_bars_vu(canvas, layout, params),
+        // Needs the track text, which custom style layers don't get
+        KARAOKE_STYLE => {}
+        _ => render_bars_classic(canvas, layout, params),
+    }
+
+    // Sparks only make sense for styles with bar tips
+    match params.style {
+        1 => sparks::render(canvas, layout, params, true),
+        2 | 5..=15 => {}
+        // Classic (and its fallback), dots and blocks
+        _ => sparks::render(canvas, layout, params, false),
+    }
+}
+
+/// LED segment layout for one frame, with the zone colors parsed once
+struct Segments {
+    height: usize,
+    period: usize,
+    zones: Vec<(f32, RgbColor)>,
+}
+
+impl Segments {
+    fn new(params: &RenderParams) -> Option<Self> {
+        if params.segment_height == 0 {
+            return None;
+        }
+        let zones = params
+            .segment_zones
+            .iter()
+            .filter_map(|zone| Some((zone.from, RgbColor::from_hex(&zone.color)?)))
+            .collect();
+        Some(Self {
+            height: params.segment_height,
+            period: params.segment_height + params.segment_gap,
+            zones,
+        })
+    }
+
+    /// Height actually drawn for a bar `filled` pixels tall: only whole segments light up
+    fn lit(&self, filled: usize) -> usize {
+        let whole = filled / self.period * self.period;
+        if filled - whole >= self.height {
+            whole + self.height
+        } else {
+            whole
+        }
+    }
+
+    /// Like `lit`, but a bar with a minimum height keeps at least one segment
+    fn lit_above(&self, filled: usize, min: usize) -> usize {
+        if min > 0 {
+            self.lit(filled.max(self.height))
+        } else {
+            self.lit(filled)
+        }
+    }
+
+    fn in_gap(&self, y_offset: usize) -> bool {
+        y_offset % self.period >= self.height
+    }
+
+    /// Zone color of the segment holding `y_offset` in a bar area `extent` pixels tall
+    fn zone_color(&self, y_offset: usize, extent: usize) -> Option<(u8, u8, u8)> {
+        let start = (y_offset - y_offset % self.period) as f32 / extent.max(1) as f32;
+        self.zones
+            .iter()
+            .filter(|(from, _)| *from <= start)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, c)| (c.r, c.g, c.b))
+    }
+}
+
+/// Color of pixel `y_offset` up a bar, or `None` when it falls in a segment gap.
+fn bar_pixel_color(
+    segments: Option<&Segments>,
+    y_offset: usize,
+    extent: usize,
+    position: f32,
+    params: &RenderParams,
+) -> Option<(u8, u8, u8)> {
+    if let Some(segments) = segments {
+        if segments.in_gap(y_offset) {
+            return None;
+        }
+        if let Some(color) = segments.zone_color(y_offset, extent) {
+            return Some(color);
+        }
+    }
+    let intensity = y_offset as f32 / extent as f32;
+    Some(params.color_scheme.get_color(position, intensity))
+}
+
+/// Rounded ends for solid bars: (round the tip, round the base), or None for square bars
+fn rounded_ends(segments: Option<&Segments>, params: &RenderParams) -> Option<(bool, bool)> {
+    if segments.is_some() {
+        return None;
+    }
+    match params.bar_rounding {
+        BarRounding::None => None,
+        BarRounding::Top => Some((true, false)),
+        BarRounding::Pill => Some((true, true)),
     }
 }
 
 /// Style 0: Classic vertical bars from bottom
-fn render_bars_classic(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
+fn render_bars_classic(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let segments = Segments::new(params);
+    let rounding = rounded_ends(segments.as_ref(), params);
+    let paint = Paint { opacity: params.opacity, rows: layout.bars_y_start..layout.bars_y_start + layout.bars_height };
+
     for i in 0..layout.displayable {
         let magnitude = layout.render_frequencies[i];
-        let bar_height = (magnitude * layout.bars_height as f32) as usize;
-        let x_start = layout.start_x + i * layout.slot_width;
+        let mut bar_height = ((magnitude * layout.bars_height as f32) as usize)
+            .max(layout.min_bar_height)
+            .min(layout.bars_height);
+        if let Some(segments) = &segments {
+            bar_height = segments.lit_above(bar_height, layout.min_bar_height);
+        }
+        let x_start = layout.bar_x(i);
         let position = i as f32 / layout.displayable as f32;
 
-        for y_offset in 0..bar_height.min(layout.bars_height) {
+        if let Some(ends) = rounding {
+            let base = layout.bars_y_start + layout.bars_height;
+            let color = |y: isize| {
+                let y_offset = base as isize - 1 - y;
+                params.color_scheme.get_color(position, y_offset as f32 / layout.bars_height as f32)
+            };
+            let rows = ((base - bar_height) as f32, base as f32);
+            draw::fill_capsule(canvas, (x_start as f32, params.bar_width as f32), rows, ends, &paint, color);
+            continue;
+        }
+
+        for y_offset in 0..bar_height {
             let y = layout.bars_y_start + layout.bars_height - 1 - y_offset;
-            let intensity = y_offset as f32 / layout.bars_height as f32;
-            let (r, g, b) = params.color_scheme.get_color(position, intensity);
+            let Some((r, g, b)) = bar_pixel_color(segments.as_ref(), y_offset, layout.bars_height, position, params) else {
+                continue;
+            };
 
             for bx in 0..params.bar_width {
                 let x = x_start + bx;
@@ -66,26 +289,48 @@ fn render_bars_classic(canvas: &mut Canvas, layout: &BarLayout, params: &RenderP
 }
 
 /// Style 1: Mirrored bars growing from center
-fn render_bars_mirrored(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
+fn render_bars_mirrored(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     let center_y = layout.bars_y_start + layout.bars_height / 2;
+    let segments = Segments::new(params);
+    // Both ends of a mirrored bar are tips
+    let rounded = rounded_ends(segments.as_ref(), params).is_some();
+    let paint = Paint { opacity: params.opacity, rows: layout.bars_y_start..layout.bars_y_start + layout.bars_height };
 
     for i in 0..layout.displayable {
         let magnitude = layout.render_frequencies[i];
-        let half_height = (magnitude * layout.bars_height as f32 / 2.0) as usize;
-        let x_start = layout.start_x + i * layout.slot_width;
+        // Each half holds half of the minimum height
+        let min_half = layout.min_bar_height.div_ceil(2);
+        let mut half_height = ((magnitude * layout.bars_height as f32 / 2.0) as usize)
+            .max(min_half)
+            .min(layout.bars_height / 2);
+        if let Some(segments) = &segments {
+            half_height = segments.lit_above(half_height, min_half);
+        }
+        let x_start = layout.bar_x(i);
         let position = i as f32 / layout.displayable as f32;
 
-        for y_offset in 0..half_height.min(layout.bars_height / 2) {
-            let intensity = y_offset as f32 / (layout.bars_height as f32 / 2.0);
-            let (r, g, b) = params.color_scheme.get_color(position, intensity);
+        if rounded && half_height > 0 {
+            let color = |y: isize| {
+                let y_offset = (y - center_y as isize).unsigned_abs();
+                params.color_scheme.get_color(position, y_offset as f32 / (layout.bars_height / 2) as f32)
+            };
+            let rows = ((center_y + 1 - half_height) as f32, (center_y + half_height) as f32);
+            draw::fill_capsule(canvas, (x_start as f32, params.bar_width as f32), rows, (true, true), &paint, color);
+            continue;
+        }
+
+        for y_offset in 0..half_height {
+            let Some((r, g, b)) = bar_pixel_color(segments.as_ref(), y_offset, layout.bars_height / 2, position, params) else {
+                continue;
+            };
 
-            // Upper half
+            // Upper half (both halves write the center row, so overwrite)
             let y_up = center_y.saturating_sub(y_offset);
             if y_up >= layout.bars_y_start {
                 for bx in 0..params.bar_width {
                     let x = x_start + bx;
                     if x < canvas.width && y_up < canvas.height {
-                        canvas.put_pixel(x, y_up, r, g, b, params.opacity);
+                        canvas.put_pixel_replace(x, y_up, r, g, b, params.opacity);
                     }
                 }
             }
@@ -96,7 +341,7 @@ fn render_bars_mirrored(canvas: &mut Canvas, layout: &BarLayout, params: &Render
                 for bx in 0..params.bar_width {
                     let x = x_start + bx;
                     if x < canvas.width && y_down < canvas.height {
-                        canvas.put_pixel(x, y_down, r, g, b, params.opacity);
+                        canvas.put_pixel_replace(x, y_down, r, g, b, params.opacity);
                     }
                 }
             }
@@ -105,58 +350,45 @@ fn render_bars_mirrored(canvas: &mut Canvas, layout: &BarLayout, params: &Render
 }
 
 /// Style 2: Wave centered on middle row
-fn render_bars_wave(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
+fn render_bars_wave(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     let center_y = layout.bars_y_start + layout.bars_height / 2;
-    let wave_width = (params.bar_width / 3).max(1);
+    let wave_width = (params.bar_width / 3).max(1) as f32;
+    let paint = Paint { opacity: params.opacity, rows: layout.bars_y_start..layout.bars_y_start + layout.bars_height };
 
     for i in 0..layout.displayable {
         let magnitude = layout.render_frequencies[i];
-        let wave_height = (magnitude * layout.bars_height as f32 / 2.0) as isize;
-        let x_start = layout.start_x + i * layout.slot_width;
+        let wave_height = magnitude * layout.bars_height as f32 / 2.0;
+        let x_start = layout.bar_x(i) as f32;
         let position = i as f32 / layout.displayable as f32;
 
-        for offset in -wave_height..=wave_height {
-            let y = (center_y as isize + offset) as usize;
-            if y >= layout.bars_y_start && y < layout.bars_y_start + layout.bars_height && y < canvas.height {
-                let intensity = 1.0 - (offset.unsigned_abs() as f32 / wave_height.max(1) as f32);
-                let (r, g, b) = params.color_scheme.get_color(position, intensity);
-
-                for bx in 0..wave_width {
-                    let x = x_start + bx;
-                    if x < canvas.width {
-                        canvas.put_pixel(x, y, r, g, b, params.opacity * intensity);
-                    }
-                }
-            }
-        }
+        // Brightest and most opaque on the center line, fading out towards both tips
+        let shade = |y: isize| {
+            let offset = (y - center_y as isize).unsigned_abs() as f32;
+            let intensity = (1.0 - offset / wave_height.max(1.0)).max(0.0);
+            (params.color_scheme.get_color(position, intensity), intensity)
+        };
+        let rows = (center_y as f32 - wave_height, center_y as f32 + 1.0 + wave_height);
+        draw::fill_capsule_shaded(canvas, (x_start, wave_width), rows, (true, true), &paint, shade);
     }
 }
 
 /// Style 3: Dots at peak with trailing dots below
-fn render_bars_dots(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
+fn render_bars_dots(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     let dot_radius = (params.bar_width / 3).max(2);
+    let dot_paint = Paint { opacity: params.opacity, rows: layout.bars_y_start..layout.bars_y_start + layout.bars_height };
 
     for i in 0..layout.displayable {
         let magnitude = layout.render_frequencies[i];
         let peak_y = layout.bars_y_start + layout.bars_height - 1
             - (magnitude * (layout.bars_height - 1) as f32) as usize;
-        let x_center = layout.start_x + i * layout.slot_width + params.bar_width / 2;
+        let x_center = layout.bar_x(i) + params.bar_width / 2;
         let position = i as f32 / layout.displayable as f32;
         let (r, g, b) = params.color_scheme.get_color(position, magnitude);
 
         // Draw dot (filled circle)
-        let r2 = (dot_radius * dot_radius) as isize;
-        for dy in -(dot_radius as isize)..=(dot_radius as isize) {
-            for dx in -(dot_radius as isize)..=(dot_radius as isize) {
-                if dx * dx + dy * dy <= r2 {
-                    let x = (x_center as isize + dx) as usize;
-                    let y = (peak_y as isize + dy) as usize;
-                    if x < canvas.width && y >= layout.bars_y_start && y < layout.bars_y_start + layout.bars_height && y < canvas.height {
-                        canvas.put_pixel(x, y, r, g, b, params.opacity);
-                    }
-                }
-            }
-        }
+        let radius = dot_radius as f32 + 0.5;
+        let center = (x_center as f32 + 0.5, peak_y as f32 + 0.5);
+        draw::fill_ellipse(canvas, center, (radius, radius), (r, g, b), &dot_paint);
 
         // Draw trail below dot
         let trail_width = (params.bar_width / 4).max(1);
@@ -179,15 +411,15 @@ fn render_bars_dots(canvas: &mut Canvas, layout: &BarLayout, params: &RenderPara
 }
 
 /// Style 4: Blocks with gradient fade at top edge
-fn render_bars_blocks(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
+fn render_bars_blocks(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     let fade_height = (params.bar_width / 2).max(2);
 
     for i in 0..layout.displayable {
         let magnitude = layout.render_frequencies[i];
-        let bar_height_f = magnitude * layout.bars_height as f32;
+        let bar_height_f = (magnitude * layout.bars_height as f32).max(layout.min_bar_height as f32);
         let bar_height = bar_height_f as usize;
         let fractional = bar_height_f - bar_height as f32;
-        let x_start = layout.start_x + i * layout.slot_width;
+        let x_start = layout.bar_x(i);
         let position = i as f32 / layout.displayable as f32;
 
         // Draw solid portion
@@ -227,55 +459,66 @@ fn render_bars_blocks(canvas: &mut Canvas, layout: &BarLayout, params: &RenderPa
 }
 
 /// Style 5: Oscilloscope — raw waveform as a continuous line
-fn render_bars_oscilloscope(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
+fn render_bars_oscilloscope(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     if params.waveform.is_empty() {
         return;
     }
 
-    let num_samples = params.waveform.len();
-    let center_y = layout.bars_y_start + layout.bars_height / 2;
-    let half_height = layout.bars_height as f32 / 2.0;
-    let thickness = (params.bar_width / 4).max(1);
-
-    let mut prev_y: Option<usize> = None;
-
-    for x in 0..canvas.width {
-        let sample_idx = (x * num_samples) / canvas.width;
-        let sample = params.waveform[sample_idx.min(num_samples - 1)];
-
-        let y = ((center_y as f32 - sample * half_height) as usize)
-            .max(layout.bars_y_start)
-            .min(layout.bars_y_start + layout.bars_height - 1);
-
-        let position = x as f32 / canvas.width as f32;
-        let intensity = sample.abs().min(1.0);
-        let (r, g, b) = params.color_scheme.get_color(position, intensity.max(0.3));
-
-        let y_min;
-        let y_max;
-        if let Some(py) = prev_y {
-            y_min = py.min(y);
-            y_max = py.max(y);
-        } else {
-            y_min = y;
-            y_max = y;
-        }
-
-        for fill_y in y_min..=y_max {
-            for t in 0..thickness {
-                let py = fill_y + t;
-                if x < canvas.width && py >= layout.bars_y_start && py < layout.bars_y_start + layout.bars_height && py < canvas.height {
-                    canvas.put_pixel(x, py, r, g, b, params.opacity);
-                }
+    let slices = wide_slices(canvas.width, layout);
+    match params.oscilloscope.wide_mode {
+        OscilloscopeWideMode::Repeat if slices > 1 => {
+            for slice in 0..slices {
+                let x_start = slice * canvas.width / slices;
+                let x_end = (slice + 1) * canvas.width / slices;
+                draw_waveform(canvas, layout, params, params.waveform, x_start, x_end);
             }
         }
+        OscilloscopeWideMode::Zoom if slices > 1 => {
+            // Middle 1/slices of the buffer, so each cycle keeps its 16:9 width
+            let len = params.waveform.len();
+            let window = (len / slices).max(2).min(len);
+            let start = (len - window) / 2;
+            draw_waveform(canvas, layout, params, &params.waveform[start..start + window], 0, canvas.width);
+        }
+        _ => draw_waveform(canvas, layout, params, params.waveform, 0, canvas.width),
+    }
+}
 
-        prev_y = Some(y);
+/// Draw `samples` as a connected line spanning columns `x_start..x_end`
+fn draw_waveform(
+    canvas: &mut BarCanvas,
+    layout: &BarLayout,
+    params: &RenderParams,
+    samples: &[f32],
+    x_start: usize,
+    x_end: usize,
+) {
+    let num_samples = samples.len();
+    let span = x_end.saturating_sub(x_start);
+    if num_samples == 0 || span == 0 {
+        return;
     }
+
+    let center_y = layout.bars_y_start as f32 + layout.bars_height as f32 / 2.0;
+    let half_height = layout.bars_height as f32 / 2.0;
+    let thickness = (params.bar_width / 4).max(1) as f32;
+    let (top, bottom) = (layout.bars_y_start as f32, (layout.bars_y_start + layout.bars_height) as f32);
+
+    // One point per sample, or per column when there are more samples than columns
+    let points = num_samples.min(span);
+    let line = (0..points).map(|k| {
+        let sample = samples[k * num_samples / points];
+        let x = x_start as f32 + (k as f32 + 0.5) * span as f32 / points as f32;
+        let y = (center_y - sample * half_height).clamp(top, bottom);
+        let position = k as f32 / points as f32;
+        ((x, y), params.color_scheme.get_color(position, sample.abs().clamp(0.3, 1.0)))
+    });
+    let paint = Paint { opacity: params.opacity, rows: layout.bars_y_start..layout.bars_y_start + layout.bars_height };
+    draw::polyline(canvas, line, thickness, &paint);
 }
 
 /// Style 6: Spectrogram — scrolling 2D heatmap (X=frequency, Y=time)
-fn render_bars_spectrogram(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
+fn render_bars_spectrogram(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     let history = params.spectrogram_history;
     if history.is_empty() {
         return;
@@ -296,7 +539,8 @@ fn render_bars_spectrogram(canvas: &mut Canvas, layout: &BarLayout, params: &Ren
 
         for x in 0..canvas.width {
             let freq_idx = (x * num_freqs) / canvas.width;
-            let magnitude = slice[freq_idx.min(num_freqs - 1)];
+            let raw = slice[freq_idx.min(num_freqs - 1)];
+            let magnitude = (raw * params.spectrogram.gain).clamp(0.0, 1.0).powf(params.spectrogram.contrast.max(0.01));
             let position = x as f32 / canvas.width as f32;
             let (r, g, b) = params.color_scheme.get_color(position, magnitude);
             canvas.put_pixel(x, y, r, g, b, params.opacity * magnitude.max(0.05));
@@ -304,38 +548,107 @@ fn render_bars_spectrogram(canvas: &mut Canvas, layout: &BarLayout, params: &Ren
     }
 }
 
+/// Append a frequency snapshot to the spectrogram history, applying the
+/// configured frequency-axis kernel and temporal smoothing, and trim the
+/// history to `max_rows`. Shared by every backend so the waterfall looks the same.
+pub fn push_spectrogram_row(
+    history: &mut Vec<Vec<f32>>,
+    frequencies: &[f32],
+    config: &SpectrogramConfig,
+    max_rows: usize,
+) {
+    let kernel = &config.frequency_kernel;
+    let kernel_sum: f32 = kernel.iter().sum();
+    let mut row: Vec<f32> = if kernel.len() > 1 && kernel_sum > 0.0 {
+        let half = kernel.len() / 2;
+        (0..frequencies.len())
+            .map(|i| {
+                let mut acc = 0.0;
+                let mut weight = 0.0;
+                for (k, w) in kernel.iter().enumerate() {
+                    // Skip taps that fall off either end and renormalize
+                    if let Some(&v) = (i + k).checked_sub(half).and_then(|j| frequencies.get(j)) {
+                        acc += v * w;
+                        weight += w;
+                    }
+                }
+                if weight > 0.0 { acc / weight } else { 0.0 }
+            })
+            .collect()
+    } else {
+        frequencies.to_vec()
+    };
+
+    let alpha = config.temporal_smoothing.clamp(0.0, 0.99);
+    if alpha > 0.0 {
+        if let Some(prev) = history.last().filter(|p| p.len() == row.len()) {
+            for (v, p) in row.iter_mut().zip(prev) {
+                *v = p * alpha + *v * (1.0 - alpha);
+            }
+        }
+    }
+
+    history.push(row);
+    if history.len() > max_rows {
+        let excess = history.len() - max_rows;
+        history.drain(..excess);
+    }
+}
+
 /// Style 7: Radial — frequency bars radiating outward from a circle
-fn render_bars_radial(canvas: &mut Canvas, layout: &BarLayout, params: &RenderParams) {
-    let cx = canvas.width as f32 / 2.0;
+fn render_bars_radial(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
     let cy = (layout.bars_y_start as f32) + layout.bars_height as f32 / 2.0;
-    let half_dim = (canvas.width.min(layout.bars_height) as f32) / 2.0;
-    let base_radius = half_dim * 0.35;
-    let max_radius = half_dim * 0.95;
-    let thickness = (params.bar_width / 3).max(2);
+    let slices = wide_slices(canvas.width, layout);
+
+    match params.radial.wide_mode {
+        RadialWideMode::Rings if slices > 1 => {
+            let slice_width = canvas.width as f32 / slices as f32;
+            let half_dim = slice_width.min(layout.bars_height as f32) / 2.0;
+            for slice in 0..slices {
+                let cx = slice_width * (slice as f32 + 0.5);
+                draw_radial(canvas, layout, params, (cx, cy), (half_dim, half_dim));
+            }
+        }
+        RadialWideMode::Ellipse => {
+            let half = (canvas.width as f32 / 2.0, layout.bars_height as f32 / 2.0);
+            draw_radial(canvas, layout, params, (canvas.width as f32 / 2.0, cy), half);
+        }
+        _ => {
+            let half_dim = (canvas.width.min(layout.bars_height) as f32) / 2.0;
+            draw_radial(canvas, layout, params, (canvas.width as f32 / 2.0, cy), (half_dim, half_dim));
+        }
+    }
+}
+
+/// Draw one radial ring centered at `center`, with horizontal and vertical
+/// half-extents `half` (equal for a circle, different for an ellipse)
+fn draw_radial(
+    canvas: &mut BarCanvas,
+    layout: &BarLayout,
+    params: &RenderParams,
+    (cx, cy): (f32, f32),
+    (half_x, half_y): (f32, f32),
+) {
+    // Radii are computed along the vertical axis and stretched horizontally
+    let stretch = half_x / half_y.max(1.0);
+    let base_radius = half_y * 0.35;
+    let max_radius = half_y * 0.95;
+    let thickness = (params.bar_width / 3).max(2) as f32;
 
     let bar_count = layout.render_frequencies.len();
     if bar_count == 0 {
         return;
     }
+    let rows = layout.bars_y_start..layout.bars_y_start + layout.bars_height;
 
     // Draw base circle
-    let circle_steps = (base_radius * std::f32::consts::TAU).ceil() as usize;
-    for step in 0..circle_steps {
-        let angle = (step as f32 / circle_steps as f32) * std::f32::consts::TAU;
-        let px = (cx + angle.cos() * base_radius).round() as usize;
-        let py = (cy + angle.sin() * base_radius).round() as usize;
-        let position = (angle + std::f32::consts::FRAC_PI_2) / std::f32::consts::TAU;
-        let position = position.rem_euclid(1.0);
-        let (r, g, b) = params.color_scheme.get_color(position, 0.3);
-        for t in 0..thickness {
-            let tx = px + t;
-            if tx < canvas.width && py >= layout.bars_y_start && py < layout.bars_y_start + layout.bars_height && py < canvas.height {
-                canvas.put_pixel(tx, py, r, g, b, params.opacity * 0.5);
-            }
-        }
-    }
+    let circle_paint = Paint { opacity: params.opacity * 0.5, rows: rows.clone() };
+    draw::stroke_ellipse(canvas, (cx, cy), (base_radius * stretch, base_radius), thickness, &circle_paint, |position| {
+        params.color_scheme.get_color(position, 0.3)
+    });
 
     // Draw radial bars
+    let paint = Paint { opacity: params.opacity, rows };
     for i in 0..bar_count {
         let magnitude = layout.render_frequencies[i];
         if magnitude < 0.01 {
@@ -346,23 +659,391 @@ fn render_bars_radial(canvas: &mut Canvas, layout: &BarLayout, params: &RenderPa
         let bar_length = magnitude * (max_radius - base_radius);
         let position = i as f32 / bar_count as f32;
 
-        let steps = (bar_length.ceil() as usize).max(1);
-        let cos_a = angle.cos();
-        let sin_a = angle.sin();
-        for s in 0..=steps {
-            let r_dist = base_radius + (s as f32 / steps as f32) * bar_length;
-            let px = (cx + cos_a * r_dist).round() as isize;
-            let py_val = (cy + sin_a * r_dist).round() as isize;
-            let intensity = (r_dist - base_radius) / (max_radius - base_radius);
-            let (r, g, b) = params.color_scheme.get_color(position, magnitude * 0.5 + intensity * 0.5);
-
-            for t in -(thickness as isize / 2)..=(thickness as isize / 2) {
-                let tx = (px as f32 - sin_a * t as f32).round() as usize;
-                let ty = (py_val as f32 + cos_a * t as f32).round() as usize;
-                if tx < canvas.width && ty >= layout.bars_y_start && ty < layout.bars_y_start + layout.bars_height && ty < canvas.height {
-                    canvas.put_pixel(tx, ty, r, g, b, params.opacity);
+        let (cos_a, sin_a) = (angle.cos(), angle.sin());
+        let point = |r_dist: f32| (cx + cos_a * r_dist * stretch, cy + sin_a * r_dist);
+        // Brighter toward the tip
+        let reach = bar_length / (max_radius - base_radius);
+        draw::thick_line(canvas, point(base_radius), point(base_radius + bar_length), thickness, &paint, |t| {
+            params.color_scheme.get_color(position, magnitude * 0.5 + t * reach * 0.5)
+        });
+    }
+}
+
+/// Style 8: Particles — bursts launched by bass hits that arc and fade
+fn render_bars_particles(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let radius = (params.bar_width / 3).max(1) as f32;
+    let bottom = (layout.bars_y_start + layout.bars_height) as f32;
+    let rows = layout.bars_y_start..layout.bars_y_start + layout.bars_height;
+
+    for p in &params.particles.particles {
+        let life = p.life();
+        let center = (p.x * canvas.width as f32, bottom - p.y * layout.bars_height as f32);
+        let color = params.color_scheme.get_color(p.hue, life);
+        // Shrink as the particle fades
+        let size = (radius * (0.5 + life * 0.5)).max(1.0) + 0.5;
+        let paint = Paint { opacity: params.opacity * life, rows: rows.clone() };
+        draw::fill_ellipse(canvas, center, (size, size), color, &paint);
+    }
+}
+
+/// Style 9: Ring — the raw waveform wrapped around a circle, radius modulated
+/// by sample amplitude
+fn render_bars_ring(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let num_samples = params.waveform.len();
+    if num_samples < 2 {
+        return;
+    }
+
+    let cx = canvas.width as f32 / 2.0;
+    let cy = (layout.bars_y_start as f32) + layout.bars_height as f32 / 2.0;
+    let half_dim = (canvas.width.min(layout.bars_height) as f32) / 2.0;
+    let base_radius = half_dim * 0.55;
+    let amplitude = half_dim * 0.4;
+    let thickness = (params.bar_width / 8 * 2 + 1) as f32;
+
+    // One point per couple of pixels of circumference, capped by the sample count
+    let points = ((base_radius * std::f32::consts::TAU / 2.0) as usize).max(16).min(num_samples);
+    // Cross-fade the last stretch toward the first sample so the ring closes without a seam
+    let blend_start = points - points / 8;
+    let sample_at = |k: usize| {
+        let sample = params.waveform[k * num_samples / points];
+        if k >= blend_start {
+            let t = (k - blend_start) as f32 / (points - blend_start) as f32;
+            sample * (1.0 - t) + params.waveform[0] * t
+        } else {
+            sample
+        }
+    };
+
+    // Back to the first point at the end, closing the ring
+    let line = (0..=points).map(|k| {
+        let sample = sample_at(k % points);
+        let angle = -std::f32::consts::FRAC_PI_2 + (k as f32 / points as f32) * std::f32::consts::TAU;
+        let radius = base_radius + sample * amplitude;
+        let position = (k % points) as f32 / points as f32;
+        ((cx + angle.cos() * radius, cy + angle.sin() * radius), params.color_scheme.get_color(position, sample.abs().max(0.3)))
+    });
+    let paint = Paint { opacity: params.opacity, rows: layout.bars_y_start..layout.bars_y_start + layout.bars_height };
+    draw::polyline(canvas, line, thickness, &paint);
+}
+
+/// Rows of spectrogram history shown by the skyline, front to back
+const SKYLINE_ROWS: usize = 24;
+/// Frames of history between consecutive skyline rows
+const SKYLINE_ROW_STEP: usize = 3;
+
+/// Style 10: Skyline — rows of recent spectra receding toward a vanishing
+/// point, drawn back to front with depth shading
+fn render_bars_skyline(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let history = params.spectrogram_history;
+    if history.is_empty() {
+        return;
+    }
+
+    let cx = canvas.width as f32 / 2.0;
+    let bottom = (layout.bars_y_start + layout.bars_height) as f32;
+    let horizon = layout.bars_y_start as f32 + layout.bars_height as f32 * 0.3;
+    let max_height = layout.bars_height as f32 * 0.6;
+    let rows = SKYLINE_ROWS.min(history.len().div_ceil(SKYLINE_ROW_STEP));
+
+    for depth in (0..rows).rev() {
+        let Some(slice) = history.len().checked_sub(1 + depth * SKYLINE_ROW_STEP).map(|i| &history[i]) else {
+            continue;
+        };
+        let num_freqs = slice.len();
+        if num_freqs == 0 {
+            continue;
+        }
+
+        // Perspective: each row further back is smaller and closer to the horizon
+        let z = 1.0 / (1.0 + depth as f32 * 0.25);
+        let base_y = horizon + (bottom - horizon) * z;
+        let row_width = canvas.width as f32 * z;
+        let left = cx - row_width / 2.0;
+        let slot = row_width / num_freqs as f32;
+        let bar_w = (slot * 0.7).max(1.0);
+        // Distant rows fade into the background
+        let shade = z.powf(0.7);
+
+        for (i, &magnitude) in slice.iter().enumerate() {
+            let height = magnitude.clamp(0.0, 1.0) * max_height * z;
+            if height < 1.0 {
+                continue;
+            }
+            let x0 = (left + i as f32 * slot + (slot - bar_w) / 2.0) as usize;
+            let x1 = ((x0 as f32 + bar_w) as usize).max(x0 + 1);
+            let y_top = (base_y - height).max(layout.bars_y_start as f32) as usize;
+            let y_bottom = (base_y as usize).min(layout.bars_y_start + layout.bars_height);
+            let position = i as f32 / num_freqs as f32;
+
+            for y in y_top..y_bottom {
+                // Brighter top edge gives each block a lit "roof"; nearer
+                // rows overwrite (hide) the ones behind
+                let roof = if y <= y_top + 1 { 1.0 } else { 0.75 };
+                let (r, g, b) = params.color_scheme.get_color(position, magnitude * roof);
+                for x in x0..x1.min(canvas.width) {
+                    if y < canvas.height {
+                        canvas.put_pixel_replace(x, y, r, g, b, params.opacity * shade * roof);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Style 11: Seismograph — the loudness envelope of the last
+/// `window_secs` scrolling right to left, mirrored around the center line
+fn render_bars_seismograph(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let history = params.amplitude_history;
+    let Some(&(now, _)) = history.last() else {
+        return;
+    };
+    if canvas.width == 0 {
+        return;
+    }
+
+    let config = params.seismograph;
+    let window = seismograph_window(config);
+    let scale = if config.normalize {
+        let peak = history.iter().filter(|&&(t, _)| t >= now - window).map(|&(_, a)| a).fold(0.0f32, f32::max);
+        if peak > 0.0 { 1.0 / peak } else { 0.0 }
+    } else {
+        config.gain
+    };
+    let center = layout.bars_y_start as f32 + layout.bars_height as f32 / 2.0;
+    let half = layout.bars_height as f32 / 2.0;
+    let y_end = (layout.bars_y_start + layout.bars_height).min(canvas.height);
+
+    // Newest sample at the right edge; walk columns and history backwards together
+    let mut i = history.len() - 1;
+    for x in (0..canvas.width).rev() {
+        let t = now - window * (canvas.width - 1 - x) as f32 / canvas.width as f32;
+        while i > 0 && history[i - 1].0 >= t {
+            i -= 1;
+        }
+        let amplitude = match i.checked_sub(1).map(|j| history[j]) {
+            Some((t0, a0)) => {
+                let (t1, a1) = history[i];
+                let f = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+                a0 + (a1 - a0) * f
+            }
+            // Columns older than the first recorded frame stay empty
+            None if history[0].0 > t => break,
+            None => history[0].1,
+        };
+
+        let level = (amplitude * scale).clamp(0.0, 1.0);
+        let extent = (level * half).max(0.5);
+        let y_top = ((center - extent).max(layout.bars_y_start as f32)) as usize;
+        let y_bottom = ((center + extent).ceil() as usize).min(y_end);
+        let (r, g, b) = params.color_scheme.get_color(x as f32 / canvas.width as f32, level);
+        for y in y_top..y_bottom {
+            canvas.put_pixel(x, y, r, g, b, params.opacity);
+        }
+    }
+}
+
+/// Style 12: Fire — the heat grid of `params.fire` scaled up to the bar
+/// area, colored by heat and fading out where it has cooled
+fn render_bars_fire(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let fire = params.fire;
+    let y_end = (layout.bars_y_start + layout.bars_height).min(canvas.height);
+    if fire.is_empty() || canvas.width == 0 || y_end <= layout.bars_y_start {
+        return;
+    }
+
+    // One color per heat level, worked out once per frame
+    let palette: Vec<(u8, u8, u8)> = (0..=255)
+        .map(|i| {
+            let heat = i as f32 / 255.0;
+            params.color_scheme.get_color(heat, heat)
+        })
+        .collect();
+
+    let bottom = layout.bars_y_start + layout.bars_height;
+    for y in layout.bars_y_start..y_end {
+        let v = (bottom - y) as f32 / layout.bars_height as f32;
+        for x in 0..canvas.width {
+            let heat = fire.sample((x as f32 + 0.5) / canvas.width as f32, v).min(1.0);
+            // Embers below this would only tint the background
+            if heat < 0.02 {
+                continue;
+            }
+            let (r, g, b) = palette[(heat * 255.0) as usize];
+            canvas.put_pixel(x, y, r, g, b, params.opacity * (heat * 3.0).min(1.0));
+        }
+    }
+}
+
+/// Style 13: Vectorscope — the phosphor grid of `params.vectorscope` scaled
+/// up to a square in the middle of the bar area, with the stereo correlation
+/// meter below it
+fn render_bars_vectorscope(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let scope = params.vectorscope;
+    if scope.is_empty() {
+        return;
+    }
+    let correlation = scope.correlation();
+    let meter_height = if correlation.is_some() { (layout.bars_height / 60).max(2) } else { 0 };
+    // The meter sits one meter height below the figure
+    let side = layout.bars_height.saturating_sub(meter_height * 2).min(canvas.width);
+    if side < 2 {
+        return;
+    }
+    let x0 = (canvas.width - side) / 2;
+    let y0 = layout.bars_y_start;
+    let paint = Paint { opacity: params.opacity, rows: y0..(layout.bars_y_start + layout.bars_height).min(canvas.height) };
+
+    // Faint center cross for reference
+    let mid = side as f32 / 2.0;
+    let grid_color = params.color_scheme.get_color(0.5, 0.3);
+    let faint = Paint { opacity: params.opacity * 0.15, ..paint.clone() };
+    draw::line(canvas, (x0 as f32 + mid, y0 as f32), (x0 as f32 + mid, (y0 + side) as f32), grid_color, &faint);
+    draw::line(canvas, (x0 as f32, y0 as f32 + mid), ((x0 + side) as f32, y0 as f32 + mid), grid_color, &faint);
+
+    // Brightness by glow, with a square root lifting the faint spread of noisy material
+    let palette: Vec<(u8, u8, u8)> = (0..=255)
+        .map(|i| {
+            let brightness = i as f32 / 255.0;
+            params.color_scheme.get_color(brightness, brightness)
+        })
+        .collect();
+    let cells = |offset: usize| {
+        let (cell, t) = fire::grid_position((offset as f32 + 0.5) / side as f32, vectorscope::SIZE);
+        (cell, (cell + 1).min(vectorscope::SIZE - 1), t)
+    };
+    let columns: Vec<(usize, usize, f32)> = (0..side).map(cells).collect();
+    for dy in 0..side {
+        let y = y0 + dy;
+        if !paint.rows.contains(&y) {
+            break;
+        }
+        let (row, row1, ty) = cells(dy);
+        for (dx, &(col, col1, tx)) in columns.iter().enumerate() {
+            let top = scope.glow(col, row) * (1.0 - tx) + scope.glow(col1, row) * tx;
+            let bottom = scope.glow(col, row1) * (1.0 - tx) + scope.glow(col1, row1) * tx;
+            let glow = top * (1.0 - ty) + bottom * ty;
+            let brightness = 1.0 - (-glow.sqrt() * 2.0).exp();
+            if brightness < 0.01 {
+                continue;
+            }
+            let (r, g, b) = palette[(brightness * 255.0) as usize];
+            canvas.put_pixel(x0 + dx, y, r, g, b, params.opacity * brightness);
+        }
+    }
+
+    // Correlation meter: a dim track with a marker from -1 (left) to +1 (right)
+    if let Some(correlation) = correlation {
+        let position = (correlation + 1.0) / 2.0;
+        let (r, g, b) = params.color_scheme.get_color(position, 1.0);
+        let marker = (side / 100).max(2);
+        let marker_x = x0 + ((side - marker) as f32 * position) as usize;
+        let meter_y = y0 + side + meter_height;
+        for y in meter_y..(meter_y + meter_height).min(paint.rows.end) {
+            for x in x0..x0 + side {
+                if (marker_x..marker_x + marker).contains(&x) {
+                    canvas.put_pixel(x, y, r, g, b, params.opacity);
+                } else {
+                    canvas.put_pixel(x, y, grid_color.0, grid_color.1, grid_color.2, faint.opacity);
                 }
             }
         }
     }
 }
+
+/// Half the angle the VU scale spans, in turns (±50°)
+const VU_SWEEP: f32 = 50.0 / 360.0;
+/// Scale markings of the VU meters, in VU; the longer ticks are labelled on real meters
+const VU_TICKS: &[(f32, bool)] = &[
+    (-20.0, true),
+    (-10.0, true),
+    (-7.0, false),
+    (-5.0, true),
+    (-3.0, false),
+    (-2.0, false),
+    (-1.0, false),
+    (0.0, true),
+    (1.0, false),
+    (2.0, false),
+    (3.0, true),
+];
+
+/// Style 14: VU Meters — one analog meter per needle of `params.vu`, side by
+/// side, each with its scale, red zone above 0 VU, needle and peak LED
+fn render_bars_vu(canvas: &mut BarCanvas, layout: &BarLayout, params: &RenderParams) {
+    let needles = params.vu.needles();
+    let y_end = (layout.bars_y_start + layout.bars_height).min(canvas.height);
+    if needles.is_empty() || y_end <= layout.bars_y_start {
+        return;
+    }
+    let paint = Paint { opacity: params.opacity, rows: layout.bars_y_start..y_end };
+    let faint = Paint { opacity: params.opacity * 0.15, ..paint.clone() };
+
+    // Meter faces of about 2:1, centered in equal shares of the width
+    let panel_height = layout.bars_height as f32;
+    let share = canvas.width as f32 / needles.len() as f32;
+    let panel_width = share.min(panel_height * 2.0);
+    let radius = (panel_height * 0.8).min(panel_width * 0.45 / (VU_SWEEP * std::f32::consts::TAU).sin());
+    let stroke = (radius * 0.012).max(1.0);
+    let scale_color = params.color_scheme.get_color(0.4, 0.6);
+    let hot_color = params.color_scheme.get_color(1.0, 1.0);
+    // Turns from the top for a deflection, and the point at `r` from the pivot in that direction
+    let turn = |deflection: f32| VU_SWEEP * (deflection * 2.0 - 1.0);
+    let point = |pivot: (f32, f32), turn: f32, r: f32| {
+        let (sin, cos) = (turn * std::f32::consts::TAU).sin_cos();
+        (pivot.0 + r * sin, pivot.1 - r * cos)
+    };
+
+    for (i, needle) in needles.iter().enumerate() {
+        let center_x = share * (i as f32 + 0.5);
+        let top = layout.bars_y_start as f32;
+        let pivot = (center_x, top + panel_height * 0.95);
+
+        // Scale, with a heavier red zone from 0 VU up
+        let zero = turn(vu::deflection(0.0));
+        draw::stroke_arc(canvas, pivot, radius, (turn(0.0), zero), stroke, &paint, |_| scale_color);
+        draw::stroke_arc(canvas, pivot, radius, (zero, turn(1.0)), stroke * 3.0, &paint, |_| hot_color);
+        for &(level, major) in VU_TICKS {
+            let at = turn(vu::deflection(level));
+            let inner = radius * if major { 0.88 } else { 0.93 };
+            let color = if level > 0.0 { hot_color } else { scale_color };
+            draw::thick_line(canvas, point(pivot, at, inner), point(pivot, at, radius), stroke, &paint, |_| color);
+        }
+
+        // Needle from the pivot to just past the scale
+        let needle_color = params.color_scheme.get_color(needle.position.min(1.0), 1.0);
+        let tip = point(pivot, turn(needle.position), radius * 1.04);
+        draw::thick_line(canvas, pivot, tip, stroke * 1.5, &paint, |_| needle_color);
+        draw::fill_ellipse(canvas, pivot, (radius * 0.04, radius * 0.04), scale_color, &paint);
+
+        // Peak LED in the top right corner of the face, dim when unlit
+        let led = (center_x + panel_width * 0.4, top + panel_height * 0.1);
+        let led_radius = (radius * 0.035).max(1.5);
+        draw::fill_ellipse(canvas, led, (led_radius, led_radius), hot_color, if needle.peak() { &paint } else { &faint });
+    }
+}
+
+/// History length shown by the seismograph, in seconds
+fn seismograph_window(config: &SeismographConfig) -> f32 {
+    config.window_secs.clamp(1.0, 120.0)
+}
+
+/// Append this frame's RMS loudness to the seismograph history and drop
+/// frames that have scrolled out of the window. Shared by every backend.
+pub fn push_amplitude(history: &mut Vec<(f32, f32)>, waveform: &[f32], time: f32, config: &SeismographConfig) {
+    let rms = if waveform.is_empty() {
+        0.0
+    } else {
+        (waveform.iter().map(|s| s * s).sum::<f32>() / waveform.len() as f32).sqrt()
+    };
+    // The clock restarted (e.g. a new visualizer): start a fresh trace
+    if history.last().is_some_and(|&(t, _)| t > time) {
+        history.clear();
+    }
+    history.push((time, rms));
+
+    // Keep one frame before the window so the left edge can interpolate
+    let cutoff = time - seismograph_window(config);
+    let stale = history.partition_point(|&(t, _)| t < cutoff).saturating_sub(1);
+    history.drain(..stale);
+}
@@ -1,15 +1,158 @@
 //! Bitmap font text rendering for the pixel canvas.
 //!
-//! Renders track info using an 8×8 bitmap font, with support for font styles
+//! Renders track info using the 8×8 bitmap font in [`super::font`] (or a
+//! TrueType font, see [`super::ttf`]), with support for font styles
 //! (Normal, Bold, Ascii, Figlet), text animations (Scroll, Pulse, Fade, Wave),
-//! and alignment/positioning.
+//! alignment/positioning, and an optional two-line marquee layout.
 
 use tracing::info;
 
-use crate::config::{FontStyle, TextAlignment, TextAnimation, TextPosition};
-use super::{Canvas, FrameData, RenderParams};
+use crate::config::{FontStyle, ScrollDirection, TextAlignment, TextAnimation, TextCase, TextConfig, TextPosition};
+use super::ttf::TrueTypeFont;
+use super::{font, Canvas, FrameData, RenderParams};
 
-pub fn render_text(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams) {
+/// Gap between the end of a marquee line and its wrapped-around copy, in characters
+pub const MARQUEE_GAP_CHARS: usize = 4;
+
+/// Share of the canvas width a karaoke line may fill at rest
+const KARAOKE_WIDTH: f32 = 0.9;
+/// Tallest karaoke title letters, as a share of the canvas height
+const KARAOKE_MAX_HEIGHT: f32 = 0.3;
+/// Karaoke artist letters relative to the title's
+const KARAOKE_ARTIST_SCALE: f32 = 0.45;
+
+/// Scaled glyph metrics for the current font style and canvas height.
+struct FontMetrics<'a> {
+    scale: usize,
+    char_width: usize,
+    char_height: usize,
+    char_spacing: usize,
+    /// Outline font and its pixel size, when one is configured
+    font: Option<(&'a TrueTypeFont, f32)>,
+}
+
+impl<'a> FontMetrics<'a> {
+    fn new(font_style: FontStyle, height: usize, font: Option<&'a TrueTypeFont>) -> Self {
+        // Scale factor based on font style, proportional to canvas size.
+        // Base scales are tuned for ~800px height; scale proportionally for other sizes.
+        let base_scale = match font_style {
+            FontStyle::Normal => 3.0,
+            FontStyle::Bold => 4.0,
+            FontStyle::Ascii => 2.0,
+            FontStyle::Figlet => 5.0,
+        };
+        let size_factor = height as f32 / 800.0;
+        let scale = (base_scale * size_factor).round().max(1.0) as usize;
+
+        let char_spacing = match font_style {
+            FontStyle::Bold => 2 * scale,
+            _ => scale,
+        };
+        let char_height = 8 * scale;
+        Self {
+            scale,
+            char_width: 8 * scale,
+            char_height,
+            char_spacing,
+            // Outline glyphs reach as high as bitmap capitals (7 of 8 rows)
+            font: font.map(|f| (f, f.size_for_ascent(char_height * 7 / 8))),
+        }
+    }
+
+    /// Horizontal advance per bitmap character
+    fn advance(&self) -> usize {
+        self.char_width + self.char_spacing
+    }
+
+    /// Horizontal advance of `ch`
+    fn char_advance(&self, ch: char) -> usize {
+        match self.font {
+            // Bold keeps its extra spacing
+            Some((font, px)) if font.has_glyph(ch) => font.advance(ch, px).round() as usize + self.char_spacing - self.scale,
+            _ => self.advance(),
+        }
+    }
+
+    /// Width of `text` in pixels
+    fn text_width(&self, text: &str) -> usize {
+        text.chars().map(|ch| self.char_advance(ch)).sum()
+    }
+
+    /// Draw `ch` in the character cell whose top-left corner is (x, y).
+    fn draw_char(&self, canvas: &mut Canvas, x: usize, y: usize, ch: char, (r, g, b): (u8, u8, u8), opacity: f32) {
+        match self.font {
+            Some((font, px)) if font.has_glyph(ch) => {
+                let baseline = y + font.ascent(px).round() as usize;
+                font.draw_char(canvas, x, baseline, ch, px, (r, g, b), opacity);
+            }
+            _ => render_char(canvas, x, y, ch, r, g, b, self.scale, opacity),
+        }
+    }
+
+    /// Like [`Self::draw_char`], but composited over what is already drawn.
+    fn blend_char(&self, canvas: &mut Canvas, x: usize, y: usize, ch: char, color: (u8, u8, u8), opacity: f32) {
+        match self.font {
+            Some((font, _)) if font.has_glyph(ch) => self.draw_char(canvas, x, y, ch, color, opacity),
+            _ => draw_bitmap_char(canvas, x, y, ch, self.scale, |canvas, px, py| {
+                canvas.put_pixel(px, py, color.0, color.1, color.2, opacity)
+            }),
+        }
+    }
+}
+
+/// `text` in the configured letter case
+fn apply_case(text: &str, case: TextCase) -> String {
+    text.chars().map(|ch| case.apply(ch)).collect()
+}
+
+/// Compute the marquee viewport offset for continuously scrolling text.
+///
+/// Returns how far (in the same units as `content_width`) the content has
+/// scrolled, in the range `0..content_width + gap`. Shared by the pixel and
+/// terminal text renderers so both scroll identically.
+pub fn marquee_offset(content_width: usize, gap: usize, speed: f32, direction: ScrollDirection, time: f32) -> usize {
+    let period = (content_width + gap).max(1) as f32;
+    let travelled = (time * speed).rem_euclid(period);
+    let offset = match direction {
+        ScrollDirection::Left => travelled,
+        ScrollDirection::Right => (period - travelled) % period,
+    };
+    offset as usize
+}
+
+/// Whether title and artist are laid out on separate marquee rows this frame.
+fn uses_two_line(text_config: &TextConfig, frame: &FrameData) -> bool {
+    text_config.two_line
+        && text_config.show_title
+        && text_config.show_artist
+        && frame.track_title.is_some()
+        && frame.track_artist.is_some()
+}
+
+/// Top and height of the text area, or `None` when text is hidden.
+///
+/// Used to size album art placed beside the text.
+pub fn text_band(canvas: &Canvas, frame: &FrameData, params: &RenderParams) -> Option<(usize, usize)> {
+    let text_config = params.text_config;
+    if !text_config.show_title && !text_config.show_artist {
+        return None;
+    }
+    let metrics = FontMetrics::new(text_config.font_style, canvas.height, params.font);
+    let char_height = metrics.char_height;
+    let block_height = if uses_two_line(text_config, frame) {
+        char_height * 2 + char_height / 2
+    } else {
+        char_height
+    };
+    let text_area_height = block_height + 20;
+    let (top, _) = text_origin(text_config, canvas.width, canvas.height, text_area_height, block_height);
+    Some((top, text_area_height))
+}
+
+/// Render track text. `left_inset` reserves space to the left of the text
+/// block (e.g. for album art). Returns the block's left edge before scrolling,
+/// or `None` when text is hidden.
+pub fn render_text(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams, left_inset: usize) -> Option<usize> {
     let text_config = params.text_config;
     let width = canvas.width;
     let height = canvas.height;
@@ -34,7 +177,15 @@ pub fn render_text(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams
     }
 
     if !text_config.show_title && !text_config.show_artist {
-        return;
+        return None;
+    }
+
+    if uses_two_line(text_config, frame) {
+        if let (Some(title), Some(artist)) = (track_title, track_artist) {
+            let title = apply_case(title, text_config.case);
+            let artist = apply_case(artist, text_config.case);
+            return Some(render_two_line(canvas, frame, params, &title, &artist, left_inset));
+        }
     }
 
     // Build display text and track where title ends for color splitting
@@ -46,57 +197,33 @@ pub fn render_text(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams
     ) {
         (true, true, Some(title), Some(artist)) => {
             let combined = format!("{} - {}", title, artist);
-            (combined, title.len())
+            (combined, title.chars().count())
         }
-        (true, true, Some(title), None) => (title.clone(), title.len()),
+        (true, true, Some(title), None) => (title.clone(), title.chars().count()),
         (true, true, None, Some(artist)) => (artist.clone(), 0),
-        (true, false, Some(title), _) => (title.clone(), title.len()),
+        (true, false, Some(title), _) => (title.clone(), title.chars().count()),
         (false, true, _, Some(artist)) => (artist.clone(), 0),
         _ => ("cavibe".to_string(), 6),
     };
+    let text = apply_case(&text, text_config.case);
 
-    // Scale factor based on font style, proportional to canvas size.
-    // Base scales are tuned for ~800px height; scale proportionally for other sizes.
-    let base_scale = match text_config.font_style {
-        FontStyle::Normal => 3.0,
-        FontStyle::Bold => 4.0,
-        FontStyle::Ascii => 2.0,
-        FontStyle::Figlet => 5.0,
-    };
-    let size_factor = height as f32 / 800.0;
-    let scale = (base_scale * size_factor).round().max(1.0) as usize;
-
-    let char_width = 8 * scale;
-    let char_height = 8 * scale;
-    let char_spacing = match text_config.font_style {
-        FontStyle::Bold => 2 * scale,
-        _ => scale,
-    };
+    let metrics = FontMetrics::new(text_config.font_style, height, params.font);
+    let char_height = metrics.char_height;
 
     let text_area_height = char_height + 20;
     let margin_h = text_config.margin_horizontal as usize;
+    let margin_left = margin_h + left_inset;
 
     // Calculate text Y position based on position setting
-    let (base_text_y, coord_x_override) = match text_config.position {
-        TextPosition::Top => (text_config.margin_top as usize, None),
-        TextPosition::Bottom => (height.saturating_sub(text_area_height + text_config.margin_bottom as usize), None),
-        TextPosition::Center => ((height.saturating_sub(char_height)) / 2, None),
-        TextPosition::Coordinates { x, y } => (y.resolve(height), Some(x.resolve(width))),
-    };
+    let (base_text_y, coord_x_override) = text_origin(text_config, width, height, text_area_height, char_height);
 
-    let text_width = text.len() * (char_width + char_spacing);
-    let available_width = width.saturating_sub(margin_h * 2);
+    let text_width = metrics.text_width(&text);
+    let available_width = width.saturating_sub(margin_left + margin_h);
 
     // Calculate base X position based on alignment (or coordinate override)
-    let base_start_x = if let Some(cx) = coord_x_override {
-        cx
-    } else {
-        match text_config.alignment {
-            TextAlignment::Left => margin_h,
-            TextAlignment::Center => margin_h + (available_width.saturating_sub(text_width)) / 2,
-            TextAlignment::Right => margin_h + available_width.saturating_sub(text_width),
-        }
-    };
+    let base_start_x = coord_x_override
+        .map(|x| x + left_inset)
+        .unwrap_or_else(|| aligned_x(text_config.alignment, margin_left, available_width, text_width));
 
     // Apply scroll animation offset if text is wider than available space
     let scroll_offset = match text_config.animation_style {
@@ -114,29 +241,11 @@ pub fn render_text(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams
     let y = base_text_y + (text_area_height.saturating_sub(char_height)) / 2;
 
     // Render background if configured
-    if let Some(bg_color) = text_config.background_color {
-        let bg_padding = 10;
-        let bg_x_start = base_start_x.saturating_sub(bg_padding);
-        let bg_x_end = (base_start_x + text_width + bg_padding).min(width);
-        let bg_y_start = base_text_y.saturating_sub(bg_padding);
-        let bg_y_end = (base_text_y + text_area_height + bg_padding).min(height);
-
-        for py in bg_y_start..bg_y_end {
-            for px in bg_x_start..bg_x_end {
-                let idx = (py * width + px) * 4;
-                if idx + 3 < canvas.data.len() {
-                    canvas.data[idx] = (bg_color.r as f32 * params.opacity) as u8;
-                    canvas.data[idx + 1] = (bg_color.g as f32 * params.opacity) as u8;
-                    canvas.data[idx + 2] = (bg_color.b as f32 * params.opacity) as u8;
-                    canvas.data[idx + 3] = (params.opacity * 255.0 * 0.8) as u8;
-                }
-            }
-        }
-    }
+    fill_background(canvas, params, base_start_x, base_text_y, text_width, text_area_height);
 
     // Get colors for text
     let colors: Vec<(u8, u8, u8)> = if text_config.use_color_scheme {
-        params.color_scheme.get_text_gradient(text.len(), intensity * text_config.pulse_intensity, time * text_config.animation_speed)
+        params.color_scheme.get_text_gradient(text.chars().count(), intensity * text_config.pulse_intensity, time * text_config.animation_speed)
     } else {
         let title_color = text_config.title_color.unwrap_or(crate::config::RgbColor { r: 255, g: 255, b: 255 });
         let artist_color = text_config.artist_color.unwrap_or(crate::config::RgbColor { r: 200, g: 200, b: 200 });
@@ -150,8 +259,275 @@ pub fn render_text(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams
         }).collect()
     };
 
+    let start_x = base_start_x as isize - scroll_offset;
+    draw_text_run(canvas, &text, start_x, y, &colors, &metrics, params, frame);
+    Some(base_start_x)
+}
+
+/// Render the Karaoke style: the track title, and the artist below it, in
+/// letters as large as fit across the canvas. Each letter is bound to a
+/// frequency band, left to right across the spectrum, and grows and
+/// brightens with it. Upright regardless of `visualizer.orientation`.
+pub fn render_karaoke(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams) {
+    let case = params.text_config.case;
+    let title = frame.track_title.as_deref().map_or_else(|| "cavibe".to_string(), |t| apply_case(t, case));
+    let artist = frame
+        .track_artist
+        .as_deref()
+        .filter(|_| params.karaoke.artist)
+        .map(|a| apply_case(a, case));
+    let letters = KaraokeLetters { font: params.font };
+
+    // Cap heights at which each line fits the width
+    let fit = |text: &str, max: f32| (canvas.width as f32 * KARAOKE_WIDTH / letters.line_width(text).max(1.0)).min(max);
+    let title_height = fit(&title, canvas.height as f32 * KARAOKE_MAX_HEIGHT);
+    let artist_height = artist.as_deref().map_or(0.0, |a| fit(a, title_height * KARAOKE_ARTIST_SCALE));
+    // Title and artist stacked around the middle
+    let gap = artist_height * 0.8;
+    let block = title_height + if artist.is_some() { gap + artist_height } else { 0.0 };
+    let title_baseline = (canvas.height as f32 - block) / 2.0 + title_height;
+
+    letters.draw_line(canvas, &title, title_height, title_baseline, frame, params);
+    if let Some(artist) = artist {
+        letters.draw_line(canvas, &artist, artist_height, title_baseline + gap + artist_height, frame, params);
+    }
+}
+
+/// Letters of the Karaoke style, sized by cap height in pixels and drawn
+/// with the outline font where it has the glyph
+struct KaraokeLetters<'a> {
+    font: Option<&'a TrueTypeFont>,
+}
+
+impl KaraokeLetters<'_> {
+    /// Outline font pixel size for a cap height, when `ch` is drawn with it.
+    /// Sizes are rounded to steps of about 2%, so pulsing letters reuse
+    /// cached glyphs instead of rasterizing every size on the way.
+    fn font_size(&self, ch: char, cap_height: f32) -> Option<(&TrueTypeFont, f32)> {
+        self.font.filter(|font| font.has_glyph(ch)).map(|font| {
+            let px = cap_height / font.ascent(1.0);
+            let step = (px / 48.0).max(1.0);
+            (font, ((px / step).round() * step).max(1.0))
+        })
+    }
+
+    /// Advance of `ch` per pixel of cap height
+    fn advance(&self, ch: char) -> f32 {
+        match self.font_size(ch, 100.0) {
+            Some((font, px)) => font.advance(ch, px) / 100.0,
+            // Bitmap capitals are 7 rows of an 8-wide cell, plus a column of spacing
+            None => 9.0 / 7.0,
+        }
+    }
+
+    /// Width of `text` per pixel of cap height
+    fn line_width(&self, text: &str) -> f32 {
+        text.chars().map(|ch| self.advance(ch)).sum()
+    }
+
+    /// Draw `text` centered horizontally on `baseline`, each letter scaled
+    /// and colored by its band
+    fn draw_line(&self, canvas: &mut Canvas, text: &str, cap_height: f32, baseline: f32, frame: &FrameData, params: &RenderParams) {
+        let bands = frame.frequencies;
+        let count = text.chars().filter(|ch| !ch.is_whitespace()).count().max(1);
+        // Letters keep their resting positions, so loud ones grow into their neighbours
+        let mut pen = (canvas.width as f32 - self.line_width(text) * cap_height) / 2.0;
+        // Index among the letters only, like `count`
+        let mut letter = 0;
+        for ch in text.chars() {
+            let advance = self.advance(ch) * cap_height;
+            let center_x = pen + advance / 2.0;
+            pen += advance;
+            if ch.is_whitespace() {
+                continue;
+            }
+            let position = (letter as f32 + 0.5) / count as f32;
+            letter += 1;
+            let level = match bands.len() {
+                0 => 0.0,
+                n => bands[((position * n as f32) as usize).min(n - 1)].clamp(0.0, 1.0),
+            };
+            let size = cap_height * (1.0 + params.karaoke.pulse * level);
+            let color = params.color_scheme.get_color(position, level);
+            self.draw_letter(canvas, ch, center_x, baseline, size, color, params.opacity * (0.6 + 0.4 * level));
+        }
+    }
+
+    /// Draw `ch` `size` pixels tall, centered on `center_x`, on `baseline`
+    #[allow(clippy::too_many_arguments)]
+    fn draw_letter(&self, canvas: &mut Canvas, ch: char, center_x: f32, baseline: f32, size: f32, color: (u8, u8, u8), opacity: f32) {
+        match self.font_size(ch, size) {
+            Some((font, px)) => {
+                let x = center_x - font.advance(ch, px) / 2.0;
+                if x >= 0.0 && baseline >= 0.0 {
+                    font.draw_char(canvas, x as usize, baseline as usize, ch, px, color, opacity);
+                }
+            }
+            None => {
+                let scale = (size / 7.0).round().max(1.0);
+                let (x, y) = (center_x - 4.0 * scale, baseline - 7.0 * scale);
+                if x >= 0.0 && y >= 0.0 {
+                    render_char(canvas, x as usize, y as usize, ch, color.0, color.1, color.2, scale as usize, opacity);
+                }
+            }
+        }
+    }
+}
+
+/// Resolve the top of the text area and an optional X override from the position setting.
+fn text_origin(
+    text_config: &TextConfig,
+    width: usize,
+    height: usize,
+    text_area_height: usize,
+    char_height: usize,
+) -> (usize, Option<usize>) {
+    match text_config.position {
+        TextPosition::Top => (text_config.margin_top as usize, None),
+        TextPosition::Bottom => (height.saturating_sub(text_area_height + text_config.margin_bottom as usize), None),
+        TextPosition::Center => ((height.saturating_sub(char_height)) / 2, None),
+        TextPosition::Coordinates { x, y } => (y.resolve(height), Some(x.resolve(width))),
+    }
+}
+
+/// X position of a line of `text_width` pixels for the given alignment.
+fn aligned_x(alignment: TextAlignment, margin_h: usize, available_width: usize, text_width: usize) -> usize {
+    match alignment {
+        TextAlignment::Left => margin_h,
+        TextAlignment::Center => margin_h + (available_width.saturating_sub(text_width)) / 2,
+        TextAlignment::Right => margin_h + available_width.saturating_sub(text_width),
+    }
+}
+
+/// Fill the configured text background rectangle (no-op when unset).
+fn fill_background(
+    canvas: &mut Canvas,
+    params: &RenderParams,
+    x: usize,
+    y: usize,
+    text_width: usize,
+    text_area_height: usize,
+) {
+    let Some(bg_color) = params.text_config.background_color else {
+        return;
+    };
+    let width = canvas.width;
+    let bg_padding = 10;
+    let bg_x_start = x.saturating_sub(bg_padding);
+    let bg_x_end = (x + text_width + bg_padding).min(width);
+    let bg_y_start = y.saturating_sub(bg_padding);
+    let bg_y_end = (y + text_area_height + bg_padding).min(canvas.height);
+
+    for py in bg_y_start..bg_y_end {
+        for px in bg_x_start..bg_x_end {
+            let idx = (py * width + px) * 4;
+            if idx + 3 < canvas.data.len() {
+                canvas.data[idx] = (bg_color.r as f32 * params.opacity) as u8;
+                canvas.data[idx + 1] = (bg_color.g as f32 * params.opacity) as u8;
+                canvas.data[idx + 2] = (bg_color.b as f32 * params.opacity) as u8;
+                canvas.data[idx + 3] = (params.opacity * 255.0 * 0.8) as u8;
+            }
+        }
+    }
+}
+
+/// Render title and artist on separate rows, each with an independent marquee.
+/// Returns the left edge of the text block.
+fn render_two_line(
+    canvas: &mut Canvas,
+    frame: &FrameData,
+    params: &RenderParams,
+    title: &str,
+    artist: &str,
+    left_inset: usize,
+) -> usize {
+    let text_config = params.text_config;
+    let width = canvas.width;
+    let height = canvas.height;
+    let metrics = FontMetrics::new(text_config.font_style, height, params.font);
+    let char_height = metrics.char_height;
+    let line_gap = char_height / 2;
+    let block_height = char_height * 2 + line_gap;
+    let text_area_height = block_height + 20;
+    let margin_h = text_config.margin_horizontal as usize;
+    let margin_left = margin_h + left_inset;
+    let available_width = width.saturating_sub(margin_left + margin_h);
+
+    let (base_text_y, coord_x_override) = text_origin(text_config, width, height, text_area_height, block_height);
+    let coord_x_override = coord_x_override.map(|x| x + left_inset);
+    let title_y = base_text_y + (text_area_height - block_height) / 2;
+    let artist_y = title_y + char_height + line_gap;
+
+    let widest = metrics.text_width(title).max(metrics.text_width(artist));
+    let bg_x = coord_x_override
+        .unwrap_or_else(|| aligned_x(text_config.alignment, margin_left, available_width, widest.min(available_width)));
+    fill_background(canvas, params, bg_x, base_text_y, widest.min(available_width), text_area_height);
+
+    let lines = [
+        (title, title_y, text_config.title_scroll_speed, text_config.title_scroll_direction, text_config.title_color, (255, 255, 255), 0.0),
+        (artist, artist_y, text_config.artist_scroll_speed, text_config.artist_scroll_direction, text_config.artist_color, (200, 200, 200), 0.5),
+    ];
+
+    for (text, y, speed, direction, custom_color, fallback, phase) in lines {
+        let len = text.chars().count();
+        let text_width = metrics.text_width(text);
+        let colors: Vec<(u8, u8, u8)> = if text_config.use_color_scheme {
+            params.color_scheme.get_text_gradient(
+                len,
+                frame.intensity * text_config.pulse_intensity,
+                frame.time * text_config.animation_speed + phase,
+            )
+        } else {
+            let c = custom_color.map(|c| (c.r, c.g, c.b)).unwrap_or(fallback);
+            vec![c; len]
+        };
+
+        if text_width <= available_width || text_config.animation_style != TextAnimation::Scroll {
+            let x = coord_x_override
+                .unwrap_or_else(|| aligned_x(text_config.alignment, margin_left, available_width, text_width));
+            draw_text_run(canvas, text, x as isize, y, &colors, &metrics, params, frame);
+        } else {
+            let gap = MARQUEE_GAP_CHARS * metrics.advance();
+            let speed_px = text_config.animation_speed * 30.0 * speed;
+            let offset = marquee_offset(text_width, gap, speed_px, direction, frame.time) as isize;
+            let x = margin_left as isize - offset;
+            draw_text_run(canvas, text, x, y, &colors, &metrics, params, frame);
+            draw_text_run(canvas, text, x + (text_width + gap) as isize, y, &colors, &metrics, params, frame);
+        }
+    }
+    bg_x
+}
+
+/// Draw a run of characters starting at `start_x`, applying per-character
+/// animation effects, the configured font style, and the shadow and outline.
+#[allow(clippy::too_many_arguments)]
+fn draw_text_run(
+    canvas: &mut Canvas,
+    text: &str,
+    start_x: isize,
+    y: usize,
+    colors: &[(u8, u8, u8)],
+    metrics: &FontMetrics,
+    params: &RenderParams,
+    frame: &FrameData,
+) {
+    let text_config = params.text_config;
+    let width = canvas.width;
+    let char_width = metrics.char_width;
+    let time = frame.time;
+    let intensity = frame.intensity;
+
+    // (x, y, character, color, opacity) of each visible character
+    let mut placed = Vec::with_capacity(text.len());
+    let mut pen = start_x;
     for (i, ch) in text.chars().enumerate() {
-        let base_x = (base_start_x as isize - scroll_offset + (i * (char_width + char_spacing)) as isize) as usize;
+        let x = pen;
+        pen += metrics.char_advance(ch) as isize;
+        // Skip characters that start left of the canvas
+        if x < 0 {
+            continue;
+        }
+        let base_x = x as usize;
 
         // Apply animation effects per character
         let (char_x, char_y, char_opacity) = match text_config.animation_style {
@@ -177,97 +553,102 @@ pub fn render_text(canvas: &mut Canvas, frame: &FrameData, params: &RenderParams
             continue;
         }
 
-        let (r, g, b) = colors.get(i).copied().unwrap_or((255, 255, 255));
+        let color = colors.get(i).copied().unwrap_or((255, 255, 255));
+        placed.push((char_x, char_y, ch, color, char_opacity));
+    }
 
+    // Shadows and outlines go beneath every glyph, so draw them in separate passes
+    let step = metrics.scale as isize;
+    if let Some(shadow) = text_config.shadow {
+        let color = (shadow.color.r, shadow.color.g, shadow.color.b);
+        let alpha = shadow.alpha.clamp(0.0, 1.0);
+        for &(x, y, ch, _, opacity) in &placed {
+            let (sx, sy) = (x as isize + shadow.offset_x as isize * step, y as isize + shadow.offset_y as isize * step);
+            if sx >= 0 && sy >= 0 {
+                draw_shape(canvas, metrics, text_config.font_style, sx as usize, sy as usize, ch, color, opacity * alpha);
+            }
+        }
+    }
+    if let Some(outline) = text_config.outline.filter(|o| o.width > 0) {
+        let color = (outline.color.r, outline.color.g, outline.color.b);
+        let w = outline.width as isize;
+        for &(x, y, ch, _, opacity) in &placed {
+            for dy in -w..=w {
+                for dx in -w..=w {
+                    // Round the corners of wide outlines
+                    if (dx == 0 && dy == 0) || dx * dx + dy * dy > w * w + w {
+                        continue;
+                    }
+                    let (ox, oy) = (x as isize + dx * step, y as isize + dy * step);
+                    if ox >= 0 && oy >= 0 {
+                        draw_shape(canvas, metrics, text_config.font_style, ox as usize, oy as usize, ch, color, opacity);
+                    }
+                }
+            }
+        }
+    }
+
+    for (char_x, char_y, ch, (r, g, b), char_opacity) in placed {
         // Render with font style variations
         match text_config.font_style {
             FontStyle::Bold => {
-                render_char(canvas, char_x, char_y, ch, r, g, b, scale, char_opacity);
-                render_char(canvas, char_x + 1, char_y, ch, r, g, b, scale, char_opacity);
-                render_char(canvas, char_x, char_y + 1, ch, r, g, b, scale, char_opacity);
+                metrics.draw_char(canvas, char_x, char_y, ch, (r, g, b), char_opacity);
+                metrics.draw_char(canvas, char_x + 1, char_y, ch, (r, g, b), char_opacity);
+                metrics.draw_char(canvas, char_x, char_y + 1, ch, (r, g, b), char_opacity);
             }
             FontStyle::Figlet => {
                 let outline_color = (r / 3, g / 3, b / 3);
                 for ox in [0isize, 2].iter() {
                     for oy in [0isize, 2].iter() {
                         if *ox != 1 || *oy != 1 {
-                            render_char(canvas,
+                            metrics.draw_char(canvas,
                                 (char_x as isize + ox) as usize,
                                 (char_y as isize + oy) as usize,
-                                ch, outline_color.0, outline_color.1, outline_color.2,
-                                scale, char_opacity * 0.5);
+                                ch, outline_color, char_opacity * 0.5);
                         }
                     }
                 }
-                render_char(canvas, char_x + 1, char_y + 1, ch, r, g, b, scale, char_opacity);
+                metrics.draw_char(canvas, char_x + 1, char_y + 1, ch, (r, g, b), char_opacity);
             }
             FontStyle::Normal | FontStyle::Ascii => {
-                render_char(canvas, char_x, char_y, ch, r, g, b, scale, char_opacity);
+                metrics.draw_char(canvas, char_x, char_y, ch, (r, g, b), char_opacity);
             }
         }
     }
 }
 
-/// Simple 8x8 bitmap font for basic text rendering.
-/// Each character is represented as 8 bytes, one per row.
-fn get_char_bitmap(ch: char) -> Option<[u8; 8]> {
-    let ch = ch.to_ascii_uppercase();
-    Some(match ch {
-        'A' => [0x18, 0x24, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x00],
-        'B' => [0x7C, 0x42, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x00],
-        'C' => [0x3C, 0x42, 0x40, 0x40, 0x40, 0x42, 0x3C, 0x00],
-        'D' => [0x78, 0x44, 0x42, 0x42, 0x42, 0x44, 0x78, 0x00],
-        'E' => [0x7E, 0x40, 0x7C, 0x40, 0x40, 0x40, 0x7E, 0x00],
-        'F' => [0x7E, 0x40, 0x7C, 0x40, 0x40, 0x40, 0x40, 0x00],
-        'G' => [0x3C, 0x42, 0x40, 0x4E, 0x42, 0x42, 0x3C, 0x00],
-        'H' => [0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x00],
-        'I' => [0x3E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x3E, 0x00],
-        'J' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x44, 0x38, 0x00],
-        'K' => [0x42, 0x44, 0x78, 0x48, 0x44, 0x42, 0x42, 0x00],
-        'L' => [0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7E, 0x00],
-        'M' => [0x42, 0x66, 0x5A, 0x42, 0x42, 0x42, 0x42, 0x00],
-        'N' => [0x42, 0x62, 0x52, 0x4A, 0x46, 0x42, 0x42, 0x00],
-        'O' => [0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00],
-        'P' => [0x7C, 0x42, 0x42, 0x7C, 0x40, 0x40, 0x40, 0x00],
-        'Q' => [0x3C, 0x42, 0x42, 0x42, 0x4A, 0x44, 0x3A, 0x00],
-        'R' => [0x7C, 0x42, 0x42, 0x7C, 0x48, 0x44, 0x42, 0x00],
-        'S' => [0x3C, 0x42, 0x30, 0x0C, 0x02, 0x42, 0x3C, 0x00],
-        'T' => [0x7F, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00],
-        'U' => [0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00],
-        'V' => [0x42, 0x42, 0x42, 0x42, 0x24, 0x24, 0x18, 0x00],
-        'W' => [0x42, 0x42, 0x42, 0x5A, 0x5A, 0x66, 0x42, 0x00],
-        'X' => [0x42, 0x24, 0x18, 0x18, 0x24, 0x42, 0x42, 0x00],
-        'Y' => [0x41, 0x22, 0x14, 0x08, 0x08, 0x08, 0x08, 0x00],
-        'Z' => [0x7E, 0x04, 0x08, 0x10, 0x20, 0x40, 0x7E, 0x00],
-        '0' => [0x3C, 0x42, 0x46, 0x5A, 0x62, 0x42, 0x3C, 0x00],
-        '1' => [0x08, 0x18, 0x28, 0x08, 0x08, 0x08, 0x3E, 0x00],
-        '2' => [0x3C, 0x42, 0x02, 0x0C, 0x30, 0x40, 0x7E, 0x00],
-        '3' => [0x3C, 0x42, 0x02, 0x1C, 0x02, 0x42, 0x3C, 0x00],
-        '4' => [0x04, 0x0C, 0x14, 0x24, 0x7E, 0x04, 0x04, 0x00],
-        '5' => [0x7E, 0x40, 0x7C, 0x02, 0x02, 0x42, 0x3C, 0x00],
-        '6' => [0x1C, 0x20, 0x40, 0x7C, 0x42, 0x42, 0x3C, 0x00],
-        '7' => [0x7E, 0x02, 0x04, 0x08, 0x10, 0x10, 0x10, 0x00],
-        '8' => [0x3C, 0x42, 0x42, 0x3C, 0x42, 0x42, 0x3C, 0x00],
-        '9' => [0x3C, 0x42, 0x42, 0x3E, 0x02, 0x04, 0x38, 0x00],
-        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
-        '-' => [0x00, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x00, 0x00],
-        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00],
-        ',' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x08, 0x10],
-        '!' => [0x08, 0x08, 0x08, 0x08, 0x08, 0x00, 0x08, 0x00],
-        '?' => [0x3C, 0x42, 0x02, 0x0C, 0x10, 0x00, 0x10, 0x00],
-        ':' => [0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00],
-        '\'' => [0x08, 0x08, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00],
-        '"' => [0x24, 0x24, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00],
-        '(' => [0x04, 0x08, 0x10, 0x10, 0x10, 0x08, 0x04, 0x00],
-        ')' => [0x20, 0x10, 0x08, 0x08, 0x08, 0x10, 0x20, 0x00],
-        '&' => [0x30, 0x48, 0x30, 0x50, 0x4A, 0x44, 0x3A, 0x00],
-        _ => return None,
-    })
+/// Blend the solid shape of `ch` in one color (for shadows and outlines),
+/// matching the glyph footprint of the font style.
+#[allow(clippy::too_many_arguments)]
+fn draw_shape(
+    canvas: &mut Canvas,
+    metrics: &FontMetrics,
+    font_style: FontStyle,
+    x: usize,
+    y: usize,
+    ch: char,
+    color: (u8, u8, u8),
+    opacity: f32,
+) {
+    match font_style {
+        FontStyle::Bold => {
+            metrics.blend_char(canvas, x, y, ch, color, opacity);
+            metrics.blend_char(canvas, x + 1, y, ch, color, opacity);
+            metrics.blend_char(canvas, x, y + 1, ch, color, opacity);
+        }
+        FontStyle::Figlet => metrics.blend_char(canvas, x + 1, y + 1, ch, color, opacity),
+        FontStyle::Normal | FontStyle::Ascii => metrics.blend_char(canvas, x, y, ch, color, opacity),
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
-fn render_char(canvas: &mut Canvas, x: usize, y: usize, ch: char, r: u8, g: u8, b: u8, scale: usize, opacity: f32) {
-    let bitmap = match get_char_bitmap(ch) {
+pub(super) fn render_char(canvas: &mut Canvas, x: usize, y: usize, ch: char, r: u8, g: u8, b: u8, scale: usize, opacity: f32) {
+    draw_bitmap_char(canvas, x, y, ch, scale, |canvas, px, py| canvas.put_pixel(px, py, r, g, b, opacity));
+}
+
+/// Call `plot` for every on-canvas pixel of the bitmap glyph for `ch` at `scale`.
+fn draw_bitmap_char(canvas: &mut Canvas, x: usize, y: usize, ch: char, scale: usize, mut plot: impl FnMut(&mut Canvas, usize, usize)) {
+    let bitmap = match font::glyph(ch) {
         Some(b) => b,
         None => return,
     };
@@ -280,7 +661,7 @@ fn render_char(canvas: &mut Canvas, x: usize, y: usize, ch: char, r: u8, g: u8,
                         let px = x + col * scale + sx;
                         let py = y + row_idx * scale + sy;
                         if px < canvas.width && py < canvas.height {
-                            canvas.put_pixel(px, py, r, g, b, opacity);
+                            plot(canvas, px, py);
                         }
                     }
                 }
@@ -0,0 +1,122 @@
+//! Whole-frame rotation and flips (`visualizer.rotation`, `flip_horizontal`,
+//! `flip_vertical`), and bar symmetry (`visualizer.symmetry`).
+//!
+//! The frame is rendered as usual into a scratch canvas (with width and
+//! height swapped for quarter turns), then copied into the output turned and
+//! mirrored, so every style, the text and the widgets follow without any
+//! per-style code. Symmetry works the same way on the bars alone: they are
+//! drawn into a half- or quarter-size canvas that is composited into each
+//! part of the frame, reflected.
+
+use std::cell::RefCell;
+
+use crate::config::{BlendMode, Rotation, Symmetry, VisualizerConfig};
+use super::{mix, Canvas};
+
+thread_local! {
+    /// Untransformed frame, reused across frames
+    static SCRATCH: RefCell<Canvas> = RefCell::new(Canvas::new(0, 0));
+    /// Unreflected bars, reused across frames
+    static BARS: RefCell<Canvas> = RefCell::new(Canvas::new(0, 0));
+}
+
+/// A frame transform: clockwise rotation, then the flips
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+}
+
+impl Transform {
+    pub fn from_config(config: &VisualizerConfig) -> Self {
+        Self {
+            rotation: config.rotation,
+            flip_horizontal: config.flip_horizontal,
+            flip_vertical: config.flip_vertical,
+        }
+    }
+
+    /// Whether the transform leaves the frame as it is
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::default()
+    }
+
+    /// Size to render at for an output of `width`×`height`
+    fn source_size(&self, width: usize, height: usize) -> (usize, usize) {
+        if self.rotation.is_sideways() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Source pixel shown at output pixel (`x`, `y`) of a `width`×`height` output
+    #[inline]
+    fn source(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        // Undo the flips, then the rotation
+        let x = if self.flip_horizontal { width - 1 - x } else { x };
+        let y = if self.flip_vertical { height - 1 - y } else { y };
+        match self.rotation {
+            Rotation::None => (x, y),
+            Rotation::Quarter => (y, width - 1 - x),
+            Rotation::Half => (width - 1 - x, height - 1 - y),
+            Rotation::ThreeQuarters => (height - 1 - y, x),
+        }
+    }
+}
+
+/// Draw a frame with `render` and put it into `canvas` through `transform`.
+pub fn render_transformed(canvas: &mut Canvas, transform: Transform, render: impl FnOnce(&mut Canvas)) {
+    if transform.is_identity() || canvas.width == 0 || canvas.height == 0 {
+        render(canvas);
+        return;
+    }
+    SCRATCH.with(|scratch| {
+        let mut scratch = scratch.borrow_mut();
+        let (width, height) = transform.source_size(canvas.width, canvas.height);
+        scratch.resize(width, height);
+        render(&mut scratch);
+
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                let (sx, sy) = transform.source(x, y, canvas.width, canvas.height);
+                let (si, di) = ((sy * width + sx) * 4, (y * canvas.width + x) * 4);
+                canvas.data[di..di + 4].copy_from_slice(&scratch.data[si..si + 4]);
+            }
+        }
+    });
+}
+
+/// Draw the bars with `render` and composite them into `canvas` reflected by
+/// `symmetry`. The part drawn as is sits top left; an odd middle row or
+/// column is shared by both halves.
+pub fn render_symmetric(canvas: &mut Canvas, symmetry: Symmetry, render: impl FnOnce(&mut Canvas)) {
+    if symmetry == Symmetry::None || canvas.width < 2 || canvas.height < 2 {
+        render(canvas);
+        return;
+    }
+    BARS.with(|bars| {
+        let mut bars = bars.borrow_mut();
+        let width = if symmetry.reflects_x() { canvas.width.div_ceil(2) } else { canvas.width };
+        let height = if symmetry.reflects_y() { canvas.height.div_ceil(2) } else { canvas.height };
+        bars.resize(width, height);
+        bars.clear();
+        render(&mut bars);
+
+        for y in 0..canvas.height {
+            let sy = if y < height { y } else { canvas.height - 1 - y };
+            for x in 0..canvas.width {
+                let sx = if x < width { x } else { canvas.width - 1 - x };
+                let si = (sy * width + sx) * 4;
+                let px = &bars.data[si..si + 4];
+                if px == [0, 0, 0, 0] {
+                    continue;
+                }
+                let src = [px[0], px[1], px[2], px[3]].map(|c| c as f32);
+                let di = (y * canvas.width + x) * 4;
+                mix(&mut canvas.data[di..di + 4], src, BlendMode::Normal);
+            }
+        }
+    });
+}
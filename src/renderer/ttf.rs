@@ -0,0 +1,137 @@
+//! TrueType text for wallpaper mode.
+//!
+//! With `text.font_file` or `text.font_family` set, track text is drawn with
+//! an anti-aliased outline font rasterized at the text size instead of the
+//! scaled bitmap font. Characters the font lacks fall back to the bitmap font.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::TextConfig;
+use super::background::expand_home;
+use super::Canvas;
+
+/// Ascent relative to the pixel size, for fonts without line metrics
+const DEFAULT_ASCENT: f32 = 0.8;
+/// Glyph cache size at which it is cleared (sizes change with the surface)
+const MAX_CACHED_GLYPHS: usize = 2048;
+
+/// A rasterized glyph: coverage values (0-255), row-major
+struct RasterGlyph {
+    metrics: fontdue::Metrics,
+    coverage: Vec<u8>,
+}
+
+/// A loaded outline font with a cache of rasterized glyphs.
+pub struct TrueTypeFont {
+    font: fontdue::Font,
+    /// Ascent per pixel of font size
+    ascent: f32,
+    glyphs: Mutex<HashMap<(char, u32), Arc<RasterGlyph>>>,
+}
+
+impl TrueTypeFont {
+    /// Load the font configured in `[text]`: `font_file`, or else
+    /// `font_family` resolved with fontconfig. `Ok(None)` when neither is set.
+    pub fn from_config(config: &TextConfig) -> Result<Option<Self>> {
+        if let Some(path) = config.font_file.as_deref() {
+            return Self::load(&expand_home(path)).map(Some);
+        }
+        match config.font_family.as_deref() {
+            Some(family) => Self::load(Path::new(&find_family(family)?)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Load a TrueType/OpenType font file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("Failed to read font {}", path.display()))?;
+        let font = fontdue::Font::from_bytes(data, fontdue::FontSettings::default())
+            .map_err(|e| anyhow::anyhow!("Failed to parse font {}: {}", path.display(), e))?;
+        let ascent = font
+            .horizontal_line_metrics(1.0)
+            .map(|m| m.ascent)
+            .filter(|a| *a > 0.0)
+            .unwrap_or(DEFAULT_ASCENT);
+        tracing::info!("Loaded text font {}", path.display());
+        Ok(Self {
+            font,
+            ascent,
+            glyphs: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Whether the font has a glyph for `ch`
+    pub fn has_glyph(&self, ch: char) -> bool {
+        ch.is_whitespace() || self.font.lookup_glyph_index(ch) != 0
+    }
+
+    /// Font size whose ascent is `ascent` pixels
+    pub fn size_for_ascent(&self, ascent: usize) -> f32 {
+        (ascent as f32 / self.ascent).max(1.0)
+    }
+
+    /// Ascent in pixels at font size `px`
+    pub fn ascent(&self, px: f32) -> f32 {
+        self.ascent * px
+    }
+
+    /// Horizontal advance of `ch` at font size `px`
+    pub fn advance(&self, ch: char, px: f32) -> f32 {
+        self.font.metrics(ch, px).advance_width
+    }
+
+    fn glyph(&self, ch: char, px: f32) -> Arc<RasterGlyph> {
+        let mut glyphs = self.glyphs.lock().unwrap_or_else(|e| e.into_inner());
+        if glyphs.len() >= MAX_CACHED_GLYPHS {
+            glyphs.clear();
+        }
+        glyphs
+            .entry((ch, px.to_bits()))
+            .or_insert_with(|| {
+                let (metrics, coverage) = self.font.rasterize(ch, px);
+                Arc::new(RasterGlyph { metrics, coverage })
+            })
+            .clone()
+    }
+
+    /// Draw `ch` with its pen position at `x` and its baseline at `baseline`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_char(&self, canvas: &mut Canvas, x: usize, baseline: usize, ch: char, px: f32, color: (u8, u8, u8), opacity: f32) {
+        let glyph = self.glyph(ch, px);
+        let metrics = &glyph.metrics;
+        let left = x as i32 + metrics.xmin;
+        let top = baseline as i32 - metrics.ymin - metrics.height as i32;
+        for (row, coverage) in glyph.coverage.chunks(metrics.width.max(1)).enumerate() {
+            let py = top + row as i32;
+            if py < 0 || py as usize >= canvas.height {
+                continue;
+            }
+            for (col, &c) in coverage.iter().enumerate() {
+                let px = left + col as i32;
+                if c == 0 || px < 0 || px as usize >= canvas.width {
+                    continue;
+                }
+                canvas.put_pixel(px as usize, py as usize, color.0, color.1, color.2, opacity * c as f32 / 255.0);
+            }
+        }
+    }
+}
+
+/// Path of the font file fontconfig picks for `family`.
+fn find_family(family: &str) -> Result<String> {
+    let output = Command::new("fc-match")
+        .arg("--format=%{file}")
+        .arg(family)
+        .output()
+        .with_context(|| format!("Failed to run fc-match for font family '{}' (is fontconfig installed?)", family))?;
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || path.is_empty() {
+        bail!("No font found for family '{}'", family);
+    }
+    Ok(path)
+}
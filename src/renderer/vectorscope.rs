@@ -0,0 +1,133 @@
+//! Phosphor buffer for the Vectorscope style.
+//!
+//! The left and right samples of each block are plotted against each other
+//! as a continuous beam into a square grid that fades over
+//! `visualizer.vectorscope.persistence` instead of being cleared, so the
+//! figure leaves trails like a scope's phosphor. Like the fire, each surface
+//! owns a `VectorscopeState` that is advanced once per frame and handed to the
+//! renderer through `RenderParams`, which scales the grid up to a square in
+//! the middle of the bar area.
+
+use crate::config::VectorscopeConfig;
+
+/// Grid size in cells per side
+pub const SIZE: usize = 320;
+/// Glow deposited per second by the whole figure, spread over the cells it
+/// crosses; a figure covering this many cells settles at a glow of 1.0
+const BEAM_ENERGY: f32 = 400.0;
+/// Most cells one sample-to-sample segment is drawn with
+const MAX_SEGMENT_CELLS: usize = 64;
+/// Most mono samples plotted for sources without stereo
+const MONO_SAMPLES: usize = 1024;
+/// Time constant of the correlation meter, in seconds
+const CORRELATION_SMOOTHING: f32 = 0.15;
+/// Shortest supported persistence
+const MIN_PERSISTENCE: f32 = 0.02;
+
+#[derive(Default)]
+pub struct VectorscopeState {
+    /// Glow per cell, row-major with row 0 at the top
+    glow: Vec<f32>,
+    /// Smoothed stereo correlation (-1.0 out of phase, 0.0 unrelated, 1.0 mono)
+    correlation: f32,
+    /// Whether the correlation meter is shown (enabled, and the source is stereo)
+    meter: bool,
+    last_time: Option<f32>,
+}
+
+impl VectorscopeState {
+    /// Fade the traces to `time` and plot the newest samples: `stereo`, or
+    /// the end of `waveform` as a mono signal when there is no stereo.
+    pub fn update(&mut self, stereo: &[(f32, f32)], waveform: &[f32], time: f32, config: &VectorscopeConfig) {
+        if self.glow.is_empty() {
+            self.glow = vec![0.0; SIZE * SIZE];
+        }
+        let dt = self.last_time.map_or(0.0, |last| (time - last).clamp(0.0, 0.5));
+        self.last_time = Some(time);
+
+        // Exponential decay, reaching a tenth after `persistence` seconds
+        let rate = std::f32::consts::LN_10 / config.persistence.max(MIN_PERSISTENCE);
+        let fade = (-rate * dt).exp();
+        self.glow.iter_mut().for_each(|g| *g *= fade);
+
+        // Deposit in proportion to the decay, so the brightness a steady
+        // figure settles at doesn't depend on the persistence or frame rate
+        let energy = BEAM_ENERGY * rate * dt;
+        let angle = config.spin.to_radians() * time;
+        self.meter = config.correlation && !stereo.is_empty();
+        if stereo.is_empty() {
+            let mono = &waveform[waveform.len().saturating_sub(MONO_SAMPLES)..];
+            self.trace(mono.iter().map(|&s| (s, s)), mono.len(), energy, angle, config);
+        } else {
+            self.trace(stereo.iter().copied(), stereo.len(), energy, angle, config);
+            self.follow_correlation(stereo, dt);
+        }
+    }
+
+    /// Draw the beam through `samples` (`count` of them), `energy` spread evenly over them
+    fn trace(
+        &mut self,
+        samples: impl Iterator<Item = (f32, f32)>,
+        count: usize,
+        energy: f32,
+        angle: f32,
+        config: &VectorscopeConfig,
+    ) {
+        if count == 0 || energy <= 0.0 {
+            return;
+        }
+        let per_sample = energy / count as f32;
+        let (sin, cos) = angle.sin_cos();
+        let mut previous: Option<(f32, f32)> = None;
+        for (left, right) in samples {
+            // Figure coordinates, -1.0 to 1.0 with y up
+            let (x, y) = if config.rotate {
+                ((right - left) / 2.0, (left + right) / 2.0)
+            } else {
+                (left, right)
+            };
+            let (x, y) = ((x * cos - y * sin) * config.gain, (x * sin + y * cos) * config.gain);
+            let cell = ((x + 1.0) / 2.0 * (SIZE - 1) as f32, (1.0 - y) / 2.0 * (SIZE - 1) as f32);
+            let from = previous.unwrap_or(cell);
+            previous = Some(cell);
+
+            // A fast beam spreads its energy thinner, as on a real tube
+            let steps = ((cell.0 - from.0).abs().max((cell.1 - from.1).abs()).ceil() as usize).clamp(1, MAX_SEGMENT_CELLS);
+            for step in 1..=steps {
+                let t = step as f32 / steps as f32;
+                let (cx, cy) = (from.0 + (cell.0 - from.0) * t, from.1 + (cell.1 - from.1) * t);
+                if (0.0..SIZE as f32).contains(&cx) && (0.0..SIZE as f32).contains(&cy) {
+                    self.glow[cy as usize * SIZE + cx as usize] += per_sample / steps as f32;
+                }
+            }
+        }
+    }
+
+    /// Move the meter towards the correlation of `stereo`; silence leaves it where it is.
+    fn follow_correlation(&mut self, stereo: &[(f32, f32)], dt: f32) {
+        let (lr, ll, rr) = stereo.iter().fold((0.0, 0.0, 0.0), |(lr, ll, rr), &(l, r)| (lr + l * r, ll + l * l, rr + r * r));
+        let power = (ll * rr).sqrt();
+        if power < 1e-6 {
+            return;
+        }
+        let blend = 1.0 - (-dt / CORRELATION_SMOOTHING).exp();
+        self.correlation += ((lr / power).clamp(-1.0, 1.0) - self.correlation) * blend;
+    }
+
+    /// Whether there is anything to draw yet
+    pub fn is_empty(&self) -> bool {
+        self.glow.is_empty()
+    }
+
+    /// Smoothed stereo correlation, -1.0 (out of phase) to 1.0 (mono), when
+    /// the meter is shown
+    pub fn correlation(&self) -> Option<f32> {
+        self.meter.then_some(self.correlation)
+    }
+
+    /// Glow of cell (`col`, `row`), row 0 at the top
+    #[inline]
+    pub fn glow(&self, col: usize, row: usize) -> f32 {
+        self.glow.get(row * SIZE + col).copied().unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,113 @@
+//! Needle state for the VU Meters style.
+//!
+//! Each needle is a damped spring pulled towards the RMS level of its
+//! channel, so it swings up with a little overshoot and settles in about
+//! `visualizer.vu.response_ms`, like the ballistics of an analog VU meter.
+//! Each surface owns a `VuState` that is advanced once per frame and handed
+//! to the renderer through `RenderParams`.
+
+use crate::config::VuConfig;
+
+/// Lowest and highest level on the scale, in VU
+pub const MIN_VU: f32 = -20.0;
+pub const MAX_VU: f32 = 3.0;
+/// Damping ratio of the needle (below 1.0 it overshoots slightly)
+const DAMPING: f32 = 0.8;
+/// Integration step for the spring, in seconds
+const STEP: f32 = 0.002;
+/// Needle travel past full scale before it hits the pin
+const PIN: f32 = 1.05;
+/// How long the peak LED stays lit after a peak, in seconds
+const PEAK_HOLD: f32 = 0.5;
+/// Most mono samples measured for sources without stereo
+const MONO_SAMPLES: usize = 2048;
+
+/// One meter's needle. Positions are deflections, 0.0 at the left end of the
+/// scale to 1.0 at +3 VU, proportional to the signal voltage as on a real meter.
+#[derive(Default, Clone, Copy)]
+pub struct Needle {
+    pub position: f32,
+    velocity: f32,
+    /// Seconds the peak LED stays lit
+    peak_hold: f32,
+}
+
+impl Needle {
+    /// Whether the peak LED is lit
+    pub fn peak(&self) -> bool {
+        self.peak_hold > 0.0
+    }
+
+    fn advance(&mut self, target: f32, omega: f32, dt: f32) {
+        let mut remaining = dt;
+        while remaining > 0.0 {
+            let step = remaining.min(STEP);
+            let accel = omega * omega * (target - self.position) - 2.0 * DAMPING * omega * self.velocity;
+            self.velocity += accel * step;
+            self.position += self.velocity * step;
+            // The pins at either end stop the needle dead
+            if self.position < 0.0 || self.position > PIN {
+                self.position = self.position.clamp(0.0, PIN);
+                self.velocity = 0.0;
+            }
+            remaining -= step;
+        }
+        self.peak_hold = (self.peak_hold - dt).max(0.0);
+    }
+}
+
+#[derive(Default)]
+pub struct VuState {
+    needles: [Needle; 2],
+    /// Meters shown: 2 for stereo, 1 for the mix
+    meters: usize,
+    last_time: Option<f32>,
+}
+
+impl VuState {
+    /// Move the needles towards the levels of `stereo`, or of the end of
+    /// `waveform` when there is no stereo.
+    pub fn update(&mut self, stereo: &[(f32, f32)], waveform: &[f32], time: f32, config: &VuConfig) {
+        let dt = self.last_time.map_or(0.0, |last| (time - last).clamp(0.0, 0.1));
+        self.last_time = Some(time);
+        self.meters = if config.stereo { 2 } else { 1 };
+
+        let levels = if stereo.is_empty() {
+            let mono = &waveform[waveform.len().saturating_sub(MONO_SAMPLES)..];
+            [measure(mono.iter().copied()); 2]
+        } else if config.stereo {
+            [measure(stereo.iter().map(|s| s.0)), measure(stereo.iter().map(|s| s.1))]
+        } else {
+            [measure(stereo.iter().map(|(l, r)| (l + r) / 2.0)); 2]
+        };
+
+        // A damped spring settles to within 1% after about 4.6 / (damping × ω)
+        let omega = 4.6 / (DAMPING * (config.response_ms.max(10) as f32 / 1000.0));
+        let peak_threshold = 10.0_f32.powf(config.peak_db / 20.0);
+        for (needle, (rms, peak)) in self.needles.iter_mut().zip(levels) {
+            let vu = 20.0 * rms.max(1e-6).log10() - config.reference_db;
+            needle.advance(deflection(vu), omega, dt);
+            if peak >= peak_threshold {
+                needle.peak_hold = PEAK_HOLD;
+            }
+        }
+    }
+
+    /// The needles of the meters shown, left to right
+    pub fn needles(&self) -> &[Needle] {
+        &self.needles[..self.meters.max(1)]
+    }
+}
+
+/// RMS and peak of `samples`
+fn measure(samples: impl Iterator<Item = f32>) -> (f32, f32) {
+    let (sum, peak, count) = samples.fold((0.0, 0.0f32, 0usize), |(sum, peak, count), s| (sum + s * s, peak.max(s.abs()), count + 1));
+    ((sum / count.max(1) as f32).sqrt(), peak)
+}
+
+/// Needle deflection for a level in VU: proportional to voltage, so the
+/// scale is stretched towards the top like a real meter's
+pub fn deflection(vu: f32) -> f32 {
+    let voltage = |vu: f32| 10.0_f32.powf(vu / 20.0);
+    ((voltage(vu) - voltage(MIN_VU)) / (voltage(MAX_VU) - voltage(MIN_VU))).max(0.0)
+}
@@ -0,0 +1,101 @@
+//! Small overlays configured under `[widgets]`, drawn the same way by every backend.
+
+use std::time::Instant;
+
+use crate::config::IntensityBarPosition;
+use super::text::render_char;
+use super::{Canvas, RenderParams};
+
+/// Gap between the text area and a below-text intensity bar
+const TEXT_GAP: usize = 2;
+/// OSD glyph height as a fraction of the surface height
+const OSD_HEIGHT_RATIO: usize = 24;
+/// How long the OSD takes to fade out once its time is up
+const OSD_FADE_SECS: f32 = 0.5;
+/// Opacity of the backdrop behind the OSD text
+const OSD_BACKDROP_OPACITY: f32 = 0.6;
+
+/// Draw the intensity bar: a horizontal meter that grows outward from the
+/// center with `intensity` (0.0-1.0). `text_band` is the text area's top and
+/// height, or None when text is hidden.
+pub fn render_intensity_bar(canvas: &mut Canvas, intensity: f32, text_band: Option<(usize, usize)>, params: &RenderParams) {
+    let config = &params.widgets.intensity_bar;
+    let (w, h) = (canvas.width, canvas.height);
+    let height = config.height.clamp(1, h.max(1));
+    if w == 0 || h < height {
+        return;
+    }
+
+    let bottom = h - height;
+    let y0 = match (config.position, text_band) {
+        (IntensityBarPosition::Top, _) => 0,
+        (IntensityBarPosition::BelowText, Some((top, band_height))) => (top + band_height + TEXT_GAP).min(bottom),
+        (IntensityBarPosition::Bottom | IntensityBarPosition::BelowText, _) => bottom,
+    };
+
+    let scheme = config.scheme.as_ref().unwrap_or(params.color_scheme);
+    let half = (intensity.clamp(0.0, 1.0) * w as f32 / 2.0) as usize;
+    let center = w / 2;
+    for x in center.saturating_sub(half)..(center + half).min(w) {
+        let (r, g, b) = scheme.get_color(x as f32 / w as f32, 1.0);
+        for y in y0..y0 + height {
+            canvas.put_pixel(x, y, r, g, b, params.opacity);
+        }
+    }
+}
+
+/// The message currently shown by the OSD, if any
+#[derive(Default)]
+pub struct Osd {
+    message: Option<(String, Instant)>,
+}
+
+impl Osd {
+    pub fn show(&mut self, text: String) {
+        self.message = Some((text, Instant::now()));
+    }
+
+    /// The message and its opacity after `duration_secs` on screen and a
+    /// short fade; None once it has gone.
+    pub fn current(&self, duration_secs: f32) -> Option<(&str, f32)> {
+        let (text, shown) = self.message.as_ref()?;
+        let fade = (shown.elapsed().as_secs_f32() - duration_secs.max(0.0)) / OSD_FADE_SECS;
+        (fade < 1.0).then(|| (text.as_str(), (1.0 - fade).min(1.0)))
+    }
+}
+
+/// The first setting that differs between two `(label, value)` snapshots,
+/// as an OSD message ("Style: Radial").
+pub fn describe_change(before: &[(&str, String)], after: &[(&str, String)]) -> Option<String> {
+    after
+        .iter()
+        .zip(before)
+        .find(|(new, old)| new != old)
+        .map(|((label, value), _)| format!("{}: {}", label, value))
+}
+
+/// Draw `text` centered near the top edge on a dark backdrop.
+pub fn render_osd(canvas: &mut Canvas, text: &str, opacity: f32, params: &RenderParams) {
+    let scale = (canvas.height / OSD_HEIGHT_RATIO / 8).max(1);
+    let advance = 9 * scale;
+    let count = text.chars().count();
+    let padding = 4 * scale;
+    let width = (count * advance).saturating_sub(scale) + 2 * padding;
+    let height = 8 * scale + 2 * padding;
+    if width > canvas.width || height + padding > canvas.height {
+        return;
+    }
+
+    let x0 = (canvas.width - width) / 2;
+    let y0 = padding;
+    for y in y0..y0 + height {
+        for x in x0..x0 + width {
+            canvas.put_pixel(x, y, 0, 0, 0, OSD_BACKDROP_OPACITY * opacity);
+        }
+    }
+    let (r, g, b) = params.color_scheme.get_color(0.5, 1.0);
+    for (i, ch) in text.chars().enumerate() {
+        let ch = params.text_config.case.apply(ch);
+        render_char(canvas, x0 + padding + i * advance, y0 + padding, ch, r, g, b, scale, opacity);
+    }
+}
@@ -0,0 +1,245 @@
+//! Opt-in local usage statistics (`[stats]` section).
+//!
+//! When enabled, each render loop records how long cavibe runs and how long
+//! each style and color scheme is on screen. Totals are kept in
+//! `~/.local/share/cavibe/stats.json` and never leave the machine; view them
+//! with `cavibe stats summary`, or write them out with `cavibe stats export`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::config::StatsConfig;
+
+/// How often recorded time is merged into the stats file
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+/// Longest frame gap counted, so a suspended machine doesn't inflate totals
+const MAX_FRAME_SECS: f32 = 1.0;
+
+/// Accumulated usage totals, as stored on disk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageStats {
+    /// Number of times cavibe started with stats enabled
+    pub sessions: u64,
+    /// Total running time in seconds
+    pub runtime_secs: f64,
+    /// Seconds each style was on screen
+    pub styles: BTreeMap<String, f64>,
+    /// Seconds each color scheme was on screen
+    pub color_schemes: BTreeMap<String, f64>,
+}
+
+impl UsageStats {
+    /// `~/.local/share/cavibe/stats.json`
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("cavibe").join("stats.json"))
+    }
+
+    /// Load the stats file; a missing file means nothing was recorded yet.
+    pub fn load() -> Result<Self> {
+        let Some((path, content)) = Self::read()? else {
+            return Ok(Self::default());
+        };
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Like [`Self::load`], but a file that doesn't parse is moved aside to
+    /// `stats.json.bak` and recording starts over, rather than every flush
+    /// failing on it.
+    fn load_or_recover() -> Result<Self> {
+        let Some((path, content)) = Self::read()? else {
+            return Ok(Self::default());
+        };
+        match serde_json::from_str(&content) {
+            Ok(stats) => Ok(stats),
+            Err(e) => {
+                let backup = path.with_extension("json.bak");
+                std::fs::rename(&path, &backup)
+                    .with_context(|| format!("Failed to move unreadable {} aside", path.display()))?;
+                warn!("Usage stats in {} were unreadable ({}); kept them as {} and started over", path.display(), e, backup.display());
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// The stats file's path and contents, or None if there is none yet
+    fn read() -> Result<Option<(PathBuf, String)>> {
+        let Some(path) = Self::path() else {
+            return Ok(None);
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Ok(Some((path, content))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Load, change and save the stats file while holding an advisory lock
+    /// on `stats.json.lock`, so a terminal and a wallpaper instance flushing
+    /// at the same time don't lose each other's time.
+    fn update_file(change: impl FnOnce(&mut Self)) -> Result<()> {
+        let path = Self::path().context("Could not determine data directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let lock_path = path.with_extension("json.lock");
+        let lock = std::fs::File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+        lock.lock().with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+
+        let mut stats = Self::load_or_recover()?;
+        change(&mut stats);
+        // Write-then-rename so readers never see a half-written file; the
+        // temp name is per process in case another instance ignores the lock
+        let temp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&temp, serde_json::to_string_pretty(&stats)?)?;
+        std::fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    /// Delete the stats file.
+    pub fn reset() -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn merge(&mut self, other: &UsageStats) {
+        self.sessions += other.sessions;
+        self.runtime_secs += other.runtime_secs;
+        for (name, secs) in &other.styles {
+            *self.styles.entry(name.clone()).or_default() += secs;
+        }
+        for (name, secs) in &other.color_schemes {
+            *self.color_schemes.entry(name.clone()).or_default() += secs;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sessions == 0 && self.runtime_secs == 0.0 && self.styles.is_empty() && self.color_schemes.is_empty()
+    }
+
+    /// Human-readable summary, favorites first
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "No usage recorded yet. Enable it with `enabled = true` in the [stats] config section.".to_string();
+        }
+        let mut out = String::new();
+        let _ = writeln!(out, "Sessions:  {}", self.sessions);
+        let _ = writeln!(out, "Run time:  {}", format_duration(self.runtime_secs));
+        for (heading, totals) in [("Styles", &self.styles), ("Color schemes", &self.color_schemes)] {
+            let mut entries: Vec<_> = totals.iter().collect();
+            entries.sort_by(|a, b| b.1.total_cmp(a.1));
+            let total: f64 = entries.iter().map(|(_, secs)| **secs).sum();
+            let _ = writeln!(out, "\n{} (time on screen):", heading);
+            let width = entries.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+            for (name, secs) in entries {
+                let share = if total > 0.0 { secs / total * 100.0 } else { 0.0 };
+                let _ = writeln!(out, "  {:<width$}  {:>10}  {:>5.1}%", name, format_duration(*secs), share);
+            }
+        }
+        out.trim_end().to_string()
+    }
+}
+
+fn format_duration(secs: f64) -> String {
+    let secs = secs as u64;
+    match (secs / 3600, secs / 60 % 60) {
+        (0, 0) => format!("{}s", secs),
+        (0, m) => format!("{}m {}s", m, secs % 60),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// Records usage in a render loop and merges it into the stats file
+/// periodically and when dropped.
+pub struct StatsRecorder {
+    pending: UsageStats,
+    /// Pending screen time by style number, named at flush time so frames
+    /// don't allocate style names
+    style_secs: BTreeMap<usize, f64>,
+    last_flush: Instant,
+    /// Whether this session has been counted
+    counted: bool,
+}
+
+impl Default for StatsRecorder {
+    fn default() -> Self {
+        Self {
+            pending: UsageStats::default(),
+            style_secs: BTreeMap::new(),
+            last_flush: Instant::now(),
+            counted: false,
+        }
+    }
+}
+
+impl StatsRecorder {
+    /// Add `dt` seconds of running time, and of screen time for the current
+    /// style and color scheme when `on_screen`.
+    pub fn update(&mut self, config: &StatsConfig, style: usize, color: &str, on_screen: bool, dt: f32) {
+        if !config.enabled {
+            self.pending = UsageStats::default();
+            self.style_secs.clear();
+            return;
+        }
+        if !self.counted {
+            self.pending.sessions += 1;
+            self.counted = true;
+        }
+
+        let dt = dt.clamp(0.0, MAX_FRAME_SECS) as f64;
+        self.pending.runtime_secs += dt;
+        if on_screen {
+            *self.style_secs.entry(style).or_default() += dt;
+            // Only a scheme not seen since the last flush allocates its name
+            match self.pending.color_schemes.get_mut(color) {
+                Some(secs) => *secs += dt,
+                None => {
+                    self.pending.color_schemes.insert(color.to_string(), dt);
+                }
+            }
+        }
+
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush();
+        }
+    }
+
+    /// Merge pending time into the stats file.
+    pub fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        for (style, secs) in std::mem::take(&mut self.style_secs) {
+            *self.pending.styles.entry(crate::renderer::styles::style_name(style)).or_default() += secs;
+        }
+        if self.pending.is_empty() {
+            return;
+        }
+        let result = UsageStats::update_file(|stats| stats.merge(&self.pending));
+        match result {
+            Ok(()) => self.pending = UsageStats::default(),
+            Err(e) => warn!("Failed to save usage stats: {:#}", e),
+        }
+    }
+}
+
+impl Drop for StatsRecorder {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
@@ -3,7 +3,7 @@
 //! Tracks the current style index, time, and style cycling.
 //! The actual rendering is handled by `crate::renderer`.
 
-use crate::config::{TextConfig, VisualizerConfig};
+use crate::config::{CycleOrder, TextConfig, VisualizerConfig};
 use crate::renderer::styles;
 
 /// Combined visualizer state
@@ -17,11 +17,7 @@ impl VisualizerState {
         let initial_style = visualizer_config
             .style
             .as_deref()
-            .and_then(|name| {
-                styles::STYLE_NAMES
-                    .iter()
-                    .position(|&s| s.eq_ignore_ascii_case(name))
-            })
+            .and_then(styles::find_style)
             .unwrap_or(0);
         Self {
             current_style: initial_style,
@@ -45,7 +41,61 @@ impl VisualizerState {
         }
     }
 
-    pub fn current_style_name(&self) -> &'static str {
-        styles::STYLE_NAMES[self.current_style]
+    /// Switch to a style by name (case-insensitive). Returns false if unknown.
+    pub fn set_style_by_name(&mut self, name: &str) -> bool {
+        match styles::find_style(name) {
+            Some(idx) => {
+                self.current_style = idx;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn current_style_name(&self) -> String {
+        styles::style_name(self.current_style)
+    }
+}
+
+/// Picks what auto-rotation switches to (`display.rotate_order`): the next
+/// entry in order, or a shuffled round that visits every other entry once,
+/// so the same one never comes up twice in a row.
+#[derive(Default)]
+pub struct Rotator {
+    order: CycleOrder,
+    /// Rest of the shuffled round, the next entry last
+    upcoming: Vec<usize>,
+}
+
+impl Rotator {
+    pub fn new(order: CycleOrder) -> Self {
+        Self { order, upcoming: Vec::new() }
+    }
+
+    /// The entry that follows `current` out of `count`, without advancing
+    pub fn peek(&mut self, current: usize, count: usize) -> usize {
+        if count <= 1 {
+            return 0;
+        }
+        match self.order {
+            CycleOrder::Sequential => (current + 1) % count,
+            CycleOrder::Shuffle => {
+                // The current entry may have been picked by hand, and custom
+                // entries may have gone since the round was shuffled
+                self.upcoming.retain(|&idx| idx < count && idx != current);
+                if self.upcoming.is_empty() {
+                    self.upcoming.extend((0..count).filter(|&idx| idx != current));
+                    crate::random::shuffle(&mut self.upcoming);
+                }
+                self.upcoming[self.upcoming.len() - 1]
+            }
+        }
+    }
+
+    /// Advance to the entry that follows `current` out of `count`
+    pub fn next(&mut self, current: usize, count: usize) -> usize {
+        let next = self.peek(current, count);
+        self.upcoming.pop();
+        next
     }
 }
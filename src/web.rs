@@ -0,0 +1,153 @@
+//! Web server for browser-based consumers.
+//!
+//! Serves a WebSocket endpoint at `/stream` that pushes the current frequency
+//! and waveform arrays as binary frames at `web.stream_fps`, so web canvases
+//! can be driven by the cavibe audio pipeline.
+//!
+//! Frame layout (little-endian):
+//!
+//! | Offset | Type | Field |
+//! |--------|------|-------|
+//! | 0 | `[u8; 4]` | magic `CAV1` |
+//! | 4 | `u16` | frequency count `F` |
+//! | 6 | `u16` | waveform sample count `W` |
+//! | 8 | `f32` | intensity |
+//! | 12 | `f32` | input level |
+//! | 16 | `[f32; F]` | frequencies (0.0-1.0) |
+//! | 16 + 4F | `[f32; W]` | waveform (-1.0-1.0) |
+//!
+//! The same frames (or JSON lines) are streamed over the control socket by
+//! `subscribe spectrum` (see `crate::ipc`), which shares the feed below.
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::audio::AudioData;
+use crate::config::WebConfig;
+
+/// Magic bytes at the start of every stream frame
+const FRAME_MAGIC: &[u8; 4] = b"CAV1";
+
+/// Latest audio data shown by the render loop, published for stream clients.
+static FEED: OnceLock<watch::Sender<Arc<AudioData>>> = OnceLock::new();
+
+fn feed() -> &'static watch::Sender<Arc<AudioData>> {
+    FEED.get_or_init(|| watch::channel(Arc::new(AudioData::default())).0)
+}
+
+/// Publish the audio data the render loop is currently displaying.
+/// Does nothing while no stream client is connected.
+pub fn publish(data: &Arc<AudioData>) {
+    let tx = feed();
+    if tx.receiver_count() > 0 {
+        tx.send_replace(data.clone());
+    }
+}
+
+/// Receive the audio data the render loop publishes
+pub fn subscribe() -> watch::Receiver<Arc<AudioData>> {
+    feed().subscribe()
+}
+
+/// Start the web server, accepting connections until the process exits
+pub async fn start_server(config: WebConfig) -> Result<()> {
+    let listener = TcpListener::bind(&config.bind)
+        .await
+        .with_context(|| format!("Failed to bind web server to {}", config.bind))?;
+    info!("Web server listening on ws://{}/stream", config.bind);
+
+    let interval = Duration::from_secs_f32(1.0 / config.stream_fps.clamp(1, 240) as f32);
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("Web server accept failed: {}", e);
+                tokio::time::sleep(crate::ipc::ACCEPT_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        let rx = subscribe();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, rx, interval).await {
+                debug!("Web client {} error: {}", addr, e);
+            }
+        });
+    }
+}
+
+/// Upgrade a connection to a WebSocket and stream frames until it closes
+async fn handle_client(
+    stream: TcpStream,
+    rx: watch::Receiver<Arc<AudioData>>,
+    interval: Duration,
+) -> Result<()> {
+    let ws = tokio_tungstenite::accept_hdr_async(stream, check_path).await?;
+    let (mut sink, mut incoming) = ws.split();
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let frame = encode_frame(&rx.borrow());
+                sink.send(Message::Binary(frame)).await?;
+            }
+            msg = incoming.next() => match msg {
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Handshake callback: only `/stream` is upgraded, anything else gets a 404.
+/// The error type is fixed by tungstenite's callback signature.
+#[allow(clippy::result_large_err)]
+fn check_path(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    if request.uri().path() == "/stream" {
+        Ok(response)
+    } else {
+        let mut not_found = ErrorResponse::new(Some("not found".to_string()));
+        *not_found.status_mut() = StatusCode::NOT_FOUND;
+        Err(not_found)
+    }
+}
+
+/// Encode one stream frame (see the module docs for the layout)
+pub fn encode_frame(data: &AudioData) -> Vec<u8> {
+    let freqs = &data.frequencies[..data.frequencies.len().min(u16::MAX as usize)];
+    let wave = &data.waveform[..data.waveform.len().min(u16::MAX as usize)];
+    let mut frame = Vec::with_capacity(16 + (freqs.len() + wave.len()) * 4);
+    frame.extend_from_slice(FRAME_MAGIC);
+    frame.extend_from_slice(&(freqs.len() as u16).to_le_bytes());
+    frame.extend_from_slice(&(wave.len() as u16).to_le_bytes());
+    frame.extend_from_slice(&data.intensity.to_le_bytes());
+    frame.extend_from_slice(&data.level.to_le_bytes());
+    for v in freqs.iter().chain(wave) {
+        frame.extend_from_slice(&v.to_le_bytes());
+    }
+    frame
+}
+
+/// Encode one frame as a line of JSON: bars, intensity, input level and bands
+pub fn encode_json(data: &AudioData) -> String {
+    serde_json::json!({
+        "frequencies": data.frequencies,
+        "intensity": data.intensity,
+        "level": data.level,
+        "bass": data.bands.bass,
+        "mid": data.bands.mid,
+        "treble": data.bands.treble,
+    })
+    .to_string()
+}